@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use mecomp_analysis::{
+    clustering::{InitStrategy, KMeansClusterer},
+    NUMBER_FEATURES,
+};
+use ndarray::{Array2, Axis};
+use rand::{distributions::Uniform, rngs::SmallRng, Rng, SeedableRng};
+
+const SAMPLES: usize = 1000;
+const CLUSTERS: usize = 10;
+
+/// Generate a dataset of `SAMPLES` songs spread around `CLUSTERS` random centers
+fn generate_dataset(rng: &mut SmallRng) -> Array2<f64> {
+    let centers = Array2::from_shape_simple_fn((CLUSTERS, NUMBER_FEATURES), || {
+        rng.sample(Uniform::new(-1.0, 1.0))
+    });
+    let noise = Uniform::new(-0.2, 0.2);
+
+    let mut samples = Array2::zeros((SAMPLES, NUMBER_FEATURES));
+    for (i, mut sample) in samples.axis_iter_mut(Axis(0)).enumerate() {
+        let center = centers.row(i % CLUSTERS);
+        sample.assign(&center.mapv(|v| v + rng.sample(noise)));
+    }
+    samples
+}
+
+fn bench_kmeans_init(c: &mut Criterion) {
+    let samples = generate_dataset(&mut SmallRng::seed_from_u64(0));
+
+    // compare how many iterations each strategy needs to converge, averaged over a few seeds
+    for init in [InitStrategy::Random, InitStrategy::KMeansPlusPlus] {
+        let clusterer = KMeansClusterer::new(CLUSTERS, init);
+        let total: usize = (0..20)
+            .map(|seed| {
+                clusterer
+                    .fit(samples.view(), &mut SmallRng::seed_from_u64(seed))
+                    .unwrap()
+                    .iterations
+            })
+            .sum();
+        #[allow(clippy::cast_precision_loss)]
+        let average = total as f64 / 20.;
+        println!("{init:?}: {average} iterations to converge on average");
+    }
+
+    let mut group = c.benchmark_group("mecomp-analysis: clustering.rs: KMeansClusterer");
+    for init in [InitStrategy::Random, InitStrategy::KMeansPlusPlus] {
+        let clusterer = KMeansClusterer::new(CLUSTERS, init);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{init:?}")),
+            &clusterer,
+            |b, clusterer| {
+                let mut rng = SmallRng::seed_from_u64(42);
+                b.iter(|| {
+                    let _ = clusterer.fit(black_box(samples.view()), &mut rng);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_kmeans_init);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+//! Compares the Hann and Blackman-Harris windows, both in how long the STFT takes with them,
+//! and in how much spectral leakage they let through on the test asset.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use mecomp_analysis::decoder::Decoder as DecoderTrait;
+use mecomp_analysis::decoder::MecompDecoder as Decoder;
+use mecomp_analysis::utils::{stft_with_window, window_functions::WindowFunction};
+use ndarray::Axis;
+use std::path::Path;
+
+const WINDOWS: [WindowFunction; 2] = [WindowFunction::Hann, WindowFunction::BlackmanHarris];
+
+/// The average (over all frames) fraction of a frame's energy that is more than 4 bins away from its peak.
+fn spectral_leakage(signal: &[f32], window: WindowFunction) -> f64 {
+    let stft = stft_with_window(signal, 2048, 512, window);
+
+    let leakage = stft
+        .axis_iter(Axis(1))
+        .filter_map(|frame| {
+            let (peak, _) = frame
+                .indexed_iter()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+            let total = frame.mapv(|x| x * x).sum();
+            if total == 0. {
+                return None;
+            }
+            let leaked = frame
+                .indexed_iter()
+                .filter(|(bin, _)| bin.abs_diff(peak) > 4)
+                .map(|(_, x)| x * x)
+                .sum::<f64>();
+            Some(leaked / total)
+        })
+        .collect::<Vec<_>>();
+
+    #[allow(clippy::cast_precision_loss)]
+    let frames = leakage.len() as f64;
+    leakage.iter().sum::<f64>() / frames
+}
+
+fn bench_window_functions(c: &mut Criterion) {
+    let signal = Decoder::decode(Path::new("data/piano.flac"))
+        .unwrap()
+        .samples;
+
+    for window in WINDOWS {
+        println!(
+            "spectral leakage with {window:?} window: {:.4}%",
+            spectral_leakage(&signal, window) * 100.
+        );
+    }
+
+    let mut group = c.benchmark_group("mecomp-analysis: utils.rs: stft_with_window");
+    for window in WINDOWS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{window:?}")),
+            &window,
+            |b, &window| {
+                b.iter(|| {
+                    let _ = stft_with_window(
+                        black_box(&signal),
+                        black_box(2048),
+                        black_box(512),
+                        black_box(window),
+                    );
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_window_functions);
+criterion_main!(benches);
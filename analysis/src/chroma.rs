@@ -5,10 +5,12 @@
 //! (minor / major).
 extern crate noisy_float;
 
-use crate::Feature;
+use crate::{window_scale, Feature};
 
 use super::errors::{AnalysisError, AnalysisResult};
-use super::utils::{hz_to_octs_inplace, stft, Normalize};
+use super::utils::{
+    hz_to_octs_inplace, stft_with_window, window_functions::WindowFunction, Normalize,
+};
 use ndarray::{arr1, arr2, concatenate, s, Array, Array1, Array2, Axis, Zip};
 use ndarray_stats::interpolate::Midpoint;
 use ndarray_stats::QuantileExt;
@@ -28,7 +30,10 @@ use noisy_float::prelude::*;
 #[allow(clippy::module_name_repetitions)]
 pub struct ChromaDesc {
     sample_rate: u32,
+    window_size: usize,
+    hop_size: usize,
     n_chroma: u32,
+    window_function: WindowFunction,
     values_chroma: Array2<f64>,
 }
 
@@ -38,17 +43,40 @@ impl Normalize for ChromaDesc {
 }
 
 impl ChromaDesc {
+    /// The window size at [`SAMPLE_RATE`](crate::SAMPLE_RATE), see [`ChromaDesc::window_size`].
     pub const WINDOW_SIZE: usize = 8192;
+    /// The hop size of the STFT at [`SAMPLE_RATE`](crate::SAMPLE_RATE).
+    const HOP_SIZE: usize = 2205;
 
     #[must_use]
     pub fn new(sample_rate: u32, n_chroma: u32) -> Self {
+        Self::with_window_function(sample_rate, n_chroma, WindowFunction::default())
+    }
+
+    /// Like [`ChromaDesc::new`], but the STFT uses the given window function instead of a Hann window.
+    #[must_use]
+    pub fn with_window_function(
+        sample_rate: u32,
+        n_chroma: u32,
+        window_function: WindowFunction,
+    ) -> Self {
+        let scale = window_scale(sample_rate);
         Self {
             sample_rate,
+            window_size: Self::WINDOW_SIZE * scale,
+            hop_size: Self::HOP_SIZE * scale,
             n_chroma,
+            window_function,
             values_chroma: Array2::zeros((n_chroma as usize, 0)),
         }
     }
 
+    /// The size of the STFT windows, [`ChromaDesc::WINDOW_SIZE`] scaled for the sample rate (see [`window_scale`]).
+    #[must_use]
+    pub const fn window_size(&self) -> usize {
+        self.window_size
+    }
+
     /**
      * Compute and store the chroma of a signal.
      *
@@ -57,12 +85,17 @@ impl ChromaDesc {
      */
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub fn do_(&mut self, signal: &[f32]) -> AnalysisResult<()> {
-        let mut stft = stft(signal, Self::WINDOW_SIZE, 2205);
-        let tuning = estimate_tuning(self.sample_rate, &stft, Self::WINDOW_SIZE, 0.01, 12)?;
+        let mut stft = stft_with_window(
+            signal,
+            self.window_size,
+            self.hop_size,
+            self.window_function,
+        );
+        let tuning = estimate_tuning(self.sample_rate, &stft, self.window_size, 0.01, 12)?;
         let chroma = chroma_stft(
             self.sample_rate,
             &mut stft,
-            Self::WINDOW_SIZE,
+            self.window_size,
             self.n_chroma,
             tuning,
         )?;
@@ -86,6 +119,132 @@ impl ChromaDesc {
             .mapv(|x| self.normalize(x as Feature))
             .to_vec()
     }
+
+    /**
+     * Get the song's pitch class profile, that is the mean of the chromagram
+     * over time.
+     *
+     * Returns `n_chroma` values, starting at C, all zeros if no signal has
+     * been passed to [`ChromaDesc::do_`] yet.
+     */
+    #[must_use]
+    pub fn get_pitch_class_profile(&self) -> Vec<Feature> {
+        self.values_chroma
+            .mean_axis(Axis(1))
+            .unwrap_or_else(|| Array1::zeros(self.n_chroma as usize))
+            .to_vec()
+    }
+}
+
+/**
+ * Key and mode detector.
+ *
+ * Finds the key (C, C#, ..., B) and the mode (minor / major) of a song from its
+ * pitch class profile (see [`ChromaDesc::get_pitch_class_profile`]), using the
+ * Krumhansl-Schmuckler key-finding algorithm: the profile is correlated with the
+ * Krumhansl-Kessler key profiles of the 24 major and minor keys, and the best
+ * match wins.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyDesc {
+    key: usize,
+    major: bool,
+}
+
+impl KeyDesc {
+    /// The Krumhansl-Kessler profile of C major.
+    const MAJOR_PROFILE: [f64; 12] = [
+        6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+    ];
+    /// The Krumhansl-Kessler profile of C minor.
+    const MINOR_PROFILE: [f64; 12] = [
+        6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+    ];
+
+    /**
+     * Detect the key and mode of a 12-element pitch class profile, starting at C.
+     *
+     * If the profile is flat (e.g. silence), no key fits better than another,
+     * and C major is returned.
+     *
+     * # Errors
+     *
+     * Returns an error if `pitch_class_profile` doesn't have exactly 12 elements.
+     */
+    pub fn new(pitch_class_profile: &[Feature]) -> AnalysisResult<Self> {
+        let profile: &[Feature; 12] = pitch_class_profile.try_into().map_err(|_| {
+            AnalysisError::AnalysisError(format!(
+                "in key detection: expected 12 pitch classes, got {}",
+                pitch_class_profile.len()
+            ))
+        })?;
+
+        let mut best = Self {
+            key: 0,
+            major: true,
+        };
+        let mut best_correlation = f64::NEG_INFINITY;
+        for (major, key_profile) in [(true, &Self::MAJOR_PROFILE), (false, &Self::MINOR_PROFILE)] {
+            for key in 0..12 {
+                let rotated: [f64; 12] = std::array::from_fn(|i| profile[(i + key) % 12]);
+                let correlation = pearson_correlation(&rotated, key_profile);
+                if correlation > best_correlation {
+                    best_correlation = correlation;
+                    best = Self { key, major };
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    /// The detected key, from 0 (C) to 11 (B).
+    #[must_use]
+    pub const fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Whether the detected mode is major (`true`) or minor (`false`).
+    #[must_use]
+    pub const fn is_major(&self) -> bool {
+        self.major
+    }
+
+    /**
+     * Get the detected key and mode as features.
+     *
+     * Keys wrap around (B is a semitone away from C), so the key is encoded as
+     * a point on a circle: the sine and cosine of 2π·key/12, both in [-1, 1].
+     * That way the distance between two keys only depends on how many semitones apart they are.
+     * The mode is 0 for minor and 1 for major.
+     */
+    #[must_use]
+    pub fn get_value(&self) -> Vec<Feature> {
+        #[allow(clippy::cast_precision_loss)]
+        let angle = std::f64::consts::TAU * self.key as Feature / 12.;
+        vec![angle.sin(), angle.cos(), if self.major { 1. } else { 0. }]
+    }
+}
+
+/// The Pearson correlation coefficient of `a` and `b`, 0 if either of them is constant.
+fn pearson_correlation(a: &[f64; 12], b: &[f64; 12]) -> f64 {
+    let mean_a = a.iter().sum::<f64>() / 12.;
+    let mean_b = b.iter().sum::<f64>() / 12.;
+    let (covariance, variance_a, variance_b) = a.iter().zip(b).fold(
+        (0., 0., 0.),
+        |(covariance, variance_a, variance_b), (a, b)| {
+            let (a, b) = (a - mean_a, b - mean_b);
+            (
+                a.mul_add(b, covariance),
+                a.mul_add(a, variance_a),
+                b.mul_add(b, variance_b),
+            )
+        },
+    );
+    let denominator = (variance_a * variance_b).sqrt();
+    if denominator < f64::MIN_POSITIVE {
+        return 0.;
+    }
+    covariance / denominator
 }
 
 // Functions below are Rust versions of python notebooks by AudioLabs Erlang
@@ -509,6 +668,102 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_pitch_class_profile() {
+        let mut chroma_desc = ChromaDesc::new(SAMPLE_RATE, 12);
+        assert_eq!(chroma_desc.get_pitch_class_profile(), vec![0.; 12]);
+
+        let song = Decoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+        chroma_desc.do_(&song.samples).unwrap();
+        let profile = chroma_desc.get_pitch_class_profile();
+        assert_eq!(profile.len(), 12);
+        assert!(
+            profile.iter().all(|x| (0. ..=1.).contains(x)),
+            "{profile:?}"
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::c_major(KeyDesc::MAJOR_PROFILE, 0, 0, true)]
+    #[case::g_major(KeyDesc::MAJOR_PROFILE, 7, 7, true)]
+    #[case::c_minor(KeyDesc::MINOR_PROFILE, 0, 0, false)]
+    #[case::a_minor(KeyDesc::MINOR_PROFILE, 9, 9, false)]
+    fn test_key_desc(
+        #[case] key_profile: [f64; 12],
+        #[case] shift: usize,
+        #[case] expected_key: usize,
+        #[case] expected_major: bool,
+    ) {
+        // the profile of the key `shift` semitones above C
+        let profile: [f64; 12] = std::array::from_fn(|i| key_profile[(i + 12 - shift) % 12]);
+        let key_desc = KeyDesc::new(&profile).unwrap();
+        assert_eq!(key_desc.key(), expected_key);
+        assert_eq!(key_desc.is_major(), expected_major);
+    }
+
+    #[test]
+    fn test_key_desc_triad() {
+        // C, E and G
+        let mut profile = [0.; 12];
+        profile[0] = 1.;
+        profile[4] = 1.;
+        profile[7] = 1.;
+        let key_desc = KeyDesc::new(&profile).unwrap();
+        assert_eq!(key_desc.get_value(), vec![0., 1., 1.]);
+
+        // A, C and E
+        let mut profile = [0.; 12];
+        profile[9] = 1.;
+        profile[0] = 1.;
+        profile[4] = 1.;
+        let key_desc = KeyDesc::new(&profile).unwrap();
+        let value = key_desc.get_value();
+        assert!((value[0] + 1.).abs() < 1e-12, "{value:?}");
+        assert!(value[1].abs() < 1e-12, "{value:?}");
+        assert_eq!(value[2], 0.);
+    }
+
+    #[test]
+    fn test_key_desc_circular() {
+        let distance = |a: usize, b: usize| {
+            let a = KeyDesc {
+                key: a,
+                major: true,
+            }
+            .get_value();
+            let b = KeyDesc {
+                key: b,
+                major: true,
+            }
+            .get_value();
+            a.iter()
+                .zip(&b)
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+
+        // B and C are neighbours, just like C and C#
+        assert!((distance(11, 0) - distance(0, 1)).abs() < 1e-12);
+        // and the furthest keys from C are a tritone away
+        for key in 0..12 {
+            assert!(distance(0, key) <= distance(0, 6) + 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_key_desc_boundaries() {
+        let key_desc = KeyDesc::new(&[0.; 12]).unwrap();
+        assert_eq!(key_desc.get_value(), vec![0., 1., 1.]);
+
+        let key_desc = KeyDesc::new(&KeyDesc::MAJOR_PROFILE.map(|x| -x)).unwrap();
+        for value in key_desc.get_value() {
+            assert!((-1. ..=1.).contains(&value));
+        }
+
+        assert!(KeyDesc::new(&[0.; 11]).is_err());
+    }
+
     #[test]
     fn test_chroma_stft_decode() {
         let signal = Decoder::decode(Path::new("data/s16_mono_22_5kHz.flac"))
@@ -10,17 +10,25 @@
 //! - The Davies-Bouldin index [wikipedia](https://en.wikipedia.org/wiki/Davies%E2%80%93Bouldin_index)
 
 use linfa::prelude::*;
-use linfa_clustering::{GaussianMixtureModel, KMeans};
+use linfa_clustering::GaussianMixtureModel;
 use linfa_nn::distance::{Distance, L2Dist};
 use linfa_tsne::TSneParams;
 use log::{debug, info};
 use ndarray::{Array, Array1, Array2, ArrayView1, ArrayView2, Axis};
 use ndarray_rand::RandomExt;
-use rand::distributions::Uniform;
+use rand::{
+    distributions::{Distribution, Uniform, WeightedIndex},
+    Rng,
+};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 use statrs::statistics::Statistics;
 
-use crate::{errors::ClusteringError, Analysis, Feature, NUMBER_FEATURES};
+use crate::{
+    errors::ClusteringError,
+    metrics::{DistanceMetric, EuclideanDistance},
+    Analysis, Feature, NUMBER_FEATURES,
+};
 
 pub struct AnalysisArray(pub(crate) Array2<Feature>);
 
@@ -61,11 +69,10 @@ impl ClusteringMethod {
     fn fit(self, k: usize, samples: &Array2<Feature>) -> Array1<usize> {
         match self {
             Self::KMeans => {
-                let model = KMeans::params(k)
-                    // .max_n_iterations(MAX_ITERATIONS)
-                    .fit(&Dataset::from(samples.clone()))
-                    .unwrap();
-                model.predict(samples)
+                KMeansClusterer::new(k, InitStrategy::KMeansPlusPlus)
+                    .fit(samples.view(), &mut rand::thread_rng())
+                    .unwrap()
+                    .labels
             }
             Self::GaussianMixtureModel => {
                 let model = GaussianMixtureModel::params(k)
@@ -79,6 +86,640 @@ impl ClusteringMethod {
     }
 }
 
+/// How the initial centroids of a [`KMeansClusterer`] are chosen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InitStrategy {
+    /// Pick `k` distinct samples uniformly at random
+    Random,
+    /// Pick the first centroid uniformly at random, then pick each subsequent centroid with probability
+    /// proportional to its squared distance from the nearest already-chosen centroid.
+    ///
+    /// This spreads the initial centroids out, which usually means fewer iterations are needed to converge,
+    /// and that the result is closer to the optimal clustering.
+    ///
+    /// # References:
+    ///
+    /// - [D. Arthur and S. Vassilvitskii (Stanford University, 2007)](https://theory.stanford.edu/~sergei/papers/kMeansPP-soda.pdf)
+    #[default]
+    KMeansPlusPlus,
+}
+
+/// A k-means clusterer (Lloyd's algorithm) with a configurable initialization strategy
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KMeansClusterer {
+    /// The number of clusters
+    pub k: usize,
+    /// How the initial centroids are chosen
+    pub init: InitStrategy,
+    /// The maximum number of iterations to run before giving up on convergence
+    pub max_iterations: usize,
+    /// The algorithm has converged once no centroid moves further than this in an iteration
+    pub tolerance: f64,
+}
+
+/// The result of fitting a [`KMeansClusterer`] to some samples
+#[derive(Clone, Debug, PartialEq)]
+pub struct KMeansFit {
+    /// The centroids of the clusters, as a kxD array
+    pub centroids: Array2<Feature>,
+    /// The cluster that each sample belongs to, as a Nx1 array
+    pub labels: Array1<usize>,
+    /// The number of iterations it took to converge (or `max_iterations` if it didn't)
+    pub iterations: usize,
+}
+
+impl KMeansClusterer {
+    pub const DEFAULT_MAX_ITERATIONS: usize = 300;
+    pub const DEFAULT_TOLERANCE: f64 = 1e-4;
+
+    /// Create a new `KMeansClusterer` with the default `max_iterations` and `tolerance`
+    #[must_use]
+    pub const fn new(k: usize, init: InitStrategy) -> Self {
+        Self {
+            k,
+            init,
+            max_iterations: Self::DEFAULT_MAX_ITERATIONS,
+            tolerance: Self::DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Cluster the samples (a NxD array) into `k` clusters
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `k` is 0, or if there are fewer samples than clusters
+    pub fn fit<R: Rng + ?Sized>(
+        &self,
+        samples: ArrayView2<Feature>,
+        rng: &mut R,
+    ) -> Result<KMeansFit, ClusteringError> {
+        if self.k == 0 || self.k > samples.nrows() {
+            return Err(ClusteringError::InvalidClusterCount(
+                self.k,
+                samples.nrows(),
+            ));
+        }
+
+        let mut centroids = match self.init {
+            InitStrategy::Random => init_random(samples, self.k, rng),
+            InitStrategy::KMeansPlusPlus => init_kmeans_plus_plus(samples, self.k, rng),
+        };
+        let mut labels = assign_labels(samples, centroids.view());
+        let mut iterations = 0;
+
+        while iterations < self.max_iterations {
+            iterations += 1;
+
+            let new_centroids = update_centroids(samples, labels.view(), centroids.view());
+            let max_shift = centroids
+                .outer_iter()
+                .zip(new_centroids.outer_iter())
+                .map(|(old, new)| squared_distance(old, new).sqrt())
+                .fold(0.0, f64::max);
+
+            centroids = new_centroids;
+            labels = assign_labels(samples, centroids.view());
+
+            if max_shift <= self.tolerance {
+                break;
+            }
+        }
+
+        Ok(KMeansFit {
+            centroids,
+            labels,
+            iterations,
+        })
+    }
+}
+
+/// Pick `k` distinct samples uniformly at random to be the initial centroids
+fn init_random<R: Rng + ?Sized>(
+    samples: ArrayView2<Feature>,
+    k: usize,
+    rng: &mut R,
+) -> Array2<Feature> {
+    let indices = rand::seq::index::sample(rng, samples.nrows(), k).into_vec();
+    samples.select(Axis(0), &indices)
+}
+
+/// Pick the initial centroids using the k-means++ algorithm, see [`InitStrategy::KMeansPlusPlus`]
+fn init_kmeans_plus_plus<R: Rng + ?Sized>(
+    samples: ArrayView2<Feature>,
+    k: usize,
+    rng: &mut R,
+) -> Array2<Feature> {
+    let mut indices = Vec::with_capacity(k);
+    indices.push(rng.gen_range(0..samples.nrows()));
+
+    // the squared distance from each sample to the nearest chosen centroid
+    let mut distances = samples
+        .outer_iter()
+        .map(|sample| squared_distance(sample, samples.row(indices[0])))
+        .collect::<Vec<_>>();
+
+    while indices.len() < k {
+        // if every sample is on top of a centroid (e.g. there are duplicate samples), fall back to a uniform choice
+        let next = match WeightedIndex::new(&distances) {
+            Ok(weights) => weights.sample(rng),
+            Err(_) => rng.gen_range(0..samples.nrows()),
+        };
+        indices.push(next);
+
+        for (distance, sample) in distances.iter_mut().zip(samples.outer_iter()) {
+            *distance = distance.min(squared_distance(sample, samples.row(next)));
+        }
+    }
+
+    samples.select(Axis(0), &indices)
+}
+
+/// Label each sample with the index of its nearest centroid
+fn assign_labels(samples: ArrayView2<Feature>, centroids: ArrayView2<Feature>) -> Array1<usize> {
+    samples
+        .outer_iter()
+        .map(|sample| {
+            centroids
+                .outer_iter()
+                .map(|centroid| squared_distance(sample, centroid))
+                .enumerate()
+                .fold(
+                    (0, f64::INFINITY),
+                    |(best, best_distance), (i, distance)| {
+                        if distance < best_distance {
+                            (i, distance)
+                        } else {
+                            (best, best_distance)
+                        }
+                    },
+                )
+                .0
+        })
+        .collect()
+}
+
+/// Move each centroid to the mean of the samples labeled with it, centroids of empty clusters are left where they are
+fn update_centroids(
+    samples: ArrayView2<Feature>,
+    labels: ArrayView1<usize>,
+    centroids: ArrayView2<Feature>,
+) -> Array2<Feature> {
+    let mut sums = Array2::<Feature>::zeros(centroids.raw_dim());
+    let mut counts = vec![0usize; centroids.nrows()];
+
+    for (sample, &label) in samples.outer_iter().zip(labels.iter()) {
+        let mut sum = sums.row_mut(label);
+        sum += &sample;
+        counts[label] += 1;
+    }
+
+    for ((mut sum, centroid), count) in sums
+        .outer_iter_mut()
+        .zip(centroids.outer_iter())
+        .zip(counts)
+    {
+        if count == 0 {
+            sum.assign(&centroid);
+        } else {
+            #[allow(clippy::cast_precision_loss)]
+            sum.mapv_inplace(|v| v / count as f64);
+        }
+    }
+
+    sums
+}
+
+/// A DBSCAN (Density-Based Spatial Clustering of Applications with Noise) clusterer.
+///
+/// Clusters are grown from "core" samples, which have at least `min_points` samples (themselves included)
+/// within `epsilon` of them, so unlike k-means the clusters can have any shape, and their number isn't fixed in advance.
+/// Samples that aren't within `epsilon` of a core sample are noise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dbscan<M: DistanceMetric = EuclideanDistance> {
+    /// The maximum distance between two samples for them to be neighbors
+    pub epsilon: f64,
+    /// The minimum number of neighbors (including itself) a sample needs to be a core sample
+    pub min_points: usize,
+    /// How the distance between two samples is measured
+    pub metric: M,
+    /// The number of clusters found by the last call to [`Dbscan::fit`]
+    cluster_count: usize,
+}
+
+impl Dbscan {
+    /// Create a new `Dbscan` clusterer that uses the Euclidean distance
+    #[must_use]
+    pub const fn new(epsilon: f64, min_points: usize) -> Self {
+        Self::with_metric(epsilon, min_points, EuclideanDistance)
+    }
+}
+
+impl<M: DistanceMetric> Dbscan<M> {
+    /// Create a new `Dbscan` clusterer that uses the given distance metric
+    #[must_use]
+    pub const fn with_metric(epsilon: f64, min_points: usize, metric: M) -> Self {
+        Self {
+            epsilon,
+            min_points,
+            metric,
+            cluster_count: 0,
+        }
+    }
+
+    /// The number of clusters found by the last call to [`Dbscan::fit`], 0 if it hasn't been called yet
+    #[must_use]
+    pub const fn cluster_count(&self) -> usize {
+        self.cluster_count
+    }
+
+    /// Cluster the analyses
+    ///
+    /// Returns the cluster that each analysis belongs to, in the same order as `analyses`,
+    /// `None` if the analysis is noise. Clusters are numbered from 0 to [`Dbscan::cluster_count`] (exclusive).
+    pub fn fit(&mut self, analyses: &[Analysis]) -> Vec<Option<usize>> {
+        let neighbors = analyses
+            .par_iter()
+            .map(|analysis| {
+                analyses
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| analysis.distance(other, &self.metric) <= self.epsilon)
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let is_core = |i: usize| neighbors[i].len() >= self.min_points;
+
+        let mut labels = vec![None; analyses.len()];
+        let mut cluster_count = 0;
+        for start in 0..analyses.len() {
+            if labels[start].is_some() || !is_core(start) {
+                continue;
+            }
+
+            // grow a new cluster from this core sample
+            labels[start] = Some(cluster_count);
+            let mut frontier = vec![start];
+            while let Some(i) = frontier.pop() {
+                for &neighbor in &neighbors[i] {
+                    if labels[neighbor].is_some() {
+                        continue;
+                    }
+                    labels[neighbor] = Some(cluster_count);
+                    // only core samples extend the cluster, the others are on its border
+                    if is_core(neighbor) {
+                        frontier.push(neighbor);
+                    }
+                }
+            }
+            cluster_count += 1;
+        }
+
+        self.cluster_count = cluster_count;
+        labels
+    }
+}
+
+/// How the distance between two clusters is measured by [`AgglomerativeClustering`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Linkage {
+    /// The distance between the closest samples of the two clusters
+    Single,
+    /// The distance between the furthest samples of the two clusters
+    Complete,
+    /// The mean distance between the samples of the two clusters
+    Average,
+    /// The increase in within-cluster variance caused by merging the two clusters,
+    /// which tends to produce compact clusters of similar sizes
+    #[default]
+    Ward,
+}
+
+impl Linkage {
+    /// The distance between cluster `k` and the union of clusters `i` and `j`,
+    /// given the (Euclidean) distances between them and their sizes (Lance-Williams formula)
+    fn merged_distance(
+        self,
+        (d_ki, n_i): (f64, usize),
+        (d_kj, n_j): (f64, usize),
+        (d_ij, n_k): (f64, usize),
+    ) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let (n_i, n_j, n_k) = (n_i as f64, n_j as f64, n_k as f64);
+        match self {
+            Self::Single => d_ki.min(d_kj),
+            Self::Complete => d_ki.max(d_kj),
+            Self::Average => n_i.mul_add(d_ki, n_j * d_kj) / (n_i + n_j),
+            Self::Ward => ((n_i + n_k).mul_add(
+                d_ki.powi(2),
+                (n_j + n_k).mul_add(d_kj.powi(2), -n_k * d_ij.powi(2)),
+            ) / (n_i + n_j + n_k))
+                .max(0.)
+                .sqrt(),
+        }
+    }
+}
+
+/// A merge of two clusters in a [`Dendrogram`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Merge {
+    /// The lower numbered of the merged clusters
+    pub left: usize,
+    /// The higher numbered of the merged clusters
+    pub right: usize,
+    /// The distance between the merged clusters
+    pub distance: f64,
+    /// The number of samples in the resulting cluster
+    pub size: usize,
+}
+
+/// The tree of merges built by [`AgglomerativeClustering`]
+///
+/// Clusters are numbered like in scipy: the clusters `0..leaves` are the individual samples,
+/// and the cluster `leaves + i` is the one created by `merges[i]`.
+/// Merges are sorted by increasing distance.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dendrogram {
+    /// The number of samples that were clustered
+    pub leaves: usize,
+    /// The merges, from the first (closest clusters) to the last (which creates the cluster of all samples)
+    pub merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    /// Cut the dendrogram, keeping only the merges of clusters at most `distance_threshold` apart
+    ///
+    /// Returns the cluster that each sample belongs to, clusters are numbered in order of their first sample.
+    #[must_use]
+    pub fn cut(&self, distance_threshold: f64) -> Vec<usize> {
+        // the root of each cluster, so that samples can find which cluster they end up in
+        let mut parents = (0..self.leaves + self.merges.len()).collect::<Vec<_>>();
+        for (i, merge) in self.merges.iter().enumerate() {
+            if merge.distance > distance_threshold {
+                break;
+            }
+            parents[merge.left] = self.leaves + i;
+            parents[merge.right] = self.leaves + i;
+        }
+        let root = |mut node: usize| {
+            while parents[node] != node {
+                node = parents[node];
+            }
+            node
+        };
+
+        let mut roots = Vec::new();
+        (0..self.leaves)
+            .map(|leaf| {
+                let root = root(leaf);
+                roots.iter().position(|&r| r == root).unwrap_or_else(|| {
+                    roots.push(root);
+                    roots.len() - 1
+                })
+            })
+            .collect()
+    }
+}
+
+/// Hierarchical agglomerative clustering
+///
+/// Starting with every sample in its own cluster, the two closest clusters (according to the [`Linkage`])
+/// are merged until there is only one cluster left. The resulting [`Dendrogram`] can then be cut at any distance,
+/// so the number of clusters doesn't have to be chosen in advance.
+///
+/// The distances between samples are Euclidean. Building the dendrogram takes O(n^2) memory and O(n^3) time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AgglomerativeClustering {
+    pub linkage: Linkage,
+}
+
+impl AgglomerativeClustering {
+    #[must_use]
+    pub const fn new(linkage: Linkage) -> Self {
+        Self { linkage }
+    }
+
+    /// Build the dendrogram of the analyses
+    #[must_use]
+    pub fn fit(&self, analyses: &[Analysis]) -> Dendrogram {
+        let n = analyses.len();
+        let mut distances = Analysis::similarity_matrix(analyses, &EuclideanDistance);
+        // the dendrogram node, and size, of the cluster in each row of `distances`, `None` once it's been merged into another
+        let mut clusters = (0..n).map(|i| Some((i, 1))).collect::<Vec<_>>();
+        let mut merges = Vec::with_capacity(n.saturating_sub(1));
+
+        for step in 0..n.saturating_sub(1) {
+            let active = (0..n)
+                .filter(|&i| clusters[i].is_some())
+                .collect::<Vec<_>>();
+            let (i, j) = active
+                .iter()
+                .enumerate()
+                .flat_map(|(a, &i)| active[a + 1..].iter().map(move |&j| (i, j)))
+                .min_by(|&(a, b), &(c, d)| distances[[a, b]].total_cmp(&distances[[c, d]]))
+                .expect("there are at least two clusters left");
+            let ((node_i, n_i), (node_j, n_j)) = (clusters[i].unwrap(), clusters[j].unwrap());
+            let d_ij = distances[[i, j]];
+
+            // the merged cluster takes the place of `i`
+            for (k, &cluster) in clusters.iter().enumerate() {
+                let Some((_, n_k)) = cluster else {
+                    continue;
+                };
+                if k == i || k == j {
+                    continue;
+                }
+                let distance = self.linkage.merged_distance(
+                    (distances[[k, i]], n_i),
+                    (distances[[k, j]], n_j),
+                    (d_ij, n_k),
+                );
+                distances[[k, i]] = distance;
+                distances[[i, k]] = distance;
+            }
+            clusters[i] = Some((n + step, n_i + n_j));
+            clusters[j] = None;
+
+            merges.push(Merge {
+                left: node_i.min(node_j),
+                right: node_i.max(node_j),
+                distance: d_ij,
+                size: n_i + n_j,
+            });
+        }
+
+        Dendrogram { leaves: n, merges }
+    }
+
+    /// Cluster the analyses, merging clusters up to `distance_threshold` apart (see [`Dendrogram::cut`])
+    ///
+    /// Returns the cluster that each analysis belongs to, in the same order as `analyses`.
+    #[must_use]
+    pub fn fit_with_threshold(&self, analyses: &[Analysis], distance_threshold: f64) -> Vec<usize> {
+        self.fit(analyses).cut(distance_threshold)
+    }
+}
+
+/// The number of clusters in `labels`, assuming they're numbered from 0
+fn cluster_count(labels: &[usize]) -> usize {
+    labels.iter().max().map_or(0, |&max| max + 1)
+}
+
+/// The mean [silhouette coefficient](https://en.wikipedia.org/wiki/Silhouette_(clustering)) of the analyses,
+/// which measures how much closer each sample is to the rest of its cluster than to the nearest other cluster.
+///
+/// Ranges from -1 (samples are in the wrong clusters) to 1 (dense, well separated clusters),
+/// values around 0 mean that the clusters overlap.
+/// Samples alone in their cluster have a coefficient of 0.
+/// Returns 0 if there are fewer than 2 clusters, since the coefficient isn't defined then.
+///
+/// `labels[i]` is the cluster of `analyses[i]`, clusters are numbered from 0.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn silhouette_score(
+    analyses: &[Analysis],
+    labels: &[usize],
+    metric: &dyn DistanceMetric,
+) -> f64 {
+    debug_assert_eq!(analyses.len(), labels.len());
+    let k = cluster_count(labels);
+    if k < 2 {
+        return 0.;
+    }
+
+    let mut sizes = vec![0usize; k];
+    for &label in labels {
+        sizes[label] += 1;
+    }
+    let distances = Analysis::similarity_matrix(analyses, metric);
+
+    let total = distances
+        .outer_iter()
+        .zip(labels)
+        .map(|(row, &label)| {
+            if sizes[label] == 1 {
+                return 0.;
+            }
+            // the mean distance to the samples of each cluster
+            let mut means = vec![0.; k];
+            for (distance, &other) in row.iter().zip(labels) {
+                means[other] += distance;
+            }
+            for (cluster, mean) in means.iter_mut().enumerate() {
+                // don't count the sample itself
+                let size = sizes[cluster] - usize::from(cluster == label);
+                *mean /= size.max(1) as f64;
+            }
+
+            let a = means[label];
+            let b = means
+                .iter()
+                .enumerate()
+                .filter(|&(cluster, _)| cluster != label && sizes[cluster] > 0)
+                .map(|(_, &mean)| mean)
+                .fold(f64::INFINITY, f64::min);
+            if a.max(b) == 0. {
+                0.
+            } else {
+                (b - a) / a.max(b)
+            }
+        })
+        .sum::<f64>();
+
+    total / analyses.len() as f64
+}
+
+/// The inertia of the clustering, the sum of the squared distances between the analyses and the centroid
+/// (the mean of the features) of their cluster.
+///
+/// Lower is better, but it always decreases as the number of clusters grows, so it's best used to compare
+/// clusterings with the same number of clusters, or to look for an "elbow" when choosing k.
+///
+/// `labels[i]` is the cluster of `analyses[i]`, clusters are numbered from 0.
+#[must_use]
+pub fn inertia(analyses: &[Analysis], labels: &[usize], metric: &dyn DistanceMetric) -> f64 {
+    debug_assert_eq!(analyses.len(), labels.len());
+    let k = cluster_count(labels);
+
+    let mut centroids = vec![[0.; NUMBER_FEATURES]; k];
+    let mut sizes = vec![0usize; k];
+    for (analysis, &label) in analyses.iter().zip(labels) {
+        for (sum, feature) in centroids[label].iter_mut().zip(analysis.inner()) {
+            *sum += feature;
+        }
+        sizes[label] += 1;
+    }
+    let centroids = centroids
+        .into_iter()
+        .zip(sizes)
+        .map(|(mut centroid, size)| {
+            #[allow(clippy::cast_precision_loss)]
+            centroid.iter_mut().for_each(|v| *v /= size.max(1) as f64);
+            Analysis::new(centroid)
+        })
+        .collect::<Vec<_>>();
+
+    analyses
+        .iter()
+        .zip(labels)
+        .map(|(analysis, &label)| analysis.distance(&centroids[label], metric).powi(2))
+        .sum()
+}
+
+/// The labels of a clustering, along with measures of its quality
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusteringResult {
+    /// The cluster that each sample belongs to, numbered from 0
+    pub labels: Vec<usize>,
+    /// The number of clusters
+    pub k: usize,
+    /// See [`silhouette_score`]
+    pub silhouette_score: f64,
+    /// See [`inertia`]
+    pub inertia: f64,
+}
+
+impl ClusteringResult {
+    /// Evaluate the clustering of `analyses` given by `labels`
+    #[must_use]
+    pub fn new(analyses: &[Analysis], labels: Vec<usize>, metric: &dyn DistanceMetric) -> Self {
+        Self {
+            k: cluster_count(&labels),
+            silhouette_score: silhouette_score(analyses, &labels, metric),
+            inertia: inertia(analyses, &labels, metric),
+            labels,
+        }
+    }
+
+    /// The number of samples in each cluster
+    #[must_use]
+    pub fn cluster_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.k];
+        for &label in &self.labels {
+            sizes[label] += 1;
+        }
+        sizes
+    }
+}
+
+impl std::fmt::Display for ClusteringResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} samples in {} clusters (sizes: {:?}), silhouette score: {:.3}, inertia: {:.3}",
+            self.labels.len(),
+            self.k,
+            self.cluster_sizes(),
+            self.silhouette_score,
+            self.inertia
+        )
+    }
+}
+
+fn squared_distance(a: ArrayView1<Feature>, b: ArrayView1<Feature>) -> Feature {
+    a.iter().zip(b.iter()).map(|(a, b)| (a - b).powi(2)).sum()
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum KOptimal {
     GapStatistic {
@@ -197,6 +838,30 @@ impl ClusteringHelper<NotInitialized> {
         })
     }
 
+    /// Initialize the `KMeansHelper` object with a fixed number of clusters,
+    /// instead of searching for the optimal number of clusters
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `k` is 0, or if there are fewer samples than clusters
+    pub fn initialize_with_k(
+        self,
+        k: usize,
+    ) -> Result<ClusteringHelper<Initialized>, ClusteringError> {
+        let samples = self.state.embeddings.nrows();
+        if k == 0 || k > samples {
+            return Err(ClusteringError::InvalidClusterCount(k, samples));
+        }
+
+        Ok(ClusteringHelper {
+            state: Initialized {
+                embeddings: self.state.embeddings,
+                k,
+                clustering_method: self.state.clustering_method,
+            },
+        })
+    }
+
     fn get_optimal_k(&self) -> Result<usize, ClusteringError> {
         match self.state.optimizer {
             KOptimal::GapStatistic { b } => self.get_optimal_k_gap_statistic(b),
@@ -476,6 +1141,8 @@ mod tests {
     use super::*;
     use ndarray::{arr1, arr2, s};
     use pretty_assertions::assert_eq;
+    use rand::{rngs::SmallRng, SeedableRng};
+    use rstest::rstest;
 
     #[test]
     fn test_generate_reference_data_set() {
@@ -502,6 +1169,364 @@ mod tests {
         assert_ne!(ref_data, data);
     }
 
+    #[test]
+    fn test_kmeans_separated_clusters() {
+        let samples = arr2(&[
+            [0.0, 0.0],
+            [0.1, 0.0],
+            [0.0, 0.1],
+            [10.0, 10.0],
+            [10.1, 10.0],
+            [10.0, 10.1],
+            [-10.0, 10.0],
+            [-10.1, 10.0],
+            [-10.0, 10.1],
+        ]);
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        let fit = KMeansClusterer::new(3, InitStrategy::KMeansPlusPlus)
+            .fit(samples.view(), &mut rng)
+            .unwrap();
+
+        assert_eq!(fit.centroids.shape(), &[3, 2]);
+        assert!(fit.iterations <= KMeansClusterer::DEFAULT_MAX_ITERATIONS);
+        // samples in the same group share a label, and each group has its own label
+        for group in fit.labels.exact_chunks(3) {
+            assert!(group.iter().all(|&label| label == group[0]));
+        }
+        assert_ne!(fit.labels[0], fit.labels[3]);
+        assert_ne!(fit.labels[0], fit.labels[6]);
+        assert_ne!(fit.labels[3], fit.labels[6]);
+    }
+
+    #[test]
+    fn test_clustering_method_kmeans() {
+        let samples = arr2(&[
+            [0.0, 0.0],
+            [0.1, 0.0],
+            [0.0, 0.1],
+            [10.0, 10.0],
+            [10.1, 10.0],
+            [10.0, 10.1],
+        ]);
+
+        let labels = ClusteringMethod::KMeans.fit(2, &samples);
+
+        assert_eq!(labels.len(), 6);
+        for group in labels.exact_chunks(3) {
+            assert!(group.iter().all(|&label| label == group[0]));
+        }
+        assert_ne!(labels[0], labels[3]);
+    }
+
+    #[rstest]
+    #[case::random(InitStrategy::Random)]
+    #[case::kmeans_plus_plus(InitStrategy::KMeansPlusPlus)]
+    fn test_kmeans_one_cluster_per_sample(#[case] init: InitStrategy) {
+        let samples = arr2(&[[1.0, 1.0], [2.0, 2.0], [3.0, 3.0]]);
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        let fit = KMeansClusterer::new(3, init)
+            .fit(samples.view(), &mut rng)
+            .unwrap();
+
+        // every sample is its own centroid, so nothing moves after the first iteration
+        assert_eq!(fit.iterations, 1);
+        let mut labels = fit.labels.to_vec();
+        labels.sort_unstable();
+        assert_eq!(labels, vec![0, 1, 2]);
+    }
+
+    #[rstest]
+    #[case::random(InitStrategy::Random)]
+    #[case::kmeans_plus_plus(InitStrategy::KMeansPlusPlus)]
+    fn test_kmeans_duplicate_samples(#[case] init: InitStrategy) {
+        let samples = arr2(&[[1.0, 1.0], [1.0, 1.0], [1.0, 1.0], [1.0, 1.0]]);
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        let fit = KMeansClusterer::new(2, init)
+            .fit(samples.view(), &mut rng)
+            .unwrap();
+
+        assert!(fit.centroids.iter().all(|&v| v == 1.0));
+    }
+
+    #[rstest]
+    #[case::zero_clusters(0)]
+    #[case::more_clusters_than_samples(4)]
+    fn test_kmeans_invalid_cluster_count(#[case] k: usize) {
+        let samples = arr2(&[[1.0, 1.0], [2.0, 2.0], [3.0, 3.0]]);
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        let result = KMeansClusterer::new(k, InitStrategy::default()).fit(samples.view(), &mut rng);
+
+        assert!(matches!(
+            result,
+            Err(ClusteringError::InvalidClusterCount(actual, 3)) if actual == k
+        ));
+    }
+
+    fn point(x: Feature, y: Feature) -> Analysis {
+        let mut features = [0.; NUMBER_FEATURES];
+        features[0] = x;
+        features[1] = y;
+        Analysis::new(features)
+    }
+
+    #[rstest]
+    #[case::zero_clusters(0)]
+    #[case::more_clusters_than_samples(21)]
+    fn test_initialize_with_invalid_k(#[case] k: usize) {
+        #[allow(clippy::cast_precision_loss)]
+        let analyses = (0..20)
+            .map(|i| point(i as Feature, (i % 2) as Feature))
+            .collect::<Vec<_>>();
+        let helper = ClusteringHelper::new(
+            analyses.into(),
+            8,
+            KOptimal::DaviesBouldin,
+            ClusteringMethod::KMeans,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            helper.initialize_with_k(k),
+            Err(ClusteringError::InvalidClusterCount(actual, 20)) if actual == k
+        ));
+    }
+
+    #[test]
+    fn test_initialize_with_k() {
+        // two well separated groups of points
+        #[allow(clippy::cast_precision_loss)]
+        let analyses = (0..20)
+            .map(|i| {
+                let offset = if i < 10 { 0.0 } else { 100.0 };
+                point(offset + (i % 10) as Feature * 0.1, offset)
+            })
+            .collect::<Vec<_>>();
+        let helper = ClusteringHelper::new(
+            analyses.clone().into(),
+            8,
+            KOptimal::DaviesBouldin,
+            ClusteringMethod::KMeans,
+        )
+        .unwrap();
+
+        let clusters = helper
+            .initialize_with_k(3)
+            .unwrap()
+            .cluster()
+            .extract_analysis_clusters(analyses);
+
+        assert_eq!(clusters.len(), 3);
+        assert_eq!(clusters.iter().map(Vec::len).sum::<usize>(), 20);
+    }
+
+    #[test]
+    fn test_dbscan_separated_clusters() {
+        let analyses = [
+            point(0.0, 0.0),
+            point(0.1, 0.0),
+            point(0.0, 0.1),
+            point(10.0, 10.0),
+            point(10.1, 10.0),
+            point(10.0, 10.1),
+            // noise
+            point(-10.0, 10.0),
+        ];
+        let mut dbscan = Dbscan::new(0.5, 3);
+        assert_eq!(dbscan.cluster_count(), 0);
+
+        let labels = dbscan.fit(&analyses);
+
+        assert_eq!(dbscan.cluster_count(), 2);
+        assert_eq!(
+            labels,
+            vec![Some(0), Some(0), Some(0), Some(1), Some(1), Some(1), None]
+        );
+    }
+
+    #[test]
+    fn test_dbscan_arbitrary_shape() {
+        // two long parallel lines, closer to each other than to their own ends,
+        // which k-means would split across rather than along
+        let analyses = (0..20)
+            .map(|i| point(Feature::from(i) * 0.5, 0.0))
+            .chain((0..20).map(|i| point(Feature::from(i) * 0.5, 2.0)))
+            .collect::<Vec<_>>();
+        let mut dbscan = Dbscan::new(0.6, 2);
+
+        let labels = dbscan.fit(&analyses);
+
+        assert_eq!(dbscan.cluster_count(), 2);
+        assert!(labels[..20].iter().all(|&label| label == Some(0)));
+        assert!(labels[20..].iter().all(|&label| label == Some(1)));
+    }
+
+    #[test]
+    fn test_dbscan_border_and_noise() {
+        let analyses = [point(0.0, 0.0), point(0.4, 0.0), point(0.8, 0.0)];
+
+        // only the middle sample is a core sample, the others are on the border of its cluster
+        let mut dbscan = Dbscan::new(0.5, 3);
+        assert_eq!(dbscan.fit(&analyses), vec![Some(0), Some(0), Some(0)]);
+        assert_eq!(dbscan.cluster_count(), 1);
+
+        // no core samples, everything is noise
+        let mut dbscan = Dbscan::new(0.5, 4);
+        assert_eq!(dbscan.fit(&analyses), vec![None, None, None]);
+        assert_eq!(dbscan.cluster_count(), 0);
+
+        assert!(dbscan.fit(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_dbscan_with_metric() {
+        // the same directions are close in cosine distance, however far apart they are
+        let analyses = [
+            point(1.0, 0.0),
+            point(100.0, 1.0),
+            point(0.0, 1.0),
+            point(1.0, 100.0),
+        ];
+        let mut dbscan = Dbscan::with_metric(0.01, 2, crate::metrics::CosineDistance);
+
+        assert_eq!(
+            dbscan.fit(&analyses),
+            vec![Some(0), Some(0), Some(1), Some(1)]
+        );
+    }
+
+    #[rstest]
+    #[case::single(Linkage::Single, 4.)]
+    #[case::complete(Linkage::Complete, 5.)]
+    #[case::average(Linkage::Average, 4.5)]
+    #[case::ward(Linkage::Ward, 27f64.sqrt())]
+    fn test_agglomerative_clustering(#[case] linkage: Linkage, #[case] last_distance: f64) {
+        let analyses = [point(0.0, 0.0), point(1.0, 0.0), point(5.0, 0.0)];
+
+        let dendrogram = AgglomerativeClustering::new(linkage).fit(&analyses);
+
+        assert_eq!(dendrogram.leaves, 3);
+        assert_eq!(dendrogram.merges.len(), 2);
+        assert_eq!(
+            dendrogram.merges[0],
+            Merge {
+                left: 0,
+                right: 1,
+                distance: 1.,
+                size: 2
+            }
+        );
+        assert_eq!(dendrogram.merges[1].left, 2);
+        assert_eq!(dendrogram.merges[1].right, 3);
+        assert_eq!(dendrogram.merges[1].size, 3);
+        assert!(
+            (dendrogram.merges[1].distance - last_distance).abs() < 0.000_000_1,
+            "{} !~= {last_distance}",
+            dendrogram.merges[1].distance
+        );
+
+        assert_eq!(dendrogram.cut(0.5), vec![0, 1, 2]);
+        assert_eq!(dendrogram.cut(2.), vec![0, 0, 1]);
+        assert_eq!(dendrogram.cut(10.), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_agglomerative_clustering_threshold() {
+        let analyses = [
+            point(0.0, 0.0),
+            point(10.0, 10.0),
+            point(0.1, 0.0),
+            point(10.1, 10.0),
+            point(0.0, 0.1),
+            point(10.0, 10.1),
+        ];
+
+        let labels = AgglomerativeClustering::default().fit_with_threshold(&analyses, 1.);
+
+        assert_eq!(labels, vec![0, 1, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_agglomerative_clustering_empty() {
+        let dendrogram = AgglomerativeClustering::default().fit(&[]);
+        assert_eq!(dendrogram, Dendrogram::default());
+        assert!(dendrogram.cut(1.).is_empty());
+    }
+
+    #[test]
+    fn test_silhouette_score() {
+        let analyses = [
+            point(0.0, 0.0),
+            point(1.0, 0.0),
+            point(10.0, 0.0),
+            point(11.0, 0.0),
+        ];
+        let metric = EuclideanDistance;
+
+        // samples 0 and 3: a = 1, b = mean(10, 11) = 10.5
+        // samples 1 and 2: a = 1, b = mean(9, 10) = 9.5
+        let expected = 2f64.mul_add(1. - 1. / 10.5, 2. * (1. - 1. / 9.5)) / 4.;
+        let score = silhouette_score(&analyses, &[0, 0, 1, 1], &metric);
+        assert!(
+            (score - expected).abs() < 0.000_000_1,
+            "{score} !~= {expected}"
+        );
+
+        // a bad clustering has a negative score
+        assert!(silhouette_score(&analyses, &[0, 1, 0, 1], &metric) < 0.);
+        // a single cluster has no score
+        assert!(silhouette_score(&analyses, &[0, 0, 0, 0], &metric).abs() < f64::EPSILON);
+        // singletons have a coefficient of 0
+        let score = silhouette_score(&analyses, &[0, 0, 1, 2], &metric);
+        // sample 0: a = 1, b = 10, sample 1: a = 1, b = 9
+        let expected = (1. - 1. / 10. + 1. - 1. / 9.) / 4.;
+        assert!(
+            (score - expected).abs() < 0.000_000_1,
+            "{score} !~= {expected}"
+        );
+    }
+
+    #[test]
+    fn test_inertia() {
+        let analyses = [
+            point(0.0, 0.0),
+            point(2.0, 0.0),
+            point(10.0, 0.0),
+            point(10.0, 4.0),
+        ];
+        let metric = EuclideanDistance;
+
+        // centroids at (1, 0) and (10, 2)
+        let inertia = inertia(&analyses, &[0, 0, 1, 1], &metric);
+        assert!((inertia - 10.).abs() < 0.000_000_1, "{inertia} !~= 10");
+    }
+
+    #[test]
+    fn test_clustering_result() {
+        let analyses = [
+            point(0.0, 0.0),
+            point(2.0, 0.0),
+            point(10.0, 0.0),
+            point(10.0, 4.0),
+        ];
+
+        let result = ClusteringResult::new(&analyses, vec![0, 0, 1, 1], &EuclideanDistance);
+
+        assert_eq!(result.k, 2);
+        assert_eq!(result.cluster_sizes(), vec![2, 2]);
+        assert!((result.inertia - 10.).abs() < 0.000_000_1);
+        assert_eq!(
+            result.to_string(),
+            format!(
+                "4 samples in 2 clusters (sizes: [2, 2]), silhouette score: {:.3}, inertia: 10.000",
+                result.silhouette_score
+            )
+        );
+    }
+
     #[test]
     fn test_pairwise_distances() {
         let samples = arr2(&[[1.0, 1.0], [1.0, 1.0], [2.0, 2.0], [2.0, 2.0]]);
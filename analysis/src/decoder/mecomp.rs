@@ -1,6 +1,6 @@
 //! Implementation of the mecomp decoder, which is rodio/rubato based.
 
-use std::{fs::File, io::BufReader};
+use std::{fs::File, io::BufReader, time::Duration};
 
 use rodio::Source;
 use rubato::{FastFixedIn, PolynomialDegree, Resampler};
@@ -19,6 +19,18 @@ impl Decoder for MecompDecoder {
     /// The output sample array should be resampled to f32le, one channel, with a sampling rate
     /// of 22050 Hz. Anything other than that will yield wrong results.
     fn decode(path: &std::path::Path) -> AnalysisResult<ResampledAudio> {
+        Self::decode_with_sample_rate(path, SAMPLE_RATE)
+    }
+
+    /// Like [`MecompDecoder::decode`], but resamples the audio to any of the [`SUPPORTED_SAMPLE_RATES`](crate::SUPPORTED_SAMPLE_RATES).
+    fn decode_with_sample_rate(
+        path: &std::path::Path,
+        target_sample_rate: u32,
+    ) -> AnalysisResult<ResampledAudio> {
+        if !crate::SUPPORTED_SAMPLE_RATES.contains(&target_sample_rate) {
+            return Err(AnalysisError::UnsupportedSampleRate(target_sample_rate));
+        }
+
         let file = BufReader::new(File::open(path)?);
         let source = rodio::Decoder::new(file)?.convert_samples::<f32>();
 
@@ -56,13 +68,19 @@ impl Decoder for MecompDecoder {
             )
         };
 
-        // then we need to resample the audio source into 22050 Hz
-        let resampled_array = if sample_rate == SAMPLE_RATE {
+        // the duration reported by the source isn't always accurate, so we compute it from the samples instead
+        #[allow(clippy::cast_precision_loss)]
+        let duration =
+            Duration::try_from_secs_f64(mono_sample_array.len() as f64 / f64::from(sample_rate))
+                .ok();
+
+        // then we need to resample the audio source into the target sample rate
+        let resampled_array = if sample_rate == target_sample_rate {
             mono_sample_array.shrink_to_fit();
             mono_sample_array
         } else {
             let mut resampler = FastFixedIn::new(
-                f64::from(SAMPLE_RATE) / f64::from(sample_rate),
+                f64::from(target_sample_rate) / f64::from(sample_rate),
                 1.0,
                 PolynomialDegree::Cubic,
                 mono_sample_array.len(),
@@ -71,10 +89,15 @@ impl Decoder for MecompDecoder {
             resampler.process(&[&mono_sample_array], None)?[0].clone()
         };
 
-        Ok(ResampledAudio {
+        let audio = ResampledAudio {
             path: path.to_owned(),
             samples: resampled_array,
-        })
+            sample_rate: target_sample_rate,
+            duration,
+        };
+        audio.validate()?;
+
+        Ok(audio)
     }
 }
 
@@ -5,11 +5,15 @@ use std::{
     path::{Path, PathBuf},
     sync::mpsc,
     thread,
+    time::Duration,
 };
 
 use log::info;
 
-use crate::{errors::AnalysisResult, Analysis, ResampledAudio};
+use crate::{
+    errors::{AnalysisError, AnalysisResult},
+    Analysis, ResampledAudio, SAMPLE_RATE, SUPPORTED_SAMPLE_RATES,
+};
 
 mod mecomp;
 #[allow(clippy::module_name_repetitions)]
@@ -31,17 +35,36 @@ pub trait Decoder {
     ///
     /// The output sample array should be resampled to f32le, one channel, with a sampling rate
     /// of 22050 Hz. Anything other than that will yield wrong results.
+    /// Implementations should call [`ResampledAudio::validate`] before returning.
     ///
     /// # Errors
     ///
     /// This function will return an error if the file path is invalid, if
     /// the file path points to a file containing no or corrupted audio stream,
+    /// if the resampled audio is invalid,
     /// or if the analysis could not be conducted to the end for some reason.
     ///
     /// The error type returned should give a hint as to whether it was a
     /// decoding or an analysis error.
     fn decode(path: &Path) -> AnalysisResult<ResampledAudio>;
 
+    /// Like [`Decoder::decode`], but the song should be resampled to `sample_rate` instead of [`SAMPLE_RATE`].
+    ///
+    /// The default implementation only supports [`SAMPLE_RATE`], decoders that can resample to
+    /// the other [`SUPPORTED_SAMPLE_RATES`] should override it.
+    /// See [`ResampledAudioBuilder`] for a more convenient way to call this.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the same errors as [`Decoder::decode`],
+    /// or [`AnalysisError::UnsupportedSampleRate`] if the decoder can't resample to `sample_rate`.
+    fn decode_with_sample_rate(path: &Path, sample_rate: u32) -> AnalysisResult<ResampledAudio> {
+        if sample_rate != SAMPLE_RATE {
+            return Err(AnalysisError::UnsupportedSampleRate(sample_rate));
+        }
+        Self::decode(path)
+    }
+
     /// Returns a decoded song's `Analysis` given a file path, or an error if the song
     /// could not be analyzed for some reason.
     ///
@@ -61,6 +84,30 @@ pub trait Decoder {
         Self::decode(path.as_ref())?.try_into()
     }
 
+    /// Returns a decoded song's `Analysis` given a file path, or an error if the song
+    /// could not be analyzed for some reason, or if the analysis took longer than `timeout`.
+    ///
+    /// The song is resampled to `sample_rate` (see [`Decoder::decode_with_sample_rate`]) before it's analyzed,
+    /// and the time spent decoding it doesn't count towards the timeout.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file path is invalid, if
+    /// the file path points to a file containing no or corrupted audio stream,
+    /// if the decoder can't resample to `sample_rate`,
+    /// if the analysis could not be conducted to the end for some reason,
+    /// or [`AnalysisError::Timeout`](crate::errors::AnalysisError::Timeout) if the analysis timed out.
+    fn analyze_path_with_timeout<P: AsRef<Path>>(
+        path: P,
+        sample_rate: u32,
+        timeout: Duration,
+    ) -> AnalysisResult<Analysis> {
+        Analysis::from_samples_with_timeout(
+            &Self::decode_with_sample_rate(path.as_ref(), sample_rate)?,
+            timeout,
+        )
+    }
+
     /// Analyze songs in `paths`, and return the `Analysis` objects through an
     /// [`mpsc::IntoIter`].
     ///
@@ -222,6 +269,195 @@ pub trait DecoderWithCallback: Decoder {
             }
         });
     }
+
+    /// Analyze songs in `paths` (resampled to `sample_rate`) in parallel, giving up on any song whose analysis takes longer than `timeout`,
+    /// and send the `Analysis` objects over the provided channel as soon as each song is ready.
+    ///
+    /// Songs that time out are sent with an [`AnalysisError::Timeout`](crate::errors::AnalysisError::Timeout) error.
+    fn analyze_paths_with_timeout_and_callback<P: Into<PathBuf>, I: IntoIterator<Item = P>>(
+        paths: I,
+        sample_rate: u32,
+        timeout: Duration,
+        callback: mpsc::Sender<(PathBuf, AnalysisResult<Analysis>)>,
+    ) {
+        let cores = thread::available_parallelism().unwrap_or(NonZeroUsize::new(1).unwrap());
+        let paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+        let mut chunk_length = paths.len() / cores;
+        if chunk_length == 0 {
+            chunk_length = paths.len();
+        }
+
+        if paths.is_empty() {
+            return;
+        }
+
+        thread::scope(move |scope| {
+            let mut handles = Vec::new();
+            for chunk in paths.chunks(chunk_length) {
+                let owned_chunk = chunk.to_owned();
+
+                let tx_thread: mpsc::Sender<_> = callback.clone();
+
+                let child = scope.spawn(move || {
+                    for path in owned_chunk {
+                        info!("Analyzing file '{:?}'", path);
+
+                        let song = Self::analyze_path_with_timeout(&path, sample_rate, timeout);
+
+                        tx_thread.send((path, song)).unwrap();
+                    }
+                });
+                handles.push(child);
+            }
+
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        });
+    }
+
+    /// Decode (and resample) songs in `paths` in parallel, sending the decoded audio
+    /// over the provided channel as soon as each song is ready.
+    ///
+    /// This is useful for computing things from the audio that aren't part of the `Analysis`,
+    /// such as the `ReplayGain` of a song.
+    ///
+    /// Sends tuples made of the song path (to display to the user in case decoding failed),
+    /// and a `Result<ResampledAudio>`.
+    fn decode_paths_with_callback<P: Into<PathBuf>, I: IntoIterator<Item = P>>(
+        paths: I,
+        callback: mpsc::Sender<(PathBuf, AnalysisResult<ResampledAudio>)>,
+    ) {
+        let cores = thread::available_parallelism().unwrap_or(NonZeroUsize::new(1).unwrap());
+        let paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+        let mut chunk_length = paths.len() / cores;
+        if chunk_length == 0 {
+            chunk_length = paths.len();
+        }
+
+        if paths.is_empty() {
+            return;
+        }
+
+        thread::scope(move |scope| {
+            let mut handles = Vec::new();
+            for chunk in paths.chunks(chunk_length) {
+                let owned_chunk = chunk.to_owned();
+
+                let tx_thread: mpsc::Sender<_> = callback.clone();
+
+                let child = scope.spawn(move || {
+                    for path in owned_chunk {
+                        info!("Decoding file '{:?}'", path);
+
+                        let audio = Self::decode(&path);
+
+                        tx_thread.send((path, audio)).unwrap();
+                    }
+                });
+                handles.push(child);
+            }
+
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        });
+    }
 }
 
 impl<T: Decoder> DecoderWithCallback for T {}
+
+/// Builder for decoding songs into [`ResampledAudio`] at a sample rate other than the default [`SAMPLE_RATE`].
+///
+/// Higher sample rates avoid losing the high frequencies of songs recorded at those rates,
+/// at the cost of a slower analysis.
+///
+/// ```ignore
+/// let audio = ResampledAudioBuilder::new()
+///     .sample_rate(44100)?
+///     .decode::<MecompDecoder>(path)?;
+/// let analysis = Analysis::from_samples(&audio)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ResampledAudioBuilder {
+    sample_rate: u32,
+}
+
+impl Default for ResampledAudioBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResampledAudioBuilder {
+    /// Create a builder that resamples songs to [`SAMPLE_RATE`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sample_rate: SAMPLE_RATE,
+        }
+    }
+
+    /// Set the sample rate (in Hz) songs will be resampled to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnalysisError::UnsupportedSampleRate`] if `sample_rate` isn't one of the [`SUPPORTED_SAMPLE_RATES`].
+    pub fn sample_rate(mut self, sample_rate: u32) -> AnalysisResult<Self> {
+        if !SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+            return Err(AnalysisError::UnsupportedSampleRate(sample_rate));
+        }
+        self.sample_rate = sample_rate;
+        Ok(self)
+    }
+
+    /// Decode (and resample) the song at `path` using the decoder `D`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Decoder::decode_with_sample_rate`].
+    pub fn decode<D: Decoder>(&self, path: &Path) -> AnalysisResult<ResampledAudio> {
+        D::decode_with_sample_rate(path, self.sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resampled_audio_builder() {
+        assert_eq!(ResampledAudioBuilder::default().sample_rate, SAMPLE_RATE);
+        for sample_rate in SUPPORTED_SAMPLE_RATES {
+            let builder = ResampledAudioBuilder::new()
+                .sample_rate(sample_rate)
+                .unwrap();
+            assert_eq!(builder.sample_rate, sample_rate);
+        }
+        assert!(matches!(
+            ResampledAudioBuilder::new().sample_rate(16000),
+            Err(AnalysisError::UnsupportedSampleRate(16000))
+        ));
+    }
+
+    #[test]
+    fn test_resampled_audio_builder_decode() {
+        let path = Path::new("data/s16_mono_22_5kHz.flac");
+        let default = ResampledAudioBuilder::new()
+            .decode::<MecompDecoder>(path)
+            .unwrap();
+        let audio = ResampledAudioBuilder::new()
+            .sample_rate(48000)
+            .unwrap()
+            .decode::<MecompDecoder>(path)
+            .unwrap();
+
+        assert_eq!(default.sample_rate, SAMPLE_RATE);
+        assert_eq!(audio.sample_rate, 48000);
+        // the same song, at about 48000 / 22050 times as many samples
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = audio.samples.len() as f64 / default.samples.len() as f64;
+        assert!((ratio - 48000. / 22050.).abs() < 0.01, "{ratio}");
+    }
+}
@@ -18,6 +18,12 @@ pub enum AnalysisError {
     InfiniteAudioSource,
     #[error("Too many or too little features were provided at the end of the analysis")]
     InvalidFeaturesLen,
+    #[error("Analysis took too long and was aborted")]
+    Timeout,
+    #[error("Audio should have a sample rate of {expected} Hz, but it seems to be about {found_estimate} Hz")]
+    InvalidSampleRate { expected: u32, found_estimate: u32 },
+    #[error("Unsupported sample rate: {0} Hz, audio can only be analyzed at one of {supported:?} Hz", supported = crate::SUPPORTED_SAMPLE_RATES)]
+    UnsupportedSampleRate(u32),
 }
 
 pub type AnalysisResult<T> = Result<T, AnalysisError>;
@@ -28,6 +34,8 @@ pub enum ClusteringError {
     SmallLibrary,
     #[error("Optimal k could not be found below k={0}")]
     OptimalKNotFound(usize),
+    #[error("Can't fit {0} clusters to {1} samples")]
+    InvalidClusterCount(usize, usize),
     #[error("Failed to project data: {0}")]
     ProjectionError(#[from] linfa_tsne::TSneError),
 }
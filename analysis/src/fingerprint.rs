@@ -0,0 +1,208 @@
+//! Acoustic fingerprinting.
+//!
+//! The fingerprint is computed the way Haitsma and Kalker describe in
+//! "A Highly Robust Audio Fingerprinting System" (the same family of algorithms as Chromaprint):
+//! every frame of audio is reduced to 32 bits, each recording whether the energy difference
+//! between two neighbouring frequency bands went up or down since the previous frame.
+//!
+//! Those bits only depend on the coarse shape of the spectrum, so they survive lossy encoding,
+//! resampling and volume changes, and two fingerprints are compared by counting the bits that
+//! differ between them (see [`bit_error_rate`]) rather than by checking that they are equal.
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use crate::{utils::window_functions::WindowFunction, window_scale, SAMPLE_RATE};
+
+/// The size of the frames (at [`SAMPLE_RATE`]), about 186ms.
+pub const WINDOW_SIZE: usize = 4096;
+/// The number of samples (at [`SAMPLE_RATE`]) between the starts of consecutive frames, about 46ms.
+pub const HOP_SIZE: usize = 1024;
+
+/// The frequency range (in Hz) that the bands cover, where most of the energy of music is
+/// and where lossy codecs are the least destructive.
+const MIN_FREQUENCY: f32 = 300.;
+const MAX_FREQUENCY: f32 = 2000.;
+/// One more band than there are bits, since each bit compares two neighbouring bands.
+const NUMBER_BANDS: usize = 33;
+
+/// How far (in frames, about 2 seconds) two fingerprints can be shifted against each other
+/// by [`bit_error_rate`], to account for different amounts of leading silence or encoder delay.
+pub const MAX_OFFSET: usize = 43;
+/// The minimum number of frames (about 10 seconds) that must overlap for two fingerprints to be compared
+/// (unless one of them is shorter than that).
+pub const MIN_OVERLAP: usize = 215;
+
+/// Fingerprints of the same recording with a [`bit_error_rate`] below this are considered to match.
+///
+/// Re-encoding a song typically flips a few percent of the bits, while unrelated songs differ in about half of them.
+pub const MATCH_THRESHOLD: f32 = 0.25;
+
+/// Compute the fingerprint of some (mono) audio, one 32 bit word per frame.
+///
+/// The hop size is scaled to the sample rate so that fingerprints computed at different sample rates
+/// are aligned in time and can be compared, audio shorter than two frames has an empty fingerprint.
+#[must_use]
+pub fn fingerprint(samples: &[f32], sample_rate: u32) -> Vec<u32> {
+    let window_size = WINDOW_SIZE * window_scale(sample_rate);
+    #[allow(clippy::cast_possible_truncation)]
+    let hop_size = (HOP_SIZE as u64 * u64::from(sample_rate) / u64::from(SAMPLE_RATE)) as usize;
+
+    if samples.len() < window_size + hop_size {
+        return Vec::new();
+    }
+
+    let bands = band_bins(window_size, sample_rate);
+    let window = WindowFunction::Hann.coefficients(window_size);
+    let fft = FftPlanner::new().plan_fft_forward(window_size);
+    let mut buffer = vec![Complex::default(); window_size];
+
+    let mut previous: Option<[f32; NUMBER_BANDS]> = None;
+    let mut fingerprint = Vec::with_capacity((samples.len() - window_size) / hop_size);
+
+    for frame in samples.windows(window_size).step_by(hop_size) {
+        for ((value, sample), coefficient) in buffer.iter_mut().zip(frame).zip(&window) {
+            *value = Complex::new(sample * coefficient, 0.);
+        }
+        fft.process(&mut buffer);
+
+        let mut energies = [0.; NUMBER_BANDS];
+        for (energy, &(start, end)) in energies.iter_mut().zip(&bands) {
+            *energy = buffer[start..end].iter().map(Complex::norm_sqr).sum();
+        }
+
+        if let Some(previous) = previous {
+            let word = (0..NUMBER_BANDS - 1)
+                .filter(|&band| {
+                    (energies[band] - energies[band + 1]) - (previous[band] - previous[band + 1])
+                        > 0.
+                })
+                .fold(0, |word, band| word | 1 << band);
+            fingerprint.push(word);
+        }
+        previous = Some(energies);
+    }
+
+    fingerprint
+}
+
+/// The (half-open) ranges of FFT bins that make up each band, the bands are spaced logarithmically.
+fn band_bins(window_size: usize, sample_rate: u32) -> [(usize, usize); NUMBER_BANDS] {
+    #[allow(clippy::cast_precision_loss)]
+    let bin_width = sample_rate as f32 / window_size as f32;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let edge = |band: usize| {
+        #[allow(clippy::cast_precision_loss)]
+        let exponent = band as f32 / NUMBER_BANDS as f32;
+        (MIN_FREQUENCY * (MAX_FREQUENCY / MIN_FREQUENCY).powf(exponent) / bin_width).round()
+            as usize
+    };
+
+    std::array::from_fn(|band| {
+        let start = edge(band);
+        (start, edge(band + 1).max(start + 1))
+    })
+}
+
+/// The fraction of bits that differ between two fingerprints, at the offset (of up to [`MAX_OFFSET`] frames)
+/// where they match best.
+///
+/// Only offsets where the fingerprints overlap by at least [`MIN_OVERLAP`] frames (or the whole of the
+/// shorter fingerprint, if it is shorter than that) are considered.
+///
+/// Returns `None` if either fingerprint is empty.
+#[must_use]
+pub fn bit_error_rate(a: &[u32], b: &[u32]) -> Option<f32> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let min_overlap = MIN_OVERLAP.min(a.len()).min(b.len());
+
+    let shifted = |a: &[u32], b: &[u32], offset: usize| {
+        let a = a.get(offset..)?;
+        let overlap = a.len().min(b.len());
+        if overlap < min_overlap {
+            return None;
+        }
+        let errors: u32 = a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum();
+        #[allow(clippy::cast_precision_loss)]
+        Some(errors as f32 / (overlap * 32) as f32)
+    };
+
+    (0..=MAX_OFFSET)
+        .flat_map(|offset| [shifted(a, b, offset), shifted(b, a, offset)])
+        .flatten()
+        .min_by(f32::total_cmp)
+}
+
+/// Whether two fingerprints are of the same recording, see [`MATCH_THRESHOLD`].
+#[must_use]
+pub fn is_match(a: &[u32], b: &[u32]) -> bool {
+    bit_error_rate(a, b).is_some_and(|rate| rate < MATCH_THRESHOLD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::{Decoder as DecoderTrait, MecompDecoder as Decoder};
+    use std::path::Path;
+
+    fn fingerprint_file(path: &str) -> Vec<u32> {
+        let song = Decoder::decode(Path::new(path)).unwrap();
+        fingerprint(&song.samples, song.sample_rate)
+    }
+
+    #[test]
+    fn test_fingerprint_length() {
+        let samples = vec![0.; SAMPLE_RATE as usize * 10];
+        assert_eq!(
+            fingerprint(&samples, SAMPLE_RATE).len(),
+            (samples.len() - WINDOW_SIZE) / HOP_SIZE
+        );
+        assert!(fingerprint(&samples[..WINDOW_SIZE], SAMPLE_RATE).is_empty());
+    }
+
+    #[test]
+    fn test_fingerprint_volume_invariant() {
+        let song = Decoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+        let quieter = song.samples.iter().map(|s| s * 0.25).collect::<Vec<_>>();
+        assert_eq!(
+            fingerprint(&song.samples, song.sample_rate),
+            fingerprint(&quieter, song.sample_rate)
+        );
+    }
+
+    #[test]
+    fn test_bit_error_rate() {
+        let a = fingerprint_file("data/s16_mono_22_5kHz.flac");
+        assert_eq!(bit_error_rate(&a, &a), Some(0.));
+        assert_eq!(bit_error_rate(&a, &[]), None);
+
+        // the same fingerprint, shifted by a few frames
+        assert_eq!(bit_error_rate(&a, &a[5..]), Some(0.));
+        assert_eq!(bit_error_rate(&a[5..], &a), Some(0.));
+    }
+
+    #[test]
+    fn test_reencoded_audio_matches() {
+        // the same recording, as lossless and lossy files
+        let flac = fingerprint_file("data/s32_stereo_44_1_kHz.flac");
+        let mp3 = fingerprint_file("data/s32_stereo_44_1_kHz.mp3");
+        let rate = bit_error_rate(&flac, &mp3).unwrap();
+        assert!(rate < MATCH_THRESHOLD, "{rate} >= {MATCH_THRESHOLD}");
+
+        // the same recording, at a different sample rate and bit depth
+        let resampled = fingerprint_file("data/s16_mono_22_5kHz.flac");
+        let rate = bit_error_rate(&mp3, &resampled).unwrap();
+        assert!(rate < MATCH_THRESHOLD, "{rate} >= {MATCH_THRESHOLD}");
+    }
+
+    #[test]
+    fn test_different_audio_does_not_match() {
+        let a = fingerprint_file("data/s32_stereo_44_1_kHz.flac");
+        let b = fingerprint_file("data/piano.flac");
+        let c = fingerprint_file("data/white_noise.mp3");
+        assert!(!is_match(&a, &b));
+        assert!(!is_match(&a, &c));
+        assert!(!is_match(&b, &c));
+    }
+}
@@ -11,29 +11,90 @@ pub mod chroma;
 pub mod clustering;
 pub mod decoder;
 pub mod errors;
+pub mod fingerprint;
+pub mod metrics;
 pub mod misc;
 pub mod temporal;
 pub mod timbral;
 pub mod utils;
 
-use std::{ops::Index, path::PathBuf};
+use std::{
+    num::NonZeroUsize,
+    ops::Index,
+    path::PathBuf,
+    sync::mpsc,
+    thread::{self, Scope, ScopedJoinHandle},
+    time::Duration,
+};
 
 use misc::LoudnessDesc;
+use ndarray::Array2;
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 use strum::{EnumCount, EnumIter, IntoEnumIterator};
 
-use chroma::ChromaDesc;
+use chroma::{ChromaDesc, KeyDesc};
 use errors::{AnalysisError, AnalysisResult};
+use metrics::DistanceMetric;
 use temporal::BPMDesc;
-use timbral::{SpectralDesc, ZeroCrossingRateDesc};
+use timbral::{MfccDesc, PercussionDesc, SpectralDesc, ZeroCrossingRateDesc};
 
 /// The resampled audio data used for analysis.
 ///
-/// Must be in mono (1 channel), with a sample rate of 22050 Hz.
+/// Must be in mono (1 channel), with a sample rate of `sample_rate` Hz,
+/// which must be one of the [`SUPPORTED_SAMPLE_RATES`].
+///
+/// Use a [`ResampledAudioBuilder`](decoder::ResampledAudioBuilder) to decode songs at a sample rate other than [`SAMPLE_RATE`].
 #[derive(Debug)]
 pub struct ResampledAudio {
     pub path: PathBuf,
     pub samples: Vec<f32>,
+    /// The sample rate of the samples, in Hz.
+    pub sample_rate: u32,
+    /// The duration of the audio, if known.
+    ///
+    /// Used to check that the samples actually have a sample rate of `sample_rate` Hz.
+    pub duration: Option<Duration>,
+}
+
+/// How far (as a fraction of the expected sample rate) the sample rate estimated by [`ResampledAudio::validate`]
+/// can be from the expected sample rate before the audio is considered invalid.
+const SAMPLE_RATE_TOLERANCE: f64 = 0.05;
+
+impl ResampledAudio {
+    /// Check that the audio upholds the invariants documented on [`ResampledAudio`].
+    ///
+    /// If the duration of the audio is known, the sample rate is estimated from the number of samples
+    /// and must be within 5% of `sample_rate`.
+    /// This also catches audio that wasn't collapsed to mono, since interleaved channels multiply the number of samples.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnalysisError::UnsupportedSampleRate`] if `sample_rate` isn't one of the [`SUPPORTED_SAMPLE_RATES`],
+    /// or [`AnalysisError::InvalidSampleRate`] if the estimated sample rate is too far from `sample_rate`.
+    pub fn validate(&self) -> AnalysisResult<()> {
+        if !SUPPORTED_SAMPLE_RATES.contains(&self.sample_rate) {
+            return Err(AnalysisError::UnsupportedSampleRate(self.sample_rate));
+        }
+
+        let Some(duration) = self.duration.filter(|d| !d.is_zero()) else {
+            return Ok(());
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let estimate = self.samples.len() as f64 / duration.as_secs_f64();
+        let expected = f64::from(self.sample_rate);
+
+        if (estimate - expected).abs() > expected * SAMPLE_RATE_TOLERANCE {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            return Err(AnalysisError::InvalidSampleRate {
+                expected: self.sample_rate,
+                found_estimate: estimate.round() as u32,
+            });
+        }
+
+        Ok(())
+    }
 }
 
 impl TryInto<Analysis> for ResampledAudio {
@@ -44,9 +105,63 @@ impl TryInto<Analysis> for ResampledAudio {
     }
 }
 
-/// The sampling rate used for the analysis.
+/// The default sampling rate used for the analysis.
 pub const SAMPLE_RATE: u32 = 22050;
 
+/// The sampling rates that audio can be analyzed at.
+///
+/// Features are normalized the same way whatever the sample rate,
+/// so the analyses of songs analyzed at different sample rates can be compared.
+pub const SUPPORTED_SAMPLE_RATES: [u32; 3] = [SAMPLE_RATE, 44100, 48000];
+
+/// The factor by which the window and hop sizes of the descriptors are scaled for audio sampled at `sample_rate`,
+/// so that they cover (about) the same duration as at [`SAMPLE_RATE`], preserving the time / frequency resolution.
+///
+/// This is rounded to a power of two, since the FFTs need power of two sizes, and is at least 1.
+#[must_use]
+pub fn window_scale(sample_rate: u32) -> usize {
+    let ratio = f64::from(sample_rate) / f64::from(SAMPLE_RATE);
+    #[allow(clippy::cast_possible_truncation)]
+    let exponent = ratio.log2().round().max(0.) as u32;
+    2usize.pow(exponent)
+}
+
+/// Audio shorter than this is analyzed on the calling thread, one descriptor after the other,
+/// since spawning a thread per descriptor costs more than it saves for such short clips.
+pub const PARALLEL_ANALYSIS_MIN_DURATION: Duration = Duration::from_secs(30);
+
+/// A descriptor that is either running on its own scoped thread, or has already been computed.
+enum DescriptorTask<'scope, T> {
+    Spawned(ScopedJoinHandle<'scope, T>),
+    Done(T),
+}
+
+impl<'scope, T: Send + 'scope> DescriptorTask<'scope, T> {
+    /// Run `f` on a new thread of `scope` if `parallel` is true, otherwise run it right away.
+    fn spawn<'env, F>(scope: &'scope Scope<'scope, 'env>, parallel: bool, f: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'scope,
+    {
+        if parallel {
+            Self::Spawned(scope.spawn(f))
+        } else {
+            Self::Done(f())
+        }
+    }
+
+    /// Wait for the descriptor to be computed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread computing the descriptor panicked.
+    fn join(self) -> T {
+        match self {
+            Self::Spawned(handle) => handle.join().unwrap(),
+            Self::Done(value) => value,
+        }
+    }
+}
+
 #[derive(Debug, EnumIter, EnumCount)]
 /// Indexes different fields of an Analysis.
 ///
@@ -76,6 +191,54 @@ pub enum AnalysisIndex {
     Chroma8,
     Chroma9,
     Chroma10,
+    MeanMfcc1,
+    MeanMfcc2,
+    MeanMfcc3,
+    MeanMfcc4,
+    MeanMfcc5,
+    MeanMfcc6,
+    MeanMfcc7,
+    MeanMfcc8,
+    MeanMfcc9,
+    MeanMfcc10,
+    MeanMfcc11,
+    MeanMfcc12,
+    MeanMfcc13,
+    StdDeviationMfcc1,
+    StdDeviationMfcc2,
+    StdDeviationMfcc3,
+    StdDeviationMfcc4,
+    StdDeviationMfcc5,
+    StdDeviationMfcc6,
+    StdDeviationMfcc7,
+    StdDeviationMfcc8,
+    StdDeviationMfcc9,
+    StdDeviationMfcc10,
+    StdDeviationMfcc11,
+    StdDeviationMfcc12,
+    StdDeviationMfcc13,
+    MeanSpectralBandwidth,
+    StdDeviationSpectralBandwidth,
+    MeanSpectralContrast1,
+    MeanSpectralContrast2,
+    MeanSpectralContrast3,
+    MeanSpectralContrast4,
+    MeanSpectralContrast5,
+    MeanSpectralContrast6,
+    MeanSpectralContrast7,
+    StdDeviationSpectralContrast1,
+    StdDeviationSpectralContrast2,
+    StdDeviationSpectralContrast3,
+    StdDeviationSpectralContrast4,
+    StdDeviationSpectralContrast5,
+    StdDeviationSpectralContrast6,
+    StdDeviationSpectralContrast7,
+    OnsetDensity,
+    PercussiveEnergyRatio,
+    DetectedKeySin,
+    DetectedKeyCos,
+    DetectedMode,
+    IntegratedLufs,
 }
 
 /// The Type of individual features
@@ -83,7 +246,7 @@ pub type Feature = f64;
 /// The number of features used in `Analysis`
 pub const NUMBER_FEATURES: usize = AnalysisIndex::COUNT;
 
-#[derive(Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 /// Object holding the results of the song's analysis.
 ///
 /// Only use it if you want to have an in-depth look of what is
@@ -99,9 +262,54 @@ pub const NUMBER_FEATURES: usize = AnalysisIndex::COUNT;
 /// on most of the features, except the chroma ones, which are documented
 /// directly in this code.
 pub struct Analysis {
+    #[serde(with = "serde_features")]
     pub(crate) internal_analysis: [Feature; NUMBER_FEATURES],
 }
 
+/// (De)serialize an array of [`NUMBER_FEATURES`] features as a sequence,
+/// for use with `#[serde(with = "mecomp_analysis::serde_features")]`.
+///
+/// serde can't derive this for arrays of more than 32 elements.
+/// Deserializing fails if the sequence doesn't have exactly [`NUMBER_FEATURES`] features,
+/// so features extracted by an older version of the analysis produce an error instead of being misread.
+pub mod serde_features {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    use super::{Feature, NUMBER_FEATURES};
+
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails to serialize the sequence.
+    pub fn serialize<S: Serializer>(
+        features: &[Feature; NUMBER_FEATURES],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(features)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the input isn't a sequence of exactly [`NUMBER_FEATURES`] features.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[Feature; NUMBER_FEATURES], D::Error> {
+        let features = Vec::<Feature>::deserialize(deserializer)?;
+        let len = features.len();
+        features.try_into().map_err(|_| {
+            D::Error::invalid_length(len, &format!("{NUMBER_FEATURES} features").as_str())
+        })
+    }
+}
+
+// `Default` can't be derived for arrays of more than 32 elements
+impl Default for Analysis {
+    fn default() -> Self {
+        Self {
+            internal_analysis: [0.; NUMBER_FEATURES],
+        }
+    }
+}
+
 impl Index<AnalysisIndex> for Analysis {
     type Output = Feature;
 
@@ -172,41 +380,146 @@ impl Analysis {
         self.internal_analysis.to_vec()
     }
 
+    /// Compute the distance between this analysis and `other`, using the given metric.
+    ///
+    /// See the [`metrics`] module for the built-in metrics.
+    #[must_use]
+    pub fn distance(&self, other: &Self, metric: &dyn DistanceMetric) -> f64 {
+        metric.distance(&self.internal_analysis, &other.internal_analysis)
+    }
+
+    /// Compute the distance between every pair of `analyses`, using the given metric.
+    ///
+    /// Returns an `n`x`n` matrix, where `n` is the number of analyses, whose element `[i, j]` is the distance
+    /// between `analyses[i]` and `analyses[j]`.
+    /// The matrix is symmetric with a zero diagonal, so only the distances above the diagonal are actually computed,
+    /// with the rows computed in parallel.
+    #[must_use]
+    pub fn similarity_matrix(analyses: &[Self], metric: &dyn DistanceMetric) -> Array2<f64> {
+        let n = analyses.len();
+        let upper_triangle = (0..n)
+            .into_par_iter()
+            .map(|i| {
+                analyses[i + 1..]
+                    .iter()
+                    .map(|other| analyses[i].distance(other, metric))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut matrix = Array2::zeros((n, n));
+        for (i, row) in upper_triangle.into_iter().enumerate() {
+            for (j, distance) in row.into_iter().enumerate().map(|(j, d)| (i + 1 + j, d)) {
+                matrix[[i, j]] = distance;
+                matrix[[j, i]] = distance;
+            }
+        }
+        matrix
+    }
+
+    /// Compute the mean and the (population) standard deviation of every feature over `analyses`.
+    ///
+    /// Use them with [`Analysis::normalize`] to put all the features on the same scale.
+    /// If `analyses` is empty, both the means and the standard deviations are all zeros.
+    #[must_use]
+    pub fn compute_population_stats(
+        analyses: &[Self],
+    ) -> ([Feature; NUMBER_FEATURES], [Feature; NUMBER_FEATURES]) {
+        let mut mean = [0.; NUMBER_FEATURES];
+        let mut std = [0.; NUMBER_FEATURES];
+        if analyses.is_empty() {
+            return (mean, std);
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let count = analyses.len() as Feature;
+
+        for analysis in analyses {
+            for (mean, feature) in mean.iter_mut().zip(analysis.internal_analysis) {
+                *mean += feature / count;
+            }
+        }
+        for analysis in analyses {
+            for ((std, mean), feature) in std.iter_mut().zip(mean).zip(analysis.internal_analysis) {
+                *std += (feature - mean).powi(2) / count;
+            }
+        }
+        for std in &mut std {
+            *std = std.sqrt();
+        }
+
+        (mean, std)
+    }
+
+    /// Standardize the features of this analysis, i.e. compute their z-scores,
+    /// given the `mean` and standard deviation `std` of every feature over a population of analyses
+    /// (see [`Analysis::compute_population_stats`]).
+    ///
+    /// This way, features with a wide range (like the tempo) don't dominate the distance between analyses.
+    /// Features whose standard deviation is zero (or nearly) are the same for the whole population, so they are set to zero.
+    #[must_use]
+    pub fn normalize(
+        &self,
+        mean: &[Feature; NUMBER_FEATURES],
+        std: &[Feature; NUMBER_FEATURES],
+    ) -> Self {
+        let mut features = self.internal_analysis;
+        for ((feature, mean), std) in features.iter_mut().zip(mean).zip(std) {
+            *feature = if *std < f64::EPSILON {
+                0.
+            } else {
+                (*feature - mean) / std
+            };
+        }
+        Self::new(features)
+    }
+
     /// Create an `Analysis` object from a `ResampledAudio`.
     /// This is the main function you should use to create an `Analysis` object.
     /// It will compute all the features from the audio samples.
     /// You can get a `ResampledAudio` object by using a `Decoder` to decode an audio file.
     ///
+    /// The descriptors are run at `audio.sample_rate`, with their window and hop sizes scaled by [`window_scale`].
+    /// Each descriptor is computed on its own thread, unless the audio is shorter than [`PARALLEL_ANALYSIS_MIN_DURATION`].
+    ///
     /// # Errors
     ///
-    /// This function will return an error if the samples are empty or too short.
-    /// Or if there is an error during the analysis.
+    /// This function will return an error if the samples are empty or too short,
+    /// if the samples don't have a valid sample rate (see [`ResampledAudio::validate`]),
+    /// or if there is an error during the analysis.
     ///
     /// # Panics
     ///
     /// This function will panic it cannot join the threads.
     pub fn from_samples(audio: &ResampledAudio) -> AnalysisResult<Self> {
+        audio.validate()?;
+        let sample_rate = audio.sample_rate;
+        let scale = window_scale(sample_rate);
         let largest_window = vec![
             BPMDesc::WINDOW_SIZE,
             ChromaDesc::WINDOW_SIZE,
             SpectralDesc::WINDOW_SIZE,
             LoudnessDesc::WINDOW_SIZE,
+            MfccDesc::WINDOW_SIZE,
+            PercussionDesc::WINDOW_SIZE,
         ]
         .into_iter()
         .max()
-        .unwrap();
+        .unwrap()
+            * scale;
         if audio.samples.len() < largest_window {
             return Err(AnalysisError::EmptySamples);
         }
+        let parallel = audio.samples.len() as u64
+            >= u64::from(sample_rate) * PARALLEL_ANALYSIS_MIN_DURATION.as_secs();
 
-        std::thread::scope(|s| -> AnalysisResult<Self> {
-            let child_tempo: std::thread::ScopedJoinHandle<AnalysisResult<Feature>> =
-                s.spawn(|| {
-                    let mut tempo_desc = BPMDesc::new(SAMPLE_RATE)?;
+        thread::scope(|s| -> AnalysisResult<Self> {
+            let child_tempo: DescriptorTask<AnalysisResult<Feature>> =
+                DescriptorTask::spawn(s, parallel, || {
+                    let mut tempo_desc = BPMDesc::new(sample_rate)?;
                     let windows = audio
                         .samples
-                        .windows(BPMDesc::WINDOW_SIZE)
-                        .step_by(BPMDesc::HOP_SIZE);
+                        .windows(tempo_desc.window_size())
+                        .step_by(tempo_desc.hop_size());
 
                     for window in windows {
                         tempo_desc.do_(window)?;
@@ -214,54 +527,97 @@ impl Analysis {
                     Ok(tempo_desc.get_value())
                 });
 
-            let child_chroma: std::thread::ScopedJoinHandle<AnalysisResult<Vec<Feature>>> = s
-                .spawn(|| {
-                    let mut chroma_desc = ChromaDesc::new(SAMPLE_RATE, 12);
+            let child_chroma: DescriptorTask<AnalysisResult<(Vec<Feature>, Vec<Feature>)>> =
+                DescriptorTask::spawn(s, parallel, || {
+                    let mut chroma_desc = ChromaDesc::new(sample_rate, 12);
                     chroma_desc.do_(&audio.samples)?;
-                    Ok(chroma_desc.get_value())
+                    let key_desc = KeyDesc::new(&chroma_desc.get_pitch_class_profile())?;
+                    Ok((chroma_desc.get_value(), key_desc.get_value()))
                 });
 
             #[allow(clippy::type_complexity)]
-            let child_timbral: std::thread::ScopedJoinHandle<
-                AnalysisResult<(Vec<Feature>, Vec<Feature>, Vec<Feature>)>,
-            > = s.spawn(|| {
-                let mut spectral_desc = SpectralDesc::new(SAMPLE_RATE)?;
+            let child_timbral: DescriptorTask<
+                AnalysisResult<(
+                    Vec<Feature>,
+                    Vec<Feature>,
+                    Vec<Feature>,
+                    Vec<Feature>,
+                    Vec<Feature>,
+                )>,
+            > = DescriptorTask::spawn(s, parallel, || {
+                let mut spectral_desc = SpectralDesc::new(sample_rate)?;
                 let windows = audio
                     .samples
-                    .windows(SpectralDesc::WINDOW_SIZE)
-                    .step_by(SpectralDesc::HOP_SIZE);
+                    .windows(spectral_desc.window_size())
+                    .step_by(spectral_desc.hop_size());
                 for window in windows {
                     spectral_desc.do_(window)?;
                 }
                 let centroid = spectral_desc.get_centroid();
                 let rolloff = spectral_desc.get_rolloff();
                 let flatness = spectral_desc.get_flatness();
-                Ok((centroid, rolloff, flatness))
+                let bandwidth = spectral_desc.get_bandwidth();
+                let contrast = spectral_desc.get_contrast();
+                Ok((centroid, rolloff, flatness, bandwidth, contrast))
             });
 
-            let child_zcr: std::thread::ScopedJoinHandle<AnalysisResult<Feature>> = s.spawn(|| {
-                let mut zcr_desc = ZeroCrossingRateDesc::default();
-                zcr_desc.do_(&audio.samples);
-                Ok(zcr_desc.get_value())
-            });
+            let child_mfcc: DescriptorTask<AnalysisResult<Vec<Feature>>> =
+                DescriptorTask::spawn(s, parallel, || {
+                    let mut mfcc_desc = MfccDesc::new(sample_rate)?;
+                    let windows = audio
+                        .samples
+                        .windows(mfcc_desc.window_size())
+                        .step_by(mfcc_desc.hop_size());
+
+                    for window in windows {
+                        mfcc_desc.do_(window)?;
+                    }
+                    Ok(mfcc_desc.get_value())
+                });
+
+            let child_percussion: DescriptorTask<AnalysisResult<Vec<Feature>>> =
+                DescriptorTask::spawn(s, parallel, || {
+                    let mut percussion_desc = PercussionDesc::new(sample_rate)?;
+                    let windows = audio
+                        .samples
+                        .windows(percussion_desc.window_size())
+                        .step_by(percussion_desc.hop_size());
+
+                    for window in windows {
+                        percussion_desc.do_(window)?;
+                    }
+                    Ok(percussion_desc.get_value())
+                });
+
+            let child_zcr: DescriptorTask<AnalysisResult<Feature>> =
+                DescriptorTask::spawn(s, parallel, || {
+                    let mut zcr_desc = ZeroCrossingRateDesc::default();
+                    zcr_desc.do_(&audio.samples);
+                    Ok(zcr_desc.get_value())
+                });
 
-            let child_loudness: std::thread::ScopedJoinHandle<AnalysisResult<Vec<Feature>>> = s
-                .spawn(|| {
-                    let mut loudness_desc = LoudnessDesc::default();
-                    let windows = audio.samples.chunks(LoudnessDesc::WINDOW_SIZE);
+            let child_loudness: DescriptorTask<AnalysisResult<(Vec<Feature>, Feature)>> =
+                DescriptorTask::spawn(s, parallel, || {
+                    let mut loudness_desc = LoudnessDesc::new(sample_rate);
+                    let windows = audio.samples.chunks(LoudnessDesc::WINDOW_SIZE * scale);
 
                     for window in windows {
                         loudness_desc.do_(window);
                     }
-                    Ok(loudness_desc.get_value())
+                    Ok((
+                        loudness_desc.get_value(),
+                        loudness_desc.get_integrated_lufs_value(),
+                    ))
                 });
 
             // Non-streaming approach for that one
-            let tempo = child_tempo.join().unwrap()?;
-            let chroma = child_chroma.join().unwrap()?;
-            let (centroid, rolloff, flatness) = child_timbral.join().unwrap()?;
-            let loudness = child_loudness.join().unwrap()?;
-            let zcr = child_zcr.join().unwrap()?;
+            let tempo = child_tempo.join()?;
+            let (chroma, key) = child_chroma.join()?;
+            let (centroid, rolloff, flatness, bandwidth, contrast) = child_timbral.join()?;
+            let (loudness, integrated_lufs) = child_loudness.join()?;
+            let zcr = child_zcr.join()?;
+            let mfcc = child_mfcc.join()?;
+            let percussion = child_percussion.join()?;
 
             let mut result = vec![tempo, zcr];
             result.extend_from_slice(&centroid);
@@ -269,10 +625,363 @@ impl Analysis {
             result.extend_from_slice(&flatness);
             result.extend_from_slice(&loudness);
             result.extend_from_slice(&chroma);
+            result.extend_from_slice(&mfcc);
+            result.extend_from_slice(&bandwidth);
+            result.extend_from_slice(&contrast);
+            result.extend_from_slice(&percussion);
+            result.extend_from_slice(&key);
+            result.push(integrated_lufs);
             let array: [Feature; NUMBER_FEATURES] = result
                 .try_into()
                 .map_err(|_| AnalysisError::InvalidFeaturesLen)?;
             Ok(Self::new(array))
         })
     }
+
+    /// Analyze every `ResampledAudio` in `audios`, several at a time.
+    ///
+    /// This is the same as calling [`Analysis::from_samples`] on every element of `audios`,
+    /// but the songs are analyzed concurrently using as many threads as your system has cores.
+    /// Use [`Analysis::from_samples_batch_with_cores`] to limit the number of threads.
+    ///
+    /// The results are in the same order as `audios`.
+    #[must_use]
+    pub fn from_samples_batch(audios: &[ResampledAudio]) -> Vec<AnalysisResult<Self>> {
+        let cores = thread::available_parallelism().unwrap_or(NonZeroUsize::new(1).unwrap());
+        Self::from_samples_batch_with_cores(audios, cores)
+    }
+
+    /// Analyze every `ResampledAudio` in `audios`, several at a time.
+    /// `number_cores` sets the number of songs that are analyzed at once, capped by your system's capacity.
+    ///
+    /// Note that songs longer than [`PARALLEL_ANALYSIS_MIN_DURATION`] still compute their descriptors on separate threads,
+    /// so the total number of threads can briefly exceed `number_cores`.
+    ///
+    /// The results are in the same order as `audios`.
+    #[must_use]
+    pub fn from_samples_batch_with_cores(
+        audios: &[ResampledAudio],
+        number_cores: NonZeroUsize,
+    ) -> Vec<AnalysisResult<Self>> {
+        let mut cores = thread::available_parallelism().unwrap_or(NonZeroUsize::new(1).unwrap());
+        if cores > number_cores {
+            cores = number_cores;
+        }
+
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(cores.get())
+            .build()
+        {
+            Ok(pool) => pool.install(|| audios.par_iter().map(Self::from_samples).collect()),
+            Err(e) => {
+                log::warn!(
+                    "Failed to build the analysis thread pool, analyzing songs one at a time: {e}"
+                );
+                audios.iter().map(Self::from_samples).collect()
+            }
+        }
+    }
+
+    /// Create an `Analysis` object from a `ResampledAudio`, giving up if it takes longer than `timeout`.
+    ///
+    /// This is the same as [`Analysis::from_samples`], but the analysis is run on a separate thread
+    /// so that malformed or very long audio files can't block the caller indefinitely.
+    ///
+    /// Note that the analysis thread can't be killed, so on timeout it is detached and left to finish in the background,
+    /// its result is discarded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the samples are empty or too short,
+    /// if there is an error during the analysis,
+    /// or [`AnalysisError::Timeout`] if the analysis didn't finish within `timeout`.
+    pub fn from_samples_with_timeout(
+        audio: &ResampledAudio,
+        timeout: Duration,
+    ) -> AnalysisResult<Self> {
+        // the analysis thread may outlive this call, so it needs its own copy of the audio
+        let audio = ResampledAudio {
+            path: audio.path.clone(),
+            samples: audio.samples.clone(),
+            sample_rate: audio.sample_rate,
+            duration: audio.duration,
+        };
+        let (tx, rx) = mpsc::sync_channel(1);
+
+        std::thread::Builder::new()
+            .name(format!("Analysis of {}", audio.path.display()))
+            .spawn(move || {
+                // the receiver is dropped if we timed out, in which case nobody cares about the result
+                let _ = tx.send(Self::from_samples(&audio));
+            })
+            .map_err(|e| AnalysisError::AnalysisError(e.to_string()))?;
+
+        match rx.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(AnalysisError::Timeout),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(AnalysisError::AnalysisError(
+                String::from("analysis thread panicked"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::{Decoder as _, MecompDecoder};
+
+    use rstest::rstest;
+    use std::path::Path;
+
+    #[test]
+    fn test_from_samples_with_timeout() {
+        let audio = MecompDecoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+
+        let expected = Analysis::from_samples(&audio).unwrap();
+        let actual = Analysis::from_samples_with_timeout(&audio, Duration::from_secs(60)).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_from_samples_with_timeout_times_out() {
+        let audio = MecompDecoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+
+        let result = Analysis::from_samples_with_timeout(&audio, Duration::ZERO);
+        assert!(matches!(result, Err(AnalysisError::Timeout)));
+    }
+
+    #[test]
+    fn test_from_samples_with_timeout_empty() {
+        let audio = ResampledAudio {
+            path: PathBuf::from("empty"),
+            samples: vec![],
+            sample_rate: SAMPLE_RATE,
+            duration: None,
+        };
+
+        let result = Analysis::from_samples_with_timeout(&audio, Duration::from_secs(60));
+        assert!(matches!(result, Err(AnalysisError::EmptySamples)));
+    }
+
+    #[test]
+    fn test_from_samples_batch() {
+        let decode = || MecompDecoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+        let expected = Analysis::from_samples(&decode()).unwrap();
+        let audios = [
+            decode(),
+            ResampledAudio {
+                path: PathBuf::from("empty"),
+                samples: vec![],
+                sample_rate: SAMPLE_RATE,
+                duration: None,
+            },
+            decode(),
+        ];
+
+        for results in [
+            Analysis::from_samples_batch(&audios),
+            Analysis::from_samples_batch_with_cores(&audios, NonZeroUsize::new(1).unwrap()),
+        ] {
+            assert_eq!(results.len(), audios.len());
+            assert_eq!(results[0].as_ref().unwrap(), &expected);
+            assert!(matches!(results[1], Err(AnalysisError::EmptySamples)));
+            assert_eq!(results[2].as_ref().unwrap(), &expected);
+        }
+    }
+
+    #[test]
+    fn test_from_samples_batch_empty() {
+        assert!(Analysis::from_samples_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_from_samples_long_audio() {
+        let audio = MecompDecoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+        let min_len = SAMPLE_RATE as usize * PARALLEL_ANALYSIS_MIN_DURATION.as_secs() as usize;
+        let samples = audio
+            .samples
+            .iter()
+            .copied()
+            .cycle()
+            .take(min_len + 1)
+            .collect::<Vec<_>>();
+        let audio = ResampledAudio {
+            path: audio.path,
+            duration: None,
+            sample_rate: SAMPLE_RATE,
+            samples,
+        };
+
+        // long enough for the descriptors to be computed on separate threads
+        assert!(Analysis::from_samples(&audio).is_ok());
+    }
+
+    #[test]
+    fn test_distance() {
+        let a = Analysis::new([0.; NUMBER_FEATURES]);
+        let mut features = [0.; NUMBER_FEATURES];
+        features[AnalysisIndex::Tempo as usize] = 3.;
+        features[AnalysisIndex::Zcr as usize] = 4.;
+        let b = Analysis::new(features);
+
+        assert!((a.distance(&b, &metrics::EuclideanDistance) - 5.).abs() < f64::EPSILON);
+        assert!((a.distance(&b, &metrics::ManhattanDistance) - 7.).abs() < f64::EPSILON);
+        assert!(b.distance(&b, &metrics::CosineDistance).abs() < 0.000_000_1);
+
+        let mut weighted = metrics::WeightedEuclideanDistance::uniform();
+        weighted.weights[AnalysisIndex::Tempo as usize] = 0.;
+        assert!((a.distance(&b, &weighted) - 4.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_similarity_matrix() {
+        let analyses = [0., 3., 7.].map(|tempo| {
+            let mut features = [0.; NUMBER_FEATURES];
+            features[AnalysisIndex::Tempo as usize] = tempo;
+            Analysis::new(features)
+        });
+
+        let matrix = Analysis::similarity_matrix(&analyses, &metrics::EuclideanDistance);
+        assert_eq!(
+            matrix,
+            ndarray::arr2(&[[0., 3., 7.], [3., 0., 4.], [7., 4., 0.]])
+        );
+
+        assert_eq!(
+            Analysis::similarity_matrix(&[], &metrics::EuclideanDistance).shape(),
+            &[0, 0]
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let analyses = [1., 2., 3.].map(|tempo| {
+            let mut features = [0.5; NUMBER_FEATURES];
+            features[AnalysisIndex::Tempo as usize] = tempo;
+            Analysis::new(features)
+        });
+
+        let (mean, std) = Analysis::compute_population_stats(&analyses);
+        assert!((mean[AnalysisIndex::Tempo as usize] - 2.).abs() < f64::EPSILON);
+        assert!((std[AnalysisIndex::Tempo as usize] - (2f64 / 3.).sqrt()).abs() < f64::EPSILON);
+        assert!((mean[AnalysisIndex::Zcr as usize] - 0.5).abs() < f64::EPSILON);
+        assert!(std[AnalysisIndex::Zcr as usize].abs() < f64::EPSILON);
+
+        let normalized = analyses.map(|analysis| analysis.normalize(&mean, &std));
+        let (mean, std) = Analysis::compute_population_stats(&normalized);
+        assert!(mean.iter().all(|mean| mean.abs() < 0.000_000_1), "{mean:?}");
+        assert!((std[AnalysisIndex::Tempo as usize] - 1.).abs() < 0.000_000_1);
+        // constant features can't be scaled, they're zeroed out
+        assert!(normalized
+            .iter()
+            .all(|analysis| analysis[AnalysisIndex::Zcr].abs() < f64::EPSILON));
+    }
+
+    #[test]
+    fn test_compute_population_stats_empty() {
+        assert_eq!(
+            Analysis::compute_population_stats(&[]),
+            ([0.; NUMBER_FEATURES], [0.; NUMBER_FEATURES])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_features() {
+        use serde::de::value::{Error, SeqDeserializer};
+
+        let features = SeqDeserializer::<_, Error>::new(vec![0.5; NUMBER_FEATURES].into_iter());
+        assert_eq!(
+            serde_features::deserialize(features).unwrap(),
+            [0.5; NUMBER_FEATURES]
+        );
+
+        // features from before the mfcc were added
+        let features = SeqDeserializer::<_, Error>::new(vec![0.5; 20].into_iter());
+        assert!(serde_features::deserialize(features).is_err());
+    }
+
+    #[rstest]
+    #[case::exact(SAMPLE_RATE as usize * 10, Some(Duration::from_secs(10)))]
+    #[case::within_tolerance(SAMPLE_RATE as usize * 104 / 10, Some(Duration::from_secs(10)))]
+    #[case::unknown_duration(44100 * 10, None)]
+    #[case::zero_duration(SAMPLE_RATE as usize, Some(Duration::ZERO))]
+    fn test_validate_ok(#[case] len: usize, #[case] duration: Option<Duration>) {
+        let audio = ResampledAudio {
+            path: PathBuf::from("test"),
+            samples: vec![0.; len],
+            sample_rate: SAMPLE_RATE,
+            duration,
+        };
+        assert!(audio.validate().is_ok());
+    }
+
+    #[rstest]
+    #[case::not_resampled(44100 * 10, 44100)]
+    #[case::too_few_samples(11025 * 10, 11025)]
+    #[case::just_outside_tolerance(SAMPLE_RATE as usize * 106 / 10, 23373)]
+    fn test_validate_invalid_sample_rate(#[case] len: usize, #[case] estimate: u32) {
+        let audio = ResampledAudio {
+            path: PathBuf::from("test"),
+            samples: vec![0.; len],
+            sample_rate: SAMPLE_RATE,
+            duration: Some(Duration::from_secs(10)),
+        };
+        assert!(matches!(
+            audio.validate(),
+            Err(AnalysisError::InvalidSampleRate {
+                expected: SAMPLE_RATE,
+                found_estimate
+            }) if found_estimate == estimate
+        ));
+        assert!(matches!(
+            Analysis::from_samples(&audio),
+            Err(AnalysisError::InvalidSampleRate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_unsupported_sample_rate() {
+        let audio = ResampledAudio {
+            path: PathBuf::from("test"),
+            samples: vec![0.; 16000 * 10],
+            sample_rate: 16000,
+            duration: Some(Duration::from_secs(10)),
+        };
+        assert!(matches!(
+            audio.validate(),
+            Err(AnalysisError::UnsupportedSampleRate(16000))
+        ));
+    }
+
+    #[rstest]
+    #[case::default(SAMPLE_RATE, 1)]
+    #[case::cd(44100, 2)]
+    #[case::dvd(48000, 2)]
+    #[case::high_resolution(96000, 4)]
+    #[case::lower(8000, 1)]
+    fn test_window_scale(#[case] sample_rate: u32, #[case] expected: usize) {
+        assert_eq!(window_scale(sample_rate), expected);
+    }
+
+    #[test]
+    fn test_from_samples_higher_sample_rate() {
+        let audio = decoder::ResampledAudioBuilder::new()
+            .sample_rate(44100)
+            .unwrap()
+            .decode::<MecompDecoder>(Path::new("data/s16_mono_22_5kHz.flac"))
+            .unwrap();
+        assert_eq!(audio.sample_rate, 44100);
+
+        let analysis = Analysis::from_samples(&audio).unwrap();
+        let expected = Analysis::from_samples(
+            &MecompDecoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap(),
+        )
+        .unwrap();
+        // the windows cover the same durations, so the tempo should be about the same
+        assert!(
+            (analysis[AnalysisIndex::Tempo] - expected[AnalysisIndex::Tempo]).abs() < 0.05,
+            "{analysis:?} !~= {expected:?}"
+        );
+    }
 }
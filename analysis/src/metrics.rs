@@ -0,0 +1,152 @@
+//! Distance metrics between the features of two analyses.
+//!
+//! Use them with [`Analysis::distance`](crate::Analysis::distance), or directly on feature slices.
+
+use crate::{Feature, NUMBER_FEATURES};
+
+/// A way to measure how different two sets of features are.
+///
+/// Implementations can assume that `a` and `b` have the same length,
+/// and should return `0.` for identical features and larger values for features that are further apart.
+///
+/// Metrics must be `Sync`, so that distances can be computed in parallel
+/// (see [`Analysis::similarity_matrix`](crate::Analysis::similarity_matrix)).
+pub trait DistanceMetric: Sync {
+    fn distance(&self, a: &[Feature], b: &[Feature]) -> f64;
+}
+
+/// The [Euclidean distance](https://en.wikipedia.org/wiki/Euclidean_distance) between two sets of features.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EuclideanDistance;
+
+impl DistanceMetric for EuclideanDistance {
+    fn distance(&self, a: &[Feature], b: &[Feature]) -> f64 {
+        debug_assert_eq!(a.len(), b.len());
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// The [cosine distance](https://en.wikipedia.org/wiki/Cosine_similarity#Cosine_distance) between two sets of features,
+/// that is, `1 - cosine similarity`.
+///
+/// Ranges from 0 (same direction) to 2 (opposite directions).
+/// The cosine similarity isn't defined if either set of features is all zeros, in which case the distance is 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CosineDistance;
+
+impl DistanceMetric for CosineDistance {
+    fn distance(&self, a: &[Feature], b: &[Feature]) -> f64 {
+        debug_assert_eq!(a.len(), b.len());
+        let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+        let norm_a = a.iter().map(|a| a.powi(2)).sum::<f64>().sqrt();
+        let norm_b = b.iter().map(|b| b.powi(2)).sum::<f64>().sqrt();
+
+        if norm_a == 0. || norm_b == 0. {
+            return 1.;
+        }
+        1. - dot / (norm_a * norm_b)
+    }
+}
+
+/// The [Manhattan distance](https://en.wikipedia.org/wiki/Taxicab_geometry) between two sets of features,
+/// that is, the sum of the absolute differences between the features.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ManhattanDistance;
+
+impl DistanceMetric for ManhattanDistance {
+    fn distance(&self, a: &[Feature], b: &[Feature]) -> f64 {
+        debug_assert_eq!(a.len(), b.len());
+        a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+    }
+}
+
+/// The Euclidean distance between two sets of features, with the squared difference of every feature scaled by a weight.
+///
+/// Useful to make some features matter more (or less) than others,
+/// for example, down-weighting the tempo (see [`AnalysisIndex::Tempo`](crate::AnalysisIndex::Tempo))
+/// when comparing songs from the same album.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedEuclideanDistance {
+    pub weights: [Feature; NUMBER_FEATURES],
+}
+
+impl WeightedEuclideanDistance {
+    /// Create a metric that weights every feature by `1.`, equivalent to [`EuclideanDistance`].
+    #[must_use]
+    pub const fn uniform() -> Self {
+        Self {
+            weights: [1.; NUMBER_FEATURES],
+        }
+    }
+}
+
+impl Default for WeightedEuclideanDistance {
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
+
+impl DistanceMetric for WeightedEuclideanDistance {
+    fn distance(&self, a: &[Feature], b: &[Feature]) -> f64 {
+        debug_assert_eq!(a.len(), b.len());
+        a.iter()
+            .zip(b)
+            .zip(self.weights)
+            .map(|((a, b), weight)| weight * (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnalysisIndex;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::euclidean(&EuclideanDistance, [3., 4.], [0., 0.], 5.)]
+    #[case::cosine(&CosineDistance, [0., 3.], [3., 7.], 1. - 21. / (3. * 58f64.sqrt()))]
+    #[case::manhattan(&ManhattanDistance, [3., 4.], [0., 0.], 7.)]
+    #[case::weighted_uniform(&WeightedEuclideanDistance::uniform(), [3., 4.], [0., 0.], 5.)]
+    fn test_distance(
+        #[case] metric: &dyn DistanceMetric,
+        #[case] a: [Feature; 2],
+        #[case] b: [Feature; 2],
+        #[case] expected: f64,
+    ) {
+        let actual = metric.distance(&a, &b);
+        assert!(
+            0.000_000_1 > (expected - actual).abs(),
+            "{expected} !~= {actual}"
+        );
+        assert!(0.000_000_1 > (actual - metric.distance(&b, &a)).abs());
+        assert!(0.000_000_1 > metric.distance(&a, &a).abs());
+    }
+
+    #[test]
+    fn test_cosine_distance_zero() {
+        assert!((CosineDistance.distance(&[0., 0.], &[1., 2.]) - 1.).abs() < f64::EPSILON);
+        assert!((CosineDistance.distance(&[1., 2.], &[-1., -2.]) - 2.).abs() < 0.000_000_1);
+    }
+
+    #[test]
+    fn test_weighted_euclidean_distance() {
+        let a = [0.; NUMBER_FEATURES];
+        let mut b = [0.; NUMBER_FEATURES];
+        b[AnalysisIndex::Tempo as usize] = 2.;
+        b[AnalysisIndex::Zcr as usize] = 1.;
+
+        let mut metric = WeightedEuclideanDistance::uniform();
+        assert!((metric.distance(&a, &b) - 5f64.sqrt()).abs() < 0.000_000_1);
+
+        // ignoring the tempo leaves only the zero-crossing rate
+        metric.weights[AnalysisIndex::Tempo as usize] = 0.;
+        assert!((metric.distance(&a, &b) - 1.).abs() < 0.000_000_1);
+    }
+}
@@ -6,7 +6,7 @@
 use bliss_audio_aubio_rs::level_lin;
 use ndarray::{arr1, Axis};
 
-use crate::Feature;
+use crate::{Feature, SAMPLE_RATE};
 
 use super::utils::{mean, Normalize};
 
@@ -26,18 +26,86 @@ use super::utils::{mean, Normalize};
  *
  * (This is technically the sound pressure level of the track, but loudness is
  * way more visual)
+ *
+ * It also measures the integrated loudness and loudness range of the track, as
+ * defined by EBU R128 (ITU-R BS.1770), see [`LoudnessDesc::get_integrated_lufs`]
+ * and [`LoudnessDesc::get_lra`].
  */
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct LoudnessDesc {
     pub values: Vec<f32>,
+    /// The K-weighting filter, a high shelf followed by a high pass.
+    k_weighting: [Biquad; 2],
+    /// The number of samples in 100ms of audio.
+    sub_block_size: usize,
+    /// The sum of the squared K-weighted samples of the 100ms sub-block being processed.
+    sub_block_sum: Feature,
+    /// The number of samples in the sub-block being processed.
+    sub_block_len: usize,
+    /// The mean square of the K-weighted samples of every 100ms sub-block processed so far.
+    sub_block_powers: Vec<Feature>,
+}
+
+impl Default for LoudnessDesc {
+    fn default() -> Self {
+        Self::new(SAMPLE_RATE)
+    }
 }
 
+/// The reference loudness (in LUFS) that `ReplayGain` 2.0 normalizes tracks to.
+pub const REPLAYGAIN_REFERENCE_LOUDNESS: Feature = -18.0;
+
 impl LoudnessDesc {
     pub const WINDOW_SIZE: usize = 1024;
+    /// Blocks quieter than this (in LUFS) are ignored when computing the integrated loudness.
+    const ABSOLUTE_GATE: Feature = -70.0;
+    /// Blocks more than this many LU below the ungated loudness are ignored when computing the integrated loudness.
+    const RELATIVE_GATE: Feature = -10.0;
+    /// Short-term blocks more than this many LU below the ungated loudness are ignored when computing the loudness range.
+    const LRA_RELATIVE_GATE: Feature = -20.0;
+    /// The number of 100ms sub-blocks in a momentary (400ms) block.
+    const MOMENTARY_SUB_BLOCKS: usize = 4;
+    /// The number of 100ms sub-blocks in a short-term (3s) block.
+    const SHORT_TERM_SUB_BLOCKS: usize = 30;
+
+    /// Create a loudness descriptor for audio sampled at `sample_rate`.
+    #[must_use]
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = Feature::from(sample_rate);
+        Self {
+            values: Vec::new(),
+            k_weighting: [
+                Biquad::high_shelf(sample_rate),
+                Biquad::high_pass(sample_rate),
+            ],
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            sub_block_size: (sample_rate / 10.).round() as usize,
+            sub_block_sum: 0.,
+            sub_block_len: 0,
+            sub_block_powers: Vec::new(),
+        }
+    }
 
     pub fn do_(&mut self, chunk: &[f32]) {
         let level = level_lin(chunk);
         self.values.push(level);
+
+        for &sample in chunk {
+            let weighted = self
+                .k_weighting
+                .iter_mut()
+                .fold(Feature::from(sample), |x, filter| filter.process(x));
+            self.sub_block_sum = weighted.mul_add(weighted, self.sub_block_sum);
+            self.sub_block_len += 1;
+
+            if self.sub_block_len == self.sub_block_size {
+                #[allow(clippy::cast_precision_loss)]
+                self.sub_block_powers
+                    .push(self.sub_block_sum / self.sub_block_size as Feature);
+                self.sub_block_sum = 0.;
+                self.sub_block_len = 0;
+            }
+        }
     }
 
     pub fn get_value(&mut self) -> Vec<Feature> {
@@ -54,6 +122,141 @@ impl LoudnessDesc {
             self.normalize(10.0 * std_value.log10()),
         ]
     }
+
+    /// Get the integrated loudness (in LUFS) of the processed audio,
+    /// using the two-stage gating described in EBU R128 (ITU-R BS.1770).
+    ///
+    /// Note that the blocks are not K-weighted, so this is an approximation of the true integrated loudness,
+    /// see [`LoudnessDesc::get_integrated_lufs`] for the real thing.
+    ///
+    /// Returns `None` if every block was gated out (e.g. the audio is silent).
+    #[must_use]
+    pub fn get_integrated_loudness(&self) -> Option<Feature> {
+        Self::gated_loudness(&[self])
+    }
+
+    /// The integrated loudness (see [`LoudnessDesc::get_integrated_loudness`]) of the blocks of all the given descriptors,
+    /// as if their audio was processed one after the other.
+    fn gated_loudness(descs: &[&Self]) -> Option<Feature> {
+        let gated_loudness = |threshold: Feature| {
+            mean_loudness(
+                descs
+                    .iter()
+                    .flat_map(|desc| desc.values.iter())
+                    .map(|&power| Feature::from(power))
+                    .filter(|&power| power > 0. && block_loudness(power) > threshold),
+            )
+        };
+
+        // first pass, absolute gating
+        let ungated = gated_loudness(Self::ABSOLUTE_GATE)?;
+        // second pass, relative gating
+        gated_loudness(ungated + Self::RELATIVE_GATE)
+    }
+
+    /// Get the integrated loudness (in LUFS) of the processed audio, as defined by EBU R128 (ITU-R BS.1770):
+    /// the mean power of the K-weighted, 400ms long, 75% overlapping blocks,
+    /// ignoring blocks below -70 LUFS and then blocks more than 10 LU below the mean of the remaining blocks.
+    ///
+    /// Returns `None` if less than 400ms of audio was processed, or if every block was gated out (e.g. the audio is silent).
+    #[must_use]
+    pub fn get_integrated_lufs(&self) -> Option<Feature> {
+        let blocks = self.block_powers(Self::MOMENTARY_SUB_BLOCKS);
+        let ungated = mean_loudness(
+            blocks
+                .iter()
+                .copied()
+                .filter(|&power| power > 0. && block_loudness(power) > Self::ABSOLUTE_GATE),
+        )?;
+        mean_loudness(
+            blocks.into_iter().filter(|&power| {
+                power > 0. && block_loudness(power) > ungated + Self::RELATIVE_GATE
+            }),
+        )
+    }
+
+    /// Get the loudness range (in LU) of the processed audio, as defined by EBU R128 (EBU Tech 3342):
+    /// the difference between the 95th and the 10th percentiles of the loudness of the K-weighted, 3s long, short-term blocks,
+    /// ignoring blocks below -70 LUFS and then blocks more than 20 LU below the mean of the remaining blocks.
+    ///
+    /// Returns `None` if less than 3s of audio was processed, or if every block was gated out (e.g. the audio is silent).
+    #[must_use]
+    pub fn get_lra(&self) -> Option<Feature> {
+        let blocks = self.block_powers(Self::SHORT_TERM_SUB_BLOCKS);
+        let ungated = mean_loudness(
+            blocks
+                .iter()
+                .copied()
+                .filter(|&power| power > 0. && block_loudness(power) > Self::ABSOLUTE_GATE),
+        )?;
+        let mut loudness = blocks
+            .into_iter()
+            .filter(|&power| power > 0.)
+            .map(block_loudness)
+            .filter(|&loudness| {
+                loudness > Self::ABSOLUTE_GATE && loudness > ungated + Self::LRA_RELATIVE_GATE
+            })
+            .collect::<Vec<_>>();
+        if loudness.is_empty() {
+            return None;
+        }
+        loudness.sort_by(Feature::total_cmp);
+
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss
+        )]
+        let percentile =
+            |p: Feature| loudness[((loudness.len() - 1) as Feature * p).round() as usize];
+        Some(percentile(0.95) - percentile(0.10))
+    }
+
+    /// The mean power of every block of `sub_blocks` consecutive 100ms sub-blocks, with a step of one sub-block.
+    fn block_powers(&self, sub_blocks: usize) -> Vec<Feature> {
+        #[allow(clippy::cast_precision_loss)]
+        self.sub_block_powers
+            .windows(sub_blocks)
+            .map(|window| window.iter().sum::<Feature>() / sub_blocks as Feature)
+            .collect()
+    }
+
+    /// Get the integrated loudness as a feature, normalized like the mean loudness
+    /// (so it can be converted back to LUFS with [`LoudnessDesc::denormalize_loudness`]).
+    ///
+    /// Silent audio, which has no integrated loudness, gets the lowest value.
+    #[must_use]
+    pub fn get_integrated_lufs_value(&self) -> Feature {
+        self.normalize(self.get_integrated_lufs().unwrap_or(Self::MIN_VALUE))
+    }
+
+    /// Get the `ReplayGain` 2.0 track gain (in dB), the gain needed to normalize the processed audio
+    /// to the reference loudness of -18 LUFS.
+    ///
+    /// Returns `None` if the integrated loudness couldn't be computed (e.g. the audio is silent).
+    #[must_use]
+    pub fn get_replaygain(&self) -> Option<Feature> {
+        self.get_integrated_loudness()
+            .map(|loudness| REPLAYGAIN_REFERENCE_LOUDNESS - loudness)
+    }
+
+    /// Get the `ReplayGain` 2.0 album gain (in dB) of the album whose tracks were processed by `tracks`,
+    /// the gain needed to normalize the album as a whole to the reference loudness.
+    ///
+    /// The gating is done over the blocks of every track, so (unlike an average of the track gains)
+    /// quiet passages in one track are gated relative to the loudness of the whole album.
+    ///
+    /// Returns `None` if the integrated loudness couldn't be computed (e.g. every track is silent).
+    #[must_use]
+    pub fn get_album_replaygain(tracks: &[&Self]) -> Option<Feature> {
+        Self::gated_loudness(tracks).map(|loudness| REPLAYGAIN_REFERENCE_LOUDNESS - loudness)
+    }
+
+    /// Convert a (normalized) loudness feature of an analysis back into a loudness (in dB).
+    #[must_use]
+    pub fn denormalize_loudness(feature: Feature) -> Feature {
+        (feature + 1.) / 2. * (Self::MAX_VALUE - Self::MIN_VALUE) + Self::MIN_VALUE
+    }
 }
 
 impl Normalize for LoudnessDesc {
@@ -61,12 +264,90 @@ impl Normalize for LoudnessDesc {
     const MIN_VALUE: Feature = -90.;
 }
 
+/// The loudness (in LUFS) of a block of a single channel with the given mean square.
+fn block_loudness(power: Feature) -> Feature {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// The loudness (in LUFS) of the mean power of `powers`, `None` if there are none.
+fn mean_loudness(powers: impl Iterator<Item = Feature>) -> Option<Feature> {
+    let (sum, count) = powers.fold((0., 0usize), |(sum, count), power| (sum + power, count + 1));
+    #[allow(clippy::cast_precision_loss)]
+    (count > 0).then(|| block_loudness(sum / count as Feature))
+}
+
+/// A second order IIR filter (transposed direct form II), used for K-weighting.
+///
+/// The coefficients are computed for any sample rate the same way as libebur128 does,
+/// matching the ones given in ITU-R BS.1770 at 48 kHz.
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [Feature; 3],
+    a: [Feature; 2],
+    state: [Feature; 2],
+}
+
+impl Biquad {
+    /// The first stage of the K-weighting filter, which models the acoustic effects of the head.
+    fn high_shelf(sample_rate: Feature) -> Self {
+        let f0 = 1_681.974_450_955_533;
+        let gain = 3.999_843_853_973_347;
+        let q = 0.707_175_236_955_419_6;
+
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let vh = 10f64.powf(gain / 20.);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1. + k / q + k * k;
+        Self {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2. * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2. * (k * k - 1.) / a0, (1. - k / q + k * k) / a0],
+            state: [0.; 2],
+        }
+    }
+
+    /// The second stage of the K-weighting filter, the "RLB" high pass filter.
+    fn high_pass(sample_rate: Feature) -> Self {
+        let f0 = 38.135_470_876_024_44;
+        let q = 0.500_327_037_323_877_3;
+
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let a0 = 1. + k / q + k * k;
+        Self {
+            b: [1., -2., 1.],
+            a: [2. * (k * k - 1.) / a0, (1. - k / q + k * k) / a0],
+            state: [0.; 2],
+        }
+    }
+
+    fn process(&mut self, x: Feature) -> Feature {
+        let y = self.b[0].mul_add(x, self.state[0]);
+        self.state[0] = self.b[1].mul_add(x, -self.a[0] * y) + self.state[1];
+        self.state[1] = self.b[2].mul_add(x, -self.a[1] * y);
+        y
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::decoder::{Decoder as DecoderTrait, MecompDecoder as Decoder};
     use std::path::Path;
 
+    #[test]
+    fn test_denormalize_loudness() {
+        let loudness_desc = LoudnessDesc::default();
+        assert_eq!(-90., LoudnessDesc::denormalize_loudness(-1.));
+        assert_eq!(0., LoudnessDesc::denormalize_loudness(1.));
+        assert!(
+            1e-9 > (-12.5 - LoudnessDesc::denormalize_loudness(loudness_desc.normalize(-12.5)))
+                .abs()
+        );
+    }
+
     #[test]
     fn test_loudness() {
         let song = Decoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
@@ -80,6 +361,120 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_integrated_loudness() {
+        // a full scale square wave has a power of 1, so its loudness is -0.691 LUFS
+        let mut loudness_desc = LoudnessDesc::default();
+        loudness_desc.do_(&[1.; 1024]);
+        loudness_desc.do_(&[-1.; 1024]);
+        let loudness = loudness_desc.get_integrated_loudness().unwrap();
+        assert!(0.000_1 > (loudness + 0.691).abs(), "{loudness} !~= -0.691");
+        let gain = loudness_desc.get_replaygain().unwrap();
+        assert!(0.000_1 > (gain + 17.309).abs(), "{gain} !~= -17.309");
+
+        // silent blocks are gated out
+        loudness_desc.do_(&[0.; 1024]);
+        let loudness = loudness_desc.get_integrated_loudness().unwrap();
+        assert!(0.000_1 > (loudness + 0.691).abs(), "{loudness} !~= -0.691");
+
+        // if everything is silent, there is no loudness
+        let mut loudness_desc = LoudnessDesc::default();
+        loudness_desc.do_(&[0.; 1024]);
+        assert_eq!(loudness_desc.get_integrated_loudness(), None);
+        assert_eq!(loudness_desc.get_replaygain(), None);
+    }
+
+    #[test]
+    fn test_album_replaygain() {
+        // a full scale square wave (power 1), and a half scale one (power 0.25)
+        let mut loud = LoudnessDesc::default();
+        loud.do_(&[1.; 1024]);
+        loud.do_(&[-1.; 1024]);
+        let mut quiet = LoudnessDesc::default();
+        quiet.do_(&[0.5; 1024]);
+        quiet.do_(&[-0.5; 1024]);
+        // and a silent track, which is gated out
+        let mut silent = LoudnessDesc::default();
+        silent.do_(&[0.; 1024]);
+
+        // the album's loudness is that of the mean power of all the blocks, 0.625
+        let gain = LoudnessDesc::get_album_replaygain(&[&loud, &quiet, &silent]).unwrap();
+        let expected = REPLAYGAIN_REFERENCE_LOUDNESS - block_loudness(0.625);
+        assert!(0.000_1 > (gain - expected).abs(), "{gain} !~= {expected}");
+        // which is between the gains of the tracks
+        assert!(gain > loud.get_replaygain().unwrap());
+        assert!(gain < quiet.get_replaygain().unwrap());
+
+        // an album of a single track has the same gain as the track
+        assert_eq!(
+            LoudnessDesc::get_album_replaygain(&[&loud]),
+            loud.get_replaygain()
+        );
+        assert_eq!(LoudnessDesc::get_album_replaygain(&[&silent]), None);
+        assert_eq!(LoudnessDesc::get_album_replaygain(&[]), None);
+    }
+
+    fn sine(sample_rate: u32, amplitude: f32, seconds: usize) -> Vec<f32> {
+        (0..sample_rate as usize * seconds)
+            .map(|i| {
+                #[allow(clippy::cast_precision_loss)]
+                let t = i as f32 / sample_rate as f32;
+                amplitude * (2. * std::f32::consts::PI * 997. * t).sin()
+            })
+            .collect()
+    }
+
+    #[rstest::rstest]
+    #[case::resampled(SAMPLE_RATE)]
+    #[case::cd_quality(44100)]
+    #[case::dvd_quality(48000)]
+    fn test_integrated_lufs(#[case] sample_rate: u32) {
+        // a full scale 997 Hz sine wave has a loudness of -3.01 LUFS
+        let mut loudness_desc = LoudnessDesc::new(sample_rate);
+        for chunk in sine(sample_rate, 1., 5).chunks(LoudnessDesc::WINDOW_SIZE) {
+            loudness_desc.do_(chunk);
+        }
+        let loudness = loudness_desc.get_integrated_lufs().unwrap();
+        assert!(0.05 > (loudness + 3.01).abs(), "{loudness} !~= -3.01");
+        let feature = loudness_desc.get_integrated_lufs_value();
+        assert!(1e-9 > (LoudnessDesc::denormalize_loudness(feature) - loudness).abs());
+
+        // a constant signal has no loudness range
+        let lra = loudness_desc.get_lra().unwrap();
+        assert!(0.05 > lra.abs(), "{lra} !~= 0");
+    }
+
+    #[test]
+    fn test_lra() {
+        // 10s at full scale, then 10s 20 dB quieter
+        let mut loudness_desc = LoudnessDesc::default();
+        for chunk in [sine(SAMPLE_RATE, 1., 10), sine(SAMPLE_RATE, 0.1, 10)]
+            .concat()
+            .chunks(LoudnessDesc::WINDOW_SIZE)
+        {
+            loudness_desc.do_(chunk);
+        }
+        let lra = loudness_desc.get_lra().unwrap();
+        assert!(0.5 > (lra - 20.).abs(), "{lra} !~= 20");
+    }
+
+    #[test]
+    fn test_integrated_lufs_boundaries() {
+        // too short
+        let mut loudness_desc = LoudnessDesc::default();
+        loudness_desc.do_(&sine(SAMPLE_RATE, 1., 1)[..SAMPLE_RATE as usize / 5]);
+        assert_eq!(loudness_desc.get_integrated_lufs(), None);
+        assert_eq!(loudness_desc.get_lra(), None);
+
+        // silent
+        let mut loudness_desc = LoudnessDesc::default();
+        let silence = vec![0.; SAMPLE_RATE as usize * 5];
+        loudness_desc.do_(&silence);
+        assert_eq!(loudness_desc.get_integrated_lufs(), None);
+        assert_eq!(loudness_desc.get_lra(), None);
+        assert!(1e-9 > (loudness_desc.get_integrated_lufs_value() + 1.).abs());
+    }
+
     #[test]
     fn test_loudness_boundaries() {
         let mut loudness_desc = LoudnessDesc::default();
@@ -3,7 +3,7 @@
 //! Contains functions to extract & summarize the temporal aspects
 //! of a given Song.
 
-use crate::Feature;
+use crate::{window_scale, Feature};
 
 use super::errors::{AnalysisError, AnalysisResult};
 use super::utils::Normalize;
@@ -32,31 +32,62 @@ use noisy_float::prelude::*;
  */
 pub struct BPMDesc {
     aubio_obj: Tempo,
+    sample_rate: u32,
+    window_size: usize,
+    hop_size: usize,
     bpms: Vec<f32>,
+    /// The positions (in samples) of the beats found so far.
+    beats: Vec<usize>,
 }
 
 // TODO>1.0 use the confidence value to discard this descriptor if confidence
 // is too low.
 impl BPMDesc {
+    /// The window size at [`SAMPLE_RATE`](crate::SAMPLE_RATE), see [`BPMDesc::window_size`].
     pub const WINDOW_SIZE: usize = 512;
+    /// The hop size at [`SAMPLE_RATE`](crate::SAMPLE_RATE), see [`BPMDesc::hop_size`].
     pub const HOP_SIZE: usize = Self::WINDOW_SIZE / 2;
+    /// The maximum number of tempo candidates returned by `get_candidates`.
+    pub const MAX_CANDIDATES: usize = 4;
+    /// The slowest tempo considered by `get_candidates`.
+    const MIN_CANDIDATE_BPM: f64 = 30.;
 
+    /// Unlike the other descriptors, this doesn't take a window function:
+    /// aubio's tempo detection always uses a Hann window, and doesn't let us change it.
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub fn new(sample_rate: u32) -> AnalysisResult<Self> {
+        let scale = window_scale(sample_rate);
+        let window_size = Self::WINDOW_SIZE * scale;
+        let hop_size = Self::HOP_SIZE * scale;
         Ok(Self {
-            aubio_obj: Tempo::new(
-                OnsetMode::SpecFlux,
-                Self::WINDOW_SIZE,
-                Self::HOP_SIZE,
-                sample_rate,
-            )
-            .map_err(|e| {
-                AnalysisError::AnalysisError(format!("error while loading aubio tempo object: {e}"))
-            })?,
+            aubio_obj: Tempo::new(OnsetMode::SpecFlux, window_size, hop_size, sample_rate)
+                .map_err(|e| {
+                    AnalysisError::AnalysisError(format!(
+                        "error while loading aubio tempo object: {e}"
+                    ))
+                })?,
+            sample_rate,
+            window_size,
+            hop_size,
             bpms: Vec::new(),
+            beats: Vec::new(),
         })
     }
 
+    /// The size of the windows to pass to `do_`, [`BPMDesc::WINDOW_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The number of samples between the starts of consecutive windows, [`BPMDesc::HOP_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub fn do_(&mut self, chunk: &[f32]) -> AnalysisResult<()> {
         let result = self.aubio_obj.do_result(chunk).map_err(|e| {
@@ -65,6 +96,7 @@ impl BPMDesc {
 
         if result > 0. {
             self.bpms.push(self.aubio_obj.get_bpm());
+            self.beats.push(self.aubio_obj.get_last());
         }
         Ok(())
     }
@@ -87,6 +119,103 @@ impl BPMDesc {
             .unwrap();
         self.normalize(median.into())
     }
+
+    /**
+     * Get up to `MAX_CANDIDATES` tempo hypotheses, as `(bpm, confidence)` pairs
+     * sorted from most to least confident.
+     *
+     * Songs with an ambiguous tempo (e.g. a half-time feel) have several candidates
+     * with similar confidences.
+     *
+     * The candidates are the peaks of the autocorrelation of the beats found so far,
+     * the confidence is the height of the peak normalized by the number of beats,
+     * so it ranges from 0 to 1.
+     *
+     * Unlike `get_value`, the BPM isn't normalized.
+     */
+    #[must_use]
+    pub fn get_candidates(&self) -> Vec<(Feature, f32)> {
+        let mut beats = self.beats.clone();
+        beats.dedup();
+        if beats.len() < 2 {
+            return Vec::new();
+        }
+
+        let hop_size = self.hop_size as f64;
+        let sample_rate = f64::from(self.sample_rate);
+        let lag_to_bpm = |lag: f64| 60. * sample_rate / (lag * hop_size);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let min_lag = (lag_to_bpm(Self::MAX_VALUE) as usize).max(1);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let max_lag = lag_to_bpm(Self::MIN_CANDIDATE_BPM).ceil() as usize;
+
+        // autocorrelation of the beat train, with a resolution of one hop
+        let mut autocorrelation = vec![0f32; max_lag + 2];
+        for (i, first) in beats.iter().enumerate() {
+            for second in &beats[i + 1..] {
+                let lag = (second - first + self.hop_size / 2) / self.hop_size;
+                if lag >= autocorrelation.len() {
+                    break;
+                }
+                autocorrelation[lag] += 1.;
+            }
+        }
+        // smooth it, so that beats that are off by one hop still count towards the same peak
+        let smoothed = (0..autocorrelation.len())
+            .map(|lag| {
+                autocorrelation[lag.saturating_sub(1)..=(lag + 1).min(autocorrelation.len() - 1)]
+                    .iter()
+                    .sum::<f32>()
+            })
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::cast_precision_loss)]
+        let pairs = (beats.len() - 1) as f32;
+        let mut peaks = (min_lag..=max_lag)
+            .filter(|&lag| {
+                smoothed[lag] > 0.
+                    && smoothed[lag] > smoothed[lag - 1]
+                    && smoothed[lag] >= smoothed[lag + 1]
+            })
+            .map(|lag| {
+                // refine the lag with the centroid of the peak
+                let window = &autocorrelation[lag - 1..=lag + 1];
+                #[allow(clippy::cast_precision_loss)]
+                let centroid = window
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, count)| (lag - 1 + offset) as f32 * count)
+                    .sum::<f32>()
+                    / smoothed[lag];
+                (
+                    lag_to_bpm(f64::from(centroid)),
+                    (smoothed[lag] / pairs).min(1.),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        peaks.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        peaks.truncate(Self::MAX_CANDIDATES);
+        peaks
+    }
+
+    /**
+     * Normalize a tempo (in BPM) the same way `get_value` does, so it can be
+     * compared against the tempo feature of an existing analysis.
+     */
+    #[must_use]
+    pub fn normalize_bpm(bpm: Feature) -> Feature {
+        2. * (bpm - Self::MIN_VALUE) / (Self::MAX_VALUE - Self::MIN_VALUE) - 1.
+    }
+
+    /**
+     * The inverse of [`BPMDesc::normalize_bpm`], converts the tempo feature
+     * of an analysis back into a tempo (in BPM).
+     */
+    #[must_use]
+    pub fn denormalize_bpm(feature: Feature) -> Feature {
+        (feature + 1.) / 2. * (Self::MAX_VALUE - Self::MIN_VALUE) + Self::MIN_VALUE
+    }
 }
 
 impl Normalize for BPMDesc {
@@ -117,6 +246,14 @@ mod tests {
             "{} !~= 0.378605",
             tempo_desc.get_value()
         );
+        // the most confident candidate agrees with the tempo
+        let (bpm, _) = tempo_desc.get_candidates()[0];
+        assert!(
+            0.01 > (tempo_desc.normalize(bpm) - tempo_desc.get_value()).abs(),
+            "{} !~= {}",
+            tempo_desc.normalize(bpm),
+            tempo_desc.get_value()
+        );
     }
 
     #[test]
@@ -142,6 +279,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tempo_candidates() {
+        let mut tempo_desc = BPMDesc::new(22050).unwrap();
+        assert!(tempo_desc.get_candidates().is_empty());
+
+        // one beat every second, so 60 BPM
+        let mut one_chunk = vec![0.; 22000];
+        one_chunk.append(&mut vec![1.; 100]);
+        let chunks = std::iter::repeat_n(one_chunk.iter(), 100)
+            .flatten()
+            .copied()
+            .collect::<Vec<f32>>();
+        for chunk in chunks.chunks_exact(BPMDesc::HOP_SIZE) {
+            tempo_desc.do_(chunk).unwrap();
+        }
+
+        let candidates = tempo_desc.get_candidates();
+        assert!(!candidates.is_empty());
+        assert!(candidates.len() <= BPMDesc::MAX_CANDIDATES);
+        let (bpm, confidence) = candidates[0];
+        assert!(1. > (60. - bpm).abs(), "{bpm} !~= 60");
+        assert!(confidence > 0.9, "{confidence} <= 0.9");
+        assert!(candidates.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(candidates
+            .iter()
+            .all(|(_, confidence)| (0. ..=1.).contains(confidence)));
+    }
+
+    #[test]
+    fn test_normalize_bpm() {
+        assert_eq!(-1., BPMDesc::normalize_bpm(0.));
+        assert_eq!(1., BPMDesc::normalize_bpm(206.));
+        // -0.41 is 60 BPM normalized
+        assert!(0.01 > (-0.416_853 - BPMDesc::normalize_bpm(60.)).abs());
+        let tempo_desc = BPMDesc::new(SAMPLE_RATE).unwrap();
+        assert_eq!(tempo_desc.normalize(120.), BPMDesc::normalize_bpm(120.));
+    }
+
+    #[test]
+    fn test_denormalize_bpm() {
+        assert_eq!(0., BPMDesc::denormalize_bpm(-1.));
+        assert_eq!(206., BPMDesc::denormalize_bpm(1.));
+        for bpm in [60., 120., 174.5] {
+            assert!(1e-9 > (bpm - BPMDesc::denormalize_bpm(BPMDesc::normalize_bpm(bpm))).abs());
+        }
+    }
+
     #[test]
     fn test_tempo_boundaries() {
         let mut tempo_desc = BPMDesc::new(10).unwrap();
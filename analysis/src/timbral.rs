@@ -1,46 +1,66 @@
 //! Timbral feature extraction module.
 //!
 //! Contains functions to extract & summarize the zero-crossing rate,
-//! spectral centroid, spectral flatness and spectral roll-off of
-//! a given Song.
+//! spectral centroid, spectral flatness, spectral roll-off, spectral bandwidth,
+//! spectral contrast, mel-frequency cepstral coefficients and percussiveness of a given Song.
 
 use bliss_audio_aubio_rs::vec::CVec;
-use bliss_audio_aubio_rs::{bin_to_freq, PVoc, SpecDesc, SpecShape};
-use ndarray::{arr1, Axis};
+use bliss_audio_aubio_rs::{bin_to_freq, PVoc, SpecDesc, SpecShape, MFCC};
+use ndarray::{arr1, s, Array1, Array2, Axis};
 
-use crate::Feature;
+use crate::{window_scale, Feature};
 
 use super::errors::{AnalysisError, AnalysisResult};
-use super::utils::{geometric_mean, mean, number_crossings, Normalize};
+use super::utils::{
+    geometric_mean, mean, number_crossings, window_functions::WindowFunction, Normalize,
+};
 use super::SAMPLE_RATE;
 
 /**
  * General object holding all the spectral descriptor.
  *
- * Holds 3 spectral descriptors together. It would be better conceptually
- * to have 3 different spectral descriptor objects, but this avoids re-computing
- * the same FFT three times.
+ * Holds 5 spectral descriptors together. It would be better conceptually
+ * to have 5 different spectral descriptor objects, but this avoids re-computing
+ * the same FFT five times.
  *
- * Current spectral descriptors are spectral centroid, spectral rolloff and
- * spectral flatness (see `values_object` for a further description of the
- * object.
+ * Current spectral descriptors are spectral centroid, spectral rolloff,
+ * spectral flatness, spectral bandwidth and spectral contrast (see `values_object`
+ * for a further description of the object.
  *
- * All descriptors are currently summarized by their mean only.
+ * All descriptors are summarized by their mean and standard deviation.
  */
 pub struct SpectralDesc {
     phase_vocoder: PVoc,
     sample_rate: u32,
+    window_size: usize,
+    hop_size: usize,
 
     centroid_aubio_desc: SpecDesc,
     rolloff_aubio_desc: SpecDesc,
     values_centroid: Vec<f32>,
     values_rolloff: Vec<f32>,
     values_flatness: Vec<f32>,
+    values_bandwidth: Vec<f32>,
+    /// One value per sub-band for every chunk.
+    values_contrast: Vec<f32>,
+    /// The frequency of every bin of the spectrum.
+    bin_frequencies: Vec<f32>,
 }
 
 impl SpectralDesc {
+    /// The window size at [`SAMPLE_RATE`], see [`SpectralDesc::window_size`].
     pub const WINDOW_SIZE: usize = 512;
+    /// The hop size at [`SAMPLE_RATE`], see [`SpectralDesc::hop_size`].
     pub const HOP_SIZE: usize = Self::WINDOW_SIZE / 4;
+    /// The number of sub-bands the spectral contrast is computed for.
+    pub const CONTRAST_BANDS: usize = 7;
+    /// The upper edge of the first spectral contrast sub-band,
+    /// every following sub-band is an octave above the previous one, and the last one goes up to the Nyquist frequency.
+    const CONTRAST_MIN_FREQUENCY: f32 = 200.;
+    /// The fraction of the bins of a sub-band that are averaged to get its peak (or valley).
+    const CONTRAST_QUANTILE: f32 = 0.02;
+    /// Added to the peaks and valleys before taking their ratio, so silent sub-bands don't have an infinite contrast.
+    const CONTRAST_AMIN: f32 = 1e-5;
 
     /**
      * Compute score related to the
@@ -125,45 +145,150 @@ impl SpectralDesc {
         ]
     }
 
+    /**
+     * Compute score related to the spectral bandwidth values, obtained
+     * after repeatedly calling `do_` on all of the song's chunks.
+     *
+     * Spectral bandwidth is the weighted standard deviation of the frequencies
+     * of the spectrum around its centroid.
+     *
+     * It tells apart sounds concentrated around a few frequencies (low bandwidth)
+     * from sounds spread over the whole spectrum, like percussions or distorted guitars
+     * (high bandwidth).
+     *
+     * The value range is between 0 and `sample_rate / 2`.
+     */
+    pub fn get_bandwidth(&mut self) -> Vec<Feature> {
+        vec![
+            self.normalize(Feature::from(mean(&self.values_bandwidth))),
+            self.normalize(Feature::from(
+                arr1(&self.values_bandwidth)
+                    .std_axis(Axis(0), 0.)
+                    .into_scalar(),
+            )),
+        ]
+    }
+
+    /**
+     * Compute score related to the spectral contrast values, obtained
+     * after repeatedly calling `do_` on all of the song's chunks.
+     *
+     * Spectral contrast is the difference, in decibels, between the peaks and
+     * the valleys of the spectrum in each of `CONTRAST_BANDS` octave sub-bands.
+     *
+     * High contrast values usually come from clear, narrow-band signals (like
+     * tonal instruments), while low contrast values come from broad-band noise.
+     *
+     * Returns the mean contrast of every sub-band, followed by their standard deviations.
+     * The value range is between 0 and 100 dB.
+     */
+    #[allow(clippy::missing_panics_doc)]
+    pub fn get_contrast(&mut self) -> Vec<Feature> {
+        let max_value = 100.;
+        let min_value = 0.;
+        let chunks = self.values_contrast.len() / Self::CONTRAST_BANDS;
+        let contrast =
+            Array2::from_shape_vec((chunks, Self::CONTRAST_BANDS), self.values_contrast.clone())
+                // `do_` always pushes exactly `CONTRAST_BANDS` values
+                .unwrap()
+                .mapv(Feature::from);
+
+        let means = contrast
+            .mean_axis(Axis(0))
+            .unwrap_or_else(|| Array1::zeros(Self::CONTRAST_BANDS));
+        let std_deviations = contrast.std_axis(Axis(0), 0.);
+
+        // Range is different from the other spectral algorithms, so normalizing
+        // manually here.
+        means
+            .iter()
+            .chain(std_deviations.iter())
+            .map(|value| 2. * (value - min_value) / (max_value - min_value) - 1.)
+            .collect()
+    }
+
     /// # Errors
     ///
     /// This function will return an error if there is an error loading the aubio objects
     pub fn new(sample_rate: u32) -> AnalysisResult<Self> {
+        Self::with_window_function(sample_rate, WindowFunction::default())
+    }
+
+    /// Like [`SpectralDesc::new`], but the phase vocoder uses the given window function instead of a Hann window.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an error loading the aubio objects,
+    /// or if aubio doesn't support the given window function (it has no flat top window).
+    pub fn with_window_function(
+        sample_rate: u32,
+        window_function: WindowFunction,
+    ) -> AnalysisResult<Self> {
+        let window_type = window_function.to_aubio().ok_or_else(|| {
+            AnalysisError::AnalysisError(format!(
+                "aubio's phase vocoder doesn't support the {window_function:?} window"
+            ))
+        })?;
+        let scale = window_scale(sample_rate);
+        let window_size = Self::WINDOW_SIZE * scale;
+        let hop_size = Self::HOP_SIZE * scale;
         Ok(Self {
-            centroid_aubio_desc: SpecDesc::new(SpecShape::Centroid, Self::WINDOW_SIZE).map_err(
-                |e| {
-                    AnalysisError::AnalysisError(format!(
-                        "error while loading aubio centroid object: {e}",
-                    ))
-                },
-            )?,
-            rolloff_aubio_desc: SpecDesc::new(SpecShape::Rolloff, Self::WINDOW_SIZE).map_err(
-                |e| {
+            centroid_aubio_desc: SpecDesc::new(SpecShape::Centroid, window_size).map_err(|e| {
+                AnalysisError::AnalysisError(format!(
+                    "error while loading aubio centroid object: {e}",
+                ))
+            })?,
+            rolloff_aubio_desc: SpecDesc::new(SpecShape::Rolloff, window_size).map_err(|e| {
+                AnalysisError::AnalysisError(format!(
+                    "error while loading aubio rolloff object: {e}",
+                ))
+            })?,
+            phase_vocoder: PVoc::new(window_size, hop_size)
+                .and_then(|pvoc| pvoc.with_window(window_type))
+                .map_err(|e| {
                     AnalysisError::AnalysisError(format!(
-                        "error while loading aubio rolloff object: {e}",
+                        "error while loading aubio pvoc object: {e}",
                     ))
-                },
-            )?,
-            phase_vocoder: PVoc::new(Self::WINDOW_SIZE, Self::HOP_SIZE).map_err(|e| {
-                AnalysisError::AnalysisError(format!("error while loading aubio pvoc object: {e}",))
-            })?,
+                })?,
             values_centroid: Vec::new(),
             values_rolloff: Vec::new(),
             values_flatness: Vec::new(),
+            values_bandwidth: Vec::new(),
+            values_contrast: Vec::new(),
+            #[allow(clippy::cast_precision_loss)]
+            bin_frequencies: (0..window_size / 2)
+                .map(|bin| bin_to_freq(bin as f32, sample_rate as f32, window_size as f32))
+                .collect(),
             sample_rate,
+            window_size,
+            hop_size,
         })
     }
 
+    /// The size of the windows to pass to `do_`, [`SpectralDesc::WINDOW_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The number of samples between the starts of consecutive windows, [`SpectralDesc::HOP_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
     /**
     Compute all the descriptors' value for the given chunk.
 
     After using this on all the song's chunks, you can call
-    `get_centroid`, `get_flatness` and `get_rolloff` to get the respective
-    descriptors' values.
+    `get_centroid`, `get_flatness`, `get_rolloff`, `get_bandwidth` and
+    `get_contrast` to get the respective descriptors' values.
     */
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub fn do_(&mut self, chunk: &[f32]) -> AnalysisResult<()> {
-        let mut fftgrain: Vec<f32> = vec![0.0; Self::WINDOW_SIZE];
+        let mut fftgrain: Vec<f32> = vec![0.0; self.window_size];
         self.phase_vocoder
             .do_(chunk, fftgrain.as_mut_slice())
             .map_err(|e| {
@@ -180,7 +305,7 @@ impl SpectralDesc {
             })?;
 
         #[allow(clippy::cast_precision_loss)]
-        let freq = bin_to_freq(bin, self.sample_rate as f32, Self::WINDOW_SIZE as f32);
+        let freq = bin_to_freq(bin, self.sample_rate as f32, self.window_size as f32);
         self.values_centroid.push(freq);
 
         let mut bin = self
@@ -190,15 +315,22 @@ impl SpectralDesc {
 
         // Until https://github.com/aubio/aubio/pull/318 is in
         #[allow(clippy::cast_precision_loss)]
-        if bin > Self::WINDOW_SIZE as f32 / 2. {
-            bin = Self::WINDOW_SIZE as f32 / 2.;
+        if bin > self.window_size as f32 / 2. {
+            bin = self.window_size as f32 / 2.;
         }
 
         #[allow(clippy::cast_precision_loss)]
-        let freq = bin_to_freq(bin, self.sample_rate as f32, Self::WINDOW_SIZE as f32);
+        let freq = bin_to_freq(bin, self.sample_rate as f32, self.window_size as f32);
         self.values_rolloff.push(freq);
 
         let cvec: CVec = fftgrain.as_slice().into();
+        let bandwidth = self.bandwidth(cvec.norm());
+        self.values_bandwidth.push(bandwidth);
+        for band in 0..Self::CONTRAST_BANDS {
+            let contrast = self.contrast(cvec.norm(), band);
+            self.values_contrast.push(contrast);
+        }
+
         let geo_mean = geometric_mean(cvec.norm());
         if geo_mean == 0.0 {
             self.values_flatness.push(0.0);
@@ -208,6 +340,65 @@ impl SpectralDesc {
         self.values_flatness.push(flatness);
         Ok(())
     }
+
+    /// The spectral bandwidth of the given magnitude spectrum, in Hz.
+    fn bandwidth(&self, magnitudes: &[f32]) -> f32 {
+        let total = magnitudes.iter().sum::<f32>();
+        if total == 0. {
+            return 0.;
+        }
+        let centroid = magnitudes
+            .iter()
+            .zip(&self.bin_frequencies)
+            .map(|(magnitude, frequency)| magnitude * frequency)
+            .sum::<f32>()
+            / total;
+        let variance = magnitudes
+            .iter()
+            .zip(&self.bin_frequencies)
+            .map(|(magnitude, frequency)| magnitude * (frequency - centroid).powi(2))
+            .sum::<f32>()
+            / total;
+        variance.sqrt()
+    }
+
+    /// The spectral contrast of the `band`-th sub-band of the given magnitude spectrum, in dB.
+    fn contrast(&self, magnitudes: &[f32], band: usize) -> f32 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let low = if band == 0 {
+            0.
+        } else {
+            Self::CONTRAST_MIN_FREQUENCY * 2f32.powi(band as i32 - 1)
+        };
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let high = if band == Self::CONTRAST_BANDS - 1 {
+            f32::INFINITY
+        } else {
+            Self::CONTRAST_MIN_FREQUENCY * 2f32.powi(band as i32)
+        };
+
+        let mut bins = magnitudes
+            .iter()
+            .zip(&self.bin_frequencies)
+            .filter(|(_, frequency)| (low..high).contains(*frequency))
+            .map(|(magnitude, _)| *magnitude)
+            .collect::<Vec<_>>();
+        if bins.is_empty() {
+            return 0.;
+        }
+        bins.sort_by(f32::total_cmp);
+
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let n = ((bins.len() as f32 * Self::CONTRAST_QUANTILE).round() as usize).max(1);
+        let valley = mean(&bins[..n]);
+        let peak = mean(&bins[bins.len() - n..]);
+
+        20. * ((peak + Self::CONTRAST_AMIN) / (valley + Self::CONTRAST_AMIN)).log10()
+    }
 }
 
 impl Normalize for SpectralDesc {
@@ -216,6 +407,336 @@ impl Normalize for SpectralDesc {
     const MIN_VALUE: Feature = 0.;
 }
 
+/**
+ * [Mel-frequency cepstral coefficients](https://en.wikipedia.org/wiki/Mel-frequency_cepstrum)
+ * detection object.
+ *
+ * MFCCs describe the overall shape of the spectral envelope of a sound on a
+ * perceptual (mel) scale, which makes them one of the most informative timbral
+ * features for music similarity.
+ *
+ * The coefficients are computed with a 40 band mel filterbank over the same
+ * STFT windows as [`SpectralDesc`], and summarized by their mean and standard
+ * deviation over the whole song.
+ *
+ * The 0th coefficient is not kept, as it only measures the overall energy of
+ * the window, which is already covered by the loudness descriptor.
+ */
+pub struct MfccDesc {
+    phase_vocoder: PVoc,
+    mfcc_aubio_desc: MFCC,
+    window_size: usize,
+    hop_size: usize,
+    n_coefficients: usize,
+    /// The coefficients of every window processed so far, including the 0th one.
+    values: Vec<f32>,
+}
+
+impl MfccDesc {
+    /// The window size at [`SAMPLE_RATE`], see [`MfccDesc::window_size`].
+    pub const WINDOW_SIZE: usize = SpectralDesc::WINDOW_SIZE;
+    /// The hop size at [`SAMPLE_RATE`], see [`MfccDesc::hop_size`].
+    pub const HOP_SIZE: usize = SpectralDesc::HOP_SIZE;
+    /// The number of coefficients computed by [`MfccDesc::new`].
+    pub const DEFAULT_COEFFICIENTS: usize = 13;
+    /// The number of bands in the mel filterbank.
+    const FILTERS: usize = 40;
+
+    /// # Errors
+    ///
+    /// This function will return an error if there is an error loading the aubio objects
+    pub fn new(sample_rate: u32) -> AnalysisResult<Self> {
+        Self::with_coefficients(sample_rate, Self::DEFAULT_COEFFICIENTS)
+    }
+
+    /// Like [`MfccDesc::new`], but computes `n_coefficients` coefficients instead of [`MfccDesc::DEFAULT_COEFFICIENTS`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an error loading the aubio objects,
+    /// or if `n_coefficients` is 0.
+    pub fn with_coefficients(sample_rate: u32, n_coefficients: usize) -> AnalysisResult<Self> {
+        if n_coefficients == 0 {
+            return Err(AnalysisError::AnalysisError(String::from(
+                "can't compute 0 mfcc coefficients",
+            )));
+        }
+        let scale = window_scale(sample_rate);
+        let window_size = Self::WINDOW_SIZE * scale;
+        let hop_size = Self::HOP_SIZE * scale;
+        Ok(Self {
+            phase_vocoder: PVoc::new(window_size, hop_size).map_err(|e| {
+                AnalysisError::AnalysisError(format!("error while loading aubio pvoc object: {e}"))
+            })?,
+            // one more coefficient than needed, since the 0th one is discarded
+            mfcc_aubio_desc: MFCC::new(window_size, Self::FILTERS, n_coefficients + 1, sample_rate)
+                .map_err(|e| {
+                    AnalysisError::AnalysisError(format!(
+                        "error while loading aubio mfcc object: {e}"
+                    ))
+                })?,
+            window_size,
+            hop_size,
+            n_coefficients,
+            values: Vec::new(),
+        })
+    }
+
+    /// The size of the windows to pass to `do_`, [`MfccDesc::WINDOW_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The number of samples between the starts of consecutive windows, [`MfccDesc::HOP_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// Compute the coefficients of the given chunk.
+    ///
+    /// After using this on all the song's chunks, you can call `get_value` to
+    /// get the summarized coefficients.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn do_(&mut self, chunk: &[f32]) -> AnalysisResult<()> {
+        let mut fftgrain: Vec<f32> = vec![0.0; self.window_size];
+        self.phase_vocoder
+            .do_(chunk, fftgrain.as_mut_slice())
+            .map_err(|e| {
+                AnalysisError::AnalysisError(format!("error while processing aubio pv object: {e}"))
+            })?;
+
+        let mut coefficients = vec![0.0; self.n_coefficients + 1];
+        self.mfcc_aubio_desc
+            .do_(fftgrain.as_slice(), coefficients.as_mut_slice())
+            .map_err(|e| {
+                AnalysisError::AnalysisError(format!(
+                    "error while processing aubio mfcc object: {e}"
+                ))
+            })?;
+        self.values.extend_from_slice(&coefficients);
+        Ok(())
+    }
+
+    /// Summarize the coefficients witnessed so far.
+    ///
+    /// Returns the mean of every coefficient (except the 0th one), followed by their standard deviations,
+    /// so `2 * n_coefficients` values in total.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn get_value(&mut self) -> Vec<Feature> {
+        let columns = self.n_coefficients + 1;
+        let windows = self.values.len() / columns;
+        let coefficients = Array2::from_shape_vec((windows, columns), self.values.clone())
+            // `do_` always pushes exactly `columns` values
+            .unwrap()
+            .mapv(Feature::from);
+        let coefficients = coefficients.slice(s![.., 1..]);
+
+        let means = coefficients
+            .mean_axis(Axis(0))
+            .unwrap_or_else(|| Array1::zeros(self.n_coefficients));
+        let std_deviations = coefficients.std_axis(Axis(0), 0.);
+
+        means
+            .iter()
+            .chain(std_deviations.iter())
+            .map(|value| self.normalize(*value))
+            .collect()
+    }
+}
+
+impl Normalize for MfccDesc {
+    // aubio computes the coefficients from the log10 of the filterbank energies,
+    // which keeps the coefficients of music well within this range.
+    const MAX_VALUE: Feature = 20.;
+    const MIN_VALUE: Feature = -20.;
+}
+
+/**
+ * Percussiveness detection object.
+ *
+ * Songs with lots of percussive hits (e.g. electronic or metal) feel very
+ * different from songs made of smooth, sustained tones (e.g. classical or ambient).
+ * This descriptor captures that with two values:
+ *
+ * - the onset density, the number of onsets per second, found by peak-picking
+ *   the [spectral flux](https://en.wikipedia.org/wiki/Spectral_flux) of the song.
+ *   Ranges between 0 and 10 onsets per second.
+ * - the percussive energy ratio, the mean fraction of the energy of each window
+ *   that is above `HIGH_FREQUENCY_CUTOFF` Hz, since percussive hits are broadband
+ *   while most tonal sounds have little energy in the high frequencies.
+ *   Ranges between 0 and 1.
+ */
+pub struct PercussionDesc {
+    phase_vocoder: PVoc,
+    sample_rate: u32,
+    window_size: usize,
+    hop_size: usize,
+    /// The first bin above `HIGH_FREQUENCY_CUTOFF`.
+    cutoff_bin: usize,
+    previous_magnitudes: Vec<f32>,
+    values_flux: Vec<f32>,
+    values_energy_ratio: Vec<f32>,
+}
+
+impl PercussionDesc {
+    /// The window size at [`SAMPLE_RATE`], see [`PercussionDesc::window_size`].
+    pub const WINDOW_SIZE: usize = SpectralDesc::WINDOW_SIZE;
+    /// The hop size at [`SAMPLE_RATE`], see [`PercussionDesc::hop_size`].
+    pub const HOP_SIZE: usize = SpectralDesc::HOP_SIZE;
+    /// The frequency (in Hz) above which energy is considered percussive.
+    const HIGH_FREQUENCY_CUTOFF: f32 = 4000.;
+    /// How long (in seconds) the window used to compute the adaptive onset threshold extends on each side of a frame.
+    const THRESHOLD_WINDOW: f32 = 0.05;
+    /// How much the spectral flux must exceed its local mean to be considered an onset.
+    const THRESHOLD_MULTIPLIER: f32 = 1.5;
+    /// The fraction of the song's strongest spectral flux that an onset must reach,
+    /// so that small fluctuations of a sustained sound aren't counted as onsets.
+    const MIN_ONSET_STRENGTH: f32 = 0.1;
+
+    /// # Errors
+    ///
+    /// This function will return an error if there is an error loading the aubio objects
+    pub fn new(sample_rate: u32) -> AnalysisResult<Self> {
+        let scale = window_scale(sample_rate);
+        let window_size = Self::WINDOW_SIZE * scale;
+        let hop_size = Self::HOP_SIZE * scale;
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let cutoff_bin =
+            (Self::HIGH_FREQUENCY_CUTOFF * window_size as f32 / sample_rate as f32).ceil() as usize;
+        Ok(Self {
+            phase_vocoder: PVoc::new(window_size, hop_size).map_err(|e| {
+                AnalysisError::AnalysisError(format!("error while loading aubio pvoc object: {e}"))
+            })?,
+            sample_rate,
+            window_size,
+            hop_size,
+            cutoff_bin,
+            previous_magnitudes: Vec::new(),
+            values_flux: Vec::new(),
+            values_energy_ratio: Vec::new(),
+        })
+    }
+
+    /// The size of the windows to pass to `do_`, [`PercussionDesc::WINDOW_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The number of samples between the starts of consecutive windows, [`PercussionDesc::HOP_SIZE`] scaled for the sample rate
+    /// (see [`window_scale`]).
+    #[must_use]
+    pub const fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// Compute the spectral flux and the percussive energy ratio of the given chunk.
+    ///
+    /// After using this on all the song's chunks, you can call `get_value` to
+    /// get the onset density and the percussive energy ratio.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn do_(&mut self, chunk: &[f32]) -> AnalysisResult<()> {
+        let mut fftgrain: Vec<f32> = vec![0.0; self.window_size];
+        self.phase_vocoder
+            .do_(chunk, fftgrain.as_mut_slice())
+            .map_err(|e| {
+                AnalysisError::AnalysisError(format!("error while processing aubio pv object: {e}"))
+            })?;
+        let cvec: CVec = fftgrain.as_slice().into();
+        let magnitudes = cvec.norm();
+
+        // only increases in energy count towards the flux, so onsets stand out but offsets don't
+        let flux = if self.previous_magnitudes.is_empty() {
+            magnitudes.iter().sum()
+        } else {
+            magnitudes
+                .iter()
+                .zip(&self.previous_magnitudes)
+                .map(|(current, previous)| (current - previous).max(0.))
+                .sum()
+        };
+        self.values_flux.push(flux);
+        self.previous_magnitudes = magnitudes.to_vec();
+
+        // silent windows have no energy to split, so they're skipped
+        let energy = magnitudes.iter().map(|m| m * m).sum::<f32>();
+        if energy > 0. {
+            let high_energy = magnitudes
+                .iter()
+                .skip(self.cutoff_bin)
+                .map(|m| m * m)
+                .sum::<f32>();
+            self.values_energy_ratio.push(high_energy / energy);
+        }
+        Ok(())
+    }
+
+    /// Count the onsets found in the spectral flux.
+    ///
+    /// A frame is an onset if its flux is a local maximum, exceeds `THRESHOLD_MULTIPLIER` times
+    /// the mean flux of the frames within `THRESHOLD_WINDOW` seconds of it, and is at least
+    /// `MIN_ONSET_STRENGTH` times the strongest flux.
+    fn count_onsets(&self) -> usize {
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let radius = ((Self::THRESHOLD_WINDOW * self.sample_rate as f32 / self.hop_size as f32)
+            .round() as usize)
+            .max(1);
+        let flux = &self.values_flux;
+        let min_strength = Self::MIN_ONSET_STRENGTH * flux.iter().copied().fold(0., f32::max);
+
+        (0..flux.len())
+            .filter(|&i| {
+                let is_peak = flux[i] > min_strength
+                    && (i == 0 || flux[i] >= flux[i - 1])
+                    && (i + 1 == flux.len() || flux[i] > flux[i + 1]);
+                let neighborhood =
+                    &flux[i.saturating_sub(radius)..(i + radius + 1).min(flux.len())];
+                is_peak && flux[i] > Self::THRESHOLD_MULTIPLIER * mean(neighborhood)
+            })
+            .count()
+    }
+
+    /// Get the onset density and the percussive energy ratio of the chunks witnessed so far, normalized.
+    pub fn get_value(&mut self) -> Vec<Feature> {
+        #[allow(clippy::cast_precision_loss)]
+        let duration =
+            (self.values_flux.len() * self.hop_size) as Feature / Feature::from(self.sample_rate);
+        #[allow(clippy::cast_precision_loss)]
+        let onset_density = if duration > 0. {
+            self.count_onsets() as Feature / duration
+        } else {
+            0.
+        };
+
+        let energy_ratio = if self.values_energy_ratio.is_empty() {
+            0.
+        } else {
+            Feature::from(mean(&self.values_energy_ratio))
+        };
+
+        // the energy ratio has a different range than the onset density, so normalizing it manually here.
+        vec![self.normalize(onset_density), 2. * energy_ratio - 1.]
+    }
+}
+
+impl Normalize for PercussionDesc {
+    const MAX_VALUE: Feature = 10.;
+    const MIN_VALUE: Feature = 0.;
+}
+
 /**
  * [Zero-crossing rate](https://en.wikipedia.org/wiki/Zero-crossing_rate)
  * detection object.
@@ -268,6 +789,14 @@ mod tests {
     use crate::decoder::{Decoder as DecoderTrait, MecompDecoder as Decoder};
     use std::path::Path;
 
+    #[test]
+    fn test_spectral_desc_window_function() {
+        assert!(
+            SpectralDesc::with_window_function(SAMPLE_RATE, WindowFunction::BlackmanHarris).is_ok()
+        );
+        assert!(SpectralDesc::with_window_function(SAMPLE_RATE, WindowFunction::FlatTop).is_err());
+    }
+
     #[test]
     fn test_zcr_boundaries() {
         let mut zcr_desc = ZeroCrossingRateDesc::default();
@@ -434,6 +963,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_spectral_bandwidth_boundaries() {
+        let mut spectral_desc = SpectralDesc::new(10).unwrap();
+        let chunk = vec![0.; 512];
+
+        spectral_desc.do_(&chunk).unwrap();
+        let expected_values = [-1., -1.];
+        for (expected, actual) in expected_values
+            .iter()
+            .zip(spectral_desc.get_bandwidth().iter())
+        {
+            assert!(
+                0.000_000_1 > (expected - actual).abs(),
+                "{expected} !~= {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_spectral_bandwidth() {
+        let mut bandwidths = Vec::new();
+        for path in ["data/tone_11080Hz.flac", "data/white_noise.mp3"] {
+            let song = Decoder::decode(Path::new(path)).unwrap();
+            let mut spectral_desc = SpectralDesc::new(SAMPLE_RATE).unwrap();
+            for chunk in song.samples.chunks_exact(SpectralDesc::HOP_SIZE) {
+                spectral_desc.do_(chunk).unwrap();
+            }
+            bandwidths.push(spectral_desc.get_bandwidth()[0]);
+        }
+        // a pure tone is much narrower than white noise
+        assert!(
+            bandwidths[0] < bandwidths[1],
+            "tone: {}, white noise: {}",
+            bandwidths[0],
+            bandwidths[1]
+        );
+    }
+
+    #[test]
+    fn test_spectral_contrast_boundaries() {
+        let mut spectral_desc = SpectralDesc::new(SAMPLE_RATE).unwrap();
+        let chunk = vec![0.; 512];
+
+        spectral_desc.do_(&chunk).unwrap();
+        let values = spectral_desc.get_contrast();
+        assert_eq!(values.len(), 2 * SpectralDesc::CONTRAST_BANDS);
+        for actual in values {
+            assert!(0.000_000_1 > (-1. - actual).abs(), "-1 !~= {actual}");
+        }
+    }
+
+    #[test]
+    fn test_spectral_contrast() {
+        let mut contrasts = Vec::new();
+        for path in ["data/tone_11080Hz.flac", "data/white_noise.mp3"] {
+            let song = Decoder::decode(Path::new(path)).unwrap();
+            let mut spectral_desc = SpectralDesc::new(SAMPLE_RATE).unwrap();
+            for chunk in song.samples.chunks_exact(SpectralDesc::HOP_SIZE) {
+                spectral_desc.do_(chunk).unwrap();
+            }
+            let values = spectral_desc.get_contrast();
+            assert_eq!(values.len(), 2 * SpectralDesc::CONTRAST_BANDS);
+            contrasts.push(values[SpectralDesc::CONTRAST_BANDS - 1]);
+        }
+        // the tone is a sharp peak in the highest sub-band, noise is spread evenly over it
+        assert!(
+            contrasts[0] > contrasts[1],
+            "tone: {}, white noise: {}",
+            contrasts[0],
+            contrasts[1]
+        );
+    }
+
     #[test]
     fn test_spectral_centroid_boundaries() {
         let mut spectral_desc = SpectralDesc::new(10).unwrap();
@@ -469,4 +1071,105 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_mfcc_coefficients() {
+        assert!(MfccDesc::with_coefficients(SAMPLE_RATE, 0).is_err());
+
+        let mut mfcc_desc = MfccDesc::with_coefficients(SAMPLE_RATE, 5).unwrap();
+        let chunk = vec![0.; MfccDesc::HOP_SIZE];
+        mfcc_desc.do_(&chunk).unwrap();
+        assert_eq!(mfcc_desc.get_value().len(), 10);
+    }
+
+    #[test]
+    fn test_mfcc_boundaries() {
+        // silence has a flat spectrum, so every coefficient but the (discarded) 0th one is 0
+        let mut mfcc_desc = MfccDesc::new(SAMPLE_RATE).unwrap();
+        let chunk = vec![0.; MfccDesc::HOP_SIZE];
+        for _ in 0..10 {
+            mfcc_desc.do_(&chunk).unwrap();
+        }
+        let values = mfcc_desc.get_value();
+        assert_eq!(values.len(), 2 * MfccDesc::DEFAULT_COEFFICIENTS);
+        for actual in values {
+            assert!(0.000_1 > actual.abs(), "0 !~= {actual}");
+        }
+    }
+
+    #[test]
+    fn test_mfcc() {
+        let song = Decoder::decode(Path::new("data/s16_mono_22_5kHz.flac")).unwrap();
+        let mut mfcc_desc = MfccDesc::new(SAMPLE_RATE).unwrap();
+        for chunk in song.samples.chunks_exact(MfccDesc::HOP_SIZE) {
+            mfcc_desc.do_(chunk).unwrap();
+        }
+        let values = mfcc_desc.get_value();
+        assert_eq!(values.len(), 2 * MfccDesc::DEFAULT_COEFFICIENTS);
+        for actual in &values {
+            assert!((-1. ..=1.).contains(actual), "{actual} is out of range");
+        }
+        // the song isn't silent, so its spectral envelope changes over time
+        assert!(values[MfccDesc::DEFAULT_COEFFICIENTS..]
+            .iter()
+            .any(|std_deviation| *std_deviation > 0.));
+    }
+
+    #[test]
+    fn test_percussion_boundaries() {
+        let mut percussion_desc = PercussionDesc::new(SAMPLE_RATE).unwrap();
+        let chunk = vec![0.; PercussionDesc::HOP_SIZE];
+        for _ in 0..100 {
+            percussion_desc.do_(&chunk).unwrap();
+        }
+        assert_eq!(percussion_desc.get_value(), vec![-1., -1.]);
+    }
+
+    #[test]
+    fn test_percussion_clicks() {
+        // 10 clicks, one every half second
+        let mut samples = vec![0.; SAMPLE_RATE as usize * 5];
+        for click in samples.iter_mut().step_by(SAMPLE_RATE as usize / 2) {
+            *click = 1.;
+        }
+
+        let mut percussion_desc = PercussionDesc::new(SAMPLE_RATE).unwrap();
+        for chunk in samples.chunks_exact(PercussionDesc::HOP_SIZE) {
+            percussion_desc.do_(chunk).unwrap();
+        }
+        let values = percussion_desc.get_value();
+
+        // 2 onsets per second
+        let expected = percussion_desc.normalize(2.);
+        assert!(
+            0.05 > (expected - values[0]).abs(),
+            "{expected} !~= {}",
+            values[0]
+        );
+        // clicks have a flat spectrum, so most of their energy is above the cutoff
+        let expected = 2. * (1. - 4000. / 11025.) - 1.;
+        assert!(
+            0.1 > (expected - values[1]).abs(),
+            "{expected} !~= {}",
+            values[1]
+        );
+    }
+
+    #[test]
+    fn test_percussion_tone() {
+        // a sustained low tone has no onsets after it starts, and no high frequency energy
+        #[allow(clippy::cast_precision_loss)]
+        let samples = (0..SAMPLE_RATE as usize * 5)
+            .map(|i| (2. * std::f32::consts::PI * 220. * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect::<Vec<_>>();
+
+        let mut percussion_desc = PercussionDesc::new(SAMPLE_RATE).unwrap();
+        for chunk in samples.chunks_exact(PercussionDesc::HOP_SIZE) {
+            percussion_desc.do_(chunk).unwrap();
+        }
+        let values = percussion_desc.get_value();
+
+        assert!(values[0] < percussion_desc.normalize(1.), "{}", values[0]);
+        assert!(0.01 > (-1. - values[1]).abs(), "-1 !~= {}", values[1]);
+    }
 }
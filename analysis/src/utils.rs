@@ -3,10 +3,11 @@ use ndarray::{arr1, s, Array, Array1, Array2};
 use rustfft::num_complex::Complex;
 use rustfft::num_traits::Zero;
 use rustfft::FftPlanner;
-use std::f32::consts::PI;
 
 use crate::Feature;
 
+use window_functions::WindowFunction;
+
 #[must_use]
 pub fn reflect_pad(array: &[f32], pad: usize) -> Vec<f32> {
     let prefix = array[1..=pad].iter().rev().copied().collect::<Vec<f32>>();
@@ -23,21 +24,27 @@ pub fn reflect_pad(array: &[f32], pad: usize) -> Vec<f32> {
     output
 }
 
+/// Compute the magnitude of the short-time Fourier transform of a signal, using a Hann window.
 #[must_use]
 pub fn stft(signal: &[f32], window_length: usize, hop_length: usize) -> Array2<f64> {
+    stft_with_window(signal, window_length, hop_length, WindowFunction::Hann)
+}
+
+/// Compute the magnitude of the short-time Fourier transform of a signal,
+/// using the given window function.
+#[must_use]
+pub fn stft_with_window(
+    signal: &[f32],
+    window_length: usize,
+    hop_length: usize,
+    window_function: WindowFunction,
+) -> Array2<f64> {
     // Take advantage of raw-major order to have contiguous window for the
     // `assign`, reversing the axes to have the expected shape at the end only.
     let mut stft = Array2::zeros((signal.len().div_ceil(hop_length), window_length / 2 + 1));
     let signal = reflect_pad(signal, window_length / 2);
 
-    // Periodic, so window_size + 1
-    let mut hann_window = Array::zeros(window_length + 1);
-    #[allow(clippy::cast_precision_loss)]
-    for n in 0..window_length {
-        hann_window[[n]] =
-            0.5f32.mul_add(-f32::cos(2. * n as f32 * PI / (window_length as f32)), 0.5);
-    }
-    hann_window = hann_window.slice_move(s![0..window_length]);
+    let window_coefficients = Array1::from(window_function.coefficients(window_length));
     let mut planner = FftPlanner::new();
     let fft = planner.plan_fft_forward(window_length);
 
@@ -46,7 +53,7 @@ pub fn stft(signal: &[f32], window_length: usize, hop_length: usize) -> Array2<f
         .step_by(hop_length)
         .zip(stft.rows_mut())
     {
-        let mut signal = (arr1(window) * &hann_window).mapv(|x| Complex::new(x, 0.));
+        let mut signal = (arr1(window) * &window_coefficients).mapv(|x| Complex::new(x, 0.));
         if let Some(s) = signal.as_slice_mut() {
             fft.process(s);
         } else {
@@ -172,6 +179,106 @@ pub fn convolve(input: &Array1<f64>, kernel: &Array1<f64>) -> Array1<f64> {
     multiplication / multiplication_length
 }
 
+pub mod window_functions {
+    //! Window functions, used to taper a frame of samples before taking its Fourier transform.
+    //!
+    //! All windows are periodic (as opposed to symmetric), which is what you want for spectral analysis.
+
+    use std::f32::consts::PI;
+
+    use bliss_audio_aubio_rs::WindowType;
+
+    /// A window function.
+    ///
+    /// These are all [cosine-sum windows](https://en.wikipedia.org/wiki/Window_function#Cosine-sum_windows),
+    /// they trade off the width of the main lobe (frequency resolution) against the height of the
+    /// side lobes (spectral leakage) differently.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub enum WindowFunction {
+        /// Good all-round window, this is what the descriptors have always used.
+        #[default]
+        Hann,
+        /// Like the Hann window, but cancels out the first side lobe.
+        Hamming,
+        /// Lower side lobes than Hann or Hamming, at the cost of a wider main lobe.
+        Blackman,
+        /// Very low side lobes (-92 dB), use this when spectral leakage is a problem.
+        BlackmanHarris,
+        /// Very wide main lobe, but measures the amplitude of sinusoids very accurately.
+        FlatTop,
+    }
+
+    impl WindowFunction {
+        /// The coefficients of the cosine sum, `a0, a1, a2, ...`.
+        const fn cosine_coefficients(self) -> &'static [f32] {
+            match self {
+                Self::Hann => &[0.5, 0.5],
+                Self::Hamming => &[0.54, 0.46],
+                Self::Blackman => &[0.42, 0.5, 0.08],
+                Self::BlackmanHarris => &[0.358_75, 0.488_29, 0.141_28, 0.011_68],
+                Self::FlatTop => &[
+                    0.215_578_95,
+                    0.416_631_58,
+                    0.277_263_16,
+                    0.083_578_95,
+                    0.006_947_368,
+                ],
+            }
+        }
+
+        /// Compute the `length` coefficients of the window.
+        #[must_use]
+        pub fn coefficients(self, length: usize) -> Vec<f32> {
+            #[allow(clippy::cast_precision_loss)]
+            let length_f32 = length as f32;
+            (0..length)
+                .map(|n| {
+                    #[allow(clippy::cast_precision_loss)]
+                    let x = 2. * n as f32 * PI / length_f32;
+                    match self {
+                        // kept separate so results are bit-for-bit identical to what we used before window functions were configurable
+                        Self::Hann => 0.5f32.mul_add(-f32::cos(x), 0.5),
+                        _ => self
+                            .cosine_coefficients()
+                            .iter()
+                            .enumerate()
+                            .map(|(k, a)| {
+                                #[allow(clippy::cast_precision_loss)]
+                                let term = a * f32::cos(k as f32 * x);
+                                if k % 2 == 0 {
+                                    term
+                                } else {
+                                    -term
+                                }
+                            })
+                            .sum(),
+                    }
+                })
+                .collect()
+        }
+
+        /// The equivalent aubio window type, if aubio has one.
+        #[must_use]
+        pub const fn to_aubio(self) -> Option<WindowType> {
+            match self {
+                Self::Hann => Some(WindowType::Hanningz),
+                Self::Hamming => Some(WindowType::Hamming),
+                Self::Blackman => Some(WindowType::Blackman),
+                Self::BlackmanHarris => Some(WindowType::BlackmanHarris),
+                Self::FlatTop => None,
+            }
+        }
+    }
+
+    /// Multiply the samples, in place, by the given window function.
+    pub fn apply(samples: &mut [f32], window: WindowFunction) {
+        let coefficients = window.coefficients(samples.len());
+        for (sample, coefficient) in samples.iter_mut().zip(coefficients) {
+            *sample *= coefficient;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -519,6 +626,77 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_window_function_coefficients() {
+        for window in [
+            WindowFunction::Hann,
+            WindowFunction::Hamming,
+            WindowFunction::Blackman,
+            WindowFunction::BlackmanHarris,
+            WindowFunction::FlatTop,
+        ] {
+            let coefficients = window.coefficients(64);
+            assert_eq!(coefficients.len(), 64);
+            // periodic windows peak in the middle...
+            assert!(
+                0.0001 > (coefficients[32] - 1.).abs(),
+                "{window:?}: {} !~= 1",
+                coefficients[32]
+            );
+            // ...and are symmetric around it
+            for n in 1..32 {
+                assert!(
+                    0.0001 > (coefficients[n] - coefficients[64 - n]).abs(),
+                    "{window:?}: {} !~= {}",
+                    coefficients[n],
+                    coefficients[64 - n]
+                );
+            }
+        }
+
+        assert_eq!(WindowFunction::Hann.coefficients(64)[0], 0.);
+        assert!(0.0001 > (WindowFunction::Hamming.coefficients(64)[0] - 0.08).abs());
+    }
+
+    #[test]
+    fn test_window_function_apply() {
+        let mut samples = vec![2.0; 8];
+        window_functions::apply(&mut samples, WindowFunction::Hann);
+
+        let expected = WindowFunction::Hann
+            .coefficients(8)
+            .into_iter()
+            .map(|c| c * 2.)
+            .collect::<Vec<_>>();
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn test_blackman_harris_has_less_leakage() {
+        // a sinusoid that falls between two bins, the worst case for leakage
+        #[allow(clippy::cast_precision_loss)]
+        let signal = (0..8192)
+            .map(|n| f32::sin(2. * std::f32::consts::PI * 10.5 * n as f32 / 256.))
+            .collect::<Vec<_>>();
+
+        // the fraction of the energy more than 4 bins away from the sinusoid
+        let leakage = |window| {
+            let stft = stft_with_window(&signal, 256, 256, window);
+            let frame = stft.column(stft.ncols() / 2);
+            let total = frame.mapv(|x| x * x).sum();
+            let leaked = frame
+                .indexed_iter()
+                .filter(|(bin, _)| bin.abs_diff(10) > 4)
+                .map(|(_, x)| x * x)
+                .sum::<f64>();
+            leaked / total
+        };
+
+        let hann = leakage(WindowFunction::Hann);
+        let blackman_harris = leakage(WindowFunction::BlackmanHarris);
+        assert!(blackman_harris < hann, "{blackman_harris} >= {hann}");
+    }
+
     #[test]
     fn test_reflect_pad() {
         let array = Array::range(0., 100_000., 1.);
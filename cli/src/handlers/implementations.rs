@@ -3,27 +3,33 @@ use std::{
     time::Duration,
 };
 
-use crate::handlers::{printing, utils};
+use crate::handlers::{printing, search, utils};
 
 use super::{
-    Command, CommandHandler, CurrentTarget, LibraryCommand, LibraryGetTarget, LibraryListTarget,
-    PlaylistGetMethod, QueueAddTarget, QueueCommand, RandTarget, SearchTarget, SeekCommand,
+    playback::parse_seek_position, ArtistCommand, Command, CommandHandler, CurrentTarget,
+    EqualizerCommand, FailedAnalysisCommand, GenreCommand, LibraryCommand, LibraryGetTarget,
+    LibraryListTarget, PlaylistGetMethod, QueueAddTarget, QueueCommand, RandTarget, SeekCommand,
     VolumeCommand,
 };
 
 use anyhow::bail;
 use mecomp_core::{
-    rpc::SearchResult,
+    rpc::ReclusterRequest,
     state::{
-        library::{LibraryBrief, LibraryFull, LibraryHealth},
-        SeekType,
+        library::{
+            CollectionAnalysisStats, DuplicateGroup, LibraryBrief, LibraryFull, LibraryHealth,
+            SongVerificationResult,
+        },
+        AudioLevels, SeekType,
     },
 };
 use mecomp_storage::db::schemas::{
     album::{self, Album, AlbumBrief},
     artist::{self, Artist, ArtistBrief},
     collection::{self, Collection, CollectionBrief},
+    failed_analysis::{self, FailedAnalysis},
     playlist::{self, Playlist, PlaylistBrief},
+    scan_history::ScanMetrics,
     song::{self, Song, SongBrief},
     Id, Thing,
 };
@@ -94,51 +100,11 @@ impl CommandHandler for Command {
                 Ok(())
             }
             Self::Search {
-                target,
                 query,
+                kind,
                 limit,
-            } => {
-                match target {
-                    SearchTarget::All => {
-                        let SearchResult {
-                            songs,
-                            albums,
-                            artists,
-                        } = client.search(ctx, query.clone(), *limit).await?;
-                        println!(
-                            "Daemon response:\n{}\n{}\n{}",
-                            printing::song_list("Songs", &songs, false)?,
-                            printing::album_list("Albums", &albums)?,
-                            printing::artist_list("Artists", &artists)?
-                        );
-                    }
-                    SearchTarget::Artist => {
-                        let resp: Box<[Artist]> =
-                            client.search_artist(ctx, query.clone(), *limit).await?;
-                        println!(
-                            "Daemon response:\n{}",
-                            printing::artist_list("Artists", &resp)?
-                        );
-                    }
-                    SearchTarget::Album => {
-                        let resp: Box<[Album]> =
-                            client.search_album(ctx, query.clone(), *limit).await?;
-                        println!(
-                            "Daemon response:\n{}",
-                            printing::album_list("Albums", &resp)?
-                        );
-                    }
-                    SearchTarget::Song => {
-                        let resp: Box<[Song]> =
-                            client.search_song(ctx, query.clone(), *limit).await?;
-                        println!(
-                            "Daemon response:\n{}",
-                            printing::song_list("Songs", &resp, false)?
-                        );
-                    }
-                }
-                Ok(())
-            }
+                format,
+            } => search::search(ctx, client, query, *kind, *limit, *format).await,
             Self::Playback { command } => command.handle(ctx, client).await,
             Self::Queue { command } => command.handle(ctx, client).await,
             Self::Playlist { command } => command.handle(ctx, client).await,
@@ -176,8 +142,15 @@ impl CommandHandler for LibraryCommand {
                 }
                 Ok(())
             }
-            Self::Recluster => {
-                let resp: Result<(), _> = client.library_recluster(ctx).await?;
+            Self::Recluster { clusters } => {
+                let resp: Result<(), _> = client
+                    .library_recluster(
+                        ctx,
+                        ReclusterRequest {
+                            n_clusters: *clusters,
+                        },
+                    )
+                    .await?;
                 if let Err(e) = resp {
                     println!("Daemon response:\n{e}");
                 } else {
@@ -200,6 +173,113 @@ impl CommandHandler for LibraryCommand {
                 println!("Daemon response:\n{resp:#?}");
                 Ok(())
             }
+            Self::ScanHistory => {
+                let resp: Box<[ScanMetrics]> = client.library_scan_history(ctx).await??;
+                println!("Daemon response:\n{}", printing::scan_history(&resp)?);
+                Ok(())
+            }
+            Self::Verify { ids } => {
+                let ids = (!ids.is_empty()).then(|| {
+                    ids.iter()
+                        .map(|id| Thing {
+                            tb: song::TABLE_NAME.to_owned(),
+                            id: Id::String(id.to_owned()),
+                        })
+                        .collect()
+                });
+                let resp: Box<[SongVerificationResult]> = client.library_verify(ctx, ids).await??;
+                println!(
+                    "Daemon response:\n{}",
+                    printing::verification_results(&resp)?
+                );
+                Ok(())
+            }
+            Self::DeadLinks { remove: false } => {
+                let resp: Vec<SongBrief> = client.library_scan_dead_links(ctx).await??;
+                println!(
+                    "Daemon response:\n{}",
+                    printing::song_brief_list("Dead links", &resp)?
+                );
+                Ok(())
+            }
+            Self::DeadLinks { remove: true } => {
+                let resp: u64 = client.library_remove_dead_links(ctx).await??;
+                println!("Daemon response:\n{resp} dead links removed");
+                Ok(())
+            }
+            Self::Duplicates { acoustic: false } => {
+                let resp: Vec<DuplicateGroup> = client.library_find_duplicates(ctx).await??;
+                println!("Daemon response:\n{}", printing::duplicate_groups(&resp)?);
+                Ok(())
+            }
+            Self::Duplicates { acoustic: true } => {
+                let resp: Vec<DuplicateGroup> =
+                    client.library_find_acoustid_duplicates(ctx).await??;
+                println!("Daemon response:\n{}", printing::duplicate_groups(&resp)?);
+                Ok(())
+            }
+            Self::RemoveDuplicates { keep, remove } => {
+                let song_id = |id: &String| Thing {
+                    tb: song::TABLE_NAME.to_owned(),
+                    id: Id::String(id.to_owned()),
+                };
+                let resp: Song = client
+                    .library_remove_duplicate(
+                        ctx,
+                        song_id(keep),
+                        remove.iter().map(song_id).collect(),
+                    )
+                    .await??;
+                println!("Daemon response:\nduplicates removed:\n{resp:#?}");
+                Ok(())
+            }
+            Self::Songs {
+                year_from,
+                year_to,
+                limit,
+            } => {
+                let start = year_from.unwrap_or(i32::MIN);
+                let end = year_to.unwrap_or(i32::MAX);
+                let resp: Box<[Song]> =
+                    client.library_year_range(ctx, start, end, *limit).await??;
+                let prefix = match (year_from, year_to) {
+                    (Some(from), Some(to)) => format!("Songs released from {from} to {to}"),
+                    (Some(from), None) => format!("Songs released in or after {from}"),
+                    (None, Some(to)) => format!("Songs released in or before {to}"),
+                    (None, None) => "Songs with a release year".to_string(),
+                };
+                println!(
+                    "Daemon response:\n{}",
+                    printing::song_list(&prefix, &resp, false)?
+                );
+                Ok(())
+            }
+            Self::Bpm { min, max, limit } => {
+                let resp: Box<[Song]> = client.library_bpm_range(ctx, *min, *max, *limit).await??;
+                println!(
+                    "Daemon response:\n{}",
+                    printing::song_list(
+                        &format!("Songs with a tempo from {min} to {max} BPM"),
+                        &resp,
+                        false
+                    )?
+                );
+                Ok(())
+            }
+            Self::Random { limit, seed } => {
+                let resp: Box<[Song]> = client.library_random_songs(ctx, *limit, *seed).await??;
+                client
+                    .queue_add_list(
+                        ctx,
+                        resp.iter().map(|song| song.id.clone().into()).collect(),
+                    )
+                    .await??;
+                println!(
+                    "Daemon response:\n{}",
+                    printing::song_list("Random songs added to queue", &resp, false)?
+                );
+                Ok(())
+            }
             Self::List { full, target } => {
                 if *full {
                     match target {
@@ -308,10 +388,107 @@ impl CommandHandler for LibraryCommand {
                 }
                 Ok(())
             }
+            Self::Genre { command } => command.handle(ctx, client).await,
+            Self::Artist { command } => command.handle(ctx, client).await,
+            Self::FailedAnalysis { command } => command.handle(ctx, client).await,
         }
     }
 }
 
+impl CommandHandler for ArtistCommand {
+    type Output = anyhow::Result<()>;
+
+    async fn handle(
+        &self,
+        ctx: tarpc::context::Context,
+        client: mecomp_core::rpc::MusicPlayerClient,
+    ) -> Self::Output {
+        match self {
+            Self::Merge { source, target } => {
+                let resp: Artist = client
+                    .library_artist_merge(
+                        ctx,
+                        Thing {
+                            tb: artist::TABLE_NAME.to_owned(),
+                            id: Id::String(source.to_owned()),
+                        },
+                        Thing {
+                            tb: artist::TABLE_NAME.to_owned(),
+                            id: Id::String(target.to_owned()),
+                        },
+                    )
+                    .await??;
+                println!("Daemon response:\nartists merged:\n{resp:#?}");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CommandHandler for FailedAnalysisCommand {
+    type Output = anyhow::Result<()>;
+
+    async fn handle(
+        &self,
+        ctx: tarpc::context::Context,
+        client: mecomp_core::rpc::MusicPlayerClient,
+    ) -> Self::Output {
+        match self {
+            Self::List => {
+                let resp: Box<[FailedAnalysis]> =
+                    client.library_list_failed_analysis(ctx).await??;
+                println!(
+                    "Daemon response:\n{}",
+                    printing::failed_analysis_list(&resp)?
+                );
+            }
+            Self::Retry { ids } => {
+                let ids = ids
+                    .iter()
+                    .map(|id| Thing {
+                        tb: failed_analysis::TABLE_NAME.to_owned(),
+                        id: Id::String(id.to_owned()),
+                    })
+                    .collect();
+                let resp: Result<(), _> = client.library_retry_failed_analysis(ctx, ids).await?;
+                if let Err(e) = resp {
+                    println!("Daemon response:\n{e}");
+                } else {
+                    println!("Daemon response:\nRetrying failed analysis");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CommandHandler for GenreCommand {
+    type Output = anyhow::Result<()>;
+
+    async fn handle(
+        &self,
+        ctx: tarpc::context::Context,
+        client: mecomp_core::rpc::MusicPlayerClient,
+    ) -> Self::Output {
+        match self {
+            Self::List => {
+                let resp: Vec<String> = client.library_list_genres(ctx).await??;
+                println!("Daemon response:\n{}", printing::genre_list(&resp)?);
+            }
+            Self::Songs { genre, limit } => {
+                let resp: Box<[Song]> = client
+                    .library_songs_by_genre(ctx, genre.clone(), *limit)
+                    .await??;
+                println!(
+                    "Daemon response:\n{}",
+                    printing::song_list(genre, &resp, false)?
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 impl CommandHandler for super::StatusCommand {
     type Output = anyhow::Result<()>;
 
@@ -391,8 +568,40 @@ impl CommandHandler for super::PlaybackCommand {
                 println!("Daemon response:\nprevious track started");
                 Ok(())
             }
-            Self::Seek { command } => command.handle(ctx, client).await,
+            Self::Seek {
+                command: Some(command),
+                ..
+            } => command.handle(ctx, client).await,
+            Self::Seek {
+                position: Some(position),
+                ..
+            } => {
+                let runtime = client
+                    .state_audio(ctx)
+                    .await?
+                    .and_then(|state| state.current_song)
+                    .map(|song| song.runtime);
+                let (seek_type, duration) = parse_seek_position(position, runtime)?;
+                client.playback_seek(ctx, seek_type, duration).await?;
+                match seek_type {
+                    SeekType::Absolute => println!(
+                        "Daemon response:\nseeked to position {:.2}s",
+                        duration.as_secs_f32()
+                    ),
+                    SeekType::RelativeForwards => println!(
+                        "Daemon response:\nseeked forward by {:.2}s",
+                        duration.as_secs_f32()
+                    ),
+                    SeekType::RelativeBackwards => println!(
+                        "Daemon response:\nseeked backward by {:.2}s",
+                        duration.as_secs_f32()
+                    ),
+                }
+                Ok(())
+            }
+            Self::Seek { .. } => bail!("either a position or a seek subcommand is required"),
             Self::Volume { command } => command.handle(ctx, client).await,
+            Self::Equalizer { command } => command.handle(ctx, client).await,
             Self::Repeat { mode } => {
                 let mode: mecomp_core::state::RepeatMode = (*mode).into();
                 client.playback_repeat(ctx, mode).await?;
@@ -404,6 +613,32 @@ impl CommandHandler for super::PlaybackCommand {
                 println!("Daemon response:\nqueue shuffled");
                 Ok(())
             }
+            Self::ReplayGain { mode } => {
+                let mode: mecomp_core::state::ReplayGainMode = (*mode).into();
+                client.playback_replaygain(ctx, mode).await?;
+                println!("Daemon response:\nReplayGain mode set to {mode}");
+                Ok(())
+            }
+            Self::Speed { speed } => {
+                client.playback_set_speed(ctx, *speed).await?;
+                println!("Daemon response:\nplayback speed set to {speed}x");
+                Ok(())
+            }
+            Self::ListDevices => {
+                let resp: Vec<String> = client.playback_list_output_devices(ctx).await?;
+                println!("Daemon response:\n{resp:#?}");
+                Ok(())
+            }
+            Self::SetDevice { name } => {
+                client.playback_set_output_device(ctx, name.clone()).await?;
+                println!("Daemon response:\nswitching output device to {name}");
+                Ok(())
+            }
+            Self::Levels => {
+                let resp: AudioLevels = client.playback_levels(ctx).await?;
+                println!("Daemon response:\n{resp}");
+                Ok(())
+            }
         }
     }
 }
@@ -482,6 +717,59 @@ impl CommandHandler for VolumeCommand {
                 println!("Daemon response:\nvolume unmuted");
                 Ok(())
             }
+            Self::Fade { volume, duration } => {
+                client
+                    .playback_fade_to_volume(ctx, *volume / 100.0, *duration)
+                    .await?;
+                println!(
+                    "Daemon response:\nfading volume to {volume} over {}",
+                    mecomp_core::format_duration(duration)
+                );
+                Ok(())
+            }
+            Self::CancelFade => {
+                client.playback_cancel_fade(ctx).await?;
+                println!("Daemon response:\nvolume fade cancelled");
+                Ok(())
+            }
+            Self::Crossfade { duration } => {
+                client.playback_crossfade(ctx, *duration).await?;
+                println!(
+                    "Daemon response:\ncrossfade duration set to {}",
+                    mecomp_core::format_duration(duration)
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+impl CommandHandler for EqualizerCommand {
+    type Output = anyhow::Result<()>;
+
+    async fn handle(
+        &self,
+        ctx: tarpc::context::Context,
+        client: mecomp_core::rpc::MusicPlayerClient,
+    ) -> Self::Output {
+        match self {
+            Self::Preset { name } => {
+                if client
+                    .playback_equalizer_preset(ctx, name.clone())
+                    .await?
+                    .is_some()
+                {
+                    println!("Daemon response:\nequalizer preset {name} applied");
+                } else {
+                    println!("Daemon response:\nno equalizer preset named {name}");
+                }
+                Ok(())
+            }
+            Self::Presets => {
+                let resp: Vec<String> = client.playback_equalizer_presets(ctx).await?;
+                println!("Daemon response:\n{resp:#?}");
+                Ok(())
+            }
         }
     }
 }
@@ -571,10 +859,36 @@ impl CommandHandler for QueueCommand {
                 client.queue_remove_range(ctx, *start..*end).await?;
                 println!("Daemon response:\nitems removed from queue");
             }
+            Self::Insert { index, id } => {
+                client
+                    .queue_insert_at(
+                        ctx,
+                        *index,
+                        Thing {
+                            tb: song::TABLE_NAME.to_owned(),
+                            id: Id::String(id.clone()),
+                        },
+                    )
+                    .await??;
+                println!("Daemon response:\nsong inserted into queue at index {index}");
+            }
+            Self::Move { from, to } => {
+                client.queue_move(ctx, *from, *to).await?;
+                println!("Daemon response:\nmoved item {from} to {to}");
+            }
             Self::Set { index } => {
                 client.queue_set_index(ctx, *index).await?;
                 println!("Daemon response:\ncurrent song set to index {index}");
             }
+            Self::Dedup => {
+                client.queue_dedup(ctx).await?;
+                println!("Daemon response:\nduplicate songs removed from queue");
+            }
+            Self::Sort { strategy } => {
+                let strategy: mecomp_core::state::SortStrategy = (*strategy).into();
+                client.queue_sort_by_analysis(ctx, strategy).await??;
+                println!("Daemon response:\nqueue sorted by {strategy}");
+            }
             Self::Pipe => {
                 let stdin = std::io::stdin();
                 if stdin.is_terminal() {
@@ -594,6 +908,10 @@ impl CommandHandler for QueueCommand {
                     println!("Daemon response:\nitems added to queue");
                 }
             }
+            Self::SaveAsPlaylist { name } => {
+                let resp = client.queue_save_as_playlist(ctx, name.clone()).await??;
+                println!("Daemon response:\nqueue saved as playlist {resp}");
+            }
         }
         Ok(())
     }
@@ -680,6 +998,27 @@ impl CommandHandler for super::PlaylistCommand {
                     .await??;
                 println!("Daemon response:\nsongs removed from playlist");
 
+                Ok(())
+            }
+            Self::Import { path, threshold } => {
+                // the daemon resolves paths relative to its own working directory
+                let path = path.canonicalize()?;
+                let resp = client
+                    .playlist_import_spotify_csv(ctx, path, *threshold)
+                    .await??;
+                println!("Daemon response:\n{resp:#?}");
+
+                Ok(())
+            }
+            Self::ExportAll { dir, no_overwrite } => {
+                // the daemon resolves paths relative to its own working directory,
+                // and the directory may not exist yet so we can't canonicalize it
+                let dir = std::env::current_dir()?.join(dir);
+                let resp = client
+                    .library_export_all_playlists(ctx, dir, !no_overwrite)
+                    .await??;
+                println!("Daemon response:\n{resp:#?}");
+
                 Ok(())
             }
         }
@@ -802,14 +1141,39 @@ impl CommandHandler for super::CollectionCommand {
                 println!("Daemon response:\n{resp:?}");
                 Ok(())
             }
-            Self::Recluster => {
+            Self::Recluster { clusters } => {
                 let resp: Result<&str, _> = client
-                    .library_recluster(ctx)
+                    .library_recluster(
+                        ctx,
+                        ReclusterRequest {
+                            n_clusters: *clusters,
+                        },
+                    )
                     .await?
                     .map(|()| "reclustering started");
                 println!("Daemon response:\n{resp:?}");
                 Ok(())
             }
+            Self::Stats { id } => {
+                let resp: Option<CollectionAnalysisStats> = client
+                    .library_collection_get_analysis_stats(
+                        ctx,
+                        Thing {
+                            tb: collection::TABLE_NAME.to_owned(),
+                            id: Id::String(id.clone()),
+                        },
+                    )
+                    .await??;
+                if let Some(stats) = resp {
+                    println!(
+                        "Daemon response:\n{}",
+                        printing::collection_analysis_stats(&stats)?
+                    );
+                } else {
+                    println!("Daemon response:\nNo songs in the collection have been analyzed");
+                }
+                Ok(())
+            }
             Self::Freeze { id, name } => {
                 let resp: Thing = client
                     .collection_freeze(
@@ -1,10 +1,14 @@
 pub mod implementations;
+pub mod playback;
 pub mod printing;
+pub mod search;
 pub mod utils;
 
 #[cfg(test)]
 mod smoke_tests;
 
+use std::{path::PathBuf, time::Duration};
+
 use clap::{Subcommand, ValueEnum};
 
 pub trait CommandHandler {
@@ -40,18 +44,22 @@ pub enum Command {
     Current { target: CurrentTarget },
     /// Rand (audio state)
     Rand { target: RandTarget },
-    /// Search (fuzzy keys)
-    #[clap(hide = true)]
+    /// Full-text search of the library, results are ordered by relevance
     Search {
-        /// What we're searching for
-        target: SearchTarget,
-
         /// The search query
         query: String,
 
-        /// The number of results to return
-        #[clap(default_value = "10")]
+        /// What we're searching for
+        #[clap(long = "type", value_enum, default_value = "all")]
+        kind: SearchTarget,
+
+        /// The number of results to return (of each type)
+        #[clap(long, default_value = "10")]
         limit: u32,
+
+        /// How to print the results, `plain` can be piped into other commands
+        #[clap(long, value_enum, default_value = "plain")]
+        format: OutputFormat,
     },
     /// Playback control
     Playback {
@@ -87,13 +95,46 @@ pub enum LibraryCommand {
     /// Analyze the library
     Analyze,
     /// Recluster the library
-    Recluster,
+    Recluster {
+        /// The number of collections to create, chosen automatically if not given
+        #[clap(long)]
+        clusters: Option<u32>,
+    },
     /// Get brief library info
     Brief,
     /// Get detailed library info
     Full,
     /// Get library health info
     Health,
+    /// Get the metrics of recent library scans, broken down by library path
+    ScanHistory,
+    /// Check that the files of songs in the library still exist and can be decoded
+    Verify {
+        /// The ids of the songs to verify, if none are given every song is verified
+        ids: Vec<String>,
+    },
+    /// List the songs whose files don't exist anymore (e.g. because they were moved or deleted)
+    DeadLinks {
+        /// Delete the songs from the library, instead of just listing them
+        #[clap(long)]
+        remove: bool,
+    },
+    /// List the groups of songs that are likely duplicates of each other
+    /// (same title and artists, and runtimes within 2 seconds of each other)
+    Duplicates {
+        /// Group songs with matching acoustic fingerprints instead, which also catches renamed, re-tagged, or re-encoded duplicates
+        /// (songs that haven't been fingerprinted yet are fingerprinted first, which can take a while)
+        #[clap(long)]
+        acoustic: bool,
+    },
+    /// Merge duplicate songs into the song to keep (adding up their play counts, etc.), then delete them
+    RemoveDuplicates {
+        /// The id of the song to keep
+        keep: String,
+        /// The ids of the duplicates to remove
+        #[clap(required = true)]
+        remove: Vec<String>,
+    },
     /// List of stuff in the library
     List {
         /// List detailed info
@@ -109,6 +150,95 @@ pub enum LibraryCommand {
         /// The id of the item
         id: String,
     },
+    /// List the songs released within a range of years, ordered by release year then title.
+    /// Songs without a release year are excluded.
+    Songs {
+        /// The earliest release year (inclusive), if omitted there is no lower bound
+        #[clap(long, required_unless_present = "year_to")]
+        year_from: Option<i32>,
+        /// The latest release year (inclusive), if omitted there is no upper bound
+        #[clap(long)]
+        year_to: Option<i32>,
+        /// The maximum number of songs to return
+        #[clap(long, default_value = "100")]
+        limit: usize,
+    },
+    /// List the songs with a tempo within a range of BPM, ordered by tempo.
+    /// Songs that haven't been analyzed are excluded.
+    Bpm {
+        /// The slowest tempo (inclusive)
+        #[clap(long)]
+        min: f64,
+        /// The fastest tempo (inclusive)
+        #[clap(long)]
+        max: f64,
+        /// The maximum number of songs to return
+        #[clap(long, default_value = "100")]
+        limit: usize,
+    },
+    /// Add a random sample of songs from the library to the queue
+    Random {
+        /// The number of songs to add
+        #[clap(long, default_value = "20")]
+        limit: usize,
+        /// Seed for the random sample, the same seed gives the same songs (as long as the library doesn't change)
+        #[clap(long)]
+        seed: Option<u64>,
+    },
+    /// Genre commands
+    Genre {
+        #[clap(subcommand)]
+        command: GenreCommand,
+    },
+    /// Artist commands
+    Artist {
+        #[clap(subcommand)]
+        command: ArtistCommand,
+    },
+    /// Commands for songs that failed to be analyzed
+    FailedAnalysis {
+        #[clap(subcommand)]
+        command: FailedAnalysisCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FailedAnalysisCommand {
+    /// List the songs that failed to be analyzed
+    List,
+    /// Retry the analysis of songs that failed to be analyzed,
+    /// even if they've already failed too many times to be retried automatically
+    Retry {
+        /// The ids of the failed analysis records to retry
+        #[clap(required = true)]
+        ids: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ArtistCommand {
+    /// Merge an artist into another (e.g. to fix an artist that was split by inconsistent tagging),
+    /// the songs and albums of the source artist are given to the target artist and the source artist is deleted
+    Merge {
+        /// The id of the artist to merge (this artist will be deleted)
+        source: String,
+        /// The id of the artist to merge into
+        target: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GenreCommand {
+    /// List the genres of the songs in the library
+    List,
+    /// List the songs with a given genre
+    Songs {
+        /// The genre (case sensitive)
+        genre: String,
+        /// The maximum number of songs to return
+        #[clap(default_value = "100")]
+        limit: usize,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
@@ -141,10 +271,24 @@ pub enum RandTarget {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
 pub enum SearchTarget {
+    /// Songs, albums, and artists
     All,
-    Artist,
-    Album,
-    Song,
+    #[value(alias = "artist")]
+    Artists,
+    #[value(alias = "album")]
+    Albums,
+    #[value(alias = "song")]
+    Songs,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    /// One result per line, with the ids first
+    Plain,
+    /// Aligned columns, with a header row
+    Table,
+    /// The results as JSON, for other programs to parse
+    Json,
 }
 
 #[derive(Debug, Subcommand)]
@@ -174,15 +318,28 @@ pub enum PlaybackCommand {
     /// Previous
     Previous,
     /// Seek
+    #[clap(args_conflicts_with_subcommands = true)]
     Seek {
+        /// The position to seek to, one of:
+        /// `MM:SS` or `HH:MM:SS` (absolute),
+        /// `+10s` or `-5s` (relative forwards or backwards),
+        /// `75%` (percentage of the current song)
+        #[clap(allow_hyphen_values = true)]
+        position: Option<String>,
         #[clap(subcommand)]
-        command: SeekCommand,
+        command: Option<SeekCommand>,
     },
     /// Set volume
     Volume {
         #[clap(subcommand)]
         command: VolumeCommand,
     },
+    /// Control the equalizer
+    #[clap(name = "eq")]
+    Equalizer {
+        #[clap(subcommand)]
+        command: EqualizerCommand,
+    },
     /// Set repeat mode
     Repeat {
         /// The repeat mode to set to (none, once, continuous)
@@ -190,6 +347,26 @@ pub enum PlaybackCommand {
     },
     /// Shuffle the queue
     Shuffle,
+    /// Set how songs are levelled using their ReplayGain
+    #[clap(name = "replaygain")]
+    ReplayGain {
+        /// The ReplayGain mode to set to (off, track, album)
+        mode: ReplayGainMode,
+    },
+    /// Set the playback speed
+    Speed {
+        /// The speed to play at, 1.0 is normal speed (0.5 to 2.0)
+        speed: f32,
+    },
+    /// List the audio output devices
+    ListDevices,
+    /// Switch the audio output to another device
+    SetDevice {
+        /// The name of the output device (see `list-devices`)
+        name: String,
+    },
+    /// Show the peak and RMS levels of the audio being played
+    Levels,
 }
 
 #[derive(Debug, Subcommand)]
@@ -237,6 +414,33 @@ pub enum VolumeCommand {
     Mute,
     /// Unmute the volume
     Unmute,
+    /// Gradually change the volume over a period of time
+    Fade {
+        /// The volume to fade to (0 is mute, 100 is max)
+        volume: f32,
+        /// How long the fade should take (e.g. 30s, 1m, 00:30)
+        #[clap(value_parser = mecomp_core::parse_duration)]
+        duration: Duration,
+    },
+    /// Cancel the volume fade in progress
+    CancelFade,
+    /// Set how long the end of a song overlaps with the start of the next one
+    Crossfade {
+        /// How long the crossfade should take (e.g. 5s, 00:05), 0 disables it
+        #[clap(value_parser = mecomp_core::parse_duration)]
+        duration: Duration,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EqualizerCommand {
+    /// Apply an equalizer preset
+    Preset {
+        /// The name of the preset (see `presets`)
+        name: String,
+    },
+    /// List the equalizer presets
+    Presets,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
@@ -256,6 +460,23 @@ impl From<RepeatMode> for mecomp_core::state::RepeatMode {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum ReplayGainMode {
+    Off,
+    Track,
+    Album,
+}
+
+impl From<ReplayGainMode> for mecomp_core::state::ReplayGainMode {
+    fn from(mode: ReplayGainMode) -> Self {
+        match mode {
+            ReplayGainMode::Off => Self::Off,
+            ReplayGainMode::Track => Self::Track,
+            ReplayGainMode::Album => Self::Album,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum QueueCommand {
     /// Clear the queue
@@ -276,18 +497,44 @@ pub enum QueueCommand {
         /// The end index of the range to remove
         end: usize,
     },
+    /// Insert a song into the queue at the given index, without changing the current song
+    Insert {
+        /// The index to insert the song at (songs inserted past the end are appended)
+        index: usize,
+        /// The id of the song
+        id: String,
+    },
+    /// Move a song to another position in the queue, without changing the current song
+    Move {
+        /// The index of the song to move
+        from: usize,
+        /// The index to move the song to
+        to: usize,
+    },
     /// set the current song to the given index
     Set {
         /// The index to set the current song to
         index: usize,
     },
+    /// Remove duplicate songs from the queue, keeping the first occurrence of each
+    Dedup,
+    /// Sort the queue using the audio analysis of its songs, without changing the current song
+    Sort {
+        /// How to sort the queue
+        strategy: SortStrategy,
+    },
     /// Add a list of items to the queue (from a pipe)
     /// ex:
     /// ```sh, ignore
-    /// mecomp-cli search all "the beatles" -q | mecomp-cli queue pipe
+    /// mecomp-cli search "the beatles" | mecomp-cli queue pipe
     /// ```
     /// This will add all the results of the search to the queue
     Pipe,
+    /// Save the queue as a new playlist
+    SaveAsPlaylist {
+        /// The name of the new playlist
+        name: String,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
@@ -299,6 +546,26 @@ pub enum QueueAddTarget {
     Collection,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum SortStrategy {
+    /// From the slowest song to the fastest
+    Tempo,
+    /// From the quietest song to the loudest
+    Energy,
+    /// From the current song to the song that sounds the least like it
+    Similarity,
+}
+
+impl From<SortStrategy> for mecomp_core::state::SortStrategy {
+    fn from(strategy: SortStrategy) -> Self {
+        match strategy {
+            SortStrategy::Tempo => Self::ByTempo,
+            SortStrategy::Energy => Self::ByEnergy,
+            SortStrategy::Similarity => Self::BySimilarityToCurrentSong,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum PlaylistCommand {
     /// List playlists
@@ -332,6 +599,23 @@ pub enum PlaylistCommand {
         /// The id of the songs(s) to remove
         item_ids: Vec<String>,
     },
+    /// Import a playlist from a Spotify or Apple Music CSV export
+    Import {
+        /// The path to the CSV file, the playlist will be named after the file
+        path: PathBuf,
+        /// How similar (0.0 to 1.0) a track must be to a song in the library to be considered a match
+        #[clap(long, default_value = "0.85")]
+        threshold: f64,
+    },
+    /// Export every playlist to an M3U file (named after the playlist) in the given directory
+    ExportAll {
+        /// The directory to export the playlists to, it will be created if it doesn't exist
+        #[clap(long)]
+        dir: PathBuf,
+        /// Skip playlists whose file already exists, instead of overwriting it
+        #[clap(long)]
+        no_overwrite: bool,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
@@ -366,7 +650,7 @@ pub enum PlaylistAddCommand {
     /// Add a list of items to the playlist (from a pipe)
     /// ex:
     /// ```sh, ignore
-    /// mecomp-cli search all "the beatles" -q | mecomp-cli playlist add pipe
+    /// mecomp-cli search "the beatles" | mecomp-cli playlist add pipe
     /// ```
     /// This will add all the results of the search to the playlist
     Pipe {
@@ -385,7 +669,16 @@ pub enum CollectionCommand {
         id: String,
     },
     /// Recluster collections
-    Recluster,
+    Recluster {
+        /// The number of collections to create, chosen automatically if not given
+        #[clap(long)]
+        clusters: Option<u32>,
+    },
+    /// Get summary statistics (mean tempo, loudness, etc.) of the analyzed songs in a collection
+    Stats {
+        /// The id of the collection
+        id: String,
+    },
     /// Freeze a collection
     Freeze {
         /// The id of the collection
@@ -428,7 +721,7 @@ pub enum RadioCommand {
     /// Add a list of items to the radio (from a pipe)
     /// ex:
     /// ```sh, ignore
-    /// mecomp-cli search all "the beatles" -q | mecomp-cli radio pipe
+    /// mecomp-cli search "the beatles" | mecomp-cli radio pipe
     /// ```
     /// This will add all the results of the search to the radio
     Pipe {
@@ -0,0 +1,125 @@
+//! Parsing of human-readable seek positions for the `playback seek` command.
+
+use std::time::Duration;
+
+use mecomp_core::state::SeekType;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("no position given")]
+    Empty,
+    #[error("invalid time \"{0}\", expected MM:SS, HH:MM:SS, or a number of seconds (e.g. 10s)")]
+    InvalidTime(String),
+    #[error("invalid percentage \"{0}\", expected a number between 0 and 100 (e.g. 75%)")]
+    InvalidPercentage(String),
+    #[error("can't seek to a percentage when nothing is playing")]
+    NothingPlaying,
+}
+
+/// Parse a seek position.
+///
+/// Accepted formats are:
+/// - `MM:SS` or `HH:MM:SS`: seek to the given position
+/// - `+10s` or `-5s`: seek forwards or backwards by the given amount (any of the time formats can follow the sign)
+/// - `75%`: seek to the given percentage of the current song, requires the `runtime` of the current song
+///
+/// A bare number (optionally suffixed with `s`, `m`, or `h`) is also accepted as a time.
+///
+/// # Errors
+///
+/// Returns an error if the position isn't in one of the above formats,
+/// or if it's a percentage and there is no current song (`runtime` is `None`).
+pub fn parse_seek_position(
+    s: &str,
+    runtime: Option<Duration>,
+) -> Result<(SeekType, Duration), ParseError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    if let Some(percentage) = s.strip_suffix('%') {
+        let percentage = percentage
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|p| (0.0..=100.0).contains(p))
+            .ok_or_else(|| ParseError::InvalidPercentage(s.to_string()))?;
+        let runtime = runtime.ok_or(ParseError::NothingPlaying)?;
+        return Ok((SeekType::Absolute, runtime.mul_f64(percentage / 100.0)));
+    }
+
+    if let Some(amount) = s.strip_prefix('+') {
+        return Ok((SeekType::RelativeForwards, parse_time(amount)?));
+    }
+    if let Some(amount) = s.strip_prefix('-') {
+        return Ok((SeekType::RelativeBackwards, parse_time(amount)?));
+    }
+
+    Ok((SeekType::Absolute, parse_time(s)?))
+}
+
+/// Parse a time given as `MM:SS`, `HH:MM:SS`, or a number of seconds (optionally suffixed with `s`, `m`, or `h`).
+///
+/// The seconds may be fractional (e.g. `1:30.5`).
+fn parse_time(s: &str) -> Result<Duration, ParseError> {
+    mecomp_core::parse_duration(s).map_err(|_| ParseError::InvalidTime(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::minutes_seconds("1:30", SeekType::Absolute, 90.0)]
+    #[case::hours_minutes_seconds("1:02:03", SeekType::Absolute, 3723.0)]
+    #[case::fractional_seconds("0:01.5", SeekType::Absolute, 1.5)]
+    #[case::long_minutes("90:00", SeekType::Absolute, 5400.0)]
+    #[case::bare_seconds("42", SeekType::Absolute, 42.0)]
+    #[case::forwards("+10s", SeekType::RelativeForwards, 10.0)]
+    #[case::backwards("-5s", SeekType::RelativeBackwards, 5.0)]
+    #[case::forwards_minutes("+2m", SeekType::RelativeForwards, 120.0)]
+    #[case::backwards_time("-1:00", SeekType::RelativeBackwards, 60.0)]
+    #[case::whitespace(" +10s ", SeekType::RelativeForwards, 10.0)]
+    #[case::percentage("50%", SeekType::Absolute, 100.0)]
+    #[case::percentage_start("0%", SeekType::Absolute, 0.0)]
+    #[case::percentage_end("100%", SeekType::Absolute, 200.0)]
+    fn test_parse_seek_position(
+        #[case] input: &str,
+        #[case] expected_type: SeekType,
+        #[case] expected_secs: f64,
+    ) {
+        let runtime = Some(Duration::from_secs(200));
+        assert_eq!(
+            parse_seek_position(input, runtime),
+            Ok((expected_type, Duration::from_secs_f64(expected_secs)))
+        );
+    }
+
+    #[rstest]
+    #[case::empty("", ParseError::Empty)]
+    #[case::garbage("abc", ParseError::InvalidTime("abc".into()))]
+    #[case::too_many_parts("1:2:3:4", ParseError::InvalidTime("1:2:3:4".into()))]
+    #[case::seconds_overflow("1:60", ParseError::InvalidTime("1:60".into()))]
+    #[case::minutes_overflow("1:60:00", ParseError::InvalidTime("1:60:00".into()))]
+    #[case::double_sign("+-5s", ParseError::InvalidTime("-5s".into()))]
+    #[case::percentage_too_large("150%", ParseError::InvalidPercentage("150%".into()))]
+    #[case::percentage_negative("-5%", ParseError::InvalidPercentage("-5%".into()))]
+    fn test_parse_seek_position_invalid(#[case] input: &str, #[case] expected: ParseError) {
+        assert_eq!(
+            parse_seek_position(input, Some(Duration::from_secs(200))),
+            Err(expected)
+        );
+    }
+
+    #[test]
+    fn test_parse_seek_position_percentage_nothing_playing() {
+        assert_eq!(
+            parse_seek_position("50%", None),
+            Err(ParseError::NothingPlaying)
+        );
+    }
+}
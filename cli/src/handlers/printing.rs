@@ -2,12 +2,19 @@
 
 use std::fmt::Write;
 
-use mecomp_core::state::StateAudio;
+use mecomp_core::state::{
+    library::{
+        CollectionAnalysisStats, DuplicateGroup, SongVerificationResult, VerificationStatus,
+    },
+    StateAudio,
+};
 use mecomp_storage::db::schemas::{
     album::{Album, AlbumBrief},
     artist::{Artist, ArtistBrief},
     collection::CollectionBrief,
+    failed_analysis::FailedAnalysis,
     playlist::PlaylistBrief,
+    scan_history::ScanMetrics,
     song::{Song, SongBrief},
     Thing,
 };
@@ -86,6 +93,60 @@ pub fn song_list(prefix: &str, songs: &[Song], indexed: bool) -> Result<String,
     Ok(output)
 }
 
+/// Print `rows` as a table under `headers`, with every column as wide as its widest cell.
+fn table<const N: usize>(
+    prefix: &str,
+    headers: [&str; N],
+    rows: &[[String; N]],
+) -> Result<String, std::fmt::Error> {
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::new();
+    let mut write_row = |cells: &[&str]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(output, "\t{}", line.trim_end())
+    };
+
+    write_row(&headers)?;
+    write_row(
+        &widths
+            .map(|width| "-".repeat(width))
+            .each_ref()
+            .map(String::as_str),
+    )?;
+    for row in rows {
+        write_row(&row.each_ref().map(String::as_str))?;
+    }
+
+    Ok(format!("{prefix}:\n{output}"))
+}
+
+pub fn song_table(prefix: &str, songs: &[Song]) -> Result<String, std::fmt::Error> {
+    let rows: Vec<_> = songs
+        .iter()
+        .map(|song| {
+            [
+                song.title.to_string(),
+                song.artist.as_slice().join(", "),
+                song.album.to_string(),
+                song.id.to_string(),
+            ]
+        })
+        .collect();
+
+    table(prefix, ["Title", "Artist", "Album", "Id"], &rows)
+}
+
 pub fn song_brief_list(prefix: &str, songs: &[SongBrief]) -> Result<String, std::fmt::Error> {
     let mut output = String::new();
 
@@ -114,6 +175,21 @@ pub fn album_list(prefix: &str, albums: &[Album]) -> Result<String, std::fmt::Er
     Ok(output)
 }
 
+pub fn album_table(prefix: &str, albums: &[Album]) -> Result<String, std::fmt::Error> {
+    let rows: Vec<_> = albums
+        .iter()
+        .map(|album| {
+            [
+                album.title.to_string(),
+                album.artist.as_slice().join(", "),
+                album.id.to_string(),
+            ]
+        })
+        .collect();
+
+    table(prefix, ["Title", "Artist", "Id"], &rows)
+}
+
 pub fn album_brief_list(prefix: &str, albums: &[AlbumBrief]) -> Result<String, std::fmt::Error> {
     let mut output = String::new();
 
@@ -142,6 +218,15 @@ pub fn artist_list(prefix: &str, artists: &[Artist]) -> Result<String, std::fmt:
     Ok(output)
 }
 
+pub fn artist_table(prefix: &str, artists: &[Artist]) -> Result<String, std::fmt::Error> {
+    let rows: Vec<_> = artists
+        .iter()
+        .map(|artist| [artist.name.to_string(), artist.id.to_string()])
+        .collect();
+
+    table(prefix, ["Name", "Id"], &rows)
+}
+
 pub fn artist_brief_list(prefix: &str, artists: &[ArtistBrief]) -> Result<String, std::fmt::Error> {
     let mut output = String::new();
 
@@ -192,6 +277,133 @@ pub fn playlist_collection_list(
     Ok(output)
 }
 
+pub fn genre_list(genres: &[String]) -> Result<String, std::fmt::Error> {
+    let mut output = String::new();
+
+    writeln!(output, "Genres:")?;
+
+    for genre in genres {
+        writeln!(output, "\t\"{genre}\"")?;
+    }
+
+    Ok(output)
+}
+
+pub fn failed_analysis_list(failures: &[FailedAnalysis]) -> Result<String, std::fmt::Error> {
+    let mut output = String::new();
+
+    writeln!(output, "Failed Analyses:")?;
+
+    for failure in failures {
+        writeln!(
+            output,
+            "\t{}: song: {}, attempts: {}, last attempted: {}, error: \"{}\"",
+            failure.id,
+            failure.song_id,
+            failure.attempt_count,
+            failure.last_attempted,
+            failure.error
+        )?;
+    }
+
+    Ok(output)
+}
+
+pub fn collection_analysis_stats(
+    stats: &CollectionAnalysisStats,
+) -> Result<String, std::fmt::Error> {
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "Collection Stats ({} analyzed songs):",
+        stats.analyzed_songs
+    )?;
+    writeln!(
+        output,
+        "\tTempo: {:.1} BPM (std. dev. {:.1})",
+        stats.mean_bpm, stats.tempo_std
+    )?;
+    writeln!(output, "\tLoudness: {:.1} dB", stats.mean_loudness)?;
+    writeln!(
+        output,
+        "\tChroma centroid: [{}]",
+        stats
+            .chroma_centroid
+            .iter()
+            .map(|v| format!("{v:.3}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )?;
+
+    Ok(output)
+}
+
+pub fn scan_history(history: &[ScanMetrics]) -> Result<String, std::fmt::Error> {
+    let mut output = String::new();
+
+    writeln!(output, "Scan History:")?;
+
+    for metrics in history {
+        writeln!(
+            output,
+            "\t{}: path: {}, found: {}, scanned: {}, errors: {}, took: {}ms, health: {:?}",
+            metrics.scanned_at,
+            metrics.path.display(),
+            metrics.files_found,
+            metrics.files_scanned,
+            metrics.errors,
+            metrics.duration_ms,
+            metrics.health()
+        )?;
+    }
+
+    Ok(output)
+}
+
+pub fn verification_results(results: &[SongVerificationResult]) -> Result<String, std::fmt::Error> {
+    let mut output = String::new();
+
+    let failures = results
+        .iter()
+        .filter(|result| result.status != VerificationStatus::Ok)
+        .collect::<Vec<_>>();
+
+    writeln!(
+        output,
+        "Verified {} songs, {} failed:",
+        results.len(),
+        failures.len()
+    )?;
+
+    for failure in failures {
+        writeln!(output, "\t{}: {:?}", failure.id, failure.status)?;
+    }
+
+    Ok(output)
+}
+
+pub fn duplicate_groups(groups: &[DuplicateGroup]) -> Result<String, std::fmt::Error> {
+    let mut output = String::new();
+
+    writeln!(output, "Found {} groups of duplicate songs:", groups.len())?;
+
+    for group in groups {
+        writeln!(output, "\t{}:", group.songs[0].title)?;
+        for song in &group.songs {
+            writeln!(
+                output,
+                "\t\t{}: {} ({:?})",
+                song.id,
+                song.path.display(),
+                song.runtime
+            )?;
+        }
+    }
+
+    Ok(output)
+}
+
 pub fn thing_list(things: &[Thing]) -> Result<String, std::fmt::Error> {
     let mut output = String::new();
 
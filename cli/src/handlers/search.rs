@@ -0,0 +1,233 @@
+//! The `search` command, a full-text search of the library through the daemon's `library_search` endpoint.
+
+use mecomp_core::rpc::{MusicPlayerClient, SearchRequest, SearchResponse};
+
+use super::{printing, OutputFormat, SearchTarget};
+
+impl SearchTarget {
+    const fn songs(self) -> bool {
+        matches!(self, Self::All | Self::Songs)
+    }
+
+    const fn albums(self) -> bool {
+        matches!(self, Self::All | Self::Albums)
+    }
+
+    const fn artists(self) -> bool {
+        matches!(self, Self::All | Self::Artists)
+    }
+}
+
+/// Search the library for `query`, and print the results of the given `kind` in the given `format`.
+///
+/// # Errors
+///
+/// Returns an error if the daemon can't be reached, or if the results can't be formatted.
+pub async fn search(
+    ctx: tarpc::context::Context,
+    client: MusicPlayerClient,
+    query: &str,
+    kind: SearchTarget,
+    limit: u32,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let request = SearchRequest {
+        query: query.to_string(),
+        limit,
+    };
+    let response: SearchResponse = client.library_search(ctx, request).await?;
+    print!("{}", format_results(&response, kind, format)?);
+    Ok(())
+}
+
+/// Format the results of the given `kind` in the given `format`.
+///
+/// The `plain` format is what the `pipe` commands (e.g. `queue pipe`) read the ids from.
+///
+/// # Errors
+///
+/// Returns an error if the results can't be formatted.
+pub fn format_results(
+    response: &SearchResponse,
+    kind: SearchTarget,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let mut sections = Vec::new();
+
+    match format {
+        OutputFormat::Plain | OutputFormat::Table => {
+            let table = format == OutputFormat::Table;
+            if kind.songs() {
+                sections.push(if table {
+                    printing::song_table("Songs", &response.songs)?
+                } else {
+                    printing::song_list("Songs", &response.songs, false)?
+                });
+            }
+            if kind.albums() {
+                sections.push(if table {
+                    printing::album_table("Albums", &response.albums)?
+                } else {
+                    printing::album_list("Albums", &response.albums)?
+                });
+            }
+            if kind.artists() {
+                sections.push(if table {
+                    printing::artist_table("Artists", &response.artists)?
+                } else {
+                    printing::artist_list("Artists", &response.artists)?
+                });
+            }
+
+            if table {
+                Ok(format!("{}\n", sections.join("\n")))
+            } else {
+                Ok(format!("Daemon response:\n{}\n", sections.join("\n")))
+            }
+        }
+        OutputFormat::Json => {
+            let json = match kind {
+                SearchTarget::All => serde_json::to_string_pretty(response)?,
+                SearchTarget::Songs => serde_json::to_string_pretty(&response.songs)?,
+                SearchTarget::Albums => serde_json::to_string_pretty(&response.albums)?,
+                SearchTarget::Artists => serde_json::to_string_pretty(&response.artists)?,
+            };
+            Ok(format!("{json}\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mecomp_storage::db::schemas::{album::Album, artist::Artist, song::Song};
+    use one_or_many::OneOrMany;
+    use pretty_assertions::assert_eq;
+    use rstest::{fixture, rstest};
+    use std::time::Duration;
+    use surrealdb::sql::Thing;
+
+    #[fixture]
+    fn response() -> SearchResponse {
+        let song = Song {
+            id: Thing::from(("song", "abc")),
+            title: "Yesterday".into(),
+            artist: OneOrMany::One("The Beatles".into()),
+            album_artist: OneOrMany::One("The Beatles".into()),
+            album: "Help!".into(),
+            genre: OneOrMany::One("Rock".into()),
+            runtime: Duration::from_secs(125),
+            track: Some(13),
+            disc: Some(1),
+            release_year: Some(1965),
+            extension: "mp3".into(),
+            path: "yesterday.mp3".into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
+        };
+        let album = Album {
+            id: Thing::from(("album", "def")),
+            title: song.album.clone(),
+            artist: song.album_artist.clone(),
+            release: song.release_year,
+            runtime: song.runtime,
+            song_count: 1,
+            discs: 1,
+            genre: song.genre.clone(),
+            description: None,
+        };
+        let artist = Artist {
+            id: Thing::from(("artist", "ghi")),
+            name: "The Beatles".into(),
+            runtime: song.runtime,
+            album_count: 1,
+            song_count: 1,
+            biography: None,
+        };
+
+        SearchResponse {
+            songs: vec![song].into(),
+            albums: vec![album].into(),
+            artists: vec![artist].into(),
+        }
+    }
+
+    #[rstest]
+    fn test_format_plain(response: SearchResponse) {
+        assert_eq!(
+            format_results(&response, SearchTarget::All, OutputFormat::Plain).unwrap(),
+            "Daemon response:
+Songs:
+\tsong:abc: \"Yesterday\"
+
+Albums:
+\talbum:def: \"Help!\" (by: One(\"The Beatles\")),
+
+Artists:
+\tartist:ghi: \"The Beatles\"
+
+"
+        );
+        assert_eq!(
+            format_results(&response, SearchTarget::Albums, OutputFormat::Plain).unwrap(),
+            "Daemon response:
+Albums:
+\talbum:def: \"Help!\" (by: One(\"The Beatles\")),
+
+"
+        );
+    }
+
+    #[rstest]
+    fn test_format_table(response: SearchResponse) {
+        assert_eq!(
+            format_results(&response, SearchTarget::All, OutputFormat::Table).unwrap(),
+            "Songs:
+\tTitle      Artist       Album  Id
+\t---------  -----------  -----  --------
+\tYesterday  The Beatles  Help!  song:abc
+
+Albums:
+\tTitle  Artist       Id
+\t-----  -----------  ---------
+\tHelp!  The Beatles  album:def
+
+Artists:
+\tName         Id
+\t-----------  ----------
+\tThe Beatles  artist:ghi
+
+"
+        );
+        assert_eq!(
+            format_results(&response, SearchTarget::Songs, OutputFormat::Table).unwrap(),
+            "Songs:
+\tTitle      Artist       Album  Id
+\t---------  -----------  -----  --------
+\tYesterday  The Beatles  Help!  song:abc
+
+"
+        );
+    }
+
+    #[rstest]
+    fn test_format_json(response: SearchResponse) {
+        let all = format_results(&response, SearchTarget::All, OutputFormat::Json).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SearchResponse>(&all).unwrap(),
+            response
+        );
+
+        let artists = format_results(&response, SearchTarget::Artists, OutputFormat::Json).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Box<[Artist]>>(&artists).unwrap(),
+            response.artists
+        );
+    }
+}
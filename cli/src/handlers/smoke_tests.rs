@@ -16,10 +16,11 @@ use surrealdb::{engine::local::Db, sql::Thing, Surreal};
 use tempfile::tempdir;
 
 use crate::handlers::{
-    CollectionCommand, Command, CommandHandler, CurrentTarget, LibraryCommand, LibraryGetTarget,
-    LibraryListTarget, PlaybackCommand, PlaylistAddCommand, PlaylistCommand, PlaylistGetMethod,
-    QueueAddTarget, QueueCommand, RadioCommand, RandTarget, RepeatMode, SearchTarget, SeekCommand,
-    StatusCommand, VolumeCommand,
+    ArtistCommand, CollectionCommand, Command, CommandHandler, CurrentTarget, EqualizerCommand,
+    FailedAnalysisCommand, GenreCommand, LibraryCommand, LibraryGetTarget, LibraryListTarget,
+    OutputFormat, PlaybackCommand, PlaylistAddCommand, PlaylistCommand, PlaylistGetMethod,
+    QueueAddTarget, QueueCommand, RadioCommand, RandTarget, RepeatMode, ReplayGainMode,
+    SearchTarget, SeekCommand, SortStrategy, StatusCommand, VolumeCommand,
 };
 
 #[test]
@@ -32,6 +33,55 @@ fn test_cli_args_parse() {
     assert!(flags.subcommand.is_none());
 }
 
+#[rstest]
+#[case(vec!["seek", "1:30"], Some("1:30"), false)]
+#[case(vec!["seek", "-5s"], Some("-5s"), false)]
+#[case(vec!["seek", "+10s"], Some("+10s"), false)]
+#[case(vec!["seek", "75%"], Some("75%"), false)]
+#[case(vec!["seek", "forward", "10"], None, true)]
+fn test_cli_args_parse_seek(
+    #[case] args: Vec<&str>,
+    #[case] expected_position: Option<&str>,
+    #[case] expected_subcommand: bool,
+) {
+    let args = ["mecomp-cli", "playback"].into_iter().chain(args);
+    let flags = crate::Flags::try_parse_from(args).unwrap();
+    let Some(Command::Playback {
+        command: PlaybackCommand::Seek { position, command },
+    }) = flags.subcommand
+    else {
+        panic!("expected a seek command");
+    };
+    assert_eq!(position.as_deref(), expected_position);
+    assert_eq!(command.is_some(), expected_subcommand);
+}
+
+#[rstest]
+#[case(vec!["--year-from", "1970", "--year-to", "1979"], Some((Some(1970), Some(1979))))]
+#[case(vec!["--year-from", "1970"], Some((Some(1970), None)))]
+#[case(vec!["--year-to", "1979"], Some((None, Some(1979))))]
+#[case(vec![], None)]
+fn test_cli_args_parse_library_songs(
+    #[case] args: Vec<&str>,
+    #[case] expected: Option<(Option<i32>, Option<i32>)>,
+) {
+    let args = ["mecomp-cli", "library", "songs"].into_iter().chain(args);
+    let flags = crate::Flags::try_parse_from(args);
+    let Some(expected) = expected else {
+        assert!(flags.is_err(), "at least one bound should be required");
+        return;
+    };
+    let Some(Command::Library {
+        command: LibraryCommand::Songs {
+            year_from, year_to, ..
+        },
+    }) = flags.unwrap().subcommand
+    else {
+        panic!("expected a library songs command");
+    };
+    assert_eq!((year_from, year_to), expected);
+}
+
 /// the id used for all the items in this fake library
 pub fn item_id() -> &'static str {
     "01J1K5B6RJ84WJXCWYJ5WNE12E"
@@ -62,6 +112,14 @@ async fn db_with_state() -> Arc<Surreal<Db>> {
         release_year: Some(2021),
         extension: "mp3".into(),
         path: "test.mp3".into(),
+        replaygain_track_gain: None,
+        replaygain_album_gain: None,
+        play_count: 0,
+        skip_count: 0,
+        last_played: None,
+        rating: None,
+        favorite: false,
+        bookmark_positions: Vec::new(),
     };
     let analysis = Analysis {
         id: analysis_id.clone().into(),
@@ -73,6 +131,7 @@ async fn db_with_state() -> Arc<Surreal<Db>> {
         runtime: song.runtime,
         album_count: 1,
         song_count: 1,
+        biography: None,
     };
     let album = Album {
         id: album_id.clone().into(),
@@ -83,6 +142,7 @@ async fn db_with_state() -> Arc<Surreal<Db>> {
         song_count: 1,
         discs: 1,
         genre: song.genre.clone(),
+        description: None,
     };
     let collection = Collection {
         id: collection_id.clone().into(),
@@ -133,7 +193,7 @@ async fn client() -> MusicPlayerClient {
 
     let db = db_with_state().await;
     let mut settings: Settings = Default::default();
-    settings.daemon.library_paths = vec![music_dir.path().to_path_buf()].into_boxed_slice();
+    settings.daemon.library_paths = vec![music_dir.path().to_path_buf().into()].into_boxed_slice();
     let settings = Arc::new(settings);
     let audio_kernel = AudioKernelSender::start();
 
@@ -162,11 +222,47 @@ async fn test_stop_command(#[future] client: MusicPlayerClient) {
 
 #[rstest]
 #[case(LibraryCommand::Rescan)]
-#[case(LibraryCommand::Recluster)]
+#[case(LibraryCommand::Recluster { clusters: None })]
 #[case(LibraryCommand::Analyze)]
 #[case(LibraryCommand::Full)]
 #[case(LibraryCommand::Brief)]
 #[case(LibraryCommand::Health)]
+#[case(LibraryCommand::ScanHistory)]
+#[case(LibraryCommand::Verify { ids: vec![] })]
+#[case(LibraryCommand::Verify {
+    ids: vec![item_id().to_string()],
+})]
+#[case(LibraryCommand::DeadLinks { remove: false })]
+#[case(LibraryCommand::DeadLinks { remove: true })]
+#[case(LibraryCommand::Duplicates { acoustic: false })]
+#[case(LibraryCommand::Duplicates { acoustic: true })]
+#[case(LibraryCommand::RemoveDuplicates {
+    keep: item_id().to_string(),
+    remove: vec!["not a song".to_string()],
+})]
+#[case(LibraryCommand::Songs {
+    year_from: Some(1970),
+    year_to: Some(1979),
+    limit: 10,
+})]
+#[case(LibraryCommand::Songs {
+    year_from: None,
+    year_to: Some(2000),
+    limit: 10,
+})]
+#[case(LibraryCommand::Bpm {
+    min: 120.,
+    max: 140.,
+    limit: 10,
+})]
+#[case(LibraryCommand::Random {
+    limit: 10,
+    seed: None,
+})]
+#[case(LibraryCommand::Random {
+    limit: 10,
+    seed: Some(42),
+})]
 #[case(LibraryCommand::List {
     full: false,
     target: LibraryListTarget::Artists,
@@ -207,6 +303,29 @@ async fn test_stop_command(#[future] client: MusicPlayerClient) {
     target: LibraryGetTarget::Playlist,
     id: item_id().to_string(),
 })]
+#[case(LibraryCommand::Genre {
+    command: GenreCommand::List,
+})]
+#[case(LibraryCommand::Artist {
+    command: ArtistCommand::Merge {
+        source: item_id().to_string(),
+        target: item_id().to_string(),
+    },
+})]
+#[case(LibraryCommand::FailedAnalysis {
+    command: FailedAnalysisCommand::List,
+})]
+#[case(LibraryCommand::FailedAnalysis {
+    command: FailedAnalysisCommand::Retry {
+        ids: vec![item_id().to_string()],
+    },
+})]
+#[case(LibraryCommand::Genre {
+    command: GenreCommand::Songs {
+        genre: "Genre 0".to_string(),
+        limit: 10,
+    },
+})]
 #[tokio::test]
 async fn test_library_command(
     #[future] client: MusicPlayerClient,
@@ -269,17 +388,47 @@ async fn test_rand_command(#[future] client: MusicPlayerClient, #[case] target:
 }
 
 #[rstest]
-#[case(SearchTarget::Album)]
-#[case(SearchTarget::Artist)]
-#[case(SearchTarget::Song)]
+#[case(vec![], SearchTarget::All, 10)]
+#[case(vec!["--type", "songs"], SearchTarget::Songs, 10)]
+#[case(vec!["--type", "album", "--limit", "5"], SearchTarget::Albums, 5)]
+#[case(vec!["--limit", "1", "--type", "artists"], SearchTarget::Artists, 1)]
+fn test_cli_args_parse_search(
+    #[case] args: Vec<&str>,
+    #[case] expected_kind: SearchTarget,
+    #[case] expected_limit: u32,
+) {
+    let args = ["mecomp-cli", "search", "the beatles"]
+        .into_iter()
+        .chain(args);
+    let flags = crate::Flags::try_parse_from(args).unwrap();
+    let Some(Command::Search {
+        query, kind, limit, ..
+    }) = flags.subcommand
+    else {
+        panic!("expected a search command");
+    };
+    assert_eq!(query, "the beatles");
+    assert_eq!(kind, expected_kind);
+    assert_eq!(limit, expected_limit);
+}
+
+#[rstest]
+#[case(SearchTarget::Albums)]
+#[case(SearchTarget::Artists)]
+#[case(SearchTarget::Songs)]
 #[case(SearchTarget::All)]
 #[tokio::test]
-async fn test_search_command(#[future] client: MusicPlayerClient, #[case] target: SearchTarget) {
+async fn test_search_command(
+    #[future] client: MusicPlayerClient,
+    #[case] kind: SearchTarget,
+    #[values(OutputFormat::Plain, OutputFormat::Table, OutputFormat::Json)] format: OutputFormat,
+) {
     let ctx = tarpc::context::current();
     let command = Command::Search {
-        target,
+        kind,
         query: "test".to_string(),
         limit: 10,
+        format,
     };
 
     let result = command.handle(ctx, client.await).await;
@@ -292,20 +441,36 @@ async fn test_search_command(#[future] client: MusicPlayerClient, #[case] target
 #[case(PlaybackCommand::Stop)]
 #[case(PlaybackCommand::Next)]
 #[case(PlaybackCommand::Previous)]
-#[case(PlaybackCommand::Seek { command: SeekCommand::Absolute { position: 0. } })]
-#[case(PlaybackCommand::Seek { command: SeekCommand::Forward { amount: 0. } })]
-#[case(PlaybackCommand::Seek { command: SeekCommand::Backward { amount: 0. } })]
+#[case(PlaybackCommand::Seek { position: None, command: Some(SeekCommand::Absolute { position: 0. }) })]
+#[case(PlaybackCommand::Seek { position: None, command: Some(SeekCommand::Forward { amount: 0. }) })]
+#[case(PlaybackCommand::Seek { position: None, command: Some(SeekCommand::Backward { amount: 0. }) })]
+#[case(PlaybackCommand::Seek { position: Some("0:00".into()), command: None })]
+#[case(PlaybackCommand::Seek { position: Some("+10s".into()), command: None })]
+#[case(PlaybackCommand::Seek { position: Some("-5s".into()), command: None })]
 #[case(PlaybackCommand::Volume { command: VolumeCommand::Decrease { amount: 0. } })]
 #[case(PlaybackCommand::Volume { command: VolumeCommand::Increase { amount: 0. } })]
 #[case(PlaybackCommand::Volume { command: VolumeCommand::Set { volume: 0. } })]
 #[case(PlaybackCommand::Volume { command: VolumeCommand::Mute })]
 #[case(PlaybackCommand::Volume { command: VolumeCommand::Unmute })]
+#[case(PlaybackCommand::Volume { command: VolumeCommand::Fade { volume: 0., duration: std::time::Duration::from_secs(30) } })]
+#[case(PlaybackCommand::Volume { command: VolumeCommand::CancelFade })]
+#[case(PlaybackCommand::Volume { command: VolumeCommand::Crossfade { duration: std::time::Duration::from_secs(5) } })]
 #[case(PlaybackCommand::Toggle)]
 #[case(PlaybackCommand::Restart)]
 #[case(PlaybackCommand::Shuffle)]
 #[case(PlaybackCommand::Repeat { mode: RepeatMode::None })]
 #[case(PlaybackCommand::Repeat { mode: RepeatMode::Once })]
 #[case(PlaybackCommand::Repeat { mode: RepeatMode::Continuous })]
+#[case(PlaybackCommand::ReplayGain { mode: ReplayGainMode::Off })]
+#[case(PlaybackCommand::ReplayGain { mode: ReplayGainMode::Track })]
+#[case(PlaybackCommand::ReplayGain { mode: ReplayGainMode::Album })]
+#[case(PlaybackCommand::Speed { speed: 1.5 })]
+#[case(PlaybackCommand::ListDevices)]
+#[case(PlaybackCommand::SetDevice { name: "USB DAC".into() })]
+#[case(PlaybackCommand::Levels)]
+#[case(PlaybackCommand::Equalizer { command: EqualizerCommand::Preset { name: "bass_boost".into() } })]
+#[case(PlaybackCommand::Equalizer { command: EqualizerCommand::Preset { name: "not a preset".into() } })]
+#[case(PlaybackCommand::Equalizer { command: EqualizerCommand::Presets })]
 #[tokio::test]
 async fn test_playback_command(
     #[future] client: MusicPlayerClient,
@@ -327,7 +492,13 @@ async fn test_playback_command(
 #[case(QueueCommand::Remove { start: 0, end: 1 })]
 #[case(QueueCommand::Clear)]
 #[case(QueueCommand::List)]
+#[case(QueueCommand::Insert { index: 0, id: item_id().to_string() })]
+#[case(QueueCommand::Move { from: 0, to: 1 })]
 #[case(QueueCommand::Set { index: 0 })]
+#[case(QueueCommand::Dedup)]
+#[case(QueueCommand::Sort { strategy: SortStrategy::Tempo })]
+#[case(QueueCommand::Sort { strategy: SortStrategy::Similarity })]
+#[case(QueueCommand::SaveAsPlaylist { name: "Saved Queue".to_string() })]
 #[tokio::test]
 async fn test_queue_command(#[future] client: MusicPlayerClient, #[case] command: QueueCommand) {
     let ctx = tarpc::context::current();
@@ -358,10 +529,54 @@ async fn test_playlist_command(
     assert!(result.is_ok());
 }
 
+#[rstest]
+#[tokio::test]
+async fn test_playlist_import_command(#[future] client: MusicPlayerClient) {
+    let ctx = tarpc::context::current();
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("Imported.csv");
+    std::fs::write(&path, "Title,Artist\nTest Song,Test Artist\n").unwrap();
+    let command = Command::Playlist {
+        command: PlaylistCommand::Import {
+            path,
+            threshold: 0.85,
+        },
+    };
+
+    let result = command.handle(ctx, client.await).await;
+    assert!(result.is_ok());
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_playlist_export_all_command(
+    #[future] client: MusicPlayerClient,
+    #[values(true, false)] no_overwrite: bool,
+) {
+    let ctx = tarpc::context::current();
+    let dir = tempdir().unwrap();
+    let command = Command::Playlist {
+        command: PlaylistCommand::ExportAll {
+            dir: dir.path().join("playlists"),
+            no_overwrite,
+        },
+    };
+
+    let result = command.handle(ctx, client.await).await;
+    assert!(result.is_ok());
+    assert!(dir
+        .path()
+        .join("playlists")
+        .join("Test Playlist.m3u")
+        .exists());
+}
+
 #[rstest]
 #[case(CollectionCommand::List)]
 #[case(CollectionCommand::Get { id: item_id().to_string() })]
-#[case(CollectionCommand::Recluster)]
+#[case(CollectionCommand::Recluster { clusters: None })]
+#[case(CollectionCommand::Recluster { clusters: Some(2) })]
+#[case(CollectionCommand::Stats { id: item_id().to_string() })]
 #[case(CollectionCommand::Freeze { id: Playlist::generate_id().id.to_string(), name: "Test Collection".to_string() })]
 #[tokio::test]
 async fn test_collection_command(
@@ -3,12 +3,15 @@
 
 use std::{fmt::Display, ops::Range, time::Duration};
 
-use mecomp_storage::db::schemas::song::Song;
+use mecomp_storage::db::schemas::song::{Song, SongId};
 use one_or_many::OneOrMany;
 
 use crate::{
     format_duration,
-    state::{RepeatMode, SeekType, StateAudio},
+    state::{
+        equalizer::EqBand, AudioLevels, RepeatMode, ReplayGainMode, SeekType, SortStrategy,
+        StateAudio,
+    },
 };
 
 /// Commands that can be sent to the audio kernel
@@ -20,6 +23,8 @@ pub enum AudioCommand {
     RestartSong,
     /// only clear the player (i.e. stop playback)
     ClearPlayer,
+    /// the current song has played to completion, sent by the duration watcher to move on to the next song
+    SongFinished,
     /// Queue Commands
     Queue(QueueCommand),
     /// Stop the audio kernel
@@ -30,6 +35,28 @@ pub enum AudioCommand {
     Volume(VolumeCommand),
     /// seek commands
     Seek(SeekType, Duration),
+    /// seek to a percentage (0.0-100.0) of the way through the current song
+    SeekPercent(f32),
+    /// set the pre-amplifier gain (in decibels), this is applied independently of the volume
+    SetGain(f32),
+    /// gradually change the volume to the given value over the given duration
+    FadeToVolume(f32, Duration),
+    /// stop the volume fade in progress (if any), leaving the volume where it is
+    CancelFade,
+    /// set how long the end of a song overlaps with the start of the next one (fading out as it fades in), `Duration::ZERO` disables it
+    SetCrossfadeDuration(Duration),
+    /// set how songs are levelled using their `ReplayGain`
+    SetReplayGainMode(ReplayGainMode),
+    /// set the playback speed, `1.0` is normal speed (clamped to between 0.5x and 2x)
+    SetSpeed(f32),
+    /// switch the audio output to the output device with the given name
+    SetOutputDevice(String),
+    /// report the names of the available audio output devices
+    ListOutputDevices(tokio::sync::oneshot::Sender<Vec<String>>),
+    /// replace the bands of the equalizer
+    SetEqualizer(Vec<EqBand>),
+    /// report the peak and RMS levels of the audio being played (over about the last 100ms)
+    ReportLevels(tokio::sync::oneshot::Sender<AudioLevels>),
 }
 
 impl PartialEq for AudioCommand {
@@ -40,11 +67,27 @@ impl PartialEq for AudioCommand {
             | (Self::TogglePlayback, Self::TogglePlayback)
             | (Self::ClearPlayer, Self::ClearPlayer)
             | (Self::RestartSong, Self::RestartSong)
+            | (Self::SongFinished, Self::SongFinished)
             | (Self::Exit, Self::Exit)
-            | (Self::ReportStatus(_), Self::ReportStatus(_)) => true,
+            | (Self::CancelFade, Self::CancelFade)
+            | (Self::ReportStatus(_), Self::ReportStatus(_))
+            | (Self::ListOutputDevices(_), Self::ListOutputDevices(_))
+            | (Self::ReportLevels(_), Self::ReportLevels(_)) => true,
             (Self::Queue(a), Self::Queue(b)) => a == b,
             (Self::Volume(a), Self::Volume(b)) => a == b,
             (Self::Seek(a, b), Self::Seek(c, d)) => a == c && b == d,
+            #[allow(clippy::float_cmp)]
+            (Self::SeekPercent(a), Self::SeekPercent(b)) => a == b,
+            #[allow(clippy::float_cmp)]
+            (Self::SetGain(a), Self::SetGain(b)) => a == b,
+            #[allow(clippy::float_cmp)]
+            (Self::FadeToVolume(a, b), Self::FadeToVolume(c, d)) => a == c && b == d,
+            (Self::SetCrossfadeDuration(a), Self::SetCrossfadeDuration(b)) => a == b,
+            (Self::SetReplayGainMode(a), Self::SetReplayGainMode(b)) => a == b,
+            #[allow(clippy::float_cmp)]
+            (Self::SetSpeed(a), Self::SetSpeed(b)) => a == b,
+            (Self::SetOutputDevice(a), Self::SetOutputDevice(b)) => a == b,
+            (Self::SetEqualizer(a), Self::SetEqualizer(b)) => a == b,
             #[cfg(not(tarpaulin_include))]
             _ => false,
         }
@@ -59,6 +102,7 @@ impl Display for AudioCommand {
             Self::TogglePlayback => write!(f, "Toggle Playback"),
             Self::RestartSong => write!(f, "Restart Song"),
             Self::ClearPlayer => write!(f, "Clear Player"),
+            Self::SongFinished => write!(f, "Song Finished"),
             Self::Queue(command) => write!(f, "Queue: {command}"),
             Self::Exit => write!(f, "Exit"),
             Self::ReportStatus(_) => write!(f, "Report Status"),
@@ -70,6 +114,37 @@ impl Display for AudioCommand {
                     format_duration(duration)
                 )
             }
+            Self::SeekPercent(percent) => write!(f, "Seek: {percent:.2}%"),
+            Self::SetGain(gain) => write!(f, "Set Gain: {gain:+.1}dB"),
+            Self::FadeToVolume(target, duration) => write!(
+                f,
+                "Fade Volume: ={target:.0}% over {} (HH:MM:SS)",
+                format_duration(duration),
+                target = target * 100.0
+            ),
+            Self::CancelFade => write!(f, "Cancel Fade"),
+            Self::SetCrossfadeDuration(duration) => write!(
+                f,
+                "Set Crossfade Duration: {} (HH:MM:SS)",
+                format_duration(duration)
+            ),
+            Self::SetReplayGainMode(mode) => write!(f, "Set ReplayGain Mode: {mode}"),
+            Self::SetSpeed(speed) => write!(f, "Set Speed: {speed}x"),
+            Self::SetOutputDevice(name) => write!(f, "Set Output Device: {name}"),
+            Self::ListOutputDevices(_) => write!(f, "List Output Devices"),
+            Self::ReportLevels(_) => write!(f, "Report Levels"),
+            Self::SetEqualizer(bands) => write!(
+                f,
+                "Set Equalizer: [{}]",
+                bands
+                    .iter()
+                    .map(|band| format!(
+                        "{}Hz {:+.1}dB Q{}",
+                        band.frequency_hz, band.gain_db, band.q
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -82,9 +157,22 @@ pub enum QueueCommand {
     SetPosition(usize),
     Shuffle,
     AddToQueue(Box<OneOrMany<Song>>),
+    /// Insert a song at the given index (or at the end, if the index is past the end of the queue),
+    /// without changing the current song
+    InsertAt(usize, Box<Song>),
     RemoveRange(Range<usize>),
+    /// Move the song at the first index to the second index, shifting the songs in between,
+    /// without changing the current song
+    Move(usize, usize),
     Clear,
     SetRepeatMode(RepeatMode),
+    /// Remove duplicate songs from the queue, keeping the first occurrence of each
+    Dedup,
+    /// Sort the queue into the order of the given song ids, which were ranked by their analysis using the given strategy
+    /// (see [`Queue::sort_by_analysis`](super::queue::Queue::sort_by_analysis))
+    SortByAnalysis(SortStrategy, Box<[SongId]>),
+    /// Set the maximum number of recently played songs the queue remembers
+    SetHistoryDepth(usize),
 }
 
 impl Display for QueueCommand {
@@ -110,10 +198,15 @@ impl Display for QueueCommand {
                     )
                 }
             },
+            Self::InsertAt(index, song) => write!(f, "Insert \"{}\" at {index}", song.title),
             Self::RemoveRange(range) => {
                 write!(f, "Remove items {}..{}", range.start, range.end)
             }
+            Self::Move(from, to) => write!(f, "Move item {from} to {to}"),
             Self::Clear => write!(f, "Clear"),
+            Self::Dedup => write!(f, "Remove Duplicates"),
+            Self::SortByAnalysis(strategy, _) => write!(f, "Sort by {strategy}"),
+            Self::SetHistoryDepth(depth) => write!(f, "Set History Depth to {depth}"),
             Self::SetRepeatMode(mode) => {
                 write!(f, "Set Repeat Mode to {mode}")
             }
@@ -159,6 +252,8 @@ mod tests {
     #[case(AudioCommand::Pause, AudioCommand::Pause, true)]
     #[case(AudioCommand::TogglePlayback, AudioCommand::TogglePlayback, true)]
     #[case(AudioCommand::RestartSong, AudioCommand::RestartSong, true)]
+    #[case(AudioCommand::SongFinished, AudioCommand::SongFinished, true)]
+    #[case(AudioCommand::SongFinished, AudioCommand::RestartSong, false)]
     #[case(
         AudioCommand::Queue(QueueCommand::Clear),
         AudioCommand::Queue(QueueCommand::Clear),
@@ -274,6 +369,12 @@ mod tests {
         AudioCommand::Seek(SeekType::Absolute, Duration::from_secs(20)),
         false
     )]
+    #[case(AudioCommand::SeekPercent(50.0), AudioCommand::SeekPercent(50.0), true)]
+    #[case(
+        AudioCommand::SeekPercent(50.0),
+        AudioCommand::SeekPercent(25.0),
+        false
+    )]
     #[case(
         AudioCommand::Seek(SeekType::RelativeForwards, Duration::from_secs(10)),
         AudioCommand::Seek(SeekType::RelativeForwards, Duration::from_secs(10)),
@@ -309,6 +410,87 @@ mod tests {
         AudioCommand::Seek(SeekType::RelativeBackwards, Duration::from_secs(10)),
         false
     )]
+    #[case(AudioCommand::SetGain(3.0), AudioCommand::SetGain(3.0), true)]
+    #[case(AudioCommand::SetGain(3.0), AudioCommand::SetGain(-3.0), false)]
+    #[case(
+        AudioCommand::SetGain(1.0),
+        AudioCommand::Volume(VolumeCommand::Set(1.0)),
+        false
+    )]
+    #[case(
+        AudioCommand::FadeToVolume(0.0, Duration::from_secs(30)),
+        AudioCommand::FadeToVolume(0.0, Duration::from_secs(30)),
+        true
+    )]
+    #[case(
+        AudioCommand::FadeToVolume(0.0, Duration::from_secs(30)),
+        AudioCommand::FadeToVolume(0.5, Duration::from_secs(30)),
+        false
+    )]
+    #[case(
+        AudioCommand::FadeToVolume(0.0, Duration::from_secs(30)),
+        AudioCommand::FadeToVolume(0.0, Duration::from_secs(10)),
+        false
+    )]
+    #[case(AudioCommand::CancelFade, AudioCommand::CancelFade, true)]
+    #[case(AudioCommand::CancelFade, AudioCommand::Pause, false)]
+    #[case(
+        AudioCommand::SetCrossfadeDuration(Duration::from_secs(5)),
+        AudioCommand::SetCrossfadeDuration(Duration::from_secs(5)),
+        true
+    )]
+    #[case(
+        AudioCommand::SetCrossfadeDuration(Duration::from_secs(5)),
+        AudioCommand::SetCrossfadeDuration(Duration::ZERO),
+        false
+    )]
+    #[case(
+        AudioCommand::SetReplayGainMode(ReplayGainMode::Track),
+        AudioCommand::SetReplayGainMode(ReplayGainMode::Track),
+        true
+    )]
+    #[case(
+        AudioCommand::SetReplayGainMode(ReplayGainMode::Track),
+        AudioCommand::SetReplayGainMode(ReplayGainMode::Album),
+        false
+    )]
+    #[case(AudioCommand::SetSpeed(1.5), AudioCommand::SetSpeed(1.5), true)]
+    #[case(AudioCommand::SetSpeed(1.5), AudioCommand::SetSpeed(1.0), false)]
+    #[case(
+        AudioCommand::SetOutputDevice("USB DAC".into()),
+        AudioCommand::SetOutputDevice("USB DAC".into()),
+        true
+    )]
+    #[case(
+        AudioCommand::SetOutputDevice("USB DAC".into()),
+        AudioCommand::SetOutputDevice("HDMI".into()),
+        false
+    )]
+    #[case(
+        AudioCommand::ListOutputDevices(tokio::sync::oneshot::channel().0),
+        AudioCommand::ListOutputDevices(tokio::sync::oneshot::channel().0),
+        true
+    )]
+    #[case(
+        AudioCommand::ReportLevels(tokio::sync::oneshot::channel().0),
+        AudioCommand::ReportLevels(tokio::sync::oneshot::channel().0),
+        true
+    )]
+    #[case(
+        AudioCommand::ReportLevels(tokio::sync::oneshot::channel().0),
+        AudioCommand::ReportStatus(tokio::sync::oneshot::channel().0),
+        false
+    )]
+    #[case(
+        AudioCommand::SetEqualizer(vec![EqBand::new(60.0, 3.0, 1.0)]),
+        AudioCommand::SetEqualizer(vec![EqBand::new(60.0, 3.0, 1.0)]),
+        true
+    )]
+    #[case(
+        AudioCommand::SetEqualizer(vec![EqBand::new(60.0, 3.0, 1.0)]),
+        AudioCommand::SetEqualizer(vec![]),
+        false
+    )]
     fn test_audio_command_equality(
         #[case] lhs: AudioCommand,
         #[case] rhs: AudioCommand,
@@ -335,6 +517,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: "foo/bar.mp3".into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         }
     }
 
@@ -344,8 +534,13 @@ mod tests {
     #[case(AudioCommand::TogglePlayback, "Toggle Playback")]
     #[case(AudioCommand::ClearPlayer, "Clear Player")]
     #[case(AudioCommand::RestartSong, "Restart Song")]
+    #[case(AudioCommand::SongFinished, "Song Finished")]
     #[case(AudioCommand::Queue(QueueCommand::Clear), "Queue: Clear")]
     #[case(AudioCommand::Queue(QueueCommand::Shuffle), "Queue: Shuffle")]
+    #[case(
+        AudioCommand::Queue(QueueCommand::SetHistoryDepth(20)),
+        "Queue: Set History Depth to 20"
+    )]
     #[case(
         AudioCommand::Queue(QueueCommand::AddToQueue(Box::new(OneOrMany::None))),
         "Queue: Add nothing"
@@ -358,10 +553,22 @@ mod tests {
         AudioCommand::Queue(QueueCommand::AddToQueue(Box::new(OneOrMany::Many(vec![dummy_song()])))),
         "Queue: Add [\"Song 1\"]"
     )]
+    #[case(
+        AudioCommand::Queue(QueueCommand::InsertAt(2, Box::new(dummy_song()))),
+        "Queue: Insert \"Song 1\" at 2"
+    )]
     #[case(
         AudioCommand::Queue(QueueCommand::RemoveRange(0..1)),
         "Queue: Remove items 0..1"
     )]
+    #[case(
+        AudioCommand::Queue(QueueCommand::Move(3, 0)),
+        "Queue: Move item 3 to 0"
+    )]
+    #[case(
+        AudioCommand::Queue(QueueCommand::SortByAnalysis(SortStrategy::ByTempo, Box::new([]))),
+        "Queue: Sort by Tempo"
+    )]
     #[case(
         AudioCommand::Queue(QueueCommand::SetRepeatMode(RepeatMode::None)),
         "Queue: Set Repeat Mode to None"
@@ -390,6 +597,7 @@ mod tests {
         AudioCommand::Seek(SeekType::Absolute, Duration::from_secs(10)),
         "Seek: Absolute 00:00:10.00 (HH:MM:SS)"
     )]
+    #[case(AudioCommand::SeekPercent(37.5), "Seek: 37.50%")]
     #[case(
         AudioCommand::Seek(SeekType::RelativeForwards, Duration::from_secs(10)),
         "Seek: Forwards 00:00:10.00 (HH:MM:SS)"
@@ -402,6 +610,32 @@ mod tests {
         AudioCommand::Seek(SeekType::Absolute, Duration::from_secs(3600 + 120 + 1)),
         "Seek: Absolute 01:02:01.00 (HH:MM:SS)"
     )]
+    #[case(AudioCommand::SetGain(3.0), "Set Gain: +3.0dB")]
+    #[case(AudioCommand::SetGain(-6.5), "Set Gain: -6.5dB")]
+    #[case(
+        AudioCommand::FadeToVolume(0.0, Duration::from_secs(30)),
+        "Fade Volume: =0% over 00:00:30.00 (HH:MM:SS)"
+    )]
+    #[case(AudioCommand::CancelFade, "Cancel Fade")]
+    #[case(
+        AudioCommand::SetCrossfadeDuration(Duration::from_secs(5)),
+        "Set Crossfade Duration: 00:00:05.00 (HH:MM:SS)"
+    )]
+    #[case(
+        AudioCommand::SetReplayGainMode(ReplayGainMode::Album),
+        "Set ReplayGain Mode: Album"
+    )]
+    #[case(AudioCommand::SetSpeed(1.5), "Set Speed: 1.5x")]
+    #[case(AudioCommand::SetOutputDevice("USB DAC".into()), "Set Output Device: USB DAC")]
+    #[case(
+        AudioCommand::ListOutputDevices(tokio::sync::oneshot::channel().0),
+        "List Output Devices"
+    )]
+    #[case(AudioCommand::ReportLevels(tokio::sync::oneshot::channel().0), "Report Levels")]
+    #[case(
+        AudioCommand::SetEqualizer(vec![EqBand::new(60.0, 3.0, 1.0), EqBand::new(8000.0, -1.5, 0.7)]),
+        "Set Equalizer: [60Hz +3.0dB Q1, 8000Hz -1.5dB Q0.7]"
+    )]
     fn test_audio_command_display(#[case] command: AudioCommand, #[case] expected: &str) {
         let actual = command.to_string();
         assert_str_eq!(actual, expected);
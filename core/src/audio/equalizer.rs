@@ -0,0 +1,248 @@
+//! A [`Source`] that runs audio through a series of peaking equalizer filters.
+
+use std::time::Duration;
+
+use rodio::{source::SeekError, Source};
+
+use crate::state::equalizer::EqBand;
+
+/// The coefficients of a peaking biquad filter, normalized so that `a0` is 1.
+///
+/// See the [Audio EQ Cookbook](https://www.w3.org/TR/audio-eq-cookbook/).
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    /// The filter for `band` at the given sample rate.
+    ///
+    /// Returns `None` if the band wouldn't change the audio, either because it's flat
+    /// or because its frequency is above the Nyquist frequency.
+    #[allow(clippy::cast_precision_loss)]
+    fn peaking(band: &EqBand, sample_rate: u32) -> Option<Self> {
+        let sample_rate = sample_rate as f32;
+        if band.is_flat() || band.frequency_hz >= sample_rate / 2.0 {
+            return None;
+        }
+
+        let a = 10f32.powf(band.gain_db / 40.0);
+        let w0 = std::f32::consts::TAU * band.frequency_hz / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * band.q);
+        let a0 = 1.0 + alpha / a;
+
+        Some(Self {
+            b0: alpha.mul_add(a, 1.0) / a0,
+            b1: -2.0 * cos / a0,
+            b2: (-alpha).mul_add(a, 1.0) / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha / a) / a0,
+        })
+    }
+
+    /// Filter one sample, updating the filter's state (transposed direct form II).
+    #[inline]
+    fn process(&self, state: &mut [f32; 2], x: f32) -> f32 {
+        let y = self.b0.mul_add(x, state[0]);
+        state[0] = self.b1.mul_add(x, (-self.a1).mul_add(y, state[1]));
+        state[1] = self.b2.mul_add(x, -self.a2 * y);
+        y
+    }
+}
+
+/// Runs the samples of the inner source through one peaking filter per [`EqBand`].
+///
+/// Bands that are flat are skipped, so a flat equalizer leaves the samples untouched.
+pub struct Equalizer<S> {
+    inner: S,
+    /// the bands the filters were built from
+    bands: Vec<EqBand>,
+    /// the filters of the bands that change the audio
+    filters: Vec<Biquad>,
+    /// the state of every filter, for every channel (`filters.len() * channels` entries)
+    state: Vec<[f32; 2]>,
+    /// the channel of the next sample
+    channel: usize,
+    /// the channels and sample rate the filters were built for
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S> Equalizer<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(inner: S, bands: &[EqBand]) -> Self {
+        let mut equalizer = Self {
+            channels: inner.channels(),
+            sample_rate: inner.sample_rate(),
+            inner,
+            bands: Vec::new(),
+            filters: Vec::new(),
+            state: Vec::new(),
+            channel: 0,
+        };
+        equalizer.set_bands(bands);
+        equalizer
+    }
+
+    /// The bands the equalizer is currently applying.
+    #[must_use]
+    pub fn bands(&self) -> &[EqBand] {
+        &self.bands
+    }
+
+    /// Replace the bands of the equalizer.
+    pub fn set_bands(&mut self, bands: &[EqBand]) {
+        bands.clone_into(&mut self.bands);
+        self.rebuild_filters();
+    }
+
+    fn rebuild_filters(&mut self) {
+        self.filters = self
+            .bands
+            .iter()
+            .filter_map(|band| Biquad::peaking(band, self.sample_rate))
+            .collect();
+        self.reset();
+    }
+
+    /// Clear the state of the filters, e.g. after seeking.
+    fn reset(&mut self) {
+        self.state = vec![[0.0; 2]; self.filters.len() * usize::from(self.channels.max(1))];
+    }
+}
+
+impl<S> Iterator for Equalizer<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // the format of the inner source can only change at the start of a frame
+        if self.channel == 0
+            && (self.inner.channels() != self.channels
+                || self.inner.sample_rate() != self.sample_rate)
+        {
+            self.channels = self.inner.channels();
+            self.sample_rate = self.inner.sample_rate();
+            self.rebuild_filters();
+        }
+
+        let sample = self.inner.next()?;
+        let offset = self.channel * self.filters.len();
+        let filtered = self
+            .filters
+            .iter()
+            .zip(&mut self.state[offset..])
+            .fold(sample, |x, (filter, state)| filter.process(state, x));
+
+        self.channel = (self.channel + 1) % usize::from(self.channels.max(1));
+        Some(filtered)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Source for Equalizer<S>
+where
+    S: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.channel = 0;
+        self.reset();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::equalizer::{flat, DEFAULT_Q};
+
+    use rodio::source::SineWave;
+    use rstest::rstest;
+
+    /// The peak amplitude of a sine wave after it's been through the equalizer, once the filters have settled.
+    fn peak_amplitude(frequency_hz: f32, bands: &[EqBand]) -> f32 {
+        Equalizer::new(SineWave::new(frequency_hz), bands)
+            .take(48_000)
+            .skip(24_000)
+            .fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    #[test]
+    fn test_flat_equalizer_is_transparent() {
+        let equalizer = Equalizer::new(SineWave::new(440.0), &flat());
+        assert_eq!(equalizer.bands(), flat().as_slice());
+        assert!(equalizer.filters.is_empty());
+        assert!(equalizer
+            .zip(SineWave::new(440.0))
+            .take(4800)
+            .all(|(a, b)| (a - b).abs() < f32::EPSILON));
+    }
+
+    #[rstest]
+    #[case::boost(6.0)]
+    #[case::cut(-6.0)]
+    fn test_center_frequency_gain(#[case] gain_db: f32) {
+        let bands = [EqBand::new(1000.0, gain_db, DEFAULT_Q)];
+
+        // the center frequency is boosted (or cut) by the gain of the band
+        let expected = 10f32.powf(gain_db / 20.0);
+        let actual = peak_amplitude(1000.0, &bands);
+        assert!((actual - expected).abs() < 0.02, "{actual} !~= {expected}");
+
+        // frequencies far from the band are (nearly) unchanged
+        let actual = peak_amplitude(50.0, &bands);
+        assert!((actual - 1.0).abs() < 0.05, "{actual} !~= 1.0");
+    }
+
+    #[test]
+    fn test_band_above_nyquist_is_skipped() {
+        let equalizer = Equalizer::new(SineWave::new(440.0), &[EqBand::new(30_000.0, 6.0, 1.0)]);
+        assert!(equalizer.filters.is_empty());
+    }
+
+    #[test]
+    fn test_set_bands() {
+        let mut equalizer = Equalizer::new(SineWave::new(440.0), &flat());
+        let bands = [EqBand::new(440.0, 6.0, 1.0), EqBand::new(100.0, 0.0, 1.0)];
+        equalizer.set_bands(&bands);
+        assert_eq!(equalizer.bands(), bands.as_slice());
+        // only the band that changes the audio gets a filter
+        assert_eq!(equalizer.filters.len(), 1);
+        assert_eq!(equalizer.state.len(), 1);
+    }
+}
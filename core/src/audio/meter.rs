@@ -0,0 +1,213 @@
+//! A [`Source`] that measures the peak and RMS levels of the audio passing through it.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rodio::{source::SeekError, Source};
+
+use crate::state::AudioLevels;
+
+/// How much audio each measurement covers
+pub const LEVEL_WINDOW: Duration = Duration::from_millis(100);
+
+/// The levels of the most recent window of audio, before the volume is applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelReading {
+    /// the largest absolute sample value in the window
+    pub peak: f32,
+    /// the root mean square of the samples in the window
+    pub rms: f32,
+    /// when the window ended
+    pub measured_at: Instant,
+}
+
+impl LevelReading {
+    /// The levels of the reading once the given (linear) volume is applied.
+    ///
+    /// Readings older than a couple of windows are stale (playback is paused or has stopped), so they're reported as silence.
+    #[must_use]
+    pub fn levels(&self, volume: f32) -> AudioLevels {
+        if self.measured_at.elapsed() > LEVEL_WINDOW * 2 {
+            return AudioLevels::default();
+        }
+        AudioLevels::new(self.peak * volume, self.rms * volume)
+    }
+}
+
+/// Passes the samples of the inner source through unchanged,
+/// publishing a [`LevelReading`] to `reading` after every [`LEVEL_WINDOW`] of audio.
+pub struct LevelMeter<S> {
+    inner: S,
+    reading: Arc<Mutex<Option<LevelReading>>>,
+    /// the largest absolute sample value so far in this window
+    peak: f32,
+    /// the sum of the squares of the samples so far in this window
+    sum_of_squares: f32,
+    /// the number of samples so far in this window
+    samples: usize,
+    /// the number of samples in a full window
+    window_samples: usize,
+}
+
+impl<S> LevelMeter<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(inner: S, reading: Arc<Mutex<Option<LevelReading>>>) -> Self {
+        let window_samples = window_samples(&inner);
+        Self {
+            inner,
+            reading,
+            peak: 0.0,
+            sum_of_squares: 0.0,
+            samples: 0,
+            window_samples,
+        }
+    }
+
+    /// Start a new window, discarding the samples of the current one.
+    fn reset(&mut self) {
+        self.peak = 0.0;
+        self.sum_of_squares = 0.0;
+        self.samples = 0;
+        self.window_samples = window_samples(&self.inner);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn publish(&mut self) {
+        let reading = LevelReading {
+            peak: self.peak,
+            rms: (self.sum_of_squares / self.samples as f32).sqrt(),
+            measured_at: Instant::now(),
+        };
+        // this runs on the audio thread, so don't wait if someone is reading the levels,
+        // the next window will be published soon enough
+        if let Ok(mut shared) = self.reading.try_lock() {
+            *shared = Some(reading);
+        }
+        self.reset();
+    }
+}
+
+/// The number of samples (across all channels) in a [`LEVEL_WINDOW`] of `source`.
+#[allow(clippy::cast_possible_truncation)]
+fn window_samples<S: Source<Item = f32>>(source: &S) -> usize {
+    let samples_per_second = source.sample_rate() as usize * usize::from(source.channels());
+    (samples_per_second * LEVEL_WINDOW.as_millis() as usize / 1000).max(1)
+}
+
+impl<S> Iterator for LevelMeter<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(sample) = self.inner.next() else {
+            // publish whatever is left of the last window
+            if self.samples > 0 {
+                self.publish();
+            }
+            return None;
+        };
+
+        self.peak = self.peak.max(sample.abs());
+        self.sum_of_squares = sample.mul_add(sample, self.sum_of_squares);
+        self.samples += 1;
+        if self.samples >= self.window_samples {
+            self.publish();
+        }
+
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Source for LevelMeter<S>
+where
+    S: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.reset();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rodio::source::SineWave;
+
+    #[test]
+    fn test_sine_wave_levels() {
+        let reading = Arc::new(Mutex::new(None));
+        let meter = LevelMeter::new(SineWave::new(440.0).amplify(0.5), reading.clone());
+        let window_samples = meter.window_samples;
+        assert_eq!(window_samples, 4800);
+
+        // nothing is published until a full window has been measured
+        let mut meter = meter.take(window_samples * 3);
+        meter.by_ref().take(window_samples - 1).for_each(drop);
+        assert!(reading.lock().unwrap().is_none());
+        meter.for_each(drop);
+
+        let reading = reading.lock().unwrap().unwrap();
+        assert!((reading.peak - 0.5).abs() < 0.01, "{}", reading.peak);
+        assert!(
+            (reading.rms - 0.5 / std::f32::consts::SQRT_2).abs() < 0.01,
+            "{}",
+            reading.rms
+        );
+
+        let levels = reading.levels(1.0);
+        assert!((levels.peak_db + 6.02).abs() < 0.1, "{}", levels.peak_db);
+        assert!((levels.rms_db + 9.03).abs() < 0.1, "{}", levels.rms_db);
+        assert!(!levels.clip);
+
+        // turning the volume up past the headroom clips
+        let levels = reading.levels(4.0);
+        assert!(levels.clip);
+    }
+
+    #[test]
+    fn test_stale_reading_is_silence() {
+        let Some(measured_at) = Instant::now().checked_sub(LEVEL_WINDOW * 3) else {
+            return;
+        };
+        let reading = LevelReading {
+            peak: 1.0,
+            rms: 0.5,
+            measured_at,
+        };
+        assert_eq!(reading.levels(1.0), AudioLevels::default());
+    }
+}
@@ -12,31 +12,103 @@ use std::{
     time::Duration,
 };
 
-use log::{debug, error};
-use rodio::{source::SeekError, Decoder, Source};
+use log::{debug, error, warn};
+use rodio::{
+    source::{Amplify, SeekError, Stoppable, TrackPosition},
+    Decoder, Source,
+};
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::instrument;
 
 use crate::{
     errors::LibraryError,
     format_duration,
-    state::{Percent, SeekType, StateAudio, StateRuntime},
+    state::{
+        equalizer::{self as eq, EqBand},
+        AudioLevels, Percent, ReplayGainMode, SeekType, StateAudio, StateChange, StateRuntime,
+    },
 };
-use mecomp_storage::db::schemas::song::Song;
+use mecomp_storage::db::schemas::song::{Song, SongId};
 use one_or_many::OneOrMany;
 
 pub mod commands;
+pub mod equalizer;
+pub mod meter;
 pub mod queue;
 
 use commands::{AudioCommand, QueueCommand, VolumeCommand};
+use equalizer::Equalizer;
+use meter::{LevelMeter, LevelReading};
 use queue::Queue;
 
 const DURATION_WATCHER_TICK_MS: u64 = 50;
 const DURATION_WATCHER_NEXT_SONG_THRESHOLD_MS: u64 = 100;
+/// Songs changed by the user before this fraction of their runtime has been played count as skipped
+const SKIP_THRESHOLD: f32 = 0.8;
 
 /// The minimum volume that can be set, currently set to 0.0 (no sound)
 const MIN_VOLUME: f32 = 0.0;
 /// The maximum volume that can be set, currently set to 10.0 (10x volume)
 const MAX_VOLUME: f32 = 10.0;
+/// The minimum pre-amplifier gain that can be set, in decibels
+pub const MIN_GAIN_DB: f32 = -24.0;
+/// The maximum pre-amplifier gain that can be set, in decibels
+pub const MAX_GAIN_DB: f32 = 12.0;
+/// How often the amplifier wrapping the current source checks for changes to the gain
+const GAIN_UPDATE_PERIOD_MS: u64 = 10;
+/// The number of steps a volume fade is split into
+const FADE_STEPS: u16 = 100;
+/// The slowest playback speed that can be set
+const MIN_SPEED: f32 = 0.5;
+/// The fastest playback speed that can be set
+const MAX_SPEED: f32 = 2.0;
+/// The longest crossfade that can be set
+const MAX_CROSSFADE_DURATION: Duration = Duration::from_secs(12);
+/// The loudness (in LUFS) that songs are levelled to when `ReplayGain` is enabled
+const REPLAYGAIN_TARGET_LOUDNESS: f32 = -14.0;
+/// The reference loudness (in LUFS) that the `ReplayGain` gains stored with songs are relative to
+const REPLAYGAIN_REFERENCE_LOUDNESS: f32 = -18.0;
+/// The largest `ReplayGain` adjustment (in either direction) that will be applied, in decibels
+const MAX_REPLAYGAIN_DB: f32 = 12.0;
+
+/// Convert a gain in decibels to a linear amplitude multiplier
+#[must_use]
+#[inline]
+pub(crate) fn db_to_linear(gain_db: f32) -> f32 {
+    10f32.powf(gain_db / 20.0)
+}
+
+/// The names of the audio output devices available on the system
+#[cfg(not(feature = "mock_playback"))]
+fn output_device_names() -> Vec<String> {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+    match rodio::cpal::default_host().output_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(e) => {
+            error!("Failed to list the output devices: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// The names of the audio output devices available on the system
+///
+/// this is the version for tests, where there is no audio output, so there are no devices
+#[cfg(feature = "mock_playback")]
+const fn output_device_names() -> Vec<String> {
+    Vec::new()
+}
+
+/// Convert a stored `ReplayGain` gain into the (clamped) gain needed to reach the target loudness
+#[must_use]
+#[inline]
+fn replaygain_adjustment(replaygain: Option<f32>) -> f32 {
+    replaygain.map_or(0.0, |gain| {
+        (gain + REPLAYGAIN_TARGET_LOUDNESS - REPLAYGAIN_REFERENCE_LOUDNESS)
+            .clamp(-MAX_REPLAYGAIN_DB, MAX_REPLAYGAIN_DB)
+    })
+}
 
 #[derive(Debug, Clone)]
 pub struct AudioKernelSender {
@@ -55,12 +127,29 @@ impl AudioKernelSender {
     /// Panics if there is an issue spawning the audio kernel thread (if the name contains null bytes, which it doesn't, so this should never happen)
     #[must_use]
     pub fn start() -> Arc<Self> {
+        Self::spawn(None)
+    }
+
+    /// Starts the audio kernel in a detached thread and returns a sender to be used to send commands to the audio kernel.
+    ///
+    /// Unlike [`AudioKernelSender::start`], the audio kernel will report [`StateChange`]s (such as songs finishing) to `events`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is an issue spawning the audio kernel thread (if the name contains null bytes, which it doesn't, so this should never happen)
+    #[must_use]
+    pub fn start_with_events(events: UnboundedSender<StateChange>) -> Arc<Self> {
+        Self::spawn(Some(events))
+    }
+
+    fn spawn(events: Option<UnboundedSender<StateChange>>) -> Arc<Self> {
         let (tx, rx) = std::sync::mpsc::channel();
         let tx_clone = tx.clone();
         std::thread::Builder::new()
             .name(String::from("Audio Kernel"))
             .spawn(move || {
-                let kernel = AudioKernel::new();
+                let mut kernel = AudioKernel::new();
+                kernel.events = events;
                 kernel.init(tx_clone, rx);
             })
             .unwrap();
@@ -85,12 +174,65 @@ impl AudioKernelSender {
     }
 }
 
+/// Book-keeping for volume fades
+#[derive(Debug, Default)]
+struct FadeInfo {
+    /// incremented whenever a fade is started or cancelled, the thread running a fade stops once this changes
+    generation: u64,
+    /// whether a fade is in progress
+    in_progress: bool,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 struct DurationInfo {
     time_played: Duration,
     current_duration: Duration,
 }
 
+impl DurationInfo {
+    /// How long the song actually overlaps with the songs before and after it,
+    /// at most half of the song, so short songs aren't skipped over.
+    fn crossfade(&self, crossfade: Duration) -> Duration {
+        crossfade.min(self.current_duration / 2)
+    }
+}
+
+/// How far a song is through its crossfades, tracked by the song's source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct CrossfadeProgress {
+    /// how long the song has been playing for, regardless of seeking
+    elapsed: Duration,
+    /// whether the song fades in, because the previous song is still playing out
+    fade_in: bool,
+    /// when (in `elapsed` time) the song started fading out early, because it was skipped
+    fade_out_from: Option<Duration>,
+}
+
+impl CrossfadeProgress {
+    /// The amplitude multiplier of the crossfade: ramping up over the first `crossfade` the song plays for (if it fades in),
+    /// and down over the last `crossfade` of the song, or over the `crossfade` after it was skipped.
+    fn factor(&self, position: DurationInfo, crossfade: Duration) -> f32 {
+        let crossfade = position.crossfade(crossfade).as_secs_f32();
+        if crossfade == 0.0 {
+            return 1.0;
+        }
+        let fade_in = if self.fade_in {
+            self.elapsed.as_secs_f32() / crossfade
+        } else {
+            1.0
+        };
+        let fade_out = position
+            .current_duration
+            .saturating_sub(position.time_played)
+            .as_secs_f32()
+            / crossfade;
+        let skipped = self.fade_out_from.map_or(1.0, |from| {
+            1.0 - self.elapsed.saturating_sub(from).as_secs_f32() / crossfade
+        });
+        fade_in.min(fade_out).min(skipped).clamp(0.0, 1.0)
+    }
+}
+
 pub(crate) struct AudioKernel {
     /// this is not used, but is needed to keep the stream alive
     #[cfg(not(feature = "mock_playback"))]
@@ -101,16 +243,36 @@ pub(crate) struct AudioKernel {
     // tx: Sender<(AudioCommand, tracing::Span)>,
     /// the rodio sink used to play audio
     player: Arc<rodio::Sink>,
+    /// during a crossfade, the end of the previous song plays out on this sink while the next song starts on `player`
+    outgoing: Arc<rodio::Sink>,
+    /// tells the song on `player` to fade out, see [`Self::hand_over_current_song`]
+    fade_out_current: Mutex<Arc<AtomicBool>>,
     /// the queue of songs to play
     queue: Arc<Mutex<Queue>>,
     /// The value `1.0` is the "normal" volume (unfiltered input). Any value other than `1.0` will multiply each sample by this value.
     volume: Arc<Mutex<f32>>,
     /// whether the audio is muted
     muted: Arc<AtomicBool>,
+    /// The pre-amplifier gain in decibels, applied to each source independently of the volume
+    gain_db: Arc<Mutex<f32>>,
     /// the current song duration and the time played
     duration_info: Arc<Mutex<DurationInfo>>,
     /// whether the audio kernel is paused
     paused: Arc<AtomicBool>,
+    /// the state of the volume fade (if any)
+    fade: Arc<Mutex<FadeInfo>>,
+    /// how long the end of a song overlaps with the start of the next one, fading out as the next song fades in
+    crossfade_duration: Arc<Mutex<Duration>>,
+    /// how songs are levelled using their `ReplayGain`
+    replaygain_mode: Arc<Mutex<ReplayGainMode>>,
+    /// the playback speed, `1.0` is normal speed
+    speed: Arc<Mutex<f32>>,
+    /// the bands of the equalizer
+    equalizer: Arc<Mutex<Vec<EqBand>>>,
+    /// the levels of the most recently played window of audio
+    levels: Arc<Mutex<Option<LevelReading>>>,
+    /// where changes in the state of the kernel (such as songs finishing) are reported, if anywhere
+    events: Option<UnboundedSender<StateChange>>,
 }
 
 impl AudioKernel {
@@ -126,16 +288,28 @@ impl AudioKernel {
 
         let sink = rodio::Sink::try_new(&stream_handle).unwrap();
         sink.pause();
+        let outgoing = rodio::Sink::try_new(&stream_handle).unwrap();
+        outgoing.pause();
         let queue = Queue::new();
 
         Self {
             _music_output: (stream, stream_handle),
             player: sink.into(),
+            outgoing: outgoing.into(),
+            fade_out_current: Mutex::new(Arc::new(AtomicBool::new(false))),
             queue: Arc::new(Mutex::new(queue)),
             volume: Arc::new(Mutex::new(1.0)),
             muted: Arc::new(AtomicBool::new(false)),
+            gain_db: Arc::new(Mutex::new(0.0)),
             duration_info: Arc::new(Mutex::new(DurationInfo::default())),
             paused: Arc::new(AtomicBool::new(true)),
+            fade: Arc::new(Mutex::new(FadeInfo::default())),
+            crossfade_duration: Arc::new(Mutex::new(Duration::ZERO)),
+            replaygain_mode: Arc::new(Mutex::new(ReplayGainMode::default())),
+            speed: Arc::new(Mutex::new(1.0)),
+            equalizer: Arc::new(Mutex::new(eq::flat())),
+            levels: Arc::new(Mutex::new(None)),
+            events: None,
         }
     }
 
@@ -150,6 +324,7 @@ impl AudioKernel {
     #[cfg(feature = "mock_playback")]
     pub fn new() -> Self {
         let (sink, mut queue_rx) = rodio::Sink::new_idle();
+        let (outgoing, mut outgoing_queue_rx) = rodio::Sink::new_idle();
 
         // start a detached thread that continuously polls the queue_rx, until it receives a command to exit
         let (tx, rx) = tokio::sync::oneshot::channel();
@@ -166,6 +341,7 @@ impl AudioKernel {
                         () = async {
                             loop {
                                 queue_rx.next();
+                                outgoing_queue_rx.next();
                                 tokio::time::sleep(std::time::Duration::from_millis(1)).await;
                             }
                         } => {},
@@ -174,15 +350,26 @@ impl AudioKernel {
         });
 
         sink.pause();
+        outgoing.pause();
 
         Self {
             player: sink.into(),
+            outgoing: outgoing.into(),
+            fade_out_current: Mutex::new(Arc::new(AtomicBool::new(false))),
             queue_rx_end_tx: tx,
             queue: Arc::new(Mutex::new(Queue::new())),
             volume: Arc::new(Mutex::new(1.0)),
             muted: Arc::new(AtomicBool::new(false)),
+            gain_db: Arc::new(Mutex::new(0.0)),
             duration_info: Arc::new(Mutex::new(DurationInfo::default())),
             paused: Arc::new(AtomicBool::new(true)),
+            fade: Arc::new(Mutex::new(FadeInfo::default())),
+            crossfade_duration: Arc::new(Mutex::new(Duration::ZERO)),
+            replaygain_mode: Arc::new(Mutex::new(ReplayGainMode::default())),
+            speed: Arc::new(Mutex::new(1.0)),
+            equalizer: Arc::new(Mutex::new(eq::flat())),
+            levels: Arc::new(Mutex::new(None)),
+            events: None,
         }
     }
 
@@ -202,7 +389,7 @@ impl AudioKernel {
     ///
     /// if the `mock_playback` feature is enabled, this function may panic if it is unable to signal the `queue_rx` thread to end.
     pub fn init(
-        self,
+        mut self,
         tx: Sender<(AudioCommand, tracing::Span)>,
         rx: Receiver<(AudioCommand, tracing::Span)>,
     ) {
@@ -212,6 +399,8 @@ impl AudioKernel {
         // we won't be able to access this AudioKernel instance reliably, so we need to clone Arcs to all the values we need
         let duration_info = self.duration_info.clone();
         let paused = self.paused.clone();
+        let speed = self.speed.clone();
+        let crossfade_duration = self.crossfade_duration.clone();
 
         // NOTE: as of rodio v0.19.0, we have access to the `get_pos` command, which allows us to get the current position of the audio stream
         // it may seem like this means we don't need to have a duration watcher, but the key point is that we need to know when to skip to the next song
@@ -234,11 +423,12 @@ impl AudioKernel {
                                 tokio::time::sleep(sleep_time).await;
                                 let mut duration_info = duration_info.lock().unwrap();
                                 if !paused.load(std::sync::atomic::Ordering::Relaxed) {
-                                    // if we aren't paused, increment the time played
-                                    duration_info.time_played += sleep_time;
-                                    // if we're within the threshold of the end of the song, signal to the audio kernel to skip to the next song
-                                    if duration_info.time_played >= duration_info.current_duration.saturating_sub(duration_threshold) {
-                                        if let Err(e) = tx.send((AudioCommand::Queue(QueueCommand::SkipForward(1)), tracing::Span::current())) {
+                                    // if we aren't paused, increment the time played (which passes faster or slower depending on the playback speed)
+                                    duration_info.time_played += sleep_time.mul_f32(*speed.lock().unwrap());
+                                    // if we're within the threshold (or the crossfade) of the end of the song, signal to the audio kernel that the song is finished
+                                    let crossfade = duration_info.crossfade(*crossfade_duration.lock().unwrap());
+                                    if duration_info.time_played >= duration_info.current_duration.saturating_sub(duration_threshold.max(crossfade)) {
+                                        if let Err(e) = tx.send((AudioCommand::SongFinished, tracing::Span::current())) {
                                             error!("Failed to send command to audio kernel: {e}");
                                             panic!("Failed to send command to audio kernel: {e}");
                                         }
@@ -259,6 +449,7 @@ impl AudioKernel {
                 AudioCommand::TogglePlayback => self.toggle_playback(),
                 AudioCommand::RestartSong => self.restart_song(),
                 AudioCommand::ClearPlayer => self.clear_player(),
+                AudioCommand::SongFinished => self.song_finished(),
                 AudioCommand::Queue(command) => self.queue_control(command),
                 AudioCommand::Exit => break,
                 AudioCommand::ReportStatus(tx) => {
@@ -273,6 +464,31 @@ impl AudioKernel {
                 }
                 AudioCommand::Volume(command) => self.volume_control(command),
                 AudioCommand::Seek(seek, duration) => self.seek(seek, duration),
+                AudioCommand::SeekPercent(percent) => self.seek_percent(percent),
+                AudioCommand::SetGain(gain_db) => self.set_gain(gain_db),
+                AudioCommand::FadeToVolume(target, duration) => {
+                    self.fade_to_volume(target, duration);
+                }
+                AudioCommand::CancelFade => self.cancel_fade(),
+                AudioCommand::SetCrossfadeDuration(duration) => {
+                    self.set_crossfade_duration(duration);
+                }
+                AudioCommand::SetReplayGainMode(mode) => self.set_replaygain_mode(mode),
+                AudioCommand::SetSpeed(speed) => self.set_speed(speed),
+                AudioCommand::SetEqualizer(bands) => self.set_equalizer(bands),
+                AudioCommand::SetOutputDevice(name) => self.set_output_device(&name),
+                AudioCommand::ListOutputDevices(tx) => {
+                    if let Err(e) = tx.send(output_device_names()) {
+                        error!(
+                            "Audio Kernel failed to send the output devices to the receiver: {e:?}"
+                        );
+                    }
+                }
+                AudioCommand::ReportLevels(tx) => {
+                    if let Err(e) = tx.send(self.levels()) {
+                        error!("Audio Kernel failed to send the levels to the receiver: {e}");
+                    }
+                }
             }
         }
 
@@ -284,6 +500,7 @@ impl AudioKernel {
     #[instrument(skip(self))]
     fn play(&self) {
         self.player.play();
+        self.outgoing.play();
         self.paused
             .store(false, std::sync::atomic::Ordering::Relaxed);
     }
@@ -291,6 +508,7 @@ impl AudioKernel {
     #[instrument(skip(self))]
     fn pause(&self) {
         self.player.pause();
+        self.outgoing.pause();
         self.paused
             .store(true, std::sync::atomic::Ordering::Relaxed);
     }
@@ -309,8 +527,9 @@ impl AudioKernel {
         let paused = self.player.is_paused();
         self.clear_player();
 
-        if let Some(song) = self.queue.lock().unwrap().current_song() {
-            if let Err(e) = self.append_song_to_player(song) {
+        let current_song = self.queue.lock().unwrap().current_song().cloned();
+        if let Some(song) = current_song {
+            if let Err(e) = self.append_song_to_player(&song, false) {
                 error!("Failed to append song to player: {}", e);
             }
 
@@ -329,26 +548,56 @@ impl AudioKernel {
     #[instrument(skip(self))]
     fn clear_player(&self) {
         self.player.clear();
+        // cut off the end of the previous song too, if it's still playing out
+        self.outgoing.clear();
         self.paused
             .store(true, std::sync::atomic::Ordering::Relaxed);
         *self.duration_info.lock().unwrap() = DurationInfo::default();
     }
 
     #[instrument(skip(self))]
-    fn queue_control(&self, command: QueueCommand) {
+    fn queue_control(&mut self, command: QueueCommand) {
         match command {
             QueueCommand::Clear => self.clear(),
-            QueueCommand::SkipForward(n) => self.skip_forward(n),
-            QueueCommand::SkipBackward(n) => self.skip_backward(n),
-            QueueCommand::SetPosition(n) => self.set_position(n),
+            QueueCommand::SkipForward(n) => {
+                if n > 0 {
+                    self.song_skipped();
+                }
+                self.skip_forward(n);
+            }
+            QueueCommand::SkipBackward(n) => {
+                if n > 0 {
+                    self.song_skipped();
+                }
+                self.skip_backward(n);
+            }
+            QueueCommand::SetPosition(n) => {
+                if self.queue.lock().unwrap().current_index() != Some(n) {
+                    self.song_skipped();
+                }
+                self.set_position(n);
+            }
             QueueCommand::Shuffle => self.queue.lock().unwrap().shuffle(),
             QueueCommand::AddToQueue(song_box) => match *song_box {
                 OneOrMany::None => {}
                 OneOrMany::One(song) => self.add_song_to_queue(song),
                 OneOrMany::Many(songs) => self.add_songs_to_queue(songs),
             },
+            QueueCommand::InsertAt(index, song) => self.insert_song_into_queue(index, *song),
             QueueCommand::RemoveRange(range) => self.remove_range_from_queue(range),
+            QueueCommand::Move(from, to) => self.queue.lock().unwrap().move_song(from, to),
+            QueueCommand::SortByAnalysis(_, ranking) => {
+                self.queue.lock().unwrap().sort_by_analysis(&ranking);
+            }
             QueueCommand::SetRepeatMode(mode) => self.queue.lock().unwrap().set_repeat_mode(mode),
+            QueueCommand::Dedup => self
+                .queue
+                .lock()
+                .unwrap()
+                .dedup_by_key(|song| song.id.clone()),
+            QueueCommand::SetHistoryDepth(depth) => {
+                self.queue.lock().unwrap().set_history_depth(depth);
+            }
         }
     }
 
@@ -364,7 +613,8 @@ impl AudioKernel {
             let duration = duration_info.current_duration;
             drop(duration_info);
             let seek_percent =
-                Percent::new(seek_position.as_secs_f32() / duration.as_secs_f32() * 100.0);
+                Percent::from_ratio(seek_position.as_secs_f32(), duration.as_secs_f32())
+                    .unwrap_or_default();
             StateRuntime {
                 seek_position,
                 seek_percent,
@@ -378,8 +628,15 @@ impl AudioKernel {
         );
         let muted = self.muted.load(std::sync::atomic::Ordering::Relaxed);
         let volume = *self.volume.lock().unwrap();
+        let gain_db = *self.gain_db.lock().unwrap();
+        let fade_in_progress = self.fade.lock().unwrap().in_progress;
+        let crossfade_duration = *self.crossfade_duration.lock().unwrap();
+        let replaygain_mode = *self.replaygain_mode.lock().unwrap();
+        let playback_speed = *self.speed.lock().unwrap();
+        let equalizer = self.equalizer.lock().unwrap().clone().into_boxed_slice();
 
         let queued_songs = queue.queued_songs();
+        let history = queue.history().iter().cloned().collect();
         drop(queue);
 
         StateAudio {
@@ -391,18 +648,106 @@ impl AudioKernel {
             paused,
             muted,
             volume,
+            gain_db,
+            fade_in_progress,
+            crossfade_duration,
+            replaygain_mode,
+            playback_speed,
+            equalizer,
+            history,
         }
     }
 
+    /// The levels of the audio being played, with the volume applied.
+    ///
+    /// Reports silence if nothing is playing.
     #[instrument(skip(self))]
-    fn skip_forward(&self, n: usize) {
+    fn levels(&self) -> AudioLevels {
+        if self.player.is_paused() || self.player.empty() {
+            return AudioLevels::default();
+        }
+        self.levels
+            .lock()
+            .unwrap()
+            .map_or_else(AudioLevels::default, |reading| {
+                reading.levels(self.player.volume())
+            })
+    }
+
+    /// Report a change to the current song (if there is one) to whoever is listening for state changes.
+    fn report_current_song(&self, change: fn(SongId) -> StateChange) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let current_song = self
+            .queue
+            .lock()
+            .unwrap()
+            .current_song()
+            .map(|song| song.id.clone());
+        if let Some(id) = current_song {
+            if let Err(e) = events.send(change(id)) {
+                warn!("Failed to report a change to the current song: {e}");
+            }
+        }
+    }
+
+    /// The current song played to completion: report it, then move on to the next song.
+    ///
+    /// With a crossfade, the song is finished when its crossfade starts, and the rest of it (which fades out)
+    /// plays out while the next song starts (see [`Self::hand_over_current_song`]).
+    #[instrument(skip(self))]
+    fn song_finished(&mut self) {
+        self.report_current_song(StateChange::SongFinished);
+        self.skip_forward(1);
+    }
+
+    /// Make way for the next song on the player.
+    ///
+    /// With a crossfade, if a song is playing, it's handed over to the `outgoing` sink where it fades out
+    /// while the next song starts (and fades in) on the player. Otherwise, the player is just cleared.
+    ///
+    /// Returns whether the next song should fade in.
+    #[instrument(skip(self))]
+    fn hand_over_current_song(&mut self) -> bool {
+        if self.crossfade_duration.lock().unwrap().is_zero()
+            || self.player.is_paused()
+            || self.player.empty()
+        {
+            self.clear_player();
+            return false;
+        }
+
+        // the end of the song before this one may still be playing out
+        self.outgoing.clear();
+        self.fade_out_current
+            .lock()
+            .unwrap()
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        std::mem::swap(&mut self.player, &mut self.outgoing);
+        self.paused
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        *self.duration_info.lock().unwrap() = DurationInfo::default();
+        true
+    }
+
+    /// The current song is about to be changed by the user: if not enough of it has been played, report it as skipped.
+    fn song_skipped(&self) {
+        let duration_info = *self.duration_info.lock().unwrap();
+        if duration_info.time_played < duration_info.current_duration.mul_f32(SKIP_THRESHOLD) {
+            self.report_current_song(StateChange::SongSkipped);
+        }
+    }
+
+    #[instrument(skip(self))]
+    fn skip_forward(&mut self, n: usize) {
         let paused = self.player.is_paused();
-        self.clear_player();
+        let fade_in = self.hand_over_current_song();
 
         let next_song = self.queue.lock().unwrap().skip_forward(n).cloned();
 
         if let Some(song) = next_song {
-            if let Err(e) = self.append_song_to_player(&song) {
+            if let Err(e) = self.append_song_to_player(&song, fade_in) {
                 error!("Failed to append song to player: {}", e);
             }
 
@@ -419,14 +764,14 @@ impl AudioKernel {
     }
 
     #[instrument(skip(self))]
-    fn skip_backward(&self, n: usize) {
+    fn skip_backward(&mut self, n: usize) {
         let paused = self.player.is_paused();
-        self.clear_player();
+        let fade_in = self.hand_over_current_song();
 
         let next_song = self.queue.lock().unwrap().skip_backward(n).cloned();
 
         if let Some(song) = next_song {
-            if let Err(e) = self.append_song_to_player(&song) {
+            if let Err(e) = self.append_song_to_player(&song, fade_in) {
                 error!("Failed to append song to player: {}", e);
             }
             if !paused {
@@ -436,9 +781,9 @@ impl AudioKernel {
     }
 
     #[instrument(skip(self))]
-    fn set_position(&self, n: usize) {
+    fn set_position(&mut self, n: usize) {
         let paused = self.player.is_paused();
-        self.clear_player();
+        let fade_in = self.hand_over_current_song();
 
         let mut binding = self.queue.lock().unwrap();
         binding.set_current_index(n);
@@ -446,7 +791,7 @@ impl AudioKernel {
         drop(binding);
 
         if let Some(song) = next_song {
-            if let Err(e) = self.append_song_to_player(&song) {
+            if let Err(e) = self.append_song_to_player(&song, fade_in) {
                 error!("Failed to append song to player: {e}");
             }
             if !paused {
@@ -466,7 +811,7 @@ impl AudioKernel {
             if let Some(song) =
                 current_index.map_or_else(|| self.get_next_song(), |_| self.get_current_song())
             {
-                if let Err(e) = self.append_song_to_player(&song) {
+                if let Err(e) = self.append_song_to_player(&song, false) {
                     error!("Failed to append song to player: {e}");
                 }
                 self.play();
@@ -485,7 +830,26 @@ impl AudioKernel {
             if let Some(song) =
                 current_index.map_or_else(|| self.get_next_song(), |_| self.get_current_song())
             {
-                if let Err(e) = self.append_song_to_player(&song) {
+                if let Err(e) = self.append_song_to_player(&song, false) {
+                    error!("Failed to append song to player: {e}");
+                }
+                self.play();
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    fn insert_song_into_queue(&self, index: usize, song: Song) {
+        self.queue.lock().unwrap().insert_song(index, song);
+
+        // if the player is empty, start playback
+        if self.player.empty() {
+            let current_index = self.queue.lock().unwrap().current_index();
+
+            if let Some(song) =
+                current_index.map_or_else(|| self.get_next_song(), |_| self.get_current_song())
+            {
+                if let Err(e) = self.append_song_to_player(&song, false) {
                     error!("Failed to append song to player: {e}");
                 }
                 self.play();
@@ -510,7 +874,7 @@ impl AudioKernel {
         if current_to_be_removed {
             self.clear_player();
             if let Some(song) = self.get_current_song() {
-                if let Err(e) = self.append_song_to_player(&song) {
+                if let Err(e) = self.append_song_to_player(&song, false) {
                     error!("Failed to append song to player: {e}");
                 }
                 if !paused {
@@ -544,9 +908,92 @@ impl AudioKernel {
         self.player.append(source);
     }
 
+    /// Append the song to the player, run through the equalizer, amplified by the gain and `ReplayGain`,
+    /// and faded by the crossfade, with its levels measured by a [`LevelMeter`].
+    ///
+    /// The song fades in if `fade_in` (because the previous song is still playing out), and out at its end or once it's skipped.
+    /// The fades follow the progress of the song itself rather than the kernel's `duration_info`,
+    /// so the song keeps fading out after it's been handed over to the `outgoing` sink (see [`Self::hand_over_current_song`]).
+    ///
+    /// The queue must not be locked when this is called.
     #[instrument(skip(self))]
-    fn append_song_to_player(&self, song: &Song) -> Result<(), LibraryError> {
-        let source = Decoder::new(BufReader::new(File::open(&song.path)?))?.convert_samples();
+    fn append_song_to_player(&self, song: &Song, fade_in: bool) -> Result<(), LibraryError> {
+        let gain_db = self.gain_db.clone();
+        let crossfade_duration = self.crossfade_duration.clone();
+        let runtime = song.runtime;
+        let replaygain_mode = self.replaygain_mode.clone();
+        let equalizer = self.equalizer.clone();
+        let track_gain = replaygain_adjustment(song.replaygain_track_gain);
+        // songs whose album gain hasn't been computed fall back to their track gain
+        let album_gain =
+            replaygain_adjustment(song.replaygain_album_gain.or(song.replaygain_track_gain));
+        let replaygain = move |mode| match mode {
+            ReplayGainMode::Off => 0.0,
+            ReplayGainMode::Track => track_gain,
+            ReplayGainMode::Album => album_gain,
+        };
+
+        let fade_out = Arc::new(AtomicBool::new(false));
+        *self.fade_out_current.lock().unwrap() = fade_out.clone();
+        let mut progress = CrossfadeProgress {
+            fade_in,
+            ..CrossfadeProgress::default()
+        };
+
+        let initial_factor =
+            db_to_linear(*gain_db.lock().unwrap() + replaygain(*replaygain_mode.lock().unwrap()))
+                * progress.factor(
+                    DurationInfo {
+                        time_played: Duration::ZERO,
+                        current_duration: runtime,
+                    },
+                    *crossfade_duration.lock().unwrap(),
+                );
+        let decoder =
+            Decoder::new(BufReader::new(File::open(&song.path)?))?.convert_samples::<f32>();
+        let bands = equalizer.lock().unwrap().clone();
+        let source = Equalizer::new(decoder, &bands)
+            .amplify(initial_factor)
+            .track_position()
+            .stoppable()
+            // keep the equalizer and the amplification factor in sync with the gain, `ReplayGain` mode, and the crossfade,
+            // so changes apply to the song that is already playing
+            .periodic_access(
+                Duration::from_millis(GAIN_UPDATE_PERIOD_MS),
+                move |source: &mut Stoppable<TrackPosition<Amplify<Equalizer<_>>>>| {
+                    if progress.fade_out_from.is_none()
+                        && fade_out.load(std::sync::atomic::Ordering::Relaxed)
+                    {
+                        progress.fade_out_from = Some(progress.elapsed);
+                    }
+                    let crossfade_factor = progress.factor(
+                        DurationInfo {
+                            time_played: source.inner().get_pos(),
+                            current_duration: runtime,
+                        },
+                        *crossfade_duration.lock().unwrap(),
+                    );
+                    progress.elapsed += Duration::from_millis(GAIN_UPDATE_PERIOD_MS);
+
+                    // there's nothing left to hear of a skipped song once it's faded out
+                    if progress.fade_out_from.is_some() && crossfade_factor <= 0.0 {
+                        source.stop();
+                        return;
+                    }
+                    let source = source.inner_mut().inner_mut();
+
+                    let bands = equalizer.lock().unwrap();
+                    if source.inner().bands() != bands.as_slice() {
+                        source.inner_mut().set_bands(&bands);
+                    }
+                    drop(bands);
+
+                    let gain_db =
+                        *gain_db.lock().unwrap() + replaygain(*replaygain_mode.lock().unwrap());
+                    source.set_factor(db_to_linear(gain_db) * crossfade_factor);
+                },
+            );
+        let source = LevelMeter::new(source, self.levels.clone());
         *self.duration_info.lock().unwrap() = DurationInfo {
             time_played: Duration::from_secs(0),
             current_duration: song.runtime,
@@ -558,6 +1005,14 @@ impl AudioKernel {
 
     #[instrument(skip(self))]
     fn volume_control(&self, command: VolumeCommand) {
+        // explicitly changing the volume takes precedence over a fade
+        if matches!(
+            command,
+            VolumeCommand::Up(_) | VolumeCommand::Down(_) | VolumeCommand::Set(_)
+        ) {
+            self.cancel_fade();
+        }
+
         match command {
             VolumeCommand::Up(percent) => {
                 let mut volume = self.volume.lock().unwrap();
@@ -586,14 +1041,218 @@ impl AudioKernel {
             }
         }
 
-        if self.muted.load(std::sync::atomic::Ordering::Relaxed) {
-            self.player.set_volume(0.0);
+        let volume = if self.muted.load(std::sync::atomic::Ordering::Relaxed) {
+            0.0
         } else {
-            self.player
-                .set_volume(self.volume.lock().unwrap().to_owned());
+            *self.volume.lock().unwrap()
+        };
+        self.player.set_volume(volume);
+        self.outgoing.set_volume(volume);
+    }
+
+    /// Gradually change the volume to `target` over `duration`, in a detached thread.
+    ///
+    /// Starting a new fade cancels the one in progress (if any).
+    #[instrument(skip(self))]
+    fn fade_to_volume(&self, target: f32, duration: Duration) {
+        let start = *self.volume.lock().unwrap();
+        let target = target.clamp(MIN_VOLUME, MAX_VOLUME);
+        let generation = {
+            let mut fade = self.fade.lock().unwrap();
+            fade.generation = fade.generation.wrapping_add(1);
+            fade.in_progress = true;
+            fade.generation
+        };
+
+        let fade = self.fade.clone();
+        let volume = self.volume.clone();
+        let muted = self.muted.clone();
+        let player = self.player.clone();
+        let outgoing = self.outgoing.clone();
+        let step_time = duration / u32::from(FADE_STEPS);
+
+        let result = std::thread::Builder::new()
+            .name(String::from("Volume Fader"))
+            .spawn(move || {
+                for step in 1..=FADE_STEPS {
+                    std::thread::sleep(step_time);
+                    // hold the lock while changing the volume, so a cancelled fade can't take one last step
+                    let mut fade = fade.lock().unwrap();
+                    if fade.generation != generation {
+                        return;
+                    }
+                    let new_volume = if step == FADE_STEPS {
+                        target
+                    } else {
+                        (target - start).mul_add(f32::from(step) / f32::from(FADE_STEPS), start)
+                    };
+                    *volume.lock().unwrap() = new_volume;
+                    if !muted.load(std::sync::atomic::Ordering::Relaxed) {
+                        player.set_volume(new_volume);
+                        outgoing.set_volume(new_volume);
+                    }
+                    if step == FADE_STEPS {
+                        fade.in_progress = false;
+                    }
+                }
+            });
+
+        if let Err(e) = result {
+            error!("Failed to spawn the volume fader thread: {e}");
+            self.cancel_fade();
         }
     }
 
+    #[instrument(skip(self))]
+    fn cancel_fade(&self) {
+        let mut fade = self.fade.lock().unwrap();
+        fade.generation = fade.generation.wrapping_add(1);
+        fade.in_progress = false;
+    }
+
+    #[instrument(skip(self))]
+    fn set_crossfade_duration(&self, duration: Duration) {
+        *self.crossfade_duration.lock().unwrap() = duration.min(MAX_CROSSFADE_DURATION);
+    }
+
+    /// Switch the audio output to the output device named `name`.
+    ///
+    /// The player is reconnected to the new device, and the current song resumes from where it was.
+    /// If the device can't be found or opened, the current output is kept.
+    #[cfg(not(feature = "mock_playback"))]
+    #[instrument(skip(self))]
+    fn set_output_device(&mut self, name: &str) {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+        let device = match rodio::cpal::default_host().output_devices() {
+            Ok(mut devices) => devices.find(|device| device.name().is_ok_and(|n| n == name)),
+            Err(e) => {
+                error!("Failed to list the output devices: {e}");
+                return;
+            }
+        };
+        let Some(device) = device else {
+            error!("Output device not found: {name}");
+            return;
+        };
+        let (stream, stream_handle) = match rodio::OutputStream::try_from_device(&device) {
+            Ok(output) => output,
+            Err(e) => {
+                error!("Failed to open output device {name}: {e}");
+                return;
+            }
+        };
+        let (sink, outgoing) = match (
+            rodio::Sink::try_new(&stream_handle),
+            rodio::Sink::try_new(&stream_handle),
+        ) {
+            (Ok(sink), Ok(outgoing)) => (sink, outgoing),
+            (Err(e), _) | (_, Err(e)) => {
+                error!("Failed to connect to output device {name}: {e}");
+                return;
+            }
+        };
+
+        // the fade would keep changing the volume of the old player
+        self.cancel_fade();
+        let paused = self.player.is_paused();
+        let time_played = self.duration_info.lock().unwrap().time_played;
+
+        let volume = if self.muted.load(std::sync::atomic::Ordering::Relaxed) {
+            0.0
+        } else {
+            *self.volume.lock().unwrap()
+        };
+        for sink in [&sink, &outgoing] {
+            sink.pause();
+            sink.set_speed(*self.speed.lock().unwrap());
+            sink.set_volume(volume);
+        }
+
+        self.player.stop();
+        self.player = sink.into();
+        // the end of the previous song (if it was still playing out) is cut off
+        self.outgoing.stop();
+        self.outgoing = outgoing.into();
+        self._music_output = (stream, stream_handle);
+        self.paused
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        *self.duration_info.lock().unwrap() = DurationInfo::default();
+
+        // resume the current song from where it was
+        if let Some(song) = self.get_current_song() {
+            if let Err(e) = self.append_song_to_player(&song, false) {
+                error!("Failed to append song to player: {e}");
+            }
+            if !time_played.is_zero() {
+                self.seek(SeekType::Absolute, time_played);
+            }
+            if !paused {
+                self.play();
+            }
+        }
+    }
+
+    /// Switch the audio output to the output device named `name`.
+    ///
+    /// this is the version for tests, where there is no audio output, so there are no devices to switch to
+    #[cfg(feature = "mock_playback")]
+    #[allow(clippy::unused_self)]
+    #[instrument(skip(self))]
+    fn set_output_device(&mut self, name: &str) {
+        error!("Output device not found: {name}");
+    }
+
+    /// Replace the bands of the equalizer, clamping them to the allowed range and dropping the ones that aren't finite.
+    #[instrument(skip(self))]
+    fn set_equalizer(&self, bands: Vec<EqBand>) {
+        *self.equalizer.lock().unwrap() = bands.into_iter().filter_map(EqBand::clamped).collect();
+    }
+
+    #[instrument(skip(self))]
+    fn set_replaygain_mode(&self, mode: ReplayGainMode) {
+        *self.replaygain_mode.lock().unwrap() = mode;
+    }
+
+    /// Set the playback speed, clamped to the allowed range.
+    ///
+    /// The speed is applied by the player, which wraps every song it plays in a [`rodio::source::Speed`],
+    /// so it takes effect immediately and carries over to the songs that play next.
+    /// At `1.0` the wrapper leaves the samples untouched, so there's no resampling overhead.
+    /// Non-finite speeds are ignored.
+    #[instrument(skip(self))]
+    fn set_speed(&self, speed: f32) {
+        if !speed.is_finite() {
+            return;
+        }
+        let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        // hold the lock while updating the player so the duration watcher sees both change together
+        let mut current_speed = self.speed.lock().unwrap();
+        *current_speed = speed;
+        self.player.set_speed(speed);
+        self.outgoing.set_speed(speed);
+    }
+
+    #[instrument(skip(self))]
+    fn set_gain(&self, gain_db: f32) {
+        *self.gain_db.lock().unwrap() = gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB);
+    }
+
+    /// Seek to `percent` (clamped to 0-100) of the way through the current song.
+    ///
+    /// Does nothing if there is no current song, or if `percent` is NaN.
+    #[instrument(skip(self))]
+    fn seek_percent(&self, percent: f32) {
+        if percent.is_nan() {
+            return;
+        }
+        let Some(song) = self.get_current_song() else {
+            return;
+        };
+        let percent = percent.clamp(0.0, 100.0);
+        self.seek(SeekType::Absolute, song.runtime.mul_f32(percent / 100.0));
+    }
+
     #[instrument(skip(self))]
     fn seek(&self, seek: SeekType, duration: Duration) {
         // get a lock on the current song duration and time played
@@ -740,6 +1399,231 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn test_fade_to_volume(audio_kernel: AudioKernel) {
+        audio_kernel.fade_to_volume(0.0, Duration::from_millis(100));
+        assert!(audio_kernel.state().fade_in_progress);
+
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(!audio_kernel.state().fade_in_progress);
+        assert_eq!(*audio_kernel.volume.lock().unwrap(), 0.0);
+
+        // the target volume is clamped
+        audio_kernel.fade_to_volume(100.0, Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!audio_kernel.state().fade_in_progress);
+        assert_eq!(*audio_kernel.volume.lock().unwrap(), MAX_VOLUME);
+    }
+
+    #[rstest]
+    fn test_cancel_fade(audio_kernel: AudioKernel) {
+        audio_kernel.fade_to_volume(0.0, Duration::from_secs(10));
+        assert!(audio_kernel.state().fade_in_progress);
+
+        audio_kernel.cancel_fade();
+        assert!(!audio_kernel.state().fade_in_progress);
+        let volume = *audio_kernel.volume.lock().unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(*audio_kernel.volume.lock().unwrap(), volume);
+
+        // explicitly setting the volume also cancels a fade
+        audio_kernel.fade_to_volume(0.0, Duration::from_secs(10));
+        audio_kernel.volume_control(VolumeCommand::Set(0.5));
+        assert!(!audio_kernel.state().fade_in_progress);
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(*audio_kernel.volume.lock().unwrap(), 0.5);
+    }
+
+    #[rstest]
+    #[case::unity(0.0, 1.0)]
+    #[case::plus_6db(6.0, 1.995_262)]
+    #[case::minus_6db(-6.0, 0.501_187_2)]
+    #[case::plus_20db(20.0, 10.0)]
+    fn test_db_to_linear(#[case] gain_db: f32, #[case] expected: f32) {
+        assert!((db_to_linear(gain_db) - expected).abs() < 1e-5);
+    }
+
+    #[rstest]
+    fn test_set_gain(audio_kernel: AudioKernel) {
+        audio_kernel.set_gain(3.0);
+        assert_eq!(*audio_kernel.gain_db.lock().unwrap(), 3.0);
+
+        // gain is clamped to the allowed range
+        audio_kernel.set_gain(100.0);
+        assert_eq!(*audio_kernel.gain_db.lock().unwrap(), MAX_GAIN_DB);
+        audio_kernel.set_gain(-100.0);
+        assert_eq!(*audio_kernel.gain_db.lock().unwrap(), MIN_GAIN_DB);
+
+        // muting doesn't reset the gain
+        audio_kernel.set_gain(-3.0);
+        audio_kernel.volume_control(VolumeCommand::Mute);
+        audio_kernel.volume_control(VolumeCommand::Unmute);
+        assert_eq!(*audio_kernel.gain_db.lock().unwrap(), -3.0);
+        assert_eq!(audio_kernel.state().gain_db, -3.0);
+    }
+
+    #[rstest]
+    fn test_set_output_device_not_found(mut audio_kernel: AudioKernel) {
+        audio_kernel.set_speed(1.5);
+        audio_kernel.set_output_device("not a device");
+
+        // the player is left as it was
+        assert_eq!(audio_kernel.player.speed(), 1.5);
+        assert!(audio_kernel.player.is_paused());
+    }
+
+    #[rstest]
+    fn test_set_equalizer(audio_kernel: AudioKernel) {
+        assert_eq!(
+            audio_kernel.state().equalizer.as_ref(),
+            eq::flat().as_slice()
+        );
+
+        audio_kernel.set_equalizer(vec![
+            EqBand::new(60.0, 3.0, 1.0),
+            EqBand::new(1000.0, 100.0, 1.0),
+            EqBand::new(f32::NAN, 3.0, 1.0),
+        ]);
+        assert_eq!(
+            audio_kernel.state().equalizer.as_ref(),
+            &[
+                EqBand::new(60.0, 3.0, 1.0),
+                // bands are clamped, and dropped if they aren't finite
+                EqBand::new(1000.0, eq::MAX_GAIN_DB, 1.0),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_set_speed(audio_kernel: AudioKernel) {
+        assert_eq!(audio_kernel.state().playback_speed, 1.0);
+
+        audio_kernel.set_speed(1.5);
+        assert_eq!(audio_kernel.state().playback_speed, 1.5);
+        assert_eq!(audio_kernel.player.speed(), 1.5);
+
+        // speed is clamped to the allowed range
+        audio_kernel.set_speed(10.0);
+        assert_eq!(audio_kernel.state().playback_speed, MAX_SPEED);
+        audio_kernel.set_speed(0.0);
+        assert_eq!(audio_kernel.state().playback_speed, MIN_SPEED);
+
+        // non-finite speeds are ignored
+        audio_kernel.set_speed(f32::NAN);
+        assert_eq!(audio_kernel.state().playback_speed, MIN_SPEED);
+        assert_eq!(audio_kernel.player.speed(), MIN_SPEED);
+    }
+
+    #[rstest]
+    #[case::disabled(Duration::ZERO, true, None, Duration::ZERO, 1.0)]
+    #[case::start(Duration::from_secs(2), true, None, Duration::ZERO, 0.0)]
+    // songs only fade in when the previous song is still playing out
+    #[case::start_without_fade_in(Duration::from_secs(2), false, None, Duration::ZERO, 1.0)]
+    #[case::fading_in(Duration::from_secs(2), true, None, Duration::from_secs(1), 0.5)]
+    #[case::middle(Duration::from_secs(2), true, None, Duration::from_secs(50), 1.0)]
+    #[case::fading_out(
+        Duration::from_secs(2),
+        true,
+        None,
+        Duration::from_millis(99_500),
+        0.25
+    )]
+    #[case::end(Duration::from_secs(2), true, None, Duration::from_secs(100), 0.0)]
+    #[case::skipped(
+        Duration::from_secs(2),
+        false,
+        Some(Duration::from_secs(50)),
+        Duration::from_secs(51),
+        0.5
+    )]
+    #[case::skipped_faded_out(
+        Duration::from_secs(2),
+        false,
+        Some(Duration::from_secs(50)),
+        Duration::from_secs(53),
+        0.0
+    )]
+    // the crossfade is at most half of the song
+    #[case::longer_than_song(Duration::from_secs(200), true, None, Duration::from_secs(25), 0.5)]
+    fn test_crossfade_factor(
+        #[case] crossfade: Duration,
+        #[case] fade_in: bool,
+        #[case] fade_out_from: Option<Duration>,
+        #[case] time_played: Duration,
+        #[case] expected: f32,
+    ) {
+        let progress = CrossfadeProgress {
+            elapsed: time_played,
+            fade_in,
+            fade_out_from,
+        };
+        let duration_info = DurationInfo {
+            time_played,
+            current_duration: Duration::from_secs(100),
+        };
+        let factor = progress.factor(duration_info, crossfade);
+        assert!((factor - expected).abs() < 1e-5, "{factor} !~= {expected}");
+    }
+
+    #[test]
+    fn test_crossfade_factor_after_seeking() {
+        // the fade in follows how long the song has been playing, not its position
+        let progress = CrossfadeProgress {
+            elapsed: Duration::from_secs(1),
+            fade_in: true,
+            fade_out_from: None,
+        };
+        let duration_info = DurationInfo {
+            time_played: Duration::from_secs(50),
+            current_duration: Duration::from_secs(100),
+        };
+        let factor = progress.factor(duration_info, Duration::from_secs(2));
+        assert!((factor - 0.5).abs() < 1e-5, "{factor} !~= 0.5");
+    }
+
+    #[rstest]
+    #[case::not_computed(None, 0.0)]
+    #[case::quiet(Some(3.0), 7.0)]
+    #[case::loud(Some(-10.0), -6.0)]
+    #[case::clamped_up(Some(20.0), MAX_REPLAYGAIN_DB)]
+    #[case::clamped_down(Some(-30.0), -MAX_REPLAYGAIN_DB)]
+    fn test_replaygain_adjustment(#[case] replaygain: Option<f32>, #[case] expected: f32) {
+        let actual = replaygain_adjustment(replaygain);
+        assert!(
+            (actual - expected).abs() < f32::EPSILON,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[rstest]
+    fn test_set_replaygain_mode(audio_kernel: AudioKernel) {
+        assert_eq!(audio_kernel.state().replaygain_mode, ReplayGainMode::Off);
+
+        audio_kernel.set_replaygain_mode(ReplayGainMode::Album);
+        assert_eq!(audio_kernel.state().replaygain_mode, ReplayGainMode::Album);
+
+        audio_kernel.set_replaygain_mode(ReplayGainMode::Track);
+        assert_eq!(audio_kernel.state().replaygain_mode, ReplayGainMode::Track);
+    }
+
+    #[rstest]
+    fn test_set_crossfade_duration(audio_kernel: AudioKernel) {
+        assert_eq!(audio_kernel.state().crossfade_duration, Duration::ZERO);
+
+        audio_kernel.set_crossfade_duration(Duration::from_secs(3));
+        assert_eq!(
+            audio_kernel.state().crossfade_duration,
+            Duration::from_secs(3)
+        );
+
+        // the crossfade duration is clamped
+        audio_kernel.set_crossfade_duration(Duration::from_secs(60));
+        assert_eq!(
+            audio_kernel.state().crossfade_duration,
+            MAX_CROSSFADE_DURATION
+        );
+    }
+
     mod playback_tests {
         //! These are tests that require the audio kernel to be able to play audio
         //! As such, they cannot be run on CI.
@@ -830,7 +1714,216 @@ mod tests {
         #[rstest]
         #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
         #[tokio::test]
-        async fn test_audio_kernel_skip_forward(audio_kernel: AudioKernel) {
+        async fn test_audio_kernel_song_finished(mut audio_kernel: AudioKernel) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            audio_kernel.events = Some(tx);
+
+            let songs = vec![
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+            ];
+            audio_kernel.queue_control(QueueCommand::AddToQueue(Box::new(OneOrMany::Many(
+                songs.clone(),
+            ))));
+
+            // the song finishing is reported, and the next song starts playing
+            audio_kernel.song_finished();
+            assert_eq!(
+                rx.try_recv(),
+                Ok(StateChange::SongFinished(songs[0].id.clone()))
+            );
+            let state = audio_kernel.state();
+            assert_eq!(state.queue_position, Some(1));
+            assert!(!state.paused);
+
+            // skipping a song isn't reported as finishing it
+            audio_kernel.queue_control(QueueCommand::SkipForward(1));
+            assert_eq!(
+                rx.try_recv(),
+                Ok(StateChange::SongSkipped(songs[1].id.clone()))
+            );
+            assert!(rx.try_recv().is_err());
+            assert_eq!(audio_kernel.state().queue_position, Some(2));
+        }
+
+        #[rstest]
+        #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_audio_kernel_song_finished_crossfade(mut audio_kernel: AudioKernel) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            audio_kernel.events = Some(tx);
+
+            let songs = vec![
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+            ];
+            audio_kernel.set_crossfade_duration(Duration::from_secs(2));
+            audio_kernel.queue_control(QueueCommand::AddToQueue(Box::new(OneOrMany::Many(
+                songs.clone(),
+            ))));
+            assert!(audio_kernel.outgoing.empty());
+
+            // the end of the finished song keeps playing while the next song starts
+            audio_kernel.song_finished();
+            assert_eq!(
+                rx.try_recv(),
+                Ok(StateChange::SongFinished(songs[0].id.clone()))
+            );
+            assert!(!audio_kernel.outgoing.empty());
+            assert!(!audio_kernel.outgoing.is_paused());
+            assert!(!audio_kernel.player.empty());
+            let state = audio_kernel.state();
+            assert_eq!(state.queue_position, Some(1));
+            assert!(!state.paused);
+        }
+
+        #[rstest]
+        #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_audio_kernel_song_skipped_crossfade(mut audio_kernel: AudioKernel) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+
+            let songs = vec![
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+                Song::try_load_into_db(
+                    &db,
+                    create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                )
+                .await
+                .unwrap(),
+            ];
+            audio_kernel.set_crossfade_duration(Duration::from_secs(2));
+            audio_kernel.queue_control(QueueCommand::AddToQueue(Box::new(OneOrMany::Many(
+                songs.clone(),
+            ))));
+
+            // the skipped song fades out while the next one starts, however the song is skipped
+            for (command, position) in [
+                (QueueCommand::SkipForward(1), 1),
+                (QueueCommand::SkipBackward(1), 0),
+                (QueueCommand::SetPosition(2), 2),
+            ] {
+                audio_kernel.queue_control(command);
+                assert!(!audio_kernel.outgoing.empty());
+                assert!(!audio_kernel.outgoing.is_paused());
+                assert!(!audio_kernel.player.empty());
+                let state = audio_kernel.state();
+                assert_eq!(state.queue_position, Some(position));
+                assert!(!state.paused);
+            }
+
+            // when paused, there's nothing to fade out
+            audio_kernel.pause();
+            audio_kernel.queue_control(QueueCommand::SkipBackward(1));
+            assert!(audio_kernel.outgoing.empty());
+            assert!(!audio_kernel.player.empty());
+            let state = audio_kernel.state();
+            assert_eq!(state.queue_position, Some(1));
+            assert!(state.paused);
+        }
+
+        #[rstest]
+        #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_audio_kernel_song_skipped(mut audio_kernel: AudioKernel) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            audio_kernel.events = Some(tx);
+
+            let mut songs = Vec::new();
+            for _ in 0..3 {
+                songs.push(
+                    Song::try_load_into_db(
+                        &db,
+                        create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                    )
+                    .await
+                    .unwrap(),
+                );
+            }
+            audio_kernel.queue_control(QueueCommand::AddToQueue(Box::new(OneOrMany::Many(
+                songs.clone(),
+            ))));
+
+            // skipping a song right after it starts is reported
+            audio_kernel.queue_control(QueueCommand::SkipForward(1));
+            assert_eq!(
+                rx.try_recv(),
+                Ok(StateChange::SongSkipped(songs[0].id.clone()))
+            );
+
+            // but not once most of it has been played
+            {
+                let mut duration_info = audio_kernel.duration_info.lock().unwrap();
+                duration_info.time_played = duration_info.current_duration.mul_f32(0.9);
+            }
+            audio_kernel.queue_control(QueueCommand::SkipForward(1));
+            assert!(rx.try_recv().is_err());
+            assert_eq!(audio_kernel.state().queue_position, Some(2));
+
+            // setting the position to the current song isn't a skip, but changing it is
+            audio_kernel.queue_control(QueueCommand::SetPosition(2));
+            assert!(rx.try_recv().is_err());
+            audio_kernel.queue_control(QueueCommand::SetPosition(0));
+            assert_eq!(
+                rx.try_recv(),
+                Ok(StateChange::SongSkipped(songs[2].id.clone()))
+            );
+            audio_kernel.queue_control(QueueCommand::SkipBackward(0));
+            assert!(rx.try_recv().is_err());
+        }
+
+        #[rstest]
+        #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_audio_kernel_skip_forward(mut audio_kernel: AudioKernel) {
             init();
             let db = init_test_database().await.unwrap();
             let tempdir = tempfile::tempdir().unwrap();
@@ -951,6 +2044,61 @@ mod tests {
             sender.send(AudioCommand::Exit);
         }
 
+        #[rstest]
+        #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_insert_song_into_queue(
+            #[from(audio_kernel_sender)] sender: Arc<AudioKernelSender>,
+        ) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+            let mut songs = Vec::new();
+            for _ in 0..3 {
+                songs.push(
+                    Song::try_load_into_db(
+                        &db,
+                        create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+                    )
+                    .await
+                    .unwrap(),
+                );
+            }
+
+            // inserting into an empty queue starts playback
+            sender.send(AudioCommand::Queue(QueueCommand::InsertAt(
+                5,
+                Box::new(songs[0].clone()),
+            )));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(state.queue_position, Some(0));
+            assert_eq!(state.current_song, Some(songs[0].clone()));
+            assert!(!state.paused);
+
+            // inserting before the current song doesn't change the current song
+            sender.send(AudioCommand::Queue(QueueCommand::InsertAt(
+                0,
+                Box::new(songs[1].clone()),
+            )));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(state.queue_position, Some(1));
+            assert_eq!(state.current_song, Some(songs[0].clone()));
+
+            // inserting past the end appends
+            sender.send(AudioCommand::Queue(QueueCommand::InsertAt(
+                10,
+                Box::new(songs[2].clone()),
+            )));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(
+                state.queue.as_ref(),
+                &[songs[1].clone(), songs[0].clone(), songs[2].clone()]
+            );
+            assert_eq!(state.queue_position, Some(1));
+
+            sender.send(AudioCommand::Exit);
+        }
+
         #[rstest]
         #[timeout(Duration::from_secs(5))] // if the test takes longer than this, the test can be considered a failure
         #[tokio::test]
@@ -1430,5 +2578,90 @@ mod tests {
 
             sender.send(AudioCommand::Exit);
         }
+
+        #[rstest]
+        #[timeout(Duration::from_secs(8))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_seek_percent(#[from(audio_kernel_sender)] sender: Arc<AudioKernelSender>) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+
+            // seeking with nothing playing does nothing
+            sender.send(AudioCommand::SeekPercent(50.0));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(state.current_song, None);
+            assert_eq!(state.runtime, None);
+
+            let song = Song::try_load_into_db(
+                &db,
+                create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+            )
+            .await
+            .unwrap();
+            sender.send(AudioCommand::Queue(QueueCommand::AddToQueue(Box::new(
+                OneOrMany::One(song.clone()),
+            ))));
+            sender.send(AudioCommand::Pause);
+
+            sender.send(AudioCommand::SeekPercent(25.0));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(
+                state.runtime.unwrap().seek_position,
+                song.runtime.mul_f32(0.25)
+            );
+            assert!(state.paused);
+
+            // out of range percentages are clamped
+            sender.send(AudioCommand::SeekPercent(-10.0));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(state.runtime.unwrap().seek_position, Duration::ZERO);
+            assert_eq!(state.current_song, Some(song.clone()));
+
+            // NaN is ignored
+            sender.send(AudioCommand::SeekPercent(50.0));
+            sender.send(AudioCommand::SeekPercent(f32::NAN));
+            let state = get_state(sender.clone()).await;
+            assert_eq!(
+                state.runtime.unwrap().seek_position,
+                song.runtime.mul_f32(0.5)
+            );
+
+            sender.send(AudioCommand::Exit);
+        }
+
+        #[rstest]
+        #[timeout(Duration::from_secs(8))] // if the test takes longer than this, the test can be considered a failure
+        #[tokio::test]
+        async fn test_report_levels(#[from(audio_kernel_sender)] sender: Arc<AudioKernelSender>) {
+            init();
+            let db = init_test_database().await.unwrap();
+            let tempdir = tempfile::tempdir().unwrap();
+
+            let get_levels = || async {
+                let (tx, rx) = tokio::sync::oneshot::channel();
+                sender.send(AudioCommand::ReportLevels(tx));
+                rx.await.unwrap()
+            };
+
+            // nothing is playing, so the levels are silence
+            assert_eq!(get_levels().await, AudioLevels::default());
+
+            let song = Song::try_load_into_db(
+                &db,
+                create_song_metadata(&tempdir, arb_song_case()()).unwrap(),
+            )
+            .await
+            .unwrap();
+            sender.send(AudioCommand::Queue(QueueCommand::AddToQueue(Box::new(
+                OneOrMany::One(song),
+            ))));
+
+            // playback is paused, so the levels are silence
+            sender.send(AudioCommand::Pause);
+            assert_eq!(get_levels().await, AudioLevels::default());
+
+            sender.send(AudioCommand::Exit);
+        }
     }
 }
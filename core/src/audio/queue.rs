@@ -1,15 +1,23 @@
+use std::collections::VecDeque;
+
 use rand::{prelude::SliceRandom, thread_rng};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use crate::state::RepeatMode;
-use mecomp_storage::db::schemas::song::Song;
+use mecomp_storage::db::schemas::song::{Song, SongBrief, SongId};
+
+/// The default number of recently played songs the queue remembers.
+pub const DEFAULT_HISTORY_DEPTH: usize = 50;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Queue {
     songs: Vec<Song>,
     current_index: Option<usize>,
     repeat_mode: RepeatMode,
+    /// songs that were played this session, most recent first
+    history: VecDeque<SongBrief>,
+    history_depth: usize,
 }
 
 impl Default for Queue {
@@ -25,6 +33,8 @@ impl Queue {
             songs: Vec::new(),
             current_index: None,
             repeat_mode: RepeatMode::None,
+            history: VecDeque::new(),
+            history_depth: DEFAULT_HISTORY_DEPTH,
         }
     }
 
@@ -38,6 +48,47 @@ impl Queue {
         self.songs.extend(songs);
     }
 
+    /// Insert a song at `index`, or at the end of the queue if `index` is past the end.
+    ///
+    /// If the song is inserted at or before the current song, the current index is moved along with it,
+    /// so the current song doesn't change.
+    #[instrument]
+    pub fn insert_song(&mut self, index: usize, song: Song) {
+        let index = index.min(self.len());
+        if let Some(current_index) = self.current_index {
+            if current_index >= index {
+                self.current_index = Some(current_index + 1);
+            }
+        }
+        self.songs.insert(index, song);
+    }
+
+    /// Move the song at `from` to `to` (clamped to the end of the queue), shifting the songs in between.
+    ///
+    /// The current index follows the current song, so the current song doesn't change.
+    #[instrument]
+    pub fn move_song(&mut self, from: usize, to: usize) {
+        if from >= self.len() {
+            return;
+        }
+        let to = to.min(self.len() - 1);
+
+        let song = self.songs.remove(from);
+        self.songs.insert(to, song);
+
+        self.current_index = self.current_index.map(|current_index| {
+            if current_index == from {
+                to
+            } else if from < current_index && current_index <= to {
+                current_index - 1
+            } else if to <= current_index && current_index < from {
+                current_index + 1
+            } else {
+                current_index
+            }
+        });
+    }
+
     #[instrument]
     pub fn remove_song(&mut self, index: usize) {
         if index >= self.len() {
@@ -79,8 +130,20 @@ impl Queue {
     /// Skip forward n songs in the queue.
     ///
     /// progresses the current index by n, following the repeat mode rules.
+    /// The song that was playing before the skip is added to the history.
     #[instrument]
     pub fn skip_forward(&mut self, n: usize) -> Option<&Song> {
+        if n > 0 {
+            if let Some(song) = self.current_song().map(SongBrief::from) {
+                self.push_history(song);
+            }
+        }
+
+        self.advance(n)
+    }
+
+    /// progresses the current index by n without touching the history.
+    fn advance(&mut self, n: usize) -> Option<&Song> {
         match self.current_index {
             Some(current_index) if current_index + n < self.songs.len() => {
                 self.current_index = Some(current_index + n);
@@ -99,7 +162,7 @@ impl Queue {
                         // so let's emutate looping back to the first song and then skipping n - len songs
                         self.current_index = Some(0);
                         self.repeat_mode = RepeatMode::None;
-                        self.skip_forward((current_index + n) - self.songs.len())
+                        self.advance((current_index + n) - self.songs.len())
                     }
                     RepeatMode::Continuous => {
                         // if we reach this point, then skipping would put us past the end of the queue,
@@ -115,11 +178,32 @@ impl Queue {
                 }
 
                 self.current_index = Some(0);
-                self.skip_forward(n - 1)
+                self.advance(n - 1)
             }
         }
     }
 
+    fn push_history(&mut self, song: SongBrief) {
+        if self.history_depth == 0 {
+            return;
+        }
+        self.history.push_front(song);
+        self.history.truncate(self.history_depth);
+    }
+
+    /// The songs that were played this session, most recent first.
+    #[must_use]
+    pub const fn history(&self) -> &VecDeque<SongBrief> {
+        &self.history
+    }
+
+    /// Sets the maximum number of songs kept in the history, dropping the oldest entries if needed.
+    #[instrument]
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history_depth = depth;
+        self.history.truncate(depth);
+    }
+
     #[instrument]
     pub fn previous_song(&mut self) -> Option<&Song> {
         self.skip_backward(1)
@@ -205,6 +289,59 @@ impl Queue {
         }
     }
 
+    /// Removes songs with duplicate keys from the queue, keeping the first occurrence of each.
+    ///
+    /// If the current song is a duplicate, the current index is moved to the occurrence that was kept.
+    #[instrument(skip(key))]
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&Song) -> K,
+        K: Eq + std::hash::Hash,
+    {
+        // maps each key to the index of its first occurrence in the deduplicated queue
+        let mut seen = std::collections::HashMap::new();
+        let mut songs = Vec::with_capacity(self.songs.len());
+        let mut current_index = None;
+
+        for (index, song) in std::mem::take(&mut self.songs).into_iter().enumerate() {
+            let new_index = *seen.entry(key(&song)).or_insert_with(|| {
+                songs.push(song);
+                songs.len() - 1
+            });
+            if self.current_index == Some(index) {
+                current_index = Some(new_index);
+            }
+        }
+
+        self.songs = songs;
+        self.current_index = current_index;
+    }
+
+    /// Sort the queue into the order of `ranking`, a list of song ids ordered using their audio analysis
+    /// (see [`SortStrategy`](crate::state::SortStrategy)).
+    ///
+    /// Songs that aren't in the ranking (e.g. because they haven't been analyzed) are moved to the end of the queue,
+    /// in the order they were in. The current index follows the current song, so the current song doesn't change.
+    #[instrument]
+    pub fn sort_by_analysis(&mut self, ranking: &[SongId]) {
+        let mut ranks = std::collections::HashMap::with_capacity(ranking.len());
+        for (rank, id) in ranking.iter().enumerate() {
+            ranks.entry(id).or_insert(rank);
+        }
+
+        let mut songs = std::mem::take(&mut self.songs)
+            .into_iter()
+            .enumerate()
+            .collect::<Vec<_>>();
+        // the sort is stable, so songs with the same rank (or no rank) stay in the order they were in
+        songs.sort_by_key(|(_, song)| ranks.get(&song.id).copied().unwrap_or(usize::MAX));
+
+        self.current_index = self
+            .current_index
+            .and_then(|current_index| songs.iter().position(|(index, _)| *index == current_index));
+        self.songs = songs.into_iter().map(|(_, song)| song).collect();
+    }
+
     /// Removes a range of songs from the queue.
     /// If the current index is within the range, it will be set to the next valid index (or the
     /// previous valid index if the range included the end of the queue).
@@ -309,6 +446,144 @@ mod tests {
         assert_eq!(queue.current_song(), None);
     }
 
+    #[rstest]
+    #[case::before_current(Some(1), 0, Some(2))]
+    #[case::at_current(Some(1), 1, Some(2))]
+    #[case::after_current(Some(1), 2, Some(1))]
+    #[case::past_the_end(Some(1), 10, Some(1))]
+    #[case::nothing_playing(None, 0, None)]
+    #[tokio::test]
+    async fn test_insert_song(
+        #[case] current_index_before: Option<usize>,
+        #[case] index_to_insert: usize,
+        #[case] expected_current_index_after: Option<usize>,
+    ) {
+        init();
+        let db = init_test_database().await.unwrap();
+        let mut queue = Queue::new();
+
+        let mut songs = Vec::new();
+        for _ in 0..3 {
+            songs.push(
+                create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                    .await
+                    .unwrap(),
+            );
+        }
+        let song = songs.pop().unwrap();
+        queue.add_songs(songs);
+        if let Some(index) = current_index_before {
+            queue.set_current_index(index);
+        }
+        let current_song = queue.current_song().cloned();
+
+        queue.insert_song(index_to_insert, song.clone());
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.get(index_to_insert.min(2)), Some(&song));
+        assert_eq!(queue.current_index(), expected_current_index_after);
+        // the current song doesn't change
+        assert_eq!(queue.current_song().cloned(), current_song);
+    }
+
+    #[rstest]
+    #[case::current_forwards(Some(1), 1, 3, [0, 2, 3, 1], Some(3))]
+    #[case::current_backwards(Some(2), 2, 0, [2, 0, 1, 3], Some(0))]
+    #[case::over_current_forwards(Some(1), 0, 2, [1, 2, 0, 3], Some(0))]
+    #[case::over_current_backwards(Some(1), 3, 0, [3, 0, 1, 2], Some(2))]
+    #[case::onto_current(Some(2), 0, 2, [1, 2, 0, 3], Some(1))]
+    #[case::away_from_current(Some(0), 2, 3, [0, 1, 3, 2], Some(0))]
+    #[case::to_past_the_end(Some(3), 0, 10, [1, 2, 3, 0], Some(2))]
+    #[case::from_past_the_end(Some(1), 10, 0, [0, 1, 2, 3], Some(1))]
+    #[case::nothing_playing(None, 0, 1, [1, 0, 2, 3], None)]
+    #[tokio::test]
+    async fn test_move_song(
+        #[case] current_index_before: Option<usize>,
+        #[case] from: usize,
+        #[case] to: usize,
+        #[case] expected_order: [usize; 4],
+        #[case] expected_current_index_after: Option<usize>,
+    ) {
+        init();
+        let db = init_test_database().await.unwrap();
+        let mut queue = Queue::new();
+
+        let mut songs = Vec::new();
+        for _ in 0..4 {
+            songs.push(
+                create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                    .await
+                    .unwrap(),
+            );
+        }
+        queue.add_songs(songs.clone());
+        if let Some(index) = current_index_before {
+            queue.set_current_index(index);
+        }
+        let current_song = queue.current_song().cloned();
+
+        queue.move_song(from, to);
+
+        assert_eq!(
+            queue.queued_songs(),
+            expected_order
+                .map(|i| songs[i].clone())
+                .to_vec()
+                .into_boxed_slice()
+        );
+        assert_eq!(queue.current_index(), expected_current_index_after);
+        // the current song doesn't change
+        assert_eq!(queue.current_song().cloned(), current_song);
+    }
+
+    #[rstest]
+    #[case::full_ranking(Some(0), vec![2, 0, 3, 1], [2, 0, 3, 1], Some(1))]
+    #[case::partial_ranking(Some(3), vec![3, 1], [3, 1, 0, 2], Some(0))]
+    #[case::empty_ranking(Some(2), vec![], [0, 1, 2, 3], Some(2))]
+    #[case::nothing_playing(None, vec![1, 0], [1, 0, 2, 3], None)]
+    #[tokio::test]
+    async fn test_sort_by_analysis(
+        #[case] current_index_before: Option<usize>,
+        #[case] ranking: Vec<usize>,
+        #[case] expected_order: [usize; 4],
+        #[case] expected_current_index_after: Option<usize>,
+    ) {
+        init();
+        let db = init_test_database().await.unwrap();
+        let mut queue = Queue::new();
+
+        let mut songs = Vec::new();
+        for _ in 0..4 {
+            songs.push(
+                create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                    .await
+                    .unwrap(),
+            );
+        }
+        queue.add_songs(songs.clone());
+        if let Some(index) = current_index_before {
+            queue.set_current_index(index);
+        }
+        let current_song = queue.current_song().cloned();
+
+        let ranking = ranking
+            .into_iter()
+            .map(|i| songs[i].id.clone())
+            .collect::<Vec<_>>();
+        queue.sort_by_analysis(&ranking);
+
+        assert_eq!(
+            queue.queued_songs(),
+            expected_order
+                .map(|i| songs[i].clone())
+                .to_vec()
+                .into_boxed_slice()
+        );
+        assert_eq!(queue.current_index(), expected_current_index_after);
+        // the current song doesn't change
+        assert_eq!(queue.current_song().cloned(), current_song);
+    }
+
     #[rstest]
     #[case::index_oob(vec![arb_song_case()(), arb_song_case()(), arb_song_case()()], 1, 4, Some(1))]
     #[case::index_before_current(vec![arb_song_case()(), arb_song_case()(), arb_song_case()()], 2, 1, Some(1))]
@@ -399,6 +674,50 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_history() -> anyhow::Result<()> {
+        init();
+        let db = init_test_database().await.unwrap();
+
+        let mut queue = Queue::new();
+        queue.set_history_depth(2);
+        let song1 =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song2 =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song3 =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        queue.add_songs(vec![song1.clone(), song2.clone(), song3.clone()]);
+
+        // nothing was playing before the first song, so nothing is recorded
+        assert_eq!(queue.next_song(), Some(&song1));
+        assert!(queue.history().is_empty());
+
+        assert_eq!(queue.next_song(), Some(&song2));
+        assert_eq!(queue.next_song(), Some(&song3));
+        assert_eq!(
+            queue.history(),
+            &VecDeque::from([SongBrief::from(&song2), SongBrief::from(&song1)])
+        );
+
+        // skipping past the end of the queue records the last song, and the oldest entry is dropped
+        assert_eq!(queue.next_song(), None);
+        assert_eq!(
+            queue.history(),
+            &VecDeque::from([SongBrief::from(&song3), SongBrief::from(&song2)])
+        );
+
+        // going backwards doesn't touch the history
+        queue.set_current_index(2);
+        queue.previous_song();
+        assert_eq!(queue.history().len(), 2);
+
+        queue.set_history_depth(1);
+        assert_eq!(queue.history(), &VecDeque::from([SongBrief::from(&song3)]));
+
+        Ok(())
+    }
+
     #[template]
     #[rstest]
     #[case::more_than_len( arb_vec(&arb_song_case(), 4..=5 )(), 7 )]
@@ -557,6 +876,41 @@ mod tests {
         Ok(())
     }
 
+    #[rstest]
+    #[case::no_current_song(None, None)]
+    #[case::first_occurrence(Some(1), Some(1))]
+    #[case::duplicate_of_first(Some(2), Some(0))]
+    #[case::shifted_by_removal(Some(3), Some(2))]
+    #[case::duplicate_of_second(Some(4), Some(1))]
+    #[tokio::test]
+    async fn test_dedup_by_key(
+        #[case] current_index_before: Option<usize>,
+        #[case] expected_current_index_after: Option<usize>,
+    ) {
+        init();
+        let db = init_test_database().await.unwrap();
+        let mut songs = Vec::new();
+        for _ in 0..3 {
+            songs.push(
+                create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                    .await
+                    .unwrap(),
+            );
+        }
+        let [a, b, c] = [songs[0].clone(), songs[1].clone(), songs[2].clone()];
+
+        let mut queue = Queue::new();
+        queue.add_songs(vec![a.clone(), b.clone(), a.clone(), c.clone(), b.clone()]);
+        if let Some(index) = current_index_before {
+            queue.set_current_index(index);
+        }
+
+        queue.dedup_by_key(|song| song.id.clone());
+
+        assert_eq!(queue.queued_songs(), vec![a, b, c].into_boxed_slice());
+        assert_eq!(queue.current_index(), expected_current_index_after);
+    }
+
     #[rstest]
     #[case( arb_vec_and_range_and_index(&arb_song_case(), 5..=10,RangeStartMode::Standard,RangeEndMode::Standard, RangeIndexMode::InRange )() )]
     #[case( arb_vec_and_range_and_index(&arb_song_case(), 5..=10,RangeStartMode::Standard,RangeEndMode::Standard, RangeIndexMode::BeforeRange )() )]
@@ -11,6 +11,19 @@ pub enum DirectoryError {
     Data,
 }
 
+/// Errors that can occur when parsing a duration with [`crate::parse_duration`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DurationParseError {
+    #[error("No duration given.")]
+    Empty,
+    #[error(
+        "Invalid duration \"{0}\", expected HH:MM:SS.ff, MM:SS, SS, or a number of seconds, minutes, or hours (e.g. 10s, 5m, 1h)."
+    )]
+    Invalid(String),
+    #[error("Duration \"{0}\" is too large.")]
+    Overflow(String),
+}
+
 /// Errors that can occur with the library.
 #[derive(Error, Debug)]
 pub enum LibraryError {
@@ -37,6 +50,12 @@ pub enum SerializableLibraryError {
     AnalysisInProgress,
     #[error("Collection Reclustering already in progress.")]
     ReclusterInProgress,
+    #[error("Can't recluster the library into 0 collections.")]
+    InvalidClusterCount,
+    #[error("Import error: {0}")]
+    Import(String),
+    #[error("Unsupported playlist format: {0}, expected an .m3u, .m3u8, or .xspf file")]
+    UnsupportedPlaylistFormat(String),
 }
 
 impl From<Error> for SerializableLibraryError {
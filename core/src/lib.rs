@@ -1,4 +1,6 @@
-use errors::DirectoryError;
+use std::time::Duration;
+
+use errors::{DirectoryError, DurationParseError};
 
 #[cfg(feature = "audio")]
 pub mod audio;
@@ -49,14 +51,113 @@ macro_rules! function_name {
     }};
 }
 
+/// Format a duration as `HH:MM:SS.ff`.
+///
+/// The fractional seconds have at least two digits, and as many more (up to nine) as are needed to
+/// represent the duration exactly, down to the nanosecond.
+///
+/// [`parse_duration`] is the inverse of this function.
 #[must_use]
-pub fn format_duration(duration: &std::time::Duration) -> String {
+pub fn format_duration(duration: &Duration) -> String {
     let total_seconds = duration.as_secs();
     let hours = total_seconds / 3600;
     let minutes = (total_seconds % 3600) / 60;
-    let seconds = duration.as_secs_f32() % 60.;
+    let seconds = total_seconds % 60;
+    let mut fraction = format!("{:09}", duration.subsec_nanos());
+    fraction.truncate(fraction.trim_end_matches('0').len().max(2));
+
+    format!("{hours:02}:{minutes:02}:{seconds:02}.{fraction}")
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Parse a duration.
+///
+/// Accepted formats are:
+/// - `HH:MM:SS.ff` or `MM:SS.ff` (the fractional seconds are optional, and may have up to 9 digits)
+/// - `SS` (a number of seconds, optionally fractional)
+/// - `Xs`, `Xm`, or `Xh` (a number of seconds, minutes, or hours, optionally fractional)
+///
+/// Minutes may be 60 or more only in the `MM:SS` format, and seconds must always be less than 60 when
+/// given after a colon.
+///
+/// This is the inverse of [`format_duration`].
+///
+/// # Errors
+///
+/// Returns an error if the string is empty, isn't in one of the above formats,
+/// or describes a duration too large to be represented.
+pub fn parse_duration(s: &str) -> Result<Duration, DurationParseError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(DurationParseError::Empty);
+    }
+    let invalid = || DurationParseError::Invalid(s.to_string());
+
+    let nanos = if s.contains(':') {
+        let parts = s.split(':').collect::<Vec<_>>();
+        let (hours, minutes, seconds) = match parts.as_slice() {
+            [minutes, seconds] => (None, *minutes, *seconds),
+            [hours, minutes, seconds] => (Some(*hours), *minutes, *seconds),
+            _ => return Err(invalid()),
+        };
+        let hours = hours.map_or(Some(0), parse_integer).ok_or_else(invalid)?;
+        let minutes = parse_integer(minutes).ok_or_else(invalid)?;
+        let seconds = parse_decimal(seconds).ok_or_else(invalid)?;
+        // minutes can only overflow into hours if hours weren't given
+        if seconds >= 60 * NANOS_PER_SEC || (parts.len() == 3 && minutes >= 60) {
+            return Err(invalid());
+        }
+        (hours * 3600 + minutes * 60) * NANOS_PER_SEC + seconds
+    } else {
+        let (number, unit) = match s.char_indices().last() {
+            Some((i, 's')) => (&s[..i], 1),
+            Some((i, 'm')) => (&s[..i], 60),
+            Some((i, 'h')) => (&s[..i], 3600),
+            _ => (s, 1),
+        };
+        parse_decimal(number.trim()).ok_or_else(invalid)? * unit
+    };
+
+    let secs = u64::try_from(nanos / NANOS_PER_SEC)
+        .map_err(|_| DurationParseError::Overflow(s.to_string()))?;
+    #[allow(clippy::cast_possible_truncation)]
+    let subsec_nanos = (nanos % NANOS_PER_SEC) as u32;
+    Ok(Duration::new(secs, subsec_nanos))
+}
 
-    format!("{hours:02}:{minutes:02}:{seconds:05.2}")
+/// Parse a non-negative integer, returning `None` if it's not one (or is absurdly large).
+fn parse_integer(s: &str) -> Option<u128> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse::<u64>().ok().map(u128::from)
+}
+
+/// Parse a non-negative decimal number (e.g. `12`, `1.5`, `.25`) into a number of nanoseconds,
+/// without going through floating point.
+///
+/// Returns `None` if it's not a decimal number or has more than 9 fractional digits.
+fn parse_decimal(s: &str) -> Option<u128> {
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 9
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        parse_integer(whole)?
+    };
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        // pad the fraction out to nanoseconds, e.g. ".25" is 250_000_000 nanoseconds
+        parse_integer(fraction)? * 10u128.pow(9 - u32::try_from(fraction.len()).ok()?)
+    };
+    Some(whole * NANOS_PER_SEC + fraction)
 }
 
 /// Get the data directory for the application.
@@ -106,15 +207,18 @@ pub fn is_server_running(port: u16) -> bool {
 
 #[cfg(test)]
 mod test {
-    use super::format_duration;
+    use super::{format_duration, parse_duration, DurationParseError};
     use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
     use rstest::rstest;
     use std::time::Duration;
 
     #[rstest]
     #[case::zero(Duration::from_secs(0), "00:00:00.00")]
     #[case::sub_second(Duration::from_millis(100), "00:00:00.10")]
-    #[case::sub_second(Duration::from_millis(101), "00:00:00.10")]
+    #[case::sub_second(Duration::from_millis(101), "00:00:00.101")]
+    #[case::nanoseconds(Duration::new(1, 123_456_789), "00:00:01.123456789")]
+    #[case::trailing_zeros(Duration::new(1, 500_000), "00:00:01.0005")]
     #[case::one_second(Duration::from_secs(1), "00:00:01.00")]
     #[case::one_minute(Duration::from_secs(60), "00:01:00.00")]
     #[case::one_hour(Duration::from_secs(3600), "01:00:00.00")]
@@ -125,6 +229,53 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[rstest]
+    #[case::full("01:02:03.45", Duration::from_millis(3_723_450))]
+    #[case::full_no_fraction("01:02:03", Duration::from_secs(3723))]
+    #[case::minutes_seconds("1:30", Duration::from_secs(90))]
+    #[case::long_minutes("90:00", Duration::from_secs(5400))]
+    #[case::nanoseconds("00:00:01.123456789", Duration::new(1, 123_456_789))]
+    #[case::bare_seconds("42", Duration::from_secs(42))]
+    #[case::bare_fractional_seconds("0.5", Duration::from_millis(500))]
+    #[case::leading_dot(".25", Duration::from_millis(250))]
+    #[case::seconds("10s", Duration::from_secs(10))]
+    #[case::minutes("5m", Duration::from_secs(300))]
+    #[case::fractional_minutes("1.5m", Duration::from_secs(90))]
+    #[case::hours("2h", Duration::from_secs(7200))]
+    #[case::whitespace(" 10 s ", Duration::from_secs(10))]
+    fn test_parse_duration(#[case] input: &str, #[case] expected: Duration) {
+        assert_eq!(parse_duration(input), Ok(expected));
+    }
+
+    #[rstest]
+    #[case::empty("  ", DurationParseError::Empty)]
+    #[case::garbage("abc", DurationParseError::Invalid("abc".into()))]
+    #[case::negative("-5s", DurationParseError::Invalid("-5s".into()))]
+    #[case::exponent("1e3", DurationParseError::Invalid("1e3".into()))]
+    #[case::too_many_parts("1:2:3:4", DurationParseError::Invalid("1:2:3:4".into()))]
+    #[case::seconds_overflow("1:60", DurationParseError::Invalid("1:60".into()))]
+    #[case::minutes_overflow("1:60:00", DurationParseError::Invalid("1:60:00".into()))]
+    #[case::fractional_minutes_in_time("1.5:00", DurationParseError::Invalid("1.5:00".into()))]
+    #[case::too_precise("0.0000000001", DurationParseError::Invalid("0.0000000001".into()))]
+    #[case::just_a_dot(".", DurationParseError::Invalid(".".into()))]
+    #[case::just_a_unit("s", DurationParseError::Invalid("s".into()))]
+    #[case::overflow(
+        "99999999999999999999h",
+        DurationParseError::Invalid("99999999999999999999h".into())
+    )]
+    #[case::overflow("18446744073709551615h", DurationParseError::Overflow("18446744073709551615h".into()))]
+    fn test_parse_duration_invalid(#[case] input: &str, #[case] expected: DurationParseError) {
+        assert_eq!(parse_duration(input), Err(expected));
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_format_duration_roundtrip(secs in 0..24 * 60 * 60u64, nanos in 0..1_000_000_000u32) {
+            let duration = Duration::new(secs, nanos);
+            prop_assert_eq!(parse_duration(&format_duration(&duration)), Ok(duration));
+        }
+    }
+
     #[test]
     fn test_function_name() {
         fn test_function() {
@@ -5,6 +5,7 @@
 use std::{
     net::{IpAddr, Ipv4Addr},
     ops::Range,
+    path::PathBuf,
     time::Duration,
 };
 
@@ -12,8 +13,10 @@ use mecomp_storage::db::schemas::{
     album::{Album, AlbumBrief},
     artist::{Artist, ArtistBrief},
     collection::{Collection, CollectionBrief},
+    failed_analysis::FailedAnalysis,
     playlist::{Playlist, PlaylistBrief},
-    song::{Song, SongBrief},
+    scan_history::ScanMetrics,
+    song::{PlayStats, Song, SongBrief, SongChangeSet},
     Thing,
 };
 use one_or_many::OneOrMany;
@@ -23,8 +26,12 @@ use tarpc::{client, tokio_serde::formats::Json};
 use crate::{
     errors::SerializableLibraryError,
     state::{
-        library::{LibraryBrief, LibraryFull, LibraryHealth},
-        RepeatMode, SeekType, StateAudio,
+        equalizer::EqBand,
+        library::{
+            CollectionAnalysisStats, DuplicateGroup, LibraryBrief, LibraryFull, LibraryHealth,
+            LibraryStatistics, ScanProgress, SongVerificationResult,
+        },
+        AudioLevels, RepeatMode, ReplayGainMode, SeekType, SortStrategy, StateAudio,
     },
 };
 
@@ -33,6 +40,7 @@ pub type ArtistId = Thing;
 pub type AlbumId = Thing;
 pub type CollectionId = Thing;
 pub type PlaylistId = Thing;
+pub type FailedAnalysisId = Thing;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct SearchResult {
@@ -53,6 +61,83 @@ impl SearchResult {
     }
 }
 
+/// The result of importing a playlist from an external export (e.g. a Spotify or Apple Music CSV).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlaylistImportReport {
+    /// The id of the playlist that was created.
+    pub playlist_id: PlaylistId,
+    /// The number of tracks that were matched to songs in the library.
+    pub matched: u32,
+    /// The tracks that couldn't be matched to any song in the library
+    /// (as "Artist - Title" for CSV exports, or the location of the track for playlist files).
+    pub unmatched: Vec<String>,
+}
+
+/// The result of importing a backup of the library made with `library_export_json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImportStats {
+    /// The number of songs that were added to the library.
+    pub songs_added: u32,
+    /// The number of songs that were skipped because they were already in the library.
+    pub songs_skipped: u32,
+    /// The number of playlists that were created or merged into.
+    pub playlists: u32,
+    /// The number of collections that were created or merged into.
+    pub collections: u32,
+    /// The songs that couldn't be imported (e.g. because their file no longer exists), and why.
+    pub errors: Vec<String>,
+}
+
+/// The result of exporting the playlists in the library (e.g. to M3U files).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlaylistExportReport {
+    /// The number of playlists that were exported.
+    pub exported: u32,
+    /// The number of playlists that were skipped because their file already existed.
+    pub skipped: u32,
+    /// The playlists that couldn't be exported, and why (as "Name: error").
+    pub errors: Vec<String>,
+}
+
+/// A request to apply the same changes to many songs at once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BatchSongUpdateRequest {
+    /// The songs to update, songs that don't exist are skipped.
+    pub ids: Vec<SongId>,
+    /// The changes to apply to every song, fields that are `None` are left unchanged.
+    pub changeset: SongChangeSet,
+}
+
+/// A request to merge several playlists into a new one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlaylistMergeRequest {
+    /// The playlists to merge, in the order their songs should appear in the new playlist.
+    pub source_ids: Vec<PlaylistId>,
+    /// The name of the new playlist.
+    pub name: String,
+}
+
+/// A request to recluster the library into collections.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReclusterRequest {
+    /// The number of collections to create,
+    /// if `None` the number of collections is chosen automatically (up to the configured maximum).
+    pub n_clusters: Option<u32>,
+}
+
+/// A request for a full-text search of the library.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchRequest {
+    /// What to search for, matched case-insensitively against the title, artist, album, and genre of songs,
+    /// the title and artist of albums, and the name of artists.
+    pub query: String,
+    /// The maximum number of songs, albums, and artists (each) to return.
+    pub limit: u32,
+}
+
+/// The response to a [`SearchRequest`], the songs, albums, and artists that match, each ordered by relevance (BM25 score).
+pub type SearchResponse = SearchResult;
+
 // TODO: commands for reading songs by paths, artists by name, etc.
 
 #[tarpc::service]
@@ -65,12 +150,33 @@ pub trait MusicPlayer {
     async fn library_rescan() -> Result<(), SerializableLibraryError>;
     /// Check if a rescan is in progress.
     async fn library_rescan_in_progress() -> bool;
+    /// Returns how far along the rescan in progress is, `None` if there is no rescan in progress.
+    /// (tarpc can't stream responses, so clients poll this while a rescan is in progress)
+    async fn library_rescan_progress() -> Option<ScanProgress>;
+    /// Returns how the scans of each library path went in the recent rescans, most recent first.
+    async fn library_scan_history() -> Result<Box<[ScanMetrics]>, SerializableLibraryError>;
     /// Analyze the music library, only error is if an analysis is already in progress.
     async fn library_analyze() -> Result<(), SerializableLibraryError>;
     /// Check if an analysis is in progress.
     async fn library_analyze_in_progress() -> bool;
-    /// Recluster the music library, only error is if a recluster is already in progress.
-    async fn library_recluster() -> Result<(), SerializableLibraryError>;
+    /// List the songs that failed to be analyzed (and how many times they've failed).
+    async fn library_list_failed_analysis(
+    ) -> Result<Box<[FailedAnalysis]>, SerializableLibraryError>;
+    /// Retry the analysis of the songs with the given failed analysis records, even if they've failed too many times to be retried automatically.
+    /// Only error is if an analysis is already in progress.
+    async fn library_retry_failed_analysis(
+        ids: Vec<FailedAnalysisId>,
+    ) -> Result<(), SerializableLibraryError>;
+    /// Compute the `ReplayGain` of the given songs (in parallel), only error is if an analysis is already in progress.
+    async fn library_compute_replaygain(
+        song_ids: Vec<SongId>,
+    ) -> Result<(), SerializableLibraryError>;
+    /// Returns how far along the `ReplayGain` computation in progress is, `None` if there is no computation in progress.
+    /// (clients poll this while a computation is in progress, like `library_rescan_progress`)
+    async fn library_compute_replaygain_progress() -> Option<ScanProgress>;
+    /// Recluster the music library, replacing the existing collections.
+    /// Errors if a recluster is already in progress, or if `n_clusters` is 0.
+    async fn library_recluster(request: ReclusterRequest) -> Result<(), SerializableLibraryError>;
     /// Check if a recluster is in progress.
     async fn library_recluster_in_progress() -> bool;
     /// Returns brief information about the music library.
@@ -91,6 +197,64 @@ pub trait MusicPlayer {
     async fn library_songs_full() -> Result<Box<[Song]>, SerializableLibraryError>;
     /// Returns information about the health of the music library (are there any missing files, etc.)
     async fn library_health() -> Result<LibraryHealth, SerializableLibraryError>;
+    /// Returns statistics about the contents of the music library (total runtime, most common genre, etc.)
+    async fn library_statistics() -> Result<LibraryStatistics, SerializableLibraryError>;
+    /// Checks that the files of the given songs (or all songs, if `None`) still exist and can be decoded.
+    async fn library_verify(
+        song_ids: Option<Vec<SongId>>,
+    ) -> Result<Box<[SongVerificationResult]>, SerializableLibraryError>;
+    /// Returns the songs whose files don't exist anymore (e.g. because they were moved or deleted externally).
+    async fn library_scan_dead_links() -> Result<Vec<SongBrief>, SerializableLibraryError>;
+    /// Deletes the songs whose files don't exist anymore, removing them from their albums, artists, playlists, and collections.
+    /// Returns the number of songs that were deleted, errors if a rescan is in progress.
+    async fn library_remove_dead_links() -> Result<u64, SerializableLibraryError>;
+    /// Returns the groups of songs that are likely duplicates of each other
+    /// (same title and artists, and runtimes within 2 seconds of each other), ordered by title.
+    async fn library_find_duplicates() -> Result<Vec<DuplicateGroup>, SerializableLibraryError>;
+    /// Returns the groups of songs that have matching acoustic fingerprints, ordered by title.
+    /// Songs that haven't been fingerprinted yet are fingerprinted first, which can take a while.
+    async fn library_find_acoustid_duplicates(
+    ) -> Result<Vec<DuplicateGroup>, SerializableLibraryError>;
+    /// Merge the `remove_ids` songs into the `keep_id` song (adding up their play counts, etc.), then delete them.
+    /// Returns the merged song.
+    async fn library_remove_duplicate(
+        keep_id: SongId,
+        remove_ids: Vec<SongId>,
+    ) -> Result<Song, SerializableLibraryError>;
+    /// Apply the same changes to every song in the request (e.g. to set the genre of an entire album at once).
+    /// Returns the number of songs that were updated, errors if the changes set an invalid rating.
+    async fn library_batch_update_songs(
+        request: BatchSongUpdateRequest,
+    ) -> Result<u64, SerializableLibraryError>;
+    /// Returns the distinct genres of the songs in the music library, sorted alphabetically.
+    async fn library_list_genres() -> Result<Vec<String>, SerializableLibraryError>;
+    /// Returns (up to `limit`) songs with the given genre, ordered by title.
+    async fn library_songs_by_genre(
+        genre: String,
+        limit: usize,
+    ) -> Result<Box<[Song]>, SerializableLibraryError>;
+    /// Returns (up to `limit`) songs released between `start_year` and `end_year` (inclusive),
+    /// ordered by release year then title. Songs without a release year are excluded.
+    async fn library_year_range(
+        start_year: i32,
+        end_year: i32,
+        limit: usize,
+    ) -> Result<Box<[Song]>, SerializableLibraryError>;
+    /// Returns (up to `limit`) songs with a tempo between `min_bpm` and `max_bpm` (inclusive),
+    /// ordered by tempo. Songs that haven't been analyzed are excluded.
+    async fn library_bpm_range(
+        min_bpm: f64,
+        max_bpm: f64,
+        limit: usize,
+    ) -> Result<Box<[Song]>, SerializableLibraryError>;
+    /// Returns a random sample of (up to `limit`) songs from the library.
+    /// If a `seed` is given, the same seed gives the same sample (as long as the library doesn't change).
+    async fn library_random_songs(
+        limit: usize,
+        seed: Option<u64>,
+    ) -> Result<Box<[Song]>, SerializableLibraryError>;
+    /// Returns the songs played this session, most recent first.
+    async fn library_playback_history() -> Box<[SongBrief]>;
 
     // music library CRUD operations
     /// Get a song by its ID.
@@ -103,11 +267,38 @@ pub trait MusicPlayer {
     async fn library_song_get_playlists(id: SongId) -> Box<[Playlist]>;
     /// Get the Collections a song is in.
     async fn library_song_get_collections(id: SongId) -> Box<[Collection]>;
+    /// Get how often, and how recently, a song has been played to completion.
+    async fn library_song_get_play_stats(id: SongId) -> Option<PlayStats>;
+    /// Get the lyrics of a song, if it has any.
+    async fn library_song_get_lyrics(id: SongId) -> Option<String>;
+    /// Rate a song from 1 to 5 stars, or clear its rating if `rating` is `None`.
+    async fn library_song_set_rating(
+        id: SongId,
+        rating: Option<u8>,
+    ) -> Result<(), SerializableLibraryError>;
+    /// Add a song to the favorites, or remove it if it's already a favorite.
+    /// Returns whether the song is now a favorite.
+    async fn library_song_toggle_favorite(id: SongId) -> Result<bool, SerializableLibraryError>;
+    /// Get the bookmarked positions in a song, in order.
+    async fn library_song_get_bookmarks(id: SongId) -> Option<Box<[Duration]>>;
+    /// Bookmark a position in a song (to the nearest millisecond).
+    /// Returns the song's bookmarks, in order.
+    async fn library_song_add_bookmark(
+        id: SongId,
+        position: Duration,
+    ) -> Result<Box<[Duration]>, SerializableLibraryError>;
+    /// Remove the bookmark at a position in a song, if there is one.
+    /// Returns the song's remaining bookmarks, in order.
+    async fn library_song_remove_bookmark(
+        id: SongId,
+        position: Duration,
+    ) -> Result<Box<[Duration]>, SerializableLibraryError>;
     /// Get an album by its ID.
     async fn library_album_get(id: AlbumId) -> Option<Album>;
     /// Get the artists of an album
     async fn library_album_get_artist(id: AlbumId) -> OneOrMany<Artist>;
-    /// Get the songs of an album
+    /// Get the songs of an album, ordered by disc number, then track number, then title.
+    /// Songs without a disc or track number come last.
     async fn library_album_get_songs(id: AlbumId) -> Option<Box<[Song]>>;
     /// Get an artist by its ID.
     async fn library_artist_get(id: ArtistId) -> Option<Artist>;
@@ -115,6 +306,12 @@ pub trait MusicPlayer {
     async fn library_artist_get_songs(id: ArtistId) -> Option<Box<[Song]>>;
     /// Get the albums of an artist
     async fn library_artist_get_albums(id: ArtistId) -> Option<Box<[Album]>>;
+    /// Merge the `source` artist into the `target` artist (e.g. to fix an artist that was split by inconsistent tagging),
+    /// the songs and albums of the source artist are given to the target artist and the source artist is deleted.
+    async fn library_artist_merge(
+        source: ArtistId,
+        target: ArtistId,
+    ) -> Result<Artist, SerializableLibraryError>;
 
     // Daemon control.
     /// tells the daemon to shutdown.
@@ -149,6 +346,8 @@ pub trait MusicPlayer {
     async fn search_album(query: String, limit: u32) -> Box<[Album]>;
     /// returns a list of songs matching the given search query.
     async fn search_song(query: String, limit: u32) -> Box<[Song]>;
+    /// performs a full-text search of the library, returning the songs, albums, and artists that match.
+    async fn library_search(request: SearchRequest) -> SearchResponse;
 
     // Playback control.
     /// toggles playback (play/pause).
@@ -169,6 +368,9 @@ pub trait MusicPlayer {
     async fn playback_clear() -> ();
     /// seek forwards, backwards, or to an absolute second in the current song.
     async fn playback_seek(seek: SeekType, duration: Duration) -> ();
+    /// seek to a percentage (0.0-100.0, clamped) of the way through the current song.
+    /// Does nothing if there is no current song.
+    async fn playback_seek_percent(percent: f32) -> ();
     /// set the repeat mode.
     async fn playback_repeat(mode: RepeatMode) -> ();
     /// Shuffle the current queue, then start playing from the 1st Song in the queue.
@@ -186,6 +388,38 @@ pub trait MusicPlayer {
     async fn playback_mute() -> ();
     /// unmute the volume.
     async fn playback_unmute() -> ();
+    /// set the pre-amplifier gain (in decibels, clamped to -24.0..=12.0).
+    /// The gain is independent of the volume, so muting doesn't reset it.
+    async fn playback_gain(gain_db: f32) -> ();
+    /// gradually change the volume to `target` over `duration` (e.g. to fade out before pausing).
+    /// Starting another fade, or explicitly changing the volume, cancels the fade in progress.
+    async fn playback_fade_to_volume(target: f32, duration: Duration) -> ();
+    /// cancel the volume fade in progress (if any), leaving the volume where it is.
+    async fn playback_cancel_fade() -> ();
+    /// set how long the end of a song overlaps with the start of the next one, fading out as it fades in (`Duration::ZERO` disables it).
+    /// The duration is clamped to at most 12 seconds.
+    async fn playback_crossfade(duration: Duration) -> ();
+    /// set how songs are levelled using their `ReplayGain` (normalizing them to -14 LUFS, by at most ±12 dB).
+    /// Songs whose `ReplayGain` hasn't been computed (see `library_compute_replaygain`) are not levelled.
+    async fn playback_replaygain(mode: ReplayGainMode) -> ();
+    /// set the playback speed, `1.0` is normal speed.
+    /// The speed is clamped to between 0.5x and 2x, and carries over to the songs that play next.
+    async fn playback_set_speed(speed: f32) -> ();
+    /// list the names of the audio output devices available to the daemon.
+    async fn playback_list_output_devices() -> Vec<String>;
+    /// switch the audio output to the output device named `name` (see `playback_list_output_devices`).
+    /// If the device can't be found or opened, the current output device is kept.
+    async fn playback_set_output_device(name: String) -> ();
+    /// replace the bands of the equalizer.
+    /// Bands are clamped to ±12 dB between 20 Hz and 20 kHz, and bands that aren't finite are dropped.
+    async fn playback_set_equalizer(bands: Vec<EqBand>) -> ();
+    /// list the names of the equalizer presets, the built-in ones followed by the ones from the settings.
+    async fn playback_equalizer_presets() -> Vec<String>;
+    /// apply the equalizer preset named `name`, returning its bands (or `None` if there is no such preset).
+    async fn playback_equalizer_preset(name: String) -> Option<Vec<EqBand>>;
+    /// get the peak and RMS levels of the audio being played (over about the last 100ms), for level meters.
+    /// Reports silence if nothing is playing.
+    async fn playback_levels() -> AudioLevels;
 
     // Queue control.
     /// add a thing to the queue.
@@ -194,12 +428,28 @@ pub trait MusicPlayer {
     /// add a list of things to the queue.
     /// (if the queue is empty, it will start playing the first thing in the list.)
     async fn queue_add_list(list: Vec<Thing>) -> Result<(), SerializableLibraryError>;
+    /// insert a song into the queue at `index` (or at the end, if `index` is past the end of the queue).
+    /// the current song doesn't change, even if the song is inserted before it.
+    /// (if the queue is empty, it will start playing the song.)
+    async fn queue_insert_at(index: usize, id: SongId) -> Result<(), SerializableLibraryError>;
     /// set the current song to a queue index.
     /// if the index is out of bounds, it will be clamped to the nearest valid index.
     async fn queue_set_index(index: usize) -> ();
     /// remove a range of songs from the queue.
     /// if the range is out of bounds, it will be clamped to the nearest valid range.
     async fn queue_remove_range(range: Range<usize>) -> ();
+    /// move the song at index `from` to index `to` (clamped to the end of the queue), shifting the songs in between.
+    /// the current song doesn't change, even if it is moved or displaced.
+    async fn queue_move(from: usize, to: usize) -> ();
+    /// remove duplicate songs from the queue, keeping the first occurrence of each.
+    async fn queue_dedup() -> ();
+    /// sort the queue using the audio analysis of its songs, without changing the current song.
+    /// songs that haven't been analyzed are moved to the end of the queue.
+    async fn queue_sort_by_analysis(strategy: SortStrategy)
+        -> Result<(), SerializableLibraryError>;
+    /// save the current queue as a new playlist with the given name.
+    /// returns the id of the new playlist.
+    async fn queue_save_as_playlist(name: String) -> Result<PlaylistId, SerializableLibraryError>;
 
     // Playlists.
     /// Returns brief information about the users playlists.
@@ -212,6 +462,13 @@ pub trait MusicPlayer {
     /// (creates a new playlist with the same name (append "copy") and contents as the given playlist.)
     /// returns the id of the new playlist.
     async fn playlist_clone(id: PlaylistId) -> Result<PlaylistId, SerializableLibraryError>;
+    /// merge playlists.
+    /// (creates a new playlist with the given name, containing the songs of every source playlist without duplicates,
+    /// the source playlists are left unchanged.)
+    /// returns the id of the new playlist, fails if any of the source playlists don't exist.
+    async fn playlist_merge(
+        request: PlaylistMergeRequest,
+    ) -> Result<PlaylistId, SerializableLibraryError>;
     /// get the id of a playlist.
     /// returns none if the playlist does not exist.
     async fn playlist_get_id(name: String) -> Option<PlaylistId>;
@@ -237,6 +494,32 @@ pub trait MusicPlayer {
     async fn playlist_get(id: PlaylistId) -> Option<Playlist>;
     /// Get the songs of a playlist
     async fn playlist_get_songs(id: PlaylistId) -> Option<Box<[Song]>>;
+    /// Import a playlist from a Spotify or Apple Music CSV export.
+    /// Tracks are fuzzy matched against the library, those with a similarity below `threshold` (0.0 to 1.0) are reported as unmatched.
+    /// The new playlist is named after the file.
+    async fn playlist_import_spotify_csv(
+        path: PathBuf,
+        threshold: f64,
+    ) -> Result<PlaylistImportReport, SerializableLibraryError>;
+    /// Import a playlist from an M3U or XSPF file, the format is detected from the extension.
+    /// Tracks are matched to songs in the library by their path.
+    async fn playlist_import(
+        path: PathBuf,
+    ) -> Result<PlaylistImportReport, SerializableLibraryError>;
+    /// Export a playlist to an M3U or XSPF file at `path`, the format is detected from the extension.
+    async fn playlist_export(id: PlaylistId, path: PathBuf)
+        -> Result<(), SerializableLibraryError>;
+    /// Export every playlist in the library to `<dir>/<playlist name>.m3u`, creating `dir` if needed.
+    /// If `overwrite` is false, playlists whose file already exists are skipped.
+    async fn library_export_all_playlists(
+        dir: PathBuf,
+        overwrite: bool,
+    ) -> Result<PlaylistExportReport, SerializableLibraryError>;
+    /// Back up the songs, albums, artists, playlists, and collections in the library to a JSON file at `path`.
+    async fn library_export_json(path: PathBuf) -> Result<(), SerializableLibraryError>;
+    /// Merge a backup made with `library_export_json` into the library.
+    /// Songs that are already in the library are skipped, and playlists and collections are matched by name.
+    async fn library_import_json(path: PathBuf) -> Result<ImportStats, SerializableLibraryError>;
 
     // Auto Curration commands.
     // (collections, radios, smart playlists, etc.)
@@ -251,6 +534,24 @@ pub trait MusicPlayer {
     ) -> Result<PlaylistId, SerializableLibraryError>;
     /// Get the songs of a collection
     async fn collection_get_songs(id: CollectionId) -> Option<Box<[Song]>>;
+    /// Collections: get summary statistics (mean tempo, loudness, etc.) of the analyzed songs in a collection.
+    /// returns `None` if none of the songs in the collection have been analyzed.
+    async fn library_collection_get_analysis_stats(
+        id: CollectionId,
+    ) -> Result<Option<CollectionAnalysisStats>, SerializableLibraryError>;
+    /// Collections: rename a collection.
+    /// returns the renamed collection, fails if the collection doesn't exist or another collection already has that name.
+    async fn collection_rename(
+        id: CollectionId,
+        name: String,
+    ) -> Result<Collection, SerializableLibraryError>;
+    /// Collections: merge the `source` collection into the `target` collection.
+    /// (the songs of `source` are added to `target`, skipping duplicates, then `source` is deleted.)
+    /// returns the merged collection.
+    async fn collection_merge(
+        source: CollectionId,
+        target: CollectionId,
+    ) -> Result<Collection, SerializableLibraryError>;
 
     // Radio commands.
     /// Radio: get the `n` most similar songs to the given things.
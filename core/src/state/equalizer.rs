@@ -0,0 +1,130 @@
+//! The bands and presets of the audio equalizer.
+
+use serde::{Deserialize, Serialize};
+
+/// The lowest center frequency a band can have, in Hz
+pub const MIN_FREQUENCY_HZ: f32 = 20.0;
+/// The highest center frequency a band can have, in Hz
+pub const MAX_FREQUENCY_HZ: f32 = 20_000.0;
+/// The largest cut a band can apply, in decibels
+pub const MIN_GAIN_DB: f32 = -12.0;
+/// The largest boost a band can apply, in decibels
+pub const MAX_GAIN_DB: f32 = 12.0;
+/// The narrowest band (highest Q) that can be set
+pub const MAX_Q: f32 = 10.0;
+/// The widest band (lowest Q) that can be set
+pub const MIN_Q: f32 = 0.1;
+
+/// The center frequencies of the bands of the default presets, one octave apart from 32 Hz to 16 kHz
+pub const DEFAULT_FREQUENCIES_HZ: [f32; 10] = [
+    32.0, 64.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+/// The Q of the bands of the default presets, which makes each band about an octave wide
+pub const DEFAULT_Q: f32 = std::f32::consts::SQRT_2;
+
+/// The names of the presets that are always available
+pub const BUILTIN_PRESETS: [&str; 5] = ["flat", "bass_boost", "treble_boost", "classical", "vocal"];
+
+/// A peaking equalizer band, which boosts or cuts the frequencies around `frequency_hz`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct EqBand {
+    /// The center frequency of the band, in Hz
+    pub frequency_hz: f32,
+    /// How much the center frequency is boosted (or cut, if negative), in decibels
+    pub gain_db: f32,
+    /// How narrow the band is, higher values affect fewer of the surrounding frequencies
+    pub q: f32,
+}
+
+impl EqBand {
+    #[must_use]
+    pub const fn new(frequency_hz: f32, gain_db: f32, q: f32) -> Self {
+        Self {
+            frequency_hz,
+            gain_db,
+            q,
+        }
+    }
+
+    /// Clamp the band to the allowed frequencies, gains, and Qs.
+    ///
+    /// Returns `None` if any of the values aren't finite.
+    #[must_use]
+    pub fn clamped(self) -> Option<Self> {
+        if !(self.frequency_hz.is_finite() && self.gain_db.is_finite() && self.q.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            frequency_hz: self.frequency_hz.clamp(MIN_FREQUENCY_HZ, MAX_FREQUENCY_HZ),
+            gain_db: self.gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB),
+            q: self.q.clamp(MIN_Q, MAX_Q),
+        })
+    }
+
+    /// Whether the band leaves the audio unchanged.
+    #[must_use]
+    pub fn is_flat(&self) -> bool {
+        self.gain_db.abs() < f32::EPSILON
+    }
+}
+
+/// The bands of the default presets, with the given gains.
+fn default_bands(gains_db: [f32; 10]) -> Vec<EqBand> {
+    DEFAULT_FREQUENCIES_HZ
+        .into_iter()
+        .zip(gains_db)
+        .map(|(frequency_hz, gain_db)| EqBand::new(frequency_hz, gain_db, DEFAULT_Q))
+        .collect()
+}
+
+/// The default equalizer: ten bands from 32 Hz to 16 kHz that leave the audio unchanged.
+#[must_use]
+pub fn flat() -> Vec<EqBand> {
+    default_bands([0.0; 10])
+}
+
+/// Get one of the [`BUILTIN_PRESETS`] by name.
+#[must_use]
+pub fn builtin_preset(name: &str) -> Option<Vec<EqBand>> {
+    let gains_db = match name {
+        "flat" => [0.0; 10],
+        "bass_boost" => [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        "treble_boost" => [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 4.0, 5.0, 6.0],
+        "classical" => [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -4.5, -4.5, -4.5, -6.0],
+        "vocal" => [-2.0, -2.0, -1.0, 1.0, 3.0, 3.0, 2.0, 1.0, 0.0, -1.0],
+        _ => return None,
+    };
+    Some(default_bands(gains_db))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[test]
+    fn test_builtin_presets() {
+        for name in BUILTIN_PRESETS {
+            let bands = builtin_preset(name).unwrap();
+            assert_eq!(bands.len(), DEFAULT_FREQUENCIES_HZ.len());
+            assert!(bands.iter().all(|band| band.clamped() == Some(*band)));
+        }
+        assert_eq!(builtin_preset("flat"), Some(flat()));
+        assert!(flat().iter().all(EqBand::is_flat));
+        assert_eq!(builtin_preset("not a preset"), None);
+    }
+
+    #[rstest]
+    #[case::unchanged(EqBand::new(1000.0, 3.0, 1.0), Some(EqBand::new(1000.0, 3.0, 1.0)))]
+    #[case::too_low(EqBand::new(1.0, -20.0, 0.0), Some(EqBand::new(MIN_FREQUENCY_HZ, MIN_GAIN_DB, MIN_Q)))]
+    #[case::too_high(
+        EqBand::new(1e6, 20.0, 100.0),
+        Some(EqBand::new(MAX_FREQUENCY_HZ, MAX_GAIN_DB, MAX_Q))
+    )]
+    #[case::nan(EqBand::new(1000.0, f32::NAN, 1.0), None)]
+    #[case::infinite(EqBand::new(f32::INFINITY, 3.0, 1.0), None)]
+    fn test_clamped(#[case] band: EqBand, #[case] expected: Option<EqBand>) {
+        assert_eq!(band.clamped(), expected);
+    }
+}
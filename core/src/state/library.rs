@@ -1,7 +1,18 @@
-use mecomp_storage::db::schemas::{
-    album::Album, artist::Artist, collection::Collection, playlist::Playlist, song::Song,
+use mecomp_storage::{
+    db::schemas::{
+        album::Album,
+        artist::Artist,
+        collection::Collection,
+        playlist::Playlist,
+        song::{Song, SongId},
+    },
+    errors::LibraryVerificationError,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How far apart the runtimes of two songs can be for them to be considered duplicates
+pub const DUPLICATE_RUNTIME_TOLERANCE: Duration = Duration::from_secs(2);
 
 /// A brief representation of the library
 #[allow(clippy::module_name_repetitions)]
@@ -25,6 +36,25 @@ pub struct LibraryFull {
     pub collections: Box<[Collection]>,
 }
 
+/// Summary statistics of the audio features of the (analyzed) songs in a collection,
+/// used to get an idea of what a collection represents musically
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CollectionAnalysisStats {
+    /// The number of songs in the collection that have been analyzed,
+    /// songs that haven't been analyzed aren't included in the stats
+    pub analyzed_songs: usize,
+    /// The mean tempo of the songs, in BPM
+    pub mean_bpm: f64,
+    /// The standard deviation of the tempo of the songs, in BPM
+    pub tempo_std: f64,
+    /// The mean loudness of the songs, in dB
+    pub mean_loudness: f64,
+    /// The mean of each of the (normalized) chroma interval features of the songs
+    pub chroma_centroid: [f64; 10],
+}
+
+impl Eq for CollectionAnalysisStats {}
+
 /// Health information about the library
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -71,3 +101,102 @@ pub struct LibraryHealth {
     // /// This is the number of collections that are not in the library
     // pub missing_files: usize,
 }
+
+/// Statistics about the contents of the library, for an overview of the library
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct LibraryStatistics {
+    /// The number of songs in the library
+    pub total_songs: usize,
+    /// The number of albums in the library
+    pub total_albums: usize,
+    /// The number of artists in the library
+    pub total_artists: usize,
+    /// The combined runtime of all the songs in the library
+    pub total_runtime: Duration,
+    /// The number of songs that have been analyzed
+    pub songs_with_analysis: usize,
+    /// The number of songs that haven't been analyzed yet
+    pub songs_without_analysis: usize,
+    /// The mean tempo of the analyzed songs, in BPM, `None` if no songs have been analyzed
+    pub average_bpm: Option<f64>,
+    /// The genre with the most songs, `None` if no songs have a genre
+    pub most_common_genre: Option<String>,
+    /// The number of distinct genres in the library
+    pub unique_genres: usize,
+}
+
+impl Eq for LibraryStatistics {}
+
+/// How far along a library rescan is.
+///
+/// A rescan first checks every song already in the library, then visits every file under the library paths,
+/// `total` counts both.
+///
+/// Also used for the progress of computing the `ReplayGain` of songs, where `total` is the number of songs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScanProgress {
+    /// The number of songs and files that have been checked so far
+    pub scanned: u64,
+    /// The number of songs and files to check
+    pub total: u64,
+    /// The song or file that's being checked
+    pub current_file: String,
+    /// The number of files that couldn't be scanned so far
+    pub errors: u64,
+}
+
+impl ScanProgress {
+    /// The fraction of the rescan that's done, between 0.0 and 1.0
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.scanned as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+/// A group of songs that are likely duplicates of each other (e.g. the same file ripped twice at different qualities),
+/// they have the same title and artists, and runtimes within [`DUPLICATE_RUNTIME_TOLERANCE`] of each other
+/// (or, when grouped by acoustic fingerprint, matching fingerprints)
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DuplicateGroup {
+    /// The songs in the group, ordered by path
+    pub songs: Box<[Song]>,
+}
+
+/// The result of verifying that the file of a song can still be read and decoded
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SongVerificationResult {
+    pub id: SongId,
+    pub status: VerificationStatus,
+}
+
+/// The state of a song's file, as found by verifying it
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum VerificationStatus {
+    /// The file exists and its audio can be decoded
+    Ok,
+    /// The file doesn't exist anymore
+    Missing,
+    /// The file is empty, or its audio can't be decoded
+    Corrupted,
+    /// The file couldn't be read, e.g. because of its permissions
+    Unreadable(String),
+}
+
+impl From<Result<(), LibraryVerificationError>> for VerificationStatus {
+    fn from(result: Result<(), LibraryVerificationError>) -> Self {
+        match result {
+            Ok(()) => Self::Ok,
+            Err(LibraryVerificationError::Missing(_)) => Self::Missing,
+            Err(LibraryVerificationError::Empty(_) | LibraryVerificationError::Corrupted(_)) => {
+                Self::Corrupted
+            }
+            Err(LibraryVerificationError::Unreadable(e)) => Self::Unreadable(e.to_string()),
+        }
+    }
+}
@@ -1,11 +1,17 @@
 #![allow(clippy::module_name_repetitions)]
+pub mod equalizer;
 pub mod library;
-use std::{fmt::Display, time::Duration};
+use std::{
+    fmt::Display,
+    ops::{Add, Div, Mul, Sub},
+    time::Duration,
+};
 
-use mecomp_storage::db::schemas::song::Song;
+use mecomp_storage::db::schemas::song::{Song, SongBrief, SongId};
 use serde::{Deserialize, Serialize};
 
 use crate::format_duration;
+use equalizer::EqBand;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum SeekType {
@@ -62,6 +68,59 @@ impl RepeatMode {
     }
 }
 
+/// How songs are levelled to a common loudness using their `ReplayGain`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, Default)]
+pub enum ReplayGainMode {
+    /// Songs are played at the volume they were recorded at
+    #[default]
+    Off,
+    /// Every song is levelled independently, using its track gain
+    Track,
+    /// Songs are levelled using the gain of their album, preserving the loudness differences between the songs of an album
+    /// (songs whose album gain hasn't been computed are levelled using their track gain)
+    Album,
+}
+
+impl Display for ReplayGainMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "Off"),
+            Self::Track => write!(f, "Track"),
+            Self::Album => write!(f, "Album"),
+        }
+    }
+}
+
+/// Changes in the state of the audio kernel that the rest of the daemon may want to react to.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum StateChange {
+    /// The song with the given id played to completion (it wasn't skipped)
+    SongFinished(SongId),
+    /// The song with the given id was skipped before most of it had been played
+    SongSkipped(SongId),
+}
+
+/// How the queue is ordered using the audio analysis of its songs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SortStrategy {
+    /// From the slowest song to the fastest
+    ByTempo,
+    /// From the quietest song to the loudest
+    ByEnergy,
+    /// From the current song to the song that sounds the least like it
+    BySimilarityToCurrentSong,
+}
+
+impl Display for SortStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ByTempo => write!(f, "Tempo"),
+            Self::ByEnergy => write!(f, "Energy"),
+            Self::BySimilarityToCurrentSong => write!(f, "Similarity to Current Song"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize, Default)]
 pub struct Percent(f32);
 
@@ -75,10 +134,62 @@ impl Percent {
         })
     }
 
+    /// Create a `Percent` from the ratio `numerator / denominator`, e.g. `from_ratio(1.0, 4.0)` is 25%.
+    ///
+    /// Returns `None` if `denominator` is zero.
+    #[must_use]
+    pub fn from_ratio(numerator: f32, denominator: f32) -> Option<Self> {
+        if denominator == 0.0 {
+            None
+        } else {
+            Some(Self::new(numerator / denominator * 100.0))
+        }
+    }
+
     #[must_use]
     pub const fn into_inner(self) -> f32 {
         self.0
     }
+
+    /// The percentage remaining, i.e. `100% - self`.
+    #[must_use]
+    pub fn complement(&self) -> Self {
+        Self::new(100.0 - self.0)
+    }
+}
+
+// arithmetic on `Percent`s clamps the result to [0.0, 100.0]
+
+impl Add for Percent {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Percent {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.0 - rhs.0)
+    }
+}
+
+impl Mul<f32> for Percent {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::new(self.0 * rhs)
+    }
+}
+
+impl Div<f32> for Percent {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Self::new(self.0 / rhs)
+    }
 }
 
 impl Display for Percent {
@@ -107,6 +218,56 @@ impl Display for StateRuntime {
     }
 }
 
+/// The levels of the audio being played, measured over a short window (about 100ms).
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AudioLevels {
+    /// the level of the loudest sample, in decibels relative to full scale (0 dBFS)
+    pub peak_db: f32,
+    /// the root mean square level of the samples, in decibels relative to full scale (0 dBFS)
+    pub rms_db: f32,
+    /// whether any sample exceeded ±1.0 (full scale) once the gain and volume were applied
+    pub clip: bool,
+}
+
+impl AudioLevels {
+    /// The quietest level that is reported, quieter levels (including silence) are clamped to this
+    pub const MIN_LEVEL_DB: f32 = -96.0;
+
+    /// The levels of a window of audio with the given (linear) peak and RMS amplitudes.
+    #[must_use]
+    pub fn new(peak: f32, rms: f32) -> Self {
+        let to_db = |amplitude: f32| (20.0 * amplitude.log10()).max(Self::MIN_LEVEL_DB);
+        Self {
+            peak_db: to_db(peak),
+            rms_db: to_db(rms),
+            clip: peak > 1.0,
+        }
+    }
+}
+
+impl Default for AudioLevels {
+    /// Silence
+    fn default() -> Self {
+        Self {
+            peak_db: Self::MIN_LEVEL_DB,
+            rms_db: Self::MIN_LEVEL_DB,
+            clip: false,
+        }
+    }
+}
+
+impl Display for AudioLevels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak: {:.1}dBFS, rms: {:.1}dBFS{}",
+            self.peak_db,
+            self.rms_db,
+            if self.clip { " (clipping)" } else { "" }
+        )
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct StateAudio {
     pub queue: Box<[Song]>,
@@ -117,13 +278,27 @@ pub struct StateAudio {
     pub paused: bool,
     pub muted: bool,
     pub volume: f32,
+    /// the pre-amplifier gain, in decibels
+    pub gain_db: f32,
+    /// whether the volume is being faded (e.g. with `playback_fade_to_volume`)
+    pub fade_in_progress: bool,
+    /// how long the end of a song overlaps with the start of the next one (`Duration::ZERO` if disabled)
+    pub crossfade_duration: Duration,
+    /// how songs are levelled using their `ReplayGain`
+    pub replaygain_mode: ReplayGainMode,
+    /// the playback speed, `1.0` is normal speed
+    pub playback_speed: f32,
+    /// the bands of the equalizer
+    pub equalizer: Box<[EqBand]>,
+    /// the songs played this session, most recent first
+    pub history: Box<[SongBrief]>,
 }
 
 impl Display for StateAudio {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "StateAudio {{ queue: {:?}, queue_position: {}, current_song: {}, repeat_mode: {}, runtime: {}, paused: {}, muted: {}, volume: {:.0}% }}",
+            "StateAudio {{ queue: {:?}, queue_position: {}, current_song: {}, repeat_mode: {}, runtime: {}, paused: {}, muted: {}, volume: {:.0}%, gain: {:+.1}dB }}",
             self.queue
                 .iter()
                 .map(|song| song.title.to_string())
@@ -135,6 +310,7 @@ impl Display for StateAudio {
             self.paused,
             self.muted,
             self.volume * 100.0,
+            self.gain_db,
         )
     }
 }
@@ -158,6 +334,58 @@ mod tests {
         assert_eq!(mode.is_continuous(), expected[2]);
     }
 
+    #[rstest]
+    #[case::add(Percent::new(20.0) + Percent::new(30.0), 50.0)]
+    #[case::add_clamped(Percent::new(80.0) + Percent::new(30.0), 100.0)]
+    #[case::sub(Percent::new(50.0) - Percent::new(20.0), 30.0)]
+    #[case::sub_clamped(Percent::new(20.0) - Percent::new(50.0), 0.0)]
+    #[case::mul(Percent::new(20.0) * 2.0, 40.0)]
+    #[case::mul_clamped(Percent::new(60.0) * 2.0, 100.0)]
+    #[case::mul_negative(Percent::new(60.0) * -1.0, 0.0)]
+    #[case::div(Percent::new(50.0) / 2.0, 25.0)]
+    #[case::div_by_zero(Percent::new(50.0) / 0.0, 0.0)]
+    #[case::complement(Percent::new(25.0).complement(), 75.0)]
+    #[case::complement_full(Percent::new(100.0).complement(), 0.0)]
+    fn test_percent_arithmetic(#[case] result: Percent, #[case] expected: f32) {
+        assert_eq!(result, Percent::new(expected));
+    }
+
+    #[rstest]
+    #[case::quarter(1.0, 4.0, Some(Percent::new(25.0)))]
+    #[case::whole(3.0, 3.0, Some(Percent::new(100.0)))]
+    #[case::over(5.0, 4.0, Some(Percent::new(100.0)))]
+    #[case::zero_numerator(0.0, 4.0, Some(Percent::new(0.0)))]
+    #[case::zero_denominator(1.0, 0.0, None)]
+    fn test_percent_from_ratio(
+        #[case] numerator: f32,
+        #[case] denominator: f32,
+        #[case] expected: Option<Percent>,
+    ) {
+        assert_eq!(Percent::from_ratio(numerator, denominator), expected);
+    }
+
+    #[rstest]
+    #[case::full_scale(1.0, 1.0, 0.0, 0.0, false)]
+    #[case::half(0.5, 0.5, -6.02, -6.02, false)]
+    #[case::over(1.5, 1.0, 3.52, 0.0, true)]
+    #[case::silence(0.0, 0.0, AudioLevels::MIN_LEVEL_DB, AudioLevels::MIN_LEVEL_DB, false)]
+    fn test_audio_levels(
+        #[case] peak: f32,
+        #[case] rms: f32,
+        #[case] peak_db: f32,
+        #[case] rms_db: f32,
+        #[case] clip: bool,
+    ) {
+        let levels = AudioLevels::new(peak, rms);
+        assert!(
+            (levels.peak_db - peak_db).abs() < 0.01,
+            "{}",
+            levels.peak_db
+        );
+        assert!((levels.rms_db - rms_db).abs() < 0.01, "{}", levels.rms_db);
+        assert_eq!(levels.clip, clip);
+    }
+
     #[rstest]
     #[case::seek_type(SeekType::Absolute, "Absolute")]
     #[case::seek_type(SeekType::RelativeForwards, "Forwards")]
@@ -165,6 +393,12 @@ mod tests {
     #[case::repeat_mode(RepeatMode::None, "None")]
     #[case::repeat_mode(RepeatMode::Once, "Once")]
     #[case::repeat_mode(RepeatMode::Continuous, "Continuous")]
+    #[case::replaygain_mode(ReplayGainMode::Off, "Off")]
+    #[case::replaygain_mode(ReplayGainMode::Track, "Track")]
+    #[case::replaygain_mode(ReplayGainMode::Album, "Album")]
+    #[case::sort_strategy(SortStrategy::ByTempo, "Tempo")]
+    #[case::sort_strategy(SortStrategy::ByEnergy, "Energy")]
+    #[case::sort_strategy(SortStrategy::BySimilarityToCurrentSong, "Similarity to Current Song")]
     #[case::percent(Percent::new(50.0), "50.00%")]
     #[case::state_runtimme(
         StateRuntime {
@@ -174,6 +408,12 @@ mod tests {
         },
         "StateRuntime { seek_position: 00:00:03.00, seek_percent: 50.00%, duration: 00:00:06.00 }"
     )]
+    #[case::audio_levels(AudioLevels::new(0.5, 0.25), "peak: -6.0dBFS, rms: -12.0dBFS")]
+    #[case::audio_levels_clipping(
+        AudioLevels::new(2.0, 1.0),
+        "peak: 6.0dBFS, rms: 0.0dBFS (clipping)"
+    )]
+    #[case::audio_levels_silence(AudioLevels::default(), "peak: -96.0dBFS, rms: -96.0dBFS")]
     #[case::state_audio_empty(
         StateAudio {
             queue: Box::new([]),
@@ -184,8 +424,15 @@ mod tests {
             paused: false,
             muted: false,
             volume: 1.0,
+            gain_db: 0.0,
+            fade_in_progress: false,
+            crossfade_duration: Duration::ZERO,
+            replaygain_mode: ReplayGainMode::Off,
+            playback_speed: 1.0,
+            equalizer: Box::new([]),
+            history: Box::new([]),
         },
-        "StateAudio { queue: [], queue_position: None, current_song: None, repeat_mode: None, runtime: None, paused: false, muted: false, volume: 100% }"
+        "StateAudio { queue: [], queue_position: None, current_song: None, repeat_mode: None, runtime: None, paused: false, muted: false, volume: 100%, gain: +0.0dB }"
     )]
     #[case::state_audio(
         StateAudio {
@@ -203,6 +450,14 @@ mod tests {
                     release_year: None,
                     extension: "mp3".into(),
                     path: "foo/bar.mp3".into(),
+                    replaygain_track_gain: None,
+                    replaygain_album_gain: None,
+                    play_count: 0,
+                    skip_count: 0,
+                    last_played: None,
+                    rating: None,
+                    favorite: false,
+                    bookmark_positions: Vec::new(),
                 }
             ]),
             queue_position: Some(1),
@@ -220,6 +475,14 @@ mod tests {
                     release_year: None,
                     extension: "mp3".into(),
                     path: "foo/bar.mp3".into(),
+                    replaygain_track_gain: None,
+                    replaygain_album_gain: None,
+                    play_count: 0,
+                    skip_count: 0,
+                    last_played: None,
+                    rating: None,
+                    favorite: false,
+                    bookmark_positions: Vec::new(),
                 }
             ),
             repeat_mode: RepeatMode::None,
@@ -231,8 +494,15 @@ mod tests {
             paused: false,
             muted: false,
             volume: 1.0,
+            gain_db: 0.0,
+            fade_in_progress: false,
+            crossfade_duration: Duration::ZERO,
+            replaygain_mode: ReplayGainMode::Off,
+            playback_speed: 1.0,
+            equalizer: Box::new([]),
+            history: Box::new([]),
         },
-        "StateAudio { queue: [\"Song 1\"], queue_position: 1, current_song: \"Song 1\", repeat_mode: None, runtime: StateRuntime { seek_position: 00:00:20.00, seek_percent: 20.00%, duration: 00:01:40.00 }, paused: false, muted: false, volume: 100% }"
+        "StateAudio { queue: [\"Song 1\"], queue_position: 1, current_song: \"Song 1\", repeat_mode: None, runtime: StateRuntime { seek_position: 00:00:20.00, seek_percent: 20.00%, duration: 00:01:40.00 }, paused: false, muted: false, volume: 100%, gain: +0.0dB }"
     )]
     fn test_display_impls<T: Display>(#[case] input: T, #[case] expected: &str) {
         assert_str_eq!(input.to_string(), expected);
@@ -1,6 +1,9 @@
 //! Benchmark of the library analysis function.
 
+use std::time::Duration;
+
 use criterion::{criterion_group, criterion_main, Criterion};
+use mecomp_analysis::SAMPLE_RATE;
 use mecomp_daemon::services::library::analyze;
 use mecomp_storage::db::schemas::song::Song;
 use mecomp_storage::test_utils::init_test_database;
@@ -38,7 +41,9 @@ fn benchmark_rescan(c: &mut Criterion) {
                 .unwrap()
             },
             |db| async move {
-                analyze(&db).await.unwrap();
+                analyze(&db, SAMPLE_RATE, Duration::from_secs(30))
+                    .await
+                    .unwrap();
             },
         );
     });
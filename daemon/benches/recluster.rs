@@ -66,7 +66,7 @@ fn benchmark_recluster(c: &mut Criterion) {
             },
             |db| async move {
                 let db = db.await;
-                recluster(&db, &settings).await.unwrap();
+                recluster(&db, &settings, None).await.unwrap();
             },
         );
     });
@@ -84,7 +84,7 @@ fn benchmark_recluster(c: &mut Criterion) {
             },
             |db| async move {
                 let db = db.await;
-                recluster(&db, &settings).await.unwrap();
+                recluster(&db, &settings, None).await.unwrap();
             },
         );
     });
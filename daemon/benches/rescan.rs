@@ -91,10 +91,11 @@ fn benchmark_rescan(c: &mut Criterion) {
                 let db = init_test_database().await.unwrap();
                 rescan(
                     &db,
-                    &[tempdir.path().to_path_buf()],
+                    &[tempdir.path().to_path_buf().into()],
                     &OneOrMany::One(ARTIST_NAME_SEPARATOR.to_string()),
                     Some(ARTIST_NAME_SEPARATOR),
                     MetadataConflictResolution::default(),
+                    |_| {},
                 )
                 .await
                 .unwrap();
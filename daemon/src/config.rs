@@ -6,14 +6,23 @@
 use config::{Config, ConfigError, Environment, File};
 use one_or_many::OneOrMany;
 use serde::Deserialize;
-
-use std::{path::PathBuf, str::FromStr};
-
+use thiserror::Error;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use mecomp_core::{
+    audio::{MAX_GAIN_DB, MIN_GAIN_DB},
+    state::equalizer::{self, EqBand},
+};
 use mecomp_storage::util::MetadataConflictResolution;
 
 pub static DEFAULT_CONFIG: &str = include_str!("../Mecomp.toml");
 
-#[derive(Clone, Debug, Deserialize, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Default, PartialEq)]
 pub struct Settings {
     /// General Daemon Settings
     #[serde(default)]
@@ -21,6 +30,9 @@ pub struct Settings {
     /// Parameters for the reclustering algorithm.
     #[serde(default)]
     pub reclustering: ReclusterSettings,
+    /// Equalizer presets.
+    #[serde(default)]
+    pub equalizer: EqualizerSettings,
 }
 
 impl Settings {
@@ -28,7 +40,8 @@ impl Settings {
     ///
     /// The config file is located at the path specified by the `--config` flag.
     ///
-    /// The environment variables are prefixed with `MECOMP_`.
+    /// Every setting can be overridden by an environment variable named after its section and field,
+    /// prefixed with `MECOMP_` (e.g. `MECOMP_DAEMON_RPC_PORT=6601`), see [`environment`].
     ///
     /// # Arguments
     ///
@@ -42,16 +55,32 @@ impl Settings {
         config: PathBuf,
         port: Option<u16>,
         log_level: Option<log::LevelFilter>,
+    ) -> Result<Self, ConfigError> {
+        Self::init_with_env(config, port, log_level, std::env::vars())
+    }
+
+    /// Like [`Settings::init`], but reads the environment variable overrides from `vars`
+    /// instead of the process environment.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the config file is not found or if the config file is
+    /// invalid.
+    fn init_with_env(
+        config: PathBuf,
+        port: Option<u16>,
+        log_level: Option<log::LevelFilter>,
+        vars: impl IntoIterator<Item = (String, String)>,
     ) -> Result<Self, ConfigError> {
         let s = Config::builder()
             .add_source(File::from(config))
-            .add_source(Environment::with_prefix("MECOMP"))
+            .add_source(environment(vars))
             .build()?;
 
         let mut settings: Self = s.try_deserialize()?;
 
-        for path in &mut settings.daemon.library_paths {
-            *path = shellexpand::tilde(&path.to_string_lossy())
+        for library_path in &mut settings.daemon.library_paths {
+            library_path.path = shellexpand::tilde(&library_path.path.to_string_lossy())
                 .into_owned()
                 .into();
         }
@@ -66,17 +95,144 @@ impl Settings {
 
         Ok(settings)
     }
+
+    /// Check the settings for errors that would otherwise only be discovered once the daemon is running.
+    ///
+    /// Library paths that don't exist (yet) aren't errors, since the default `~/Music/` doesn't exist on every machine,
+    /// they're only logged as warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns every problem that was found, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<ConfigValidationError>> {
+        let mut errors = Vec::new();
+
+        if self.daemon.rpc_port == 0 {
+            errors.push(ConfigValidationError::ZeroRpcPort);
+        }
+        for library_path in &self.daemon.library_paths {
+            if !library_path.path.is_dir() {
+                log::warn!(
+                    "`daemon.library_paths` contains {}, which isn't a directory (or doesn't exist)",
+                    library_path.path.display()
+                );
+            }
+            if library_path
+                .genre_separator
+                .as_ref()
+                .is_some_and(String::is_empty)
+            {
+                errors.push(ConfigValidationError::EmptyLibraryPathGenreSeparator(
+                    library_path.path.clone(),
+                ));
+            }
+        }
+        if self
+            .daemon
+            .genre_separator
+            .as_ref()
+            .is_some_and(String::is_empty)
+        {
+            errors.push(ConfigValidationError::EmptyGenreSeparator);
+        }
+        if !(MIN_GAIN_DB..=MAX_GAIN_DB).contains(&self.daemon.default_gain_db) {
+            errors.push(ConfigValidationError::GainOutOfRange(
+                self.daemon.default_gain_db,
+            ));
+        }
+        if self.daemon.analysis_timeout_secs == 0 {
+            errors.push(ConfigValidationError::ZeroAnalysisTimeout);
+        }
+        #[cfg(feature = "analysis")]
+        if !mecomp_analysis::SUPPORTED_SAMPLE_RATES.contains(&self.daemon.analysis_sample_rate) {
+            errors.push(ConfigValidationError::UnsupportedSampleRate(
+                self.daemon.analysis_sample_rate,
+            ));
+        }
+        if self.reclustering.max_clusters < 2 {
+            errors.push(ConfigValidationError::TooFewClusters(
+                self.reclustering.max_clusters,
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+/// A problem with the settings, found by [`Settings::validate`].
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ConfigValidationError {
+    #[error("`daemon.rpc_port` must not be 0")]
+    ZeroRpcPort,
+    #[error("`daemon.genre_separator` must not be empty, remove it to not split genres")]
+    EmptyGenreSeparator,
+    #[error("the `genre_separator` of library path {0} must not be empty, remove it to use `daemon.genre_separator`")]
+    EmptyLibraryPathGenreSeparator(PathBuf),
+    #[error("`daemon.default_gain_db` must be between {MIN_GAIN_DB} and {MAX_GAIN_DB}, got {0}")]
+    GainOutOfRange(f32),
+    #[error("`daemon.analysis_timeout_secs` must not be 0")]
+    ZeroAnalysisTimeout,
+    #[error("`daemon.analysis_sample_rate` must be 22050, 44100, or 48000, got {0}")]
+    UnsupportedSampleRate(u32),
+    #[error("`reclustering.max_clusters` must be at least 2, got {0}")]
+    TooFewClusters(usize),
+}
+
+/// The sections of the config file, used to split the names of environment variables into a section and a field.
+const SECTIONS: [&str; 3] = ["daemon", "reclustering", "equalizer"];
+
+/// Build the source for the environment variable overrides of the settings.
+///
+/// Variables are named `MECOMP_<SECTION>_<FIELD>`, e.g. `MECOMP_DAEMON_RPC_PORT` overrides `rpc_port` in the `[daemon]` section.
+/// Since field names contain underscores themselves, the section is matched against the known sections
+/// instead of treating every underscore as a separator.
+///
+/// Lists (e.g. `MECOMP_DAEMON_LIBRARY_PATHS=/music,/podcasts`) are comma separated.
+fn environment(vars: impl IntoIterator<Item = (String, String)>) -> Environment {
+    let source = vars
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let key = key.to_lowercase();
+            let rest = key.strip_prefix("mecomp_")?;
+            SECTIONS.iter().find_map(|section| {
+                let field = rest.strip_prefix(section)?.strip_prefix('_')?;
+                Some((format!("{section}.{field}"), value.clone()))
+            })
+        })
+        .collect();
+
+    Environment::default()
+        .source(Some(source))
+        .separator(".")
+        .try_parsing(true)
+        .list_separator(",")
+        .with_list_parse_key("daemon.library_paths")
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct DaemonSettings {
     /// The port to listen on for RPC requests.
     /// Default is 6600.
     #[serde(default = "default_port")]
     pub rpc_port: u16,
     /// The root paths of the music library.
+    ///
+    /// Each path can override the artist and genre separators for the songs under it,
+    /// paths without overrides can be given as just the path.
+    ///
+    /// ```toml
+    /// [daemon]
+    /// library_paths = [
+    ///     "~/Music",
+    ///     { path = "~/Classical", artist_separator = "; " },
+    /// ]
+    /// ```
     #[serde(default = "default_library_paths")]
-    pub library_paths: Box<[PathBuf]>,
+    pub library_paths: Box<[LibraryPathConfig]>,
     /// Separators for artist names in song metadata.
     /// For example, "Foo, Bar, Baz" would be split into \["Foo", "Bar", "Baz"\]. if the separator is ", ".
     /// If the separator is not found, the entire string is considered as a single artist.
@@ -105,6 +261,143 @@ pub struct DaemonSettings {
     #[serde(default = "default_log_level")]
     #[serde(deserialize_with = "de_log_level")]
     pub log_level: log::LevelFilter,
+    /// The pre-amplifier gain (in decibels) applied to playback when the daemon starts.
+    /// Must be between -24.0 and 12.0, independent of the volume.
+    /// Default is 0.0.
+    #[serde(default)]
+    pub default_gain_db: f32,
+    /// The maximum time (in seconds) the analysis of a single song may take,
+    /// songs that take longer are skipped.
+    /// Default is 30.
+    #[serde(default = "default_analysis_timeout_secs")]
+    pub analysis_timeout_secs: u64,
+    /// The sample rate (in Hz) songs are resampled to before they're analyzed,
+    /// higher sample rates preserve more of the high frequencies, but take longer to analyze.
+    /// Must be 22050, 44100, or 48000.
+    /// Default is 22050.
+    #[serde(default = "default_analysis_sample_rate")]
+    pub analysis_sample_rate: u32,
+    /// The maximum (euclidean) distance between a new song's analysis and the centroid of an existing collection
+    /// for the song to be automatically added to that collection after the library is analyzed.
+    /// 0.0 disables automatically updating collections.
+    /// Default is 0.0.
+    #[serde(default)]
+    pub auto_collection_threshold: f64,
+    /// How many recently played songs the daemon remembers for the playback history.
+    /// Default is 50.
+    #[serde(default = "default_queue_history_depth")]
+    pub queue_history_depth: usize,
+    /// The name of the audio output device to play through (see `mecomp-cli playback list-devices`).
+    /// If unset, or if the device can't be found, the system's default output device is used.
+    #[serde(default)]
+    pub output_device: Option<String>,
+    /// Whether to log the groups of (likely) duplicate songs in the library at the end of every rescan.
+    /// Default is false.
+    #[serde(default)]
+    pub report_duplicates: bool,
+    /// How long (in seconds) the daemon waits for the requests it's handling to finish when it's shutting down,
+    /// requests that are still running after that are dropped.
+    /// Default is 5.
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+}
+
+/// One of the root paths of the music library, and the settings that are specific to the songs under it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(from = "RawLibraryPathConfig")]
+pub struct LibraryPathConfig {
+    /// The root path.
+    pub path: PathBuf,
+    /// Overrides `daemon.artist_separator` for the songs under this path.
+    pub artist_separator: Option<OneOrMany<String>>,
+    /// Overrides `daemon.genre_separator` for the songs under this path.
+    pub genre_separator: Option<String>,
+}
+
+impl LibraryPathConfig {
+    /// Whether `path` is under this library path.
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.path)
+            || self
+                .path
+                .canonicalize()
+                .is_ok_and(|root| path.starts_with(root))
+    }
+
+    /// The artist separator for the songs under this path, `default` if it isn't overridden.
+    #[must_use]
+    pub fn artist_separator<'a>(&'a self, default: &'a OneOrMany<String>) -> &'a OneOrMany<String> {
+        self.artist_separator.as_ref().unwrap_or(default)
+    }
+
+    /// The genre separator for the songs under this path, `default` if it isn't overridden.
+    #[must_use]
+    pub fn genre_separator<'a>(&'a self, default: Option<&'a str>) -> Option<&'a str> {
+        self.genre_separator.as_deref().or(default)
+    }
+}
+
+impl From<PathBuf> for LibraryPathConfig {
+    fn from(path: PathBuf) -> Self {
+        Self {
+            path,
+            artist_separator: None,
+            genre_separator: None,
+        }
+    }
+}
+
+/// Find the library path that `path` is under, the most specific one if library paths are nested.
+#[must_use]
+pub fn library_path_of<'a>(
+    library_paths: &'a [LibraryPathConfig],
+    path: &Path,
+) -> Option<&'a LibraryPathConfig> {
+    library_paths
+        .iter()
+        .filter(|library_path| library_path.contains(path))
+        .max_by_key(|library_path| library_path.path.components().count())
+}
+
+/// A library path as it's written in the config file, either just the path or a table with overrides.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawLibraryPathConfig {
+    Path(PathBuf),
+    Config {
+        path: PathBuf,
+        #[serde(default, deserialize_with = "de_artist_separator_override")]
+        artist_separator: Option<OneOrMany<String>>,
+        #[serde(default)]
+        genre_separator: Option<String>,
+    },
+}
+
+impl From<RawLibraryPathConfig> for LibraryPathConfig {
+    fn from(raw: RawLibraryPathConfig) -> Self {
+        match raw {
+            RawLibraryPathConfig::Path(path) => path.into(),
+            RawLibraryPathConfig::Config {
+                path,
+                artist_separator,
+                genre_separator,
+            } => Self {
+                path,
+                artist_separator,
+                genre_separator,
+            },
+        }
+    }
+}
+
+fn de_artist_separator_override<'de, D>(
+    deserializer: D,
+) -> Result<Option<OneOrMany<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    de_artist_separator(deserializer).map(Some)
 }
 
 fn de_artist_separator<'de, D>(deserializer: D) -> Result<OneOrMany<String>, D::Error>
@@ -134,14 +427,31 @@ const fn default_port() -> u16 {
     6600
 }
 
-fn default_library_paths() -> Box<[PathBuf]> {
-    vec![shellexpand::tilde("~/Music/").into_owned().into()].into_boxed_slice()
+fn default_library_paths() -> Box<[LibraryPathConfig]> {
+    vec![PathBuf::from(shellexpand::tilde("~/Music/").into_owned()).into()].into_boxed_slice()
 }
 
 const fn default_log_level() -> log::LevelFilter {
     log::LevelFilter::Info
 }
 
+const fn default_analysis_timeout_secs() -> u64 {
+    30
+}
+
+/// The same as `mecomp_analysis::SAMPLE_RATE`, which isn't available without the `analysis` feature.
+const fn default_analysis_sample_rate() -> u32 {
+    22050
+}
+
+const fn default_shutdown_timeout_secs() -> u64 {
+    5
+}
+
+const fn default_queue_history_depth() -> usize {
+    mecomp_core::audio::queue::DEFAULT_HISTORY_DEPTH
+}
+
 impl Default for DaemonSettings {
     fn default() -> Self {
         Self {
@@ -151,6 +461,14 @@ impl Default for DaemonSettings {
             genre_separator: None,
             conflict_resolution: MetadataConflictResolution::Overwrite,
             log_level: default_log_level(),
+            default_gain_db: 0.0,
+            analysis_timeout_secs: default_analysis_timeout_secs(),
+            analysis_sample_rate: default_analysis_sample_rate(),
+            auto_collection_threshold: 0.0,
+            queue_history_depth: default_queue_history_depth(),
+            output_device: None,
+            report_duplicates: false,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
         }
     }
 }
@@ -215,6 +533,62 @@ impl Default for ReclusterSettings {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct EqualizerSettings {
+    /// The equalizer preset applied when the daemon starts,
+    /// either one of the built-in presets ("flat", "bass_boost", "treble_boost", "classical", "vocal")
+    /// or one of the custom `presets`.
+    /// Default is "flat".
+    #[serde(default = "default_equalizer_preset")]
+    pub preset: String,
+    /// Custom equalizer presets, by name.
+    /// A custom preset takes precedence over a built-in preset with the same name.
+    #[serde(default)]
+    pub presets: HashMap<String, Vec<EqBand>>,
+}
+
+fn default_equalizer_preset() -> String {
+    "flat".to_string()
+}
+
+impl Default for EqualizerSettings {
+    fn default() -> Self {
+        Self {
+            preset: default_equalizer_preset(),
+            presets: HashMap::new(),
+        }
+    }
+}
+
+impl EqualizerSettings {
+    /// Get the bands of the preset named `name`, looking at the custom presets before the built-in ones.
+    #[must_use]
+    pub fn preset(&self, name: &str) -> Option<Vec<EqBand>> {
+        self.presets
+            .get(name)
+            .cloned()
+            .or_else(|| equalizer::builtin_preset(name))
+    }
+
+    /// The names of all the presets, the built-in ones followed by the custom ones (sorted).
+    #[must_use]
+    pub fn preset_names(&self) -> Vec<String> {
+        let mut custom = self
+            .presets
+            .keys()
+            .filter(|name| !equalizer::BUILTIN_PRESETS.contains(&name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        custom.sort();
+
+        equalizer::BUILTIN_PRESETS
+            .iter()
+            .map(ToString::to_string)
+            .chain(custom)
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,16 +640,30 @@ mod tests {
             r#"            
 [daemon]
 rpc_port = 6600
-library_paths = ["/Music"]
+library_paths = ["/Music", { path = "/Classical", artist_separator = "; ", genre_separator = " / " }]
 artist_separator = ["; "]
 genre_separator = ", "
 conflict_resolution = "overwrite"
 log_level = "debug"
+default_gain_db = 3.0
+analysis_timeout_secs = 60
+analysis_sample_rate = 44100
+auto_collection_threshold = 0.5
+queue_history_depth = 20
+output_device = "USB DAC"
+report_duplicates = true
+shutdown_timeout_secs = 10
 
 [reclustering]
 gap_statistic_reference_datasets = 50
 max_clusters = 24
 algorithm = "gmm"
+
+[equalizer]
+preset = "loud"
+
+[equalizer.presets]
+loud = [{ frequency_hz = 60.0, gain_db = 6.0, q = 1.0 }, { frequency_hz = 10000.0, gain_db = 4.5, q = 0.7 }]
             "#,
         )
         .unwrap();
@@ -283,17 +671,40 @@ algorithm = "gmm"
         let expected = Settings {
             daemon: DaemonSettings {
                 rpc_port: 6600,
-                library_paths: ["/Music".into()].into(),
+                library_paths: [
+                    PathBuf::from("/Music").into(),
+                    LibraryPathConfig {
+                        path: "/Classical".into(),
+                        artist_separator: Some(OneOrMany::One("; ".into())),
+                        genre_separator: Some(" / ".into()),
+                    },
+                ]
+                .into(),
                 artist_separator: vec!["; ".into()].into(),
                 genre_separator: Some(", ".into()),
                 conflict_resolution: MetadataConflictResolution::Overwrite,
                 log_level: log::LevelFilter::Debug,
+                default_gain_db: 3.0,
+                analysis_timeout_secs: 60,
+                analysis_sample_rate: 44100,
+                auto_collection_threshold: 0.5,
+                queue_history_depth: 20,
+                output_device: Some("USB DAC".into()),
+                report_duplicates: true,
+                shutdown_timeout_secs: 10,
             },
             reclustering: ReclusterSettings {
                 gap_statistic_reference_datasets: 50,
                 max_clusters: 24,
                 algorithm: ClusterAlgorithm::GMM,
             },
+            equalizer: EqualizerSettings {
+                preset: "loud".into(),
+                presets: HashMap::from([(
+                    "loud".into(),
+                    vec![EqBand::new(60.0, 6.0, 1.0), EqBand::new(10000.0, 4.5, 0.7)],
+                )]),
+            },
         };
 
         let settings = Settings::init(config_path, None, None).unwrap();
@@ -301,6 +712,68 @@ algorithm = "gmm"
         assert_eq!(settings, expected);
     }
 
+    #[test]
+    fn test_env_overrides_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+[daemon]
+rpc_port = 6600
+library_paths = ["/Music"]
+log_level = "debug"
+auto_collection_threshold = 0.5
+
+[reclustering]
+max_clusters = 24
+algorithm = "gmm"
+            "#,
+        )
+        .unwrap();
+
+        let vars = [
+            ("MECOMP_DAEMON_RPC_PORT", "6601"),
+            ("MECOMP_DAEMON_LIBRARY_PATHS", "/music,/podcasts"),
+            ("MECOMP_DAEMON_LOG_LEVEL", "warn"),
+            ("MECOMP_DAEMON_AUTO_COLLECTION_THRESHOLD", "1.5"),
+            ("MECOMP_RECLUSTERING_ALGORITHM", "kmeans"),
+            // not settings, should be ignored
+            ("MECOMP_DATA", "/data"),
+            ("MECOMP_CONFIG", "/config"),
+            ("HOME", "/home/mecomp"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+
+        let settings = Settings::init_with_env(config_path, None, None, vars).unwrap();
+
+        assert_eq!(settings.daemon.rpc_port, 6601);
+        assert_eq!(
+            settings.daemon.library_paths,
+            [
+                PathBuf::from("/music").into(),
+                PathBuf::from("/podcasts").into()
+            ]
+            .into()
+        );
+        assert_eq!(settings.daemon.log_level, log::LevelFilter::Warn);
+        assert!((settings.daemon.auto_collection_threshold - 1.5).abs() < f64::EPSILON);
+        assert_eq!(settings.reclustering.max_clusters, 24);
+        assert_eq!(settings.reclustering.algorithm, ClusterAlgorithm::KMeans);
+    }
+
+    #[test]
+    fn test_cli_overrides_env() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, DEFAULT_CONFIG).unwrap();
+
+        let vars = [("MECOMP_DAEMON_RPC_PORT".to_string(), "6601".to_string())];
+        let settings = Settings::init_with_env(config_path, Some(6602), None, vars).unwrap();
+
+        assert_eq!(settings.daemon.rpc_port, 6602);
+    }
+
     #[test]
     fn test_default_config_works() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -311,4 +784,121 @@ algorithm = "gmm"
 
         assert!(settings.is_ok(), "Error: {:?}", settings.err());
     }
+
+    #[test]
+    fn test_library_path_of() {
+        let library_paths: [LibraryPathConfig; 3] = [
+            PathBuf::from("/music").into(),
+            LibraryPathConfig {
+                artist_separator: Some(OneOrMany::One("; ".into())),
+                ..PathBuf::from("/music/classical").into()
+            },
+            PathBuf::from("/podcasts").into(),
+        ];
+
+        let library_path = library_path_of(&library_paths, Path::new("/music/pop/song.mp3"));
+        assert_eq!(library_path, Some(&library_paths[0]));
+        // the most specific library path is used
+        let library_path = library_path_of(&library_paths, Path::new("/music/classical/song.mp3"));
+        assert_eq!(library_path, Some(&library_paths[1]));
+        assert_eq!(
+            library_path_of(&library_paths, Path::new("/videos/video.mp4")),
+            None
+        );
+
+        // separators fall back to the defaults
+        let default = OneOrMany::One(", ".to_string());
+        assert_eq!(library_paths[0].artist_separator(&default), &default);
+        assert_eq!(
+            library_paths[1].artist_separator(&default),
+            &OneOrMany::One("; ".to_string())
+        );
+        assert_eq!(library_paths[1].genre_separator(Some(", ")), Some(", "));
+    }
+
+    #[test]
+    fn test_validate() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let mut settings = Settings::default();
+        settings.daemon.library_paths = [temp_dir.path().to_owned().into()].into();
+        assert_eq!(settings.validate(), Ok(()));
+
+        let missing = temp_dir.path().join("missing");
+        settings.daemon.library_paths = [
+            temp_dir.path().to_owned().into(),
+            LibraryPathConfig {
+                genre_separator: Some(String::new()),
+                ..missing.clone().into()
+            },
+        ]
+        .into();
+        settings.daemon.rpc_port = 0;
+        settings.daemon.genre_separator = Some(String::new());
+        settings.daemon.default_gain_db = 20.0;
+        settings.daemon.analysis_timeout_secs = 0;
+        settings.daemon.analysis_sample_rate = 16000;
+        settings.reclustering.max_clusters = 1;
+        assert_eq!(
+            settings.validate(),
+            Err(vec![
+                ConfigValidationError::ZeroRpcPort,
+                ConfigValidationError::EmptyLibraryPathGenreSeparator(missing),
+                ConfigValidationError::EmptyGenreSeparator,
+                ConfigValidationError::GainOutOfRange(20.0),
+                ConfigValidationError::ZeroAnalysisTimeout,
+                ConfigValidationError::UnsupportedSampleRate(16000),
+                ConfigValidationError::TooFewClusters(1),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_library_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // a library path that doesn't exist (yet) is only a warning
+        let mut settings = Settings::default();
+        settings.daemon.library_paths = [temp_dir.path().join("missing").into()].into();
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "analysis")]
+    fn test_default_analysis_sample_rate() {
+        assert_eq!(
+            DaemonSettings::default().analysis_sample_rate,
+            mecomp_analysis::SAMPLE_RATE
+        );
+    }
+
+    #[test]
+    fn test_equalizer_presets() {
+        let custom = vec![EqBand::new(60.0, 6.0, 1.0)];
+        let settings = EqualizerSettings {
+            preset: "flat".into(),
+            presets: HashMap::from([
+                ("loud".into(), custom.clone()),
+                ("bass_boost".into(), custom.clone()),
+            ]),
+        };
+
+        assert_eq!(settings.preset("loud"), Some(custom.clone()));
+        // custom presets take precedence over the built-in ones
+        assert_eq!(settings.preset("bass_boost"), Some(custom));
+        assert_eq!(settings.preset("flat"), Some(equalizer::flat()));
+        assert_eq!(settings.preset("not a preset"), None);
+
+        assert_eq!(
+            settings.preset_names(),
+            vec![
+                "flat",
+                "bass_boost",
+                "treble_boost",
+                "classical",
+                "vocal",
+                "loud"
+            ]
+        );
+    }
 }
@@ -1,5 +1,5 @@
 //----------------------------------------------------------------------------------------- std lib
-use std::{ops::Range, sync::Arc, time::Duration};
+use std::{ops::Range, path::PathBuf, sync::Arc, time::Duration};
 //--------------------------------------------------------------------------------- other libraries
 use ::tarpc::context::Context;
 use log::{debug, error, info, warn};
@@ -14,10 +14,18 @@ use mecomp_core::{
         AudioKernelSender,
     },
     errors::SerializableLibraryError,
-    rpc::{AlbumId, ArtistId, CollectionId, MusicPlayer, PlaylistId, SearchResult, SongId},
+    rpc::{
+        AlbumId, ArtistId, BatchSongUpdateRequest, CollectionId, FailedAnalysisId, ImportStats,
+        MusicPlayer, PlaylistExportReport, PlaylistId, PlaylistImportReport, PlaylistMergeRequest,
+        ReclusterRequest, SearchRequest, SearchResponse, SearchResult, SongId,
+    },
     state::{
-        library::{LibraryBrief, LibraryFull, LibraryHealth},
-        RepeatMode, SeekType, StateAudio,
+        equalizer::EqBand,
+        library::{
+            CollectionAnalysisStats, DuplicateGroup, LibraryBrief, LibraryFull, LibraryHealth,
+            LibraryStatistics, ScanProgress, SongVerificationResult,
+        },
+        AudioLevels, RepeatMode, ReplayGainMode, SeekType, SortStrategy, StateAudio,
     },
 };
 use mecomp_storage::{
@@ -26,8 +34,11 @@ use mecomp_storage::{
         album::{Album, AlbumBrief},
         artist::{Artist, ArtistBrief},
         collection::{Collection, CollectionBrief},
+        failed_analysis::FailedAnalysis,
+        lyrics::Lyrics,
         playlist::{Playlist, PlaylistBrief},
-        song::{Song, SongBrief},
+        scan_history::ScanMetrics,
+        song::{PlayStats, Song, SongBrief},
     },
     errors::Error,
 };
@@ -39,10 +50,17 @@ use crate::{
 };
 
 mod locks {
+    use mecomp_core::state::library::ScanProgress;
     use tokio::sync::Mutex;
 
     pub static LIBRARY_RESCAN_LOCK: Mutex<()> = Mutex::const_new(());
+    /// The progress of the rescan in progress, if there is one.
+    pub static LIBRARY_RESCAN_PROGRESS: std::sync::Mutex<Option<ScanProgress>> =
+        std::sync::Mutex::new(None);
     pub static LIBRARY_ANALYZE_LOCK: Mutex<()> = Mutex::const_new(());
+    /// The progress of the `ReplayGain` computation in progress, if there is one.
+    pub static LIBRARY_REPLAYGAIN_PROGRESS: std::sync::Mutex<Option<ScanProgress>> =
+        std::sync::Mutex::new(None);
     pub static COLLECTION_RECLUSTER_LOCK: Mutex<()> = Mutex::const_new(());
 }
 
@@ -89,18 +107,30 @@ impl MusicPlayer for MusicPlayerServer {
             .spawn(move || {
                 futures::executor::block_on(async {
                     let _guard = locks::LIBRARY_RESCAN_LOCK.lock().await;
+                    *locks::LIBRARY_RESCAN_PROGRESS.lock().unwrap() = Some(ScanProgress::default());
                     match services::library::rescan(
                         &self.db,
                         &self.settings.daemon.library_paths,
                         &self.settings.daemon.artist_separator,
                         self.settings.daemon.genre_separator.as_deref(),
                         self.settings.daemon.conflict_resolution,
+                        |progress| {
+                            *locks::LIBRARY_RESCAN_PROGRESS.lock().unwrap() =
+                                Some(progress.clone());
+                        },
                     )
                     .await
                     {
                         Ok(()) => info!("Library rescan complete"),
                         Err(e) => error!("Error in library_rescan: {e}"),
                     }
+                    locks::LIBRARY_RESCAN_PROGRESS.lock().unwrap().take();
+
+                    if self.settings.daemon.report_duplicates {
+                        if let Err(e) = services::library::report_duplicates(&self.db).await {
+                            error!("Error reporting duplicate songs: {e}");
+                        }
+                    }
                 });
             })?;
 
@@ -111,6 +141,23 @@ impl MusicPlayer for MusicPlayerServer {
     async fn library_rescan_in_progress(self, context: Context) -> bool {
         locks::LIBRARY_RESCAN_LOCK.try_lock().is_err()
     }
+    /// Returns how far along the rescan in progress is.
+    #[instrument]
+    async fn library_rescan_progress(self, context: Context) -> Option<ScanProgress> {
+        locks::LIBRARY_RESCAN_PROGRESS.lock().unwrap().clone()
+    }
+    /// Returns how the scans of each library path went in the recent rescans, most recent first.
+    #[instrument]
+    async fn library_scan_history(
+        self,
+        context: Context,
+    ) -> Result<Box<[ScanMetrics]>, SerializableLibraryError> {
+        info!("Getting library scan history");
+        Ok(services::library::scan_history(&self.db)
+            .await
+            .map(std::vec::Vec::into_boxed_slice)
+            .tap_err(|e| warn!("Error in library_scan_history: {e}"))?)
+    }
     /// Analyze the music library, only error is if an analysis is already in progress.
     #[instrument]
     async fn library_analyze(self, context: Context) -> Result<(), SerializableLibraryError> {
@@ -134,10 +181,29 @@ impl MusicPlayer for MusicPlayerServer {
                 .spawn(move || {
                     futures::executor::block_on(async {
                         let _guard = locks::LIBRARY_ANALYZE_LOCK.lock().await;
-                        match services::library::analyze(&self.db).await {
+                        let timeout =
+                            Duration::from_secs(self.settings.daemon.analysis_timeout_secs);
+                        match services::library::analyze(
+                            &self.db,
+                            self.settings.daemon.analysis_sample_rate,
+                            timeout,
+                        )
+                        .await
+                        {
                             Ok(()) => info!("Library analysis complete"),
                             Err(e) => error!("Error in library_analyze: {e}"),
                         }
+
+                        // place newly analyzed songs into existing collections, if enabled
+                        let threshold = self.settings.daemon.auto_collection_threshold;
+                        if threshold > 0.0 {
+                            if let Err(e) =
+                                services::library::auto_update_collections(&self.db, threshold)
+                                    .await
+                            {
+                                error!("Error automatically updating collections: {e}");
+                            }
+                        }
                     });
                 })?;
 
@@ -149,9 +215,140 @@ impl MusicPlayer for MusicPlayerServer {
     async fn library_analyze_in_progress(self, context: Context) -> bool {
         locks::LIBRARY_ANALYZE_LOCK.try_lock().is_err()
     }
-    /// Recluster the music library, only error is if a recluster is already in progress.
+    /// List the songs that failed to be analyzed (and how many times they've failed).
+    #[instrument]
+    async fn library_list_failed_analysis(
+        self,
+        context: Context,
+    ) -> Result<Box<[FailedAnalysis]>, SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            info!("Listing failed analyses");
+            Ok(FailedAnalysis::read_all(&self.db)
+                .await
+                .tap_err(|e| warn!("Error in library_list_failed_analysis: {e}"))?
+                .into_boxed_slice())
+        }
+    }
+    /// Retry the analysis of the songs with the given failed analysis records, even if they've failed too many times to be retried automatically.
+    /// Only error is if an analysis is already in progress.
     #[instrument]
-    async fn library_recluster(self, context: Context) -> Result<(), SerializableLibraryError> {
+    async fn library_retry_failed_analysis(
+        self,
+        context: Context,
+        ids: Vec<FailedAnalysisId>,
+    ) -> Result<(), SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            info!("Retrying {} failed analyses", ids.len());
+
+            // this shares the analysis lock, since it analyzes songs the same way the analysis does
+            if locks::LIBRARY_ANALYZE_LOCK.try_lock().is_err() {
+                warn!("Library analysis already in progress");
+                return Err(SerializableLibraryError::AnalysisInProgress);
+            }
+
+            std::thread::Builder::new()
+                .name(String::from("Retry Failed Analysis"))
+                .spawn(move || {
+                    futures::executor::block_on(async {
+                        let _guard = locks::LIBRARY_ANALYZE_LOCK.lock().await;
+                        let timeout =
+                            Duration::from_secs(self.settings.daemon.analysis_timeout_secs);
+                        let ids = ids.into_iter().map(Into::into).collect();
+                        match services::library::retry_failed_analysis(
+                            &self.db,
+                            ids,
+                            self.settings.daemon.analysis_sample_rate,
+                            timeout,
+                        )
+                        .await
+                        {
+                            Ok(()) => info!("Retrying failed analyses complete"),
+                            Err(e) => error!("Error in library_retry_failed_analysis: {e}"),
+                        }
+                    });
+                })?;
+
+            Ok(())
+        }
+    }
+    /// Compute the `ReplayGain` of the given songs (in parallel), only error is if an analysis is already in progress.
+    #[instrument]
+    async fn library_compute_replaygain(
+        self,
+        context: Context,
+        song_ids: Vec<SongId>,
+    ) -> Result<(), SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            info!("Computing ReplayGain for {} songs", song_ids.len());
+
+            // this shares the analysis lock, since it decodes songs the same way the analysis does
+            if locks::LIBRARY_ANALYZE_LOCK.try_lock().is_err() {
+                warn!("Library analysis already in progress");
+                return Err(SerializableLibraryError::AnalysisInProgress);
+            }
+
+            std::thread::Builder::new()
+                .name(String::from("ReplayGain Computation"))
+                .spawn(move || {
+                    futures::executor::block_on(async {
+                        let _guard = locks::LIBRARY_ANALYZE_LOCK.lock().await;
+                        *locks::LIBRARY_REPLAYGAIN_PROGRESS.lock().unwrap() =
+                            Some(ScanProgress::default());
+                        let song_ids = song_ids.into_iter().map(Into::into).collect();
+                        match services::library::compute_replaygain(
+                            &self.db,
+                            song_ids,
+                            |progress| {
+                                *locks::LIBRARY_REPLAYGAIN_PROGRESS.lock().unwrap() =
+                                    Some(progress.clone());
+                            },
+                        )
+                        .await
+                        {
+                            Ok(()) => info!("ReplayGain computation complete"),
+                            Err(e) => error!("Error in library_compute_replaygain: {e}"),
+                        }
+                        locks::LIBRARY_REPLAYGAIN_PROGRESS.lock().unwrap().take();
+                    });
+                })?;
+
+            Ok(())
+        }
+    }
+    /// Returns how far along the `ReplayGain` computation in progress is.
+    #[instrument]
+    async fn library_compute_replaygain_progress(self, context: Context) -> Option<ScanProgress> {
+        locks::LIBRARY_REPLAYGAIN_PROGRESS.lock().unwrap().clone()
+    }
+    /// Recluster the music library, replacing the existing collections.
+    /// Errors if a recluster is already in progress, or if `n_clusters` is 0.
+    #[instrument]
+    async fn library_recluster(
+        self,
+        context: Context,
+        request: ReclusterRequest,
+    ) -> Result<(), SerializableLibraryError> {
         #[cfg(not(feature = "analysis"))]
         {
             warn!("Analysis is not enabled");
@@ -162,6 +359,12 @@ impl MusicPlayer for MusicPlayerServer {
         {
             info!("Reclustering collections");
 
+            let n_clusters = request.n_clusters.map(|n| n as usize);
+            if n_clusters == Some(0) {
+                warn!("Can't recluster into 0 collections");
+                return Err(SerializableLibraryError::InvalidClusterCount);
+            }
+
             if locks::COLLECTION_RECLUSTER_LOCK.try_lock().is_err() {
                 warn!("Collection reclustering already in progress");
                 return Err(SerializableLibraryError::ReclusterInProgress);
@@ -172,8 +375,12 @@ impl MusicPlayer for MusicPlayerServer {
                 .spawn(move || {
                     futures::executor::block_on(async {
                         let _guard = locks::COLLECTION_RECLUSTER_LOCK.lock().await;
-                        match services::library::recluster(&self.db, &self.settings.reclustering)
-                            .await
+                        match services::library::recluster(
+                            &self.db,
+                            &self.settings.reclustering,
+                            n_clusters,
+                        )
+                        .await
                         {
                             Ok(()) => info!("Collection reclustering complete"),
                             Err(e) => error!("Error in collection_recluster: {e}"),
@@ -297,6 +504,248 @@ impl MusicPlayer for MusicPlayerServer {
             .await
             .tap_err(|e| warn!("Error in library_health: {e}"))?)
     }
+    /// Returns statistics about the contents of the music library (total runtime, most common genre, etc.)
+    #[instrument]
+    async fn library_statistics(
+        self,
+        context: Context,
+    ) -> Result<LibraryStatistics, SerializableLibraryError> {
+        info!("Computing library statistics");
+        Ok(services::library::statistics(&self.db)
+            .await
+            .tap_err(|e| warn!("Error in library_statistics: {e}"))?)
+    }
+    /// Checks that the files of the given songs (or all songs, if `None`) still exist and can be decoded.
+    #[instrument]
+    async fn library_verify(
+        self,
+        context: Context,
+        song_ids: Option<Vec<SongId>>,
+    ) -> Result<Box<[SongVerificationResult]>, SerializableLibraryError> {
+        info!("Verifying library files");
+        Ok(services::library::verify(
+            &self.db,
+            song_ids.map(|ids| ids.into_iter().map(Into::into).collect()),
+        )
+        .await
+        .tap_err(|e| warn!("Error in library_verify: {e}"))?
+        .into_boxed_slice())
+    }
+    /// Returns the songs whose files don't exist anymore.
+    #[instrument]
+    async fn library_scan_dead_links(
+        self,
+        context: Context,
+    ) -> Result<Vec<SongBrief>, SerializableLibraryError> {
+        info!("Scanning for dead links");
+        Ok(services::library::dead_links(&self.db)
+            .await
+            .tap_err(|e| warn!("Error in library_scan_dead_links: {e}"))?
+            .iter()
+            .map(SongBrief::from)
+            .collect())
+    }
+    /// Deletes the songs whose files don't exist anymore, only error is if a rescan is in progress.
+    #[instrument]
+    async fn library_remove_dead_links(
+        self,
+        context: Context,
+    ) -> Result<u64, SerializableLibraryError> {
+        info!("Removing dead links");
+        // the rescan also deletes songs whose files are missing, so don't run at the same time as one
+        let Ok(_guard) = locks::LIBRARY_RESCAN_LOCK.try_lock() else {
+            warn!("Library rescan already in progress");
+            return Err(SerializableLibraryError::RescanInProgress);
+        };
+        Ok(services::library::remove_dead_links(&self.db)
+            .await
+            .tap_err(|e| warn!("Error in library_remove_dead_links: {e}"))?)
+    }
+    /// Returns the groups of songs in the music library that are likely duplicates of each other.
+    #[instrument]
+    async fn library_find_duplicates(
+        self,
+        context: Context,
+    ) -> Result<Vec<DuplicateGroup>, SerializableLibraryError> {
+        info!("Finding duplicate songs");
+        Ok(services::library::find_duplicates(&self.db)
+            .await
+            .tap_err(|e| warn!("Error in library_find_duplicates: {e}"))?)
+    }
+    /// Returns the groups of songs in the music library that have matching acoustic fingerprints.
+    #[instrument]
+    async fn library_find_acoustid_duplicates(
+        self,
+        context: Context,
+    ) -> Result<Vec<DuplicateGroup>, SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            info!("Finding duplicate songs by acoustic fingerprint");
+
+            // this shares the analysis lock, since it decodes songs the same way the analysis does
+            let Ok(_guard) = locks::LIBRARY_ANALYZE_LOCK.try_lock() else {
+                warn!("Library analysis already in progress");
+                return Err(SerializableLibraryError::AnalysisInProgress);
+            };
+
+            Ok(services::library::find_acoustid_duplicates(&self.db)
+                .await
+                .tap_err(|e| warn!("Error in library_find_acoustid_duplicates: {e}"))?)
+        }
+    }
+    /// Merge the `remove_ids` songs into the `keep_id` song, then delete them.
+    #[instrument]
+    async fn library_remove_duplicate(
+        self,
+        context: Context,
+        keep_id: SongId,
+        remove_ids: Vec<SongId>,
+    ) -> Result<Song, SerializableLibraryError> {
+        let keep_id = keep_id.into();
+        info!(
+            "Merging {} duplicate(s) into song {keep_id}",
+            remove_ids.len()
+        );
+        Ok(Song::merge_duplicates(
+            &self.db,
+            keep_id,
+            remove_ids.into_iter().map(Into::into).collect(),
+        )
+        .await
+        .tap_err(|e| warn!("Error in library_remove_duplicate: {e}"))?
+        .ok_or(Error::NotFound)?)
+    }
+    /// Apply the same changes to every song in the request, returning the number of songs that were updated.
+    #[instrument]
+    async fn library_batch_update_songs(
+        self,
+        context: Context,
+        request: BatchSongUpdateRequest,
+    ) -> Result<u64, SerializableLibraryError> {
+        info!("Updating {} song(s)", request.ids.len());
+        Ok(Song::batch_update(
+            &self.db,
+            request.ids.into_iter().map(Into::into).collect(),
+            request.changeset,
+        )
+        .await
+        .tap_err(|e| warn!("Error in library_batch_update_songs: {e}"))?)
+    }
+    /// Returns the distinct genres of the songs in the music library, sorted alphabetically.
+    #[instrument]
+    async fn library_list_genres(
+        self,
+        context: Context,
+    ) -> Result<Vec<String>, SerializableLibraryError> {
+        info!("Listing genres");
+        Ok(Song::read_genres(&self.db)
+            .await
+            .map(|genres| genres.iter().map(ToString::to_string).collect())
+            .tap_err(|e| warn!("Error in library_list_genres: {e}"))?)
+    }
+    /// Returns (up to `limit`) songs with the given genre, ordered by title.
+    #[instrument]
+    async fn library_songs_by_genre(
+        self,
+        context: Context,
+        genre: String,
+        limit: usize,
+    ) -> Result<Box<[Song]>, SerializableLibraryError> {
+        info!("Getting songs with genre: {genre}");
+        Ok(
+            Song::read_by_genre(&self.db, &genre, i64::try_from(limit).unwrap_or(i64::MAX))
+                .await
+                .map(std::vec::Vec::into_boxed_slice)
+                .tap_err(|e| warn!("Error in library_songs_by_genre: {e}"))?,
+        )
+    }
+    /// Returns (up to `limit`) songs released between `start_year` and `end_year` (inclusive),
+    /// ordered by release year then title. Songs without a release year are excluded.
+    #[instrument]
+    async fn library_year_range(
+        self,
+        context: Context,
+        start_year: i32,
+        end_year: i32,
+        limit: usize,
+    ) -> Result<Box<[Song]>, SerializableLibraryError> {
+        info!("Getting songs released between {start_year} and {end_year}");
+        Ok(Song::read_by_year_range(
+            &self.db,
+            start_year,
+            end_year,
+            i64::try_from(limit).unwrap_or(i64::MAX),
+        )
+        .await
+        .map(std::vec::Vec::into_boxed_slice)
+        .tap_err(|e| warn!("Error in library_year_range: {e}"))?)
+    }
+    /// Returns (up to `limit`) songs with a tempo between `min_bpm` and `max_bpm` (inclusive),
+    /// ordered by tempo. Songs that haven't been analyzed are excluded.
+    #[instrument]
+    async fn library_bpm_range(
+        self,
+        context: Context,
+        min_bpm: f64,
+        max_bpm: f64,
+        limit: usize,
+    ) -> Result<Box<[Song]>, SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            info!("Getting songs with a tempo between {min_bpm} and {max_bpm} BPM");
+            Ok(schemas::analysis::Analysis::read_songs_in_bpm_range(
+                &self.db,
+                min_bpm,
+                max_bpm,
+                i64::try_from(limit).unwrap_or(i64::MAX),
+            )
+            .await
+            .map(std::vec::Vec::into_boxed_slice)
+            .tap_err(|e| warn!("Error in library_bpm_range: {e}"))?)
+        }
+    }
+    /// Returns a random sample of (up to `limit`) songs from the library.
+    /// If a `seed` is given, the same seed gives the same sample (as long as the library doesn't change).
+    #[instrument]
+    async fn library_random_songs(
+        self,
+        context: Context,
+        limit: usize,
+        seed: Option<u64>,
+    ) -> Result<Box<[Song]>, SerializableLibraryError> {
+        info!("Getting {limit} random songs (seed: {seed:?})");
+        Ok(
+            Song::read_random(&self.db, i64::try_from(limit).unwrap_or(i64::MAX), seed)
+                .await
+                .map(std::vec::Vec::into_boxed_slice)
+                .tap_err(|e| warn!("Error in library_random_songs: {e}"))?,
+        )
+    }
+    /// Get the songs played this session, most recent first.
+    #[instrument]
+    async fn library_playback_history(self, context: Context) -> Box<[SongBrief]> {
+        info!("Getting playback history");
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.audio_kernel.send(AudioCommand::ReportStatus(tx));
+
+        rx.await
+            .tap_err(|e| warn!("Error in library_playback_history: {e}"))
+            .map(|state| state.history)
+            .unwrap_or_default()
+    }
     /// Get a song by its ID.
     #[instrument]
     async fn library_song_get(self, context: Context, id: SongId) -> Option<Song> {
@@ -354,6 +803,109 @@ impl MusicPlayer for MusicPlayerServer {
             .unwrap_or_default()
             .into()
     }
+    /// Get how often, and how recently, a song has been played to completion.
+    #[instrument]
+    async fn library_song_get_play_stats(self, context: Context, id: SongId) -> Option<PlayStats> {
+        let id = id.into();
+        info!("Getting play stats of: {id}");
+        Song::read(&self.db, id)
+            .await
+            .tap_err(|e| warn!("Error in library_song_get_play_stats: {e}"))
+            .ok()
+            .flatten()
+            .as_ref()
+            .map(PlayStats::from)
+    }
+    /// Get the lyrics of a song, if it has any.
+    #[instrument]
+    async fn library_song_get_lyrics(self, context: Context, id: SongId) -> Option<String> {
+        let id = id.into();
+        info!("Getting lyrics of: {id}");
+        Lyrics::read_for_song(&self.db, id)
+            .await
+            .tap_err(|e| warn!("Error in library_song_get_lyrics: {e}"))
+            .ok()
+            .flatten()
+            .map(|lyrics| lyrics.lyrics.to_string())
+    }
+    /// Rate a song from 1 to 5 stars, or clear its rating if `rating` is `None`.
+    #[instrument]
+    async fn library_song_set_rating(
+        self,
+        context: Context,
+        id: SongId,
+        rating: Option<u8>,
+    ) -> Result<(), SerializableLibraryError> {
+        let id = id.into();
+        info!("Setting rating of {id} to {rating:?}");
+        Song::set_rating(&self.db, id, rating)
+            .await?
+            .ok_or(Error::NotFound)?;
+        Ok(())
+    }
+    /// Add a song to the favorites, or remove it if it's already a favorite.
+    /// Returns whether the song is now a favorite.
+    #[instrument]
+    async fn library_song_toggle_favorite(
+        self,
+        context: Context,
+        id: SongId,
+    ) -> Result<bool, SerializableLibraryError> {
+        let id = id.into();
+        info!("Toggling whether {id} is a favorite");
+        let song = Song::toggle_favorite(&self.db, id)
+            .await?
+            .ok_or(Error::NotFound)?;
+        Ok(song.favorite)
+    }
+    /// Get the bookmarked positions in a song, in order.
+    #[instrument]
+    async fn library_song_get_bookmarks(
+        self,
+        context: Context,
+        id: SongId,
+    ) -> Option<Box<[Duration]>> {
+        let id = id.into();
+        info!("Getting bookmarks of: {id}");
+        Song::read(&self.db, id)
+            .await
+            .tap_err(|e| warn!("Error in library_song_get_bookmarks: {e}"))
+            .ok()
+            .flatten()
+            .map(|song| song.bookmark_positions.into_boxed_slice())
+    }
+    /// Bookmark a position in a song (to the nearest millisecond).
+    /// Returns the song's bookmarks, in order.
+    #[instrument]
+    async fn library_song_add_bookmark(
+        self,
+        context: Context,
+        id: SongId,
+        position: Duration,
+    ) -> Result<Box<[Duration]>, SerializableLibraryError> {
+        let id = id.into();
+        info!("Bookmarking {position:?} in {id}");
+        let song = Song::add_bookmark(&self.db, id, position)
+            .await?
+            .ok_or(Error::NotFound)?;
+        Ok(song.bookmark_positions.into_boxed_slice())
+    }
+    /// Remove the bookmark at a position in a song, if there is one.
+    /// Returns the song's remaining bookmarks, in order.
+    #[instrument]
+    async fn library_song_remove_bookmark(
+        self,
+        context: Context,
+        id: SongId,
+        position: Duration,
+    ) -> Result<Box<[Duration]>, SerializableLibraryError> {
+        let id = id.into();
+        info!("Removing the bookmark at {position:?} in {id}");
+        let song = Song::remove_bookmark(&self.db, id, position)
+            .await?
+            .ok_or(Error::NotFound)?;
+        Ok(song.bookmark_positions.into_boxed_slice())
+    }
 
     /// Get an album by its ID.
     #[instrument]
@@ -425,6 +977,22 @@ impl MusicPlayer for MusicPlayerServer {
             .ok()
             .map(Into::into)
     }
+    /// Merge the `source` artist into the `target` artist.
+    #[instrument]
+    async fn library_artist_merge(
+        self,
+        context: Context,
+        source: ArtistId,
+        target: ArtistId,
+    ) -> Result<Artist, SerializableLibraryError> {
+        let source = source.into();
+        let target = target.into();
+        info!("Merging artist {source} into {target}");
+        Ok(Artist::merge(&self.db, source, target)
+            .await
+            .tap_err(|e| warn!("Error in library_artist_merge: {e}"))?
+            .ok_or(Error::NotFound)?)
+    }
 
     /// tells the daemon to shutdown.
     #[instrument]
@@ -614,6 +1182,12 @@ impl MusicPlayer for MusicPlayerServer {
             .unwrap_or_default()
             .into()
     }
+    /// performs a full-text search of the library, returning the songs, albums, and artists that match.
+    #[instrument]
+    async fn library_search(self, context: Context, request: SearchRequest) -> SearchResponse {
+        let SearchRequest { query, limit } = request;
+        self.search(context, query, limit).await
+    }
 
     /// toggles playback (play/pause).
     #[instrument]
@@ -673,6 +1247,13 @@ impl MusicPlayer for MusicPlayerServer {
         info!("Seeking {seek} by {:.2}s", duration.as_secs_f32());
         self.audio_kernel.send(AudioCommand::Seek(seek, duration));
     }
+    /// seek to a percentage (0.0-100.0, clamped) of the way through the current song.
+    /// Does nothing if there is no current song.
+    #[instrument]
+    async fn playback_seek_percent(self, context: Context, percent: f32) {
+        info!("Seeking to {percent:.2}%");
+        self.audio_kernel.send(AudioCommand::SeekPercent(percent));
+    }
     /// set the repeat mode.
     #[instrument]
     async fn playback_repeat(self, context: Context, mode: RepeatMode) {
@@ -730,6 +1311,118 @@ impl MusicPlayer for MusicPlayerServer {
         self.audio_kernel
             .send(AudioCommand::Volume(VolumeCommand::Unmute));
     }
+    /// set the pre-amplifier gain (in decibels, clamped to -24.0..=12.0).
+    /// The gain is independent of the volume, so muting doesn't reset it.
+    #[instrument]
+    async fn playback_gain(self, context: Context, gain_db: f32) {
+        info!("Setting gain to: {gain_db}dB",);
+        self.audio_kernel.send(AudioCommand::SetGain(gain_db));
+    }
+    /// gradually change the volume to `target` over `duration` (e.g. to fade out before pausing).
+    /// Starting another fade, or explicitly changing the volume, cancels the fade in progress.
+    #[instrument]
+    async fn playback_fade_to_volume(self, context: Context, target: f32, duration: Duration) {
+        info!(
+            "Fading volume to: {target} over {:.2}s",
+            duration.as_secs_f32()
+        );
+        self.audio_kernel
+            .send(AudioCommand::FadeToVolume(target, duration));
+    }
+    /// cancel the volume fade in progress (if any), leaving the volume where it is.
+    #[instrument]
+    async fn playback_cancel_fade(self, context: Context) {
+        info!("Cancelling volume fade");
+        self.audio_kernel.send(AudioCommand::CancelFade);
+    }
+    /// set how long the end of a song overlaps with the start of the next one, fading out as it fades in (`Duration::ZERO` disables it).
+    /// The duration is clamped to at most 12 seconds.
+    #[instrument]
+    async fn playback_crossfade(self, context: Context, duration: Duration) {
+        info!(
+            "Setting crossfade duration to: {:.2}s",
+            duration.as_secs_f32()
+        );
+        self.audio_kernel
+            .send(AudioCommand::SetCrossfadeDuration(duration));
+    }
+    /// set how songs are levelled using their `ReplayGain` (normalizing them to -14 LUFS, by at most ±12 dB).
+    /// Songs whose `ReplayGain` hasn't been computed (see `library_compute_replaygain`) are not levelled.
+    #[instrument]
+    async fn playback_replaygain(self, context: Context, mode: ReplayGainMode) {
+        info!("Setting ReplayGain mode to: {mode}");
+        self.audio_kernel
+            .send(AudioCommand::SetReplayGainMode(mode));
+    }
+    /// set the playback speed, `1.0` is normal speed.
+    /// The speed is clamped to between 0.5x and 2x, and carries over to the songs that play next.
+    #[instrument]
+    async fn playback_set_speed(self, context: Context, speed: f32) {
+        info!("Setting playback speed to: {speed}x");
+        self.audio_kernel.send(AudioCommand::SetSpeed(speed));
+    }
+    /// list the names of the audio output devices available to the daemon.
+    #[instrument]
+    async fn playback_list_output_devices(self, context: Context) -> Vec<String> {
+        info!("Listing output devices");
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.audio_kernel.send(AudioCommand::ListOutputDevices(tx));
+
+        rx.await
+            .tap_err(|e| warn!("Error in playback_list_output_devices: {e}"))
+            .unwrap_or_default()
+    }
+    /// switch the audio output to the output device named `name` (see `playback_list_output_devices`).
+    /// If the device can't be found or opened, the current output device is kept.
+    #[instrument]
+    async fn playback_set_output_device(self, context: Context, name: String) {
+        info!("Switching output device to: {name}");
+        self.audio_kernel.send(AudioCommand::SetOutputDevice(name));
+    }
+    /// replace the bands of the equalizer.
+    /// Bands are clamped to ±12 dB between 20 Hz and 20 kHz, and bands that aren't finite are dropped.
+    #[instrument]
+    async fn playback_set_equalizer(self, context: Context, bands: Vec<EqBand>) {
+        info!("Setting the equalizer to {} bands", bands.len());
+        self.audio_kernel.send(AudioCommand::SetEqualizer(bands));
+    }
+    /// list the names of the equalizer presets, the built-in ones followed by the ones from the settings.
+    #[instrument]
+    async fn playback_equalizer_presets(self, context: Context) -> Vec<String> {
+        info!("Listing equalizer presets");
+        self.settings.equalizer.preset_names()
+    }
+    /// apply the equalizer preset named `name`, returning its bands (or `None` if there is no such preset).
+    #[instrument]
+    async fn playback_equalizer_preset(
+        self,
+        context: Context,
+        name: String,
+    ) -> Option<Vec<EqBand>> {
+        info!("Applying equalizer preset: {name}");
+        let bands = self.settings.equalizer.preset(&name);
+        if let Some(bands) = &bands {
+            self.audio_kernel
+                .send(AudioCommand::SetEqualizer(bands.clone()));
+        } else {
+            warn!("No equalizer preset named: {name}");
+        }
+        bands
+    }
+    /// get the peak and RMS levels of the audio being played (over about the last 100ms), for level meters.
+    /// Reports silence if nothing is playing.
+    #[instrument]
+    async fn playback_levels(self, context: Context) -> AudioLevels {
+        debug!("Getting the audio levels");
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.audio_kernel.send(AudioCommand::ReportLevels(tx));
+
+        rx.await
+            .tap_err(|e| warn!("Error in playback_levels: {e}"))
+            .unwrap_or_default()
+    }
 
     /// add a song to the queue.
     /// (if the queue is empty, it will start playing the song.)
@@ -780,6 +1473,29 @@ impl MusicPlayer for MusicPlayerServer {
 
         Ok(())
     }
+    /// insert a song into the queue at `index` (or at the end, if `index` is past the end of the queue).
+    /// the current song doesn't change, even if the song is inserted before it.
+    /// (if the queue is empty, it will start playing the song.)
+    #[instrument]
+    async fn queue_insert_at(
+        self,
+        context: Context,
+        index: usize,
+        id: SongId,
+    ) -> Result<(), SerializableLibraryError> {
+        let id = id.into();
+        info!("Inserting song into queue at {index}: {id}");
+
+        let song = Song::read(&self.db, id).await?.ok_or(Error::NotFound)?;
+
+        self.audio_kernel
+            .send(AudioCommand::Queue(QueueCommand::InsertAt(
+                index,
+                Box::new(song),
+            )));
+
+        Ok(())
+    }
     /// set the current song to a queue index.
     /// if the index is out of bounds, it will be clamped to the nearest valid index.
     #[instrument]
@@ -798,6 +1514,107 @@ impl MusicPlayer for MusicPlayerServer {
         self.audio_kernel
             .send(AudioCommand::Queue(QueueCommand::RemoveRange(range)));
     }
+    /// move the song at index `from` to index `to` (clamped to the end of the queue), shifting the songs in between.
+    /// the current song doesn't change, even if it is moved or displaced.
+    #[instrument]
+    async fn queue_move(self, context: Context, from: usize, to: usize) {
+        info!("Moving queue item {from} to {to}");
+
+        self.audio_kernel
+            .send(AudioCommand::Queue(QueueCommand::Move(from, to)));
+    }
+    /// remove duplicate songs from the queue, keeping the first occurrence of each.
+    #[instrument]
+    async fn queue_dedup(self, context: Context) {
+        info!("Removing duplicate songs from the queue");
+
+        self.audio_kernel
+            .send(AudioCommand::Queue(QueueCommand::Dedup));
+    }
+    /// sort the queue using the audio analysis of its songs, without changing the current song.
+    /// songs that haven't been analyzed are moved to the end of the queue.
+    #[instrument]
+    async fn queue_sort_by_analysis(
+        self,
+        context: Context,
+        strategy: SortStrategy,
+    ) -> Result<(), SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            info!("Sorting the queue by: {strategy}");
+
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            self.audio_kernel.send(AudioCommand::ReportStatus(tx));
+            let state = rx
+                .await
+                .tap_err(|e| warn!("Error in queue_sort_by_analysis (getting queue): {e}"))
+                .map_err(|_| Error::NotFound)?;
+
+            let ranking = services::queue::rank_by_analysis(
+                &self.db,
+                &state.queue,
+                state.current_song.as_ref(),
+                strategy,
+            )
+            .await
+            .tap_err(|e| warn!("Error in queue_sort_by_analysis (ranking songs): {e}"))?;
+
+            self.audio_kernel
+                .send(AudioCommand::Queue(QueueCommand::SortByAnalysis(
+                    strategy,
+                    ranking.into_boxed_slice(),
+                )));
+
+            Ok(())
+        }
+    }
+    /// save the current queue as a new playlist with the given name.
+    /// returns the id of the new playlist.
+    #[instrument]
+    async fn queue_save_as_playlist(
+        self,
+        context: Context,
+        name: String,
+    ) -> Result<PlaylistId, SerializableLibraryError> {
+        info!("Saving queue as playlist: {name}");
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.audio_kernel.send(AudioCommand::ReportStatus(tx));
+        let queue = rx
+            .await
+            .tap_err(|e| warn!("Error in queue_save_as_playlist (getting queue): {e}"))
+            .map_err(|_| Error::NotFound)?
+            .queue;
+
+        let playlist = Playlist::create(
+            &self.db,
+            Playlist {
+                id: Playlist::generate_id(),
+                name: name.into(),
+                runtime: Duration::from_secs(0),
+                song_count: 0,
+            },
+        )
+        .await
+        .tap_err(|e| warn!("Error in queue_save_as_playlist (creating playlist): {e}"))?
+        .ok_or(Error::NotCreated)?;
+
+        Playlist::add_songs(
+            &self.db,
+            playlist.id.clone(),
+            queue.iter().map(|song| song.id.clone()).collect(),
+        )
+        .await
+        .tap_err(|e| warn!("Error in queue_save_as_playlist (adding songs): {e}"))?;
+
+        Ok(playlist.id.into())
+    }
 
     /// Returns brief information about the users playlists.
     #[instrument]
@@ -877,6 +1694,27 @@ impl MusicPlayer for MusicPlayerServer {
 
         Ok(new_playlist.id.into())
     }
+    /// merge playlists into a new playlist.
+    #[instrument]
+    async fn playlist_merge(
+        self,
+        context: Context,
+        request: PlaylistMergeRequest,
+    ) -> Result<PlaylistId, SerializableLibraryError> {
+        let PlaylistMergeRequest { source_ids, name } = request;
+        info!(
+            "Merging {} playlists into new playlist: {name}",
+            source_ids.len()
+        );
+
+        let source_ids = source_ids.into_iter().map(Into::into).collect();
+        let merged = Playlist::merge(&self.db, source_ids, name.into())
+            .await
+            .tap_err(|e| warn!("Error in playlist_merge: {e}"))?
+            .ok_or(Error::NotFound)?;
+
+        Ok(merged.id.into())
+    }
     /// get the id of a playlist.
     /// returns none if the playlist does not exist.
     #[instrument]
@@ -978,6 +1816,81 @@ impl MusicPlayer for MusicPlayerServer {
             .ok()
             .map(Into::into)
     }
+    /// Import a playlist from a Spotify or Apple Music CSV export.
+    #[instrument]
+    async fn playlist_import_spotify_csv(
+        self,
+        context: Context,
+        path: PathBuf,
+        threshold: f64,
+    ) -> Result<PlaylistImportReport, SerializableLibraryError> {
+        info!("Importing playlist from: {}", path.display());
+        services::playlist::import_csv(&self.db, &path, threshold)
+            .await
+            .tap_err(|e| warn!("Error in playlist_import_spotify_csv: {e}"))
+    }
+    /// Import a playlist from an M3U or XSPF file.
+    #[instrument]
+    async fn playlist_import(
+        self,
+        context: Context,
+        path: PathBuf,
+    ) -> Result<PlaylistImportReport, SerializableLibraryError> {
+        info!("Importing playlist from: {}", path.display());
+        services::playlist::import_file(&self.db, &path)
+            .await
+            .tap_err(|e| warn!("Error in playlist_import: {e}"))
+    }
+    /// Export a playlist to an M3U or XSPF file.
+    #[instrument]
+    async fn playlist_export(
+        self,
+        context: Context,
+        id: PlaylistId,
+        path: PathBuf,
+    ) -> Result<(), SerializableLibraryError> {
+        info!("Exporting playlist {id:?} to: {}", path.display());
+        services::playlist::export_file(&self.db, id.into(), &path)
+            .await
+            .tap_err(|e| warn!("Error in playlist_export: {e}"))
+    }
+    /// Export every playlist in the library to M3U files in the given directory.
+    #[instrument]
+    async fn library_export_all_playlists(
+        self,
+        context: Context,
+        dir: PathBuf,
+        overwrite: bool,
+    ) -> Result<PlaylistExportReport, SerializableLibraryError> {
+        info!("Exporting all playlists to: {}", dir.display());
+        services::playlist::export_all_m3u(&self.db, &dir, overwrite)
+            .await
+            .tap_err(|e| warn!("Error in library_export_all_playlists: {e}"))
+    }
+    /// Back up the metadata of the library to a JSON file.
+    #[instrument]
+    async fn library_export_json(
+        self,
+        context: Context,
+        path: PathBuf,
+    ) -> Result<(), SerializableLibraryError> {
+        info!("Exporting library to: {}", path.display());
+        services::backup::export_json(&self.db, &path)
+            .await
+            .tap_err(|e| warn!("Error in library_export_json: {e}"))
+    }
+    /// Merge a backup of the library into the library.
+    #[instrument]
+    async fn library_import_json(
+        self,
+        context: Context,
+        path: PathBuf,
+    ) -> Result<ImportStats, SerializableLibraryError> {
+        info!("Importing library from: {}", path.display());
+        services::backup::import_json(&self.db, &path)
+            .await
+            .tap_err(|e| warn!("Error in library_import_json: {e}"))
+    }
 
     /// Collections: Return brief information about the users auto curration collections.
     #[instrument]
@@ -1024,6 +1937,59 @@ impl MusicPlayer for MusicPlayerServer {
             .ok()
             .map(Into::into)
     }
+    /// Collections: get summary statistics (mean tempo, loudness, etc.) of the analyzed songs in a collection.
+    #[instrument]
+    async fn library_collection_get_analysis_stats(
+        self,
+        context: Context,
+        id: CollectionId,
+    ) -> Result<Option<CollectionAnalysisStats>, SerializableLibraryError> {
+        #[cfg(not(feature = "analysis"))]
+        {
+            warn!("Analysis is not enabled");
+            return Err(SerializableLibraryError::AnalysisNotEnabled);
+        }
+
+        #[cfg(feature = "analysis")]
+        {
+            let id = id.into();
+            info!("Getting analysis stats of: {id}");
+            Ok(services::library::collection_analysis_stats(&self.db, id)
+                .await
+                .tap_err(|e| warn!("Error in library_collection_get_analysis_stats: {e}"))?)
+        }
+    }
+    /// Collections: rename a collection.
+    #[instrument]
+    async fn collection_rename(
+        self,
+        context: Context,
+        id: CollectionId,
+        name: String,
+    ) -> Result<Collection, SerializableLibraryError> {
+        let id = id.into();
+        info!("Renaming collection {id} to {name}");
+        Ok(Collection::rename(&self.db, id, name.into())
+            .await
+            .tap_err(|e| warn!("Error in collection_rename: {e}"))?
+            .ok_or(Error::NotFound)?)
+    }
+    /// Collections: merge the `source` collection into the `target` collection.
+    #[instrument]
+    async fn collection_merge(
+        self,
+        context: Context,
+        source: CollectionId,
+        target: CollectionId,
+    ) -> Result<Collection, SerializableLibraryError> {
+        let source = source.into();
+        let target = target.into();
+        info!("Merging collection {source} into {target}");
+        Ok(Collection::merge(&self.db, source, target)
+            .await
+            .tap_err(|e| warn!("Error in collection_merge: {e}"))?
+            .ok_or(Error::NotFound)?)
+    }
 
     /// Radio: get the `n` most similar songs to the given things.
     #[instrument]
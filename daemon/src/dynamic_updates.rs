@@ -3,12 +3,15 @@
 //! This module is only available when the `dynamic_updates` feature is enabled.
 //!
 //! The `init_music_library_watcher`
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use futures::FutureExt;
 use futures::StreamExt;
 use log::{debug, error, info, trace, warn};
-use mecomp_storage::db::schemas::song::{Song, SongChangeSet, SongMetadata};
+use mecomp_storage::db::schemas::{
+    lyrics::Lyrics,
+    song::{Song, SongChangeSet, SongMetadata},
+};
 #[cfg(target_os = "macos")]
 use notify::FsEventWatcher;
 #[cfg(target_os = "linux")]
@@ -24,6 +27,8 @@ use notify_debouncer_full::{new_debouncer, DebouncedEvent, Debouncer};
 use one_or_many::OneOrMany;
 use surrealdb::{engine::local::Db, Surreal};
 
+use crate::config::{library_path_of, LibraryPathConfig};
+
 #[cfg(target_os = "linux")]
 type WatcherType = INotifyWatcher;
 #[cfg(target_os = "macos")]
@@ -43,7 +48,7 @@ pub const MAX_DEBOUNCE_TIME: Duration = Duration::from_millis(500);
 ///
 /// # Arguments
 ///
-/// * `library_paths` - The root paths of the music library.
+/// * `library_paths` - The root paths of the music library, and their separator overrides.
 /// * `db` - The database connection used to update the library.
 ///
 /// # Returns
@@ -56,10 +61,11 @@ pub const MAX_DEBOUNCE_TIME: Duration = Duration::from_millis(500);
 /// If the watcher could not be started, an error is returned.
 pub fn init_music_library_watcher(
     db: Arc<Surreal<Db>>,
-    library_paths: &[PathBuf],
+    library_paths: &[LibraryPathConfig],
     artist_name_separator: OneOrMany<String>,
     genre_separator: Option<String>,
 ) -> anyhow::Result<MusicLibEventHandlerGuard> {
+    let handler_library_paths: Box<[LibraryPathConfig]> = library_paths.into();
     let (tx, rx) = futures::channel::mpsc::unbounded();
     // create a oneshot that can be used to stop the watcher
     let (stop_tx, stop_rx) = futures::channel::oneshot::channel();
@@ -68,7 +74,12 @@ pub fn init_music_library_watcher(
     std::thread::Builder::new()
         .name(String::from("Music Library Watcher"))
         .spawn(move || {
-            let handler = MusicLibEventHandler::new(db, artist_name_separator, genre_separator);
+            let handler = MusicLibEventHandler::new(
+                db,
+                handler_library_paths,
+                artist_name_separator,
+                genre_separator,
+            );
             futures::executor::block_on(async {
                 let mut stop_rx = stop_rx.fuse();
                 let mut rx = rx.fuse();
@@ -108,11 +119,18 @@ pub fn init_music_library_watcher(
         })?;
 
     // Add all library paths to the debouncer.
-    for path in library_paths {
-        log::debug!("watching path: {:?}", path);
+    for library_path in library_paths {
+        if !library_path.path.is_dir() {
+            log::warn!(
+                "not watching path: {:?}, it isn't a directory (or doesn't exist)",
+                library_path.path
+            );
+            continue;
+        }
+        log::debug!("watching path: {:?}", library_path.path);
         // Add a path to be watched. All files and directories at that path and
         // below will be monitored for changes.
-        debouncer.watch(path, RecursiveMode::Recursive)?;
+        debouncer.watch(&library_path.path, RecursiveMode::Recursive)?;
     }
 
     Ok(MusicLibEventHandlerGuard { debouncer, stop_tx })
@@ -134,6 +152,7 @@ impl MusicLibEventHandlerGuard {
 /// Handles incoming file Events.
 struct MusicLibEventHandler {
     db: Arc<Surreal<Db>>,
+    library_paths: Box<[LibraryPathConfig]>,
     artist_name_separator: OneOrMany<String>,
     genre_separator: Option<String>,
 }
@@ -142,16 +161,32 @@ impl MusicLibEventHandler {
     /// Creates a new `MusicLibEventHandler`.
     pub const fn new(
         db: Arc<Surreal<Db>>,
+        library_paths: Box<[LibraryPathConfig]>,
         artist_name_separator: OneOrMany<String>,
         genre_separator: Option<String>,
     ) -> Self {
         Self {
             db,
+            library_paths,
             artist_name_separator,
             genre_separator,
         }
     }
 
+    /// The artist and genre separators for the song at `path`, taking the overrides of its library path into account.
+    fn separators(&self, path: &Path) -> (&OneOrMany<String>, Option<&str>) {
+        let genre_separator = self.genre_separator.as_deref();
+        library_path_of(&self.library_paths, path).map_or(
+            (&self.artist_name_separator, genre_separator),
+            |library_path| {
+                (
+                    library_path.artist_separator(&self.artist_name_separator),
+                    library_path.genre_separator(genre_separator),
+                )
+            },
+        )
+    }
+
     /// Handles incoming file Events.
     async fn handle_event(&self, event: DebouncedEvent) -> anyhow::Result<()> {
         trace!("file event detected: {event:?}");
@@ -233,10 +268,11 @@ impl MusicLibEventHandler {
                         Some(Some(ext)) if VALID_AUDIO_EXTENSIONS.contains(&ext) => {
                             info!("file created: {:?}. adding to db", event.paths);
 
+                            let (artist_name_separator, genre_separator) = self.separators(path);
                             let metadata = SongMetadata::load_from_path(
                                 path.to_owned(),
-                                &self.artist_name_separator,
-                                self.genre_separator.as_deref(),
+                                artist_name_separator,
+                                genre_separator,
                             )?;
 
                             Song::try_load_into_db(&self.db, metadata).await?;
@@ -279,15 +315,17 @@ impl MusicLibEventHandler {
                         // NOTE: if this fails, the song may just not've been added previously, may want to handle that in the future
                         let song = Song::read_by_path(&self.db, path.clone()).await?.ok_or(mecomp_storage::errors::Error::NotFound)?;
 
+                        let (artist_name_separator, genre_separator) = self.separators(path);
                         let new_metadata: SongMetadata = SongMetadata::load_from_path(
                             path.to_owned(),
-                            &self.artist_name_separator,
-                            self.genre_separator.as_deref(),
+                            artist_name_separator,
+                            genre_separator,
                         )?;
 
                         let changeset = new_metadata.merge_with_song(&song);
 
-                        Song::update(&self.db, song.id, changeset).await?;
+                        Song::update(&self.db, song.id.clone(), changeset).await?;
+                        Lyrics::set_for_song(&self.db, song.id, new_metadata.lyrics).await?;
                     }
                     _ => {
                         debug!("file data modified ({kind:?}): {:?}.  not a song, no action needed", event.paths);
@@ -378,6 +416,8 @@ mod tests {
 
     use super::*;
 
+    use std::path::PathBuf;
+
     use lofty::file::AudioFile;
     use pretty_assertions::assert_eq;
     use rstest::{fixture, rstest};
@@ -394,7 +434,7 @@ mod tests {
         let db = Arc::new(init_test_database().await.unwrap());
         let handler = init_music_library_watcher(
             db.clone(),
-            &[music_lib.path().to_owned()],
+            &[music_lib.path().to_owned().into()],
             OneOrMany::One(ARTIST_NAME_SEPARATOR.into()),
             Some(ARTIST_NAME_SEPARATOR.into()),
         )
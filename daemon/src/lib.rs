@@ -2,39 +2,44 @@
 use std::{
     net::{IpAddr, Ipv4Addr},
     sync::Arc,
+    time::Duration,
 };
 //--------------------------------------------------------------------------------- other libraries
 use futures::{future, prelude::*};
-use log::info;
+use log::{info, warn};
 use surrealdb::{engine::local::Db, Surreal};
 use tarpc::{
     self,
-    server::{incoming::Incoming as _, BaseChannel, Channel as _},
+    server::{incoming::Incoming as _, BaseChannel, Channel as _, InFlightRequest},
     tokio_serde::formats::Json,
+    RequestName as _,
 };
 //-------------------------------------------------------------------------------- MECOMP libraries
 use mecomp_core::{
-    audio::AudioKernelSender,
+    audio::{
+        commands::{AudioCommand, QueueCommand},
+        AudioKernelSender,
+    },
     is_server_running,
     logger::{init_logger, init_tracing},
-    rpc::{MusicPlayer as _, MusicPlayerClient},
+    rpc::{MusicPlayer as _, MusicPlayerClient, MusicPlayerRequest, MusicPlayerResponse},
 };
 use mecomp_storage::db::{init_database, set_database_path};
 
-async fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
-    tokio::spawn(fut);
-}
-
 pub mod config;
 pub mod controller;
 #[cfg(feature = "dynamic_updates")]
 pub mod dynamic_updates;
+pub mod pid_file;
 pub mod services;
+pub mod shutdown;
 #[cfg(test)]
 pub mod test_utils;
 
 use crate::config::Settings;
 use crate::controller::MusicPlayerServer;
+use crate::pid_file::PidFile;
+use crate::shutdown::InFlightRequests;
 
 // TODO: at some point, we should probably add a panic handler to the daemon to ensure graceful shutdown.
 
@@ -48,6 +53,8 @@ use crate::controller::MusicPlayerServer;
 /// * `db_dir` - The directory where the database is stored.
 ///              If the directory does not exist, it will be created.
 /// * `log_file_path` - The path to the file where logs will be written.
+/// * `pid_file_path` - The path to the PID file, used to refuse to start if the daemon is already running.
+///                     If `None`, no PID file is written or checked.
 ///
 /// # Errors
 ///
@@ -60,10 +67,14 @@ pub async fn start_daemon(
     settings: Settings,
     db_dir: std::path::PathBuf,
     log_file_path: Option<std::path::PathBuf>,
+    pid_file_path: Option<std::path::PathBuf>,
 ) -> anyhow::Result<()> {
     // Throw the given settings into an Arc so we can share settings across threads.
     let settings = Arc::new(settings);
 
+    // check if the daemon is already running, the PID file is removed when this function returns.
+    let _pid_file = pid_file_path.map(PidFile::create).transpose()?;
+
     // check if a server is already running
     if is_server_running(settings.daemon.rpc_port) {
         anyhow::bail!(
@@ -72,8 +83,23 @@ pub async fn start_daemon(
         );
     }
 
-    // Initialize the logger, database, and tracing.
+    // Initialize the logger.
     init_logger(settings.daemon.log_level, log_file_path);
+
+    // Make sure the settings are usable before starting anything else,
+    // this is done after initializing the logger so that warnings about the settings are logged.
+    if let Err(errors) = settings.validate() {
+        anyhow::bail!(
+            "Invalid settings:\n{}",
+            errors
+                .iter()
+                .map(|error| format!("  - {error}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    // Initialize the database, and tracing.
     set_database_path(db_dir)?;
     let db = Arc::new(init_database().await?);
     tracing::subscriber::set_global_default(init_tracing())?;
@@ -87,8 +113,25 @@ pub async fn start_daemon(
         settings.daemon.genre_separator.clone(),
     )?;
 
-    // Start the audio kernel.
-    let audio_kernel = AudioKernelSender::start();
+    // Start the audio kernel, and record the songs it finishes playing.
+    let (events_tx, events_rx) = tokio::sync::mpsc::unbounded_channel();
+    let audio_kernel = AudioKernelSender::start_with_events(events_tx);
+    let db_clone = db.clone();
+    tokio::spawn(async move { services::handle_state_changes(&db_clone, events_rx).await });
+    audio_kernel.send(AudioCommand::SetGain(settings.daemon.default_gain_db));
+    audio_kernel.send(AudioCommand::Queue(QueueCommand::SetHistoryDepth(
+        settings.daemon.queue_history_depth,
+    )));
+    if let Some(output_device) = settings.daemon.output_device.clone() {
+        audio_kernel.send(AudioCommand::SetOutputDevice(output_device));
+    }
+    match settings.equalizer.preset(&settings.equalizer.preset) {
+        Some(bands) => audio_kernel.send(AudioCommand::SetEqualizer(bands)),
+        None => warn!(
+            "Unknown equalizer preset: {}, using a flat equalizer",
+            settings.equalizer.preset
+        ),
+    }
 
     // Start the RPC server.
     let server_addr = (IpAddr::V4(Ipv4Addr::LOCALHOST), settings.daemon.rpc_port);
@@ -96,7 +139,8 @@ pub async fn start_daemon(
     let mut listener = tarpc::serde_transport::tcp::listen(&server_addr, Json::default).await?;
     info!("Listening on {}", listener.local_addr());
     listener.config_mut().max_frame_length(usize::MAX);
-    listener
+    let in_flight = InFlightRequests::new();
+    let server = listener
         // Ignore accept errors.
         .filter_map(|r| future::ready(r.ok()))
         .map(BaseChannel::with_defaults)
@@ -106,16 +150,49 @@ pub async fn start_daemon(
         // serve is generated by the service attribute.
         // It takes as input any type implementing the generated MusicPlayer trait.
         .map(|channel| {
-            let server = MusicPlayerServer::new(db.clone(), settings.clone(), audio_kernel.clone());
-            channel.execute(server.serve()).for_each(spawn)
+            let serve =
+                MusicPlayerServer::new(db.clone(), settings.clone(), audio_kernel.clone()).serve();
+            let in_flight = in_flight.clone();
+            // like `Channel::execute`, but keeping track of the requests being handled
+            channel
+                .requests()
+                .take_while(|request| {
+                    if let Err(e) = request {
+                        warn!("Requests stream errored out: {e}");
+                    }
+                    future::ready(request.is_ok())
+                })
+                .filter_map(|request| future::ready(request.ok()))
+                .for_each(
+                    move |request: InFlightRequest<MusicPlayerRequest, MusicPlayerResponse>| {
+                        let name = request.get().message.name().to_owned();
+                        in_flight.spawn(name, request.execute(serve.clone()));
+                        future::ready(())
+                    },
+                )
         })
         // Max 10 channels.
         // this means that we will only process 10 requests at a time
         // NOTE: if we have issues with concurrency (e.g. deadlocks or data-races),
         //       and have too much of a skill issue to fix it, we can set this number to 1.
         .buffer_unordered(10)
-        .for_each(|()| async {})
-        .await;
+        .for_each(|()| async {});
+
+    // Run the server until we're interrupted or terminated, then give the requests being handled a chance to finish.
+    tokio::select! {
+        () = server => {}
+        () = shutdown_signal() => {
+            info!("Shutting down");
+            let timeout = Duration::from_secs(settings.daemon.shutdown_timeout_secs);
+            if !in_flight.drain(timeout).await {
+                warn!(
+                    "Shutdown timeout ({}s) expired with requests still in flight: {}",
+                    timeout.as_secs(),
+                    in_flight.names().join(", ")
+                );
+            }
+        }
+    }
 
     #[cfg(feature = "dynamic_updates")]
     guard.stop();
@@ -123,6 +200,37 @@ pub async fn start_daemon(
     Ok(())
 }
 
+/// Wait for a signal asking the daemon to shut down.
+///
+/// That's an interrupt (Ctrl+C / `SIGINT`), or on unix, a `SIGTERM` (what `kill` and systemd send).
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for the interrupt signal: {e}");
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for the terminate signal: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => {}
+        () = terminate => {}
+    }
+}
+
 /// Initialize a test client, sends and receives messages over a channel / pipe.
 /// This is useful for testing the server without needing to start it.
 #[must_use]
@@ -156,9 +264,24 @@ mod test_client_tests {
 
     use super::*;
     use anyhow::Result;
-    use mecomp_core::state::library::LibraryFull;
+    use mecomp_core::{
+        rpc::SearchRequest,
+        state::{
+            equalizer::{self, EqBand},
+            library::{LibraryFull, SongVerificationResult, VerificationStatus},
+            AudioLevels, ReplayGainMode, SortStrategy,
+        },
+    };
     use mecomp_storage::{
-        db::schemas::{collection::Collection, playlist::Playlist, song::SongChangeSet},
+        db::schemas::{
+            artist::Artist,
+            collection::Collection,
+            failed_analysis::FailedAnalysis,
+            lyrics::Lyrics,
+            playlist::Playlist,
+            scan_history::ScanMetrics,
+            song::{PlayStats, Song, SongBrief, SongChangeSet},
+        },
         test_utils::{create_song_with_overrides, init_test_database, SongCase},
     };
 
@@ -301,201 +424,445 @@ mod test_client_tests {
 
     #[rstest]
     #[tokio::test]
-    async fn test_library_album_get_artist(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_song_get_play_stats(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
 
+        // the song hasn't been played yet
         let ctx = tarpc::context::current();
         let response = client
-            .library_album_get_artist(ctx, library_full.albums.first().unwrap().id.clone().into())
+            .library_song_get_play_stats(ctx, library_full.songs.first().unwrap().id.clone().into())
             .await?;
+        assert_eq!(response, Some(PlayStats::default()));
 
-        assert_eq!(response, library_full.artists.into_vec().into());
+        // songs that don't exist have no stats
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_song_get_play_stats(ctx, Song::generate_id().into())
+            .await?;
+        assert_eq!(response, None);
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_library_album_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_song_get_lyrics(#[future] db: Arc<Surreal<Db>>) -> Result<()> {
+        let db = db.await;
+
+        let client = init_test_client_server(
+            db.clone(),
+            Arc::new(Settings::default()),
+            AudioKernelSender::start(),
+        );
+
+        let song = Song::read_all(&db).await?.first().unwrap().clone();
+
+        // the song doesn't have any lyrics yet
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_song_get_lyrics(ctx, song.id.clone().into())
+            .await?;
+        assert_eq!(response, None);
+
+        Lyrics::upsert_for_song(&db, song.id.clone(), "la la la".into()).await?;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_song_get_lyrics(ctx, song.id.into()).await?;
+        assert_eq!(response, Some("la la la".into()));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_song_set_rating(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
+        let id = library_full.songs.first().unwrap().id.clone();
+
+        let ctx = tarpc::context::current();
+        client
+            .library_song_set_rating(ctx, id.clone().into(), Some(4))
+            .await??;
+        let ctx = tarpc::context::current();
+        let song = client.library_song_get(ctx, id.clone().into()).await?;
+        assert_eq!(song.unwrap().rating, Some(4));
 
+        // ratings outside of 1-5 stars are rejected
         let ctx = tarpc::context::current();
         let response = client
-            .library_album_get_songs(ctx, library_full.albums.first().unwrap().id.clone().into())
-            .await?
-            .unwrap();
+            .library_song_set_rating(ctx, id.clone().into(), Some(6))
+            .await?;
+        assert!(response.is_err());
 
-        assert_eq!(response, library_full.songs);
+        // clearing the rating
+        let ctx = tarpc::context::current();
+        client
+            .library_song_set_rating(ctx, id.clone().into(), None)
+            .await??;
+        let ctx = tarpc::context::current();
+        let song = client.library_song_get(ctx, id.into()).await?;
+        assert_eq!(song.unwrap().rating, None);
+
+        // songs that don't exist can't be rated
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_song_set_rating(ctx, Song::generate_id().into(), Some(4))
+            .await?;
+        assert!(response.is_err());
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_library_artist_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_song_toggle_favorite(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
+        let id = library_full.songs.first().unwrap().id.clone();
 
         let ctx = tarpc::context::current();
-        let response = client
-            .library_artist_get_songs(ctx, library_full.artists.first().unwrap().id.clone().into())
-            .await?
-            .unwrap();
+        let favorite = client
+            .library_song_toggle_favorite(ctx, id.clone().into())
+            .await??;
+        assert!(favorite);
+        let ctx = tarpc::context::current();
+        let song = client.library_song_get(ctx, id.clone().into()).await?;
+        assert!(song.unwrap().favorite);
 
-        assert_eq!(response, library_full.songs);
+        let ctx = tarpc::context::current();
+        let favorite = client
+            .library_song_toggle_favorite(ctx, id.into())
+            .await??;
+        assert!(!favorite);
+
+        // songs that don't exist can't be favorited
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_song_toggle_favorite(ctx, Song::generate_id().into())
+            .await?;
+        assert!(response.is_err());
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_library_artist_get_albums(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_song_bookmarks(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
+        let id = library_full.songs.first().unwrap().id.clone();
+
+        let ctx = tarpc::context::current();
+        let bookmarks = client
+            .library_song_get_bookmarks(ctx, id.clone().into())
+            .await?;
+        assert_eq!(bookmarks, Some(vec![].into_boxed_slice()));
+
+        let ctx = tarpc::context::current();
+        client
+            .library_song_add_bookmark(ctx, id.clone().into(), std::time::Duration::from_secs(60))
+            .await??;
+        let ctx = tarpc::context::current();
+        let bookmarks = client
+            .library_song_add_bookmark(ctx, id.clone().into(), std::time::Duration::from_secs(15))
+            .await??;
+        assert_eq!(
+            bookmarks.as_ref(),
+            &[
+                std::time::Duration::from_secs(15),
+                std::time::Duration::from_secs(60)
+            ]
+        );
+        let ctx = tarpc::context::current();
+        let song = client.library_song_get(ctx, id.clone().into()).await?;
+        assert_eq!(song.unwrap().bookmark_positions, bookmarks.to_vec());
+
+        let ctx = tarpc::context::current();
+        let bookmarks = client
+            .library_song_remove_bookmark(
+                ctx,
+                id.clone().into(),
+                std::time::Duration::from_secs(60),
+            )
+            .await??;
+        assert_eq!(bookmarks.as_ref(), &[std::time::Duration::from_secs(15)]);
 
+        // songs that don't exist can't be bookmarked
         let ctx = tarpc::context::current();
         let response = client
-            .library_artist_get_albums(ctx, library_full.artists.first().unwrap().id.clone().into())
-            .await?
-            .unwrap();
+            .library_song_add_bookmark(
+                ctx,
+                Song::generate_id().into(),
+                std::time::Duration::from_secs(1),
+            )
+            .await?;
+        assert!(response.is_err());
+        let ctx = tarpc::context::current();
+        let bookmarks = client
+            .library_song_get_bookmarks(ctx, Song::generate_id().into())
+            .await?;
+        assert_eq!(bookmarks, None);
 
-        assert_eq!(response, library_full.albums);
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_recluster_zero_clusters(
+        #[future] client: MusicPlayerClient,
+    ) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_recluster(
+                ctx,
+                mecomp_core::rpc::ReclusterRequest {
+                    n_clusters: Some(0),
+                },
+            )
+            .await?;
+        assert_eq!(
+            response,
+            Err(mecomp_core::errors::SerializableLibraryError::InvalidClusterCount)
+        );
+
+        // the existing collections are left alone
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        assert_eq!(library_full.collections.len(), 1);
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_playback_volume_toggle_mute(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_rescan_progress(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
+        // there's no rescan in progress
         let ctx = tarpc::context::current();
+        assert_eq!(client.library_rescan_progress(ctx).await?, None);
+
+        // nor a ReplayGain computation
+        let ctx = tarpc::context::current();
+        assert_eq!(client.library_compute_replaygain_progress(ctx).await?, None);
 
-        client.playback_volume_toggle_mute(ctx).await?;
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_queue_add_list(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_duplicates(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs[0].clone();
 
+        // the only song in the library has no duplicates
         let ctx = tarpc::context::current();
-        let response = client
-            .queue_add_list(
+        assert_eq!(client.library_find_duplicates(ctx).await??, vec![]);
+        let ctx = tarpc::context::current();
+        assert_eq!(client.library_find_acoustid_duplicates(ctx).await??, vec![]);
+
+        // songs that don't exist are ignored
+        let ctx = tarpc::context::current();
+        let merged = client
+            .library_remove_duplicate(
                 ctx,
-                vec![library_full.songs.first().unwrap().id.clone().into()],
+                song.id.clone().into(),
+                vec![Song::generate_id().into()],
             )
+            .await??;
+        assert_eq!(merged, song);
+
+        // songs that don't exist can't be kept
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_remove_duplicate(ctx, Song::generate_id().into(), vec![])
             .await?;
+        assert!(response.is_err());
 
-        assert_eq!(response, Ok(()));
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_dead_links(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs[0].clone();
+
+        // the song in the test database doesn't have a real file
+        let ctx = tarpc::context::current();
+        assert_eq!(
+            client.library_scan_dead_links(ctx).await??,
+            vec![SongBrief::from(&song)]
+        );
+
+        let ctx = tarpc::context::current();
+        assert_eq!(client.library_remove_dead_links(ctx).await??, 1);
+        let ctx = tarpc::context::current();
+        assert_eq!(client.library_scan_dead_links(ctx).await??, vec![]);
+        let ctx = tarpc::context::current();
+        assert_eq!(client.library_song_get(ctx, song.id.into()).await?, None);
 
         Ok(())
     }
 
     #[rstest]
-    #[case::get(String::from("Playlist 0"))]
-    #[case::create(String::from("Playlist 1"))]
     #[tokio::test]
-    async fn test_playlist_get_or_create(
-        #[future] client: MusicPlayerClient,
-        #[case] name: String,
-    ) -> Result<()> {
+    async fn test_library_statistics(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs[0].clone();
 
-        // get or create the playlist
-        let playlist_id = client
-            .playlist_get_or_create(ctx, name.clone())
-            .await?
-            .unwrap();
+        let ctx = tarpc::context::current();
+        let statistics = client.library_statistics(ctx).await??;
+        assert_eq!(statistics.total_songs, 1);
+        assert_eq!(statistics.total_albums, library_full.albums.len());
+        assert_eq!(statistics.total_artists, library_full.artists.len());
+        assert_eq!(statistics.total_runtime, song.runtime);
+        assert_eq!(
+            statistics.songs_with_analysis + statistics.songs_without_analysis,
+            1
+        );
+        assert_eq!(
+            statistics.unique_genres,
+            song.genre
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len()
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_export_import_json(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+        let tempdir = tempfile::tempdir()?;
+        let path = tempdir.path().join("backup.json");
 
-        // now get that playlist
         let ctx = tarpc::context::current();
-        let playlist = client.playlist_get(ctx, playlist_id).await?.unwrap();
+        client.library_export_json(ctx, path.clone()).await??;
+        assert!(path.exists());
 
-        assert_eq!(playlist.name, name.into());
+        // the song in the backup is already in the library, so nothing is added
+        let ctx = tarpc::context::current();
+        let stats = client.library_import_json(ctx, path).await??;
+        assert_eq!(stats.songs_added, 0);
+        assert_eq!(stats.songs_skipped, 1);
+        assert!(stats.errors.is_empty());
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        assert_eq!(library_full.songs.len(), 1);
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_playlist_clone(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_batch_update_songs(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs[0].clone();
 
-        // clone the only playlist in the db
+        // songs that don't exist are skipped
         let ctx = tarpc::context::current();
-        let playlist_id = client
-            .playlist_clone(
+        let updated = client
+            .library_batch_update_songs(
                 ctx,
-                library_full.playlists.first().unwrap().id.clone().into(),
+                mecomp_core::rpc::BatchSongUpdateRequest {
+                    ids: vec![song.id.clone().into(), Song::generate_id().into()],
+                    changeset: SongChangeSet {
+                        genre: Some(one_or_many::OneOrMany::One("Batch Genre".into())),
+                        track: Some(None),
+                        ..Default::default()
+                    },
+                },
             )
+            .await??;
+        assert_eq!(updated, 1);
+
+        let ctx = tarpc::context::current();
+        let updated_song = client
+            .library_song_get(ctx, song.id.clone().into())
             .await?
             .unwrap();
+        assert_eq!(
+            updated_song.genre,
+            one_or_many::OneOrMany::One("Batch Genre".into())
+        );
+        assert_eq!(updated_song.track, None);
 
-        // now get that playlist
+        // invalid ratings are rejected
         let ctx = tarpc::context::current();
-        let playlist = client.playlist_get(ctx, playlist_id).await?.unwrap();
-
-        assert_eq!(playlist.name, "Playlist 0 (copy)".into());
+        let response = client
+            .library_batch_update_songs(
+                ctx,
+                mecomp_core::rpc::BatchSongUpdateRequest {
+                    ids: vec![song.id.into()],
+                    changeset: SongChangeSet {
+                        rating: Some(Some(u8::MAX)),
+                        ..Default::default()
+                    },
+                },
+            )
+            .await?;
+        assert!(response.is_err());
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_playlist_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_album_get_artist(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
 
-        // clone the only playlist in the db
+        let ctx = tarpc::context::current();
         let response = client
-            .playlist_get_songs(
-                ctx,
-                library_full.playlists.first().unwrap().id.clone().into(),
-            )
-            .await?
-            .unwrap();
+            .library_album_get_artist(ctx, library_full.albums.first().unwrap().id.clone().into())
+            .await?;
 
-        assert_eq!(response, library_full.songs);
+        assert_eq!(response, library_full.artists.into_vec().into());
 
         Ok(())
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_collection_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+    async fn test_library_album_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
         let client = client.await;
 
         let ctx = tarpc::context::current();
         let library_full: LibraryFull = client.library_full(ctx).await??;
 
-        // clone the only playlist in the db
+        let ctx = tarpc::context::current();
         let response = client
-            .collection_get_songs(
-                ctx,
-                library_full.collections.first().unwrap().id.clone().into(),
-            )
+            .library_album_get_songs(ctx, library_full.albums.first().unwrap().id.clone().into())
             .await?
             .unwrap();
 
@@ -503,4 +870,876 @@ mod test_client_tests {
 
         Ok(())
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_list_failed_analysis(#[future] db: Arc<Surreal<Db>>) -> Result<()> {
+        let db = db.await;
+
+        let client = init_test_client_server(
+            db.clone(),
+            Arc::new(Settings::default()),
+            AudioKernelSender::start(),
+        );
+
+        let ctx = tarpc::context::current();
+        let response = client.library_list_failed_analysis(ctx).await??;
+        assert!(response.is_empty());
+
+        let song = Song::read_all(&db).await?.first().unwrap().clone();
+        let failed = FailedAnalysis::record_failure(&db, song.id, "error".into()).await?;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_list_failed_analysis(ctx).await??;
+        assert_eq!(response, vec![failed].into_boxed_slice());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_scan_history(#[future] db: Arc<Surreal<Db>>) -> Result<()> {
+        let db = db.await;
+
+        let client = init_test_client_server(
+            db.clone(),
+            Arc::new(Settings::default()),
+            AudioKernelSender::start(),
+        );
+
+        let ctx = tarpc::context::current();
+        let response = client.library_scan_history(ctx).await??;
+        assert!(response.is_empty());
+
+        let metrics =
+            ScanMetrics::new("/music".into(), 10, 9, 1, std::time::Duration::from_secs(1));
+        let recorded = ScanMetrics::record(&db, metrics).await?;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_scan_history(ctx).await??;
+        assert_eq!(response, vec![recorded].into_boxed_slice());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_verify(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_verify(ctx, None).await??;
+
+        // the songs in the test database don't have real files
+        assert_eq!(
+            response,
+            library_full
+                .songs
+                .iter()
+                .map(|song| SongVerificationResult {
+                    id: song.id.clone(),
+                    status: VerificationStatus::Missing,
+                })
+                .collect()
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_list_genres(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_list_genres(ctx).await??;
+
+        assert_eq!(response, vec!["Genre 0".to_string()]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_songs_by_genre(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_songs_by_genre(ctx, "Genre 0".to_string(), 10)
+            .await??;
+        assert_eq!(response, library_full.songs);
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_songs_by_genre(ctx, "Genre 1".to_string(), 10)
+            .await??;
+        assert!(response.is_empty());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_year_range(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        // the test song doesn't have a release year, so it's never in range
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_year_range(ctx, i32::MIN, i32::MAX, 10)
+            .await??;
+        assert!(response.is_empty());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_bpm_range(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        // the test song hasn't been analyzed, so it's never in range
+        let ctx = tarpc::context::current();
+        let response = client.library_bpm_range(ctx, 0., 300., 10).await??;
+        assert!(response.is_empty());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_random_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_random_songs(ctx, 10, None).await??;
+        assert_eq!(response, library_full.songs);
+
+        let ctx = tarpc::context::current();
+        let response = client.library_random_songs(ctx, 10, Some(42)).await??;
+        assert_eq!(response, library_full.songs);
+
+        let ctx = tarpc::context::current();
+        let response = client.library_random_songs(ctx, 0, None).await??;
+        assert!(response.is_empty());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_search(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        // the song and album match on their artist, the artist on its name
+        let ctx = tarpc::context::current();
+        let request = SearchRequest {
+            query: "artist 0".into(),
+            limit: 10,
+        };
+        let response = client.library_search(ctx, request).await?;
+        assert_eq!(response.songs, library_full.songs);
+        assert_eq!(response.albums, library_full.albums);
+        assert_eq!(response.artists, library_full.artists);
+
+        let ctx = tarpc::context::current();
+        let request = SearchRequest {
+            query: "nothing like this".into(),
+            limit: 10,
+        };
+        let response = client.library_search(ctx, request).await?;
+        assert!(response.is_empty());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_playback_history(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_playback_history(ctx).await?;
+        assert!(response.is_empty());
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs.first().unwrap().clone();
+
+        // play the song, then skip past it
+        let ctx = tarpc::context::current();
+        client
+            .queue_add_list(ctx, vec![song.id.clone().into()])
+            .await??;
+        let ctx = tarpc::context::current();
+        client.playback_skip_forward(ctx, 1).await?;
+
+        let ctx = tarpc::context::current();
+        let response = client.library_playback_history(ctx).await?;
+        assert_eq!(response, vec![SongBrief::from(song)].into_boxed_slice());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_artist_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_artist_get_songs(ctx, library_full.artists.first().unwrap().id.clone().into())
+            .await?
+            .unwrap();
+
+        assert_eq!(response, library_full.songs);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_artist_get_albums(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .library_artist_get_albums(ctx, library_full.artists.first().unwrap().id.clone().into())
+            .await?
+            .unwrap();
+
+        assert_eq!(response, library_full.albums);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_library_artist_merge(#[future] db: Arc<Surreal<Db>>) -> Result<()> {
+        let db = db.await;
+
+        // a split artist
+        let song = create_song_with_overrides(
+            &db,
+            SongCase::new(1, vec![1], vec![1], 1, 0),
+            SongChangeSet {
+                artist: Some(one_or_many::OneOrMany::One("Artist0".into())),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let client = init_test_client_server(
+            db.clone(),
+            Arc::new(Settings::default()),
+            AudioKernelSender::start(),
+        );
+
+        let source = Artist::read_by_name(&db, "Artist0").await?.unwrap();
+        let target = Artist::read_by_name(&db, "Artist 0").await?.unwrap();
+
+        let ctx = tarpc::context::current();
+        let merged = client
+            .library_artist_merge(ctx, source.id.clone().into(), target.id.clone().into())
+            .await??;
+        assert_eq!(merged.id, target.id);
+        assert_eq!(merged.song_count, 2);
+
+        let song = Song::read(&db, song.id).await?.unwrap();
+        assert_eq!(song.artist, one_or_many::OneOrMany::One("Artist 0".into()));
+
+        // merging an artist that no longer exists is an error
+        let ctx = tarpc::context::current();
+        let result = client
+            .library_artist_merge(ctx, source.id.into(), target.id.into())
+            .await?;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_volume_toggle_mute(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+
+        client.playback_volume_toggle_mute(ctx).await?;
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_fade_to_volume(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        client
+            .playback_fade_to_volume(ctx, 0.0, std::time::Duration::from_secs(30))
+            .await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert!(state.fade_in_progress);
+
+        let ctx = tarpc::context::current();
+        client.playback_cancel_fade(ctx).await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert!(!state.fade_in_progress);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_crossfade(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        client
+            .playback_crossfade(ctx, std::time::Duration::from_secs(5))
+            .await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.crossfade_duration, std::time::Duration::from_secs(5));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_replaygain(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        client
+            .playback_replaygain(ctx, ReplayGainMode::Album)
+            .await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.replaygain_mode, ReplayGainMode::Album);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_set_speed(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        client.playback_set_speed(ctx, 1.5).await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert!((state.playback_speed - 1.5).abs() < f32::EPSILON);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_seek_percent(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        // seeking with an empty queue does nothing, and keeps the audio kernel running
+        let ctx = tarpc::context::current();
+        client.playback_seek_percent(ctx, 50.0).await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert!(state.current_song.is_none());
+        assert!(state.runtime.is_none());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_levels(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        // nothing is playing, so the levels are silence
+        let ctx = tarpc::context::current();
+        let levels = client.playback_levels(ctx).await?;
+        assert_eq!(levels, AudioLevels::default());
+        assert!(!levels.clip);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_output_devices(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        // there's no audio output when testing, so there are no devices
+        let ctx = tarpc::context::current();
+        let devices = client.playback_list_output_devices(ctx).await?;
+        assert!(devices.is_empty());
+
+        // switching to a device that doesn't exist keeps the audio kernel running
+        let ctx = tarpc::context::current();
+        client
+            .playback_set_output_device(ctx, "not a device".into())
+            .await?;
+        let ctx = tarpc::context::current();
+        assert!(client.state_audio(ctx).await?.is_some());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playback_equalizer(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.equalizer.as_ref(), equalizer::flat().as_slice());
+
+        let ctx = tarpc::context::current();
+        let bands = vec![EqBand::new(60.0, 6.0, 1.0)];
+        client.playback_set_equalizer(ctx, bands.clone()).await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.equalizer.as_ref(), bands.as_slice());
+
+        let ctx = tarpc::context::current();
+        let presets = client.playback_equalizer_presets(ctx).await?;
+        assert_eq!(presets, equalizer::BUILTIN_PRESETS);
+
+        let ctx = tarpc::context::current();
+        let bands = client
+            .playback_equalizer_preset(ctx, "bass_boost".into())
+            .await?;
+        assert_eq!(bands, equalizer::builtin_preset("bass_boost"));
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(Some(state.equalizer.to_vec()), bands);
+
+        let ctx = tarpc::context::current();
+        let bands = client
+            .playback_equalizer_preset(ctx, "not a preset".into())
+            .await?;
+        assert_eq!(bands, None);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_queue_add_list(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .queue_add_list(
+                ctx,
+                vec![library_full.songs.first().unwrap().id.clone().into()],
+            )
+            .await?;
+
+        assert_eq!(response, Ok(()));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_queue_insert_at(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs.first().unwrap().clone();
+
+        let ctx = tarpc::context::current();
+        let response = client
+            .queue_insert_at(ctx, 10, song.id.clone().into())
+            .await?;
+        assert_eq!(response, Ok(()));
+
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.queue.as_ref(), &[song]);
+
+        // songs that don't exist can't be inserted
+        let ctx = tarpc::context::current();
+        let response = client
+            .queue_insert_at(ctx, 0, Song::generate_id().into())
+            .await?;
+        assert!(response.is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_queue_move(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs.first().unwrap().clone();
+
+        // queue the song twice (separately, since the things in a single list are deduplicated),
+        // the first one is the current song
+        for _ in 0..2 {
+            let ctx = tarpc::context::current();
+            client.queue_add(ctx, song.id.clone().into()).await??;
+        }
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.queue.len(), 2);
+        assert_eq!(state.queue_position, Some(0));
+
+        // moving the current song moves the queue position with it
+        let ctx = tarpc::context::current();
+        client.queue_move(ctx, 0, 1).await?;
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.queue.len(), 2);
+        assert_eq!(state.queue_position, Some(1));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_queue_sort_by_analysis(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let song = library_full.songs.first().unwrap().clone();
+
+        let ctx = tarpc::context::current();
+        client
+            .queue_add_list(ctx, vec![song.id.clone().into()])
+            .await??;
+
+        // the song hasn't been analyzed, so sorting leaves the queue as it was
+        let ctx = tarpc::context::current();
+        let response = client
+            .queue_sort_by_analysis(ctx, SortStrategy::ByTempo)
+            .await?;
+        assert_eq!(response, Ok(()));
+
+        let ctx = tarpc::context::current();
+        let state = client.state_audio(ctx).await?.unwrap();
+        assert_eq!(state.queue.as_ref(), &[song]);
+        assert_eq!(state.queue_position, Some(0));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_queue_save_as_playlist(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        let ctx = tarpc::context::current();
+        client
+            .queue_add_list(ctx, vec![library_full.songs[0].id.clone().into()])
+            .await??;
+
+        // save the queue as a playlist
+        let ctx = tarpc::context::current();
+        let playlist_id = client
+            .queue_save_as_playlist(ctx, String::from("Saved Queue"))
+            .await??;
+
+        // now get that playlist
+        let ctx = tarpc::context::current();
+        let playlist = client
+            .playlist_get(ctx, playlist_id.clone())
+            .await?
+            .unwrap();
+        assert_eq!(playlist.name, "Saved Queue".into());
+
+        let ctx = tarpc::context::current();
+        let songs = client.playlist_get_songs(ctx, playlist_id).await?.unwrap();
+        assert_eq!(songs, library_full.songs);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::get(String::from("Playlist 0"))]
+    #[case::create(String::from("Playlist 1"))]
+    #[tokio::test]
+    async fn test_playlist_get_or_create(
+        #[future] client: MusicPlayerClient,
+        #[case] name: String,
+    ) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+
+        // get or create the playlist
+        let playlist_id = client
+            .playlist_get_or_create(ctx, name.clone())
+            .await?
+            .unwrap();
+
+        // now get that playlist
+        let ctx = tarpc::context::current();
+        let playlist = client.playlist_get(ctx, playlist_id).await?.unwrap();
+
+        assert_eq!(playlist.name, name.into());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playlist_clone(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        // clone the only playlist in the db
+        let ctx = tarpc::context::current();
+        let playlist_id = client
+            .playlist_clone(
+                ctx,
+                library_full.playlists.first().unwrap().id.clone().into(),
+            )
+            .await?
+            .unwrap();
+
+        // now get that playlist
+        let ctx = tarpc::context::current();
+        let playlist = client.playlist_get(ctx, playlist_id).await?.unwrap();
+
+        assert_eq!(playlist.name, "Playlist 0 (copy)".into());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playlist_merge(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let playlist_id: mecomp_core::rpc::PlaylistId =
+            library_full.playlists.first().unwrap().id.clone().into();
+
+        // merge the playlist with a copy of itself, the shared song is only added once
+        let ctx = tarpc::context::current();
+        let copy_id = client.playlist_clone(ctx, playlist_id.clone()).await??;
+        let ctx = tarpc::context::current();
+        let merged_id = client
+            .playlist_merge(
+                ctx,
+                mecomp_core::rpc::PlaylistMergeRequest {
+                    source_ids: vec![playlist_id.clone(), copy_id],
+                    name: "Merged".to_string(),
+                },
+            )
+            .await??;
+
+        let ctx = tarpc::context::current();
+        let merged = client.playlist_get(ctx, merged_id.clone()).await?.unwrap();
+        assert_eq!(merged.name, "Merged".into());
+        assert_eq!(merged.song_count, 1);
+        assert_eq!(merged.runtime, library_full.songs[0].runtime);
+
+        let ctx = tarpc::context::current();
+        let songs = client.playlist_get_songs(ctx, merged_id).await?.unwrap();
+        assert_eq!(songs, library_full.songs);
+
+        // merging a playlist that doesn't exist fails
+        let ctx = tarpc::context::current();
+        let result = client
+            .playlist_merge(
+                ctx,
+                mecomp_core::rpc::PlaylistMergeRequest {
+                    source_ids: vec![playlist_id, Playlist::generate_id().into()],
+                    name: "Merged Again".to_string(),
+                },
+            )
+            .await?;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playlist_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        // clone the only playlist in the db
+        let response = client
+            .playlist_get_songs(
+                ctx,
+                library_full.playlists.first().unwrap().id.clone().into(),
+            )
+            .await?
+            .unwrap();
+
+        assert_eq!(response, library_full.songs);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_playlist_export_import(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+        let tempdir = tempfile::tempdir()?;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let playlist_id: mecomp_core::rpc::PlaylistId =
+            library_full.playlists.first().unwrap().id.clone().into();
+
+        // the format is detected from the extension
+        let ctx = tarpc::context::current();
+        let result = client
+            .playlist_export(ctx, playlist_id.clone(), tempdir.path().join("Mix.txt"))
+            .await?;
+        assert!(matches!(
+            result,
+            Err(mecomp_core::errors::SerializableLibraryError::UnsupportedPlaylistFormat(_))
+        ));
+
+        let path = tempdir.path().join("Mix.xspf");
+        let ctx = tarpc::context::current();
+        client
+            .playlist_export(ctx, playlist_id, path.clone())
+            .await??;
+
+        let ctx = tarpc::context::current();
+        let report = client.playlist_import(ctx, path).await??;
+        // the song in the fixture has a relative path, which is resolved against the directory of the playlist,
+        // so it's read back but doesn't match anything in the library
+        assert_eq!(report.matched, 0);
+        assert_eq!(report.unmatched.len(), 1);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_collection_get_songs(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+
+        // clone the only playlist in the db
+        let response = client
+            .collection_get_songs(
+                ctx,
+                library_full.collections.first().unwrap().id.clone().into(),
+            )
+            .await?
+            .unwrap();
+
+        assert_eq!(response, library_full.songs);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_collection_rename(#[future] client: MusicPlayerClient) -> Result<()> {
+        let client = client.await;
+
+        let ctx = tarpc::context::current();
+        let library_full: LibraryFull = client.library_full(ctx).await??;
+        let collection = library_full.collections.first().unwrap().clone();
+
+        let ctx = tarpc::context::current();
+        let renamed = client
+            .collection_rename(ctx, collection.id.clone().into(), "Renamed".to_string())
+            .await??;
+        assert_eq!(renamed.id, collection.id);
+        assert_eq!(renamed.name, "Renamed".into());
+
+        // renaming a collection that doesn't exist is an error
+        let ctx = tarpc::context::current();
+        let result = client
+            .collection_rename(ctx, Collection::generate_id().into(), "Missing".to_string())
+            .await?;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_collection_merge(#[future] db: Arc<Surreal<Db>>) -> Result<()> {
+        let db = db.await;
+
+        let source = Collection::read_all(&db).await?.pop().unwrap();
+        let target = Collection::create(
+            &db,
+            Collection {
+                id: Collection::generate_id(),
+                name: "Collection 1".into(),
+                runtime: std::time::Duration::from_secs(0),
+                song_count: 0,
+            },
+        )
+        .await?
+        .unwrap();
+
+        let client = init_test_client_server(
+            db.clone(),
+            Arc::new(Settings::default()),
+            AudioKernelSender::start(),
+        );
+
+        let ctx = tarpc::context::current();
+        let merged = client
+            .collection_merge(ctx, source.id.clone().into(), target.id.clone().into())
+            .await??;
+        assert_eq!(merged.id, target.id);
+        assert_eq!(merged.song_count, source.song_count);
+        assert_eq!(merged.runtime, source.runtime);
+        assert_eq!(Collection::read(&db, source.id.clone()).await?, None);
+
+        // merging a collection that no longer exists is an error
+        let ctx = tarpc::context::current();
+        let result = client
+            .collection_merge(ctx, source.id.into(), target.id.into())
+            .await?;
+        assert!(result.is_err());
+
+        Ok(())
+    }
 }
@@ -27,6 +27,9 @@ struct Flags {
     /// log level
     #[clap(long)]
     log_level: Option<log::LevelFilter>,
+    /// don't write a PID file (`mecomp.pid` in the data directory), or check it for an already running daemon
+    #[clap(long)]
+    no_pidfile: bool,
 }
 
 #[tokio::main]
@@ -52,19 +55,27 @@ async fn main() -> anyhow::Result<()> {
         std::fs::write(&config_file, DEFAULT_CONFIG)?;
     }
 
-    let (db_dir, log_file) = match get_data_dir() {
+    let (db_dir, log_file, pid_file) = match get_data_dir() {
         Ok(data_dir) => {
             // if the data directory does not exist, create it
             if !data_dir.exists() {
                 std::fs::create_dir_all(&data_dir)?;
             }
-            (data_dir.join("db"), data_dir.join("mecomp.log"))
+            (
+                data_dir.join("db"),
+                data_dir.join("mecomp.log"),
+                data_dir.join("mecomp.pid"),
+            )
         }
         Err(e) => {
             eprintln!("Error: {e}");
             eprintln!("Using a temporary directory for the database");
             let data_dir = std::env::temp_dir();
-            (data_dir.join("mecomp_db"), data_dir.join("mecomp.log"))
+            (
+                data_dir.join("mecomp_db"),
+                data_dir.join("mecomp.log"),
+                data_dir.join("mecomp.pid"),
+            )
         }
     };
 
@@ -74,5 +85,11 @@ async fn main() -> anyhow::Result<()> {
         flags.log_level,
     )?;
 
-    start_daemon(settings, db_dir, Some(log_file)).await
+    start_daemon(
+        settings,
+        db_dir,
+        Some(log_file),
+        (!flags.no_pidfile).then_some(pid_file),
+    )
+    .await
 }
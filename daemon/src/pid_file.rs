@@ -0,0 +1,136 @@
+//! A PID file, used to keep the daemon from being started twice.
+
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PidFileError {
+    #[error("The daemon is already running (pid {pid}), if it isn't, remove {}", path.display())]
+    AlreadyRunning { pid: u32, path: PathBuf },
+    #[error("Failed to write the pid file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A PID file containing the PID of this process, removed when dropped.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the PID of this process to the PID file at `path`.
+    ///
+    /// A PID file left behind by a daemon that didn't shut down cleanly is overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PID file contains the PID of a process that is still running,
+    /// or if the file can't be written.
+    pub fn create(path: impl Into<PathBuf>) -> Result<Self, PidFileError> {
+        let path = path.into();
+
+        if let Some(pid) = read_pid(&path) {
+            if pid != std::process::id() && is_process_running(pid) {
+                return Err(PidFileError::AlreadyRunning { pid, path });
+            }
+            warn!("Overwriting stale pid file {}", path.display());
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, std::process::id().to_string())?;
+
+        Ok(Self { path })
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove pid file {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Read the PID from the PID file at `path`, if there is one and it contains a PID.
+fn read_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Check whether a process with the given PID is running.
+#[cfg(target_os = "linux")]
+fn is_process_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Check whether a process with the given PID is running.
+///
+/// There's no `/proc` to check on this platform, so the PID file is always treated as stale,
+/// `start_daemon` still refuses to start if something is listening on the RPC port.
+#[cfg(not(target_os = "linux"))]
+const fn is_process_running(_: u32) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_create_and_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mecomp.pid");
+
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(pid_file.path(), path);
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_overwrites_stale_pid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mecomp.pid");
+
+        // a pid that can't belong to a running process
+        std::fs::write(&path, u32::MAX.to_string()).unwrap();
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        drop(pid_file);
+
+        // as well as a pid file that doesn't contain a pid
+        std::fs::write(&path, "not a pid").unwrap();
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        drop(pid_file);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_already_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mecomp.pid");
+
+        // pid 1 (init) is always running
+        std::fs::write(&path, "1").unwrap();
+        let result = PidFile::create(&path);
+        assert!(matches!(
+            result,
+            Err(PidFileError::AlreadyRunning { pid: 1, .. })
+        ));
+
+        // the pid file of the running process is left alone
+        assert_eq!(read_pid(&path), Some(1));
+    }
+}
@@ -0,0 +1,429 @@
+//! Services for backing up the metadata of the library to, and restoring it from, a JSON file.
+//!
+//! The backup contains the songs, albums, artists, playlists, and collections in the library (but not the audio files),
+//! so it can be read (or edited) by hand, and merged into another library later.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use log::{debug, info, warn};
+use mecomp_core::{errors::SerializableLibraryError, rpc::ImportStats};
+use mecomp_storage::{
+    db::schemas::{
+        album::Album,
+        artist::Artist,
+        collection::Collection,
+        playlist::Playlist,
+        song::{Song, SongChangeSet, SongId, SongMetadata},
+    },
+    errors::Error,
+};
+use serde::{Deserialize, Serialize};
+use surrealdb::{Connection, Surreal};
+use tracing::instrument;
+
+/// The version of the backup format, bumped whenever the format changes in a way older versions can't read.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// A snapshot of the metadata of the library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryBackup {
+    /// The version of the format the backup was written with, see [`BACKUP_VERSION`]
+    pub version: u32,
+    pub songs: Vec<Song>,
+    /// The albums and artists are derived from the songs when importing,
+    /// they're included so the backup is a complete picture of the library.
+    pub albums: Vec<Album>,
+    pub artists: Vec<Artist>,
+    pub playlists: Vec<PlaylistBackup>,
+    pub collections: Vec<CollectionBackup>,
+}
+
+/// A playlist, and the (ids of the) songs in it, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistBackup {
+    pub playlist: Playlist,
+    pub songs: Vec<SongId>,
+}
+
+/// A collection, and the (ids of the) songs in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionBackup {
+    pub collection: Collection,
+    pub songs: Vec<SongId>,
+}
+
+/// Read a snapshot of the library from the database.
+///
+/// # Errors
+///
+/// Returns an error if there is an error reading from the database.
+#[instrument]
+pub async fn read_backup<C: Connection>(db: &Surreal<C>) -> Result<LibraryBackup, Error> {
+    let mut playlists = Vec::new();
+    for playlist in Playlist::read_all(db).await? {
+        let songs = Playlist::read_songs(db, playlist.id.clone()).await?;
+        playlists.push(PlaylistBackup {
+            playlist,
+            songs: songs.into_iter().map(|song| song.id).collect(),
+        });
+    }
+
+    let mut collections = Vec::new();
+    for collection in Collection::read_all(db).await? {
+        let songs = Collection::read_songs(db, collection.id.clone()).await?;
+        collections.push(CollectionBackup {
+            collection,
+            songs: songs.into_iter().map(|song| song.id).collect(),
+        });
+    }
+
+    Ok(LibraryBackup {
+        version: BACKUP_VERSION,
+        songs: Song::read_all(db).await?,
+        albums: Album::read_all(db).await?,
+        artists: Artist::read_all(db).await?,
+        playlists,
+        collections,
+    })
+}
+
+/// Export the metadata of the library to a (pretty printed) JSON file at `path`, overwriting it if it exists.
+///
+/// # Errors
+///
+/// Returns an error if there is an error reading from the database, or writing the file.
+#[instrument]
+pub async fn export_json<C: Connection>(
+    db: &Surreal<C>,
+    path: &Path,
+) -> Result<(), SerializableLibraryError> {
+    let backup = read_backup(db).await?;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, &backup)
+        .map_err(|e| SerializableLibraryError::IO(e.to_string()))?;
+    writer.flush()?;
+
+    info!(
+        "Exported {} songs, {} playlists, and {} collections to {}",
+        backup.songs.len(),
+        backup.playlists.len(),
+        backup.collections.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Merge a backup made by [`export_json`] into the library.
+///
+/// Songs are matched to the songs already in the library by their path and title, songs that match are skipped.
+/// The rest are added to the library (along with their albums and artists) with their play counts, ratings, etc.,
+/// which requires their files to still exist.
+///
+/// Playlists and collections are matched by name, and created if they don't exist.
+/// Their songs are added to them (in order, for playlists), unless they're already in them.
+///
+/// Errors importing individual songs don't stop the import, they are collected in the stats instead.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or isn't a backup,
+/// or if there is an error reading from or writing to the database.
+#[instrument]
+pub async fn import_json<C: Connection>(
+    db: &Surreal<C>,
+    path: &Path,
+) -> Result<ImportStats, SerializableLibraryError> {
+    let reader = BufReader::new(File::open(path)?);
+    let backup: LibraryBackup = serde_json::from_reader(reader)
+        .map_err(|e| SerializableLibraryError::Import(e.to_string()))?;
+    if backup.version > BACKUP_VERSION {
+        return Err(SerializableLibraryError::Import(format!(
+            "unsupported backup version {}, expected at most {BACKUP_VERSION}",
+            backup.version
+        )));
+    }
+    info!(
+        "Importing {} songs, {} playlists, and {} collections from {}",
+        backup.songs.len(),
+        backup.playlists.len(),
+        backup.collections.len(),
+        path.display()
+    );
+
+    let mut stats = ImportStats::default();
+
+    // the ids of the songs in the backup, mapped to the ids of the same songs in the library
+    let mut ids: HashMap<SongId, SongId> = HashMap::with_capacity(backup.songs.len());
+    let existing: HashMap<(PathBuf, Arc<str>), SongId> = Song::read_all(db)
+        .await?
+        .into_iter()
+        .map(|song| ((song.path, song.title), song.id))
+        .collect();
+
+    for song in backup.songs {
+        if let Some(id) = existing.get(&(song.path.clone(), song.title.clone())) {
+            debug!(
+                "Skipping {}, it's already in the library",
+                song.path.display()
+            );
+            ids.insert(song.id, id.clone());
+            stats.songs_skipped += 1;
+            continue;
+        }
+
+        let backup_id = song.id.clone();
+        match import_song(db, song).await {
+            Ok(id) => {
+                ids.insert(backup_id, id);
+                stats.songs_added += 1;
+            }
+            Err(e) => {
+                warn!("Failed to import song {backup_id}: {e}");
+                stats.errors.push(format!("{backup_id}: {e}"));
+            }
+        }
+    }
+
+    for PlaylistBackup { playlist, songs } in backup.playlists {
+        let id = match Playlist::read_by_name(db, playlist.name.to_string()).await? {
+            Some(existing) => existing.id,
+            None => {
+                Playlist::create(
+                    db,
+                    Playlist {
+                        id: Playlist::generate_id(),
+                        runtime: Duration::from_secs(0),
+                        song_count: 0,
+                        ..playlist
+                    },
+                )
+                .await?
+                .ok_or(Error::NotCreated)?
+                .id
+            }
+        };
+
+        let already_in: HashSet<SongId> = Playlist::read_songs(db, id.clone())
+            .await?
+            .into_iter()
+            .map(|song| song.id)
+            .collect();
+        let new_songs = new_songs(&ids, &already_in, songs);
+        if !new_songs.is_empty() {
+            Playlist::add_songs(db, id, new_songs).await?;
+        }
+        stats.playlists += 1;
+    }
+
+    let existing_collections: HashMap<Arc<str>, _> = Collection::read_all(db)
+        .await?
+        .into_iter()
+        .map(|collection| (collection.name, collection.id))
+        .collect();
+    for CollectionBackup { collection, songs } in backup.collections {
+        let id = match existing_collections.get(&collection.name) {
+            Some(id) => id.clone(),
+            None => {
+                Collection::create(
+                    db,
+                    Collection {
+                        id: Collection::generate_id(),
+                        runtime: Duration::from_secs(0),
+                        song_count: 0,
+                        ..collection
+                    },
+                )
+                .await?
+                .ok_or(Error::NotCreated)?
+                .id
+            }
+        };
+
+        let already_in: HashSet<SongId> = Collection::read_songs(db, id.clone())
+            .await?
+            .into_iter()
+            .map(|song| song.id)
+            .collect();
+        let new_songs = new_songs(&ids, &already_in, songs);
+        if !new_songs.is_empty() {
+            Collection::add_songs(db, id, new_songs).await?;
+        }
+        stats.collections += 1;
+    }
+
+    info!(
+        "Imported {} songs ({} skipped, {} errors), {} playlists, and {} collections",
+        stats.songs_added,
+        stats.songs_skipped,
+        stats.errors.len(),
+        stats.playlists,
+        stats.collections
+    );
+
+    Ok(stats)
+}
+
+/// Add a song from a backup to the library, keeping its play counts, rating, etc.
+///
+/// Returns the id of the new song.
+async fn import_song<C: Connection>(db: &Surreal<C>, song: Song) -> Result<SongId, Error> {
+    let changes = SongChangeSet {
+        replaygain_track_gain: Some(song.replaygain_track_gain),
+        replaygain_album_gain: Some(song.replaygain_album_gain),
+        play_count: Some(song.play_count),
+        skip_count: Some(song.skip_count),
+        last_played: song.last_played,
+        rating: Some(song.rating),
+        favorite: Some(song.favorite),
+        bookmark_positions: Some(song.bookmark_positions.clone()),
+        ..Default::default()
+    };
+
+    let id = Song::try_load_into_db(db, SongMetadata::from(song))
+        .await?
+        .id;
+    Song::update(db, id.clone(), changes).await?;
+    Ok(id)
+}
+
+/// Map the (backup) ids of the songs of a playlist or collection to the ids of the songs in the library,
+/// leaving out songs that weren't imported, and songs that are already in it.
+fn new_songs(
+    ids: &HashMap<SongId, SongId>,
+    already_in: &HashSet<SongId>,
+    songs: Vec<SongId>,
+) -> Vec<SongId> {
+    songs
+        .into_iter()
+        .filter_map(|id| ids.get(&id).cloned())
+        .filter(|id| !already_in.contains(id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::init;
+
+    use mecomp_storage::test_utils::{
+        arb_song_case, create_song_metadata, create_song_with_overrides, init_test_database,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_export_import_json() {
+        init();
+        let tempdir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // a song with a real file, that's been played and rated
+        let metadata = create_song_metadata(&tempdir, arb_song_case()()).unwrap();
+        let song = Song::try_load_into_db(&db, metadata).await.unwrap();
+        let song = Song::update(
+            &db,
+            song.id.clone(),
+            SongChangeSet {
+                play_count: Some(3),
+                rating: Some(Some(4)),
+                favorite: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        // and one whose file is gone
+        let missing = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+            .await
+            .unwrap();
+
+        let playlist = Playlist::create(
+            &db,
+            Playlist {
+                id: Playlist::generate_id(),
+                name: "Backed Up".into(),
+                runtime: Duration::from_secs(0),
+                song_count: 0,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        Playlist::add_songs(&db, playlist.id, vec![song.id.clone(), missing.id.clone()])
+            .await
+            .unwrap();
+
+        let path = tempdir.path().join("backup.json");
+        export_json(&db, &path).await.unwrap();
+        let backup: LibraryBackup = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+        assert_eq!(backup.version, BACKUP_VERSION);
+        assert_eq!(backup.songs.len(), 2);
+        assert_eq!(backup.playlists.len(), 1);
+        assert_eq!(backup.playlists[0].songs.len(), 2);
+
+        // importing into the same library doesn't duplicate anything
+        let stats = import_json(&db, &path).await.unwrap();
+        assert_eq!(stats.songs_added, 0);
+        assert_eq!(stats.songs_skipped, 2);
+        assert_eq!(stats.playlists, 1);
+        assert!(stats.errors.is_empty());
+        assert_eq!(Song::read_all(&db).await.unwrap().len(), 2);
+        assert_eq!(Playlist::read_all(&db).await.unwrap()[0].song_count, 2);
+
+        // importing into an empty library restores what it can
+        let db = init_test_database().await.unwrap();
+        let stats = import_json(&db, &path).await.unwrap();
+        assert_eq!(stats.songs_added, 1);
+        assert_eq!(stats.songs_skipped, 0);
+        assert_eq!(stats.playlists, 1);
+        assert_eq!(stats.errors.len(), 1);
+
+        let songs = Song::read_all(&db).await.unwrap();
+        assert_eq!(songs.len(), 1);
+        assert_eq!(songs[0].path, song.path);
+        assert_eq!(songs[0].play_count, 3);
+        assert_eq!(songs[0].rating, Some(4));
+        assert!(songs[0].favorite);
+
+        let playlists = Playlist::read_all(&db).await.unwrap();
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].name, "Backed Up".into());
+        assert_eq!(
+            Playlist::read_songs(&db, playlists[0].id.clone())
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|song| song.id)
+                .collect::<Vec<_>>(),
+            vec![songs[0].id.clone()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_json_invalid() {
+        init();
+        let tempdir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        let path = tempdir.path().join("backup.json");
+        std::fs::write(&path, "not a backup").unwrap();
+        assert!(matches!(
+            import_json(&db, &path).await,
+            Err(SerializableLibraryError::Import(_))
+        ));
+
+        // a file that doesn't exist
+        assert!(matches!(
+            import_json(&db, &tempdir.path().join("missing.json")).await,
+            Err(SerializableLibraryError::IO(_))
+        ));
+    }
+}
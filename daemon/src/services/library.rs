@@ -1,18 +1,26 @@
 use std::{
     collections::{HashMap, HashSet},
-    path::PathBuf,
-    time::Duration,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use log::{debug, error, info, warn};
 use mecomp_analysis::{
     clustering::{ClusteringHelper, KOptimal, NotInitialized},
     decoder::{DecoderWithCallback, MecompDecoder},
+    errors::AnalysisError,
+    fingerprint::is_match,
+    misc::LoudnessDesc,
+    temporal::BPMDesc,
+    AnalysisIndex, Feature, NUMBER_FEATURES,
+};
+use mecomp_core::state::library::{
+    CollectionAnalysisStats, DuplicateGroup, LibraryBrief, LibraryFull, LibraryHealth,
+    LibraryStatistics, ScanProgress, SongVerificationResult, VerificationStatus,
+    DUPLICATE_RUNTIME_TOLERANCE,
 };
-use mecomp_core::state::library::{LibraryBrief, LibraryFull, LibraryHealth};
 use one_or_many::OneOrMany;
 use surrealdb::{Connection, Surreal};
-use tap::TapFallible;
 use tracing::instrument;
 use walkdir::WalkDir;
 
@@ -21,45 +29,65 @@ use mecomp_storage::{
         health::{
             count_albums, count_artists, count_collections, count_orphaned_albums,
             count_orphaned_artists, count_orphaned_collections, count_orphaned_playlists,
-            count_playlists, count_songs, count_unanalyzed_songs,
+            count_playlists, count_songs, count_unanalyzed_songs, read_library_statistics,
         },
         schemas::{
-            album::Album,
+            album::{Album, AlbumId},
             analysis::Analysis,
             artist::Artist,
-            collection::Collection,
+            collection::{Collection, CollectionId},
+            failed_analysis::{FailedAnalysis, FailedAnalysisId, MAX_ATTEMPTS},
+            fingerprint::Fingerprint,
+            lyrics::Lyrics,
             playlist::Playlist,
-            song::{Song, SongMetadata},
+            scan_history::ScanMetrics,
+            song::{Song, SongChangeSet, SongId, SongMetadata},
         },
     },
     errors::Error,
     util::MetadataConflictResolution,
 };
 
-use crate::config::ReclusterSettings;
+use crate::config::{library_path_of, LibraryPathConfig, ReclusterSettings};
 
 /// Index the library.
 ///
+/// `on_progress` is called with the progress of the rescan every time a song or file is checked.
+///
 /// # Errors
 ///
 /// This function will return an error if there is an error reading from the database.
 /// or if there is an error reading from the file system.
 /// or if there is an error writing to the database.
-#[instrument]
+#[instrument(skip(on_progress))]
 pub async fn rescan<C: Connection>(
     db: &Surreal<C>,
-    paths: &[PathBuf],
+    paths: &[LibraryPathConfig],
     artist_name_separator: &OneOrMany<String>,
     genre_separator: Option<&str>,
     conflict_resolution_mode: MetadataConflictResolution,
+    mut on_progress: impl FnMut(&ScanProgress),
 ) -> Result<(), Error> {
     // get all the songs in the current library
     let songs = Song::read_all(db).await?;
     let mut paths_to_skip = HashSet::new(); // use a hashset because hashing is faster than linear search, especially for large libraries
 
+    let mut progress = ScanProgress {
+        total: songs.len() as u64
+            + paths
+                .iter()
+                .map(|library_path| count_files(&library_path.path))
+                .sum::<u64>(),
+        ..Default::default()
+    };
+
     // for each song, check if the file still exists
     for song in songs {
         let path = song.path.clone();
+        progress.scanned += 1;
+        progress.current_file = path.to_string_lossy().into_owned();
+        on_progress(&progress);
+
         if !path.exists() {
             // remove the song from the library
             warn!("Song {} no longer exists, deleting", path.to_string_lossy());
@@ -67,11 +95,30 @@ pub async fn rescan<C: Connection>(
             continue;
         }
 
+        // use the separators of the library path the song is in
+        let library_path = library_path_of(paths, &path);
+        let song_artist_name_separator = library_path.map_or(artist_name_separator, |lp| {
+            lp.artist_separator(artist_name_separator)
+        });
+        let song_genre_separator =
+            library_path.map_or(genre_separator, |lp| lp.genre_separator(genre_separator));
+
         debug!("loading metadata for {}", path.to_string_lossy());
+        // the lyrics aren't stored on the song itself, so we have to read them separately
+        let indexed = SongMetadata {
+            lyrics: Lyrics::read_for_song(db, song.id.clone())
+                .await?
+                .map(|lyrics| lyrics.lyrics),
+            ..SongMetadata::from(&song)
+        };
         // check if the metadata of the file is the same as the metadata in the database
-        match SongMetadata::load_from_path(path.clone(), artist_name_separator, genre_separator) {
+        match SongMetadata::load_from_path(
+            path.clone(),
+            song_artist_name_separator,
+            song_genre_separator,
+        ) {
             // if we have metadata and the metadata is different from the song's metadata, and ...
-            Ok(metadata) if metadata != SongMetadata::from(&song) => {
+            Ok(metadata) if metadata != indexed => {
                 #[allow(
                     clippy::if_not_else,
                     reason = "we may add more conflict resolution modes in the future, and skip is the only one that won't attempt to update the song"
@@ -91,6 +138,9 @@ pub async fn rescan<C: Connection>(
                     MetadataConflictResolution::Overwrite => {
                         // if the file has been modified, update the song's metadata
                         Song::update(db, song.id.clone(), metadata.merge_with_song(&song)).await?;
+                        if metadata.lyrics != indexed.lyrics {
+                            Lyrics::set_for_song(db, song.id.clone(), metadata.lyrics).await?;
+                        }
                     }
                     // ... we are in "skip" mode, do nothing
                     MetadataConflictResolution::Skip => {
@@ -107,6 +157,8 @@ pub async fn rescan<C: Connection>(
                 );
                 info!("assuming the file isn't a song or doesn't exist anymore, removing from library");
                 Song::delete(db, song.id).await?;
+                // don't skip the file, so that it's counted as an error when the library path is scanned
+                continue;
             }
             // if the metadata is the same, do nothing
             _ => {}
@@ -119,39 +171,26 @@ pub async fn rescan<C: Connection>(
     let mut visited_paths = paths_to_skip;
 
     debug!("Indexing paths: {:?}", paths);
-    for path in paths
-        .iter()
-        .filter_map(|p| {
-            p.canonicalize()
-                .tap_err(|e| warn!("Error canonicalizing path: {e}"))
-                .ok()
-        })
-        .flat_map(|x| WalkDir::new(x).into_iter())
-        .filter_map(|x| x.tap_err(|e| warn!("Error reading path: {e}")).ok())
-        .filter_map(|x| x.file_type().is_file().then_some(x))
-    {
-        if visited_paths.contains(path.path()) {
-            continue;
-        }
-
-        visited_paths.insert(path.path().to_owned());
-
-        // if the file is a song, add it to the library
-        match SongMetadata::load_from_path(
-            path.path().to_owned(),
-            artist_name_separator,
-            genre_separator,
-        ) {
-            Ok(metadata) => Song::try_load_into_db(db, metadata).await.map_or_else(
-                |e| warn!("Error indexing {}: {}", path.path().to_string_lossy(), e),
-                |_| debug!("Indexed {}", path.path().to_string_lossy()),
-            ),
-            Err(e) => warn!(
-                "Error reading metadata for {}: {}",
-                path.path().to_string_lossy(),
-                e
-            ),
-        }
+    for library_path in paths {
+        let metrics = index_path(
+            db,
+            &library_path.path,
+            &mut visited_paths,
+            library_path.artist_separator(artist_name_separator),
+            library_path.genre_separator(genre_separator),
+            &mut progress,
+            &mut on_progress,
+        )
+        .await;
+        info!(
+            "Scanned {}: {} files found, {} scanned, {} errors, took {}ms",
+            library_path.path.display(),
+            metrics.files_found,
+            metrics.files_scanned,
+            metrics.errors,
+            metrics.duration_ms
+        );
+        ScanMetrics::record(db, metrics).await?;
     }
 
     // find and delete any remaining orphaned albums and artists
@@ -181,6 +220,114 @@ pub async fn rescan<C: Connection>(
     Ok(())
 }
 
+/// Index the songs under one of the library paths that haven't been indexed yet (aren't in `visited_paths`),
+/// and report how the scan went.
+///
+/// Errors are logged and counted in the returned [`ScanMetrics`], rather than stopping the scan.
+async fn index_path<C: Connection>(
+    db: &Surreal<C>,
+    library_path: &Path,
+    visited_paths: &mut HashSet<PathBuf>,
+    artist_name_separator: &OneOrMany<String>,
+    genre_separator: Option<&str>,
+    progress: &mut ScanProgress,
+    on_progress: &mut impl FnMut(&ScanProgress),
+) -> ScanMetrics {
+    let start = Instant::now();
+    let mut files_found = 0;
+    let mut files_scanned = 0;
+    let mut errors = 0;
+    let previous_errors = progress.errors;
+
+    match library_path.canonicalize() {
+        Ok(root) => {
+            for entry in WalkDir::new(root) {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        warn!("Error reading path: {e}");
+                        errors += 1;
+                        continue;
+                    }
+                };
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                files_found += 1;
+
+                progress.scanned += 1;
+                // files may have been added since they were counted
+                progress.total = progress.total.max(progress.scanned);
+                progress.current_file = entry.path().to_string_lossy().into_owned();
+                progress.errors = previous_errors + errors;
+                on_progress(progress);
+
+                if !visited_paths.insert(entry.path().to_owned()) {
+                    continue;
+                }
+                files_scanned += 1;
+
+                // if the file is a song, add it to the library
+                match SongMetadata::load_from_path(
+                    entry.path().to_owned(),
+                    artist_name_separator,
+                    genre_separator,
+                ) {
+                    Ok(metadata) => match Song::try_load_into_db(db, metadata).await {
+                        Ok(_) => debug!("Indexed {}", entry.path().to_string_lossy()),
+                        Err(e) => {
+                            warn!("Error indexing {}: {}", entry.path().to_string_lossy(), e);
+                            errors += 1;
+                        }
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Error reading metadata for {}: {}",
+                            entry.path().to_string_lossy(),
+                            e
+                        );
+                        errors += 1;
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Error canonicalizing path: {e}");
+            errors += 1;
+        }
+    }
+
+    progress.errors = previous_errors + errors;
+    on_progress(progress);
+
+    ScanMetrics::new(
+        library_path.to_owned(),
+        files_found,
+        files_scanned,
+        errors,
+        start.elapsed(),
+    )
+}
+
+/// Count the files under `path`, to know how many files a rescan will visit.
+fn count_files(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .count() as u64
+}
+
+/// Read the metrics of the recent library scans, most recent first.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+#[instrument]
+pub async fn scan_history<C: Connection>(db: &Surreal<C>) -> Result<Vec<ScanMetrics>, Error> {
+    ScanMetrics::read_all(db).await
+}
+
 /// Analyze the library.
 ///
 /// In order, this function will:
@@ -188,6 +335,56 @@ pub async fn rescan<C: Connection>(
 /// - start analyzing those songs in batches.
 /// - update the database with the analyses.
 ///
+/// Songs are analyzed at `sample_rate` (one of [`SUPPORTED_SAMPLE_RATES`](mecomp_analysis::SUPPORTED_SAMPLE_RATES)),
+/// and songs whose analysis takes longer than `timeout` are skipped (and logged).
+/// Songs whose analysis fails are recorded as a [`FailedAnalysis`], and once they've failed [`MAX_ATTEMPTS`] times
+/// they are skipped by future analyses (see [`retry_failed_analysis`]).
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+///
+/// # Panics
+///
+/// This function will panic if the thread(s) that analyzes the songs panics.
+#[instrument]
+pub async fn analyze<C: Connection>(
+    db: &Surreal<C>,
+    sample_rate: u32,
+    timeout: Duration,
+) -> Result<(), Error> {
+    // get all the songs that don't have an analysis, skipping those that have failed to be analyzed too many times
+    let exhausted = FailedAnalysis::read_all(db)
+        .await?
+        .into_iter()
+        .filter(FailedAnalysis::is_exhausted)
+        .map(|failed| failed.song_id)
+        .collect::<Vec<_>>();
+    if !exhausted.is_empty() {
+        info!(
+            "Skipping {} songs that have failed to be analyzed {MAX_ATTEMPTS} times",
+            exhausted.len()
+        );
+    }
+    let songs_to_analyze: Vec<Song> = Analysis::read_songs_without_analysis(db)
+        .await?
+        .into_iter()
+        .filter(|song| !exhausted.contains(&song.id))
+        .collect();
+
+    analyze_songs(db, songs_to_analyze, sample_rate, timeout).await?;
+
+    info!("Library analysis complete");
+    info!("Library brief: {:?}", brief(db).await?);
+
+    Ok(())
+}
+
+/// Retry the analysis of the songs with the given failed analysis records.
+///
+/// Unlike [`analyze`], this retries the songs even if they've already failed [`MAX_ATTEMPTS`] times.
+/// Records that don't exist (or whose song no longer exists) are skipped (and logged).
+///
 /// # Errors
 ///
 /// This function will return an error if there is an error reading from the database.
@@ -196,59 +393,364 @@ pub async fn rescan<C: Connection>(
 ///
 /// This function will panic if the thread(s) that analyzes the songs panics.
 #[instrument]
-pub async fn analyze<C: Connection>(db: &Surreal<C>) -> Result<(), Error> {
-    // get all the songs that don't have an analysis
-    let songs_to_analyze: Vec<Song> = Analysis::read_songs_without_analysis(db).await?;
+pub async fn retry_failed_analysis<C: Connection>(
+    db: &Surreal<C>,
+    ids: Vec<FailedAnalysisId>,
+    sample_rate: u32,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let mut songs = Vec::with_capacity(ids.len());
+    for id in ids {
+        let Some(failed) = FailedAnalysis::read(db, id.clone()).await? else {
+            warn!("No failed analysis found with id: {id}");
+            continue;
+        };
+        if let Some(song) = Song::read(db, failed.song_id.clone()).await? {
+            songs.push(song);
+        } else {
+            warn!("No song found with id: {}", failed.song_id);
+        }
+    }
+
+    analyze_songs(db, songs, sample_rate, timeout).await?;
+
+    info!("Retrying failed analyses complete");
+
+    Ok(())
+}
+
+/// Analyze the given songs, and update the database with the results.
+///
+/// Songs that fail to be analyzed get a [`FailedAnalysis`] record (or have theirs updated),
+/// and songs that are analyzed successfully have theirs removed.
+/// Songs are analyzed at `sample_rate`, and songs whose analysis takes longer than `timeout` are skipped (and logged).
+async fn analyze_songs<C: Connection>(
+    db: &Surreal<C>,
+    songs: Vec<Song>,
+    sample_rate: u32,
+    timeout: Duration,
+) -> Result<(), Error> {
     // crate a hashmap mapping paths to song ids
-    let paths = songs_to_analyze
-        .iter()
-        .map(|song| (song.path.clone(), song.id.clone()))
+    let paths = songs
+        .into_iter()
+        .map(|song| (song.path, song.id))
         .collect::<HashMap<_, _>>();
 
     let keys = paths.keys().cloned().collect::<Vec<_>>();
 
+    // report progress about 20 times over the course of the analysis
+    let total = keys.len();
+    let progress_interval = (total / 20).max(1);
+    let mut analyzed = 0;
+
     let (tx, rx) = std::sync::mpsc::channel();
 
     // analyze the songs in batches
     let handle = std::thread::spawn(move || {
-        MecompDecoder::analyze_paths_with_callback(keys, tx);
+        MecompDecoder::analyze_paths_with_timeout_and_callback(keys, sample_rate, timeout, tx);
     });
 
     for (song_path, maybe_analysis) in rx {
+        analyzed += 1;
+        if analyzed % progress_interval == 0 || analyzed == total {
+            info!("Analysis progress: {analyzed}/{total} songs");
+        }
+
         let Some(song_id) = paths.get(&song_path) else {
             error!("No song id found for path: {}", song_path.to_string_lossy());
             return Ok(());
         };
 
         match maybe_analysis {
-            Ok(analysis) => Analysis::create(
-                db,
-                song_id.clone(),
-                Analysis {
-                    id: Analysis::generate_id(),
-                    features: *analysis.inner(),
-                },
-            )
-            .await?
-            .map_or_else(
-                || {
+            Ok(analysis) => {
+                Analysis::create(
+                    db,
+                    song_id.clone(),
+                    Analysis {
+                        id: Analysis::generate_id(),
+                        features: *analysis.inner(),
+                    },
+                )
+                .await?
+                .map_or_else(
+                    || {
+                        warn!(
+                            "Error analyzing {}: song either wasn't found or already has an analysis",
+                            song_path.to_string_lossy()
+                        );
+                    },
+                    |_| debug!("Analyzed {}", song_path.to_string_lossy()),
+                );
+                FailedAnalysis::delete_for_songs(db, vec![song_id.clone()]).await?;
+            }
+            Err(AnalysisError::Timeout) => {
+                warn!(
+                    "Analysis of {} timed out after {}s, skipping",
+                    song_path.to_string_lossy(),
+                    timeout.as_secs()
+                );
+            }
+            Err(e) => {
+                error!("Error analyzing {}: {}", song_path.to_string_lossy(), e);
+                let failed =
+                    FailedAnalysis::record_failure(db, song_id.clone(), e.to_string()).await?;
+                if failed.is_exhausted() {
                     warn!(
-                        "Error analyzing {}: song either wasn't found or already has an analysis",
+                        "{} has failed to be analyzed {} times, it won't be retried automatically",
+                        song_path.to_string_lossy(),
+                        failed.attempt_count
+                    );
+                }
+            }
+        }
+    }
+
+    handle.join().expect("Couldn't join thread");
+
+    Ok(())
+}
+
+/// Automatically add songs that aren't in any collection to the existing collection they are most similar to.
+///
+/// Only songs that have been analyzed are considered, and a song is only added to a collection
+/// if its analysis is within `threshold` (euclidean distance) of the collection's centroid.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from or writing to the database.
+#[instrument]
+pub async fn auto_update_collections<C: Connection>(
+    db: &Surreal<C>,
+    threshold: f64,
+) -> Result<(), Error> {
+    // the centroids are computed once for the whole pass, so they don't drift as songs are added
+    let centroids = Collection::centroids(db).await?;
+
+    let mut added = 0usize;
+    for analysis in Analysis::read_all(db).await? {
+        let song = Analysis::read_song(db, analysis.id.clone()).await?;
+        if centroids
+            .iter()
+            .any(|collection| collection.songs.contains(&song.id))
+        {
+            continue;
+        }
+
+        if let Some(collection_id) = Collection::auto_update_with_centroids(
+            db,
+            song.id.clone(),
+            &analysis,
+            &centroids,
+            threshold,
+        )
+        .await?
+        {
+            debug!("Added {} to collection {collection_id}", song.id);
+            added += 1;
+        }
+    }
+
+    info!("Automatically added {added} songs to existing collections");
+
+    Ok(())
+}
+
+/// Compute the `ReplayGain` of the given songs.
+///
+/// In order, this function will:
+/// - get the songs with the given ids (ids of songs that don't exist are ignored),
+///   and the other songs on their albums (since the album gain depends on the whole album).
+/// - start decoding those songs in batches.
+/// - compute the `ReplayGain` track gain of each song, and update the database.
+/// - compute the `ReplayGain` album gain of each album from the songs on it that could be decoded,
+///   and update the database.
+///
+/// `on_progress` is called with the progress of the computation every time a song is done.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+///
+/// # Panics
+///
+/// This function will panic if the thread(s) that decode the songs panics.
+#[instrument(skip(on_progress))]
+pub async fn compute_replaygain<C: Connection>(
+    db: &Surreal<C>,
+    song_ids: Vec<SongId>,
+    mut on_progress: impl FnMut(&ScanProgress),
+) -> Result<(), Error> {
+    // crate a hashmap mapping paths to song ids
+    let mut paths = HashMap::with_capacity(song_ids.len());
+    // the albums of the songs, along with the loudness of the songs on them
+    let mut albums: Vec<(AlbumId, Vec<(SongId, LoudnessDesc)>)> = Vec::new();
+    // and a hashmap mapping paths to the index of their album
+    let mut album_indices = HashMap::new();
+    for id in song_ids {
+        let Some(song) = Song::read(db, id.clone()).await? else {
+            warn!("No song found with id: {id}");
+            continue;
+        };
+        if paths.contains_key(&song.path) {
+            // already added along with another song on the same album
+            continue;
+        }
+
+        match Song::read_album(db, song.id.clone()).await? {
+            Some(album) => {
+                for album_song in Album::read_songs(db, album.id.clone()).await? {
+                    album_indices.insert(album_song.path.clone(), albums.len());
+                    paths.insert(album_song.path, album_song.id);
+                }
+                albums.push((album.id, Vec::new()));
+            }
+            None => {
+                paths.insert(song.path, song.id);
+            }
+        }
+    }
+
+    let keys = paths.keys().cloned().collect::<Vec<_>>();
+    let mut progress = ScanProgress {
+        total: keys.len() as u64,
+        ..Default::default()
+    };
+    on_progress(&progress);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    // decode the songs in batches
+    let handle = std::thread::spawn(move || {
+        MecompDecoder::decode_paths_with_callback(keys, tx);
+    });
+
+    for (song_path, maybe_audio) in rx {
+        progress.scanned += 1;
+        progress.current_file = song_path.to_string_lossy().into_owned();
+
+        let Some(song_id) = paths.get(&song_path) else {
+            error!("No song id found for path: {}", song_path.to_string_lossy());
+            progress.errors += 1;
+            on_progress(&progress);
+            continue;
+        };
+
+        match maybe_audio {
+            Ok(audio) => match Song::compute_replaygain(db, song_id.clone(), &audio).await {
+                Ok(loudness) => {
+                    debug!("Computed ReplayGain for {}", song_path.to_string_lossy());
+                    if let Some(&index) = album_indices.get(&song_path) {
+                        albums[index].1.push((song_id.clone(), loudness));
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "Error computing ReplayGain for {}: {e}",
                         song_path.to_string_lossy()
                     );
-                },
-                |_| debug!("Analyzed {}", song_path.to_string_lossy()),
-            ),
+                    progress.errors += 1;
+                }
+            },
             Err(e) => {
-                error!("Error analyzing {}: {}", song_path.to_string_lossy(), e);
+                error!("Error decoding {}: {e}", song_path.to_string_lossy());
+                progress.errors += 1;
             }
         }
+
+        on_progress(&progress);
     }
 
     handle.join().expect("Couldn't join thread");
 
-    info!("Library analysis complete");
-    info!("Library brief: {:?}", brief(db).await?);
+    for (album, songs) in albums {
+        let loudness = songs
+            .iter()
+            .map(|(_, loudness)| loudness)
+            .collect::<Vec<_>>();
+        #[allow(clippy::cast_possible_truncation)]
+        let replaygain_album_gain =
+            LoudnessDesc::get_album_replaygain(&loudness).map(|gain| gain as f32);
+
+        for (song_id, _) in songs {
+            let changes = SongChangeSet {
+                replaygain_album_gain: Some(replaygain_album_gain),
+                ..Default::default()
+            };
+            if let Err(e) = Song::update(db, song_id, changes).await {
+                error!("Error storing the ReplayGain album gain of {album}: {e}");
+            }
+        }
+    }
+
+    info!("ReplayGain computation complete");
+
+    Ok(())
+}
+
+/// Compute the acoustic fingerprints of the given songs.
+///
+/// In order, this function will:
+/// - get the songs with the given ids (ids of songs that don't exist are ignored).
+/// - start decoding those songs in batches.
+/// - compute the fingerprint of each song, and update the database.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+///
+/// # Panics
+///
+/// This function will panic if the thread(s) that decode the songs panics.
+#[instrument]
+pub async fn compute_fingerprints<C: Connection>(
+    db: &Surreal<C>,
+    song_ids: Vec<SongId>,
+) -> Result<(), Error> {
+    // crate a hashmap mapping paths to song ids
+    let mut paths = HashMap::with_capacity(song_ids.len());
+    for id in song_ids {
+        if let Some(song) = Song::read(db, id.clone()).await? {
+            paths.insert(song.path, song.id);
+        } else {
+            warn!("No song found with id: {id}");
+        }
+    }
+
+    let keys = paths.keys().cloned().collect::<Vec<_>>();
+    let total = keys.len();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    // decode the songs in batches
+    let handle = std::thread::spawn(move || {
+        MecompDecoder::decode_paths_with_callback(keys, tx);
+    });
+
+    for (i, (song_path, maybe_audio)) in rx.into_iter().enumerate() {
+        let Some(song_id) = paths.get(&song_path) else {
+            error!("No song id found for path: {}", song_path.to_string_lossy());
+            continue;
+        };
+
+        match maybe_audio {
+            Ok(audio) => match Song::compute_fingerprint(db, song_id.clone(), &audio).await {
+                Ok(()) => debug!("Computed fingerprint for {}", song_path.to_string_lossy()),
+                Err(e) => error!(
+                    "Error computing fingerprint for {}: {e}",
+                    song_path.to_string_lossy()
+                ),
+            },
+            Err(e) => {
+                error!("Error decoding {}: {e}", song_path.to_string_lossy());
+            }
+        }
+
+        info!("Fingerprinting progress: {}/{total}", i + 1);
+    }
+
+    handle.join().expect("Couldn't join thread");
+
+    info!("Fingerprinting complete");
 
     Ok(())
 }
@@ -257,6 +759,9 @@ pub async fn analyze<C: Connection>(db: &Surreal<C>) -> Result<(), Error> {
 ///
 /// This function will remove and recompute all the "collections" (clusters) in the library.
 ///
+/// If `n_clusters` is `None`, the number of clusters is chosen automatically (up to `settings.max_clusters`),
+/// otherwise exactly `n_clusters` collections are created.
+///
 /// # Errors
 ///
 /// This function will return an error if there is an error reading from the database.
@@ -264,16 +769,24 @@ pub async fn analyze<C: Connection>(db: &Surreal<C>) -> Result<(), Error> {
 pub async fn recluster<C: Connection>(
     db: &Surreal<C>,
     settings: &ReclusterSettings,
+    n_clusters: Option<usize>,
 ) -> Result<(), Error> {
     // collect all the analyses
     let samples = Analysis::read_all(db).await?;
 
+    // standardize the features, so that the ones with a wide range don't dominate the distances
+    let analyses = samples
+        .iter()
+        .map(Into::into)
+        .collect::<Vec<mecomp_analysis::Analysis>>();
+    let (mean, std) = mecomp_analysis::Analysis::compute_population_stats(&analyses);
+
     // use clustering algorithm to cluster the analyses
     let model: ClusteringHelper<NotInitialized> = match ClusteringHelper::new(
-        samples
+        analyses
             .iter()
-            .map(Into::into)
-            .collect::<Vec<mecomp_analysis::Analysis>>()
+            .map(|analysis| analysis.normalize(&mean, &std))
+            .collect::<Vec<_>>()
             .into(),
         settings.max_clusters,
         KOptimal::GapStatistic {
@@ -288,7 +801,13 @@ pub async fn recluster<C: Connection>(
         Ok(kmeans) => kmeans,
     };
 
-    let model = match model.initialize() {
+    // both ways of initializing the model consume it, so this can't be a `map_or_else`
+    #[allow(clippy::option_if_let_else)]
+    let model = match n_clusters {
+        Some(n_clusters) => model.initialize_with_k(n_clusters),
+        None => model.initialize(),
+    };
+    let model = match model {
         Err(e) => {
             error!("There was an error initializing the clustering helper: {e}",);
             return Ok(());
@@ -393,16 +912,409 @@ pub async fn health<C: Connection>(db: &Surreal<C>) -> Result<LibraryHealth, Err
     })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::test_utils::init;
+/// Get statistics about the contents of the library, computed by the database in a single query.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+#[instrument]
+pub async fn statistics<C: Connection>(db: &Surreal<C>) -> Result<LibraryStatistics, Error> {
+    let statistics = read_library_statistics(db).await?;
+
+    Ok(LibraryStatistics {
+        total_songs: statistics.songs,
+        total_albums: statistics.albums,
+        total_artists: statistics.artists,
+        total_runtime: Duration::from_millis(statistics.runtime_ms),
+        songs_with_analysis: statistics.analyzed_songs,
+        songs_without_analysis: statistics.songs.saturating_sub(statistics.analyzed_songs),
+        // the mean of nothing is NaN
+        average_bpm: statistics
+            .mean_tempo
+            .filter(|tempo| statistics.analyzed_songs > 0 && tempo.is_finite())
+            .map(BPMDesc::denormalize_bpm),
+        unique_genres: statistics.genres.len(),
+        most_common_genre: statistics
+            .genres
+            .into_iter()
+            .next()
+            .map(|genre| genre.genre.to_string()),
+    })
+}
 
-    use mecomp_storage::db::schemas::song::{SongChangeSet, SongMetadata};
-    use mecomp_storage::test_utils::{
-        arb_analysis_features, arb_song_case, arb_vec, create_song_metadata,
-        create_song_with_overrides, init_test_database, SongCase, ARTIST_NAME_SEPARATOR,
-    };
+/// Find the songs in the library that are likely duplicates of each other,
+/// see [`DuplicateGroup`] for what makes songs duplicates.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+#[instrument]
+pub async fn find_duplicates<C: Connection>(db: &Surreal<C>) -> Result<Vec<DuplicateGroup>, Error> {
+    Ok(group_duplicates(Song::read_all(db).await?))
+}
+
+/// Find the songs in the library that have matching acoustic fingerprints,
+/// which catches duplicates that have been renamed, re-tagged, or re-encoded.
+///
+/// Fingerprints are computed lazily: only the songs that don't have a fingerprint yet are fingerprinted.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from or writing to the database.
+#[instrument]
+pub async fn find_acoustid_duplicates<C: Connection>(
+    db: &Surreal<C>,
+) -> Result<Vec<DuplicateGroup>, Error> {
+    let fingerprinted = Fingerprint::read_all(db)
+        .await?
+        .into_iter()
+        .map(|fingerprint| fingerprint.song_id.to_string())
+        .collect::<HashSet<_>>();
+    let unfingerprinted = Song::read_all(db)
+        .await?
+        .into_iter()
+        .map(|song| song.id)
+        .filter(|id| !fingerprinted.contains(&id.to_string()))
+        .collect::<Vec<_>>();
+    if !unfingerprinted.is_empty() {
+        compute_fingerprints(db, unfingerprinted).await?;
+    }
+
+    Ok(group_by_fingerprint(
+        Song::read_all(db).await?,
+        Fingerprint::read_all(db).await?,
+    ))
+}
+
+/// Log the groups of duplicate songs in the library (see [`find_duplicates`]).
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+#[instrument]
+pub async fn report_duplicates<C: Connection>(db: &Surreal<C>) -> Result<(), Error> {
+    let groups = find_duplicates(db).await?;
+    if groups.is_empty() {
+        info!("No duplicate songs found");
+        return Ok(());
+    }
+
+    warn!("Found {} groups of duplicate songs:", groups.len());
+    for group in &groups {
+        warn!(
+            "{}: {}",
+            group.songs[0].title,
+            group
+                .songs
+                .iter()
+                .map(|song| format!("{} ({})", song.id, song.path.display()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Find the songs in the library whose files don't exist anymore (e.g. because they were moved or deleted externally).
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+#[instrument]
+pub async fn dead_links<C: Connection>(db: &Surreal<C>) -> Result<Vec<Song>, Error> {
+    Ok(Song::read_all(db)
+        .await?
+        .into_iter()
+        .filter(|song| !song.path.exists())
+        .collect())
+}
+
+/// Delete the songs in the library whose files don't exist anymore (see [`dead_links`]),
+/// removing them from their albums, artists, playlists, and collections.
+///
+/// Returns the number of songs that were deleted.
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from or writing to the database.
+#[instrument]
+pub async fn remove_dead_links<C: Connection>(db: &Surreal<C>) -> Result<u64, Error> {
+    let mut removed = 0;
+    for song in dead_links(db).await? {
+        warn!("Song {} no longer exists, deleting", song.path.display());
+        if Song::delete(db, song.id).await?.is_some() {
+            removed += 1;
+        }
+    }
+    info!("Removed {removed} dead links from the library");
+    Ok(removed)
+}
+
+/// Group songs that have the same title and artists (ignoring case),
+/// and runtimes within [`DUPLICATE_RUNTIME_TOLERANCE`] of the shortest song in the group.
+///
+/// Songs without any duplicates are left out, groups are ordered by title.
+fn group_duplicates(songs: Vec<Song>) -> Vec<DuplicateGroup> {
+    let mut candidates: HashMap<(String, Vec<String>), Vec<Song>> = HashMap::new();
+    for song in songs {
+        let mut artists = song
+            .artist
+            .iter()
+            .map(|artist| artist.to_lowercase())
+            .collect::<Vec<_>>();
+        artists.sort();
+        candidates
+            .entry((song.title.to_lowercase(), artists))
+            .or_default()
+            .push(song);
+    }
+
+    let mut groups = Vec::new();
+    for mut songs in candidates.into_values().filter(|songs| songs.len() > 1) {
+        songs.sort_by_key(|song| song.runtime);
+
+        let mut group: Vec<Song> = Vec::new();
+        for song in songs {
+            if group
+                .first()
+                .is_some_and(|first| song.runtime - first.runtime > DUPLICATE_RUNTIME_TOLERANCE)
+            {
+                groups.push(std::mem::take(&mut group));
+            }
+            group.push(song);
+        }
+        groups.push(group);
+    }
+
+    let mut groups = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|mut songs| {
+            songs.sort_by(|a, b| a.path.cmp(&b.path));
+            DuplicateGroup {
+                songs: songs.into_boxed_slice(),
+            }
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| a.songs[0].title.cmp(&b.songs[0].title));
+    groups
+}
+
+/// Group songs that have matching acoustic fingerprints (see [`is_match`]), songs without a fingerprint are ignored.
+///
+/// Only songs with runtimes within [`DUPLICATE_RUNTIME_TOLERANCE`] of each other are compared,
+/// and matches are transitive: if `a` matches `b` and `b` matches `c`, all three are in the same group.
+///
+/// Songs without any duplicates are left out, groups are ordered by title.
+fn group_by_fingerprint(songs: Vec<Song>, fingerprints: Vec<Fingerprint>) -> Vec<DuplicateGroup> {
+    /// Find the root of the group that `song` is in, see <https://en.wikipedia.org/wiki/Disjoint-set_data_structure>
+    fn root(parents: &mut [usize], mut song: usize) -> usize {
+        while parents[song] != song {
+            parents[song] = parents[parents[song]];
+            song = parents[song];
+        }
+        song
+    }
+
+    let mut fingerprints = fingerprints
+        .into_iter()
+        .map(|fingerprint| (fingerprint.song_id.to_string(), fingerprint.fingerprint))
+        .collect::<HashMap<_, _>>();
+    let mut songs = songs
+        .into_iter()
+        .filter_map(|song| Some((fingerprints.remove(&song.id.to_string())?, song)))
+        .collect::<Vec<_>>();
+    songs.sort_by_key(|(_, song)| song.runtime);
+
+    let mut parents = (0..songs.len()).collect::<Vec<_>>();
+    for (i, (fingerprint, song)) in songs.iter().enumerate() {
+        for (j, (other_fingerprint, other)) in songs.iter().enumerate().skip(i + 1) {
+            if other.runtime - song.runtime > DUPLICATE_RUNTIME_TOLERANCE {
+                break;
+            }
+            if is_match(fingerprint, other_fingerprint) {
+                let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+                parents[b] = a;
+            }
+        }
+    }
+
+    let mut candidates: HashMap<usize, Vec<Song>> = HashMap::new();
+    for (i, (_, song)) in songs.into_iter().enumerate() {
+        candidates
+            .entry(root(&mut parents, i))
+            .or_default()
+            .push(song);
+    }
+
+    let mut groups = candidates
+        .into_values()
+        .filter(|songs| songs.len() > 1)
+        .map(|mut songs| {
+            songs.sort_by(|a, b| a.path.cmp(&b.path));
+            DuplicateGroup {
+                songs: songs.into_boxed_slice(),
+            }
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| a.songs[0].title.cmp(&b.songs[0].title));
+    groups
+}
+
+/// Get summary statistics of the analyses of the songs in a collection.
+///
+/// Returns `None` if none of the songs in the collection have been analyzed (or the collection doesn't exist).
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+#[instrument]
+pub async fn collection_analysis_stats<C: Connection>(
+    db: &Surreal<C>,
+    id: CollectionId,
+) -> Result<Option<CollectionAnalysisStats>, Error> {
+    let song_ids = Collection::read_songs(db, id)
+        .await?
+        .into_iter()
+        .map(|song| song.id)
+        .collect();
+
+    let features = Analysis::read_for_songs(db, song_ids)
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|analysis| analysis.features)
+        .collect::<Vec<_>>();
+
+    Ok(analysis_stats(&features))
+}
+
+/// Compute the [`CollectionAnalysisStats`] of the given feature vectors, returns `None` if there are none.
+fn analysis_stats(features: &[[Feature; NUMBER_FEATURES]]) -> Option<CollectionAnalysisStats> {
+    if features.is_empty() {
+        return None;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let n = features.len() as Feature;
+
+    let bpms = features
+        .iter()
+        .map(|f| BPMDesc::denormalize_bpm(f[AnalysisIndex::Tempo as usize]))
+        .collect::<Vec<_>>();
+    let mean_bpm = bpms.iter().sum::<Feature>() / n;
+    let tempo_std = (bpms
+        .iter()
+        .map(|bpm| (bpm - mean_bpm).powi(2))
+        .sum::<Feature>()
+        / n)
+        .sqrt();
+
+    let mean_loudness = features
+        .iter()
+        .map(|f| LoudnessDesc::denormalize_loudness(f[AnalysisIndex::MeanLoudness as usize]))
+        .sum::<Feature>()
+        / n;
+
+    let mut chroma_centroid = [0.; 10];
+    for f in features {
+        let chroma = &f[AnalysisIndex::Chroma1 as usize..=AnalysisIndex::Chroma10 as usize];
+        for (centroid, value) in chroma_centroid.iter_mut().zip(chroma) {
+            *centroid += value / n;
+        }
+    }
+
+    Some(CollectionAnalysisStats {
+        analyzed_songs: features.len(),
+        mean_bpm,
+        tempo_std,
+        mean_loudness,
+        chroma_centroid,
+    })
+}
+
+/// Verify that the files of the given songs (or all songs, if `ids` is `None`) still exist and can be decoded.
+///
+/// The songs are verified in parallel, ids that aren't in the library are skipped (and logged).
+///
+/// # Errors
+///
+/// This function will return an error if there is an error reading from the database.
+///
+/// # Panics
+///
+/// This function will panic if the thread(s) that verify the songs panic.
+#[instrument]
+pub async fn verify<C: Connection>(
+    db: &Surreal<C>,
+    ids: Option<Vec<SongId>>,
+) -> Result<Vec<SongVerificationResult>, Error> {
+    let songs = if let Some(ids) = ids {
+        let mut songs = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(song) = Song::read(db, id.clone()).await? {
+                songs.push(song);
+            } else {
+                warn!("No song found with id: {id}");
+            }
+        }
+        songs
+    } else {
+        Song::read_all(db).await?
+    };
+
+    if songs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let chunk_size = songs.len().div_ceil(threads);
+
+    let results: Vec<SongVerificationResult> = std::thread::scope(|s| {
+        let handles = songs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                s.spawn(|| {
+                    chunk
+                        .iter()
+                        .map(|song| SongVerificationResult {
+                            id: song.id.clone(),
+                            status: song.verify_file().into(),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    for result in &results {
+        if result.status != VerificationStatus::Ok {
+            warn!(
+                "Song {} failed verification: {:?}",
+                result.id, result.status
+            );
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::init;
+
+    use mecomp_analysis::SAMPLE_RATE;
+    use mecomp_storage::db::schemas::song::SongMetadata;
+    use mecomp_storage::test_utils::{
+        arb_analysis_features, arb_song_case, arb_vec, create_song_metadata,
+        create_song_with_overrides, init_test_database, SongCase, ARTIST_NAME_SEPARATOR,
+    };
     use one_or_many::OneOrMany;
     use pretty_assertions::assert_eq;
 
@@ -456,17 +1368,37 @@ mod tests {
         .unwrap();
 
         // rescan the library
+        let mut progress = Vec::new();
         rescan(
             &db,
-            &[tempdir.path().to_owned()],
+            &[tempdir.path().to_owned().into()],
             &OneOrMany::One(ARTIST_NAME_SEPARATOR.to_string()),
             Some(ARTIST_NAME_SEPARATOR),
             MetadataConflictResolution::Overwrite,
+            |p| progress.push(p.clone()),
         )
         .await
         .unwrap();
 
         // check that everything was done correctly
+        // - the progress was reported, ending with everything checked
+        assert!(progress
+            .windows(2)
+            .all(|pair| pair[0].scanned <= pair[1].scanned));
+        let last = progress.last().unwrap();
+        assert_eq!(last.scanned, last.total);
+        assert_eq!(last.errors, 2);
+        // - the scan of the library path was recorded, counting the two invalid songs as errors
+        let scan_history = scan_history(&db).await.unwrap();
+        assert_eq!(scan_history.len(), 1);
+        assert_eq!(scan_history[0].path, tempdir.path());
+        assert_eq!(scan_history[0].errors, 2);
+        assert!(scan_history[0].files_found >= scan_history[0].files_scanned);
+        assert_eq!(
+            scan_history[0].files_scanned,
+            scan_history[0].files_found - 1,
+            "the song with outdated metadata was already indexed, so it shouldn't be scanned again"
+        );
         // - `song_with_nonexistent_path` was deleted
         assert_eq!(
             Song::read(&db, song_with_nonexistent_path.id)
@@ -561,6 +1493,44 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn rescan_uses_library_path_separators() {
+        init();
+        let tempdir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        create_song_metadata(
+            &tempdir,
+            SongCase {
+                artists: vec![1, 2],
+                ..arb_song_case()()
+            },
+        )
+        .unwrap();
+
+        // the library path doesn't split artists, even though the default separator would
+        rescan(
+            &db,
+            &[LibraryPathConfig {
+                artist_separator: Some(OneOrMany::None),
+                ..tempdir.path().to_owned().into()
+            }],
+            &OneOrMany::One(ARTIST_NAME_SEPARATOR.to_string()),
+            Some(ARTIST_NAME_SEPARATOR),
+            MetadataConflictResolution::Overwrite,
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        let songs = Song::read_all(&db).await.unwrap();
+        assert_eq!(songs.len(), 1);
+        assert_eq!(
+            songs[0].artist,
+            OneOrMany::One(format!("Artist 1{ARTIST_NAME_SEPARATOR}Artist 2").into())
+        );
+    }
+
     #[tokio::test]
     async fn rescan_deletes_preexisting_orphans() {
         init();
@@ -578,10 +1548,11 @@ mod tests {
         // rescan the library
         rescan(
             &db,
-            &[tempdir.path().to_owned()],
+            &[tempdir.path().to_owned().into()],
             &OneOrMany::One(ARTIST_NAME_SEPARATOR.to_string()),
             Some(ARTIST_NAME_SEPARATOR),
             MetadataConflictResolution::Overwrite,
+            |_| {},
         )
         .await
         .unwrap();
@@ -621,10 +1592,11 @@ mod tests {
         // rescan the library
         rescan(
             &db,
-            &[tempdir.path().to_owned()],
+            &[tempdir.path().to_owned().into()],
             &OneOrMany::One(ARTIST_NAME_SEPARATOR.to_string()),
             Some(ARTIST_NAME_SEPARATOR),
             MetadataConflictResolution::Overwrite,
+            |_| {},
         )
         .await
         .unwrap();
@@ -665,7 +1637,9 @@ mod tests {
         );
 
         // analyze the library
-        analyze(&db).await.unwrap();
+        analyze(&db, SAMPLE_RATE, Duration::from_secs(30))
+            .await
+            .unwrap();
 
         // check that all the songs have analyses
         assert_eq!(
@@ -702,6 +1676,135 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_analyze_records_failures() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // load a song into the database, then corrupt its file
+        let metadata = create_song_metadata(&dir, arb_song_case()()).unwrap();
+        let song = Song::try_load_into_db(&db, metadata.clone()).await.unwrap();
+        let original = std::fs::read(&metadata.path).unwrap();
+        std::fs::write(&metadata.path, b"not actually audio").unwrap();
+
+        // each analysis records another failed attempt, until the song is given up on
+        for attempt in 1..=MAX_ATTEMPTS {
+            analyze(&db, SAMPLE_RATE, Duration::from_secs(30))
+                .await
+                .unwrap();
+            let failed = FailedAnalysis::read_for_song(&db, song.id.clone())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(failed.attempt_count, attempt);
+        }
+        analyze(&db, SAMPLE_RATE, Duration::from_secs(30))
+            .await
+            .unwrap();
+        let failed = FailedAnalysis::read_for_song(&db, song.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.attempt_count, MAX_ATTEMPTS);
+        assert!(Analysis::read_for_song(&db, song.id.clone())
+            .await
+            .unwrap()
+            .is_none());
+
+        // once the file is fixed, retrying it manually works and clears the failure
+        std::fs::write(&metadata.path, original).unwrap();
+        retry_failed_analysis(&db, vec![failed.id], SAMPLE_RATE, Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert!(Analysis::read_for_song(&db, song.id.clone())
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            FailedAnalysis::read_for_song(&db, song.id).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compute_replaygain() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // load some songs into the database
+        let song_cases = arb_vec(&arb_song_case(), 3..=5)();
+        let song_cases = song_cases.into_iter().enumerate().map(|(i, sc)| SongCase {
+            song: i as u8,
+            ..sc
+        });
+        let mut song_ids = Vec::new();
+        for song_case in song_cases {
+            let metadata = create_song_metadata(&dir, song_case).unwrap();
+            let song = Song::try_load_into_db(&db, metadata).await.unwrap();
+            assert_eq!(song.replaygain_track_gain, None);
+            song_ids.push(song.id);
+        }
+
+        // compute the replaygain of all the songs (and one that doesn't exist)
+        let mut progress = Vec::new();
+        compute_replaygain(
+            &db,
+            [song_ids.clone(), vec![Song::generate_id()]].concat(),
+            |p| progress.push(p.clone()),
+        )
+        .await
+        .unwrap();
+
+        // check that the progress was reported, ending with every song done
+        assert_eq!(progress.len(), song_ids.len() + 1);
+        assert!(progress
+            .windows(2)
+            .all(|pair| pair[0].scanned < pair[1].scanned));
+        let last = progress.last().unwrap();
+        assert_eq!(last.scanned, song_ids.len() as u64);
+        assert_eq!(last.total, song_ids.len() as u64);
+        assert_eq!(last.errors, 0);
+
+        // check that all the songs have a replaygain
+        for id in song_ids {
+            let song = Song::read(&db, id).await.unwrap().unwrap();
+            assert!(song.replaygain_track_gain.is_some());
+            assert!(song.replaygain_album_gain.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compute_replaygain_album_gain() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // load some songs on the same album into the database
+        let mut song_ids = Vec::new();
+        for i in 0..3 {
+            let metadata =
+                create_song_metadata(&dir, SongCase::new(i, vec![0], vec![0], 0, 0)).unwrap();
+            let song = Song::try_load_into_db(&db, metadata).await.unwrap();
+            song_ids.push(song.id);
+        }
+
+        // computing the replaygain of one of the songs computes it for the whole album
+        compute_replaygain(&db, vec![song_ids[0].clone()], |_| {})
+            .await
+            .unwrap();
+
+        let mut album_gains = Vec::new();
+        for id in song_ids {
+            let song = Song::read(&db, id).await.unwrap().unwrap();
+            assert!(song.replaygain_track_gain.is_some());
+            album_gains.push(song.replaygain_album_gain);
+        }
+        assert!(album_gains[0].is_some());
+        assert!(album_gains.iter().all(|gain| *gain == album_gains[0]));
+    }
+
     #[tokio::test]
     async fn test_recluster() {
         init();
@@ -744,7 +1847,7 @@ mod tests {
         }
 
         // recluster the library
-        recluster(&db, &settings).await.unwrap();
+        recluster(&db, &settings, None).await.unwrap();
 
         // check that there are collections
         let collections = Collection::read_all(&db).await.unwrap();
@@ -755,6 +1858,148 @@ mod tests {
                 .unwrap();
             assert!(!songs.is_empty());
         }
+
+        // recluster again with a fixed number of clusters,
+        // the old collections should be replaced rather than added to
+        recluster(&db, &settings, Some(4)).await.unwrap();
+
+        let collections = Collection::read_all(&db).await.unwrap();
+        assert!(!collections.is_empty());
+        assert!(collections.len() <= 4);
+        let mut song_count = 0;
+        for collection in collections {
+            let songs = Collection::read_songs(&db, collection.id.clone())
+                .await
+                .unwrap();
+            assert!(!songs.is_empty());
+            song_count += songs.len();
+        }
+        assert_eq!(song_count, songs.len());
+    }
+
+    #[tokio::test]
+    async fn test_auto_update_collections() {
+        init();
+        let db = init_test_database().await.unwrap();
+
+        // a collection with one song, and a song that isn't in any collection
+        let mut songs = Vec::new();
+        for features in [[0.0; NUMBER_FEATURES], [0.1; NUMBER_FEATURES]] {
+            let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                .await
+                .unwrap();
+            Analysis::create(
+                &db,
+                song.id.clone(),
+                Analysis {
+                    id: Analysis::generate_id(),
+                    features,
+                },
+            )
+            .await
+            .unwrap();
+            songs.push(song);
+        }
+        let collection = Collection::create(
+            &db,
+            Collection {
+                id: Collection::generate_id(),
+                name: "Collection 0".into(),
+                runtime: Duration::from_secs(0),
+                song_count: 0,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        Collection::add_songs(&db, collection.id.clone(), vec![songs[0].id.clone()])
+            .await
+            .unwrap();
+
+        auto_update_collections(&db, 1.0).await.unwrap();
+
+        let mut collection_songs = Collection::read_songs(&db, collection.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|song| song.id)
+            .collect::<Vec<_>>();
+        collection_songs.sort();
+        let mut expected = songs.into_iter().map(|song| song.id).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(collection_songs, expected);
+    }
+
+    #[tokio::test]
+    async fn test_collection_analysis_stats() {
+        init();
+        let db = init_test_database().await.unwrap();
+
+        let collection = Collection::create(
+            &db,
+            Collection {
+                id: Collection::generate_id(),
+                name: "Collection 0".into(),
+                runtime: Duration::from_secs(0),
+                song_count: 0,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        // no songs, no stats
+        assert_eq!(
+            collection_analysis_stats(&db, collection.id.clone())
+                .await
+                .unwrap(),
+            None
+        );
+
+        // two analyzed songs, and one that hasn't been analyzed
+        let mut songs = Vec::new();
+        for features in [
+            Some([0.0; NUMBER_FEATURES]),
+            Some([0.5; NUMBER_FEATURES]),
+            None,
+        ] {
+            let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                .await
+                .unwrap();
+            if let Some(features) = features {
+                Analysis::create(
+                    &db,
+                    song.id.clone(),
+                    Analysis {
+                        id: Analysis::generate_id(),
+                        features,
+                    },
+                )
+                .await
+                .unwrap();
+            }
+            songs.push(song.id);
+        }
+        Collection::add_songs(&db, collection.id.clone(), songs)
+            .await
+            .unwrap();
+
+        let stats = collection_analysis_stats(&db, collection.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // the features denormalize to 103 and 154.5 BPM, and -45 and -22.5 dB
+        assert_eq!(
+            stats,
+            CollectionAnalysisStats {
+                analyzed_songs: 2,
+                mean_bpm: 128.75,
+                tempo_std: 25.75,
+                mean_loudness: -33.75,
+                chroma_centroid: [0.25; 10],
+            }
+        );
     }
 
     #[tokio::test]
@@ -800,4 +2045,243 @@ mod tests {
         assert_eq!(health.orphaned_playlists, 0);
         assert_eq!(health.orphaned_collections, 0);
     }
+
+    #[tokio::test]
+    async fn test_statistics() {
+        init();
+        let db = init_test_database().await.unwrap();
+        assert_eq!(statistics(&db).await.unwrap(), LibraryStatistics::default());
+
+        let song = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                runtime: Some(Duration::from_secs(90)),
+                genre: Some(OneOrMany::One("Rock".into())),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut features = [0.; NUMBER_FEATURES];
+        features[AnalysisIndex::Tempo as usize] = BPMDesc::normalize_bpm(120.);
+        Analysis::create(
+            &db,
+            song.id.clone(),
+            Analysis {
+                id: Analysis::generate_id(),
+                features,
+            },
+        )
+        .await
+        .unwrap();
+        create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                runtime: Some(Duration::from_secs(30)),
+                genre: Some(OneOrMany::One("Rock".into())),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let statistics = statistics(&db).await.unwrap();
+        assert_eq!(statistics.total_songs, 2);
+        assert_eq!(statistics.total_runtime, Duration::from_secs(120));
+        assert_eq!(statistics.songs_with_analysis, 1);
+        assert_eq!(statistics.songs_without_analysis, 1);
+        assert!((statistics.average_bpm.unwrap() - 120.).abs() < 1e-6);
+        assert_eq!(statistics.most_common_genre.as_deref(), Some("Rock"));
+        assert_eq!(statistics.unique_genres, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dead_links() {
+        init();
+        let tempdir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // a song whose file exists, and one whose file was deleted
+        let metadata = create_song_metadata(&tempdir, arb_song_case()()).unwrap();
+        let alive = Song::try_load_into_db(&db, metadata).await.unwrap();
+        let metadata = create_song_metadata(&tempdir, arb_song_case()()).unwrap();
+        let dead = Song::try_load_into_db(&db, metadata).await.unwrap();
+        std::fs::remove_file(&dead.path).unwrap();
+
+        assert_eq!(dead_links(&db).await.unwrap(), vec![dead.clone()]);
+
+        assert_eq!(remove_dead_links(&db).await.unwrap(), 1);
+        assert_eq!(Song::read(&db, dead.id).await.unwrap(), None);
+        assert_eq!(
+            Song::read(&db, alive.id.clone()).await.unwrap(),
+            Some(alive)
+        );
+        assert!(dead_links(&db).await.unwrap().is_empty());
+        assert_eq!(remove_dead_links(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicates() {
+        init();
+        let db = init_test_database().await.unwrap();
+
+        let mut songs = Vec::new();
+        for (title, runtime) in [
+            ("Duplicate", 180),
+            ("duplicate", 181),
+            ("Duplicate", 190),
+            ("Original", 180),
+        ] {
+            let song = create_song_with_overrides(
+                &db,
+                arb_song_case()(),
+                SongChangeSet {
+                    title: Some(title.into()),
+                    artist: Some(OneOrMany::One("Artist".into())),
+                    runtime: Some(Duration::from_secs(runtime)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            songs.push(song);
+        }
+
+        let groups = find_duplicates(&db).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        let mut expected = vec![songs[0].clone(), songs[1].clone()];
+        expected.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(groups[0].songs.as_ref(), expected.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_find_acoustid_duplicates() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // the test songs are all copies of the same audio, with different tags
+        let song_cases = arb_vec(&arb_song_case(), 2..=3)();
+        let song_cases = song_cases.into_iter().enumerate().map(|(i, sc)| SongCase {
+            song: i as u8,
+            ..sc
+        });
+        let mut songs = Vec::new();
+        for song_case in song_cases {
+            let metadata = create_song_metadata(&dir, song_case).unwrap();
+            songs.push(Song::try_load_into_db(&db, metadata).await.unwrap());
+        }
+
+        let groups = find_acoustid_duplicates(&db).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        let mut expected = groups[0]
+            .songs
+            .iter()
+            .map(|song| song.id.clone())
+            .collect::<Vec<_>>();
+        expected.sort();
+        let mut song_ids = songs.into_iter().map(|song| song.id).collect::<Vec<_>>();
+        song_ids.sort();
+        assert_eq!(expected, song_ids);
+
+        // the fingerprints were stored
+        for song in groups[0].songs.iter() {
+            assert!(Fingerprint::read_for_song(&db, song.id.clone())
+                .await
+                .unwrap()
+                .is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_acoustid_duplicates_reencoded() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        // the same recording as a lossless and a lossy file, and a different recording of about the same length
+        let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../analysis/data");
+        let mut songs = Vec::new();
+        for file in [
+            "s32_stereo_44_1_kHz.flac",
+            "s32_stereo_44_1_kHz.mp3",
+            "white_noise.mp3",
+        ] {
+            let path = dir.path().join(file);
+            std::fs::copy(data_dir.join(file), &path).unwrap();
+            let metadata = SongMetadata::load_from_path(
+                path,
+                &OneOrMany::One(ARTIST_NAME_SEPARATOR.to_string()),
+                None,
+            )
+            .unwrap();
+            songs.push(Song::try_load_into_db(&db, metadata).await.unwrap());
+        }
+
+        let groups = find_acoustid_duplicates(&db).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        let mut expected = vec![songs[0].clone(), songs[1].clone()];
+        expected.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(groups[0].songs.as_ref(), expected.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        init();
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_test_database().await.unwrap();
+
+        let mut songs = Vec::new();
+        for song in 0..3 {
+            let song_case = SongCase {
+                song,
+                ..arb_song_case()()
+            };
+            let metadata = create_song_metadata(&dir, song_case).unwrap();
+            songs.push(Song::try_load_into_db(&db, metadata).await.unwrap());
+        }
+        let [ok, corrupted, missing] = songs.as_slice() else {
+            panic!("expected 3 songs");
+        };
+        std::fs::write(&corrupted.path, b"not actually audio").unwrap();
+        std::fs::remove_file(&missing.path).unwrap();
+
+        // verify all the songs
+        let mut results = verify(&db, None).await.unwrap();
+        results.sort_by_key(|result| result.id.to_string());
+        let mut expected = vec![
+            SongVerificationResult {
+                id: ok.id.clone(),
+                status: VerificationStatus::Ok,
+            },
+            SongVerificationResult {
+                id: corrupted.id.clone(),
+                status: VerificationStatus::Corrupted,
+            },
+            SongVerificationResult {
+                id: missing.id.clone(),
+                status: VerificationStatus::Missing,
+            },
+        ];
+        expected.sort_by_key(|result| result.id.to_string());
+        assert_eq!(results, expected);
+
+        // verify specific songs, ids that aren't in the library are skipped
+        let results = verify(&db, Some(vec![missing.id.clone(), Song::generate_id()]))
+            .await
+            .unwrap();
+        assert_eq!(
+            results,
+            vec![SongVerificationResult {
+                id: missing.id.clone(),
+                status: VerificationStatus::Missing,
+            }]
+        );
+
+        // an empty library has nothing to verify
+        let db = init_test_database().await.unwrap();
+        assert_eq!(verify(&db, None).await.unwrap(), vec![]);
+    }
 }
@@ -1,4 +1,5 @@
 use log::warn;
+use mecomp_core::state::StateChange;
 use mecomp_storage::{
     db::schemas::{
         album::{Album, TABLE_NAME as ALBUM_TABLE_NAME},
@@ -12,8 +13,13 @@ use mecomp_storage::{
 };
 use one_or_many::OneOrMany;
 use surrealdb::{Connection, Surreal};
+use tokio::sync::mpsc::UnboundedReceiver;
 
+pub mod backup;
 pub mod library;
+pub mod playlist;
+#[cfg(feature = "analysis")]
+pub mod queue;
 #[cfg(feature = "analysis")]
 pub mod radio;
 
@@ -70,3 +76,67 @@ pub async fn get_songs_from_things<C: Connection>(
 
     Ok(songs)
 }
+
+/// Update the library in response to changes in the state of the audio kernel.
+///
+/// Runs until the audio kernel stops reporting changes (i.e. the sender is dropped).
+pub async fn handle_state_changes<C: Connection>(
+    db: &Surreal<C>,
+    mut events: UnboundedReceiver<StateChange>,
+) {
+    while let Some(event) = events.recv().await {
+        match event {
+            StateChange::SongFinished(id) => {
+                match Song::increment_play_count(db, id.clone()).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => warn!("Finished song {id} is not in the library"),
+                    Err(e) => warn!("Failed to record that {id} was played: {e}"),
+                }
+            }
+            StateChange::SongSkipped(id) => {
+                match Song::increment_skip_count(db, id.clone()).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => warn!("Skipped song {id} is not in the library"),
+                    Err(e) => warn!("Failed to record that {id} was skipped: {e}"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::init;
+
+    use mecomp_storage::{
+        db::schemas::song::SongChangeSet,
+        test_utils::{arb_song_case, create_song_with_overrides, init_test_database},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_handle_state_changes() {
+        init();
+        let db = init_test_database().await.unwrap();
+        let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+            .await
+            .unwrap();
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tx.send(StateChange::SongFinished(song.id.clone())).unwrap();
+        tx.send(StateChange::SongSkipped(song.id.clone())).unwrap();
+        tx.send(StateChange::SongFinished(song.id.clone())).unwrap();
+        // songs that aren't in the library are ignored
+        tx.send(StateChange::SongFinished(Song::generate_id()))
+            .unwrap();
+        drop(tx);
+
+        handle_state_changes(&db, rx).await;
+
+        let song = Song::read(&db, song.id).await.unwrap().unwrap();
+        assert_eq!(song.play_count, 2);
+        assert_eq!(song.skip_count, 1);
+        assert!(song.last_played.is_some());
+    }
+}
@@ -0,0 +1,963 @@
+//! Services for importing playlists from, and exporting playlists to, other music players.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use log::{debug, info, warn};
+use mecomp_core::{
+    errors::SerializableLibraryError,
+    rpc::{PlaylistExportReport, PlaylistImportReport},
+};
+use mecomp_storage::{
+    db::schemas::{
+        playlist::{Playlist, PlaylistId},
+        song::{Song, SongId},
+    },
+    errors::Error,
+};
+use surrealdb::{Connection, Surreal};
+use tracing::instrument;
+use url::Url;
+
+/// The column headers used by Spotify playlist exports (e.g. from Exportify).
+const SPOTIFY_COLUMNS: (&str, &[&str]) = ("Track Name", &["Artist Name", "Artist Name(s)"]);
+/// The column headers used by Apple Music playlist exports.
+const APPLE_MUSIC_COLUMNS: (&str, &[&str]) = ("Title", &["Artist"]);
+
+/// A track read from a playlist export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImportedTrack {
+    title: String,
+    artist: String,
+}
+
+/// Read the tracks from a Spotify or Apple Music CSV export.
+///
+/// The format is detected from the column headers.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read, isn't valid CSV,
+/// or doesn't have the columns of a known export format.
+fn read_tracks(path: &Path) -> Result<Vec<ImportedTrack>, SerializableLibraryError> {
+    let mut reader = csv::Reader::from_path(path)
+        .map_err(|e| SerializableLibraryError::Import(e.to_string()))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| SerializableLibraryError::Import(e.to_string()))?
+        .clone();
+
+    let find_column = |name: &str| headers.iter().position(|header| header.trim() == name);
+    let (title_column, artist_column) = [SPOTIFY_COLUMNS, APPLE_MUSIC_COLUMNS]
+        .into_iter()
+        .find_map(|(title, artists)| {
+            let title = find_column(title)?;
+            let artist = artists.iter().find_map(|artist| find_column(artist))?;
+            Some((title, artist))
+        })
+        .ok_or_else(|| {
+            SerializableLibraryError::Import(
+                "unrecognized CSV format, expected a Spotify or Apple Music export".to_string(),
+            )
+        })?;
+
+    let mut tracks = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| SerializableLibraryError::Import(e.to_string()))?;
+        let title = record.get(title_column).unwrap_or_default().trim();
+        if title.is_empty() {
+            continue;
+        }
+        tracks.push(ImportedTrack {
+            title: title.to_string(),
+            artist: record
+                .get(artist_column)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        });
+    }
+
+    Ok(tracks)
+}
+
+/// How similar a song is to an imported track, from 0.0 (nothing alike) to 1.0 (identical).
+///
+/// This is the average of the similarity of the titles and the similarity of the artists,
+/// where the artist similarity is the best match among the song's artists (or all of them together).
+fn similarity(track: &ImportedTrack, song: &Song) -> f64 {
+    let title = strsim::jaro_winkler(&track.title.to_lowercase(), &song.title.to_lowercase());
+
+    let track_artist = track.artist.to_lowercase();
+    let all_artists = song
+        .artist
+        .as_slice()
+        .iter()
+        .map(|artist| artist.to_lowercase())
+        .collect::<Vec<_>>();
+    let artist = all_artists
+        .iter()
+        .map(|artist| strsim::jaro_winkler(&track_artist, artist))
+        .chain(std::iter::once(strsim::jaro_winkler(
+            &track_artist,
+            &all_artists.join(", "),
+        )))
+        .fold(0.0, f64::max);
+
+    (title + artist) / 2.0
+}
+
+/// Find the song in the library that best matches the given track,
+/// if one has a similarity of at least `threshold`.
+fn best_match<'a>(track: &ImportedTrack, songs: &'a [Song], threshold: f64) -> Option<&'a Song> {
+    songs
+        .iter()
+        .map(|song| (similarity(track, song), song))
+        .filter(|(score, _)| *score >= threshold)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, song)| song)
+}
+
+/// Import a playlist from a Spotify or Apple Music CSV export.
+///
+/// Every track in the export is fuzzy matched (by title and artist) against the songs in the library,
+/// tracks whose best match has a similarity below `threshold` are reported as unmatched.
+///
+/// A new playlist, named after the file, is created with the matched songs.
+/// If a playlist with that name already exists, a number is appended to the name.
+///
+/// # Errors
+///
+/// Returns an error if the export can't be read or parsed,
+/// or if there is an error reading from or writing to the database.
+#[instrument]
+pub async fn import_csv<C: Connection>(
+    db: &Surreal<C>,
+    path: &Path,
+    threshold: f64,
+) -> Result<PlaylistImportReport, SerializableLibraryError> {
+    let tracks = read_tracks(path)?;
+    info!("Importing {} tracks from {}", tracks.len(), path.display());
+
+    let songs = Song::read_all(db).await?;
+
+    let mut matched: Vec<SongId> = Vec::new();
+    let mut unmatched = Vec::new();
+    for track in &tracks {
+        if let Some(song) = best_match(track, &songs, threshold) {
+            debug!(
+                "Matched \"{} - {}\" to {}",
+                track.artist, track.title, song.id
+            );
+            matched.push(song.id.clone());
+        } else {
+            unmatched.push(format!("{} - {}", track.artist, track.title));
+        }
+    }
+
+    create_imported_playlist(db, path, matched, unmatched).await
+}
+
+/// Create a playlist, named after the file it was imported from, with the matched songs.
+/// If a playlist with that name already exists, a number is appended to the name.
+async fn create_imported_playlist<C: Connection>(
+    db: &Surreal<C>,
+    path: &Path,
+    matched: Vec<SongId>,
+    unmatched: Vec<String>,
+) -> Result<PlaylistImportReport, SerializableLibraryError> {
+    // find a name for the playlist that isn't already taken
+    let base_name = path
+        .file_stem()
+        .map_or_else(|| "Imported Playlist".into(), |stem| stem.to_string_lossy());
+    let mut name = base_name.to_string();
+    let mut suffix = 1;
+    while Playlist::read_by_name(db, name.clone()).await?.is_some() {
+        suffix += 1;
+        name = format!("{base_name} ({suffix})");
+    }
+
+    let playlist = Playlist::create(
+        db,
+        Playlist {
+            id: Playlist::generate_id(),
+            name: name.into(),
+            runtime: Duration::from_secs(0),
+            song_count: 0,
+        },
+    )
+    .await?
+    .ok_or(Error::NotCreated)?;
+
+    let matched_count = u32::try_from(matched.len()).unwrap_or(u32::MAX);
+    if !matched.is_empty() {
+        Playlist::add_songs(db, playlist.id.clone(), matched).await?;
+    }
+
+    info!(
+        "Imported playlist \"{}\": {matched_count} matched, {} unmatched",
+        playlist.name,
+        unmatched.len()
+    );
+
+    Ok(PlaylistImportReport {
+        playlist_id: playlist.id.into(),
+        matched: matched_count,
+        unmatched,
+    })
+}
+
+/// The playlist file formats that can be imported and exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    /// (Extended) M3U, one path per line
+    M3u,
+    /// XML Shareable Playlist Format
+    Xspf,
+}
+
+impl PlaylistFormat {
+    /// Detect the format of a playlist file from its extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the extension isn't that of a supported format.
+    pub fn from_path(path: &Path) -> Result<Self, SerializableLibraryError> {
+        match path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("m3u" | "m3u8") => Ok(Self::M3u),
+            Some("xspf") => Ok(Self::Xspf),
+            _ => Err(SerializableLibraryError::UnsupportedPlaylistFormat(
+                path.display().to_string(),
+            )),
+        }
+    }
+}
+
+/// Read the paths of the tracks in an M3U playlist.
+///
+/// Relative paths are resolved against `base` (the directory the playlist is in),
+/// and `.` and `..` components are removed so the paths can be matched against those in the library.
+fn read_m3u(contents: &str, base: &Path) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            if line.starts_with("file://") {
+                Url::parse(line).ok()?.to_file_path().ok()
+            } else {
+                Some(normalize_path(&base.join(line)))
+            }
+        })
+        .collect()
+}
+
+/// Lexically remove the `.` and `..` components of a path, without touching the filesystem.
+///
+/// Like a URL join, `..` at the root stays at the root.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) {
+                    normalized.pop();
+                } else if !normalized.has_root() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The contents of every `<tag>...</tag>` element in `xml` (not including nested elements of the same name).
+///
+/// The opening tag may have attributes (e.g. `<trackList xmlns="...">`), self-closing elements have no contents.
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> impl Iterator<Item = &'a str> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut rest = xml;
+    std::iter::from_fn(move || loop {
+        let after_name = rest.find(&open)? + open.len();
+        // make sure we matched the whole name, and not a prefix of another tag's (e.g. `<track` in `<trackList>`)
+        if !rest[after_name..].starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            rest = &rest[after_name..];
+            continue;
+        }
+        let start = after_name + rest[after_name..].find('>')? + 1;
+        if rest[..start].ends_with("/>") {
+            rest = &rest[start..];
+            return Some("");
+        }
+        let end = start + rest[start..].find(&close)?;
+        let contents = &rest[start..end];
+        rest = &rest[end + close.len()..];
+        return Some(contents);
+    })
+}
+
+/// Replace the predefined XML entities with the characters they stand for.
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Escape the characters that can't appear as-is in XML text.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Read the paths of the tracks in an XSPF playlist (the `<location>` of each `<track>` in the `<trackList>`).
+///
+/// Locations are URIs, relative ones are resolved against `base` (the directory the playlist is in).
+/// Tracks without a location, or whose location isn't a local file (e.g. a stream), are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the playlist doesn't have a `<trackList>`.
+fn read_xspf(contents: &str, base: &Path) -> Result<Vec<PathBuf>, SerializableLibraryError> {
+    let track_list = xml_elements(contents, "trackList").next().ok_or_else(|| {
+        SerializableLibraryError::Import("invalid XSPF playlist, no <trackList> found".to_string())
+    })?;
+    let base = Url::from_directory_path(base).ok();
+
+    Ok(xml_elements(track_list, "track")
+        .filter_map(|track| xml_elements(track, "location").next())
+        .map(str::trim)
+        .filter(|location| !location.is_empty())
+        .filter_map(|location| {
+            let location = unescape_xml(location);
+            let url = match &base {
+                Some(base) => base.join(&location),
+                None => Url::parse(&location),
+            };
+            url.ok()?.to_file_path().ok()
+        })
+        .collect())
+}
+
+/// Import a playlist from an M3U or XSPF file, the format is detected from the file extension.
+///
+/// The tracks in the playlist are matched to the songs in the library by their path,
+/// tracks that aren't in the library are reported as unmatched.
+///
+/// A new playlist, named after the file, is created with the matched songs.
+/// If a playlist with that name already exists, a number is appended to the name.
+///
+/// # Errors
+///
+/// Returns an error if the format isn't supported, the file can't be read or parsed,
+/// or if there is an error reading from or writing to the database.
+#[instrument]
+pub async fn import_file<C: Connection>(
+    db: &Surreal<C>,
+    path: &Path,
+) -> Result<PlaylistImportReport, SerializableLibraryError> {
+    let format = PlaylistFormat::from_path(path)?;
+    let contents = std::fs::read_to_string(path)?;
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    let locations = match format {
+        PlaylistFormat::M3u => read_m3u(&contents, base),
+        PlaylistFormat::Xspf => read_xspf(&contents, base)?,
+    };
+    info!(
+        "Importing {} tracks from {}",
+        locations.len(),
+        path.display()
+    );
+
+    let mut matched: Vec<SongId> = Vec::new();
+    let mut unmatched = Vec::new();
+    for location in locations {
+        if let Some(song) = Song::read_by_path(db, location.clone()).await? {
+            matched.push(song.id);
+        } else {
+            unmatched.push(location.display().to_string());
+        }
+    }
+
+    create_imported_playlist(db, path, matched, unmatched).await
+}
+
+/// Characters that aren't allowed in file names on at least one of the platforms we support.
+const INVALID_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Turn a playlist name into something that can safely be used as a file name.
+///
+/// Invalid characters (and control characters) are replaced with underscores,
+/// and leading/trailing whitespace and trailing dots are removed.
+fn sanitize_filename(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .map(|c| {
+            if INVALID_FILENAME_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    let sanitized = sanitized.trim().trim_end_matches('.');
+
+    if sanitized.is_empty() {
+        "Playlist".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Render the given songs as an extended M3U playlist.
+fn to_m3u(songs: &[Song]) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for song in songs {
+        let _ = writeln!(
+            m3u,
+            "#EXTINF:{},{} - {}",
+            song.runtime.as_secs(),
+            song.artist.as_slice().join(", "),
+            song.title
+        );
+        let _ = writeln!(m3u, "{}", song.path.display());
+    }
+    m3u
+}
+
+/// Render the given songs as an XSPF playlist with the given title.
+fn to_xspf(title: &str, songs: &[Song]) -> String {
+    let mut xspf = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n",
+    );
+    let _ = writeln!(xspf, "  <title>{}</title>", escape_xml(title));
+    xspf.push_str("  <trackList>\n");
+    for song in songs {
+        // song paths are absolute, so this only fails on paths that are invalid for the platform
+        let location = Url::from_file_path(&song.path)
+            .map_or_else(|()| song.path.display().to_string(), String::from);
+        xspf.push_str("    <track>\n");
+        let _ = writeln!(xspf, "      <location>{}</location>", escape_xml(&location));
+        let _ = writeln!(xspf, "      <title>{}</title>", escape_xml(&song.title));
+        let _ = writeln!(
+            xspf,
+            "      <creator>{}</creator>",
+            escape_xml(&song.artist.as_slice().join(", "))
+        );
+        let _ = writeln!(xspf, "      <album>{}</album>", escape_xml(&song.album));
+        let _ = writeln!(
+            xspf,
+            "      <duration>{}</duration>",
+            song.runtime.as_millis()
+        );
+        xspf.push_str("    </track>\n");
+    }
+    xspf.push_str("  </trackList>\n</playlist>\n");
+    xspf
+}
+
+/// Export the songs of a playlist to an M3U file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if there is an error reading from the database, or writing the file.
+#[instrument]
+pub async fn export_m3u<C: Connection>(
+    db: &Surreal<C>,
+    playlist: &Playlist,
+    path: &Path,
+) -> Result<(), SerializableLibraryError> {
+    let songs = Playlist::read_songs(db, playlist.id.clone()).await?;
+    std::fs::write(path, to_m3u(&songs))?;
+    debug!(
+        "Exported playlist \"{}\" ({} songs) to {}",
+        playlist.name,
+        songs.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Export a playlist to an M3U or XSPF file at `path`, the format is detected from the file extension.
+///
+/// # Errors
+///
+/// Returns an error if the format isn't supported, the playlist doesn't exist,
+/// or if there is an error reading from the database, or writing the file.
+#[instrument]
+pub async fn export_file<C: Connection>(
+    db: &Surreal<C>,
+    id: PlaylistId,
+    path: &Path,
+) -> Result<(), SerializableLibraryError> {
+    let format = PlaylistFormat::from_path(path)?;
+    let playlist = Playlist::read(db, id).await?.ok_or(Error::NotFound)?;
+
+    match format {
+        PlaylistFormat::M3u => export_m3u(db, &playlist, path).await,
+        PlaylistFormat::Xspf => {
+            let songs = Playlist::read_songs(db, playlist.id.clone()).await?;
+            std::fs::write(path, to_xspf(&playlist.name, &songs))?;
+            debug!(
+                "Exported playlist \"{}\" ({} songs) to {}",
+                playlist.name,
+                songs.len(),
+                path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Export every playlist in the library to `<dir>/<playlist name>.m3u`.
+///
+/// The directory is created if it doesn't exist.
+/// Playlist names are sanitized to be valid file names, and if two playlists end up with the same file name,
+/// a number is appended to the latter.
+/// If `overwrite` is false, playlists whose file already exists are skipped.
+///
+/// Errors exporting individual playlists don't stop the export, they are collected in the report instead.
+///
+/// # Errors
+///
+/// Returns an error if the directory can't be created, or the playlists can't be read from the database.
+#[instrument]
+pub async fn export_all_m3u<C: Connection>(
+    db: &Surreal<C>,
+    dir: &Path,
+    overwrite: bool,
+) -> Result<PlaylistExportReport, SerializableLibraryError> {
+    std::fs::create_dir_all(dir)?;
+
+    let playlists = Playlist::read_all(db).await?;
+    info!(
+        "Exporting {} playlists to {}",
+        playlists.len(),
+        dir.display()
+    );
+
+    let mut report = PlaylistExportReport::default();
+    let mut used_names = HashSet::new();
+    for playlist in &playlists {
+        // find a file name that another playlist in this export hasn't already taken
+        let base_name = sanitize_filename(&playlist.name);
+        let mut name = base_name.clone();
+        let mut suffix = 1;
+        while !used_names.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{base_name} ({suffix})");
+        }
+        let path: PathBuf = dir.join(format!("{name}.m3u"));
+
+        if !overwrite && path.exists() {
+            debug!("Skipping {}, file already exists", path.display());
+            report.skipped += 1;
+            continue;
+        }
+
+        match export_m3u(db, playlist, &path).await {
+            Ok(()) => report.exported += 1,
+            Err(e) => {
+                warn!("Failed to export playlist \"{}\": {e}", playlist.name);
+                report.errors.push(format!("{}: {e}", playlist.name));
+            }
+        }
+    }
+
+    info!(
+        "Exported {} playlists ({} skipped, {} errors)",
+        report.exported,
+        report.skipped,
+        report.errors.len()
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::init;
+
+    use std::sync::Arc;
+
+    use mecomp_storage::{
+        db::schemas::song::SongChangeSet,
+        test_utils::{arb_song_case, create_song_with_overrides, init_test_database},
+    };
+    use one_or_many::OneOrMany;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn write_csv(dir: &tempfile::TempDir, name: &str, contents: &str) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[rstest]
+    #[case::spotify(
+        "Track URI,Track Name,Artist Name,Album Name\nspotify:track:1,Foo,Bar,Baz\nspotify:track:2,Hello,World,Album\n"
+    )]
+    #[case::exportify(
+        "Track URI,Track Name,Artist Name(s),Album Name\nspotify:track:1,Foo,Bar,Baz\nspotify:track:2,Hello,World,Album\n"
+    )]
+    #[case::apple_music("Title,Artist,Album\nFoo,Bar,Baz\nHello,World,Album\n")]
+    fn test_read_tracks(#[case] contents: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_csv(&dir, "playlist.csv", contents);
+
+        let tracks = read_tracks(&path).unwrap();
+        assert_eq!(
+            tracks,
+            vec![
+                ImportedTrack {
+                    title: "Foo".to_string(),
+                    artist: "Bar".to_string(),
+                },
+                ImportedTrack {
+                    title: "Hello".to_string(),
+                    artist: "World".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_tracks_unknown_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_csv(&dir, "playlist.csv", "Name,Singer\nFoo,Bar\n");
+
+        assert!(matches!(
+            read_tracks(&path),
+            Err(SerializableLibraryError::Import(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_import_csv() {
+        init();
+        let db = init_test_database().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let song = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                title: Some(Arc::from("Bohemian Rhapsody")),
+                artist: Some(OneOrMany::One(Arc::from("Queen"))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let path = write_csv(
+            &dir,
+            "Road Trip.csv",
+            "Title,Artist\nBohemian Rhapsody (Remastered),Queen\nNever Gonna Give You Up,Rick Astley\n",
+        );
+
+        let report = import_csv(&db, &path, 0.85).await.unwrap();
+        assert_eq!(report.matched, 1);
+        assert_eq!(
+            report.unmatched,
+            vec!["Rick Astley - Never Gonna Give You Up".to_string()]
+        );
+
+        let playlist = Playlist::read(&db, report.playlist_id.clone().into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(playlist.name.as_ref(), "Road Trip");
+        let songs = Playlist::read_songs(&db, report.playlist_id.into())
+            .await
+            .unwrap();
+        assert_eq!(songs, vec![song]);
+
+        // importing the same file again shouldn't clobber the existing playlist
+        let report = import_csv(&db, &path, 0.85).await.unwrap();
+        let playlist = Playlist::read(&db, report.playlist_id.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(playlist.name.as_ref(), "Road Trip (2)");
+    }
+
+    #[rstest]
+    #[case::plain("Road Trip", "Road Trip")]
+    #[case::slashes("AC/DC Favorites", "AC_DC Favorites")]
+    #[case::reserved("What? <Live> \"2024\"", "What_ _Live_ _2024_")]
+    #[case::trailing("  Chill... ", "Chill")]
+    #[case::control("Tab\tSeparated", "Tab_Separated")]
+    #[case::empty("...", "Playlist")]
+    fn test_sanitize_filename(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(sanitize_filename(name), expected);
+    }
+
+    #[tokio::test]
+    async fn test_export_all_m3u() {
+        init();
+        let db = init_test_database().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let export_dir = dir.path().join("playlists");
+
+        let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+            .await
+            .unwrap();
+        for name in ["Road Trip", "AC/DC", "AC:DC"] {
+            let playlist = Playlist::create(
+                &db,
+                Playlist {
+                    id: Playlist::generate_id(),
+                    name: name.into(),
+                    runtime: Duration::from_secs(0),
+                    song_count: 0,
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            Playlist::add_songs(&db, playlist.id, vec![song.id.clone()])
+                .await
+                .unwrap();
+        }
+
+        let report = export_all_m3u(&db, &export_dir, true).await.unwrap();
+        assert_eq!(report.exported, 3);
+        assert_eq!(report.skipped, 0);
+        assert!(report.errors.is_empty());
+
+        let contents = std::fs::read_to_string(export_dir.join("Road Trip.m3u")).unwrap();
+        assert_eq!(contents, to_m3u(&[song]));
+        assert!(export_dir.join("AC_DC.m3u").exists());
+        assert!(export_dir.join("AC_DC (2).m3u").exists());
+
+        // existing files are skipped if we aren't overwriting
+        let report = export_all_m3u(&db, &export_dir, false).await.unwrap();
+        assert_eq!(report.exported, 0);
+        assert_eq!(report.skipped, 3);
+    }
+
+    #[rstest]
+    #[case::m3u("playlist.m3u", Some(PlaylistFormat::M3u))]
+    #[case::m3u8("playlist.m3u8", Some(PlaylistFormat::M3u))]
+    #[case::xspf("playlist.xspf", Some(PlaylistFormat::Xspf))]
+    #[case::uppercase("PLAYLIST.XSPF", Some(PlaylistFormat::Xspf))]
+    #[case::csv("playlist.csv", None)]
+    #[case::no_extension("playlist", None)]
+    fn test_playlist_format_from_path(
+        #[case] path: &str,
+        #[case] expected: Option<PlaylistFormat>,
+    ) {
+        let actual = PlaylistFormat::from_path(Path::new(path));
+        match expected {
+            Some(format) => assert_eq!(actual, Ok(format)),
+            None => assert!(matches!(
+                actual,
+                Err(SerializableLibraryError::UnsupportedPlaylistFormat(_))
+            )),
+        }
+    }
+
+    #[test]
+    fn test_read_m3u() {
+        let base = Path::new("/music/playlists");
+        let contents = "#EXTM3U\n#EXTINF:120,Artist - Title\n/music/song.mp3\n\n../album/song 2.flac\nfile:///music/song%203.ogg\n";
+
+        assert_eq!(
+            read_m3u(contents, base),
+            vec![
+                PathBuf::from("/music/song.mp3"),
+                PathBuf::from("/music/album/song 2.flac"),
+                PathBuf::from("/music/song 3.ogg"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_xspf() {
+        let base = Path::new("/music/playlists");
+        let contents = r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <title>Mix</title>
+  <trackList>
+    <track>
+      <location>file:///music/Rock%20&amp;%20Roll.mp3</location>
+      <title>Rock &amp; Roll</title>
+    </track>
+    <track>
+      <location>../album/song.flac</location>
+    </track>
+    <track>
+      <location>https://example.com/stream.mp3</location>
+    </track>
+    <track>
+      <title>No Location</title>
+    </track>
+  </trackList>
+</playlist>
+"#;
+
+        assert_eq!(
+            read_xspf(contents, base).unwrap(),
+            vec![
+                PathBuf::from("/music/Rock & Roll.mp3"),
+                PathBuf::from("/music/album/song.flac"),
+            ]
+        );
+
+        assert!(matches!(
+            read_xspf("<playlist></playlist>", base),
+            Err(SerializableLibraryError::Import(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_xspf_attributes() {
+        let base = Path::new("/music/playlists");
+        let contents = r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <trackList xmlns="http://xspf.org/ns/0/">
+    <track><location xml:base="file:///music/">../song.mp3</location></track>
+    <track><location/></track>
+    <track id="3">
+      <location
+        >song 2.mp3</location>
+    </track>
+  </trackList>
+</playlist>"#;
+
+        assert_eq!(
+            read_xspf(contents, base).unwrap(),
+            vec![
+                PathBuf::from("/music/song.mp3"),
+                PathBuf::from("/music/playlists/song 2.mp3"),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::already_normal("/music/song.mp3", "/music/song.mp3")]
+    #[case::parent("/music/playlists/../song.mp3", "/music/song.mp3")]
+    #[case::current("/music/./song.mp3", "/music/song.mp3")]
+    #[case::many_parents("/music/a/b/../../song.mp3", "/music/song.mp3")]
+    #[case::parent_of_root("/../song.mp3", "/song.mp3")]
+    #[case::relative("../a/../song.mp3", "../song.mp3")]
+    fn test_normalize_path(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(normalize_path(Path::new(path)), PathBuf::from(expected));
+    }
+
+    #[rstest]
+    #[case::m3u("Mix.m3u")]
+    #[case::xspf("Mix.xspf")]
+    #[tokio::test]
+    async fn test_export_import_file(#[case] file_name: &str) {
+        init();
+        let db = init_test_database().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let song = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                title: Some(Arc::from("Rock & Roll")),
+                path: Some(dir.path().join("Rock & Roll.mp3")),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let playlist = Playlist::create(
+            &db,
+            Playlist {
+                id: Playlist::generate_id(),
+                name: "<Mix>".into(),
+                runtime: Duration::from_secs(0),
+                song_count: 0,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        Playlist::add_songs(&db, playlist.id.clone(), vec![song.id.clone()])
+            .await
+            .unwrap();
+
+        let path = dir.path().join(file_name);
+        export_file(&db, playlist.id, &path).await.unwrap();
+
+        let report = import_file(&db, &path).await.unwrap();
+        assert_eq!(report.matched, 1);
+        assert!(report.unmatched.is_empty());
+        let playlist = Playlist::read(&db, report.playlist_id.clone().into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(playlist.name.as_ref(), "Mix");
+        let songs = Playlist::read_songs(&db, report.playlist_id.into())
+            .await
+            .unwrap();
+        assert_eq!(songs, vec![song]);
+    }
+
+    #[test]
+    fn test_to_xspf() {
+        let song = Song {
+            id: Song::generate_id(),
+            title: "Rock & Roll".into(),
+            artist: OneOrMany::Many(vec!["Foo".into(), "Bar".into()]),
+            album_artist: OneOrMany::One("Foo".into()),
+            album: "<Album>".into(),
+            genre: OneOrMany::None,
+            runtime: Duration::from_millis(123_456),
+            track: None,
+            disc: None,
+            release_year: None,
+            extension: "mp3".into(),
+            path: PathBuf::from("/music/Rock & Roll.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
+        };
+
+        assert_eq!(
+            to_xspf("Mix", &[song]),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <title>Mix</title>
+  <trackList>
+    <track>
+      <location>file:///music/Rock%20&amp;%20Roll.mp3</location>
+      <title>Rock &amp; Roll</title>
+      <creator>Foo, Bar</creator>
+      <album>&lt;Album&gt;</album>
+      <duration>123456</duration>
+    </track>
+  </trackList>
+</playlist>
+"#
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Ordering the queue using the audio analysis of its songs.
+
+use std::collections::HashSet;
+
+use mecomp_analysis::{
+    metrics::{DistanceMetric, EuclideanDistance},
+    AnalysisIndex, Feature, NUMBER_FEATURES,
+};
+use mecomp_core::state::SortStrategy;
+use mecomp_storage::{
+    db::schemas::{
+        analysis::Analysis,
+        song::{Song, SongId},
+    },
+    errors::StorageResult,
+};
+use surrealdb::{Connection, Surreal};
+use tracing::instrument;
+
+/// Rank the given songs using their analysis, in the order the strategy calls for.
+///
+/// Songs that haven't been analyzed are left out of the ranking,
+/// as is every song when sorting by similarity and the current song hasn't been analyzed (or there is no current song).
+///
+/// # Errors
+///
+/// Returns an error if there is an issue reading the analyses from the database
+#[instrument(skip(songs))]
+pub async fn rank_by_analysis<C: Connection>(
+    db: &Surreal<C>,
+    songs: &[Song],
+    current_song: Option<&Song>,
+    strategy: SortStrategy,
+) -> StorageResult<Vec<SongId>> {
+    let reference = match (strategy, current_song) {
+        (SortStrategy::BySimilarityToCurrentSong, Some(song)) => {
+            match Analysis::read_for_song(db, song.id.clone()).await? {
+                Some(analysis) => Some(analysis.features),
+                None => return Ok(Vec::new()),
+            }
+        }
+        (SortStrategy::BySimilarityToCurrentSong, None) => return Ok(Vec::new()),
+        _ => None,
+    };
+
+    // the queue can contain the same song more than once, but it only needs to be ranked once
+    let mut seen = HashSet::with_capacity(songs.len());
+    let ids = songs
+        .iter()
+        .map(|song| song.id.clone())
+        .filter(|id| seen.insert(id.clone()))
+        .collect::<Vec<_>>();
+    let analyses = Analysis::read_for_songs(db, ids.clone()).await?;
+
+    let mut ranking = ids
+        .into_iter()
+        .zip(analyses)
+        .filter_map(|(id, analysis)| {
+            analysis.map(|analysis| {
+                (
+                    id,
+                    sort_key(&analysis.features, strategy, reference.as_ref()),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    ranking.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    Ok(ranking.into_iter().map(|(id, _)| id).collect())
+}
+
+/// The value songs are sorted by (in ascending order) for the given strategy.
+///
+/// `reference` is the features of the current song, which are needed to sort by similarity.
+fn sort_key(
+    features: &[Feature; NUMBER_FEATURES],
+    strategy: SortStrategy,
+    reference: Option<&[Feature; NUMBER_FEATURES]>,
+) -> Feature {
+    match strategy {
+        SortStrategy::ByTempo => features[AnalysisIndex::Tempo as usize],
+        SortStrategy::ByEnergy => features[AnalysisIndex::MeanLoudness as usize],
+        SortStrategy::BySimilarityToCurrentSong => reference.map_or(0., |reference| {
+            EuclideanDistance.distance(features, reference)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::init;
+
+    use mecomp_storage::{
+        db::schemas::song::SongChangeSet,
+        test_utils::{arb_song_case, create_song_with_overrides, init_test_database},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// Features where the tempo and loudness are `tempo` and `loudness`, and every other feature is `other`.
+    fn features(tempo: Feature, loudness: Feature, other: Feature) -> [Feature; NUMBER_FEATURES] {
+        let mut features = [other; NUMBER_FEATURES];
+        features[AnalysisIndex::Tempo as usize] = tempo;
+        features[AnalysisIndex::MeanLoudness as usize] = loudness;
+        features
+    }
+
+    #[rstest]
+    #[case::tempo(SortStrategy::ByTempo, Some(0), vec![1, 2, 0])]
+    #[case::energy(SortStrategy::ByEnergy, Some(0), vec![0, 2, 1])]
+    #[case::similarity(SortStrategy::BySimilarityToCurrentSong, Some(2), vec![2, 0, 1])]
+    #[case::similarity_nothing_playing(SortStrategy::BySimilarityToCurrentSong, None, vec![])]
+    #[case::similarity_current_not_analyzed(SortStrategy::BySimilarityToCurrentSong, Some(3), vec![])]
+    #[tokio::test]
+    async fn test_rank_by_analysis(
+        #[case] strategy: SortStrategy,
+        #[case] current_song: Option<usize>,
+        #[case] expected: Vec<usize>,
+    ) {
+        init();
+        let db = init_test_database().await.unwrap();
+
+        // three analyzed songs, and one that hasn't been analyzed
+        let mut songs = Vec::new();
+        for features in [
+            Some(features(0.5, -0.5, 0.5)),
+            Some(features(-0.5, 0.5, -0.5)),
+            Some(features(0.0, 0.0, 0.4)),
+            None,
+        ] {
+            let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                .await
+                .unwrap();
+            if let Some(features) = features {
+                Analysis::create(
+                    &db,
+                    song.id.clone(),
+                    Analysis {
+                        id: Analysis::generate_id(),
+                        features,
+                    },
+                )
+                .await
+                .unwrap();
+            }
+            songs.push(song);
+        }
+
+        // the queue contains one of the songs twice
+        let mut queue = songs.clone();
+        queue.push(songs[1].clone());
+
+        let ranking = rank_by_analysis(&db, &queue, current_song.map(|i| &songs[i]), strategy)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ranking,
+            expected
+                .into_iter()
+                .map(|i| songs[i].id.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+}
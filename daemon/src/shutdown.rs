@@ -0,0 +1,98 @@
+//! Keeping track of the requests being handled, so the daemon can let them finish before shutting down.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// The requests that are currently being handled by the daemon.
+#[derive(Clone, Debug, Default)]
+pub struct InFlightRequests {
+    next_id: Arc<AtomicU64>,
+    requests: Arc<Mutex<HashMap<u64, String>>>,
+}
+
+impl InFlightRequests {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn the future handling the request called `name`, and keep track of it until it finishes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn spawn(&self, name: impl Into<String>, fut: impl Future<Output = ()> + Send + 'static) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.requests.lock().unwrap().insert(id, name.into());
+
+        let requests = self.requests.clone();
+        tokio::spawn(async move {
+            fut.await;
+            requests.lock().unwrap().remove(&id);
+        });
+    }
+
+    /// The names of the requests that haven't finished yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.requests.lock().unwrap().values().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Wait for all the requests to finish, for at most `timeout`.
+    ///
+    /// Returns `true` if every request finished, and `false` if the timeout expired first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            while !self.requests.lock().unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_drain_finished_requests() {
+        let in_flight = InFlightRequests::new();
+
+        in_flight.spawn("first", async {});
+        in_flight.spawn("second", tokio::time::sleep(Duration::from_millis(10)));
+
+        assert!(in_flight.drain(Duration::from_secs(5)).await);
+        assert!(in_flight.names().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drain_timeout() {
+        let in_flight = InFlightRequests::new();
+
+        in_flight.spawn("quick", async {});
+        in_flight.spawn("stuck", std::future::pending());
+
+        assert!(!in_flight.drain(Duration::from_millis(200)).await);
+        assert_eq!(in_flight.names(), vec!["stuck".to_string()]);
+    }
+}
@@ -70,7 +70,7 @@ impl Album {
         limit: i64,
     ) -> StorageResult<Vec<Self>> {
         Ok(db
-            .query("SELECT *, search::score(0) * 2 + search::score(1) * 1 AS relevance FROM album WHERE title @0@ $query OR artist @1@ $query ORDER BY relevance DESC LIMIT $limit")
+            .query("SELECT *, search::score(0) * 2 + search::score(1) * 1 + search::score(2) * 0.5 AS relevance FROM album WHERE title @0@ $query OR artist @1@ $query OR description @2@ $query ORDER BY relevance DESC LIMIT $limit")
             .bind(("query", query.to_string()))
             .bind(("limit", limit))
             .await?
@@ -128,6 +128,7 @@ impl Album {
                 song_count: 0,
                 discs: 1,
                 genre: OneOrMany::None,
+                description: None,
             },
         )
         .await?
@@ -249,6 +250,7 @@ mod tests {
             song_count: 0,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         }
     }
 
@@ -361,6 +363,7 @@ mod tests {
                 runtime: Duration::from_secs(0),
                 album_count: 0,
                 song_count: 0,
+                biography: None,
             },
         )
         .await?
@@ -397,6 +400,7 @@ mod tests {
                 runtime: Duration::from_secs(0),
                 album_count: 0,
                 song_count: 0,
+                biography: None,
             },
         )
         .await?
@@ -411,6 +415,7 @@ mod tests {
             song_count: 0,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         };
 
         let read = Album::read_or_create_by_name_and_album_artist(
@@ -454,6 +459,27 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_search_description() -> Result<()> {
+        let db = init_test_database().await?;
+        let mut album1 = create_album();
+        album1.title = "Foo".into();
+        album1.description = Some("A live recording of Baz".into());
+        let mut album2 = create_album();
+        album2.title = "Baz".into();
+
+        Album::create(&db, album1.clone()).await?;
+        Album::create(&db, album2.clone()).await?;
+
+        let found = Album::search(&db, "recording", 10).await?;
+        assert_eq!(found, vec![album1.clone()]);
+
+        // matches on the title are ranked above matches on the description
+        let found = Album::search(&db, "baz", 10).await?;
+        assert_eq!(found, vec![album2, album1]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_add_songs() -> Result<()> {
         let db = init_test_database().await?;
@@ -472,6 +498,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: "song.mp3".into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let album = Album::create(&db, album)
@@ -509,6 +543,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: "song.mp3".into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let _ = Album::create(&db, album.clone())
@@ -526,6 +568,70 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_read_songs_ordered() -> Result<()> {
+        let db = init_test_database().await?;
+
+        let album = create_album();
+        let _ = Album::create(&db, album.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Failed to create album"))?;
+
+        // (disc, track, title), in the order they should be read back
+        let expected = [
+            (Some(1), Some(1), "Intro"),
+            (Some(1), Some(2), "A Song"),
+            (Some(1), Some(2), "B Song"),
+            (Some(1), None, "Hidden Track"),
+            (Some(2), Some(1), "Second Disc"),
+            (None, Some(1), "Bonus"),
+            (None, None, "Another Bonus"),
+        ];
+
+        let mut songs = Vec::new();
+        // add the songs out of order
+        for (disc, track, title) in expected.iter().rev() {
+            let song = Song {
+                id: Song::generate_id(),
+                title: (*title).into(),
+                artist: vec!["Test Artist".into()].into(),
+                album_artist: vec!["Test Artist".into()].into(),
+                album: "Test Album".into(),
+                genre: OneOrMany::One("Test Genre".into()),
+                runtime: Duration::from_secs(120),
+                track: *track,
+                disc: *disc,
+                release_year: None,
+                extension: "mp3".into(),
+                path: format!("{title}.mp3").into(),
+                replaygain_track_gain: None,
+                replaygain_album_gain: None,
+                play_count: 0,
+                skip_count: 0,
+                last_played: None,
+                rating: None,
+                favorite: false,
+                bookmark_positions: Vec::new(),
+            };
+            let _ = Song::create(&db, song.clone())
+                .await?
+                .ok_or_else(|| anyhow!("Failed to create song"))?;
+            songs.push(song);
+        }
+
+        Album::add_songs(
+            &db,
+            album.id.clone(),
+            songs.iter().map(|song| song.id.clone()).collect(),
+        )
+        .await?;
+
+        let read = Album::read_songs(&db, album.id.clone()).await?;
+        songs.reverse();
+        assert_eq!(read, songs);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_remove_songs() -> Result<()> {
         let db = init_test_database().await?;
@@ -544,6 +650,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: "song.mp3".into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let _ = Album::create(&db, album.clone())
@@ -573,6 +687,7 @@ mod tests {
                 runtime: Duration::from_secs(0),
                 album_count: 0,
                 song_count: 0,
+                biography: None,
             },
         )
         .await?
@@ -1,5 +1,6 @@
 //! CRUD operations for the analysis table
 
+use mecomp_analysis::{temporal::BPMDesc, AnalysisIndex, NUMBER_FEATURES};
 use one_or_many::OneOrMany;
 use surrealdb::{Connection, RecordId, Surreal};
 use tracing::instrument;
@@ -7,8 +8,8 @@ use tracing::instrument;
 use crate::{
     db::{
         queries::analysis::{
-            add_to_song, nearest_neighbors, nearest_neighbors_to_many, read_for_song, read_song,
-            read_songs_without_analysis,
+            add_to_song, nearest_neighbors, nearest_neighbors_to_many, read_for_song,
+            read_in_feature_range, read_song, read_songs_without_analysis,
         },
         schemas::{
             analysis::{Analysis, AnalysisId, TABLE_NAME},
@@ -125,6 +126,32 @@ impl Analysis {
         Ok(db.query(read_songs_without_analysis()).await?.take(0)?)
     }
 
+    /// Get (up to `limit`) songs whose tempo is between `min_bpm` and `max_bpm` (inclusive), ordered by tempo
+    ///
+    /// Songs without an analysis are excluded.
+    #[instrument]
+    pub async fn read_songs_in_bpm_range<C: Connection>(
+        db: &Surreal<C>,
+        min_bpm: f64,
+        max_bpm: f64,
+        limit: i64,
+    ) -> StorageResult<Vec<Song>> {
+        let analyses: Vec<Self> = db
+            .query(read_in_feature_range(AnalysisIndex::Tempo as usize))
+            .bind(("min", BPMDesc::normalize_bpm(min_bpm)))
+            .bind(("max", BPMDesc::normalize_bpm(max_bpm)))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        futures::future::try_join_all(
+            analyses
+                .into_iter()
+                .map(|analysis| Self::read_song(db, analysis.id)),
+        )
+        .await
+    }
+
     /// Delete an analysis
     #[instrument]
     pub async fn delete<C: Connection>(
@@ -149,7 +176,7 @@ impl Analysis {
         Ok(db
             .query(nearest_neighbors(n))
             .bind(("id", id))
-            .bind(("target", features))
+            .bind(("target", features.to_vec()))
             .await?
             .take(0)?)
     }
@@ -174,12 +201,14 @@ impl Analysis {
         #[allow(clippy::cast_precision_loss)]
         let num_analyses = analyses.len() as f64;
 
-        let avg_features = analyses.iter().fold(vec![0.; 20], |acc, analysis| {
-            acc.iter()
-                .zip(analysis.features.iter())
-                .map(|(a, b)| a + (b / num_analyses))
-                .collect::<Vec<_>>()
-        });
+        let avg_features = analyses
+            .iter()
+            .fold(vec![0.; NUMBER_FEATURES], |acc, analysis| {
+                acc.iter()
+                    .zip(analysis.features.iter())
+                    .map(|(a, b)| a + (b / num_analyses))
+                    .collect::<Vec<_>>()
+            });
 
         Ok(db
             .query(nearest_neighbors_to_many(n))
@@ -210,7 +239,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
@@ -220,7 +249,7 @@ mod test {
         // if we try to create another analysis for the same song, we get Ok(None)
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [1.; 20],
+            features: [1.; NUMBER_FEATURES],
         };
         let result = Analysis::create(&db, song.id.clone(), analysis.clone()).await?;
         assert_eq!(result, None);
@@ -237,7 +266,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
@@ -260,7 +289,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
@@ -283,7 +312,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // the song doesn't have an analysis yet
@@ -314,11 +343,11 @@ mod test {
 
         let analysis1 = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
         let analysis2 = Analysis {
             id: Analysis::generate_id(),
-            features: [1.; 20],
+            features: [1.; NUMBER_FEATURES],
         };
 
         // create the analyses
@@ -347,7 +376,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
@@ -372,11 +401,11 @@ mod test {
 
         let analysis1 = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
         let analysis2 = Analysis {
             id: Analysis::generate_id(),
-            features: [1.; 20],
+            features: [1.; NUMBER_FEATURES],
         };
 
         // create the analyses
@@ -413,11 +442,11 @@ mod test {
 
         let analysis1 = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
         let analysis2 = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
@@ -439,6 +468,43 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_read_songs_in_bpm_range() -> Result<()> {
+        let db = init_test_database().await?;
+
+        let mut songs = Vec::new();
+        for bpm in [130., 90., 125., 140., 160.] {
+            let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                .await?;
+            let mut features = [0.; NUMBER_FEATURES];
+            features[AnalysisIndex::Tempo as usize] = BPMDesc::normalize_bpm(bpm);
+            let analysis = Analysis {
+                id: Analysis::generate_id(),
+                features,
+            };
+            Analysis::create(&db, song.id.clone(), analysis).await?;
+            songs.push(song);
+        }
+        // songs without an analysis are excluded
+        create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        // inclusive on both ends, ordered by tempo
+        let result = Analysis::read_songs_in_bpm_range(&db, 120., 140., 10).await?;
+        assert_eq!(
+            result,
+            vec![songs[2].clone(), songs[0].clone(), songs[3].clone()]
+        );
+
+        let result = Analysis::read_songs_in_bpm_range(&db, 120., 140., 1).await?;
+        assert_eq!(result, vec![songs[2].clone()]);
+
+        // an empty range
+        let result = Analysis::read_songs_in_bpm_range(&db, 140., 120., 10).await?;
+        assert!(result.is_empty());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_delete() -> Result<()> {
         let db = init_test_database().await?;
@@ -448,7 +514,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
@@ -483,15 +549,15 @@ mod test {
 
         let analysis1 = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
         let analysis2 = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
         let analysis3 = Analysis {
             id: Analysis::generate_id(),
-            features: [1.; 20],
+            features: [1.; NUMBER_FEATURES],
         };
 
         // create the analyses
@@ -518,7 +584,7 @@ mod test {
 
         let analysis = Analysis {
             id: Analysis::generate_id(),
-            features: [0.; 20],
+            features: [0.; NUMBER_FEATURES],
         };
 
         // create the analysis
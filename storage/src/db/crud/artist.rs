@@ -6,12 +6,9 @@ use tracing::instrument;
 
 use crate::{
     db::{
-        queries::{
-            artist::{
-                add_album, add_album_to_artists, add_songs, read_albums, read_by_name,
-                read_by_names, read_many, read_songs, remove_songs,
-            },
-            generic::full_text_search,
+        queries::artist::{
+            add_album, add_album_to_artists, add_songs, merge, read_albums, read_by_name,
+            read_by_names, read_direct_songs, read_many, read_songs, remove_songs,
         },
         schemas::{
             album::{Album, AlbumId},
@@ -51,6 +48,7 @@ impl Artist {
                     song_count: 0,
                     album_count: 0,
                     runtime: Duration::from_secs(0),
+                    biography: None,
                 },
             )
             .await
@@ -126,6 +124,9 @@ impl Artist {
         Ok(db.query(read_many()).bind(("ids", ids)).await?.take(0)?)
     }
 
+    /// Full-text search for artists whose name or biography match the query.
+    ///
+    /// Results are ordered by relevance (BM25 score), with matches on the name weighted the highest.
     #[instrument]
     pub async fn search<C: Connection>(
         db: &Surreal<C>,
@@ -133,8 +134,9 @@ impl Artist {
         limit: i64,
     ) -> StorageResult<Vec<Self>> {
         Ok(db
-            .query(full_text_search(TABLE_NAME, "name", limit))
-            .bind(("name", query.to_string()))
+            .query("SELECT *, search::score(0) * 2 + search::score(1) * 0.5 AS relevance FROM artist WHERE name @0@ $query OR biography @1@ $query ORDER BY relevance DESC LIMIT $limit")
+            .bind(("query", query.to_string()))
+            .bind(("limit", limit))
             .await?
             .take(0)?)
     }
@@ -245,6 +247,126 @@ impl Artist {
         Ok(db.query(read_songs()).bind(("artist", id)).await?.take(0)?)
     }
 
+    /// Merge the `source` artist into the `target` artist, e.g. to fix an artist that was split in two
+    /// by inconsistent tagging ("The Beatles" vs "Beatles").
+    ///
+    /// The songs and albums of the source artist are given to the target artist
+    /// (replacing the source artist's name with the target's in their metadata),
+    /// and the source artist is deleted, all in a single transaction.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - the merged target artist, or `None` if either artist doesn't exist
+    #[instrument]
+    pub async fn merge<C: Connection>(
+        db: &Surreal<C>,
+        source: ArtistId,
+        target: ArtistId,
+    ) -> StorageResult<Option<Self>> {
+        #[derive(Debug, serde::Serialize)]
+        struct SongArtists {
+            id: SongId,
+            artist: OneOrMany<Arc<str>>,
+            album_artist: OneOrMany<Arc<str>>,
+        }
+        #[derive(Debug, serde::Serialize)]
+        struct AlbumArtists {
+            id: AlbumId,
+            artist: OneOrMany<Arc<str>>,
+        }
+
+        if source == target {
+            return Self::read(db, target).await;
+        }
+        let (Some(source_artist), Some(target_artist)) = (
+            Self::read(db, source.clone()).await?,
+            Self::read(db, target.clone()).await?,
+        ) else {
+            return Ok(None);
+        };
+
+        // replace the source artist's name with the target's, without duplicating the target's name
+        let rename = |names: &OneOrMany<Arc<str>>| {
+            let mut renamed = OneOrMany::None;
+            for name in names.iter() {
+                let name = if *name == source_artist.name {
+                    &target_artist.name
+                } else {
+                    name
+                };
+                if !renamed.contains(name) {
+                    renamed.push(name.clone());
+                }
+            }
+            renamed
+        };
+
+        let songs = Self::read_songs(db, source.clone())
+            .await?
+            .into_iter()
+            .filter(|song| {
+                song.artist.contains(&source_artist.name)
+                    || song.album_artist.contains(&source_artist.name)
+            })
+            .map(|song| SongArtists {
+                artist: rename(&song.artist),
+                album_artist: rename(&song.album_artist),
+                id: song.id,
+            })
+            .collect::<Vec<_>>();
+        let source_albums = Self::read_albums(db, source.clone()).await?;
+        let albums = source_albums
+            .iter()
+            .filter(|album| album.artist.contains(&source_artist.name))
+            .map(|album| AlbumArtists {
+                id: album.id.clone(),
+                artist: rename(&album.artist),
+            })
+            .collect::<Vec<_>>();
+
+        // only relate the target artist to the songs and albums it isn't already related to
+        let target_songs = Self::read_direct_songs(db, target.clone()).await?;
+        let new_songs = Self::read_direct_songs(db, source.clone())
+            .await?
+            .into_iter()
+            .filter(|song| !target_songs.contains(song))
+            .map(|song| song.id)
+            .collect::<Vec<_>>();
+        let target_albums = Self::read_albums(db, target.clone()).await?;
+        let new_albums = source_albums
+            .into_iter()
+            .filter(|album| !target_albums.contains(album))
+            .map(|album| album.id)
+            .collect::<Vec<_>>();
+
+        db.query(merge())
+            .bind(("source", source))
+            .bind(("target", target.clone()))
+            .bind(("songs", songs))
+            .bind(("albums", albums))
+            .bind(("new_songs", new_songs))
+            .bind(("new_albums", new_albums))
+            .await?
+            .check()?;
+
+        // update runtime, and song/album count
+        Self::repair(db, target.clone()).await?;
+        Self::read(db, target).await
+    }
+
+    /// gets the songs directly associated with an artist (not through an album)
+    #[instrument]
+    pub async fn read_direct_songs<C: Connection>(
+        db: &Surreal<C>,
+        id: ArtistId,
+    ) -> StorageResult<Vec<Song>> {
+        Ok(db
+            .query(read_direct_songs())
+            .bind(("id", id))
+            .await?
+            .take(0)?)
+    }
+
     /// updates the album count, song count, and runtime of the artist
     ///
     /// # Arguments
@@ -292,6 +414,7 @@ mod tests {
             runtime: Duration::from_secs(0),
             album_count: 0,
             song_count: 0,
+            biography: None,
         }
     }
 
@@ -395,6 +518,27 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_search_biography() -> Result<()> {
+        let db = init_test_database().await?;
+        let mut artist1 = create_artist();
+        artist1.name = "Foo".into();
+        artist1.biography = Some("A jazz trio from Baz".into());
+        let mut artist2 = create_artist();
+        artist2.name = "Baz".into();
+
+        Artist::create(&db, artist1.clone()).await?;
+        Artist::create(&db, artist2.clone()).await?;
+
+        let found = Artist::search(&db, "jazz", 10).await?;
+        assert_eq!(found, vec![artist1.clone()]);
+
+        // matches on the name are ranked above matches on the biography
+        let found = Artist::search(&db, "baz", 10).await?;
+        assert_eq!(found, vec![artist2, artist1]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_update() -> Result<()> {
         let db = init_test_database().await?;
@@ -541,6 +685,7 @@ mod tests {
             release: None,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         };
 
         let _ = Artist::create(&db, artist.clone())
@@ -571,6 +716,7 @@ mod tests {
             release: None,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         };
         let song = Song {
             id: Song::generate_id(),
@@ -585,6 +731,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: PathBuf::from("song.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let _ = Artist::create(&db, artist.clone())
@@ -626,6 +780,7 @@ mod tests {
             release: None,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         };
         let song = Song {
             id: Song::generate_id(),
@@ -640,6 +795,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: PathBuf::from("song.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let _ = Artist::create(&db, artist.clone())
@@ -691,6 +854,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: PathBuf::from("song.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let artist = Artist::create(&db, artist)
@@ -729,6 +900,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: PathBuf::from("song.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let artist = Artist::create(&db, artist.clone())
@@ -765,6 +944,7 @@ mod tests {
             release: None,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         };
         let song = Song {
             id: Song::generate_id(),
@@ -779,6 +959,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: PathBuf::from("song.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
         let song2 = Song {
             id: Song::generate_id(),
@@ -793,6 +981,14 @@ mod tests {
             release_year: None,
             extension: "mp3".into(),
             path: PathBuf::from("song_2.mp3"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let _ = Artist::create(&db, artist.clone())
@@ -818,4 +1014,101 @@ mod tests {
         assert_eq!(read[1], song);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_merge() -> Result<()> {
+        use crate::{
+            db::schemas::song::SongChangeSet,
+            test_utils::{arb_song_case, create_song_with_overrides},
+        };
+
+        let db = init_test_database().await?;
+
+        let mut songs = Vec::new();
+        for (artist, album_artist, album) in [
+            (OneOrMany::One("Beatles".into()), "Beatles", "Help!"),
+            (
+                OneOrMany::One("The Beatles".into()),
+                "The Beatles",
+                "Abbey Road",
+            ),
+            (
+                OneOrMany::Many(vec!["Beatles".into(), "Billy Preston".into()]),
+                "The Beatles",
+                "Abbey Road",
+            ),
+        ] {
+            songs.push(
+                create_song_with_overrides(
+                    &db,
+                    arb_song_case()(),
+                    SongChangeSet {
+                        artist: Some(artist),
+                        album_artist: Some(OneOrMany::One(album_artist.into())),
+                        album: Some(album.into()),
+                        ..Default::default()
+                    },
+                )
+                .await?,
+            );
+        }
+
+        let source = Artist::read_by_name(&db, "Beatles")
+            .await?
+            .ok_or_else(|| anyhow!("Artist not found"))?;
+        let target = Artist::read_by_name(&db, "The Beatles")
+            .await?
+            .ok_or_else(|| anyhow!("Artist not found"))?;
+
+        let merged = Artist::merge(&db, source.id.clone(), target.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Failed to merge artists"))?;
+
+        // the source artist is gone, and the target has all the songs and albums
+        assert_eq!(Artist::read(&db, source.id).await?, None);
+        assert_eq!(merged.song_count, 3);
+        assert_eq!(merged.album_count, 2);
+        assert_eq!(
+            merged.runtime,
+            songs.iter().map(|song| song.runtime).sum::<Duration>()
+        );
+
+        // the metadata of the songs and albums was updated
+        let song = Song::read(&db, songs[0].id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(song.artist, OneOrMany::One("The Beatles".into()));
+        assert_eq!(song.album_artist, OneOrMany::One("The Beatles".into()));
+        let album = Song::read_album(&db, song.id)
+            .await?
+            .ok_or_else(|| anyhow!("Album not found"))?;
+        assert_eq!(album.artist, OneOrMany::One("The Beatles".into()));
+
+        let song = Song::read(&db, songs[2].id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(
+            song.artist,
+            OneOrMany::Many(vec!["The Beatles".into(), "Billy Preston".into()])
+        );
+        let artists = Song::read_artist(&db, song.id).await?;
+        assert_eq!(artists.len(), 2);
+        assert!(artists.iter().any(|artist| artist.id == target.id));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_missing() -> Result<()> {
+        let db = init_test_database().await?;
+        let artist = create_artist();
+        Artist::create(&db, artist.clone()).await?;
+
+        let result = Artist::merge(&db, Artist::generate_id(), artist.id.clone()).await?;
+        assert_eq!(result, None);
+        // the existing artist is left alone
+        assert_eq!(Artist::read(&db, artist.id.clone()).await?, Some(artist));
+
+        Ok(())
+    }
 }
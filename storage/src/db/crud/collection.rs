@@ -1,12 +1,16 @@
 //! CRUD operations for the collection table
 use std::{sync::Arc, time::Duration};
 
+#[cfg(feature = "analysis")]
+use mecomp_analysis::NUMBER_FEATURES;
 use surrealdb::{Connection, RecordId, Surreal};
 use tracing::instrument;
 
+#[cfg(feature = "analysis")]
+use crate::db::schemas::{analysis::Analysis, collection::CollectionCentroid};
 use crate::{
     db::{
-        queries::collection::{add_songs, read_songs, remove_songs},
+        queries::collection::{add_songs, merge, read_songs, remove_songs},
         schemas::{
             collection::{Collection, CollectionChangeSet, CollectionId, TABLE_NAME},
             playlist::Playlist,
@@ -133,6 +137,86 @@ impl Collection {
         Ok(songs.is_empty())
     }
 
+    /// Rename a collection.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - the renamed collection, or `None` if it doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another collection already has the given name (collection names are unique).
+    #[instrument]
+    pub async fn rename<C: Connection>(
+        db: &Surreal<C>,
+        id: CollectionId,
+        name: Arc<str>,
+    ) -> StorageResult<Option<Self>> {
+        if Self::read(db, id.clone()).await?.is_none() {
+            return Ok(None);
+        }
+        Self::update(
+            db,
+            id,
+            CollectionChangeSet {
+                name: Some(name),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Merge the `source` collection into the `target` collection.
+    ///
+    /// The songs of the source collection that aren't already in the target collection are added to it,
+    /// the `song_count` and `runtime` of the target are updated, and the source collection is deleted,
+    /// all in a single transaction.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - the merged target collection, or `None` if either collection doesn't exist
+    #[instrument]
+    pub async fn merge<C: Connection>(
+        db: &Surreal<C>,
+        source: CollectionId,
+        target: CollectionId,
+    ) -> StorageResult<Option<Self>> {
+        if source == target {
+            return Self::read(db, target).await;
+        }
+        if Self::read(db, source.clone()).await?.is_none()
+            || Self::read(db, target.clone()).await?.is_none()
+        {
+            return Ok(None);
+        }
+
+        let mut songs = Self::read_songs(db, target.clone()).await?;
+        let mut new_songs = Vec::new();
+        for song in Self::read_songs(db, source.clone()).await? {
+            if !songs.contains(&song) {
+                new_songs.push(song.id.clone());
+                songs.push(song);
+            }
+        }
+
+        db.query(merge())
+            .bind(("source", source))
+            .bind(("target", target.clone()))
+            .bind(("songs", new_songs))
+            .bind((
+                "changes",
+                CollectionChangeSet {
+                    song_count: Some(songs.len()),
+                    runtime: Some(songs.iter().map(|song| song.runtime).sum::<Duration>()),
+                    ..Default::default()
+                },
+            ))
+            .await?
+            .check()?;
+
+        Self::read(db, target).await
+    }
+
     /// "Freeze" a collection, this will create a playlist with the given name that contains all the songs in the given collection
     #[instrument]
     pub async fn freeze<C: Connection>(
@@ -167,6 +251,118 @@ impl Collection {
 
         Ok(playlist)
     }
+
+    /// Read the songs of every collection, and compute the centroid of their analyses.
+    ///
+    /// The centroid of a collection is the mean of the features of its analyzed songs.
+    #[cfg(feature = "analysis")]
+    #[instrument]
+    pub async fn centroids<C: Connection>(
+        db: &Surreal<C>,
+    ) -> StorageResult<Vec<CollectionCentroid>> {
+        let mut centroids = Vec::new();
+
+        for collection in Self::read_all(db).await? {
+            let songs = Self::read_songs(db, collection.id.clone())
+                .await?
+                .into_iter()
+                .map(|song| song.id)
+                .collect::<Vec<_>>();
+
+            let analyses = Analysis::read_for_songs(db, songs.clone())
+                .await?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            let centroid = (!analyses.is_empty()).then(|| {
+                #[allow(clippy::cast_precision_loss)]
+                let count = analyses.len() as f64;
+                let mut centroid = [0.0; NUMBER_FEATURES];
+                for analysis in &analyses {
+                    for (sum, feature) in centroid.iter_mut().zip(analysis.features) {
+                        *sum += feature / count;
+                    }
+                }
+                centroid
+            });
+
+            centroids.push(CollectionCentroid {
+                id: collection.id,
+                songs,
+                centroid,
+            });
+        }
+
+        Ok(centroids)
+    }
+
+    /// Add a (newly analyzed) song to the existing collection it is most similar to.
+    ///
+    /// The centroid of each collection is computed from the analyses of its songs,
+    /// and if the euclidean distance between the song's analysis and the closest centroid is within `threshold`,
+    /// the song is added to that collection.
+    /// Songs that are already in a collection are left alone.
+    ///
+    /// To add many songs, compute the centroids once with [`Collection::centroids`] and use [`Collection::auto_update_with_centroids`].
+    ///
+    /// # Returns
+    ///
+    /// * `Option<CollectionId>` - The id of the collection the song was added to, if any
+    #[cfg(feature = "analysis")]
+    #[instrument]
+    pub async fn auto_update<C: Connection>(
+        db: &Surreal<C>,
+        new_song_id: SongId,
+        analysis: &Analysis,
+        threshold: f64,
+    ) -> StorageResult<Option<CollectionId>> {
+        let centroids = Self::centroids(db).await?;
+        Self::auto_update_with_centroids(db, new_song_id, analysis, &centroids, threshold).await
+    }
+
+    /// Like [`Collection::auto_update`], but with centroids that have already been computed by [`Collection::centroids`].
+    ///
+    /// The centroids aren't updated as songs are added, so they should be recomputed before the next batch of songs.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<CollectionId>` - The id of the collection the song was added to, if any
+    #[cfg(feature = "analysis")]
+    #[instrument(skip(centroids))]
+    pub async fn auto_update_with_centroids<C: Connection>(
+        db: &Surreal<C>,
+        new_song_id: SongId,
+        analysis: &Analysis,
+        centroids: &[CollectionCentroid],
+        threshold: f64,
+    ) -> StorageResult<Option<CollectionId>> {
+        if centroids
+            .iter()
+            .any(|collection| collection.songs.contains(&new_song_id))
+        {
+            return Ok(None);
+        }
+
+        let closest = centroids
+            .iter()
+            .filter_map(|collection| {
+                let distance = collection
+                    .centroid?
+                    .iter()
+                    .zip(analysis.features)
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                (distance <= threshold).then_some((&collection.id, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let Some((id, _)) = closest else {
+            return Ok(None);
+        };
+        Self::add_songs(db, id.clone(), vec![new_song_id]).await?;
+        Ok(Some(id.clone()))
+    }
 }
 
 #[cfg(test)]
@@ -319,4 +515,230 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_rename() -> Result<()> {
+        let db = init_test_database().await?;
+        let collection = create_collection();
+        Collection::create(&db, collection.clone()).await?;
+
+        let renamed = Collection::rename(&db, collection.id.clone(), "Renamed".into())
+            .await?
+            .ok_or_else(|| anyhow!("Collection not found"))?;
+        assert_eq!(renamed.name, "Renamed".into());
+        assert_eq!(Collection::read(&db, collection.id).await?, Some(renamed));
+
+        // renaming a collection that doesn't exist does nothing
+        let result = Collection::rename(&db, Collection::generate_id(), "Missing".into()).await?;
+        assert_eq!(result, None);
+        assert_eq!(Collection::read_all(&db).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_duplicate_name() -> Result<()> {
+        let db = init_test_database().await?;
+        let collection = create_collection();
+        Collection::create(&db, collection.clone()).await?;
+        let other = Collection {
+            id: Collection::generate_id(),
+            name: "Other Collection".into(),
+            ..create_collection()
+        };
+        Collection::create(&db, other.clone()).await?;
+
+        let result = Collection::rename(&db, other.id.clone(), collection.name.clone()).await;
+        assert!(result.is_err());
+        assert_eq!(Collection::read(&db, other.id.clone()).await?, Some(other));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge() -> Result<()> {
+        let db = init_test_database().await?;
+        let source = create_collection();
+        Collection::create(&db, source.clone()).await?;
+        let target = Collection {
+            id: Collection::generate_id(),
+            name: "Target Collection".into(),
+            ..create_collection()
+        };
+        Collection::create(&db, target.clone()).await?;
+
+        let shared =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let source_only =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let target_only =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        Collection::add_songs(
+            &db,
+            source.id.clone(),
+            vec![shared.id.clone(), source_only.id.clone()],
+        )
+        .await?;
+        Collection::add_songs(
+            &db,
+            target.id.clone(),
+            vec![shared.id.clone(), target_only.id.clone()],
+        )
+        .await?;
+
+        let merged = Collection::merge(&db, source.id.clone(), target.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Collection not found"))?;
+
+        // the source collection is gone
+        assert_eq!(Collection::read(&db, source.id).await?, None);
+
+        // the target has all the songs, without duplicates
+        let mut songs = Collection::read_songs(&db, target.id.clone()).await?;
+        songs.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut expected = vec![shared, source_only, target_only];
+        expected.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(songs, expected);
+        assert_eq!(merged.song_count, 3);
+        assert_eq!(
+            merged.runtime,
+            expected.iter().map(|song| song.runtime).sum::<Duration>()
+        );
+        assert_eq!(merged.name, target.name);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_missing() -> Result<()> {
+        let db = init_test_database().await?;
+        let collection = create_collection();
+        Collection::create(&db, collection.clone()).await?;
+
+        let result =
+            Collection::merge(&db, Collection::generate_id(), collection.id.clone()).await?;
+        assert_eq!(result, None);
+        let result =
+            Collection::merge(&db, collection.id.clone(), Collection::generate_id()).await?;
+        assert_eq!(result, None);
+
+        // the existing collection wasn't touched
+        assert_eq!(
+            Collection::read(&db, collection.id.clone()).await?,
+            Some(collection)
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "analysis")]
+    #[tokio::test]
+    async fn test_auto_update() -> Result<()> {
+        let db = init_test_database().await?;
+
+        // two collections, one with songs around 0.0 and the other with songs around 1.0
+        let mut collections = Vec::new();
+        for center in [0.0, 1.0] {
+            let collection = Collection {
+                name: format!("Collection {center}").into(),
+                ..create_collection()
+            };
+            Collection::create(&db, collection.clone()).await?;
+            for offset in [-0.1, 0.1] {
+                let song =
+                    create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                        .await?;
+                Analysis::create(
+                    &db,
+                    song.id.clone(),
+                    Analysis {
+                        id: Analysis::generate_id(),
+                        features: [center + offset; NUMBER_FEATURES],
+                    },
+                )
+                .await?;
+                Collection::add_songs(&db, collection.id.clone(), vec![song.id]).await?;
+            }
+            collections.push(collection);
+        }
+
+        // a song close to the second collection's centroid is added to it
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let analysis = Analysis {
+            id: Analysis::generate_id(),
+            features: [0.9; NUMBER_FEATURES],
+        };
+        let result = Collection::auto_update(&db, song.id.clone(), &analysis, 1.0).await?;
+        assert_eq!(result, Some(collections[1].id.clone()));
+        assert!(Collection::read_songs(&db, collections[1].id.clone())
+            .await?
+            .contains(&song));
+
+        // it's already in a collection now, so it's left alone
+        let result = Collection::auto_update(&db, song.id.clone(), &analysis, 1.0).await?;
+        assert_eq!(result, None);
+
+        // a song that isn't close enough to any centroid isn't added to any collection
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let analysis = Analysis {
+            id: Analysis::generate_id(),
+            features: [0.5; NUMBER_FEATURES],
+        };
+        let result = Collection::auto_update(&db, song.id.clone(), &analysis, 1.0).await?;
+        assert_eq!(result, None);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "analysis")]
+    #[tokio::test]
+    async fn test_centroids() -> Result<()> {
+        let db = init_test_database().await?;
+
+        // a collection with two analyzed songs and one that hasn't been analyzed, and an empty collection
+        let collection = create_collection();
+        Collection::create(&db, collection.clone()).await?;
+        let mut songs = Vec::new();
+        for features in [Some(0.0), Some(1.0), None] {
+            let song = create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                .await?;
+            if let Some(features) = features {
+                Analysis::create(
+                    &db,
+                    song.id.clone(),
+                    Analysis {
+                        id: Analysis::generate_id(),
+                        features: [features; NUMBER_FEATURES],
+                    },
+                )
+                .await?;
+            }
+            songs.push(song.id);
+        }
+        Collection::add_songs(&db, collection.id.clone(), songs.clone()).await?;
+        let empty = Collection {
+            name: "Empty".into(),
+            ..create_collection()
+        };
+        Collection::create(&db, empty.clone()).await?;
+
+        let mut centroids = Collection::centroids(&db).await?;
+        assert_eq!(centroids.len(), 2);
+        centroids.sort_by_key(|centroid| centroid.songs.len());
+
+        assert_eq!(centroids[0].id, empty.id);
+        assert!(centroids[0].songs.is_empty());
+        assert_eq!(centroids[0].centroid, None);
+
+        assert_eq!(centroids[1].id, collection.id);
+        let mut expected_songs = songs;
+        expected_songs.sort();
+        centroids[1].songs.sort();
+        assert_eq!(centroids[1].songs, expected_songs);
+        assert_eq!(centroids[1].centroid, Some([0.5; NUMBER_FEATURES]));
+
+        Ok(())
+    }
 }
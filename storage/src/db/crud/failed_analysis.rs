@@ -0,0 +1,170 @@
+//! CRUD operations for the failed_analysis table
+
+use chrono::Utc;
+use surrealdb::{Connection, RecordId, Surreal};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        queries::failed_analysis::{delete_for_songs, read_for_song},
+        schemas::{
+            failed_analysis::{FailedAnalysis, FailedAnalysisId, TABLE_NAME},
+            song::SongId,
+        },
+    },
+    errors::{Error, StorageResult},
+};
+
+impl FailedAnalysis {
+    /// Record a failed attempt to analyze the given song.
+    ///
+    /// If the song has failed before, its record is updated with the new error and another attempt is counted,
+    /// otherwise a new record is created.
+    ///
+    /// # Returns
+    ///
+    /// The updated (or created) record.
+    #[instrument]
+    pub async fn record_failure<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+        error: String,
+    ) -> StorageResult<Self> {
+        let record = match Self::read_for_song(db, song_id.clone()).await? {
+            Some(existing) => Self {
+                error,
+                attempt_count: existing.attempt_count.saturating_add(1),
+                last_attempted: Utc::now(),
+                ..existing
+            },
+            None => Self {
+                id: Self::generate_id(),
+                song_id,
+                error,
+                attempt_count: 1,
+                last_attempted: Utc::now(),
+            },
+        };
+
+        let result: Option<Self> = db
+            .upsert(RecordId::from_inner(record.id.clone()))
+            .content(record)
+            .await?;
+        result.ok_or(Error::NotCreated)
+    }
+
+    #[instrument]
+    pub async fn read_all<C: Connection>(db: &Surreal<C>) -> StorageResult<Vec<Self>> {
+        Ok(db.select(TABLE_NAME).await?)
+    }
+
+    #[instrument]
+    pub async fn read<C: Connection>(
+        db: &Surreal<C>,
+        id: FailedAnalysisId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db.select(RecordId::from_inner(id)).await?)
+    }
+
+    #[instrument]
+    pub async fn read_for_song<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db
+            .query(read_for_song())
+            .bind(("song", song_id))
+            .await?
+            .take(0)?)
+    }
+
+    /// Delete the failed analysis records of the given songs (e.g. because they've been analyzed successfully).
+    #[instrument]
+    pub async fn delete_for_songs<C: Connection>(
+        db: &Surreal<C>,
+        song_ids: Vec<SongId>,
+    ) -> StorageResult<()> {
+        db.query(delete_for_songs())
+            .bind(("songs", song_ids))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::schemas::{failed_analysis::MAX_ATTEMPTS, song::SongChangeSet},
+        test_utils::{arb_song_case, create_song_with_overrides, init_test_database},
+    };
+
+    use anyhow::{anyhow, Result};
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_record_failure() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        let first = FailedAnalysis::record_failure(&db, song.id.clone(), "first".into()).await?;
+        assert_eq!(first.song_id, song.id);
+        assert_eq!(first.error, "first");
+        assert_eq!(first.attempt_count, 1);
+        assert!(!first.is_exhausted());
+
+        // failing again updates the existing record
+        let mut latest = first.clone();
+        for attempt in 2..=MAX_ATTEMPTS {
+            latest =
+                FailedAnalysis::record_failure(&db, song.id.clone(), format!("attempt {attempt}"))
+                    .await?;
+            assert_eq!(latest.id, first.id);
+            assert_eq!(latest.attempt_count, attempt);
+        }
+        assert_eq!(latest.error, format!("attempt {MAX_ATTEMPTS}"));
+        assert!(latest.last_attempted >= first.last_attempted);
+        assert!(latest.is_exhausted());
+
+        assert_eq!(FailedAnalysis::read_all(&db).await?, vec![latest.clone()]);
+        assert_eq!(
+            FailedAnalysis::read(&db, latest.id.clone()).await?,
+            Some(latest.clone())
+        );
+        assert_eq!(
+            FailedAnalysis::read_for_song(&db, song.id.clone()).await?,
+            Some(latest)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_for_songs() -> Result<()> {
+        let db = init_test_database().await?;
+        let song_a =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song_b =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        FailedAnalysis::record_failure(&db, song_a.id.clone(), "error".into()).await?;
+        let failed_b =
+            FailedAnalysis::record_failure(&db, song_b.id.clone(), "error".into()).await?;
+
+        FailedAnalysis::delete_for_songs(&db, vec![song_a.id.clone()]).await?;
+
+        assert_eq!(FailedAnalysis::read_for_song(&db, song_a.id).await?, None);
+        assert_eq!(
+            FailedAnalysis::read_all(&db)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("record for song b was deleted"))?,
+            failed_b
+        );
+
+        Ok(())
+    }
+}
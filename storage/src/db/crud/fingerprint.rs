@@ -0,0 +1,139 @@
+//! CRUD operations for the fingerprint table
+
+use surrealdb::{Connection, RecordId, Surreal};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        queries::fingerprint::{delete_for_songs, read_for_song},
+        schemas::{
+            fingerprint::{Fingerprint, TABLE_NAME},
+            song::SongId,
+        },
+    },
+    errors::{Error, StorageResult},
+};
+
+impl Fingerprint {
+    /// Store the fingerprint of the given song, replacing its previous fingerprint (if it has one).
+    ///
+    /// # Returns
+    ///
+    /// The stored record.
+    #[instrument(skip(fingerprint))]
+    pub async fn upsert_for_song<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+        fingerprint: Vec<u32>,
+    ) -> StorageResult<Self> {
+        let record = match Self::read_for_song(db, song_id.clone()).await? {
+            Some(existing) => Self {
+                fingerprint,
+                ..existing
+            },
+            None => Self {
+                id: Self::generate_id(),
+                song_id,
+                fingerprint,
+            },
+        };
+
+        let result: Option<Self> = db
+            .upsert(RecordId::from_inner(record.id.clone()))
+            .content(record)
+            .await?;
+        result.ok_or(Error::NotCreated)
+    }
+
+    #[instrument]
+    pub async fn read_all<C: Connection>(db: &Surreal<C>) -> StorageResult<Vec<Self>> {
+        Ok(db.select(TABLE_NAME).await?)
+    }
+
+    #[instrument]
+    pub async fn read_for_song<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db
+            .query(read_for_song())
+            .bind(("song", song_id))
+            .await?
+            .take(0)?)
+    }
+
+    /// Delete the fingerprints of the given songs.
+    #[instrument]
+    pub async fn delete_for_songs<C: Connection>(
+        db: &Surreal<C>,
+        song_ids: Vec<SongId>,
+    ) -> StorageResult<()> {
+        db.query(delete_for_songs())
+            .bind(("songs", song_ids))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::schemas::song::SongChangeSet,
+        test_utils::{arb_song_case, create_song_with_overrides, init_test_database},
+    };
+
+    use anyhow::{anyhow, Result};
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_upsert_for_song() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        let first = Fingerprint::upsert_for_song(&db, song.id.clone(), vec![1, 2, 3]).await?;
+        assert_eq!(first.song_id, song.id);
+        assert_eq!(first.fingerprint, vec![1, 2, 3]);
+
+        // fingerprinting the song again replaces the existing record
+        let second = Fingerprint::upsert_for_song(&db, song.id.clone(), vec![u32::MAX]).await?;
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.fingerprint, vec![u32::MAX]);
+
+        assert_eq!(Fingerprint::read_all(&db).await?, vec![second.clone()]);
+        assert_eq!(
+            Fingerprint::read_for_song(&db, song.id.clone()).await?,
+            Some(second)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_for_songs() -> Result<()> {
+        let db = init_test_database().await?;
+        let song_a =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song_b =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        Fingerprint::upsert_for_song(&db, song_a.id.clone(), vec![1]).await?;
+        let fingerprint_b = Fingerprint::upsert_for_song(&db, song_b.id.clone(), vec![2]).await?;
+
+        Fingerprint::delete_for_songs(&db, vec![song_a.id.clone()]).await?;
+
+        assert_eq!(Fingerprint::read_for_song(&db, song_a.id).await?, None);
+        assert_eq!(
+            Fingerprint::read_all(&db)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("fingerprint of song b was deleted"))?,
+            fingerprint_b
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! CRUD operations for the lyrics table
+
+use std::sync::Arc;
+
+use surrealdb::{Connection, RecordId, Surreal};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        queries::lyrics::{delete_for_songs, read_for_song},
+        schemas::{
+            lyrics::{Lyrics, TABLE_NAME},
+            song::SongId,
+        },
+    },
+    errors::{Error, StorageResult},
+};
+
+impl Lyrics {
+    /// Store the lyrics of the given song, replacing its previous lyrics (if it has any).
+    ///
+    /// # Returns
+    ///
+    /// The stored record.
+    #[instrument(skip(lyrics))]
+    pub async fn upsert_for_song<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+        lyrics: Arc<str>,
+    ) -> StorageResult<Self> {
+        let record = match Self::read_for_song(db, song_id.clone()).await? {
+            Some(existing) => Self { lyrics, ..existing },
+            None => Self {
+                id: Self::generate_id(),
+                song_id,
+                lyrics,
+            },
+        };
+
+        let result: Option<Self> = db
+            .upsert(RecordId::from_inner(record.id.clone()))
+            .content(record)
+            .await?;
+        result.ok_or(Error::NotCreated)
+    }
+
+    /// Store the lyrics of the given song if it has any, otherwise delete its previous lyrics (if it had any).
+    #[instrument(skip(lyrics))]
+    pub async fn set_for_song<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+        lyrics: Option<Arc<str>>,
+    ) -> StorageResult<()> {
+        match lyrics {
+            Some(lyrics) => Self::upsert_for_song(db, song_id, lyrics).await.map(|_| ()),
+            None => Self::delete_for_songs(db, vec![song_id]).await,
+        }
+    }
+
+    #[instrument]
+    pub async fn read_all<C: Connection>(db: &Surreal<C>) -> StorageResult<Vec<Self>> {
+        Ok(db.select(TABLE_NAME).await?)
+    }
+
+    #[instrument]
+    pub async fn read_for_song<C: Connection>(
+        db: &Surreal<C>,
+        song_id: SongId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db
+            .query(read_for_song())
+            .bind(("song", song_id))
+            .await?
+            .take(0)?)
+    }
+
+    /// Delete the lyrics of the given songs.
+    #[instrument]
+    pub async fn delete_for_songs<C: Connection>(
+        db: &Surreal<C>,
+        song_ids: Vec<SongId>,
+    ) -> StorageResult<()> {
+        db.query(delete_for_songs())
+            .bind(("songs", song_ids))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::schemas::song::SongChangeSet,
+        test_utils::{arb_song_case, create_song_with_overrides, init_test_database},
+    };
+
+    use anyhow::{anyhow, Result};
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_upsert_for_song() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        let first = Lyrics::upsert_for_song(&db, song.id.clone(), "la la la".into()).await?;
+        assert_eq!(first.song_id, song.id);
+        assert_eq!(first.lyrics, "la la la".into());
+
+        // storing the song's lyrics again replaces the existing record
+        let second = Lyrics::upsert_for_song(&db, song.id.clone(), "do re mi".into()).await?;
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.lyrics, "do re mi".into());
+
+        assert_eq!(Lyrics::read_all(&db).await?, vec![second.clone()]);
+        assert_eq!(
+            Lyrics::read_for_song(&db, song.id.clone()).await?,
+            Some(second)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_for_song() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        Lyrics::set_for_song(&db, song.id.clone(), Some("la la la".into())).await?;
+        assert_eq!(
+            Lyrics::read_for_song(&db, song.id.clone())
+                .await?
+                .map(|lyrics| lyrics.lyrics),
+            Some("la la la".into())
+        );
+
+        Lyrics::set_for_song(&db, song.id.clone(), None).await?;
+        assert_eq!(Lyrics::read_for_song(&db, song.id).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_for_songs() -> Result<()> {
+        let db = init_test_database().await?;
+        let song_a =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song_b =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        Lyrics::upsert_for_song(&db, song_a.id.clone(), "a".into()).await?;
+        let lyrics_b = Lyrics::upsert_for_song(&db, song_b.id.clone(), "b".into()).await?;
+
+        Lyrics::delete_for_songs(&db, vec![song_a.id.clone()]).await?;
+
+        assert_eq!(Lyrics::read_for_song(&db, song_a.id).await?, None);
+        assert_eq!(
+            Lyrics::read_all(&db)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("lyrics of song b were deleted"))?,
+            lyrics_b
+        );
+
+        Ok(())
+    }
+}
@@ -3,5 +3,11 @@ pub mod album;
 pub mod analysis;
 pub mod artist;
 pub mod collection;
+#[cfg(feature = "analysis")]
+pub mod failed_analysis;
+#[cfg(feature = "analysis")]
+pub mod fingerprint;
+pub mod lyrics;
 pub mod playlist;
+pub mod scan_history;
 pub mod song;
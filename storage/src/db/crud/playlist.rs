@@ -1,18 +1,18 @@
 //! CRUD operations for the playlist table
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use surrealdb::{Connection, RecordId, Surreal};
 use tracing::instrument;
 
 use crate::{
     db::{
-        queries::playlist::{add_songs, read_by_name, read_songs, remove_songs},
+        queries::playlist::{add_songs, read_by_name, read_songs, remove_songs, reorder_songs},
         schemas::{
             playlist::{Playlist, PlaylistChangeSet, PlaylistId, TABLE_NAME},
             song::{Song, SongId},
         },
     },
-    errors::StorageResult,
+    errors::{Error, StorageResult},
 };
 
 impl Playlist {
@@ -67,6 +67,54 @@ impl Playlist {
         Self::read(db, new_playlist.id.clone()).await
     }
 
+    /// Create a new playlist with the given name, containing the songs of every source playlist.
+    ///
+    /// Songs keep the order they have in the source playlists (in the order the sources are given),
+    /// songs that appear more than once are only added the first time.
+    /// The source playlists are left unchanged.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - the new playlist, or `None` if any of the source playlists don't exist
+    #[instrument]
+    pub async fn merge<C: Connection>(
+        db: &Surreal<C>,
+        sources: Vec<PlaylistId>,
+        name: Arc<str>,
+    ) -> StorageResult<Option<Self>> {
+        let mut songs: Vec<SongId> = Vec::new();
+        for source in sources {
+            if Self::read(db, source.clone()).await?.is_none() {
+                return Ok(None);
+            }
+            for song in Self::read_songs(db, source).await? {
+                if !songs.contains(&song.id) {
+                    songs.push(song.id);
+                }
+            }
+        }
+
+        let Some(playlist) = Self::create(
+            db,
+            Self {
+                id: Self::generate_id(),
+                name,
+                song_count: 0,
+                runtime: Duration::from_secs(0),
+            },
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        if !songs.is_empty() {
+            Self::add_songs(db, playlist.id.clone(), songs).await?;
+        }
+
+        Self::read(db, playlist.id).await
+    }
+
     #[instrument]
     pub async fn read_all<C: Connection>(db: &Surreal<C>) -> StorageResult<Vec<Self>> {
         Ok(db.select(TABLE_NAME).await?)
@@ -109,6 +157,7 @@ impl Playlist {
         Ok(db.delete(RecordId::from_inner(id)).await?)
     }
 
+    /// appends songs to the end of a playlist, in the order they're given
     #[instrument]
     pub async fn add_songs<C: Connection>(
         db: &Surreal<C>,
@@ -118,11 +167,13 @@ impl Playlist {
         db.query(add_songs())
             .bind(("id", id.clone()))
             .bind(("songs", song_ids))
-            .await?;
+            .await?
+            .check()?;
         Self::repair(db, id).await?;
         Ok(())
     }
 
+    /// reads the songs of a playlist, in the order they appear in the playlist
     #[instrument]
     pub async fn read_songs<C: Connection>(
         db: &Surreal<C>,
@@ -131,6 +182,37 @@ impl Playlist {
         Ok(db.query(read_songs()).bind(("id", id)).await?.take(0)?)
     }
 
+    /// reorders the songs of a playlist to match `new_song_order`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidSongOrder` if `new_song_order` isn't a permutation of the songs in the playlist.
+    #[instrument]
+    pub async fn reorder_songs<C: Connection>(
+        db: &Surreal<C>,
+        id: PlaylistId,
+        new_song_order: Vec<SongId>,
+    ) -> StorageResult<()> {
+        let mut current = Self::read_songs(db, id.clone())
+            .await?
+            .into_iter()
+            .map(|song| song.id)
+            .collect::<Vec<_>>();
+        current.sort();
+        let mut new = new_song_order.clone();
+        new.sort();
+        if current != new {
+            return Err(Error::InvalidSongOrder);
+        }
+
+        db.query(reorder_songs())
+            .bind(("id", id))
+            .bind(("songs", new_song_order))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
     #[instrument]
     /// removes songs from a playlist
     ///
@@ -234,6 +316,73 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_merge() -> Result<()> {
+        let db = init_test_database().await?;
+        let songs = vec![
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?,
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?,
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?,
+        ];
+
+        // two playlists that share a song
+        let first = create_playlist();
+        Playlist::create(&db, first.clone()).await?;
+        Playlist::add_songs(
+            &db,
+            first.id.clone(),
+            vec![songs[0].id.clone(), songs[1].id.clone()],
+        )
+        .await?;
+        let second = Playlist {
+            name: "Second Playlist".into(),
+            ..create_playlist()
+        };
+        Playlist::create(&db, second.clone()).await?;
+        Playlist::add_songs(
+            &db,
+            second.id.clone(),
+            vec![songs[2].id.clone(), songs[1].id.clone()],
+        )
+        .await?;
+
+        let merged = Playlist::merge(
+            &db,
+            vec![first.id.clone(), second.id.clone()],
+            "Merged".into(),
+        )
+        .await?
+        .ok_or_else(|| anyhow!("Merged playlist not created"))?;
+
+        assert_str_eq!(merged.name, "Merged".into());
+        assert_eq!(merged.song_count, 3);
+        assert_eq!(
+            merged.runtime,
+            songs.iter().map(|song| song.runtime).sum::<Duration>()
+        );
+        assert_eq!(
+            Playlist::read_songs(&db, merged.id.clone()).await?,
+            vec![songs[0].clone(), songs[1].clone(), songs[2].clone()]
+        );
+
+        // the sources are unchanged
+        assert_eq!(Playlist::read_songs(&db, first.id.clone()).await?.len(), 2);
+        assert_eq!(Playlist::read_songs(&db, second.id.clone()).await?.len(), 2);
+
+        // merging a playlist that doesn't exist fails
+        assert_eq!(
+            Playlist::merge(
+                &db,
+                vec![first.id, Playlist::generate_id()],
+                "Merged Again".into()
+            )
+            .await?,
+            None
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_read_all() -> Result<()> {
         let db = init_test_database().await?;
@@ -318,6 +467,151 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_add_songs_keeps_order() -> Result<()> {
+        let db = init_test_database().await?;
+        let playlist = create_playlist();
+        Playlist::create(&db, playlist.clone()).await?;
+        let mut songs = Vec::new();
+        for _ in 0..4 {
+            songs.push(
+                create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                    .await?,
+            );
+        }
+
+        // songs added in separate calls are appended after the existing ones
+        Playlist::add_songs(
+            &db,
+            playlist.id.clone(),
+            vec![songs[2].id.clone(), songs[0].id.clone()],
+        )
+        .await?;
+        Playlist::add_songs(
+            &db,
+            playlist.id.clone(),
+            vec![songs[3].id.clone(), songs[1].id.clone()],
+        )
+        .await?;
+
+        let result = Playlist::read_songs(&db, playlist.id.clone()).await?;
+        assert_eq!(
+            result,
+            vec![
+                songs[2].clone(),
+                songs[0].clone(),
+                songs[3].clone(),
+                songs[1].clone()
+            ]
+        );
+
+        // removing a song doesn't disturb the order of the others, and new songs still go at the end
+        Playlist::remove_songs(&db, playlist.id.clone(), vec![songs[0].id.clone()]).await?;
+        Playlist::add_songs(&db, playlist.id.clone(), vec![songs[0].id.clone()]).await?;
+
+        let result = Playlist::read_songs(&db, playlist.id.clone()).await?;
+        assert_eq!(
+            result,
+            vec![
+                songs[2].clone(),
+                songs[3].clone(),
+                songs[1].clone(),
+                songs[0].clone()
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reorder_songs() -> Result<()> {
+        let db = init_test_database().await?;
+        let playlist = create_playlist();
+        Playlist::create(&db, playlist.clone()).await?;
+        let mut songs = Vec::new();
+        for _ in 0..3 {
+            songs.push(
+                create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default())
+                    .await?,
+            );
+        }
+        Playlist::add_songs(
+            &db,
+            playlist.id.clone(),
+            songs.iter().map(|song| song.id.clone()).collect(),
+        )
+        .await?;
+
+        Playlist::reorder_songs(
+            &db,
+            playlist.id.clone(),
+            vec![
+                songs[1].id.clone(),
+                songs[2].id.clone(),
+                songs[0].id.clone(),
+            ],
+        )
+        .await?;
+
+        let result = Playlist::read_songs(&db, playlist.id.clone()).await?;
+        assert_eq!(
+            result,
+            vec![songs[1].clone(), songs[2].clone(), songs[0].clone()]
+        );
+
+        // songs added after a reorder go at the end
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        Playlist::add_songs(&db, playlist.id.clone(), vec![song.id.clone()]).await?;
+
+        let result = Playlist::read_songs(&db, playlist.id.clone()).await?;
+        assert_eq!(
+            result,
+            vec![songs[1].clone(), songs[2].clone(), songs[0].clone(), song]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reorder_songs_invalid() -> Result<()> {
+        let db = init_test_database().await?;
+        let playlist = create_playlist();
+        Playlist::create(&db, playlist.clone()).await?;
+        let song_a =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song_b =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        Playlist::add_songs(
+            &db,
+            playlist.id.clone(),
+            vec![song_a.id.clone(), song_b.id.clone()],
+        )
+        .await?;
+
+        // missing a song
+        let result =
+            Playlist::reorder_songs(&db, playlist.id.clone(), vec![song_b.id.clone()]).await;
+        assert!(matches!(result, Err(Error::InvalidSongOrder)));
+
+        // a song that isn't in the playlist
+        let song_c =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let result = Playlist::reorder_songs(
+            &db,
+            playlist.id.clone(),
+            vec![song_b.id.clone(), song_a.id.clone(), song_c.id.clone()],
+        )
+        .await;
+        assert!(matches!(result, Err(Error::InvalidSongOrder)));
+
+        // the order is unchanged
+        let result = Playlist::read_songs(&db, playlist.id.clone()).await?;
+        assert_eq!(result, vec![song_a, song_b]);
+
+        Ok(())
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_remove_songs() -> Result<()> {
@@ -0,0 +1,137 @@
+//! CRUD operations for the scan_history table
+
+use std::path::PathBuf;
+
+use surrealdb::{Connection, RecordId, Surreal};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        queries::scan_history::{read_all_recent_first, read_for_path},
+        schemas::scan_history::{ScanMetrics, MAX_HISTORY},
+    },
+    errors::{Error, StorageResult},
+};
+
+impl ScanMetrics {
+    /// Record the metrics of a library path's scan.
+    ///
+    /// Only the [`MAX_HISTORY`] most recent metrics are kept for each path, older ones are deleted.
+    ///
+    /// # Returns
+    ///
+    /// The created record.
+    #[instrument]
+    pub async fn record<C: Connection>(db: &Surreal<C>, metrics: Self) -> StorageResult<Self> {
+        let path = metrics.path.clone();
+        let created: Self = db
+            .create(RecordId::from_inner(metrics.id.clone()))
+            .content(metrics)
+            .await?
+            .ok_or(Error::NotCreated)?;
+
+        for old in Self::read_for_path(db, path)
+            .await?
+            .into_iter()
+            .skip(MAX_HISTORY)
+        {
+            let _: Option<Self> = db.delete(RecordId::from_inner(old.id)).await?;
+        }
+
+        Ok(created)
+    }
+
+    /// Read the metrics of every recorded scan, most recent first.
+    #[instrument]
+    pub async fn read_all<C: Connection>(db: &Surreal<C>) -> StorageResult<Vec<Self>> {
+        Ok(db.query(read_all_recent_first()).await?.take(0)?)
+    }
+
+    /// Read the metrics of the recorded scans of a library path, most recent first.
+    #[instrument]
+    pub async fn read_for_path<C: Connection>(
+        db: &Surreal<C>,
+        path: PathBuf,
+    ) -> StorageResult<Vec<Self>> {
+        Ok(db
+            .query(read_for_path())
+            .bind(("path", path))
+            .await?
+            .take(0)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::init_test_database;
+
+    use anyhow::Result;
+    use chrono::{Duration, Utc};
+    use pretty_assertions::assert_eq;
+
+    fn metrics(path: &str, minutes_ago: i64) -> ScanMetrics {
+        ScanMetrics {
+            id: ScanMetrics::generate_id(),
+            path: PathBuf::from(path),
+            files_found: 10,
+            files_scanned: 8,
+            errors: 1,
+            duration_ms: 1500,
+            scanned_at: Utc::now() - Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record() -> Result<()> {
+        let db = init_test_database().await?;
+
+        let expected = metrics("/music", 0);
+        let created = ScanMetrics::record(&db, expected.clone()).await?;
+        assert_eq!(created.id, expected.id);
+        assert_eq!(created.files_scanned, expected.files_scanned);
+
+        let all = ScanMetrics::read_all(&db).await?;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, expected.id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_keeps_recent_history() -> Result<()> {
+        let db = init_test_database().await?;
+
+        let other = ScanMetrics::record(&db, metrics("/other", 0)).await?;
+        // record more than the max history for one path, oldest first
+        let mut recorded = Vec::new();
+        for minutes_ago in (0..i64::try_from(MAX_HISTORY + 3)?).rev() {
+            recorded.push(ScanMetrics::record(&db, metrics("/music", minutes_ago)).await?);
+        }
+
+        let history = ScanMetrics::read_for_path(&db, PathBuf::from("/music")).await?;
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(
+            history.iter().map(|m| m.id.clone()).collect::<Vec<_>>(),
+            recorded
+                .iter()
+                .rev()
+                .take(MAX_HISTORY)
+                .map(|m| m.id.clone())
+                .collect::<Vec<_>>()
+        );
+
+        // the other path's history is untouched
+        assert_eq!(
+            ScanMetrics::read_for_path(&db, PathBuf::from("/other"))
+                .await?
+                .into_iter()
+                .map(|m| m.id)
+                .collect::<Vec<_>>(),
+            vec![other.id]
+        );
+        assert_eq!(ScanMetrics::read_all(&db).await?.len(), MAX_HISTORY + 1);
+
+        Ok(())
+    }
+}
@@ -1,29 +1,37 @@
 //! CRUD operations for the song table
 
-use std::path::PathBuf;
+use std::{collections::BTreeSet, path::PathBuf, sync::Arc, time::Duration};
 
 use log::info;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use surrealdb::{Connection, RecordId, Surreal};
 use tracing::instrument;
 
 #[cfg(feature = "analysis")]
-use crate::db::schemas::analysis::Analysis;
+use crate::db::schemas::{
+    analysis::Analysis, failed_analysis::FailedAnalysis, fingerprint::Fingerprint,
+};
 use crate::{
     db::{
         queries::song::{
-            read_album, read_album_artist, read_artist, read_collections, read_playlists,
-            read_song_by_path,
+            batch_update, increment_play_count, increment_skip_count, read_album,
+            read_album_artist, read_artist, read_collections, read_genres, read_ids, read_many,
+            read_playlists, read_random_songs, read_song_by_path, read_songs_by_genre,
+            read_songs_by_year_range, toggle_favorite,
         },
         schemas::{
             album::Album,
             artist::Artist,
             collection::Collection,
+            lyrics::Lyrics,
             playlist::Playlist,
-            song::{Song, SongChangeSet, SongId, SongMetadata, TABLE_NAME},
+            song::{Song, SongChangeSet, SongId, SongMetadata, MAX_RATING, TABLE_NAME},
         },
     },
     errors::{Error, SongIOError, StorageResult},
 };
+#[cfg(feature = "analysis")]
+use mecomp_analysis::{fingerprint::fingerprint, misc::LoudnessDesc, window_scale, ResampledAudio};
 use one_or_many::OneOrMany;
 
 #[derive(Debug)]
@@ -133,16 +141,124 @@ impl Song {
             .take(0)?)
     }
 
+    /// Full-text search for songs whose title, artist, album, genre, or lyrics match the query.
+    ///
+    /// Results are ordered by relevance (BM25 score), with matches on the title weighted the highest.
+    /// The lyrics are kept in their own table, so they are searched separately,
+    /// and the scores of songs that match on both are added together.
     #[instrument]
     pub async fn search<C: Connection>(
         db: &Surreal<C>,
         query: &str,
         limit: i64,
     ) -> StorageResult<Vec<Self>> {
-        Ok(db
-            .query("SELECT *, search::score(0) * 2 + search::score(1) * 1 AS relevance FROM song WHERE title @0@ $query OR artist @1@ $query ORDER BY relevance DESC LIMIT $limit")
+        #[derive(serde::Deserialize)]
+        struct Hit {
+            id: SongId,
+            relevance: f64,
+        }
+
+        let mut response = db
+            .query("SELECT id, search::score(0) * 2 + search::score(1) * 1 + search::score(2) * 1 + search::score(3) * 0.5 AS relevance FROM song WHERE title @0@ $query OR artist @1@ $query OR album @2@ $query OR genre @3@ $query ORDER BY relevance DESC LIMIT $limit")
+            .query("SELECT song_id AS id, search::score(0) * 0.5 AS relevance FROM lyrics WHERE lyrics @0@ $query ORDER BY relevance DESC LIMIT $limit")
             .bind(("query", query.to_owned()))
             .bind(("limit", limit))
+            .await?;
+        let by_tags: Vec<Hit> = response.take(0)?;
+        let by_lyrics: Vec<Hit> = response.take(1)?;
+
+        let mut hits: Vec<Hit> = Vec::with_capacity(by_tags.len() + by_lyrics.len());
+        for hit in by_tags.into_iter().chain(by_lyrics) {
+            match hits.iter_mut().find(|existing| existing.id == hit.id) {
+                Some(existing) => existing.relevance += hit.relevance,
+                None => hits.push(hit),
+            }
+        }
+        hits.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+        hits.truncate(usize::try_from(limit).unwrap_or_default());
+
+        if hits.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<SongId> = hits.into_iter().map(|hit| hit.id).collect();
+        Ok(db.query(read_many()).bind(("ids", ids)).await?.take(0)?)
+    }
+
+    /// Read the distinct genres of all the songs in the library, sorted alphabetically.
+    #[instrument]
+    pub async fn read_genres<C: Connection>(db: &Surreal<C>) -> StorageResult<Vec<Arc<str>>> {
+        let genres: Vec<OneOrMany<Arc<str>>> = db.query(read_genres()).await?.take(0)?;
+
+        Ok(genres
+            .into_iter()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect())
+    }
+
+    /// Read the songs with the given genre (exact match), ordered by title.
+    #[instrument]
+    pub async fn read_by_genre<C: Connection>(
+        db: &Surreal<C>,
+        genre: &str,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        Ok(db
+            .query(read_songs_by_genre())
+            .bind(("genre", genre.to_owned()))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?)
+    }
+
+    /// Read the songs released between `start` and `end` (inclusive), ordered by release year then title.
+    ///
+    /// Songs without a release year are excluded.
+    #[instrument]
+    pub async fn read_by_year_range<C: Connection>(
+        db: &Surreal<C>,
+        start: i32,
+        end: i32,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        Ok(db
+            .query(read_songs_by_year_range())
+            .bind(("start", start))
+            .bind(("end", end))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?)
+    }
+
+    /// Read a random sample of (up to `limit`) songs.
+    ///
+    /// If a `seed` is given, the sample is reproducible: the same seed gives the same songs (in the same order)
+    /// as long as the library doesn't change.
+    #[instrument]
+    pub async fn read_random<C: Connection>(
+        db: &Surreal<C>,
+        limit: i64,
+        seed: Option<u64>,
+    ) -> StorageResult<Vec<Self>> {
+        let Some(seed) = seed else {
+            return Ok(db
+                .query(read_random_songs())
+                .bind(("limit", limit))
+                .await?
+                .take(0)?);
+        };
+
+        // only sample the ids, so we don't have to read the whole library
+        let mut ids: Vec<SongId> = db.query(read_ids()).await?.take(0)?;
+        // the order ids are read in isn't guaranteed, so sort them to make the sample reproducible
+        ids.sort();
+        let amount = usize::try_from(limit).unwrap_or_default().min(ids.len());
+        let (sample, _) = ids.partial_shuffle(&mut StdRng::seed_from_u64(seed), amount);
+
+        Ok(db
+            .query(read_many())
+            .bind(("ids", sample.to_vec()))
             .await?
             .take(0)?)
     }
@@ -154,12 +270,22 @@ impl Song {
     /// - the album name has changed
     /// - the album artist name(s) have changed
     /// - TODO: The duration has changed
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidRating` if the changes set a rating that isn't between 1 and [`MAX_RATING`].
     #[instrument]
     pub async fn update<C: Connection>(
         db: &Surreal<C>,
         id: SongId,
         changes: SongChangeSet,
     ) -> StorageResult<Option<Self>> {
+        if let Some(Some(rating)) = changes.rating {
+            if !(1..=MAX_RATING).contains(&rating) {
+                return Err(Error::InvalidRating(rating));
+            }
+        }
+
         if changes.album.is_some() || changes.album_artist.is_some() {
             let old_album = Self::read_album(db, id.clone()).await?;
 
@@ -229,6 +355,50 @@ impl Song {
         Ok(db.update(RecordId::from_inner(id)).merge(changes).await?)
     }
 
+    /// Apply the same changes to many songs, returning the number of songs that were updated.
+    ///
+    /// Songs that don't exist are skipped.
+    ///
+    /// If the changes don't need relations to be repaired (see [`Song::update`]), they're applied to every song in a single query.
+    /// Otherwise, each song is updated (and its relations repaired) one at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidRating` if the changes set a rating that isn't between 1 and [`MAX_RATING`].
+    #[instrument]
+    pub async fn batch_update<C: Connection>(
+        db: &Surreal<C>,
+        ids: Vec<SongId>,
+        changes: SongChangeSet,
+    ) -> StorageResult<u64> {
+        if let Some(Some(rating)) = changes.rating {
+            if !(1..=MAX_RATING).contains(&rating) {
+                return Err(Error::InvalidRating(rating));
+            }
+        }
+
+        if changes.artist.is_some() || changes.album.is_some() || changes.album_artist.is_some() {
+            let mut updated = 0;
+            for id in ids {
+                if Self::read(db, id.clone()).await?.is_none() {
+                    continue;
+                }
+                if Self::update(db, id, changes.clone()).await?.is_some() {
+                    updated += 1;
+                }
+            }
+            return Ok(updated);
+        }
+
+        let updated: Vec<Self> = db
+            .query(batch_update())
+            .bind(("ids", ids))
+            .bind(("changes", changes))
+            .await?
+            .take(0)?;
+        Ok(updated.len() as u64)
+    }
+
     /// Delete a song from the database,
     /// will also:
     /// - go through the artist and album tables and remove references to it from there
@@ -248,6 +418,14 @@ impl Song {
         if let Ok(Some(analysis)) = Analysis::read_for_song(db, id.clone()).await {
             Analysis::delete(db, analysis.id).await?;
         }
+        // and any record of failing to analyze it
+        #[cfg(feature = "analysis")]
+        FailedAnalysis::delete_for_songs(db, vec![id.clone()]).await?;
+        // and its fingerprint
+        #[cfg(feature = "analysis")]
+        Fingerprint::delete_for_songs(db, vec![id.clone()]).await?;
+        // and its lyrics
+        Lyrics::delete_for_songs(db, vec![id.clone()]).await?;
 
         // if we're not deleting orphans, we can just delete the song
         if !delete_orphans {
@@ -286,6 +464,304 @@ impl Song {
         Ok(db.delete(RecordId::from_inner(id)).await?)
     }
 
+    /// Record that a [`Song`] was played to completion,
+    /// incrementing its play count and setting when it was last played to now.
+    ///
+    /// # Returns
+    ///
+    /// The updated song, or `None` if the song doesn't exist.
+    #[instrument]
+    pub async fn increment_play_count<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db
+            .query(increment_play_count())
+            .bind(("id", id))
+            .await?
+            .take(0)?)
+    }
+
+    /// Record that a [`Song`] was skipped before most of it was played, incrementing its skip count.
+    ///
+    /// # Returns
+    ///
+    /// The updated song, or `None` if the song doesn't exist.
+    #[instrument]
+    pub async fn increment_skip_count<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db
+            .query(increment_skip_count())
+            .bind(("id", id))
+            .await?
+            .take(0)?)
+    }
+
+    /// Rate a [`Song`] from 1 to [`MAX_RATING`] stars, or clear its rating if `rating` is `None`.
+    ///
+    /// # Returns
+    ///
+    /// The updated song, or `None` if the song doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidRating` if the rating isn't between 1 and [`MAX_RATING`].
+    #[instrument]
+    pub async fn set_rating<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+        rating: Option<u8>,
+    ) -> StorageResult<Option<Self>> {
+        Self::update(
+            db,
+            id,
+            SongChangeSet {
+                rating: Some(rating),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Add a [`Song`] to the user's favorites, or remove it if it's already one of them.
+    ///
+    /// # Returns
+    ///
+    /// The updated song, or `None` if the song doesn't exist.
+    #[instrument]
+    pub async fn toggle_favorite<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+    ) -> StorageResult<Option<Self>> {
+        Ok(db
+            .query(toggle_favorite())
+            .bind(("id", id))
+            .await?
+            .take(0)?)
+    }
+
+    /// Bookmark a position in a [`Song`], e.g. where the user left off in an audiobook.
+    ///
+    /// Positions are stored to the nearest millisecond, and bookmarking a position that's already bookmarked does nothing.
+    ///
+    /// # Returns
+    ///
+    /// The updated song, or `None` if the song doesn't exist.
+    #[instrument]
+    pub async fn add_bookmark<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+        position: Duration,
+    ) -> StorageResult<Option<Self>> {
+        let Some(song) = Self::read(db, id.clone()).await? else {
+            return Ok(None);
+        };
+
+        let position = bookmark_precision(position);
+        let mut bookmarks = song.bookmark_positions;
+        match bookmarks.binary_search(&position) {
+            Ok(_) => {
+                return Ok(Some(Self {
+                    bookmark_positions: bookmarks,
+                    ..song
+                }))
+            }
+            Err(index) => bookmarks.insert(index, position),
+        }
+
+        Self::update(
+            db,
+            id,
+            SongChangeSet {
+                bookmark_positions: Some(bookmarks),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Remove the bookmark at `position` (to the nearest millisecond) from a [`Song`], if there is one.
+    ///
+    /// # Returns
+    ///
+    /// The updated song, or `None` if the song doesn't exist.
+    #[instrument]
+    pub async fn remove_bookmark<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+        position: Duration,
+    ) -> StorageResult<Option<Self>> {
+        let Some(song) = Self::read(db, id.clone()).await? else {
+            return Ok(None);
+        };
+
+        let position = bookmark_precision(position);
+        let mut bookmarks = song.bookmark_positions;
+        let Ok(index) = bookmarks.binary_search(&position) else {
+            return Ok(Some(Self {
+                bookmark_positions: bookmarks,
+                ..song
+            }));
+        };
+        bookmarks.remove(index);
+
+        Self::update(
+            db,
+            id,
+            SongChangeSet {
+                bookmark_positions: Some(bookmarks),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Merge the `remove` songs into the `keep` song, e.g. when the same song was ripped twice at different qualities.
+    ///
+    /// The play and skip counts of the removed songs are added to the kept song's,
+    /// and the kept song takes the most recent play, the highest rating, and is a favorite if any of them were.
+    /// The kept song is also added to the playlists the removed songs were in, then the removed songs are deleted.
+    ///
+    /// Ids in `remove` that don't exist, or are `keep` itself, are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The merged song, or `None` if the `keep` song doesn't exist.
+    #[instrument]
+    pub async fn merge_duplicates<C: Connection>(
+        db: &Surreal<C>,
+        keep: SongId,
+        remove: Vec<SongId>,
+    ) -> StorageResult<Option<Self>> {
+        let Some(mut song) = Self::read(db, keep.clone()).await? else {
+            return Ok(None);
+        };
+        let mut playlists = Self::read_playlists(db, keep.clone())
+            .await?
+            .into_iter()
+            .map(|playlist| playlist.id.to_string())
+            .collect::<BTreeSet<_>>();
+
+        for id in remove {
+            if id == keep {
+                continue;
+            }
+            let Some(duplicate) = Self::read(db, id).await? else {
+                continue;
+            };
+
+            song.play_count += duplicate.play_count;
+            song.skip_count += duplicate.skip_count;
+            song.last_played = song.last_played.max(duplicate.last_played);
+            song.rating = song.rating.max(duplicate.rating);
+            song.favorite |= duplicate.favorite;
+
+            for playlist in Self::read_playlists(db, duplicate.id.clone()).await? {
+                if playlists.insert(playlist.id.to_string()) {
+                    Playlist::add_songs(db, playlist.id, vec![keep.clone()]).await?;
+                }
+            }
+
+            Self::delete(db, duplicate.id).await?;
+        }
+
+        Self::update(
+            db,
+            keep,
+            SongChangeSet {
+                play_count: Some(song.play_count),
+                skip_count: Some(song.skip_count),
+                last_played: song.last_played,
+                rating: Some(song.rating),
+                favorite: Some(song.favorite),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Compute the `ReplayGain` track gain of a [`Song`] from its (resampled) audio,
+    /// and store the result in the database.
+    ///
+    /// The gain is what's needed to normalize the song's integrated loudness to the
+    /// `ReplayGain` 2.0 reference loudness of -18 LUFS.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the song.
+    /// * `samples` - The decoded and resampled audio of the song.
+    ///
+    /// # Returns
+    ///
+    /// The loudness of the song, so it can be combined with the loudness of the other songs on its album
+    /// to compute their album gain (see [`LoudnessDesc::get_album_replaygain`]).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the song doesn't exist, or if there is an error updating the song.
+    #[cfg(feature = "analysis")]
+    #[instrument(skip(samples))]
+    pub async fn compute_replaygain<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+        samples: &ResampledAudio,
+    ) -> StorageResult<LoudnessDesc> {
+        let mut loudness_desc = LoudnessDesc::new(samples.sample_rate);
+        let window_size = LoudnessDesc::WINDOW_SIZE * window_scale(samples.sample_rate);
+        for window in samples.samples.chunks(window_size) {
+            loudness_desc.do_(window);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let replaygain_track_gain = loudness_desc.get_replaygain().map(|gain| gain as f32);
+
+        Self::update(
+            db,
+            id,
+            SongChangeSet {
+                replaygain_track_gain: Some(replaygain_track_gain),
+                ..Default::default()
+            },
+        )
+        .await?
+        .ok_or(Error::NotFound)?;
+
+        Ok(loudness_desc)
+    }
+
+    /// Compute the acoustic fingerprint of a [`Song`] from its (resampled) audio,
+    /// and store it in the database as the song's [`Fingerprint`].
+    ///
+    /// The fingerprint only depends on the audio, so songs with the same audio have similar fingerprints
+    /// regardless of their tags, paths, or encodings (see `mecomp_analysis::fingerprint::bit_error_rate`).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the song.
+    /// * `samples` - The decoded and resampled audio of the song.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the song doesn't exist, or if there is an error storing the fingerprint.
+    #[cfg(feature = "analysis")]
+    #[instrument(skip(samples))]
+    pub async fn compute_fingerprint<C: Connection>(
+        db: &Surreal<C>,
+        id: SongId,
+        samples: &ResampledAudio,
+    ) -> StorageResult<()> {
+        if Self::read(db, id.clone()).await?.is_none() {
+            return Err(Error::NotFound);
+        }
+
+        let fingerprint = fingerprint(&samples.samples, samples.sample_rate);
+        Fingerprint::upsert_for_song(db, id, fingerprint).await?;
+
+        Ok(())
+    }
+
     /// Create a new [`Song`] from song metadata and load it into the database.
     ///
     /// # Arguments
@@ -342,10 +818,23 @@ impl Song {
             track: metadata.track,
             disc: metadata.disc,
             path: metadata.path,
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
         // add that song to the database
         let song_id = Self::create(db, song.clone()).await?.unwrap().id;
 
+        // store its lyrics, if it has any
+        if let Some(lyrics) = metadata.lyrics {
+            Lyrics::upsert_for_song(db, song_id.clone(), lyrics).await?;
+        }
+
         // add the song to the artists, if it's not already there (which it won't be)
         for artist in &artists {
             Artist::add_songs(db, artist.id.clone(), vec![song_id.clone()]).await?;
@@ -358,6 +847,11 @@ impl Song {
     }
 }
 
+/// Bookmarks are stored to the nearest millisecond, so that they survive being round-tripped through the database as seconds.
+fn bookmark_precision(position: Duration) -> Duration {
+    Duration::from_millis(u64::try_from((position.as_micros() + 500) / 1000).unwrap_or(u64::MAX))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -369,7 +863,9 @@ mod test {
     };
 
     use anyhow::{anyhow, Result};
+    use chrono::Utc;
     use pretty_assertions::assert_eq;
+    use rstest::rstest;
     use std::time::Duration;
 
     #[tokio::test]
@@ -389,6 +885,14 @@ mod test {
             release_year: None,
             extension: "mp3".into(),
             path: "song.mp3".to_string().into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         };
 
         let created = Song::create(&db, song.clone()).await?;
@@ -611,25 +1115,173 @@ mod test {
     }
 
     #[tokio::test]
-    async fn test_search_by_title() -> Result<()> {
+    async fn test_read_genres() -> Result<()> {
         let db = init_test_database().await?;
-        let song1 = create_song_with_overrides(
-            &db,
-            arb_song_case()(),
-            SongChangeSet {
-                title: Some("Foo Bar".into()),
-                ..Default::default()
-            },
-        )
-        .await?;
-        let song2 = create_song_with_overrides(
-            &db,
-            arb_song_case()(),
-            SongChangeSet {
-                title: Some("Foo".into()),
-                ..Default::default()
-            },
-        )
+        for genre in [
+            OneOrMany::One("Rock".into()),
+            OneOrMany::Many(vec!["Jazz".into(), "Blues".into()]),
+            OneOrMany::One("Jazz".into()),
+            OneOrMany::None,
+        ] {
+            create_song_with_overrides(
+                &db,
+                arb_song_case()(),
+                SongChangeSet {
+                    genre: Some(genre),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        }
+
+        let genres = Song::read_genres(&db).await?;
+        assert_eq!(
+            genres,
+            vec![Arc::from("Blues"), Arc::from("Jazz"), Arc::from("Rock")]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_by_genre() -> Result<()> {
+        let db = init_test_database().await?;
+        let mut songs = Vec::new();
+        for (title, genre) in [
+            ("C", OneOrMany::One("Jazz".into())),
+            ("A", OneOrMany::Many(vec!["Blues".into(), "Jazz".into()])),
+            ("B", OneOrMany::One("Jazz Fusion".into())),
+            ("D", OneOrMany::None),
+        ] {
+            songs.push(
+                create_song_with_overrides(
+                    &db,
+                    arb_song_case()(),
+                    SongChangeSet {
+                        title: Some(title.into()),
+                        genre: Some(genre),
+                        ..Default::default()
+                    },
+                )
+                .await?,
+            );
+        }
+
+        // exact matches only, ordered by title
+        let found = Song::read_by_genre(&db, "Jazz", 10).await?;
+        assert_eq!(found, vec![songs[1].clone(), songs[0].clone()]);
+
+        let found = Song::read_by_genre(&db, "Jazz", 1).await?;
+        assert_eq!(found, vec![songs[1].clone()]);
+
+        let found = Song::read_by_genre(&db, "Classical", 10).await?;
+        assert!(found.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_random() -> Result<()> {
+        let db = init_test_database().await?;
+        for _ in 0..10 {
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        }
+
+        let sample = Song::read_random(&db, 5, None).await?;
+        assert_eq!(sample.len(), 5);
+        // no duplicates
+        let ids = sample
+            .iter()
+            .map(|song| song.id.to_string())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(ids.len(), 5);
+
+        // can't sample more songs than there are
+        let sample = Song::read_random(&db, 20, None).await?;
+        assert_eq!(sample.len(), 10);
+
+        // seeded samples are reproducible
+        let sample = Song::read_random(&db, 5, Some(42)).await?;
+        assert_eq!(sample.len(), 5);
+        assert_eq!(sample, Song::read_random(&db, 5, Some(42)).await?);
+        let sample = Song::read_random(&db, 20, Some(42)).await?;
+        assert_eq!(sample.len(), 10);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_by_year_range() -> Result<()> {
+        let db = init_test_database().await?;
+        let mut songs = Vec::new();
+        for (title, release_year) in [
+            ("B", Some(1975)),
+            ("A", Some(1979)),
+            ("C", Some(1970)),
+            ("A", Some(1975)),
+            ("E", Some(1980)),
+            ("F", Some(1969)),
+            ("G", None),
+        ] {
+            songs.push(
+                create_song_with_overrides(
+                    &db,
+                    arb_song_case()(),
+                    SongChangeSet {
+                        title: Some(title.into()),
+                        release_year: Some(release_year),
+                        ..Default::default()
+                    },
+                )
+                .await?,
+            );
+        }
+
+        // inclusive on both ends, ordered by year then title, songs without a year are excluded
+        let found = Song::read_by_year_range(&db, 1970, 1979, 10).await?;
+        assert_eq!(
+            found,
+            vec![
+                songs[2].clone(),
+                songs[3].clone(),
+                songs[0].clone(),
+                songs[1].clone()
+            ]
+        );
+
+        let found = Song::read_by_year_range(&db, 1970, 1979, 2).await?;
+        assert_eq!(found, vec![songs[2].clone(), songs[3].clone()]);
+
+        let found = Song::read_by_year_range(&db, i32::MIN, i32::MAX, 10).await?;
+        assert_eq!(found.len(), 6);
+
+        // an empty range
+        let found = Song::read_by_year_range(&db, 1979, 1970, 10).await?;
+        assert!(found.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_by_title() -> Result<()> {
+        let db = init_test_database().await?;
+        let song1 = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                title: Some("Foo Bar".into()),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let song2 = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                title: Some("Foo".into()),
+                ..Default::default()
+            },
+        )
         .await?;
 
         let found = Song::search(&db, "Foo", 2).await?;
@@ -688,6 +1340,80 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_search_by_album_and_genre() -> Result<()> {
+        let db = init_test_database().await?;
+        let song1 = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                album: Some("Kind of Blue".into()),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let song2 = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                genre: Some(OneOrMany::One("Jazz".into())),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let song3 = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                title: Some("Blue".into()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let found = Song::search(&db, "Blue", 3).await?;
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&song1));
+        assert!(found.contains(&song3));
+        // matches on the title are weighted the highest
+        assert_eq!(found.first(), Some(&song3));
+
+        let found = Song::search(&db, "Jazz", 3).await?;
+        assert_eq!(found, vec![song2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_by_lyrics() -> Result<()> {
+        let db = init_test_database().await?;
+        let song1 =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        Lyrics::upsert_for_song(
+            &db,
+            song1.id.clone(),
+            "Is this the real life? Is this just fantasy?".into(),
+        )
+        .await?;
+        let song2 = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                title: Some("Fantasy".into()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let found = Song::search(&db, "fantasy", 3).await?;
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&song1));
+        // matches on the title are weighted higher than matches on the lyrics
+        assert_eq!(found.first(), Some(&song2));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_update_no_repair() -> Result<()> {
         let db = init_test_database().await?;
@@ -718,6 +1444,58 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_batch_update() -> Result<()> {
+        let db = init_test_database().await?;
+        let song1 =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let song2 =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let untouched =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let ids = vec![song1.id.clone(), song2.id.clone(), Song::generate_id()];
+
+        // changes that don't need relations repaired
+        let changes = SongChangeSet {
+            genre: Some(OneOrMany::One("Batch Genre".into())),
+            release_year: Some(None),
+            ..Default::default()
+        };
+        assert_eq!(Song::batch_update(&db, ids.clone(), changes).await?, 2);
+        for id in [song1.id.clone(), song2.id.clone()] {
+            let song = Song::read(&db, id).await?.unwrap();
+            assert_eq!(song.genre, OneOrMany::One("Batch Genre".into()));
+            assert_eq!(song.release_year, None);
+        }
+        assert_eq!(
+            Song::read(&db, untouched.id.clone()).await?,
+            Some(untouched)
+        );
+
+        // changes that do
+        let changes = SongChangeSet {
+            artist: Some(OneOrMany::One("Batch Artist".into())),
+            ..Default::default()
+        };
+        assert_eq!(Song::batch_update(&db, ids.clone(), changes).await?, 2);
+        for id in [song1.id.clone(), song2.id.clone()] {
+            let artist = Song::read_artist(&db, id).await?;
+            assert_eq!(artist.len(), 1);
+            assert_eq!(artist.get(0).unwrap().name, "Batch Artist".into());
+        }
+
+        // invalid ratings are rejected
+        let changes = SongChangeSet {
+            rating: Some(Some(MAX_RATING + 1)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Song::batch_update(&db, ids, changes).await,
+            Err(Error::InvalidRating(_))
+        ));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_update_artist() -> Result<()> {
         let db = init_test_database().await?;
@@ -946,6 +1724,382 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_increment_play_count() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        assert_eq!(song.play_count, 0);
+        assert_eq!(song.last_played, None);
+
+        let before = Utc::now();
+        let played = Song::increment_play_count(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(played.play_count, 1);
+        let last_played = played
+            .last_played
+            .ok_or_else(|| anyhow!("Last played not set"))?;
+        assert!(last_played >= before);
+
+        let played = Song::increment_play_count(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(played.play_count, 2);
+        assert!(played.last_played >= Some(last_played));
+        assert_eq!(Song::read(&db, song.id).await?, Some(played));
+
+        // incrementing the play count of a song that doesn't exist does nothing
+        assert_eq!(
+            Song::increment_play_count(&db, Song::generate_id()).await?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_increment_skip_count() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        assert_eq!(song.skip_count, 0);
+
+        let skipped = Song::increment_skip_count(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(skipped.skip_count, 1);
+        // skipping a song doesn't count as playing it
+        assert_eq!(skipped.play_count, 0);
+        assert_eq!(skipped.last_played, None);
+        assert_eq!(Song::read(&db, song.id).await?, Some(skipped));
+
+        // incrementing the skip count of a song that doesn't exist does nothing
+        assert_eq!(
+            Song::increment_skip_count(&db, Song::generate_id()).await?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_play_stats() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        // none of the concurrent updates are lost
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..10 {
+            let (task_db, id) = (db.clone(), song.id.clone());
+            tasks.spawn(async move { Song::increment_play_count(&task_db, id).await });
+            let (task_db, id) = (db.clone(), song.id.clone());
+            tasks.spawn(async move { Song::increment_skip_count(&task_db, id).await });
+            let (task_db, id) = (db.clone(), song.id.clone());
+            tasks.spawn(async move { Song::toggle_favorite(&task_db, id).await });
+        }
+        while let Some(result) = tasks.join_next().await {
+            assert!(result??.is_some());
+        }
+
+        let song = Song::read(&db, song.id)
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(song.play_count, 10);
+        assert_eq!(song.skip_count, 10);
+        // toggled an even number of times
+        assert!(!song.favorite);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::lowest(Some(1))]
+    #[case::highest(Some(MAX_RATING))]
+    #[case::cleared(None)]
+    #[tokio::test]
+    async fn test_set_rating(#[case] rating: Option<u8>) -> Result<()> {
+        let db = init_test_database().await?;
+        let song = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                rating: Some(Some(3)),
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert_eq!(song.rating, Some(3));
+
+        let rated = Song::set_rating(&db, song.id.clone(), rating)
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(rated.rating, rating);
+        assert_eq!(Song::read(&db, song.id).await?, Some(rated));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::zero(0)]
+    #[case::too_high(MAX_RATING + 1)]
+    #[tokio::test]
+    async fn test_set_rating_invalid(#[case] rating: u8) -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+
+        let result = Song::set_rating(&db, song.id.clone(), Some(rating)).await;
+        assert!(matches!(result, Err(Error::InvalidRating(r)) if r == rating));
+
+        // the song is left unrated
+        let song = Song::read(&db, song.id)
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(song.rating, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_toggle_favorite() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        assert!(!song.favorite);
+
+        let toggled = Song::toggle_favorite(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert!(toggled.favorite);
+        assert_eq!(Song::read(&db, song.id.clone()).await?, Some(toggled));
+
+        let toggled = Song::toggle_favorite(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert!(!toggled.favorite);
+        assert_eq!(Song::read(&db, song.id).await?, Some(toggled));
+
+        // toggling a song that doesn't exist does nothing
+        assert_eq!(Song::toggle_favorite(&db, Song::generate_id()).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_bookmarks() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        assert!(song.bookmark_positions.is_empty());
+
+        // bookmarks are kept sorted, and rounded to the nearest millisecond
+        Song::add_bookmark(&db, song.id.clone(), Duration::from_secs(90)).await?;
+        let bookmarked =
+            Song::add_bookmark(&db, song.id.clone(), Duration::from_micros(30_000_600))
+                .await?
+                .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(
+            bookmarked.bookmark_positions,
+            vec![Duration::from_millis(30_001), Duration::from_secs(90)]
+        );
+        assert_eq!(
+            Song::read(&db, song.id.clone()).await?,
+            Some(bookmarked.clone())
+        );
+
+        // bookmarking the same position twice does nothing
+        let same = Song::add_bookmark(&db, song.id.clone(), Duration::from_secs(90))
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(same, bookmarked);
+
+        let removed =
+            Song::remove_bookmark(&db, song.id.clone(), Duration::from_micros(30_000_900))
+                .await?
+                .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(removed.bookmark_positions, vec![Duration::from_secs(90)]);
+        assert_eq!(
+            Song::read(&db, song.id.clone()).await?,
+            Some(removed.clone())
+        );
+
+        // removing a position that isn't bookmarked does nothing
+        let same = Song::remove_bookmark(&db, song.id.clone(), Duration::from_secs(1))
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?;
+        assert_eq!(same, removed);
+
+        // bookmarking a song that doesn't exist does nothing
+        assert_eq!(
+            Song::add_bookmark(&db, Song::generate_id(), Duration::from_secs(1)).await?,
+            None
+        );
+        assert_eq!(
+            Song::remove_bookmark(&db, Song::generate_id(), Duration::from_secs(1)).await?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_duplicates() -> Result<()> {
+        let db = init_test_database().await?;
+        let keep = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                play_count: Some(3),
+                skip_count: Some(1),
+                rating: Some(Some(2)),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let duplicate = create_song_with_overrides(
+            &db,
+            arb_song_case()(),
+            SongChangeSet {
+                play_count: Some(2),
+                skip_count: Some(4),
+                last_played: Some(Utc::now()),
+                rating: Some(Some(4)),
+                favorite: Some(true),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let playlist = Playlist::create(
+            &db,
+            Playlist {
+                id: Playlist::generate_id(),
+                name: "Test Playlist".into(),
+                song_count: 0,
+                runtime: Duration::from_secs(0),
+            },
+        )
+        .await?
+        .unwrap();
+        Playlist::add_songs(&db, playlist.id.clone(), vec![duplicate.id.clone()]).await?;
+
+        let merged = Song::merge_duplicates(
+            &db,
+            keep.id.clone(),
+            vec![keep.id.clone(), duplicate.id.clone(), Song::generate_id()],
+        )
+        .await?
+        .ok_or_else(|| anyhow!("Song not found"))?;
+
+        assert_eq!(merged.play_count, 5);
+        assert_eq!(merged.skip_count, 5);
+        assert_eq!(merged.last_played, duplicate.last_played);
+        assert_eq!(merged.rating, Some(4));
+        assert!(merged.favorite);
+        assert_eq!(Song::read(&db, duplicate.id).await?, None);
+        assert_eq!(Song::read(&db, keep.id.clone()).await?, Some(merged));
+        // the kept song takes the duplicate's place in the playlist
+        let playlists = Song::read_playlists(&db, keep.id).await?;
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].id, playlist.id);
+
+        // merging into a song that doesn't exist does nothing
+        assert_eq!(
+            Song::merge_duplicates(&db, Song::generate_id(), vec![]).await?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "analysis")]
+    async fn test_compute_replaygain() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        assert_eq!(song.replaygain_track_gain, None);
+
+        // a full scale square wave, which has an integrated loudness of -0.691 LUFS
+        let samples = ResampledAudio {
+            path: song.path.clone(),
+            samples: [1., -1.].repeat(LoudnessDesc::WINDOW_SIZE),
+            sample_rate: mecomp_analysis::SAMPLE_RATE,
+            duration: None,
+        };
+
+        let loudness_desc = Song::compute_replaygain(&db, song.id.clone(), &samples).await?;
+        // the loudness is returned for computing the album gain
+        assert!(loudness_desc
+            .get_replaygain()
+            .is_some_and(|gain| (gain + 17.309).abs() < 0.001));
+
+        let gain = Song::read(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Song not found"))?
+            .replaygain_track_gain
+            .ok_or_else(|| anyhow!("ReplayGain not computed"))?;
+        assert!((gain + 17.309).abs() < 0.001, "{gain} !~= -17.309");
+
+        // computing the replaygain of a song that doesn't exist is an error
+        let result = Song::compute_replaygain(&db, Song::generate_id(), &samples).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "analysis")]
+    async fn test_compute_fingerprint() -> Result<()> {
+        let db = init_test_database().await?;
+        let song =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        let other =
+            create_song_with_overrides(&db, arb_song_case()(), SongChangeSet::default()).await?;
+        assert_eq!(
+            Fingerprint::read_for_song(&db, song.id.clone()).await?,
+            None
+        );
+
+        // a few seconds of a 440Hz sine wave
+        #[allow(clippy::cast_precision_loss)]
+        let samples = ResampledAudio {
+            path: song.path.clone(),
+            samples: (0..mecomp_analysis::SAMPLE_RATE * 5)
+                .map(|i| {
+                    (i as f32 * 440. * std::f32::consts::TAU / mecomp_analysis::SAMPLE_RATE as f32)
+                        .sin()
+                })
+                .collect(),
+            sample_rate: mecomp_analysis::SAMPLE_RATE,
+            duration: None,
+        };
+
+        Song::compute_fingerprint(&db, song.id.clone(), &samples).await?;
+        Song::compute_fingerprint(&db, other.id.clone(), &samples).await?;
+
+        let fingerprint = Fingerprint::read_for_song(&db, song.id.clone())
+            .await?
+            .ok_or_else(|| anyhow!("Fingerprint not computed"))?
+            .fingerprint;
+        assert!(!fingerprint.is_empty());
+
+        // the same audio has the same fingerprint
+        let other_fingerprint = Fingerprint::read_for_song(&db, other.id.clone())
+            .await?
+            .map(|fingerprint| fingerprint.fingerprint);
+        assert_eq!(other_fingerprint, Some(fingerprint));
+
+        // deleting the song deletes its fingerprint
+        Song::delete(&db, song.id.clone()).await?;
+        assert_eq!(Fingerprint::read_for_song(&db, song.id).await?, None);
+
+        // computing the fingerprint of a song that doesn't exist is an error
+        let result = Song::compute_fingerprint(&db, Song::generate_id(), &samples).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_try_load_into_db() {
         let db = init_test_database().await.unwrap();
@@ -1,10 +1,14 @@
 //! this module hold the implementations of functions needed for the health check of the database
 
+use std::sync::Arc;
+
 use surrealdb::{Connection, Surreal};
 use tracing::instrument;
 
 use surrealqlx::traits::Table;
 
+#[cfg(feature = "analysis")]
+use crate::db::queries::generic::library_statistics;
 use crate::db::queries::generic::{count, count_orphaned, count_orphaned_both};
 use crate::db::schemas::{
     album::Album, artist::Artist, collection::Collection, playlist::Playlist, song::Song,
@@ -56,6 +60,54 @@ pub async fn count_unanalyzed_songs<C: Connection>(db: &Surreal<C>) -> Result<us
     Ok(result)
 }
 
+/// The number of songs with a genre, see [`LibraryStatistics::genres`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+pub struct GenreCount {
+    pub genre: Arc<str>,
+    pub songs: usize,
+}
+
+/// Aggregate statistics about the library, as computed by [`read_library_statistics`].
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct LibraryStatistics {
+    /// The number of songs in the library
+    pub songs: usize,
+    /// The number of albums in the library
+    pub albums: usize,
+    /// The number of artists in the library
+    pub artists: usize,
+    /// The total runtime of the songs in the library, in milliseconds
+    pub runtime_ms: u64,
+    /// The number of songs that have been analyzed
+    pub analyzed_songs: usize,
+    /// The mean of the (normalized) tempo feature of the analyses,
+    /// not finite (or missing) if nothing has been analyzed
+    #[serde(default)]
+    pub mean_tempo: Option<f64>,
+    /// The number of songs with each genre, from most to least common
+    pub genres: Vec<GenreCount>,
+}
+
+/// Compute aggregate statistics about the library, in a single query
+#[cfg(feature = "analysis")]
+#[instrument]
+pub async fn read_library_statistics<C: Connection>(
+    db: &Surreal<C>,
+) -> Result<LibraryStatistics, Error> {
+    let result: Option<LibraryStatistics> = db
+        .query(library_statistics(
+            mecomp_analysis::AnalysisIndex::Tempo as usize,
+        ))
+        .await?
+        .take(0)?;
+    let mut statistics = result.unwrap_or_default();
+    // most to least common, ties are broken alphabetically
+    statistics
+        .genres
+        .sort_by(|a, b| b.songs.cmp(&a.songs).then_with(|| a.genre.cmp(&b.genre)));
+    Ok(statistics)
+}
+
 /// Count the number of orphaned albums in the database
 /// This is the number of albums that have no songs
 #[instrument]
@@ -127,6 +179,7 @@ mod tests {
             song_count: 0,
             discs: 1,
             genre: OneOrMany::None,
+            description: None,
         }
     }
 
@@ -137,6 +190,7 @@ mod tests {
             runtime: Duration::from_secs(0),
             song_count: 0,
             album_count: 0,
+            biography: None,
         }
     }
 
@@ -319,4 +373,82 @@ mod tests {
         Song::delete(&db, song.id).await.unwrap();
         assert_eq!(count_songs(&db).await.unwrap(), 0);
     }
+
+    #[tokio::test]
+    #[cfg(feature = "analysis")]
+    async fn test_library_statistics() {
+        use crate::db::schemas::analysis::Analysis;
+        use mecomp_analysis::{AnalysisIndex, NUMBER_FEATURES};
+
+        let db = init_test_database().await.unwrap();
+
+        // an empty library
+        let statistics = read_library_statistics(&db).await.unwrap();
+        assert_eq!(statistics.songs, 0);
+        assert_eq!(statistics.runtime_ms, 0);
+        assert_eq!(statistics.analyzed_songs, 0);
+        assert!(statistics.mean_tempo.is_none_or(|tempo| !tempo.is_finite()));
+        assert_eq!(statistics.genres, vec![]);
+
+        let mut songs = Vec::new();
+        for (runtime, genre) in [
+            (60, OneOrMany::One("Rock".into())),
+            (120, OneOrMany::Many(vec!["Jazz".into(), "Rock".into()])),
+            (180, OneOrMany::One("Blues".into())),
+        ] {
+            let song = create_song_with_overrides(
+                &db,
+                arb_song_case()(),
+                SongChangeSet {
+                    runtime: Some(Duration::from_secs(runtime)),
+                    genre: Some(genre),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            songs.push(song);
+        }
+
+        // analyze two of the songs
+        for (song, tempo) in songs.iter().zip([0.25, 0.75]) {
+            let mut features = [0.; NUMBER_FEATURES];
+            features[AnalysisIndex::Tempo as usize] = tempo;
+            Analysis::create(
+                &db,
+                song.id.clone(),
+                Analysis {
+                    id: Analysis::generate_id(),
+                    features,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let statistics = read_library_statistics(&db).await.unwrap();
+        assert_eq!(statistics.songs, 3);
+        assert_eq!(statistics.albums, count_albums(&db).await.unwrap());
+        assert_eq!(statistics.artists, count_artists(&db).await.unwrap());
+        assert_eq!(statistics.runtime_ms, 360_000);
+        assert_eq!(statistics.analyzed_songs, 2);
+        assert!((statistics.mean_tempo.unwrap() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(
+            statistics.genres,
+            vec![
+                GenreCount {
+                    genre: "Rock".into(),
+                    songs: 2
+                },
+                GenreCount {
+                    genre: "Blues".into(),
+                    songs: 1
+                },
+                GenreCount {
+                    genre: "Jazz".into(),
+                    songs: 1
+                },
+            ]
+        );
+    }
 }
@@ -58,15 +58,71 @@ pub async fn init_database() -> surrealdb::Result<Surreal<Db>> {
         schemas::album::Album,
         schemas::artist::Artist,
         schemas::song::Song,
+        schemas::lyrics::Lyrics,
         schemas::collection::Collection,
-        schemas::playlist::Playlist
+        schemas::playlist::Playlist,
+        schemas::scan_history::ScanMetrics
     )?;
     #[cfg(feature = "analysis")]
-    surrealqlx::register_tables!(&db, schemas::analysis::Analysis)?;
+    surrealqlx::register_tables!(
+        &db,
+        schemas::analysis::Analysis,
+        schemas::failed_analysis::FailedAnalysis,
+        schemas::fingerprint::Fingerprint
+    )?;
+    #[cfg(feature = "analysis")]
+    migrate_analyses(&db).await?;
 
     Ok(db)
 }
 
+/// Migrate the analyses made by a version of `mecomp-analysis` that extracted a different number of features.
+///
+/// The vector index on the features has a fixed dimension, and registering the tables won't redefine an existing index.
+/// So, if the index was defined for a different number of features, the outdated analyses are deleted
+/// (so that those songs get analyzed again) and the index is redefined for the current number of features.
+///
+/// # Errors
+///
+/// This function will return an error if the queries fail.
+#[cfg(all(feature = "db", feature = "analysis"))]
+pub(crate) async fn migrate_analyses<C>(db: &Surreal<C>) -> surrealdb::Result<()>
+where
+    C: surrealdb::Connection,
+{
+    use queries::analysis::{
+        define_features_index, delete_outdated, read_table_info, FEATURES_INDEX_NAME,
+    };
+
+    #[derive(serde::Deserialize)]
+    struct TableInfo {
+        indexes: std::collections::HashMap<String, String>,
+    }
+
+    let info: Option<TableInfo> = db.query(read_table_info()).await?.take(0)?;
+    let dimension = info.and_then(|info| {
+        let definition = info.indexes.get(FEATURES_INDEX_NAME)?;
+        definition
+            .split_whitespace()
+            .skip_while(|token| *token != "DIMENSION")
+            .nth(1)
+            .and_then(|dimension| dimension.parse::<usize>().ok())
+    });
+
+    if dimension != Some(mecomp_analysis::NUMBER_FEATURES) {
+        log::info!(
+            "Migrating analyses from {dimension:?} to {} features",
+            mecomp_analysis::NUMBER_FEATURES
+        );
+        db.query(delete_outdated())
+            .query(define_features_index())
+            .await?
+            .check()?;
+    }
+
+    Ok(())
+}
+
 #[cfg(feature = "db")]
 pub(crate) async fn register_custom_analyzer<C>(db: &Surreal<C>) -> surrealdb::Result<()>
 where
@@ -115,4 +171,40 @@ mod test {
 
         Ok(())
     }
+
+    #[cfg(feature = "analysis")]
+    #[tokio::test]
+    async fn test_migrate_analyses() -> anyhow::Result<()> {
+        use super::schemas::analysis::{Analysis, TABLE_NAME};
+        use mecomp_analysis::NUMBER_FEATURES;
+
+        let db = Surreal::new::<Mem>(()).await?;
+        db.use_ns("test").use_db("test").await?;
+
+        // a database created when the analysis extracted 20 features
+        db.query("DEFINE INDEX analysis_features_vector_index ON analysis FIELDS features MTREE DIMENSION 20")
+            .query("CREATE analysis:old SET features = $features")
+            .bind(("features", vec![0.5; 20]))
+            .await?
+            .check()?;
+        <Analysis as Table>::init_table(&db).await?;
+
+        migrate_analyses(&db).await?;
+
+        // the outdated analysis is gone, and analyses with the current number of features can be created
+        let analyses: Vec<Analysis> = db.select(TABLE_NAME).await?;
+        assert!(analyses.is_empty());
+        db.query("CREATE analysis:new SET features = $features")
+            .bind(("features", vec![0.5; NUMBER_FEATURES]))
+            .await?
+            .check()?;
+
+        // migrating again doesn't touch the up-to-date analyses
+        migrate_analyses(&db).await?;
+        let analyses: Vec<Analysis> = db.select(TABLE_NAME).await?;
+        assert_eq!(analyses.len(), 1);
+        assert_eq!(analyses[0].features, [0.5; NUMBER_FEATURES]);
+
+        Ok(())
+    }
 }
@@ -1,7 +1,7 @@
 use crate::db::schemas;
 use surrealdb::opt::IntoQuery;
 
-use super::generic::{read_related_in, read_related_out, relate, unrelate};
+use super::generic::{read_related_in, relate, unrelate};
 
 /// Query to read an album by its name.
 ///
@@ -92,31 +92,24 @@ pub fn add_songs() -> impl IntoQuery {
     relate("album", "songs", "album_to_song")
 }
 
-/// Query to read the songs of an album
+/// Query to read the songs of an album, in track order.
+///
+/// Songs are sorted by disc number, then track number, then title.
+/// Songs without a disc or track number are sorted after those with one.
 ///
 /// Compiles to:
 ///
 /// ```sql, ignore
-/// SELECT * FROM $album->album_to_song.out
-/// ```
-///
-/// # Example
-///
-/// ```ignore
-/// # use pretty_assertions::assert_eq;
-/// use mecomp_storage::db::crud::queries::album::read_songs;
-/// use surrealdb::opt::IntoQuery;
-///
-/// let statement = read_songs();
-/// assert_eq!(
-///     statement.into_query().unwrap(),
-///     "SELECT * FROM $album->album_to_song.out".into_query().unwrap()
-/// );
+/// SELECT *, disc = NONE AS no_disc, track = NONE AS no_track FROM $album->album_to_song.out ORDER BY no_disc, disc, no_track, track, title
 /// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
 #[must_use]
-#[inline]
 pub fn read_songs() -> impl IntoQuery {
-    read_related_out("album", "album_to_song")
+    "SELECT *, disc = NONE AS no_disc, track = NONE AS no_track \
+    FROM $album->album_to_song.out \
+    ORDER BY no_disc, disc, no_track, track, title"
+        .into_query()
+        .unwrap()
 }
 
 /// Query to remove songs from an album
@@ -214,7 +207,7 @@ mod query_validation_tests {
         let statement = read_songs();
         assert_eq!(
             statement.into_query().unwrap(),
-            "SELECT * FROM $album->album_to_song.out"
+            "SELECT *, disc = NONE AS no_disc, track = NONE AS no_track FROM $album->album_to_song.out ORDER BY no_disc, disc, no_track, track, title"
                 .into_query()
                 .unwrap()
         );
@@ -1,8 +1,12 @@
 use crate::db::schemas;
+use mecomp_analysis::NUMBER_FEATURES;
 use surrealdb::opt::IntoQuery;
 
 use super::generic::{read_related_in, read_related_out, relate};
 
+/// The name of the vector index on the features of the analyses, as defined by the [`schemas::analysis::Analysis`] table
+pub const FEATURES_INDEX_NAME: &str = "analysis_features_vector_index";
+
 /// Query to relate an analysis to a song
 ///
 /// Compiles to:
@@ -171,6 +175,127 @@ pub fn nearest_neighbors_to_many(n: u32) -> impl IntoQuery {
     .unwrap()
 }
 
+/// Query to read the analyses whose `feature`-th feature is within a range (inclusive), ordered by that feature
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM analysis WHERE features[feature] >= $min AND features[feature] <= $max ORDER BY features[feature] LIMIT $limit
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::analysis::read_in_feature_range;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_in_feature_range(0);
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT * FROM analysis WHERE features[0] >= $min AND features[0] <= $max ORDER BY features[0] LIMIT $limit".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_in_feature_range(feature: usize) -> impl IntoQuery {
+    format!(
+        "SELECT * FROM {} WHERE features[{feature}] >= $min AND features[{feature}] <= $max ORDER BY features[{feature}] LIMIT $limit",
+        schemas::analysis::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to read the definition of the analysis table, including its indexes
+///
+/// Compiles to:
+/// ```sql, ignore
+/// INFO FOR TABLE analysis
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::analysis::read_table_info;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_table_info();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "INFO FOR TABLE analysis".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_table_info() -> impl IntoQuery {
+    format!("INFO FOR TABLE {}", schemas::analysis::TABLE_NAME)
+        .into_query()
+        .unwrap()
+}
+
+/// Query to delete the analyses that don't have [`NUMBER_FEATURES`] features
+///
+/// Compiles to:
+/// ```sql, ignore
+/// DELETE analysis WHERE array::len(features) != NUMBER_FEATURES
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::analysis::delete_outdated;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = delete_outdated();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "DELETE analysis WHERE array::len(features) != 68".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn delete_outdated() -> impl IntoQuery {
+    format!(
+        "DELETE {} WHERE array::len(features) != {NUMBER_FEATURES}",
+        schemas::analysis::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to (re)define the vector index on the features of the analyses, with [`NUMBER_FEATURES`] dimensions
+///
+/// Compiles to:
+/// ```sql, ignore
+/// DEFINE INDEX OVERWRITE analysis_features_vector_index ON analysis FIELDS features MTREE DIMENSION NUMBER_FEATURES
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::analysis::define_features_index;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = define_features_index();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "DEFINE INDEX OVERWRITE analysis_features_vector_index ON analysis FIELDS features MTREE DIMENSION 68".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn define_features_index() -> impl IntoQuery {
+    format!(
+        "DEFINE INDEX OVERWRITE {FEATURES_INDEX_NAME} ON {} FIELDS features MTREE DIMENSION {NUMBER_FEATURES}",
+        schemas::analysis::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
 #[cfg(test)]
 mod query_validation_tests {
     use pretty_assertions::assert_eq;
@@ -241,4 +366,46 @@ mod query_validation_tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_read_in_feature_range() {
+        let statement = read_in_feature_range(0);
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM analysis WHERE features[0] >= $min AND features[0] <= $max ORDER BY features[0] LIMIT $limit"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_table_info() {
+        let statement = read_table_info();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "INFO FOR TABLE analysis".into_query().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_delete_outdated() {
+        let statement = delete_outdated();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "DELETE analysis WHERE array::len(features) != 68"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_define_features_index() {
+        let statement = define_features_index();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "DEFINE INDEX OVERWRITE analysis_features_vector_index ON analysis FIELDS features MTREE DIMENSION 68"
+                .into_query()
+                .unwrap()
+        );
+    }
 }
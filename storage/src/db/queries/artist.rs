@@ -248,6 +248,64 @@ pub fn read_songs() -> impl IntoQuery {
     "RETURN array::union((SELECT * FROM $artist->artist_to_song.out), (SELECT * FROM $artist->artist_to_album->album->album_to_song.out))".into_query().unwrap()
 }
 
+/// Query to read the songs directly related to an artist (not through an album)
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM $id->artist_to_song.out
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::artist::read_direct_songs;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_direct_songs();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT * FROM $id->artist_to_song.out".into_query().unwrap()
+/// );
+/// ```
+#[must_use]
+#[inline]
+pub fn read_direct_songs() -> impl IntoQuery {
+    read_related_out("id", "artist_to_song")
+}
+
+/// Query to merge the `$source` artist into the `$target` artist, in a single transaction.
+///
+/// `$songs` and `$albums` are the songs and albums whose artist fields need to be updated
+/// (objects with the record `id` and the new values), `$new_songs` and `$new_albums` are the
+/// songs and albums that need to be related to the target artist.
+///
+/// Deleting the source artist also deletes its relations.
+///
+/// Compiles to:
+/// ```sql, ignore
+/// BEGIN TRANSACTION;
+/// FOR $song IN $songs { UPDATE $song.id MERGE { artist: $song.artist, album_artist: $song.album_artist } };
+/// FOR $album IN $albums { UPDATE $album.id MERGE { artist: $album.artist } };
+/// FOR $song IN $new_songs { RELATE $target->artist_to_song->$song };
+/// FOR $album IN $new_albums { RELATE $target->artist_to_album->$album };
+/// DELETE $source;
+/// COMMIT TRANSACTION;
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn merge() -> impl IntoQuery {
+    "BEGIN TRANSACTION;
+    FOR $song IN $songs { UPDATE $song.id MERGE { artist: $song.artist, album_artist: $song.album_artist } };
+    FOR $album IN $albums { UPDATE $album.id MERGE { artist: $album.artist } };
+    FOR $song IN $new_songs { RELATE $target->artist_to_song->$song };
+    FOR $album IN $new_albums { RELATE $target->artist_to_album->$album };
+    DELETE $source;
+    COMMIT TRANSACTION;"
+        .into_query()
+        .unwrap()
+}
+
 #[cfg(test)]
 mod query_validation_tests {
     use pretty_assertions::assert_eq;
@@ -345,4 +403,32 @@ mod query_validation_tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_read_direct_songs() {
+        let statement = read_direct_songs();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM $id->artist_to_song.out"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let statement = merge();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "BEGIN TRANSACTION;
+            FOR $song IN $songs { UPDATE $song.id MERGE { artist: $song.artist, album_artist: $song.album_artist } };
+            FOR $album IN $albums { UPDATE $album.id MERGE { artist: $album.artist } };
+            FOR $song IN $new_songs { RELATE $target->artist_to_song->$song };
+            FOR $album IN $new_albums { RELATE $target->artist_to_album->$album };
+            DELETE $source;
+            COMMIT TRANSACTION;"
+                .into_query()
+                .unwrap()
+        );
+    }
 }
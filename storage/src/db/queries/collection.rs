@@ -80,6 +80,33 @@ pub fn remove_songs() -> impl IntoQuery {
     unrelate("id", "songs", "collection_to_song")
 }
 
+/// Query to merge the `$source` collection into the `$target` collection, in a single transaction.
+///
+/// `$songs` are the songs of the source collection that aren't already in the target collection,
+/// and `$changes` is the new `song_count` and `runtime` of the target collection.
+///
+/// Deleting the source collection also deletes its relations.
+///
+/// Compiles to:
+/// ```sql, ignore
+/// BEGIN TRANSACTION;
+/// FOR $song IN $songs { RELATE $target->collection_to_song->$song };
+/// UPDATE $target MERGE $changes;
+/// DELETE $source;
+/// COMMIT TRANSACTION;
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn merge() -> impl IntoQuery {
+    "BEGIN TRANSACTION;
+    FOR $song IN $songs { RELATE $target->collection_to_song->$song };
+    UPDATE $target MERGE $changes;
+    DELETE $source;
+    COMMIT TRANSACTION;"
+        .into_query()
+        .unwrap()
+}
+
 #[cfg(test)]
 mod query_validation_tests {
     use pretty_assertions::assert_eq;
@@ -120,4 +147,19 @@ mod query_validation_tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_merge() {
+        let statement = merge();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "BEGIN TRANSACTION;
+            FOR $song IN $songs { RELATE $target->collection_to_song->$song };
+            UPDATE $target MERGE $changes;
+            DELETE $source;
+            COMMIT TRANSACTION;"
+                .into_query()
+                .unwrap()
+        );
+    }
 }
@@ -0,0 +1,67 @@
+use surrealdb::opt::IntoQuery;
+
+use crate::db::schemas;
+
+/// Query to read the failed analysis record for a song
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM failed_analysis WHERE song_id = $song LIMIT 1
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_for_song() -> impl IntoQuery {
+    format!(
+        "SELECT * FROM {} WHERE song_id = $song LIMIT 1",
+        schemas::failed_analysis::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to delete the failed analysis records for some songs
+///
+/// Compiles to:
+/// ```sql, ignore
+/// DELETE failed_analysis WHERE song_id IN $songs
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn delete_for_songs() -> impl IntoQuery {
+    format!(
+        "DELETE {} WHERE song_id IN $songs",
+        schemas::failed_analysis::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+#[cfg(test)]
+mod query_validation_tests {
+    use pretty_assertions::assert_eq;
+    use surrealdb::opt::IntoQuery;
+
+    use super::*;
+
+    #[test]
+    fn test_read_for_song() {
+        let statement = read_for_song();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM failed_analysis WHERE song_id = $song LIMIT 1"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_delete_for_songs() {
+        let statement = delete_for_songs();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "DELETE failed_analysis WHERE song_id IN $songs"
+                .into_query()
+                .unwrap()
+        );
+    }
+}
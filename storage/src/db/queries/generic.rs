@@ -297,6 +297,49 @@ pub fn full_text_search<Table: AsRef<str>, Field: AsRef<str>>(
     full_text_search_statement(table.as_ref(), field.as_ref(), limit)
 }
 
+/// Query to compute aggregate statistics about the whole library in a single round trip to the database.
+///
+/// `tempo_feature` is the index of the tempo in the analysis features,
+/// the mean tempo is of the (normalized) feature so it has to be denormalized to get a BPM.
+///
+/// The genres are counted by splitting songs with many genres into one row per genre,
+/// they aren't ordered (`SurrealDB` doesn't order grouped rows by their aggregates),
+/// see [`crate::db::health::read_library_statistics`].
+///
+/// Compiles to:
+/// ```sql, ignore
+/// RETURN {
+///     songs: array::len((SELECT id FROM song)),
+///     albums: array::len((SELECT id FROM album)),
+///     artists: array::len((SELECT id FROM artist)),
+///     runtime_ms: math::sum((SELECT VALUE duration::millis(runtime) FROM song)),
+///     analyzed_songs: array::len((SELECT id FROM song WHERE count(<-analysis_to_song.in) > 0)),
+///     mean_tempo: math::mean((SELECT VALUE features[tempo_feature] FROM analysis)),
+///     genres: (
+///         SELECT genre, count() AS songs
+///         FROM (SELECT array::flatten([genre]) AS genre FROM song WHERE genre != NONE SPLIT genre)
+///         GROUP BY genre
+///     )
+/// }
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn library_statistics(tempo_feature: usize) -> impl IntoQuery {
+    format!(
+        "RETURN {{ \
+            songs: array::len((SELECT id FROM song)), \
+            albums: array::len((SELECT id FROM album)), \
+            artists: array::len((SELECT id FROM artist)), \
+            runtime_ms: math::sum((SELECT VALUE duration::millis(runtime) FROM song)), \
+            analyzed_songs: array::len((SELECT id FROM song WHERE count(<-analysis_to_song.in) > 0)), \
+            mean_tempo: math::mean((SELECT VALUE features[{tempo_feature}] FROM analysis)), \
+            genres: (SELECT genre, count() AS songs FROM (SELECT array::flatten([genre]) AS genre FROM song WHERE genre != NONE SPLIT genre) GROUP BY genre) \
+        }}"
+    )
+    .into_query()
+    .unwrap()
+}
+
 #[cfg(test)]
 mod query_validation_tests {
     use pretty_assertions::assert_eq;
@@ -393,4 +436,15 @@ mod query_validation_tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_library_statistics() {
+        let statement = library_statistics(0);
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "RETURN { songs: array::len((SELECT id FROM song)), albums: array::len((SELECT id FROM album)), artists: array::len((SELECT id FROM artist)), runtime_ms: math::sum((SELECT VALUE duration::millis(runtime) FROM song)), analyzed_songs: array::len((SELECT id FROM song WHERE count(<-analysis_to_song.in) > 0)), mean_tempo: math::mean((SELECT VALUE features[0] FROM analysis)), genres: (SELECT genre, count() AS songs FROM (SELECT array::flatten([genre]) AS genre FROM song WHERE genre != NONE SPLIT genre) GROUP BY genre) }"
+                .into_query()
+                .unwrap()
+        );
+    }
 }
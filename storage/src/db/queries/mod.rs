@@ -8,8 +8,14 @@ pub mod album;
 pub mod analysis;
 pub mod artist;
 pub mod collection;
+#[cfg(feature = "analysis")]
+pub mod failed_analysis;
+#[cfg(feature = "analysis")]
+pub mod fingerprint;
 pub mod generic;
+pub mod lyrics;
 pub mod playlist;
+pub mod scan_history;
 pub mod song;
 
 // NOTE: blocked on https://github.com/surrealdb/surrealdb/pull/4156,
@@ -2,13 +2,23 @@ use surrealdb::opt::IntoQuery;
 
 use crate::db::schemas;
 
-use super::generic::{read_related_out, relate, unrelate};
+use super::generic::unrelate;
 
-/// Query to relate a playlist to its songs.
+/// Query to relate a playlist to its songs, appending them to the end of the playlist.
+///
+/// Each `playlist_to_song` relation carries an `order` field, the position of the song in the playlist.
+/// The new songs are numbered in the order they're given, starting after the largest `order` already in the playlist.
+/// Relations created before `order` existed have no `order`, so they're ignored here and sort before the rest.
 ///
 /// Compiles to:
 /// ```sql, ignore
-/// RELATE $id->playlist_to_song->$songs
+/// BEGIN TRANSACTION;
+/// LET $start = (array::max($id->playlist_to_song.order) ?? -1) + 1;
+/// FOR $i IN array::range(0, array::len($songs)) {
+///     LET $song = $songs[$i];
+///     RELATE $id->playlist_to_song->$song SET order = $start + $i;
+/// };
+/// COMMIT TRANSACTION;
 /// ```
 ///
 /// # Example
@@ -21,20 +31,34 @@ use super::generic::{read_related_out, relate, unrelate};
 /// let statement = add_songs();
 /// assert_eq!(
 ///     statement.into_query().unwrap(),
-///     "RELATE $id->playlist_to_song->$songs".into_query().unwrap()
+///     "BEGIN TRANSACTION;
+///     LET $start = (array::max($id->playlist_to_song.order) ?? -1) + 1;
+///     FOR $i IN array::range(0, array::len($songs)) {
+///         LET $song = $songs[$i];
+///         RELATE $id->playlist_to_song->$song SET order = $start + $i;
+///     };
+///     COMMIT TRANSACTION;".into_query().unwrap()
 /// );
 /// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
 #[must_use]
-#[inline]
 pub fn add_songs() -> impl IntoQuery {
-    relate("id", "songs", "playlist_to_song")
+    "BEGIN TRANSACTION;
+    LET $start = (array::max($id->playlist_to_song.order) ?? -1) + 1;
+    FOR $i IN array::range(0, array::len($songs)) {
+        LET $song = $songs[$i];
+        RELATE $id->playlist_to_song->$song SET order = $start + $i;
+    };
+    COMMIT TRANSACTION;"
+        .into_query()
+        .unwrap()
 }
 
-/// Query to read the songs of a playlist
+/// Query to read the songs of a playlist, in the order they appear in the playlist.
 ///
 /// Compiles to:
 /// ```sql, ignore
-/// SELECT * FROM $id->playlist_to_song.out
+/// SELECT * FROM (SELECT out, order FROM $id->playlist_to_song ORDER BY order ASC).out
 /// ```
 ///
 /// # Example
@@ -47,13 +71,55 @@ pub fn add_songs() -> impl IntoQuery {
 /// let statement = read_songs();
 /// assert_eq!(
 ///     statement.into_query().unwrap(),
-///     "SELECT * FROM $id->playlist_to_song.out".into_query().unwrap()
+///     "SELECT * FROM (SELECT out, order FROM $id->playlist_to_song ORDER BY order ASC).out".into_query().unwrap()
 /// );
 /// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
 #[must_use]
-#[inline]
 pub fn read_songs() -> impl IntoQuery {
-    read_related_out("id", "playlist_to_song")
+    "SELECT * FROM (SELECT out, order FROM $id->playlist_to_song ORDER BY order ASC).out"
+        .into_query()
+        .unwrap()
+}
+
+/// Query to renumber the songs of a playlist so that they're in the same order as `$songs`.
+///
+/// Compiles to:
+/// ```sql, ignore
+/// BEGIN TRANSACTION;
+/// FOR $edge IN (SELECT id, out FROM $id->playlist_to_song) {
+///     UPDATE $edge.id SET order = array::find_index($songs, $edge.out);
+/// };
+/// COMMIT TRANSACTION;
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::playlist::reorder_songs;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = reorder_songs();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "BEGIN TRANSACTION;
+///     FOR $edge IN (SELECT id, out FROM $id->playlist_to_song) {
+///         UPDATE $edge.id SET order = array::find_index($songs, $edge.out);
+///     };
+///     COMMIT TRANSACTION;".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn reorder_songs() -> impl IntoQuery {
+    "BEGIN TRANSACTION;
+    FOR $edge IN (SELECT id, out FROM $id->playlist_to_song) {
+        UPDATE $edge.id SET order = array::find_index($songs, $edge.out);
+    };
+    COMMIT TRANSACTION;"
+        .into_query()
+        .unwrap()
 }
 
 /// Query to remove songs from a playlist
@@ -128,7 +194,15 @@ mod query_validation_tests {
         let statement = add_songs();
         assert_eq!(
             statement.into_query().unwrap(),
-            "RELATE $id->playlist_to_song->$songs".into_query().unwrap()
+            "BEGIN TRANSACTION;
+            LET $start = (array::max($id->playlist_to_song.order) ?? -1) + 1;
+            FOR $i IN array::range(0, array::len($songs)) {
+                LET $song = $songs[$i];
+                RELATE $id->playlist_to_song->$song SET order = $start + $i;
+            };
+            COMMIT TRANSACTION;"
+                .into_query()
+                .unwrap()
         );
     }
 
@@ -137,7 +211,22 @@ mod query_validation_tests {
         let statement = read_songs();
         assert_eq!(
             statement.into_query().unwrap(),
-            "SELECT * FROM $id->playlist_to_song.out"
+            "SELECT * FROM (SELECT out, order FROM $id->playlist_to_song ORDER BY order ASC).out"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reorder_songs() {
+        let statement = reorder_songs();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "BEGIN TRANSACTION;
+            FOR $edge IN (SELECT id, out FROM $id->playlist_to_song) {
+                UPDATE $edge.id SET order = array::find_index($songs, $edge.out);
+            };
+            COMMIT TRANSACTION;"
                 .into_query()
                 .unwrap()
         );
@@ -0,0 +1,67 @@
+use surrealdb::opt::IntoQuery;
+
+use crate::db::schemas;
+
+/// Query to read all the scan metrics, most recent first
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM scan_history ORDER BY scanned_at DESC
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_all_recent_first() -> impl IntoQuery {
+    format!(
+        "SELECT * FROM {} ORDER BY scanned_at DESC",
+        schemas::scan_history::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to read the scan metrics of a library path, most recent first
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM scan_history WHERE path = $path ORDER BY scanned_at DESC
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_for_path() -> impl IntoQuery {
+    format!(
+        "SELECT * FROM {} WHERE path = $path ORDER BY scanned_at DESC",
+        schemas::scan_history::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+#[cfg(test)]
+mod query_validation_tests {
+    use pretty_assertions::assert_eq;
+    use surrealdb::opt::IntoQuery;
+
+    use super::*;
+
+    #[test]
+    fn test_read_all_recent_first() {
+        let statement = read_all_recent_first();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM scan_history ORDER BY scanned_at DESC"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_for_path() {
+        let statement = read_for_path();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM scan_history WHERE path = $path ORDER BY scanned_at DESC"
+                .into_query()
+                .unwrap()
+        );
+    }
+}
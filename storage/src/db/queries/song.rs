@@ -4,6 +4,32 @@ use crate::db::schemas;
 
 use super::generic::read_related_in;
 
+/// Query to read many songs
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM $ids
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::read_many;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_many();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT * FROM $ids".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_many() -> impl IntoQuery {
+    "SELECT * FROM $ids".into_query().unwrap()
+}
+
 /// Query to read a song by its path
 ///
 /// Compiles to:
@@ -181,6 +207,273 @@ pub fn read_collections() -> impl IntoQuery {
     read_related_in("id", "collection_to_song")
 }
 
+/// Query to read the genres of every song
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT VALUE genre FROM song
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::read_genres;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_genres();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT VALUE genre FROM song".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_genres() -> impl IntoQuery {
+    format!("SELECT VALUE genre FROM {}", schemas::song::TABLE_NAME)
+        .into_query()
+        .unwrap()
+}
+
+/// Query to read the songs with a given genre, ordered by title
+///
+/// The genre of a song can be a single string or a set of strings,
+/// wrapping it in an array and flattening it lets us check for membership in both cases
+/// (`CONTAINS` on a string would be a substring match).
+/// The filtering is done in a subquery because the query planner fails to find an iterator
+/// when a function call in the `WHERE` clause is combined with ordering by an indexed field.
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM (SELECT * FROM song WHERE array::flatten([genre]) CONTAINS $genre) ORDER BY title LIMIT $limit
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::read_songs_by_genre;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_songs_by_genre();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT * FROM (SELECT * FROM song WHERE array::flatten([genre]) CONTAINS $genre) ORDER BY title LIMIT $limit".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_songs_by_genre() -> impl IntoQuery {
+    format!(
+        "SELECT * FROM (SELECT * FROM {} WHERE array::flatten([genre]) CONTAINS $genre) ORDER BY title LIMIT $limit",
+        schemas::song::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to read the songs released within a range of years (inclusive), ordered by release year then title
+///
+/// Songs without a release year are excluded.
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM song WHERE release_year != NONE AND release_year >= $start AND release_year <= $end ORDER BY release_year, title LIMIT $limit
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::read_songs_by_year_range;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_songs_by_year_range();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT * FROM song WHERE release_year != NONE AND release_year >= $start AND release_year <= $end ORDER BY release_year, title LIMIT $limit".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_songs_by_year_range() -> impl IntoQuery {
+    format!(
+        "SELECT * FROM {} WHERE release_year != NONE AND release_year >= $start AND release_year <= $end ORDER BY release_year, title LIMIT $limit",
+        schemas::song::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to read a random sample of songs
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT * FROM song ORDER BY RAND() LIMIT $limit
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::read_random_songs;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_random_songs();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT * FROM song ORDER BY RAND() LIMIT $limit".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_random_songs() -> impl IntoQuery {
+    format!(
+        "SELECT * FROM {} ORDER BY RAND() LIMIT $limit",
+        schemas::song::TABLE_NAME
+    )
+    .into_query()
+    .unwrap()
+}
+
+/// Query to read the ids of all the songs
+///
+/// Compiles to:
+/// ```sql, ignore
+/// SELECT VALUE id FROM song
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::read_ids;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = read_ids();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "SELECT VALUE id FROM song".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn read_ids() -> impl IntoQuery {
+    format!("SELECT VALUE id FROM {}", schemas::song::TABLE_NAME)
+        .into_query()
+        .unwrap()
+}
+
+/// Query to merge the same changes into many songs at once.
+///
+/// Songs that don't exist are skipped, so the songs that are returned are the ones that were updated.
+///
+/// Compiles to:
+/// ```sql, ignore
+/// UPDATE $ids MERGE $changes
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::batch_update;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = batch_update();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "UPDATE $ids MERGE $changes".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn batch_update() -> impl IntoQuery {
+    "UPDATE $ids MERGE $changes".into_query().unwrap()
+}
+
+/// Query to record that a song was played to completion, in a single statement so concurrent plays aren't lost
+///
+/// Compiles to:
+/// ```sql, ignore
+/// UPDATE $id SET play_count = (play_count OR 0) + 1, last_played = time::now()
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::increment_play_count;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = increment_play_count();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "UPDATE $id SET play_count = (play_count OR 0) + 1, last_played = time::now()".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn increment_play_count() -> impl IntoQuery {
+    "UPDATE $id SET play_count = (play_count OR 0) + 1, last_played = time::now()"
+        .into_query()
+        .unwrap()
+}
+
+/// Query to record that a song was skipped, in a single statement so concurrent skips aren't lost
+///
+/// Compiles to:
+/// ```sql, ignore
+/// UPDATE $id SET skip_count = (skip_count OR 0) + 1
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::increment_skip_count;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = increment_skip_count();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "UPDATE $id SET skip_count = (skip_count OR 0) + 1".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn increment_skip_count() -> impl IntoQuery {
+    "UPDATE $id SET skip_count = (skip_count OR 0) + 1"
+        .into_query()
+        .unwrap()
+}
+
+/// Query to add a song to the favorites, or remove it if it's already one of them, in a single statement
+///
+/// Compiles to:
+/// ```sql, ignore
+/// UPDATE $id SET favorite = !favorite
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// # use pretty_assertions::assert_eq;
+/// use mecomp_storage::db::crud::queries::song::toggle_favorite;
+/// use surrealdb::opt::IntoQuery;
+///
+/// let statement = toggle_favorite();
+/// assert_eq!(
+///     statement.into_query().unwrap(),
+///     "UPDATE $id SET favorite = !favorite".into_query().unwrap()
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)] // can only panic if the query is invalid, which should never happen
+#[must_use]
+pub fn toggle_favorite() -> impl IntoQuery {
+    "UPDATE $id SET favorite = !favorite".into_query().unwrap()
+}
+
 #[cfg(test)]
 mod query_validation_tests {
     use pretty_assertions::assert_eq;
@@ -188,6 +481,15 @@ mod query_validation_tests {
 
     use super::*;
 
+    #[test]
+    fn test_read_many() {
+        let statement = read_many();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM $ids".into_query().unwrap()
+        );
+    }
+
     #[test]
     fn test_read_song_by_path() {
         let statement = read_song_by_path();
@@ -249,4 +551,95 @@ mod query_validation_tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_read_genres() {
+        let statement = read_genres();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT VALUE genre FROM song".into_query().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_songs_by_genre() {
+        let statement = read_songs_by_genre();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM (SELECT * FROM song WHERE array::flatten([genre]) CONTAINS $genre) ORDER BY title LIMIT $limit"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_songs_by_year_range() {
+        let statement = read_songs_by_year_range();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM song WHERE release_year != NONE AND release_year >= $start AND release_year <= $end ORDER BY release_year, title LIMIT $limit"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_random_songs() {
+        let statement = read_random_songs();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT * FROM song ORDER BY RAND() LIMIT $limit"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_ids() {
+        let statement = read_ids();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "SELECT VALUE id FROM song".into_query().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_batch_update() {
+        let statement = batch_update();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "UPDATE $ids MERGE $changes".into_query().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_increment_play_count() {
+        let statement = increment_play_count();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "UPDATE $id SET play_count = (play_count OR 0) + 1, last_played = time::now()"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_increment_skip_count() {
+        let statement = increment_skip_count();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "UPDATE $id SET skip_count = (skip_count OR 0) + 1"
+                .into_query()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_toggle_favorite() {
+        let statement = toggle_favorite();
+        assert_eq!(
+            statement.into_query().unwrap(),
+            "UPDATE $id SET favorite = !favorite".into_query().unwrap()
+        );
+    }
 }
@@ -58,6 +58,13 @@ pub struct Album {
     #[cfg_attr(feature = "db", field(dt = "option<set<string> | string>"))]
     #[cfg_attr(feature = "serde", serde(default))]
     pub genre: OneOrMany<Arc<str>>,
+    /// A description of the [`Album`], e.g. from a metadata source like MusicBrainz or Discogs.
+    #[cfg_attr(
+        feature = "db",
+        field(dt = "option<string>", index(text("custom_analyzer")))
+    )]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: Option<Arc<str>>,
 }
 
 impl Album {
@@ -88,6 +95,8 @@ pub struct AlbumChangeSet {
     pub discs: Option<u32>,
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub genre: Option<OneOrMany<Arc<str>>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub description: Option<Option<Arc<str>>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -151,6 +160,7 @@ mod tests {
             song_count: 0,
             discs: 1,
             genre: OneOrMany::One(Arc::from("test")),
+            description: None,
         }
     }
 
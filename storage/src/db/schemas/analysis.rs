@@ -4,6 +4,8 @@ use super::{Id, Thing};
 #[cfg(feature = "db")]
 use surrealdb::sql::{Id, Thing};
 
+use mecomp_analysis::NUMBER_FEATURES;
+
 pub type AnalysisId = Thing;
 
 pub const TABLE_NAME: &str = "analysis";
@@ -22,10 +24,14 @@ pub struct Analysis {
     pub id: AnalysisId,
 
     /// The [`Song`]'s audio features.
-    #[cfg_attr(feature = "db", field(dt = "array<float>", index(vector(dim = 20))))]
-    pub features: [f64; 20],
+    #[cfg_attr(feature = "db", field(dt = "array<float>", index(vector(dim = 68))))]
+    #[cfg_attr(feature = "serde", serde(with = "mecomp_analysis::serde_features"))]
+    pub features: [f64; NUMBER_FEATURES],
 }
 
+// the dimension of the vector index has to be a literal
+const _: () = assert!(NUMBER_FEATURES == 68);
+
 impl Analysis {
     #[must_use]
     pub fn generate_id() -> AnalysisId {
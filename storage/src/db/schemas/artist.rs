@@ -47,6 +47,14 @@ pub struct Artist {
     /// the number of songs this artist has.
     #[cfg_attr(feature = "db", field(dt = "int"))]
     pub song_count: usize,
+
+    /// A biography of the [`Artist`], e.g. from a metadata source like MusicBrainz or Discogs.
+    #[cfg_attr(
+        feature = "db",
+        field(dt = "option<string>", index(text("custom_analyzer")))
+    )]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub biography: Option<Arc<str>>,
 }
 
 impl Artist {
@@ -71,6 +79,8 @@ pub struct ArtistChangeSet {
     pub album_count: Option<usize>,
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub song_count: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub biography: Option<Option<Arc<str>>>,
 }
 
 /// This struct holds all the metadata about a particular [`Artist`].
@@ -124,6 +134,7 @@ mod tests {
             runtime: Duration::from_secs(3600),
             album_count: 10,
             song_count: 100,
+            biography: None,
         }
     }
 
@@ -84,6 +84,17 @@ impl From<Collection> for CollectionBrief {
     }
 }
 
+/// The songs in a [`Collection`], and the centroid of their analyses
+#[cfg(feature = "analysis")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollectionCentroid {
+    pub id: CollectionId,
+    /// the songs in the collection
+    pub songs: Vec<super::song::SongId>,
+    /// the mean of the features of the analyzed songs in the collection, `None` if none of them have been analyzed
+    pub centroid: Option<[f64; mecomp_analysis::NUMBER_FEATURES]>,
+}
+
 impl From<&Collection> for CollectionBrief {
     fn from(collection: &Collection) -> Self {
         Self {
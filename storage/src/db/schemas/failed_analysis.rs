@@ -0,0 +1,72 @@
+#![allow(clippy::module_name_repetitions)]
+#[cfg(not(feature = "db"))]
+use super::{Id, Thing};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "db")]
+use surrealdb::sql::{Id, Thing};
+
+use super::song::SongId;
+
+pub type FailedAnalysisId = Thing;
+
+pub const TABLE_NAME: &str = "failed_analysis";
+
+/// The number of times the analysis of a [`Song`] can fail before it stops being retried automatically
+/// (it can still be retried manually).
+///
+/// [`Song`]: super::song::Song
+pub const MAX_ATTEMPTS: u8 = 3;
+
+/// This struct records a failed attempt to analyze a particular [`Song`] (e.g. because its audio is corrupted),
+/// so that it can be inspected and retried later.
+///
+/// There is at most one [`FailedAnalysis`] per [`Song`], and it is removed once the [`Song`] is analyzed successfully.
+///
+/// [`Song`]: super::song::Song
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "db", derive(surrealqlx::Table))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "db", Table("failed_analysis"))]
+pub struct FailedAnalysis {
+    /// the unique identifier for this [`FailedAnalysis`].
+    #[cfg_attr(feature = "db", field("any"))]
+    pub id: FailedAnalysisId,
+
+    /// The [`Song`] that failed to be analyzed.
+    ///
+    /// [`Song`]: super::song::Song
+    #[cfg_attr(feature = "db", field(dt = "record<song>", index(unique)))]
+    pub song_id: SongId,
+
+    /// The error from the most recent attempt.
+    #[cfg_attr(feature = "db", field(dt = "string"))]
+    pub error: String,
+
+    /// How many times the analysis has been attempted (and failed).
+    #[cfg_attr(feature = "db", field(dt = "int"))]
+    pub attempt_count: u8,
+
+    /// When the most recent attempt was made.
+    #[cfg_attr(feature = "db", field(dt = "datetime"))]
+    #[cfg_attr(
+        feature = "db",
+        serde(
+            serialize_with = "super::serialize_datetime_as_sql_datetime",
+            deserialize_with = "super::deserialize_datetime_from_sql_datetime"
+        )
+    )]
+    pub last_attempted: DateTime<Utc>,
+}
+
+impl FailedAnalysis {
+    #[must_use]
+    pub fn generate_id() -> FailedAnalysisId {
+        Thing::from((TABLE_NAME, Id::ulid()))
+    }
+
+    /// Whether this [`FailedAnalysis`] has used up its automatic retries.
+    #[must_use]
+    pub const fn is_exhausted(&self) -> bool {
+        self.attempt_count >= MAX_ATTEMPTS
+    }
+}
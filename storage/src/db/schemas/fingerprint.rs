@@ -0,0 +1,46 @@
+#![allow(clippy::module_name_repetitions)]
+#[cfg(not(feature = "db"))]
+use super::{Id, Thing};
+#[cfg(feature = "db")]
+use surrealdb::sql::{Id, Thing};
+
+use super::song::SongId;
+
+pub type FingerprintId = Thing;
+
+pub const TABLE_NAME: &str = "fingerprint";
+
+/// This struct holds the acoustic fingerprint of a particular [`Song`]'s audio,
+/// used to find duplicates that have been renamed, re-tagged, or re-encoded.
+///
+/// Fingerprints are kept out of the [`Song`] table because they are large (a few KB per minute of audio),
+/// and are only needed when looking for duplicates.
+/// There is at most one [`Fingerprint`] per [`Song`].
+///
+/// [`Song`]: super::song::Song
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "db", derive(surrealqlx::Table))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "db", Table("fingerprint"))]
+pub struct Fingerprint {
+    /// the unique identifier for this [`Fingerprint`].
+    #[cfg_attr(feature = "db", field("any"))]
+    pub id: FingerprintId,
+
+    /// The [`Song`] that was fingerprinted.
+    ///
+    /// [`Song`]: super::song::Song
+    #[cfg_attr(feature = "db", field(dt = "record<song>", index(unique)))]
+    pub song_id: SongId,
+
+    /// The fingerprint, one 32 bit word per frame of audio (see `mecomp_analysis::fingerprint`).
+    #[cfg_attr(feature = "db", field(dt = "array<int>"))]
+    pub fingerprint: Vec<u32>,
+}
+
+impl Fingerprint {
+    #[must_use]
+    pub fn generate_id() -> FingerprintId {
+        Thing::from((TABLE_NAME, Id::ulid()))
+    }
+}
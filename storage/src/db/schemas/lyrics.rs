@@ -0,0 +1,48 @@
+#![allow(clippy::module_name_repetitions)]
+use std::sync::Arc;
+
+#[cfg(not(feature = "db"))]
+use super::{Id, Thing};
+#[cfg(feature = "db")]
+use surrealdb::sql::{Id, Thing};
+
+use super::song::SongId;
+
+pub type LyricsId = Thing;
+
+pub const TABLE_NAME: &str = "lyrics";
+
+/// This struct holds the lyrics of a particular [`Song`], as read from its tags.
+///
+/// Lyrics are kept out of the [`Song`] table because they can be long, and [`Song`]s are sent to clients
+/// all the time (e.g. the queue in every audio state update), while the lyrics are only needed
+/// for the song that is currently playing.
+/// There is at most one [`Lyrics`] per [`Song`].
+///
+/// [`Song`]: super::song::Song
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "db", derive(surrealqlx::Table))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "db", Table("lyrics"))]
+pub struct Lyrics {
+    /// the unique identifier for this [`Lyrics`].
+    #[cfg_attr(feature = "db", field("any"))]
+    pub id: LyricsId,
+
+    /// The [`Song`] these are the lyrics of.
+    ///
+    /// [`Song`]: super::song::Song
+    #[cfg_attr(feature = "db", field(dt = "record<song>", index(unique)))]
+    pub song_id: SongId,
+
+    /// The lyrics themselves.
+    #[cfg_attr(feature = "db", field(dt = "string", index(text("custom_analyzer"))))]
+    pub lyrics: Arc<str>,
+}
+
+impl Lyrics {
+    #[must_use]
+    pub fn generate_id() -> LyricsId {
+        Thing::from((TABLE_NAME, Id::ulid()))
+    }
+}
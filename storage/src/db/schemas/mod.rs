@@ -5,7 +5,11 @@ pub mod album;
 pub mod analysis;
 pub mod artist;
 pub mod collection;
+pub mod failed_analysis;
+pub mod fingerprint;
+pub mod lyrics;
 pub mod playlist;
+pub mod scan_history;
 pub mod song;
 
 /// Serialize a `std::time::Duration` as a `surrealdb::sql::Duration`.
@@ -60,6 +64,197 @@ where
     Ok(duration.into())
 }
 
+/// Deserialize an `Option<std::time::Duration>` from an `Option<surrealdb::sql::Duration>`.
+///
+/// # Errors
+///
+/// This function will return an error if the `Option<std::time::Duration>` cannot be deserialized from an `Option<surrealdb::sql::Duration>`.
+#[cfg(feature = "db")]
+pub fn deserialize_duration_option_from_sql_duration<'de, D>(
+    d: D,
+) -> Result<Option<std::time::Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let duration = Option::<surrealdb::sql::Duration>::deserialize(d)?;
+    Ok(duration.map(Into::into))
+}
+
+/// Serialize a `chrono::DateTime<Utc>` as a `surrealdb::sql::Datetime`.
+///
+/// # Errors
+///
+/// This function will return an error if the `chrono::DateTime<Utc>` cannot be serialized as a `surrealdb::sql::Datetime`.
+#[cfg(feature = "db")]
+pub fn serialize_datetime_as_sql_datetime<S>(
+    x: &chrono::DateTime<chrono::Utc>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::Serialize;
+
+    Into::<surrealdb::sql::Datetime>::into(*x).serialize(s)
+}
+
+/// Deserialize a `chrono::DateTime<Utc>` from a `surrealdb::sql::Datetime`.
+///
+/// # Errors
+///
+/// This function will return an error if the `chrono::DateTime<Utc>` cannot be deserialized from a `surrealdb::sql::Datetime`.
+#[cfg(feature = "db")]
+pub fn deserialize_datetime_from_sql_datetime<'de, D>(
+    d: D,
+) -> Result<chrono::DateTime<chrono::Utc>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let datetime = surrealdb::sql::Datetime::deserialize(d)?;
+    Ok(datetime.into())
+}
+
+/// Serialize an `Option<chrono::DateTime<Utc>>` as an `Option<surrealdb::sql::Datetime>`.
+///
+/// # Errors
+///
+/// This function will return an error if the `Option<chrono::DateTime<Utc>>` cannot be serialized as an `Option<surrealdb::sql::Datetime>`.
+#[cfg(feature = "db")]
+pub fn serialize_datetime_option_as_sql_datetime<S>(
+    x: &Option<chrono::DateTime<chrono::Utc>>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::Serialize;
+
+    x.map(Into::<surrealdb::sql::Datetime>::into).serialize(s)
+}
+
+/// Deserialize an `Option<chrono::DateTime<Utc>>` from an `Option<surrealdb::sql::Datetime>`.
+///
+/// # Errors
+///
+/// This function will return an error if the `Option<chrono::DateTime<Utc>>` cannot be deserialized from an `Option<surrealdb::sql::Datetime>`.
+#[cfg(feature = "db")]
+pub fn deserialize_datetime_option_from_sql_datetime<'de, D>(
+    d: D,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let datetime = Option::<surrealdb::sql::Datetime>::deserialize(d)?;
+    Ok(datetime.map(Into::into))
+}
+
+/// Serialize a list of `std::time::Duration`s as a list of (fractional) seconds.
+///
+/// # Errors
+///
+/// This function will return an error if the list cannot be serialized.
+#[cfg(feature = "serde")]
+pub fn serialize_durations_as_secs<S>(x: &[std::time::Duration], s: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    s.collect_seq(x.iter().map(std::time::Duration::as_secs_f64))
+}
+
+/// Serialize an optional list of `std::time::Duration`s as an optional list of (fractional) seconds.
+///
+/// # Errors
+///
+/// This function will return an error if the list cannot be serialized.
+#[cfg(feature = "serde")]
+#[allow(clippy::ref_option)]
+pub fn serialize_durations_option_as_secs<S>(
+    x: &Option<Vec<std::time::Duration>>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::Serialize;
+
+    x.as_ref()
+        .map(|x| {
+            x.iter()
+                .map(std::time::Duration::as_secs_f64)
+                .collect::<Vec<_>>()
+        })
+        .serialize(s)
+}
+
+/// Convert (fractional) seconds to a `std::time::Duration`, rounded to the nearest millisecond.
+///
+/// Negative (or NaN) values are treated as zero.
+#[cfg(feature = "serde")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn duration_from_secs(secs: f64) -> std::time::Duration {
+    std::time::Duration::from_millis((secs * 1000.0).round().max(0.0) as u64)
+}
+
+/// Deserialize a list of (fractional) seconds as a list of `std::time::Duration`s, rounded to the nearest millisecond.
+///
+/// A missing (`null`) list is deserialized as an empty list.
+///
+/// # Errors
+///
+/// This function will return an error if the list cannot be deserialized.
+#[cfg(feature = "serde")]
+pub fn deserialize_durations_from_secs<'de, D>(d: D) -> Result<Vec<std::time::Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let secs = Option::<Vec<f64>>::deserialize(d)?.unwrap_or_default();
+    Ok(secs.into_iter().map(duration_from_secs).collect())
+}
+
+/// Deserialize an optional list of (fractional) seconds as an optional list of `std::time::Duration`s,
+/// rounded to the nearest millisecond.
+///
+/// # Errors
+///
+/// This function will return an error if the list cannot be deserialized.
+#[cfg(feature = "serde")]
+pub fn deserialize_durations_option_from_secs<'de, D>(
+    d: D,
+) -> Result<Option<Vec<std::time::Duration>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let secs = Option::<Vec<f64>>::deserialize(d)?;
+    Ok(secs.map(|secs| secs.into_iter().map(duration_from_secs).collect()))
+}
+
+/// Deserialize a field of a changeset that's present (even if it's `null`) as `Some`,
+/// so that `Some(None)` (clear the field) and `None` (leave the field alone) survive a round trip.
+///
+/// Fields using this need `#[serde(default)]` so that missing fields are `None`.
+///
+/// # Errors
+///
+/// This function will return an error if the inner value cannot be deserialized.
+#[cfg(feature = "serde")]
+pub fn deserialize_some<'de, T, D>(d: D) -> Result<Option<T>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(d).map(Some)
+}
+
 /// Implement a version of the `surrealdb` `Thing` type that we can use when the `db` feature is not enabled.
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
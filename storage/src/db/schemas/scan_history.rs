@@ -0,0 +1,160 @@
+#![allow(clippy::module_name_repetitions)]
+#[cfg(not(feature = "db"))]
+use super::{Id, Thing};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "db")]
+use surrealdb::sql::{Id, Thing};
+
+pub type ScanMetricsId = Thing;
+
+pub const TABLE_NAME: &str = "scan_history";
+
+/// The number of [`ScanMetrics`] kept for each library path, older entries are removed.
+pub const MAX_HISTORY: usize = 10;
+
+/// The error rate (errors per scanned file) above which a library path is considered degraded.
+pub const DEGRADED_ERROR_RATE: f64 = 0.01;
+/// The error rate (errors per scanned file) above which a library path is considered failing.
+pub const FAILING_ERROR_RATE: f64 = 0.1;
+
+/// This struct records how the scan of one of the library paths went during a library rescan,
+/// so that problems with a particular path (e.g. a slow network mount) can be spotted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "db", derive(surrealqlx::Table))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "db", Table("scan_history"))]
+pub struct ScanMetrics {
+    /// the unique identifier for this [`ScanMetrics`].
+    #[cfg_attr(feature = "db", field("any"))]
+    pub id: ScanMetricsId,
+
+    /// The library path that was scanned.
+    #[cfg_attr(feature = "db", field(dt = "string", index()))]
+    pub path: PathBuf,
+
+    /// How many files were found under the path.
+    #[cfg_attr(feature = "db", field(dt = "int"))]
+    pub files_found: u64,
+
+    /// How many of the files found were read (files that were already in the library aren't read again).
+    #[cfg_attr(feature = "db", field(dt = "int"))]
+    pub files_scanned: u64,
+
+    /// How many errors occurred while scanning the path (unreadable directories, files that couldn't be indexed, etc.)
+    #[cfg_attr(feature = "db", field(dt = "int"))]
+    pub errors: u64,
+
+    /// How long the scan of the path took, in milliseconds.
+    #[cfg_attr(feature = "db", field(dt = "int"))]
+    pub duration_ms: u64,
+
+    /// When the scan finished.
+    #[cfg_attr(feature = "db", field(dt = "datetime"))]
+    #[cfg_attr(
+        feature = "db",
+        serde(
+            serialize_with = "super::serialize_datetime_as_sql_datetime",
+            deserialize_with = "super::deserialize_datetime_from_sql_datetime"
+        )
+    )]
+    pub scanned_at: DateTime<Utc>,
+}
+
+/// How healthy a library path is, based on the error rate of its most recent scan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanHealth {
+    /// (almost) no errors.
+    Healthy,
+    /// some errors, worth looking into.
+    Degraded,
+    /// lots of errors, the path is probably (partially) unreachable.
+    Failing,
+}
+
+impl ScanMetrics {
+    #[must_use]
+    pub fn generate_id() -> ScanMetricsId {
+        Thing::from((TABLE_NAME, Id::ulid()))
+    }
+
+    /// Create the metrics of a scan of `path` that just finished, and took `duration`.
+    #[must_use]
+    pub fn new(
+        path: PathBuf,
+        files_found: u64,
+        files_scanned: u64,
+        errors: u64,
+        duration: std::time::Duration,
+    ) -> Self {
+        Self {
+            id: Self::generate_id(),
+            path,
+            files_found,
+            files_scanned,
+            errors,
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            scanned_at: Utc::now(),
+        }
+    }
+
+    /// The fraction of scanned files that resulted in an error.
+    ///
+    /// Errors that occurred before any file could be scanned (e.g. the path doesn't exist) count as a 100% error rate.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn error_rate(&self) -> f64 {
+        match (self.errors, self.files_scanned) {
+            (0, _) => 0.0,
+            (_, 0) => 1.0,
+            (errors, scanned) => errors as f64 / scanned as f64,
+        }
+    }
+
+    /// How healthy the path was during this scan.
+    #[must_use]
+    pub fn health(&self) -> ScanHealth {
+        let error_rate = self.error_rate();
+        if error_rate > FAILING_ERROR_RATE {
+            ScanHealth::Failing
+        } else if error_rate > DEGRADED_ERROR_RATE {
+            ScanHealth::Degraded
+        } else {
+            ScanHealth::Healthy
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::nothing(0, 0, 0.0, ScanHealth::Healthy)]
+    #[case::no_errors(100, 0, 0.0, ScanHealth::Healthy)]
+    #[case::few_errors(1000, 5, 0.005, ScanHealth::Healthy)]
+    #[case::some_errors(100, 5, 0.05, ScanHealth::Degraded)]
+    #[case::many_errors(100, 50, 0.5, ScanHealth::Failing)]
+    #[case::unreachable(0, 1, 1.0, ScanHealth::Failing)]
+    fn test_health(
+        #[case] files_scanned: u64,
+        #[case] errors: u64,
+        #[case] error_rate: f64,
+        #[case] health: ScanHealth,
+    ) {
+        let metrics = ScanMetrics::new(
+            PathBuf::from("/music"),
+            files_scanned,
+            files_scanned,
+            errors,
+            std::time::Duration::from_millis(1500),
+        );
+        assert_eq!(metrics.duration_ms, 1500);
+
+        assert!((metrics.error_rate() - error_rate).abs() < f64::EPSILON);
+        assert_eq!(metrics.health(), health);
+    }
+}
@@ -5,12 +5,15 @@ use std::sync::Arc;
 //--------------------------------------------------------------------------------- other libraries
 #[cfg(not(feature = "db"))]
 use super::{Id, Thing};
+use chrono::{DateTime, Utc};
 use lofty::{file::TaggedFileExt, prelude::*, probe::Probe, tag::Accessor};
 use std::time::Duration;
 #[cfg(feature = "db")]
 use surrealdb::sql::{Id, Thing};
 use tracing::instrument;
 //----------------------------------------------------------------------------------- local modules
+#[cfg(feature = "verify")]
+use crate::errors::LibraryVerificationError;
 use crate::errors::SongIOError;
 use one_or_many::OneOrMany;
 
@@ -18,8 +21,11 @@ pub type SongId = Thing;
 
 pub const TABLE_NAME: &str = "song";
 
+/// The highest rating a [`Song`] can be given (the lowest is 1).
+pub const MAX_RATING: u8 = 5;
+
 /// This struct holds all the metadata about a particular [`Song`].
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "db", derive(surrealqlx::Table))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "db", Table("song"))]
@@ -42,10 +48,13 @@ pub struct Song {
     #[cfg_attr(feature = "serde", serde(default))]
     pub album_artist: OneOrMany<Arc<str>>,
     /// album title
-    #[cfg_attr(feature = "db", field(dt = "string"))]
+    #[cfg_attr(feature = "db", field(dt = "string", index(text("custom_analyzer"))))]
     pub album: Arc<str>,
     /// Genre of the [`Song`]. (Can be multiple)
-    #[cfg_attr(feature = "db", field(dt = "option<set<string> | string>"))]
+    #[cfg_attr(
+        feature = "db",
+        field(dt = "option<set<string> | string>", index(text("custom_analyzer")))
+    )]
     #[cfg_attr(feature = "serde", serde(default))]
     pub genre: OneOrMany<Arc<str>>,
 
@@ -83,17 +92,117 @@ pub struct Song {
     /// The [`PathBuf`] this [`Song`] is located at.
     #[cfg_attr(feature = "db", field(dt = "string", index(unique)))]
     pub path: PathBuf,
+
+    /// The `ReplayGain` 2.0 track gain (in dB) of this [`Song`], if it has been computed.
+    #[cfg_attr(feature = "db", field(dt = "option<float>"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub replaygain_track_gain: Option<f32>,
+    /// The `ReplayGain` 2.0 album gain (in dB) of the album this [`Song`] is on, if it has been computed.
+    ///
+    /// Every song on an album has the same album gain, it's computed from the audio of the whole album.
+    #[cfg_attr(feature = "db", field(dt = "option<float>"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub replaygain_album_gain: Option<f32>,
+
+    /// The number of times this [`Song`] has been played to completion.
+    ///
+    /// This is optional in the schema so that songs added before plays were counted are still valid.
+    #[cfg_attr(feature = "db", field(dt = "option<int>"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub play_count: u64,
+    /// The number of times this [`Song`] has been skipped before most of it was played.
+    ///
+    /// This is optional in the schema so that songs added before skips were counted are still valid.
+    #[cfg_attr(feature = "db", field(dt = "option<int>"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub skip_count: u64,
+    /// When this [`Song`] was last played to completion, if ever.
+    #[cfg_attr(feature = "db", field(dt = "option<datetime>"))]
+    #[cfg_attr(
+        feature = "db",
+        serde(
+            serialize_with = "super::serialize_datetime_option_as_sql_datetime",
+            deserialize_with = "super::deserialize_datetime_option_from_sql_datetime"
+        )
+    )]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub last_played: Option<DateTime<Utc>>,
+
+    /// The user's rating of this [`Song`], from 1 to [`MAX_RATING`] stars, if it has been rated.
+    #[cfg_attr(feature = "db", field(dt = "option<int>"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rating: Option<u8>,
+    /// Whether this [`Song`] is one of the user's favorites.
+    #[cfg_attr(feature = "db", field(dt = "option<bool>"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub favorite: bool,
+
+    /// Positions in this [`Song`] the user has bookmarked (e.g. where they left off in an audiobook or podcast),
+    /// in ascending order, stored as seconds.
+    #[cfg_attr(feature = "db", field(dt = "option<array<float>>"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            serialize_with = "super::serialize_durations_as_secs",
+            deserialize_with = "super::deserialize_durations_from_secs"
+        )
+    )]
+    pub bookmark_positions: Vec<Duration>,
 }
 
+// the replaygain is never NaN, so this is fine
+impl Eq for Song {}
+
+/// How much of a song's audio is decoded by [`Song::verify_file`].
+#[cfg(feature = "verify")]
+const VERIFY_DECODE_DURATION: Duration = Duration::from_secs(2);
+
 impl Song {
     #[must_use]
     pub fn generate_id() -> SongId {
         Thing::from((TABLE_NAME, Id::ulid()))
     }
+
+    /// Check that the file of this [`Song`] still exists, and that its audio can be decoded.
+    ///
+    /// Only the first 2 seconds of the audio are decoded, using the same decoder as playback and analysis.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is missing, empty, can't be read, or its audio can't be decoded.
+    #[cfg(feature = "verify")]
+    #[instrument]
+    pub fn verify_file(&self) -> Result<(), LibraryVerificationError> {
+        use rodio::Source;
+
+        let file = std::fs::File::open(&self.path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                LibraryVerificationError::Missing(self.path.clone())
+            } else {
+                LibraryVerificationError::Unreadable(e)
+            }
+        })?;
+
+        if file.metadata()?.len() == 0 {
+            return Err(LibraryVerificationError::Empty(self.path.clone()));
+        }
+
+        let decoder = rodio::Decoder::new(std::io::BufReader::new(file))
+            .map_err(|e| LibraryVerificationError::Corrupted(e.to_string()))?;
+        if decoder.take_duration(VERIFY_DECODE_DURATION).count() == 0 {
+            return Err(LibraryVerificationError::Corrupted(String::from(
+                "no audio could be decoded",
+            )));
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SongChangeSet {
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub title: Option<Arc<str>>,
@@ -108,19 +217,123 @@ pub struct SongChangeSet {
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(
         feature = "db",
-        serde(serialize_with = "super::serialize_duration_option_as_sql_duration")
+        serde(
+            serialize_with = "super::serialize_duration_option_as_sql_duration",
+            deserialize_with = "super::deserialize_duration_option_from_sql_duration"
+        )
     )]
     pub runtime: Option<Duration>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::deserialize_some"
+        )
+    )]
     pub track: Option<Option<u16>>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::deserialize_some"
+        )
+    )]
     pub disc: Option<Option<u16>>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::deserialize_some"
+        )
+    )]
     pub release_year: Option<Option<i32>>,
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub extension: Option<Arc<str>>,
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub path: Option<PathBuf>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::deserialize_some"
+        )
+    )]
+    pub replaygain_track_gain: Option<Option<f32>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::deserialize_some"
+        )
+    )]
+    pub replaygain_album_gain: Option<Option<f32>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub play_count: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub skip_count: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        feature = "db",
+        serde(
+            serialize_with = "super::serialize_datetime_option_as_sql_datetime",
+            deserialize_with = "super::deserialize_datetime_option_from_sql_datetime"
+        )
+    )]
+    pub last_played: Option<DateTime<Utc>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "super::deserialize_some"
+        )
+    )]
+    pub rating: Option<Option<u8>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub favorite: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "super::serialize_durations_option_as_secs",
+            deserialize_with = "super::deserialize_durations_option_from_secs"
+        )
+    )]
+    pub bookmark_positions: Option<Vec<Duration>>,
+}
+
+// the replaygain is never NaN, so this is fine
+impl Eq for SongChangeSet {}
+
+/// How often, and how recently, a [`Song`] has been played.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayStats {
+    /// The number of times the song has been played to completion
+    pub play_count: u64,
+    /// The number of times the song has been skipped before most of it was played
+    pub skip_count: u64,
+    /// When the song was last played to completion, if ever
+    pub last_played: Option<DateTime<Utc>>,
+}
+
+impl PlayStats {
+    /// The fraction of the times the song was started that it was skipped, or `None` if it's never been started.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn skip_ratio(&self) -> Option<f64> {
+        let total = self.play_count + self.skip_count;
+        (total > 0).then(|| self.skip_count as f64 / total as f64)
+    }
+}
+
+impl From<&Song> for PlayStats {
+    fn from(song: &Song) -> Self {
+        Self {
+            play_count: song.play_count,
+            skip_count: song.skip_count,
+            last_played: song.last_played,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -172,6 +385,7 @@ pub struct SongMetadata {
     pub disc: Option<u16>,
     pub extension: Arc<str>,
     pub path: PathBuf,
+    pub lyrics: Option<Arc<str>>,
 }
 
 impl From<&Song> for SongMetadata {
@@ -188,6 +402,8 @@ impl From<&Song> for SongMetadata {
             release_year: song.release_year,
             extension: song.extension.clone(),
             path: song.path.clone(),
+            // lyrics are kept in their own table, see `Lyrics`
+            lyrics: None,
         }
     }
 }
@@ -206,6 +422,8 @@ impl From<Song> for SongMetadata {
             release_year: song.release_year,
             extension: song.extension,
             path: song.path,
+            // lyrics are kept in their own table, see `Lyrics`
+            lyrics: None,
         }
     }
 }
@@ -374,6 +592,12 @@ impl SongMetadata {
                 .to_string_lossy()
                 .into(),
             path,
+            lyrics: tag
+                .get_string(&ItemKey::Lyrics)
+                .map(|x| x.replace('\0', ""))
+                .map(|x| x.trim().to_owned())
+                .filter(|x| !x.is_empty())
+                .map(Into::into),
         })
     }
 }
@@ -400,6 +624,14 @@ mod tests {
             release_year: Some(2021),
             extension: Arc::from("mp3"),
             path: PathBuf::from("path"),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         }
     }
 
@@ -438,6 +670,7 @@ mod tests {
         release_year: Some(2021),
         extension: Arc::from("mp3"),
         path: PathBuf::from("path"),
+        lyrics: None,
     },
     Song {
         id: Thing::from((TABLE_NAME, "id")),
@@ -452,6 +685,14 @@ mod tests {
         release_year: Some(2021),
         extension: Arc::from("mp3"),
         path: PathBuf::from("path"),
+        replaygain_track_gain: None,
+        replaygain_album_gain: None,
+        play_count: 0,
+        skip_count: 0,
+        last_played: None,
+        rating: None,
+        favorite: false,
+        bookmark_positions: Vec::new(),
     },
     SongChangeSet::default())]
     #[case::different(SongMetadata {
@@ -466,6 +707,7 @@ mod tests {
         release_year: Some(2021),
         extension: Arc::from("mp3"),
         path: PathBuf::from("path"),
+        lyrics: None,
     },
     Song {
         id: Thing::from((TABLE_NAME, "id")),
@@ -480,6 +722,14 @@ mod tests {
         release_year: Some(2021),
         extension: Arc::from("mp3"),
         path: PathBuf::from("path"),
+        replaygain_track_gain: None,
+        replaygain_album_gain: None,
+        play_count: 0,
+        skip_count: 0,
+        last_played: None,
+        rating: None,
+        favorite: false,
+        bookmark_positions: Vec::new(),
     },
     SongChangeSet{
         title: Some(Arc::from("song 2")),
@@ -496,4 +746,86 @@ mod tests {
         let actual = base.merge_with_song(&other);
         assert_eq!(actual, expected);
     }
+
+    #[rstest]
+    #[case::never_started(0, 0, None)]
+    #[case::never_skipped(4, 0, Some(0.0))]
+    #[case::always_skipped(0, 3, Some(1.0))]
+    #[case::sometimes_skipped(3, 1, Some(0.25))]
+    fn test_skip_ratio(
+        #[case] play_count: u64,
+        #[case] skip_count: u64,
+        #[case] expected: Option<f64>,
+    ) {
+        let stats = PlayStats::from(&Song {
+            play_count,
+            skip_count,
+            ..song()
+        });
+        assert_eq!(stats.skip_ratio(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "verify")]
+    fn test_verify_file() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let metadata = crate::test_utils::create_song_metadata(
+            &tempdir,
+            crate::test_utils::arb_song_case()(),
+        )?;
+        let song = Song {
+            path: metadata.path.clone(),
+            ..song()
+        };
+
+        assert!(song.verify_file().is_ok());
+
+        // a file that isn't audio
+        std::fs::write(&song.path, b"not actually audio")?;
+        assert!(matches!(
+            song.verify_file(),
+            Err(LibraryVerificationError::Corrupted(_))
+        ));
+
+        // an empty file
+        std::fs::write(&song.path, b"")?;
+        assert!(matches!(
+            song.verify_file(),
+            Err(LibraryVerificationError::Empty(path)) if path == song.path
+        ));
+
+        // a file that doesn't exist
+        std::fs::remove_file(&song.path)?;
+        assert!(matches!(
+            song.verify_file(),
+            Err(LibraryVerificationError::Missing(path)) if path == song.path
+        ));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::empty(SongChangeSet::default())]
+    #[case::set(SongChangeSet {
+        title: Some("new title".into()),
+        track: Some(Some(3)),
+        rating: Some(Some(4)),
+        ..Default::default()
+    })]
+    #[case::clear(SongChangeSet {
+        disc: Some(None),
+        release_year: Some(None),
+        ..Default::default()
+    })]
+    #[case::bookmarks(SongChangeSet {
+        bookmark_positions: Some(vec![Duration::from_millis(1500), Duration::from_secs(90)]),
+        ..Default::default()
+    })]
+    #[cfg(feature = "serde")]
+    fn test_changeset_serde_round_trip(#[case] changes: SongChangeSet) -> anyhow::Result<()> {
+        let json = serde_json::to_string(&changes)?;
+        let deserialized: SongChangeSet = serde_json::from_str(&json)?;
+        assert_eq!(deserialized, changes);
+        Ok(())
+    }
 }
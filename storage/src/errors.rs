@@ -17,6 +17,10 @@ pub enum Error {
     SongIOError(#[from] SongIOError),
     #[error("Item not created.")]
     NotCreated,
+    #[error("The new order must contain exactly the songs already in the playlist.")]
+    InvalidSongOrder,
+    #[error("Ratings must be between 1 and 5 stars, got {0}.")]
+    InvalidRating(u8),
 }
 
 #[derive(Error, Debug)]
@@ -35,4 +39,16 @@ pub enum SongIOError {
     DurationReadError,
 }
 
+#[derive(Error, Debug)]
+pub enum LibraryVerificationError {
+    #[error("File not found: {0}")]
+    Missing(PathBuf),
+    #[error("File is empty: {0}")]
+    Empty(PathBuf),
+    #[error("Failed to decode audio: {0}")]
+    Corrupted(String),
+    #[error("IO error: {0}")]
+    Unreadable(#[from] std::io::Error),
+}
+
 pub type StorageResult<T> = std::result::Result<T, Error>;
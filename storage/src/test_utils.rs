@@ -8,6 +8,8 @@ use std::{
 
 use anyhow::Result;
 use lofty::{config::WriteOptions, file::TaggedFileExt, prelude::*, probe::Probe, tag::Accessor};
+#[cfg(feature = "analysis")]
+use mecomp_analysis::NUMBER_FEATURES;
 use one_or_many::OneOrMany;
 use rand::{seq::IteratorRandom, Rng};
 #[cfg(feature = "db")]
@@ -17,17 +19,21 @@ use surrealdb::{
     Connection, Surreal,
 };
 
-#[cfg(feature = "analysis")]
-use crate::db::schemas::analysis::Analysis;
 #[cfg(not(feature = "db"))]
 use crate::db::schemas::Id;
 use crate::db::schemas::{
     album::Album,
     artist::Artist,
     collection::Collection,
+    lyrics::Lyrics,
     playlist::Playlist,
+    scan_history::ScanMetrics,
     song::{Song, SongChangeSet, SongMetadata},
 };
+#[cfg(feature = "analysis")]
+use crate::db::schemas::{
+    analysis::Analysis, failed_analysis::FailedAnalysis, fingerprint::Fingerprint,
+};
 
 pub const ARTIST_NAME_SEPARATOR: &str = ", ";
 
@@ -43,9 +49,18 @@ pub async fn init_test_database() -> surrealdb::Result<Surreal<Db>> {
     db.use_ns("test").use_db("test").await?;
 
     crate::db::register_custom_analyzer(&db).await?;
-    surrealqlx::register_tables!(&db, Album, Artist, Song, Collection, Playlist)?;
+    surrealqlx::register_tables!(
+        &db,
+        Album,
+        Artist,
+        Song,
+        Lyrics,
+        Collection,
+        Playlist,
+        ScanMetrics
+    )?;
     #[cfg(feature = "analysis")]
-    surrealqlx::register_tables!(&db, Analysis)?;
+    surrealqlx::register_tables!(&db, Analysis, FailedAnalysis, Fingerprint)?;
 
     Ok(db)
 }
@@ -97,6 +112,14 @@ pub async fn create_song_with_overrides<C: Connection>(
         release_year: None,
         extension: Arc::from("mp3"),
         path: PathBuf::from_str(&format!("{}.mp3", id.id))?,
+        replaygain_track_gain: None,
+        replaygain_album_gain: None,
+        play_count: 0,
+        skip_count: 0,
+        last_played: None,
+        rating: None,
+        favorite: false,
+        bookmark_positions: Vec::new(),
     };
 
     Song::create(db, song.clone()).await?;
@@ -346,10 +369,11 @@ where
     }
 }
 
-pub fn arb_analysis_features() -> impl Fn() -> [f64; 20] {
+#[cfg(feature = "analysis")]
+pub fn arb_analysis_features() -> impl Fn() -> [f64; NUMBER_FEATURES] {
     move || {
         let rng = &mut rand::thread_rng();
-        let mut features = [0.0; 20];
+        let mut features = [0.0; NUMBER_FEATURES];
         for feature in &mut features {
             *feature = rng.gen_range(-1.0..1.0);
         }
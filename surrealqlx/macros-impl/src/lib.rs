@@ -408,7 +408,7 @@ impl IndexTypeAnnotation {
             }
             _ => Err(syn::Error::new_spanned(
                 arg,
-                "Unsupported expression syntax",
+                "Unsupported expression syntax, index attribute parameters should be one of: `compound(\"field\", ...)`, `unique`, `vector(dim)`, or `text(\"analyzer\")`",
             )),
         }
     }
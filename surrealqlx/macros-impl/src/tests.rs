@@ -1,4 +1,4 @@
-use crate::table_macro_impl;
+use crate::{table_macro_impl, IndexAnnotation};
 use pretty_assertions::assert_str_eq;
 use proc_macro2::TokenStream;
 use quote::quote;
@@ -361,3 +361,67 @@ fn test_invalid_field(#[case] input: TokenStream) {
     let expanded = table_macro_impl(input);
     assert!(expanded.is_err());
 }
+
+/// parse the arguments of an `index(...)` attribute parameter
+fn index_args(input: &str) -> syn::punctuated::Punctuated<syn::Expr, syn::token::Comma> {
+    syn::parse_str::<syn::ExprCall>(input).unwrap().args
+}
+
+#[rstest]
+#[case::single_field(
+    r#"index(compound("text"))"#,
+    "DEFINE INDEX users_name_text_normal_index ON users FIELDS name,text;"
+)]
+#[case::multiple_fields(
+    r#"index(compound("text", "age"))"#,
+    "DEFINE INDEX users_name_text_age_normal_index ON users FIELDS name,text,age;"
+)]
+#[case::unique(
+    r#"index(compound("text"), unique)"#,
+    "DEFINE INDEX users_name_text_unique_index ON users FIELDS name,text UNIQUE;"
+)]
+#[case::unique_first(
+    r#"index(unique, compound("text"))"#,
+    "DEFINE INDEX users_name_text_unique_index ON users FIELDS name,text UNIQUE;"
+)]
+#[case::vector(
+    r#"index(vector(dim = 3), compound("text"))"#,
+    "DEFINE INDEX users_name_text_vector_index ON users FIELDS name,text MTREE DIMENSION 3;"
+)]
+#[case::text(
+    r#"index(compound("text"), text("ascii"))"#,
+    "DEFINE INDEX users_name_text_text_index ON users FIELDS name,text SEARCH ANALYZER ascii BM25;"
+)]
+fn test_compound_index(#[case] input: &str, #[case] expected: &str) {
+    let index = IndexAnnotation::parse(&index_args(input)).unwrap();
+    assert_str_eq!(index.to_query_string("users", "name"), expected);
+}
+
+#[rstest]
+#[case::no_fields(r#"index(compound())"#, "expects at least one string literal")]
+#[case::not_a_string(r#"index(compound(text))"#, "expects string literals")]
+#[case::two_compounds(
+    r#"index(compound("text"), compound("age"))"#,
+    "Unsupported expression syntax"
+)]
+#[case::unexpected_second_param(
+    r#"index(unique, unique)"#,
+    "unexpected parameters in index attribute"
+)]
+#[case::non_index_type_first(
+    r#"index(1, compound("text"))"#,
+    "index attribute parameters should be one of"
+)]
+#[case::non_index_type_path_first(
+    r#"index(foo, compound("text"))"#,
+    "index attribute parameters should be one of"
+)]
+fn test_invalid_compound_index(#[case] input: &str, #[case] expected_message: &str) {
+    let Err(err) = IndexAnnotation::parse(&index_args(input)) else {
+        panic!("expected `{input}` to fail to parse");
+    };
+    assert!(
+        err.to_string().contains(expected_message),
+        "expected error containing `{expected_message}`, got `{err}`"
+    );
+}
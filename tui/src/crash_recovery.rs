@@ -0,0 +1,158 @@
+//! Saving and restoring the queue when the TUI crashes.
+//!
+//! When the TUI panics, the panic hook (see [`crate::ui::init_panic_hook`]) writes the ids of the songs in the queue
+//! to a crash-recovery file in the data directory.
+//! On the next startup, if that file is recent enough, the user is asked if they want to restore the queue from it.
+//! If only the TUI crashed, the daemon kept running with the queue, so there's nothing to restore.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use mecomp_storage::db::schemas::{
+    song::{self, Song},
+    Id, Thing,
+};
+
+/// The name of the crash-recovery file, relative to the data directory.
+pub const CRASH_RECOVERY_FILE: &str = "tui-crash-recovery";
+
+/// Crash-recovery files older than this are ignored.
+pub const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Get the path to the crash-recovery file.
+///
+/// # Errors
+///
+/// Returns an error if the data directory could not be found.
+pub fn crash_recovery_file() -> Result<PathBuf, mecomp_core::errors::DirectoryError> {
+    Ok(mecomp_core::get_data_dir()?.join(CRASH_RECOVERY_FILE))
+}
+
+/// Write the ids of the songs in the queue to the crash-recovery file at `path`, one per line.
+///
+/// # Errors
+///
+/// Returns an error if the file could not be written.
+pub fn save_queue(path: &Path, queue: &[Song]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let contents = queue
+        .iter()
+        .map(|song| song.id.id.to_raw())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    fs::write(path, contents)
+}
+
+/// Read the queue from the crash-recovery file at `path`, removing the file afterwards.
+///
+/// Returns `None` if there is no crash-recovery file, it is older than [`MAX_AGE`], or the queue it contains is empty.
+/// Also returns `None` if the daemon still has a queue (`daemon_queue`), since restoring would only duplicate it.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but could not be read or removed.
+pub fn take_queue(path: &Path, daemon_queue: &[Song]) -> io::Result<Option<Vec<Thing>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let is_recent = fs::metadata(path)?
+        .modified()?
+        .elapsed()
+        .is_ok_and(|age| age <= MAX_AGE);
+    let contents = fs::read_to_string(path)?;
+    fs::remove_file(path)?;
+
+    if !is_recent || !daemon_queue.is_empty() {
+        return Ok(None);
+    }
+
+    let queue = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|id| Thing {
+            tb: song::TABLE_NAME.to_owned(),
+            id: Id::String(id.to_owned()),
+        })
+        .collect::<Vec<_>>();
+
+    Ok((!queue.is_empty()).then_some(queue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::state_with_everything;
+
+    use pretty_assertions::assert_eq;
+
+    fn song(id: &str) -> Song {
+        Song {
+            id: Thing::from((song::TABLE_NAME, Id::String(id.to_owned()))).into(),
+            ..state_with_everything().library.songs[0].clone()
+        }
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mecomp-tui-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn test_save_and_take_queue() {
+        let dir = test_dir("save-and-take-queue");
+        let path = dir.join(CRASH_RECOVERY_FILE);
+
+        save_queue(&path, &[song("a"), song("b")]).unwrap();
+        assert!(path.exists());
+
+        let queue = take_queue(&path, &[]).unwrap();
+        assert_eq!(
+            queue,
+            Some(vec![
+                Thing::from((song::TABLE_NAME, Id::String("a".into()))),
+                Thing::from((song::TABLE_NAME, Id::String("b".into()))),
+            ])
+        );
+        // the file is removed once it's been read
+        assert!(!path.exists());
+        assert_eq!(take_queue(&path, &[]).unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_take_queue_daemon_survived() {
+        let dir = test_dir("take-queue-daemon-survived");
+        let path = dir.join(CRASH_RECOVERY_FILE);
+
+        // only the TUI crashed, so the daemon still has the queue that was saved
+        let queue = [song("a"), song("b")];
+        save_queue(&path, &queue).unwrap();
+
+        assert_eq!(take_queue(&path, &queue).unwrap(), None);
+        // the file is still removed, so the user isn't asked again
+        assert!(!path.exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_take_empty_queue() {
+        let dir = test_dir("take-empty-queue");
+        let path = dir.join(CRASH_RECOVERY_FILE);
+
+        save_queue(&path, &[]).unwrap();
+        assert_eq!(take_queue(&path, &[]).unwrap(), None);
+        assert!(!path.exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
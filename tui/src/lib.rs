@@ -1,3 +1,4 @@
+pub mod crash_recovery;
 pub mod state;
 pub mod termination;
 #[cfg(test)]
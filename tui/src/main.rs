@@ -1,10 +1,11 @@
-use std::sync::Arc;
+use std::{io::Write, sync::Arc};
 
 use clap::Parser;
 #[cfg(feature = "autostart-daemon")]
 use mecomp_core::is_server_running;
-use mecomp_core::rpc::init_client;
+use mecomp_core::rpc::{init_client, MusicPlayerClient};
 use mecomp_tui::{
+    crash_recovery,
     state::Dispatcher,
     termination::{create_termination, Interrupted},
     ui::{init_panic_hook, UiManager},
@@ -21,10 +22,10 @@ struct Flags {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    init_panic_hook();
-
     let flags = Flags::parse();
 
+    init_panic_hook(flags.port);
+
     // check if the server is running, and if it's not, try to start it
     #[cfg(feature = "autostart-daemon")]
     let server_process = MaybeDaemonHandler::start(flags.port).await?;
@@ -32,6 +33,9 @@ async fn main() -> anyhow::Result<()> {
     // initialize the client
     let daemon = Arc::new(init_client(flags.port).await?);
 
+    // if the last session crashed, offer to restore the queue
+    restore_queue_from_crash(&daemon).await?;
+
     // initialize the signal handlers
     let (terminator, mut interrupt_rx) = create_termination();
     let (dispatcher, state_receivers) = Dispatcher::new();
@@ -66,6 +70,39 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// If there is a recent crash-recovery file, ask the user if they want to restore the queue from it.
+///
+/// If the daemon still has a queue (only the TUI crashed), it's left as is.
+async fn restore_queue_from_crash(daemon: &MusicPlayerClient) -> anyhow::Result<()> {
+    let Ok(path) = crash_recovery::crash_recovery_file() else {
+        return Ok(());
+    };
+    let daemon_queue = daemon
+        .state_audio(tarpc::context::current())
+        .await?
+        .map(|state| state.queue)
+        .unwrap_or_default();
+    let Some(queue) = crash_recovery::take_queue(&path, &daemon_queue)? else {
+        return Ok(());
+    };
+
+    print!("Restore queue from crash? ({} songs) [y/N] ", queue.len());
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    if matches!(answer.trim(), "y" | "Y" | "yes") {
+        if let Err(e) = daemon
+            .queue_add_list(tarpc::context::current(), queue)
+            .await?
+        {
+            eprintln!("failed to restore the queue: {e}");
+        }
+    }
+
+    Ok(())
+}
+
 /// Handler for the Daemon process, which will be started if the Daemon is not already running on the given port.
 ///
 /// Used so we can ensure that the Daemon is stopped when the TUI is stopped, by defining a Drop implementation.
@@ -4,7 +4,7 @@
 
 use std::time::Duration;
 
-use mecomp_core::state::{RepeatMode, SeekType};
+use mecomp_core::state::{equalizer::EqBand, RepeatMode, SeekType};
 use mecomp_storage::db::schemas::Thing;
 
 use crate::ui::{components::content_view::ActiveView, widgets::popups::PopupType};
@@ -57,6 +57,10 @@ pub enum PlaybackAction {
     Volume(VolumeAction),
     /// Toggle the mute state.
     ToggleMute,
+    /// Replace the bands of the equalizer.
+    SetEqualizer(Vec<EqBand>),
+    /// Apply the equalizer preset at the given index in the daemon's list of presets (wrapping around).
+    EqualizerPreset(usize),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -74,6 +78,8 @@ pub enum QueueAction {
     Add(Vec<Thing>),
     /// Remove something from the queue (by index)
     Remove(usize),
+    /// Move something in the queue from one index to another
+    Move(usize, usize),
     /// Set the current queue position
     SetPosition(usize),
     /// Shuffle the queue
@@ -82,6 +88,8 @@ pub enum QueueAction {
     Clear,
     /// Set the repeat mode
     SetRepeatMode(RepeatMode),
+    /// Remove duplicate songs from the queue
+    Dedup,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -106,6 +114,14 @@ pub enum LibraryAction {
     /// Create a new playlist with the given name (if it doesn't exist) and add the songs to it
     /// (`PlaylistName`, Vec<`SongId`>)
     CreatePlaylistAndAddThings(String, Vec<Thing>),
+    /// Save the current queue as a new playlist with the given name
+    SaveQueueAsPlaylist(String),
+    /// Merge playlists into a new playlist with the given name (Vec<`PlaylistId`>, `NewName`)
+    MergePlaylists(Vec<Thing>, String),
+    /// Rename a collection (`CollectionId`, `NewName`)
+    RenameCollection(Thing, String),
+    /// Merge a collection into another one (`SourceCollectionId`, `TargetCollectionId`)
+    MergeCollections(Thing, Thing),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
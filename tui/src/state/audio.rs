@@ -2,16 +2,28 @@
 //! which is updated every tick and used by views to render the audio playback and queue state.
 //!
 //! The audio state store is responsible for maintaining the audio state, and for handling audio related actions.
+//!
+//! To make the UI feel more responsive, some queue mutations are applied "optimistically" to the state before the daemon
+//! has processed them, see [`OptimisticState`].
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use tokio::sync::{
     broadcast,
     mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    watch,
 };
 
 use mecomp_core::rpc::MusicPlayerClient;
-use mecomp_core::state::StateAudio;
+use mecomp_core::state::{Percent, StateAudio, StateRuntime};
+use mecomp_storage::db::schemas::{
+    song::{self, Song, SongId},
+    Thing,
+};
 
 use crate::termination::Interrupted;
 
@@ -24,15 +36,27 @@ pub const TICK_RATE: Duration = Duration::from_millis(100);
 #[allow(clippy::module_name_repetitions)]
 pub struct AudioState {
     state_tx: UnboundedSender<StateAudio>,
+    /// the songs in the library, used to predict the outcome of adding songs to the queue.
+    library_songs: watch::Receiver<Box<[Song]>>,
 }
 
 impl AudioState {
     /// create a new audio state store, and return the receiver for listening to state updates.
+    ///
+    /// `library_songs` is the songs in the library (see [`LibraryState::songs`](super::library::LibraryState::songs)).
     #[must_use]
-    pub fn new() -> (Self, UnboundedReceiver<StateAudio>) {
+    pub fn new(
+        library_songs: watch::Receiver<Box<[Song]>>,
+    ) -> (Self, UnboundedReceiver<StateAudio>) {
         let (state_tx, state_rx) = unbounded_channel::<StateAudio>();
 
-        (Self { state_tx }, state_rx)
+        (
+            Self {
+                state_tx,
+                library_songs,
+            },
+            state_rx,
+        )
     }
 
     /// a loop that updates the audio state every tick.
@@ -54,11 +78,22 @@ impl AudioState {
         // the ticker
         let mut ticker = tokio::time::interval(TICK_RATE);
 
+        // the optimistic state, if there is one, is reconciled with the states we get from the daemon
+        // until the daemon confirms or rejects it
+        let mut optimistic: Option<OptimisticState> = None;
+
         let result = loop {
             tokio::select! {
                 // Handle the actions coming from the UI
                 // and process them to do async operations
                 Some(action) = action_rx.recv() => {
+                    // predict what the state will be after the action, and show that immediately
+                    let predicted =
+                        OptimisticState::predict(&state, &action, &self.library_songs.borrow());
+                    if let Some(predicted) = predicted {
+                        self.state_tx.send(predicted.state.clone())?;
+                        optimistic = Some(predicted);
+                    }
                     self.handle_action(daemon.clone(), action).await?;
                 },
                 // Tick to terminate the select every N milliseconds
@@ -70,6 +105,9 @@ impl AudioState {
             }
 
             state = get_state(daemon.clone()).await?;
+            if let Some(prediction) = optimistic.take() {
+                (state, optimistic) = prediction.reconcile(state);
+            }
             self.state_tx.send(state.clone())?;
         };
 
@@ -90,6 +128,178 @@ impl AudioState {
     }
 }
 
+/// A prediction of what the audio state will be after the daemon processes a queue mutation.
+///
+/// Only the actions whose outcome can be predicted from the current state are applied optimistically:
+/// skipping forward or backward, setting the queue position, removing songs from the queue,
+/// and adding songs to the queue (if they're all songs we know about, see [`OptimisticState::predict`]).
+///
+/// The prediction is shown until the daemon's state confirms or rejects it, see [`OptimisticState::reconcile`].
+#[derive(Debug, Clone)]
+pub struct OptimisticState {
+    /// the predicted state.
+    state: StateAudio,
+    /// the state before the action, which the daemon reports until it has processed the action.
+    previous: StateAudio,
+    /// when the prediction was made.
+    predicted_at: Instant,
+}
+
+impl OptimisticState {
+    /// How long to wait for the daemon to process the action before giving up on the prediction.
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Predict the state after the given action is processed,
+    /// returns `None` if the outcome of the action can't be predicted.
+    ///
+    /// The daemon resolves the things being added to the queue into songs, so adding to the queue is only predicted
+    /// if everything being added is a song in `library` (and, if nothing is playing, the queue is empty).
+    #[must_use]
+    pub fn predict(state: &StateAudio, action: &AudioAction, library: &[Song]) -> Option<Self> {
+        let previous = state.clone();
+        let mut state = state.clone();
+        let len = state.queue.len();
+
+        let new_position = match action {
+            AudioAction::Playback(PlaybackAction::Next) => match state.queue_position {
+                Some(position) if position + 1 < len => Some(position + 1),
+                Some(_) if state.repeat_mode.is_none() => None,
+                Some(_) => Some(0),
+                None if len > 0 => Some(0),
+                None => None,
+            },
+            AudioAction::Playback(PlaybackAction::Previous) => state
+                .queue_position
+                .and_then(|position| position.checked_sub(1)),
+            AudioAction::Queue(QueueAction::SetPosition(index)) if *index < len => Some(*index),
+            AudioAction::Queue(QueueAction::Remove(index)) if *index < len => {
+                let mut queue = state.queue.into_vec();
+                queue.remove(*index);
+                state.queue = queue.into_boxed_slice();
+
+                match state.queue_position {
+                    Some(_) if state.queue.is_empty() => None,
+                    Some(position) if position > *index || (position == *index && position > 0) => {
+                        Some(position - 1)
+                    }
+                    position => position,
+                }
+            }
+            AudioAction::Queue(QueueAction::Add(ids)) => {
+                let songs = lookup_songs(library, ids).filter(|songs| !songs.is_empty())?;
+                // if nothing is playing, the daemon starts playing the next song in the queue,
+                // which is only predictable if that's the first song being added
+                if state.current_song.is_none() && len > 0 {
+                    return None;
+                }
+                let mut queue = state.queue.into_vec();
+                queue.extend(songs);
+                state.queue = queue.into_boxed_slice();
+
+                if state.current_song.is_none() {
+                    state.paused = false;
+                    Some(0)
+                } else {
+                    state.queue_position
+                }
+            }
+            _ => return None,
+        };
+
+        let new_song = new_position.and_then(|position| state.queue.get(position).cloned());
+        if new_song != state.current_song {
+            // the song changed, so playback will start from the beginning
+            state.runtime = new_song.as_ref().map(|song| StateRuntime {
+                seek_position: Duration::from_secs(0),
+                seek_percent: Percent::new(0.0),
+                duration: song.runtime,
+            });
+        }
+        if new_song.is_none() {
+            state.paused = true;
+        }
+        state.queue_position = new_position;
+        state.current_song = new_song;
+
+        Some(Self {
+            state,
+            previous,
+            predicted_at: Instant::now(),
+        })
+    }
+
+    /// Reconcile the optimistic state with the true state from the daemon,
+    /// returning the state to show, and the prediction if it should be kept for the next reconciliation.
+    ///
+    /// - if the daemon's state matches the prediction, the prediction is confirmed and the daemon's state is shown.
+    /// - if the daemon's state still matches the state before the action, the daemon hasn't caught up yet,
+    ///   so the prediction is shown (on top of the daemon's state) and kept, unless it's older than [`Self::TIMEOUT`].
+    /// - otherwise, the prediction was wrong, so it's discarded and the daemon's state is shown.
+    #[must_use]
+    pub fn reconcile(self, actual: StateAudio) -> (StateAudio, Option<Self>) {
+        let predicted = predicted_fields(&self.state);
+        if predicted_fields(&actual) == predicted
+            || predicted_fields(&actual) != predicted_fields(&self.previous)
+            || self.predicted_at.elapsed() > Self::TIMEOUT
+        {
+            return (actual, None);
+        }
+
+        let state = StateAudio {
+            queue: self.state.queue.clone(),
+            queue_position: self.state.queue_position,
+            current_song: self.state.current_song.clone(),
+            paused: self.state.paused,
+            // the runtime is taken from the daemon (since it changes every tick), unless the song is about to change
+            runtime: if self.state.current_song == actual.current_song {
+                actual.runtime
+            } else {
+                self.state.runtime
+            },
+            ..actual
+        };
+        (state, Some(self))
+    }
+}
+
+/// The parts of the audio state that [`OptimisticState::predict`] changes, with songs identified by their ids.
+fn predicted_fields(state: &StateAudio) -> (Vec<&SongId>, Option<usize>, Option<&SongId>, bool) {
+    (
+        state.queue.iter().map(|song| &song.id).collect(),
+        state.queue_position,
+        state.current_song.as_ref().map(|song| &song.id),
+        state.paused,
+    )
+}
+
+/// Find the songs that the daemon will add to the queue for the given ids (without duplicates, like the daemon),
+/// returns `None` if any of the ids isn't a song in `library`.
+fn lookup_songs(library: &[Song], ids: &[Thing]) -> Option<Vec<Song>> {
+    if ids.iter().any(|id| id.tb != song::TABLE_NAME) {
+        return None;
+    }
+    // the ids are compared by their string representation, so make sure they're formatted the same way as the songs' ids
+    let ids = ids
+        .iter()
+        .map(|id| SongId::from(id.clone()).to_string())
+        .collect::<Vec<_>>();
+
+    let wanted = ids.iter().cloned().collect::<HashSet<_>>();
+    let found = library
+        .iter()
+        .filter_map(|song| {
+            let id = song.id.to_string();
+            wanted.contains(&id).then_some((id, song))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut added = HashSet::new();
+    ids.into_iter()
+        .filter(|id| added.insert(id.clone()))
+        .map(|id| found.get(&id).map(|&song| song.clone()))
+        .collect()
+}
+
 /// get the audio state from the daemon.
 async fn get_state(daemon: Arc<MusicPlayerClient>) -> anyhow::Result<StateAudio> {
     let ctx = tarpc::context::current();
@@ -117,6 +327,15 @@ async fn handle_playback(
             daemon.playback_volume_down(ctx, amount).await?;
         }
         PlaybackAction::ToggleMute => daemon.playback_volume_toggle_mute(ctx).await?,
+        PlaybackAction::SetEqualizer(bands) => daemon.playback_set_equalizer(ctx, bands).await?,
+        PlaybackAction::EqualizerPreset(index) => {
+            let presets = daemon.playback_equalizer_presets(ctx).await?;
+            if let Some(name) = presets.get(index % presets.len().max(1)) {
+                daemon
+                    .playback_equalizer_preset(tarpc::context::current(), name.clone())
+                    .await?;
+            }
+        }
     }
 
     Ok(())
@@ -132,11 +351,257 @@ async fn handle_queue(daemon: Arc<MusicPlayerClient>, action: QueueAction) -> an
             #[allow(clippy::range_plus_one)]
             daemon.queue_remove_range(ctx, index..index + 1).await?;
         }
+        QueueAction::Move(from, to) => daemon.queue_move(ctx, from, to).await?,
         QueueAction::SetPosition(index) => daemon.queue_set_index(ctx, index).await?,
         QueueAction::Shuffle => daemon.playback_shuffle(ctx).await?,
         QueueAction::Clear => daemon.playback_clear(ctx).await?,
         QueueAction::SetRepeatMode(mode) => daemon.playback_repeat(ctx, mode).await?,
+        QueueAction::Dedup => daemon.queue_dedup(ctx).await?,
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mecomp_core::state::{RepeatMode, ReplayGainMode};
+    use one_or_many::OneOrMany;
+    use pretty_assertions::assert_eq;
+    use rstest::{fixture, rstest};
+
+    fn song(title: &str) -> Song {
+        Song {
+            id: Song::generate_id(),
+            title: title.into(),
+            artist: OneOrMany::One("Test Artist".into()),
+            album_artist: OneOrMany::One("Test Artist".into()),
+            album: "Test Album".into(),
+            genre: OneOrMany::One("Test Genre".into()),
+            runtime: Duration::from_secs(180),
+            track: Some(0),
+            disc: Some(0),
+            release_year: Some(2021),
+            extension: "mp3".into(),
+            path: format!("{title}.mp3").into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
+        }
+    }
+
+    #[fixture]
+    fn state() -> StateAudio {
+        let queue = vec![song("0"), song("1"), song("2")];
+        StateAudio {
+            current_song: Some(queue[1].clone()),
+            queue: queue.into_boxed_slice(),
+            queue_position: Some(1),
+            repeat_mode: RepeatMode::None,
+            runtime: Some(StateRuntime {
+                seek_position: Duration::from_secs(60),
+                seek_percent: Percent::new(100.0 / 3.0),
+                duration: Duration::from_secs(180),
+            }),
+            paused: false,
+            muted: false,
+            volume: 1.0,
+            gain_db: 0.0,
+            fade_in_progress: false,
+            crossfade_duration: Duration::ZERO,
+            replaygain_mode: ReplayGainMode::Off,
+            playback_speed: 1.0,
+            equalizer: Box::new([]),
+            history: Box::new([]),
+        }
+    }
+
+    #[rstest]
+    #[case::next(AudioAction::Playback(PlaybackAction::Next), Some(2), 3)]
+    #[case::previous(AudioAction::Playback(PlaybackAction::Previous), Some(0), 3)]
+    #[case::set_position(AudioAction::Queue(QueueAction::SetPosition(0)), Some(0), 3)]
+    #[case::remove_before(AudioAction::Queue(QueueAction::Remove(0)), Some(0), 2)]
+    #[case::remove_current(AudioAction::Queue(QueueAction::Remove(1)), Some(0), 2)]
+    #[case::remove_after(AudioAction::Queue(QueueAction::Remove(2)), Some(1), 2)]
+    fn test_predict(
+        state: StateAudio,
+        #[case] action: AudioAction,
+        #[case] expected_position: Option<usize>,
+        #[case] expected_len: usize,
+    ) {
+        let predicted = OptimisticState::predict(&state, &action, &[])
+            .unwrap()
+            .state;
+
+        assert_eq!(predicted.queue_position, expected_position);
+        assert_eq!(predicted.queue.len(), expected_len);
+        assert_eq!(
+            predicted.current_song,
+            expected_position.map(|i| predicted.queue[i].clone())
+        );
+    }
+
+    #[rstest]
+    fn test_predict_skip_past_end(mut state: StateAudio) {
+        state.queue_position = Some(2);
+        state.current_song = Some(state.queue[2].clone());
+
+        let action = AudioAction::Playback(PlaybackAction::Next);
+        let predicted = OptimisticState::predict(&state, &action, &[])
+            .unwrap()
+            .state;
+        assert_eq!(predicted.queue_position, None);
+        assert_eq!(predicted.current_song, None);
+        assert!(predicted.paused);
+
+        state.repeat_mode = RepeatMode::Continuous;
+        let predicted = OptimisticState::predict(&state, &action, &[])
+            .unwrap()
+            .state;
+        assert_eq!(predicted.queue_position, Some(0));
+        assert_eq!(predicted.current_song, Some(state.queue[0].clone()));
+    }
+
+    #[rstest]
+    #[case::toggle(AudioAction::Playback(PlaybackAction::Toggle))]
+    #[case::add(AudioAction::Queue(QueueAction::Add(vec![])))]
+    #[case::shuffle(AudioAction::Queue(QueueAction::Shuffle))]
+    #[case::dedup(AudioAction::Queue(QueueAction::Dedup))]
+    #[case::move_song(AudioAction::Queue(QueueAction::Move(0, 2)))]
+    #[case::out_of_bounds(AudioAction::Queue(QueueAction::SetPosition(10)))]
+    fn test_predict_unpredictable(state: StateAudio, #[case] action: AudioAction) {
+        assert!(OptimisticState::predict(&state, &action, &[]).is_none());
+    }
+
+    #[rstest]
+    fn test_predict_add(state: StateAudio) {
+        let library = [song("3"), song("4")];
+        let ids = vec![
+            library[1].id.clone().into(),
+            library[0].id.clone().into(),
+            library[1].id.clone().into(),
+        ];
+        let action = AudioAction::Queue(QueueAction::Add(ids));
+
+        // the songs are appended (without duplicates), and the current song doesn't change
+        let predicted = OptimisticState::predict(&state, &action, &library)
+            .unwrap()
+            .state;
+        assert_eq!(
+            predicted.queue.as_ref(),
+            [
+                state.queue[0].clone(),
+                state.queue[1].clone(),
+                state.queue[2].clone(),
+                library[1].clone(),
+                library[0].clone(),
+            ]
+        );
+        assert_eq!(predicted.queue_position, state.queue_position);
+        assert_eq!(predicted.current_song, state.current_song);
+        assert_eq!(predicted.runtime, state.runtime);
+
+        // adding to an empty queue starts playing the first song
+        let empty = StateAudio {
+            queue: Box::new([]),
+            queue_position: None,
+            current_song: None,
+            runtime: None,
+            paused: true,
+            ..state.clone()
+        };
+        let predicted = OptimisticState::predict(&empty, &action, &library)
+            .unwrap()
+            .state;
+        assert_eq!(predicted.queue_position, Some(0));
+        assert_eq!(predicted.current_song, Some(library[1].clone()));
+        assert!(!predicted.paused);
+        assert_eq!(
+            predicted.runtime.map(|runtime| runtime.seek_position),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[rstest]
+    fn test_predict_add_unpredictable(state: StateAudio) {
+        let library = [song("3")];
+
+        // songs that aren't in the library
+        let action = AudioAction::Queue(QueueAction::Add(vec![song("4").id.into()]));
+        assert!(OptimisticState::predict(&state, &action, &library).is_none());
+
+        // things that aren't songs
+        let album = Thing::from(("album", crate::test_utils::item_id()));
+        let action =
+            AudioAction::Queue(QueueAction::Add(vec![library[0].id.clone().into(), album]));
+        assert!(OptimisticState::predict(&state, &action, &library).is_none());
+
+        // nothing is playing, but the queue isn't empty, so the daemon decides what to play
+        let action = AudioAction::Queue(QueueAction::Add(vec![library[0].id.clone().into()]));
+        let stopped = StateAudio {
+            queue_position: None,
+            current_song: None,
+            ..state
+        };
+        assert!(OptimisticState::predict(&stopped, &action, &library).is_none());
+    }
+
+    #[rstest]
+    fn test_reconcile(state: StateAudio) {
+        let action = AudioAction::Playback(PlaybackAction::Next);
+        let optimistic = OptimisticState::predict(&state, &action, &[]).unwrap();
+
+        // the daemon hasn't processed the skip yet, so the prediction is shown and kept
+        let (reconciled, pending) = optimistic.clone().reconcile(state.clone());
+        assert_eq!(reconciled.queue_position, Some(2));
+        assert_eq!(reconciled.current_song, Some(state.queue[2].clone()));
+        assert_eq!(reconciled.runtime, optimistic.state.runtime);
+        assert!(pending.is_some());
+
+        // the daemon agrees with the prediction, so it's confirmed
+        let actual = StateAudio {
+            queue_position: Some(2),
+            current_song: Some(state.queue[2].clone()),
+            runtime: None,
+            ..state.clone()
+        };
+        let (reconciled, pending) = optimistic.clone().reconcile(actual.clone());
+        assert_eq!(predicted_fields(&reconciled), predicted_fields(&actual));
+        assert_eq!(reconciled.runtime, actual.runtime);
+        assert!(pending.is_none());
+
+        // the daemon disagrees with the prediction (e.g. someone else cleared the queue), so it's discarded
+        let actual = StateAudio {
+            queue: Box::new([]),
+            queue_position: None,
+            current_song: None,
+            runtime: None,
+            paused: true,
+            ..state.clone()
+        };
+        let (reconciled, pending) = optimistic.reconcile(actual.clone());
+        assert_eq!(predicted_fields(&reconciled), predicted_fields(&actual));
+        assert_eq!(reconciled.runtime, actual.runtime);
+        assert!(pending.is_none());
+    }
+
+    #[rstest]
+    fn test_reconcile_timeout(state: StateAudio) {
+        let action = AudioAction::Playback(PlaybackAction::Next);
+        let mut optimistic = OptimisticState::predict(&state, &action, &[]).unwrap();
+        optimistic.predicted_at = Instant::now()
+            .checked_sub(OptimisticState::TIMEOUT * 2)
+            .unwrap();
+
+        // the daemon never processed the skip, so the prediction is given up on
+        let (reconciled, pending) = optimistic.reconcile(state.clone());
+        assert_eq!(predicted_fields(&reconciled), predicted_fields(&state));
+        assert_eq!(reconciled.runtime, state.runtime);
+        assert!(pending.is_none());
+    }
+}
@@ -7,9 +7,14 @@ use std::{sync::Arc, time::Duration};
 use tokio::sync::{
     broadcast,
     mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    watch,
 };
 
-use mecomp_core::{rpc::MusicPlayerClient, state::library::LibraryFull};
+use mecomp_core::{
+    rpc::{MusicPlayerClient, PlaylistMergeRequest, ReclusterRequest},
+    state::library::{LibraryFull, ScanProgress},
+};
+use mecomp_storage::db::schemas::song::Song;
 
 use crate::termination::Interrupted;
 
@@ -20,15 +25,45 @@ use super::action::LibraryAction;
 #[allow(clippy::module_name_repetitions)]
 pub struct LibraryState {
     state_tx: UnboundedSender<LibraryFull>,
+    scan_progress_tx: UnboundedSender<Option<ScanProgress>>,
+    songs_tx: watch::Sender<Box<[Song]>>,
 }
 
 impl LibraryState {
-    /// create a new library state store, and return the receiver for listening to state updates.
+    /// create a new library state store, and return the receivers for listening to state updates,
+    /// and to the progress of rescans.
     #[must_use]
-    pub fn new() -> (Self, UnboundedReceiver<LibraryFull>) {
+    pub fn new() -> (
+        Self,
+        UnboundedReceiver<LibraryFull>,
+        UnboundedReceiver<Option<ScanProgress>>,
+    ) {
         let (state_tx, state_rx) = unbounded_channel::<LibraryFull>();
+        let (scan_progress_tx, scan_progress_rx) = unbounded_channel::<Option<ScanProgress>>();
+        let (songs_tx, _) = watch::channel(Box::default());
+
+        (
+            Self {
+                state_tx,
+                scan_progress_tx,
+                songs_tx,
+            },
+            state_rx,
+            scan_progress_rx,
+        )
+    }
 
-        (Self { state_tx }, state_rx)
+    /// subscribe to the songs in the library, which are updated whenever the library state is.
+    #[must_use]
+    pub fn songs(&self) -> watch::Receiver<Box<[Song]>> {
+        self.songs_tx.subscribe()
+    }
+
+    /// send the library state to the UI, and update the songs in the library.
+    fn publish(&self, state: &LibraryFull) -> anyhow::Result<()> {
+        self.songs_tx.send_replace(state.songs.clone());
+        self.state_tx.send(state.clone())?;
+        Ok(())
     }
 
     /// a loop that updates the library state every tick.
@@ -47,7 +82,7 @@ impl LibraryState {
         let mut state = get_library(daemon.clone()).await?;
 
         // the initial state once
-        self.state_tx.send(state.clone())?;
+        self.publish(&state)?;
 
         let result = loop {
             tokio::select! {
@@ -56,25 +91,25 @@ impl LibraryState {
                 Some(action) = action_rx.recv() => {
                     match action {
                         LibraryAction::Rescan => {
-                            state = rescan_library(daemon.clone()).await?;
-                            self.state_tx.send(state.clone())?;
+                            state = rescan_library(daemon.clone(), &self.scan_progress_tx).await?;
+                            self.publish(&state)?;
                         }
                         LibraryAction::Update => {
                             state = get_library(daemon.clone()).await?;
-                            self.state_tx.send(state.clone())?;
+                            self.publish(&state)?;
                         }
                         LibraryAction::Analyze => {
                             analyze_library(daemon.clone()).await?;
                         }
                         LibraryAction::Recluster => {
                             state = recluster_library(daemon.clone()).await?;
-                            self.state_tx.send(state.clone())?;
+                            self.publish(&state)?;
                         }
                         LibraryAction::CreatePlaylist(name) => {
                             let ctx = tarpc::context::current();
                             daemon.playlist_get_or_create(ctx, name).await??;
                             state = get_library(daemon.clone()).await?;
-                            self.state_tx.send(state.clone())?;
+                            self.publish(&state)?;
                         }
                         LibraryAction::RemovePlaylist(id) => {
                             debug_assert_eq!(
@@ -84,7 +119,7 @@ impl LibraryState {
                             let ctx = tarpc::context::current();
                             daemon.playlist_remove(ctx, id).await??;
                             state = get_library(daemon.clone()).await?;
-                            self.state_tx.send(state.clone())?;
+                            self.publish(&state)?;
                         }
                         LibraryAction::RemoveSongsFromPlaylist(playlist, songs) => {
                             debug_assert_eq!(
@@ -108,7 +143,46 @@ impl LibraryState {
                             let playlist = daemon.playlist_get_or_create(ctx, name).await??;
                             daemon.playlist_add_list(ctx, playlist, things).await??;
                             state = get_library(daemon.clone()).await?;
-                            self.state_tx.send(state.clone())?;
+                            self.publish(&state)?;
+                        }
+                        LibraryAction::SaveQueueAsPlaylist(name) => {
+                            let ctx = tarpc::context::current();
+                            daemon.queue_save_as_playlist(ctx, name).await??;
+                            state = get_library(daemon.clone()).await?;
+                            self.publish(&state)?;
+                        }
+                        LibraryAction::MergePlaylists(source_ids, name) => {
+                            debug_assert!(source_ids.iter().all(|id| id.tb == mecomp_storage::db::schemas::playlist::TABLE_NAME));
+                            let ctx = tarpc::context::current();
+                            daemon
+                                .playlist_merge(ctx, PlaylistMergeRequest { source_ids, name })
+                                .await??;
+                            state = get_library(daemon.clone()).await?;
+                            self.publish(&state)?;
+                        }
+                        LibraryAction::RenameCollection(id, name) => {
+                            debug_assert_eq!(
+                                id.tb,
+                                mecomp_storage::db::schemas::collection::TABLE_NAME
+                            );
+                            let ctx = tarpc::context::current();
+                            daemon.collection_rename(ctx, id, name).await??;
+                            state = get_library(daemon.clone()).await?;
+                            self.publish(&state)?;
+                        }
+                        LibraryAction::MergeCollections(source, target) => {
+                            debug_assert_eq!(
+                                source.tb,
+                                mecomp_storage::db::schemas::collection::TABLE_NAME
+                            );
+                            debug_assert_eq!(
+                                target.tb,
+                                mecomp_storage::db::schemas::collection::TABLE_NAME
+                            );
+                            let ctx = tarpc::context::current();
+                            daemon.collection_merge(ctx, source, target).await??;
+                            state = get_library(daemon.clone()).await?;
+                            self.publish(&state)?;
                         }
                     }
                 },
@@ -128,8 +202,11 @@ async fn get_library(daemon: Arc<MusicPlayerClient>) -> anyhow::Result<LibraryFu
     Ok(daemon.library_full(ctx).await??)
 }
 
-/// initiate a rescan and wait until it's done
-async fn rescan_library(daemon: Arc<MusicPlayerClient>) -> anyhow::Result<LibraryFull> {
+/// initiate a rescan and wait until it's done, sending its progress along the way
+async fn rescan_library(
+    daemon: Arc<MusicPlayerClient>,
+    scan_progress_tx: &UnboundedSender<Option<ScanProgress>>,
+) -> anyhow::Result<LibraryFull> {
     let ctx = tarpc::context::current();
 
     daemon.library_rescan(ctx).await??;
@@ -139,8 +216,13 @@ async fn rescan_library(daemon: Arc<MusicPlayerClient>) -> anyhow::Result<Librar
         .library_rescan_in_progress(tarpc::context::current())
         .await?
     {
+        let progress = daemon
+            .library_rescan_progress(tarpc::context::current())
+            .await?;
+        scan_progress_tx.send(progress)?;
         tokio::time::sleep(Duration::from_secs(1)).await;
     }
+    scan_progress_tx.send(None)?;
 
     // return the new library
     let ctx = tarpc::context::current();
@@ -168,7 +250,9 @@ async fn analyze_library(daemon: Arc<MusicPlayerClient>) -> anyhow::Result<()> {
 async fn recluster_library(daemon: Arc<MusicPlayerClient>) -> anyhow::Result<LibraryFull> {
     let ctx = tarpc::context::current();
 
-    daemon.library_recluster(ctx).await??;
+    daemon
+        .library_recluster(ctx, ReclusterRequest::default())
+        .await??;
 
     // wait for it to finish
     while daemon
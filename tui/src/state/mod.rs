@@ -3,7 +3,10 @@ use std::sync::Arc;
 use action::Action;
 use mecomp_core::{
     rpc::{MusicPlayerClient, SearchResult},
-    state::{library::LibraryFull, StateAudio},
+    state::{
+        library::{LibraryFull, ScanProgress},
+        StateAudio,
+    },
 };
 use tokio::sync::{
     broadcast,
@@ -48,6 +51,7 @@ pub struct Receivers {
     pub audio: UnboundedReceiver<StateAudio>,
     pub search: UnboundedReceiver<SearchResult>,
     pub library: UnboundedReceiver<LibraryFull>,
+    pub scan_progress: UnboundedReceiver<Option<ScanProgress>>,
     pub view: UnboundedReceiver<ActiveView>,
     pub popup: UnboundedReceiver<Option<PopupType>>,
     pub component: UnboundedReceiver<component::ActiveComponent>,
@@ -56,9 +60,9 @@ pub struct Receivers {
 impl Dispatcher {
     #[must_use]
     pub fn new() -> (Self, Receivers) {
-        let (audio, audio_rx) = audio::AudioState::new();
+        let (library, library_rx, scan_progress_rx) = library::LibraryState::new();
+        let (audio, audio_rx) = audio::AudioState::new(library.songs());
         let (search, search_rx) = search::SearchState::new();
-        let (library, library_rx) = library::LibraryState::new();
         let (view, view_rx) = view::ViewState::new();
         let (popup, popup_rx) = popup::PopupState::new();
         let (active_component, active_component_rx) = component::ComponentState::new();
@@ -75,6 +79,7 @@ impl Dispatcher {
             audio: audio_rx,
             search: search_rx,
             library: library_rx,
+            scan_progress: scan_progress_rx,
             view: view_rx,
             popup: popup_rx,
             component: active_component_rx,
@@ -53,7 +53,18 @@ impl SearchState {
                 // and process them to do async operations
                 Some(query) = action_rx.recv() => {
                     let ctx = tarpc::context::current();
-                    state = daemon.search(ctx, query, 100).await?;
+                    state = if let Some((min_bpm, max_bpm)) = parse_bpm_range(&query) {
+                        SearchResult {
+                            // if the library hasn't been analyzed, there are just no results
+                            songs: daemon
+                                .library_bpm_range(ctx, min_bpm, max_bpm, 100)
+                                .await?
+                                .unwrap_or_default(),
+                            ..Default::default()
+                        }
+                    } else {
+                        daemon.search(ctx, query, 100).await?
+                    };
                     self.state_tx.send(state.clone())?;
                 },
                 // Catch and handle interrupt signal to gracefully shutdown
@@ -66,3 +77,37 @@ impl SearchState {
         Ok(result)
     }
 }
+
+/// Parse a search query of the form "bpm:120-140" into a range of tempos (inclusive).
+///
+/// Returns `None` if the query isn't a BPM range, in which case it's a regular search.
+fn parse_bpm_range(query: &str) -> Option<(f64, f64)> {
+    let (prefix, range) = query.trim().split_once(':')?;
+    if !prefix.trim().eq_ignore_ascii_case("bpm") {
+        return None;
+    }
+    let (min, max) = range.split_once('-')?;
+    let min: f64 = min.trim().parse().ok()?;
+    let max: f64 = max.trim().parse().ok()?;
+    (min <= max).then_some((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("bpm:120-140", Some((120., 140.)))]
+    #[case(" BPM: 120 - 140 ", Some((120., 140.)))]
+    #[case("bpm:92.5-100", Some((92.5, 100.)))]
+    #[case("bpm:140-120", None)]
+    #[case("bpm:120", None)]
+    #[case("bpm:fast-faster", None)]
+    #[case("tempo:120-140", None)]
+    #[case("120-140", None)]
+    fn test_parse_bpm_range(#[case] query: &str, #[case] expected: Option<(f64, f64)>) {
+        assert_eq!(parse_bpm_range(query), expected);
+    }
+}
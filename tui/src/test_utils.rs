@@ -1,6 +1,14 @@
-use mecomp_core::{rpc::SearchResult, state::library::LibraryFull};
+use mecomp_core::{
+    rpc::SearchResult,
+    state::{library::LibraryFull, StateAudio},
+};
 use mecomp_storage::db::schemas::{
-    album::Album, artist::Artist, collection::Collection, playlist::Playlist, song::Song, Id, Thing,
+    album::Album,
+    artist::Artist,
+    collection::Collection,
+    playlist::Playlist,
+    song::{Song, SongBrief},
+    Id, Thing,
 };
 use one_or_many::OneOrMany;
 use ratatui::{backend::TestBackend, layout::Rect, Terminal};
@@ -71,6 +79,14 @@ pub fn state_with_everything() -> AppState {
         release_year: Some(2021),
         extension: "mp3".into(),
         path: "test.mp3".into(),
+        replaygain_track_gain: None,
+        replaygain_album_gain: None,
+        play_count: 0,
+        skip_count: 0,
+        last_played: None,
+        rating: None,
+        favorite: false,
+        bookmark_positions: Vec::new(),
     };
     let artist = Artist {
         id: artist_id.clone().into(),
@@ -78,6 +94,7 @@ pub fn state_with_everything() -> AppState {
         runtime: song.runtime,
         album_count: 1,
         song_count: 1,
+        biography: None,
     };
     let album = Album {
         id: album_id.clone().into(),
@@ -88,6 +105,7 @@ pub fn state_with_everything() -> AppState {
         song_count: 1,
         discs: 1,
         genre: song.genre.clone(),
+        description: None,
     };
     let collection = Collection {
         id: collection_id.clone().into(),
@@ -136,6 +154,7 @@ pub fn state_with_everything() -> AppState {
                 id: collection_id,
                 collection,
                 songs: vec![song.clone()].into_boxed_slice(),
+                stats: None,
             }),
             playlist: Some(PlaylistViewProps {
                 id: playlist_id,
@@ -146,6 +165,12 @@ pub fn state_with_everything() -> AppState {
                 count: 1,
                 songs: vec![song.clone()].into_boxed_slice(),
             }),
+            statistics: None,
+            lyrics: None,
+        },
+        audio: StateAudio {
+            history: vec![SongBrief::from(&song)].into_boxed_slice(),
+            ..Default::default()
         },
         search: SearchResult {
             songs: vec![song].into_boxed_slice(),
@@ -98,6 +98,16 @@ impl App {
         }
     }
 
+    /// Move the app with the given state, but only update components that need to be updated.
+    ///
+    /// in this case, that is the sidebar
+    pub fn move_with_scan_progress(self, state: &AppState) -> Self {
+        Self {
+            sidebar: self.sidebar.move_with_state(state),
+            ..self
+        }
+    }
+
     /// Move the app with the given state, but only update components that need to be updated.
     ///
     /// in this case, that is the content view
@@ -372,7 +382,9 @@ mod tests {
     use crossterm::event::{KeyModifiers, MediaKeyCode};
     use mecomp_core::{
         rpc::SearchResult,
-        state::{library::LibraryFull, Percent, RepeatMode, StateAudio, StateRuntime},
+        state::{
+            library::LibraryFull, Percent, RepeatMode, ReplayGainMode, StateAudio, StateRuntime,
+        },
     };
     use mecomp_storage::db::schemas::song::Song;
     use one_or_many::OneOrMany;
@@ -395,6 +407,14 @@ mod tests {
             release_year: Some(2021),
             extension: "mp3".into(),
             path: "test.mp3".into(),
+            replaygain_track_gain: None,
+            replaygain_album_gain: None,
+            play_count: 0,
+            skip_count: 0,
+            last_played: None,
+            rating: None,
+            favorite: false,
+            bookmark_positions: Vec::new(),
         }
     }
 
@@ -589,6 +609,13 @@ mod tests {
                 paused: true,
                 muted: false,
                 volume: 1.0,
+                gain_db: 0.0,
+                fade_in_progress: false,
+                crossfade_duration: Duration::ZERO,
+                replaygain_mode: ReplayGainMode::Off,
+                playback_speed: 1.0,
+                equalizer: Box::new([]),
+                history: Box::new([]),
             },
             ..state
         };
@@ -607,14 +634,17 @@ mod tests {
             is_playing,
             muted,
             volume,
+            gain_db,
             song_runtime,
             song_title,
             song_artist,
+            song_bookmarks,
         } = app.control_panel.props;
 
         assert_eq!(is_playing, !state.audio.paused);
         assert_eq!(muted, state.audio.muted);
         assert_eq!(volume, state.audio.volume);
+        assert_eq!(gain_db, state.audio.gain_db);
         assert_eq!(song_runtime, state.audio.runtime);
         assert_eq!(
             song_title,
@@ -634,6 +664,15 @@ mod tests {
                     .join(", ")
             })
         );
+        assert_eq!(
+            song_bookmarks,
+            state
+                .audio
+                .current_song
+                .as_ref()
+                .map(|song| song.bookmark_positions.clone())
+                .unwrap_or_default()
+        );
     }
 
     #[rstest]
@@ -10,6 +10,8 @@ use views::{
     album::{AlbumView, LibraryAlbumsView},
     artist::{ArtistView, LibraryArtistsView},
     collection::{CollectionView, LibraryCollectionsView},
+    history::HistoryView,
+    lyrics::LyricsView,
     none::NoneView,
     playlist::{LibraryPlaylistsView, PlaylistView},
     radio::RadioView,
@@ -43,6 +45,8 @@ pub struct ContentView {
     pub(crate) collections_view: LibraryCollectionsView,
     pub(crate) collection_view: CollectionView,
     pub(crate) radio_view: RadioView,
+    pub(crate) history_view: HistoryView,
+    pub(crate) lyrics_view: LyricsView,
     //
     pub(crate) action_tx: UnboundedSender<Action>,
 }
@@ -89,6 +93,10 @@ pub enum ActiveView {
     Collection(Id),
     /// A view of a radio
     Radio(Vec<Thing>, u32),
+    /// A view of the songs played this session.
+    History,
+    /// A view of the lyrics of the song that is currently playing.
+    Lyrics,
     // TODO: views for genres, settings, etc.
 }
 
@@ -121,6 +129,8 @@ impl ContentView {
             ActiveView::Collections => &self.collections_view,
             ActiveView::Collection(_) => &self.collection_view,
             ActiveView::Radio(_, _) => &self.radio_view,
+            ActiveView::History => &self.history_view,
+            ActiveView::Lyrics => &self.lyrics_view,
         }
     }
 
@@ -139,6 +149,8 @@ impl ContentView {
             ActiveView::Collections => &mut self.collections_view,
             ActiveView::Collection(_) => &mut self.collection_view,
             ActiveView::Radio(_, _) => &mut self.radio_view,
+            ActiveView::History => &mut self.history_view,
+            ActiveView::Lyrics => &mut self.lyrics_view,
         }
     }
 }
@@ -163,6 +175,8 @@ impl Component for ContentView {
             collections_view: LibraryCollectionsView::new(state, action_tx.clone()),
             collection_view: CollectionView::new(state, action_tx.clone()),
             radio_view: RadioView::new(state, action_tx.clone()),
+            history_view: HistoryView::new(state, action_tx.clone()),
+            lyrics_view: LyricsView::new(state, action_tx.clone()),
             action_tx,
         }
         .move_with_state(state)
@@ -187,6 +201,8 @@ impl Component for ContentView {
             collections_view: self.collections_view.move_with_state(state),
             collection_view: self.collection_view.move_with_state(state),
             radio_view: self.radio_view.move_with_state(state),
+            history_view: self.history_view.move_with_state(state),
+            lyrics_view: self.lyrics_view.move_with_state(state),
             action_tx: self.action_tx,
         }
     }
@@ -273,6 +289,8 @@ impl ComponentRender<RenderProps> for ContentView {
             ActiveView::Collections => self.collections_view.render(frame, props),
             ActiveView::Collection(_) => self.collection_view.render(frame, props),
             ActiveView::Radio(_, _) => self.radio_view.render(frame, props),
+            ActiveView::History => self.history_view.render(frame, props),
+            ActiveView::Lyrics => self.lyrics_view.render(frame, props),
         }
     }
 }
@@ -299,6 +317,8 @@ mod tests {
     #[case(ActiveView::Collections)]
     #[case(ActiveView::Collection(item_id()))]
     #[case(ActiveView::Radio(vec![Thing::from(("song", item_id()))], 1))]
+    #[case(ActiveView::History)]
+    #[case(ActiveView::Lyrics)]
     fn smoke_render(
         #[case] active_view: ActiveView,
         #[values(true, false)] is_focused: bool,
@@ -332,6 +352,8 @@ mod tests {
     #[case(ActiveView::Collections)]
     #[case(ActiveView::Collection(item_id()))]
     #[case(ActiveView::Radio(vec![Thing::from(("song", item_id()))], 1))]
+    #[case(ActiveView::History)]
+    #[case(ActiveView::Lyrics)]
     fn test_get_active_view_component(#[case] active_view: ActiveView) {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
         let state = AppState {
@@ -356,6 +378,8 @@ mod tests {
             ActiveView::Collections => assert_eq!(view.name(), "Library Collections View"),
             ActiveView::Collection(_) => assert_eq!(view.name(), "Collection View"),
             ActiveView::Radio(_, _) => assert_eq!(view.name(), "Radio"),
+            ActiveView::History => assert_eq!(view.name(), "History"),
+            ActiveView::Lyrics => assert_eq!(view.name(), "Lyrics"),
         }
 
         // assert that the two "get_active_view_component" methods return the same component
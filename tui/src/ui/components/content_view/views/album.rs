@@ -27,7 +27,7 @@ use crate::{
 
 use super::{
     checktree_utils::create_album_tree_leaf, generic::ItemView, sort_mode::AlbumSort,
-    traits::SortMode, AlbumViewProps, RADIO_SIZE,
+    traits::SortMode, AlbumViewProps, LibraryViewState, RADIO_SIZE,
 };
 
 #[allow(clippy::module_name_repetitions)]
@@ -71,7 +71,9 @@ impl Component for LibraryAlbumsView {
         let tree_state = if state.active_view == ActiveView::Albums {
             self.tree_state
         } else {
-            Mutex::new(CheckTreeState::default())
+            // keep the scroll position and selection around for when the user navigates back
+            let saved = LibraryViewState::from(&self.tree_state.into_inner().unwrap());
+            Mutex::new(saved.into())
         };
 
         Self {
@@ -290,6 +292,7 @@ mod sort_mode_tests {
                 runtime: Duration::from_secs(180),
                 discs: 1,
                 genre: OneOrMany::One("A".into()),
+                description: None,
             },
             Album {
                 id: Album::generate_id(),
@@ -300,6 +303,7 @@ mod sort_mode_tests {
                 runtime: Duration::from_secs(180),
                 discs: 1,
                 genre: OneOrMany::One("C".into()),
+                description: None,
             },
             Album {
                 id: Album::generate_id(),
@@ -310,6 +314,7 @@ mod sort_mode_tests {
                 runtime: Duration::from_secs(180),
                 discs: 1,
                 genre: OneOrMany::One("B".into()),
+                description: None,
             },
         ];
 
@@ -338,6 +343,7 @@ mod item_view_tests {
     };
     use anyhow::Result;
     use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
+    use mecomp_storage::db::schemas::song::Song;
     use pretty_assertions::assert_eq;
     use ratatui::buffer::Buffer;
 
@@ -480,6 +486,151 @@ mod item_view_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_render_multiple_discs() -> Result<()> {
+        let mut state = state_with_everything();
+        let props = state.additional_view_data.album.as_mut().unwrap();
+        let song = props.songs[0].clone();
+        props.album.discs = 2;
+        props.album.song_count = 2;
+        props.songs = vec![
+            Song {
+                disc: Some(1),
+                ..song.clone()
+            },
+            Song {
+                id: Song::generate_id(),
+                title: "Other Song".into(),
+                disc: Some(2),
+                ..song
+            },
+        ]
+        .into_boxed_slice();
+
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = AlbumView::new(&state, tx);
+        let (mut terminal, area) = setup_test_terminal(60, 12);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+
+        let _frame = terminal.draw(|frame| view.render(frame, props)).unwrap();
+
+        // expand the songs
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        view.handle_key_event(KeyEvent::from(KeyCode::Right));
+        let _frame = terminal.draw(|frame| view.render(frame, props)).unwrap();
+        // expand the first disc
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        view.handle_key_event(KeyEvent::from(KeyCode::Right));
+
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Album View────────────────────────────────────────────────┐",
+            "│                  Test Album Test Artist                  │",
+            "│    Release Year: 2021  Songs: 2  Duration: 00:03:00.00   │",
+            "│                                                          │",
+            "│q: add to queue | r: start radio | p: add to playlist─────│",
+            "│Performing operations on entire album─────────────────────│",
+            "│▶ Artists (1):                                            │",
+            "│▼ Songs (2):                                              │",
+            "│  ▼ Disc 1 (1):                                           │",
+            "│    ☐ Test Song Test Artist                               │",
+            "│  ▶ Disc 2 (1):                                           │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_with_description() -> Result<()> {
+        let mut state = state_with_everything();
+        state
+            .additional_view_data
+            .album
+            .as_mut()
+            .unwrap()
+            .album
+            .description = Some("Recorded live\nat the Test Venue,\nin 2021.".into());
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = AlbumView::new(&state, tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 14);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Album View────────────────────────────────────────────────┐",
+            "│                  Test Album Test Artist                  │",
+            "│    Release Year: 2021  Songs: 1  Duration: 00:03:00.00   │",
+            "│                                                          │",
+            "│Description (PgUp/PgDn: scroll)───────────────────────────│",
+            "│Recorded live                                             │",
+            "│at the Test Venue,                                        │",
+            "│in 2021.                                                  │",
+            "│                                                          │",
+            "│q: add to queue | r: start radio | p: add to playlist─────│",
+            "│Performing operations on entire album─────────────────────│",
+            "│▶ Artists (1):                                            │",
+            "│▶ Songs (1):                                              │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+        assert_buffer_eq(&buffer, &expected);
+
+        // scroll the description down, it can't be scrolled past its last line
+        view.handle_key_event(KeyEvent::from(KeyCode::PageDown));
+        view.handle_key_event(KeyEvent::from(KeyCode::PageDown));
+        view.handle_key_event(KeyEvent::from(KeyCode::PageDown));
+
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Album View────────────────────────────────────────────────┐",
+            "│                  Test Album Test Artist                  │",
+            "│    Release Year: 2021  Songs: 1  Duration: 00:03:00.00   │",
+            "│                                                          │",
+            "│Description (PgUp/PgDn: scroll)───────────────────────────│",
+            "│in 2021.                                                  │",
+            "│                                                          │",
+            "│                                                          │",
+            "│                                                          │",
+            "│q: add to queue | r: start radio | p: add to playlist─────│",
+            "│Performing operations on entire album─────────────────────│",
+            "│▶ Artists (1):                                            │",
+            "│▶ Songs (1):                                              │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+        assert_buffer_eq(&buffer, &expected);
+
+        // the scroll position is kept while looking at the same album
+        let view = view.move_with_state(&state);
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
     #[test]
     fn smoke_navigation() {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
@@ -742,6 +893,43 @@ mod library_view_tests {
         assert_eq!(view.props.albums, new_state.library.albums);
     }
 
+    #[test]
+    fn test_move_with_state_restores_position() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let state = AppState {
+            active_view: ActiveView::Albums,
+            ..state_with_everything()
+        };
+        let mut view = LibraryAlbumsView::new(&state, tx);
+
+        // need to render the view at least once to load the tree state
+        let (mut terminal, area) = setup_test_terminal(60, 9);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        terminal.draw(|frame| view.render(frame, props)).unwrap();
+
+        // select and check the album
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        view.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        let selected = view.tree_state.lock().unwrap().selected().to_vec();
+        assert!(!selected.is_empty());
+
+        // navigate away, then back
+        let view = view
+            .move_with_state(&AppState {
+                active_view: ActiveView::Songs,
+                ..state_with_everything()
+            })
+            .move_with_state(&state);
+
+        // the selection is restored, but the checked items are not
+        let tree_state = view.tree_state.lock().unwrap();
+        assert_eq!(tree_state.selected(), selected.as_slice());
+        assert!(tree_state.checked().is_empty());
+    }
+
     #[test]
     fn test_render() -> Result<()> {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
@@ -27,7 +27,7 @@ use crate::{
 
 use super::{
     checktree_utils::create_artist_tree_leaf, generic::ItemView, sort_mode::NameSort,
-    traits::SortMode, ArtistViewProps, RADIO_SIZE,
+    traits::SortMode, ArtistViewProps, LibraryViewState, RADIO_SIZE,
 };
 
 #[allow(clippy::module_name_repetitions)]
@@ -70,7 +70,9 @@ impl Component for LibraryArtistsView {
         let tree_state = if state.active_view == ActiveView::Artists {
             self.tree_state
         } else {
-            Mutex::new(CheckTreeState::default())
+            // keep the scroll position and selection around for when the user navigates back
+            let saved = LibraryViewState::from(&self.tree_state.into_inner().unwrap());
+            Mutex::new(saved.into())
         };
 
         Self {
@@ -284,6 +286,7 @@ mod sort_mode_tests {
                 song_count: 1,
                 album_count: 1,
                 runtime: Duration::from_secs(180),
+                biography: None,
             },
             Artist {
                 id: Artist::generate_id(),
@@ -291,6 +294,7 @@ mod sort_mode_tests {
                 song_count: 1,
                 album_count: 1,
                 runtime: Duration::from_secs(180),
+                biography: None,
             },
             Artist {
                 id: Artist::generate_id(),
@@ -298,6 +302,7 @@ mod sort_mode_tests {
                 song_count: 1,
                 album_count: 1,
                 runtime: Duration::from_secs(180),
+                biography: None,
             },
         ];
 
@@ -401,6 +406,51 @@ mod item_view_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_render_with_biography() -> Result<()> {
+        let mut state = state_with_everything();
+        state
+            .additional_view_data
+            .artist
+            .as_mut()
+            .unwrap()
+            .artist
+            .biography = Some("A test artist, from the test suite.".into());
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let view = ArtistView::new(&state, tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 14);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Artist View───────────────────────────────────────────────┐",
+            "│                        Test Artist                       │",
+            "│        Albums: 1  Songs: 1  Duration: 00:03:00.00        │",
+            "│                                                          │",
+            "│Biography (PgUp/PgDn: scroll)─────────────────────────────│",
+            "│A test artist, from the test suite.                       │",
+            "│                                                          │",
+            "│                                                          │",
+            "│                                                          │",
+            "│q: add to queue | r: start radio | p: add to playlist─────│",
+            "│Performing operations on entire artist────────────────────│",
+            "│▶ Albums (1):                                             │",
+            "│▶ Songs (1):                                              │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
     #[test]
     fn test_render_with_checked() -> Result<()> {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
@@ -16,11 +16,14 @@ use ratatui::{
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
-    state::action::{Action, ViewAction},
+    state::action::{Action, PopupAction, ViewAction},
     ui::{
         colors::{BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT, TEXT_NORMAL},
         components::{content_view::ActiveView, Component, ComponentRender, RenderProps},
-        widgets::tree::{state::CheckTreeState, CheckTree},
+        widgets::{
+            popups::PopupType,
+            tree::{state::CheckTreeState, CheckTree},
+        },
         AppState,
     },
 };
@@ -32,7 +35,7 @@ use super::{
     },
     sort_mode::{NameSort, SongSort},
     traits::SortMode,
-    CollectionViewProps,
+    CollectionViewProps, LibraryViewState,
 };
 
 #[allow(clippy::module_name_repetitions)]
@@ -236,6 +239,24 @@ impl ComponentRender<RenderProps> for CollectionView {
                             Style::default().italic(),
                         ),
                     ]),
+                    state
+                        .stats
+                        .as_ref()
+                        .map(|stats| {
+                            Line::from(vec![
+                                Span::raw("BPM: "),
+                                Span::styled(
+                                    format!("{:.1} ± {:.1}", stats.mean_bpm, stats.tempo_std),
+                                    Style::default().italic(),
+                                ),
+                                Span::raw("  Loudness: "),
+                                Span::styled(
+                                    format!("{:.1} dB", stats.mean_loudness),
+                                    Style::default().italic(),
+                                ),
+                            ])
+                        })
+                        .unwrap_or_default(),
                 ])
                 .alignment(Alignment::Center),
                 info_area,
@@ -359,7 +380,9 @@ impl Component for LibraryCollectionsView {
         let tree_state = if state.active_view == ActiveView::Collections {
             self.tree_state
         } else {
-            Mutex::new(CheckTreeState::default())
+            // keep the scroll position and selection around for when the user navigates back
+            let saved = LibraryViewState::from(&self.tree_state.into_inner().unwrap());
+            Mutex::new(saved.into())
         };
 
         Self {
@@ -414,6 +437,30 @@ impl Component for LibraryCollectionsView {
                     }
                 }
             }
+            // Rename the selected collection
+            KeyCode::Char('r') => {
+                let things = self.tree_state.lock().unwrap().get_selected_thing();
+
+                if let Some(thing) = things {
+                    self.action_tx
+                        .send(Action::Popup(PopupAction::Open(
+                            PopupType::RenameCollection(thing),
+                        )))
+                        .unwrap();
+                }
+            }
+            // Merge the selected collection into another one
+            KeyCode::Char('m') => {
+                let things = self.tree_state.lock().unwrap().get_selected_thing();
+
+                if let Some(thing) = things {
+                    self.action_tx
+                        .send(Action::Popup(PopupAction::Open(
+                            PopupType::MergeCollection(thing),
+                        )))
+                        .unwrap();
+                }
+            }
             // Change sort mode
             KeyCode::Char('s') => {
                 self.props.sort_mode = self.props.sort_mode.next();
@@ -494,6 +541,7 @@ impl ComponentRender<RenderProps> for LibraryCollectionsView {
         // draw additional border around content area to display additional instructions
         let border = Block::new()
             .borders(Borders::TOP)
+            .title_top("r: rename | m: merge into...")
             .border_style(border_style);
         frame.render_widget(&border, content_area);
         let content_area = border.inner(content_area);
@@ -592,6 +640,7 @@ mod item_view_tests {
     };
     use anyhow::Result;
     use crossterm::event::KeyModifiers;
+    use mecomp_core::state::library::CollectionAnalysisStats;
     use pretty_assertions::assert_eq;
     use ratatui::buffer::Buffer;
 
@@ -679,6 +728,51 @@ mod item_view_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_render_with_stats() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut state = state_with_everything();
+        state
+            .additional_view_data
+            .collection
+            .as_mut()
+            .unwrap()
+            .stats = Some(CollectionAnalysisStats {
+            analyzed_songs: 1,
+            mean_bpm: 120.,
+            tempo_std: 5.,
+            mean_loudness: -12.,
+            chroma_centroid: [0.; 10],
+        });
+        let view = CollectionView::new(&state, tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 9);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Collection View sorted by: Artist─────────────────────────┐",
+            "│                       Collection 0                       │",
+            "│              Songs: 1  Duration: 00:03:00.00             │",
+            "│           BPM: 120.0 ± 5.0  Loudness: -12.0 dB           │",
+            "│q: add to queue | p: add to playlist──────────────────────│",
+            "│Performing operations on entire collection────────────────│",
+            "│☐ Test Song Test Artist                                   │",
+            "│s/S: change sort──────────────────────────────────────────│",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
     #[test]
     fn test_render_with_checked() -> Result<()> {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
@@ -992,7 +1086,7 @@ mod library_view_tests {
             .clone();
         let expected = Buffer::with_lines([
             "┌Library Collections sorted by: Name───────────────────────┐",
-            "│──────────────────────────────────────────────────────────│",
+            "│r: rename | m: merge into...──────────────────────────────│",
             "│▪ Collection 0                                            │",
             "│                                                          │",
             "│                                                          │",
@@ -1051,6 +1145,24 @@ mod library_view_tests {
             rx.blocking_recv().unwrap(),
             Action::ActiveView(ViewAction::Set(ActiveView::Collection(item_id())))
         );
+
+        // rename
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('r')));
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::Popup(PopupAction::Open(PopupType::RenameCollection(
+                ("collection", item_id()).into()
+            )))
+        );
+
+        // merge
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('m')));
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::Popup(PopupAction::Open(PopupType::MergeCollection(
+                ("collection", item_id()).into()
+            )))
+        );
     }
 
     #[test]
@@ -1071,7 +1183,7 @@ mod library_view_tests {
             .clone();
         let expected = Buffer::with_lines([
             "┌Library Collections sorted by: Name───────────────────────┐",
-            "│──────────────────────────────────────────────────────────│",
+            "│r: rename | m: merge into...──────────────────────────────│",
             "│▪ Collection 0                                            │",
             "│                                                          │",
             "│                                                          │",
@@ -1,11 +1,11 @@
 use std::sync::Mutex;
 
-use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::{Alignment, Margin, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
     style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -27,6 +27,9 @@ use super::{
     ItemViewProps,
 };
 
+/// How many lines of an item's description are shown at once
+const DESCRIPTION_HEIGHT: u16 = 4;
+
 #[derive(Debug)]
 pub struct ItemView<Props> {
     /// Action Sender
@@ -35,6 +38,57 @@ pub struct ItemView<Props> {
     pub props: Option<Props>,
     /// tree state
     pub tree_state: Mutex<CheckTreeState<String>>,
+    /// how many lines the description is scrolled down by
+    description_scroll: u16,
+}
+
+impl<Props> ItemView<Props>
+where
+    Props: ItemViewProps,
+{
+    /// the number of lines in the item's description, before wrapping
+    fn description_line_count(&self) -> u16 {
+        self.props
+            .as_ref()
+            .and_then(ItemViewProps::description)
+            .map_or(0, |description| {
+                u16::try_from(description.lines().count()).unwrap_or(u16::MAX)
+            })
+    }
+
+    fn scroll_description_up(&mut self) {
+        self.description_scroll = self.description_scroll.saturating_sub(1);
+    }
+
+    fn scroll_description_down(&mut self) {
+        self.description_scroll = self
+            .description_scroll
+            .saturating_add(1)
+            .min(self.description_line_count().saturating_sub(1));
+    }
+}
+
+/// Split off the panel that shows the item's description from the top of `area`, if the item has a description.
+///
+/// Returns the description area (if any), and the rest of the area.
+fn split_description<Props: ItemViewProps>(props: &Props, area: Rect) -> (Option<Rect>, Rect) {
+    if props.description().is_none() {
+        return (None, area);
+    }
+
+    let [description_area, content_area] = *Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            // the description, plus its border
+            Constraint::Length(DESCRIPTION_HEIGHT + 1),
+            Constraint::Min(4),
+        ])
+        .split(area)
+    else {
+        panic!("Failed to split item view content area")
+    };
+
+    (Some(description_area), content_area)
 }
 
 impl<Props> Component for ItemView<Props>
@@ -51,6 +105,7 @@ where
             action_tx,
             props,
             tree_state,
+            description_scroll: 0,
         }
     }
 
@@ -59,10 +114,18 @@ where
         Self: Sized,
     {
         if let Some(props) = Props::retrieve(&state.additional_view_data) {
+            // only scroll the description back to the top if we're looking at a different item
+            let description_scroll =
+                if self.props.as_ref().map(ItemViewProps::id) == Some(props.id()) {
+                    self.description_scroll
+                } else {
+                    0
+                };
             Self {
                 action_tx: self.action_tx,
                 props: Some(props),
                 tree_state: self.tree_state,
+                description_scroll,
             }
         } else {
             self
@@ -91,6 +154,9 @@ where
             KeyCode::Char(' ') => {
                 self.tree_state.lock().unwrap().key_space();
             }
+            // scroll the description
+            KeyCode::PageUp => self.scroll_description_up(),
+            KeyCode::PageDown => self.scroll_description_down(),
             // Enter key opens selected view
             KeyCode::Enter => {
                 if self.tree_state.lock().unwrap().toggle_selected() {
@@ -141,6 +207,23 @@ where
         // adjust the area to account for the border
         let area = area.inner(Margin::new(1, 1));
         let [_, content_area] = Props::split_area(area);
+        let (description_area, content_area) =
+            self.props.as_ref().map_or((None, content_area), |props| {
+                split_description(props, content_area)
+            });
+
+        // scrolling over the description scrolls the description
+        if description_area
+            .is_some_and(|area| area.contains(Position::new(mouse.column, mouse.row)))
+        {
+            match mouse.kind {
+                MouseEventKind::ScrollUp => self.scroll_description_up(),
+                MouseEventKind::ScrollDown => self.scroll_description_down(),
+                _ => {}
+            }
+            return;
+        }
+
         let content_area = Rect {
             y: content_area.y + 2,
             height: content_area.height - 2,
@@ -184,6 +267,40 @@ where
             // render item info
             frame.render_widget(state.info_widget(), info_area);
 
+            // render the item's description, if it has one
+            let (description_area, content_area) = split_description(state, content_area);
+            if let (Some(description), Some(description_area)) =
+                (state.description(), description_area)
+            {
+                let border = Block::default()
+                    .borders(Borders::TOP)
+                    .title_top(format!(
+                        "{} (PgUp/PgDn: scroll)",
+                        Props::description_title()
+                    ))
+                    .border_style(border_style);
+                frame.render_widget(&border, description_area);
+                let description_area = border.inner(description_area);
+
+                frame.render_widget(
+                    Paragraph::new(description)
+                        .style(Style::default().fg(TEXT_NORMAL.into()))
+                        .wrap(Wrap { trim: false })
+                        .scroll((self.description_scroll, 0)),
+                    description_area,
+                );
+
+                // only show the scrollbar if the description doesn't fit
+                if self.description_line_count() > description_area.height {
+                    frame.render_stateful_widget(
+                        Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                        description_area,
+                        &mut ScrollbarState::new(usize::from(self.description_line_count()))
+                            .position(usize::from(self.description_scroll)),
+                    );
+                }
+            }
+
             // draw an additional border around the content area to display additional instructions
             let border = Block::default()
                 .borders(Borders::TOP)
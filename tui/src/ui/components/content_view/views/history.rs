@@ -0,0 +1,436 @@
+//! implementation of the playback history view
+
+use std::{collections::HashSet, sync::Mutex};
+
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use mecomp_storage::db::schemas::song::SongBrief;
+use ratatui::{
+    layout::{Alignment, Margin, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Scrollbar, ScrollbarOrientation},
+    Frame,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::checktree_utils::create_song_brief_tree_leaf;
+use crate::{
+    state::action::{Action, AudioAction, PopupAction, QueueAction, ViewAction},
+    ui::{
+        colors::{BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT, TEXT_NORMAL},
+        components::{Component, ComponentRender, RenderProps},
+        widgets::{
+            popups::PopupType,
+            tree::{state::CheckTreeState, CheckTree},
+        },
+        AppState,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Props {
+    /// the recently played songs, most recent first.
+    /// songs that were played more than once only show up at their most recent position.
+    pub(crate) songs: Box<[SongBrief]>,
+}
+
+impl From<&AppState> for Props {
+    fn from(value: &AppState) -> Self {
+        let mut seen = HashSet::new();
+        Self {
+            songs: value
+                .audio
+                .history
+                .iter()
+                .filter(|song| seen.insert(song.id.to_string()))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct HistoryView {
+    /// Action Sender
+    pub action_tx: UnboundedSender<Action>,
+    /// Mapped Props from state
+    pub(crate) props: Props,
+    /// tree state
+    tree_state: Mutex<CheckTreeState<String>>,
+}
+
+impl Component for HistoryView {
+    fn new(state: &AppState, action_tx: UnboundedSender<Action>) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            action_tx,
+            props: Props::from(state),
+            tree_state: Mutex::new(CheckTreeState::default()),
+        }
+    }
+
+    fn move_with_state(self, state: &AppState) -> Self
+    where
+        Self: Sized,
+    {
+        let props = Props::from(state);
+
+        // the audio state is refreshed every tick, so only reset the tree if the history actually changed
+        if props == self.props {
+            self
+        } else {
+            Self {
+                props,
+                tree_state: Mutex::new(CheckTreeState::default()),
+                ..self
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "History"
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            // arrow keys
+            KeyCode::PageUp => {
+                self.tree_state.lock().unwrap().select_relative(|current| {
+                    current.map_or(self.props.songs.len().saturating_sub(1), |c| {
+                        c.saturating_sub(10)
+                    })
+                });
+            }
+            KeyCode::Up => {
+                self.tree_state.lock().unwrap().key_up();
+            }
+            KeyCode::PageDown => {
+                self.tree_state
+                    .lock()
+                    .unwrap()
+                    .select_relative(|current| current.map_or(0, |c| c.saturating_add(10)));
+            }
+            KeyCode::Down => {
+                self.tree_state.lock().unwrap().key_down();
+            }
+            KeyCode::Left => {
+                self.tree_state.lock().unwrap().key_left();
+            }
+            KeyCode::Right => {
+                self.tree_state.lock().unwrap().key_right();
+            }
+            KeyCode::Char(' ') => {
+                self.tree_state.lock().unwrap().key_space();
+            }
+            // Enter key opens selected view
+            KeyCode::Enter => {
+                if self.tree_state.lock().unwrap().toggle_selected() {
+                    let things = self.tree_state.lock().unwrap().get_selected_thing();
+
+                    if let Some(thing) = things {
+                        self.action_tx
+                            .send(Action::ActiveView(ViewAction::Set(thing.into())))
+                            .unwrap();
+                    }
+                }
+            }
+            // if there are checked items, send to queue, otherwise send the whole history to queue
+            KeyCode::Char('q') => {
+                let things = self.tree_state.lock().unwrap().get_checked_things();
+                if !things.is_empty() {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Queue(QueueAction::Add(things))))
+                        .unwrap();
+                } else if !self.props.songs.is_empty() {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Queue(QueueAction::Add(
+                            self.props
+                                .songs
+                                .iter()
+                                .map(|s| s.id.clone().into())
+                                .collect(),
+                        ))))
+                        .expect("failed to send action");
+                }
+            }
+            // if there are checked items, add to playlist, otherwise add the whole history to playlist
+            KeyCode::Char('p') => {
+                let things = self.tree_state.lock().unwrap().get_checked_things();
+                if !things.is_empty() {
+                    self.action_tx
+                        .send(Action::Popup(PopupAction::Open(PopupType::Playlist(
+                            things,
+                        ))))
+                        .unwrap();
+                } else if !self.props.songs.is_empty() {
+                    self.action_tx
+                        .send(Action::Popup(PopupAction::Open(PopupType::Playlist(
+                            self.props
+                                .songs
+                                .iter()
+                                .map(|s| s.id.clone().into())
+                                .collect(),
+                        ))))
+                        .expect("failed to send action");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) {
+        // adjust the area to account for the border
+        let area = area.inner(Margin::new(1, 1));
+        let area = Rect {
+            y: area.y + 2,
+            height: area.height - 2,
+            ..area
+        };
+
+        let result = self
+            .tree_state
+            .lock()
+            .unwrap()
+            .handle_mouse_event(mouse, area);
+        if let Some(action) = result {
+            self.action_tx.send(action).unwrap();
+        }
+    }
+}
+
+impl ComponentRender<RenderProps> for HistoryView {
+    fn render_border(&self, frame: &mut Frame, props: RenderProps) -> RenderProps {
+        let border_style = if props.is_focused {
+            Style::default().fg(BORDER_FOCUSED.into())
+        } else {
+            Style::default().fg(BORDER_UNFOCUSED.into())
+        };
+
+        let border = Block::bordered()
+            .title_top(Line::from(vec![
+                Span::styled("History", Style::default().bold()),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{} songs", self.props.songs.len()),
+                    Style::default().italic(),
+                ),
+            ]))
+            .title_bottom(" \u{23CE} : Open | ←/↑/↓/→: Navigate | \u{2423} Check")
+            .border_style(border_style);
+        frame.render_widget(&border, props.area);
+        let content_area = border.inner(props.area);
+
+        // create an additional border around the content area to display additional instructions
+        let border = Block::default()
+            .borders(Borders::TOP)
+            .title_top("q: add to queue | p: add to playlist")
+            .border_style(border_style);
+        frame.render_widget(&border, content_area);
+        let content_area = border.inner(content_area);
+
+        // draw an additional border around the content area to indicate whether operations will be performed on the entire item, or just the checked items
+        let border = Block::default()
+            .borders(Borders::TOP)
+            .title_top(Line::from(vec![
+                Span::raw("Performing operations on "),
+                Span::raw(
+                    if self
+                        .tree_state
+                        .lock()
+                        .unwrap()
+                        .get_checked_things()
+                        .is_empty()
+                    {
+                        "entire history"
+                    } else {
+                        "checked items"
+                    },
+                )
+                .fg(TEXT_HIGHLIGHT),
+            ]))
+            .italic()
+            .border_style(border_style);
+        frame.render_widget(&border, content_area);
+        let area = border.inner(content_area);
+
+        RenderProps { area, ..props }
+    }
+
+    fn render_content(&self, frame: &mut Frame, props: RenderProps) {
+        if self.props.songs.is_empty() {
+            frame.render_widget(
+                Line::from("Nothing played yet")
+                    .style(Style::default().fg(TEXT_NORMAL.into()))
+                    .alignment(Alignment::Center),
+                props.area,
+            );
+            return;
+        }
+
+        // create a tree to hold the history
+        let items = self
+            .props
+            .songs
+            .iter()
+            .map(create_song_brief_tree_leaf)
+            .collect::<Vec<_>>();
+
+        // render the history
+        frame.render_stateful_widget(
+            CheckTree::new(&items)
+                .unwrap()
+                .highlight_style(Style::default().fg(TEXT_HIGHLIGHT.into()).bold())
+                .experimental_scrollbar(Some(Scrollbar::new(ScrollbarOrientation::VerticalRight))),
+            props.area,
+            &mut self.tree_state.lock().unwrap(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{assert_buffer_eq, item_id, setup_test_terminal, state_with_everything},
+        ui::components::content_view::ActiveView,
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use ratatui::buffer::Buffer;
+
+    #[test]
+    fn test_new() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let state = state_with_everything();
+        let view = HistoryView::new(&state, tx);
+
+        assert_eq!(view.name(), "History");
+        assert_eq!(view.props.songs, state.audio.history);
+    }
+
+    #[test]
+    fn test_props_dedup() {
+        let mut state = state_with_everything();
+        let song = state.audio.history[0].clone();
+        state.audio.history = vec![song.clone(), song.clone(), song].into_boxed_slice();
+
+        let props = Props::from(&state);
+
+        assert_eq!(props.songs.len(), 1);
+    }
+
+    #[test]
+    fn test_render_empty() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let view = HistoryView::new(&AppState::default(), tx);
+
+        let (mut terminal, area) = setup_test_terminal(50, 6);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌History 0 songs─────────────────────────────────┐",
+            "│q: add to queue | p: add to playlist────────────│",
+            "│Performing operations on entire history─────────│",
+            "│               Nothing played yet               │",
+            "│                                                │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check─────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let view = HistoryView::new(&state_with_everything(), tx);
+
+        let (mut terminal, area) = setup_test_terminal(50, 6);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌History 1 songs─────────────────────────────────┐",
+            "│q: add to queue | p: add to playlist────────────│",
+            "│Performing operations on entire history─────────│",
+            "│☐ Test Song Test Artist                         │",
+            "│                                                │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check─────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_actions() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = HistoryView::new(&state_with_everything(), tx);
+
+        // need to render the view at least once to load the tree state
+        let (mut terminal, area) = setup_test_terminal(50, 6);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let _frame = terminal.draw(|frame| view.render(frame, props)).unwrap();
+
+        // there are no checked items, so the whole history is re-queued
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('q')));
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::Audio(AudioAction::Queue(QueueAction::Add(vec![(
+                "song",
+                item_id()
+            )
+                .into()])))
+        );
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('p')));
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::Popup(PopupAction::Open(PopupType::Playlist(vec![(
+                "song",
+                item_id()
+            )
+                .into()])))
+        );
+
+        // select the song and open it
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        let _frame = terminal.draw(|frame| view.render(frame, props)).unwrap();
+        view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::ActiveView(ViewAction::Set(ActiveView::Song(item_id())))
+        );
+
+        // check it, and re-queue just the checked items
+        view.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('q')));
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::Audio(AudioAction::Queue(QueueAction::Add(vec![(
+                "song",
+                item_id()
+            )
+                .into()])))
+        );
+    }
+}
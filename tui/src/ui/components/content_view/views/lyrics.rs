@@ -0,0 +1,355 @@
+//! implementation of the lyrics view
+
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
+    Frame,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    state::action::Action,
+    ui::{
+        colors::{BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_NORMAL},
+        components::{Component, ComponentRender, RenderProps},
+        AppState,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Props {
+    /// the title of the song that is currently playing, if any
+    pub(crate) title: Option<Arc<str>>,
+    /// the lyrics of the song that is currently playing, if it has any
+    pub(crate) lyrics: Option<Arc<str>>,
+}
+
+impl From<&AppState> for Props {
+    fn from(value: &AppState) -> Self {
+        let song = value.audio.current_song.as_ref();
+        Self {
+            title: song.map(|song| song.title.clone()),
+            // the lyrics are fetched separately, so make sure they're for the right song
+            lyrics: value
+                .additional_view_data
+                .lyrics
+                .as_ref()
+                .filter(|lyrics| song.is_some_and(|song| song.id == lyrics.id))
+                .and_then(|lyrics| lyrics.lyrics.clone()),
+        }
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct LyricsView {
+    /// Action Sender
+    pub action_tx: UnboundedSender<Action>,
+    /// Mapped Props from state
+    pub(crate) props: Props,
+    /// how many lines the lyrics are scrolled down by
+    scroll: u16,
+}
+
+impl LyricsView {
+    /// the number of lines in the lyrics, before wrapping
+    fn line_count(&self) -> u16 {
+        self.props.lyrics.as_ref().map_or(0, |lyrics| {
+            u16::try_from(lyrics.lines().count()).unwrap_or(u16::MAX)
+        })
+    }
+
+    fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self
+            .scroll
+            .saturating_add(lines)
+            .min(self.line_count().saturating_sub(1));
+    }
+}
+
+impl Component for LyricsView {
+    fn new(state: &AppState, action_tx: UnboundedSender<Action>) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            action_tx,
+            props: Props::from(state),
+            scroll: 0,
+        }
+    }
+
+    fn move_with_state(self, state: &AppState) -> Self
+    where
+        Self: Sized,
+    {
+        let props = Props::from(state);
+
+        // the audio state is refreshed every tick, so only scroll back to the top if the song actually changed
+        if props == self.props {
+            self
+        } else {
+            Self {
+                props,
+                scroll: 0,
+                ..self
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Lyrics"
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(10),
+            KeyCode::PageDown => self.scroll_down(10),
+            KeyCode::Home => self.scroll = 0,
+            _ => {}
+        }
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, _: Rect) {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll_up(1),
+            MouseEventKind::ScrollDown => self.scroll_down(1),
+            _ => {}
+        }
+    }
+}
+
+impl ComponentRender<RenderProps> for LyricsView {
+    fn render_border(&self, frame: &mut Frame, props: RenderProps) -> RenderProps {
+        let border_style = if props.is_focused {
+            Style::default().fg(BORDER_FOCUSED.into())
+        } else {
+            Style::default().fg(BORDER_UNFOCUSED.into())
+        };
+
+        let mut title = vec![Span::styled("Lyrics", Style::default().bold())];
+        if let Some(song) = &self.props.title {
+            title.push(Span::raw(" "));
+            title.push(Span::styled(song.to_string(), Style::default().italic()));
+        }
+
+        let border = Block::bordered()
+            .title_top(Line::from(title))
+            .title_bottom(" ↑/↓: Scroll | PgUp/PgDn: Scroll Faster ")
+            .border_style(border_style);
+        frame.render_widget(&border, props.area);
+        let area = border.inner(props.area);
+
+        RenderProps { area, ..props }
+    }
+
+    fn render_content(&self, frame: &mut Frame, props: RenderProps) {
+        let Some(lyrics) = &self.props.lyrics else {
+            let text = if self.props.title.is_some() {
+                "No lyrics for this song"
+            } else {
+                "Nothing playing"
+            };
+            frame.render_widget(
+                Line::from(text)
+                    .style(Style::default().fg(TEXT_NORMAL.into()))
+                    .alignment(Alignment::Center),
+                props.area,
+            );
+            return;
+        };
+
+        frame.render_widget(
+            Paragraph::new(lyrics.as_ref())
+                .style(Style::default().fg(TEXT_NORMAL.into()))
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            props.area,
+        );
+
+        // only show the scrollbar if the lyrics don't fit
+        if self.line_count() > props.area.height {
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                props.area,
+                &mut ScrollbarState::new(usize::from(self.line_count()))
+                    .position(usize::from(self.scroll)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{assert_buffer_eq, setup_test_terminal, state_with_everything},
+        ui::components::content_view::views::LyricsViewProps,
+    };
+    use anyhow::Result;
+    use mecomp_storage::db::schemas::{Id, Thing};
+    use pretty_assertions::assert_eq;
+    use ratatui::buffer::Buffer;
+
+    fn state_with_lyrics(lyrics: Option<&str>) -> AppState {
+        let mut state = state_with_everything();
+        let song = state.library.songs[0].clone();
+        state.additional_view_data.lyrics = Some(LyricsViewProps {
+            id: song.id.clone(),
+            lyrics: lyrics.map(Into::into),
+        });
+        state.audio.current_song = Some(song);
+        state
+    }
+
+    #[test]
+    fn test_new() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let state = state_with_lyrics(Some("la la la"));
+        let view = LyricsView::new(&state, tx);
+
+        assert_eq!(view.name(), "Lyrics");
+        assert_eq!(view.props.title, Some("Test Song".into()));
+        assert_eq!(view.props.lyrics, Some("la la la".into()));
+    }
+
+    #[test]
+    fn test_lyrics_of_another_song() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut state = state_with_lyrics(Some("la la la"));
+        // the song changed, but its lyrics haven't been fetched yet
+        let mut song = state.library.songs[0].clone();
+        song.id = Thing::from(("song", Id::String("another song".into()))).into();
+        state.audio.current_song = Some(song);
+        let view = LyricsView::new(&state, tx);
+
+        assert_eq!(view.props.lyrics, None);
+    }
+
+    #[test]
+    fn test_move_with_state() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let state = state_with_lyrics(Some("one\ntwo\nthree"));
+        let mut view = LyricsView::new(&state, tx);
+
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        assert_eq!(view.scroll, 1);
+
+        // the same song keeps its scroll position
+        let mut view = view.move_with_state(&state);
+        assert_eq!(view.scroll, 1);
+
+        // a new song starts at the top
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        let view = view.move_with_state(&state_with_lyrics(Some("four\nfive")));
+        assert_eq!(view.scroll, 0);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = LyricsView::new(&state_with_lyrics(Some("one\ntwo\nthree")), tx);
+
+        view.handle_key_event(KeyEvent::from(KeyCode::Up));
+        assert_eq!(view.scroll, 0);
+        view.handle_key_event(KeyEvent::from(KeyCode::PageDown));
+        assert_eq!(view.scroll, 2);
+        view.handle_key_event(KeyEvent::from(KeyCode::Up));
+        assert_eq!(view.scroll, 1);
+        view.handle_key_event(KeyEvent::from(KeyCode::Home));
+        assert_eq!(view.scroll, 0);
+    }
+
+    #[test]
+    fn test_render_nothing_playing() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let view = LyricsView::new(&state_with_everything(), tx);
+
+        let (mut terminal, area) = setup_test_terminal(50, 4);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Lyrics──────────────────────────────────────────┐",
+            "│                Nothing playing                 │",
+            "│                                                │",
+            "└ ↑/↓: Scroll | PgUp/PgDn: Scroll Faster ────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_no_lyrics() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let view = LyricsView::new(&state_with_lyrics(None), tx);
+
+        let (mut terminal, area) = setup_test_terminal(50, 4);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Lyrics Test Song────────────────────────────────┐",
+            "│            No lyrics for this song             │",
+            "│                                                │",
+            "└ ↑/↓: Scroll | PgUp/PgDn: Scroll Faster ────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_scrolled() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = LyricsView::new(&state_with_lyrics(Some("one\ntwo\nthree\nfour")), tx);
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+
+        let (mut terminal, area) = setup_test_terminal(50, 5);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Lyrics Test Song────────────────────────────────┐",
+            "│two                                            ▲│",
+            "│three                                          █│",
+            "│four                                           ▼│",
+            "└ ↑/↓: Scroll | PgUp/PgDn: Scroll Faster ────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+}
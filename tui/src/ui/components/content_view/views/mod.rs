@@ -1,6 +1,16 @@
-use mecomp_core::format_duration;
+use std::sync::Arc;
+
+use mecomp_core::{
+    format_duration,
+    state::library::{CollectionAnalysisStats, LibraryStatistics},
+};
 use mecomp_storage::db::schemas::{
-    album::Album, artist::Artist, collection::Collection, playlist::Playlist, song::Song, Thing,
+    album::Album,
+    artist::Artist,
+    collection::Collection,
+    playlist::Playlist,
+    song::{Song, SongId, MAX_RATING},
+    Thing,
 };
 use one_or_many::OneOrMany;
 use ratatui::{
@@ -11,12 +21,14 @@ use ratatui::{
 };
 use traits::ItemViewProps;
 
-use crate::ui::widgets::tree::item::CheckTreeItem;
+use crate::ui::widgets::tree::{item::CheckTreeItem, state::CheckTreeState};
 
 pub mod album;
 pub mod artist;
 pub mod collection;
 pub mod generic;
+pub mod history;
+pub mod lyrics;
 pub mod none;
 pub mod playlist;
 pub mod radio;
@@ -37,6 +49,40 @@ pub struct ViewData {
     pub playlist: Option<PlaylistViewProps>,
     pub song: Option<SongViewProps>,
     pub radio: Option<RadioViewProps>,
+    /// The lyrics of the song that is currently playing, shown in the lyrics view
+    pub lyrics: Option<LyricsViewProps>,
+    /// Statistics about the library, shown at the top of the library songs view
+    pub statistics: Option<LibraryStatistics>,
+}
+
+/// The scroll position and selection of a library view.
+///
+/// Library views hold onto this while they aren't active,
+/// so the user ends up where they left off when navigating back to them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryViewState {
+    pub scroll_offset: usize,
+    pub selected: Vec<String>,
+}
+
+impl From<&CheckTreeState<String>> for LibraryViewState {
+    fn from(state: &CheckTreeState<String>) -> Self {
+        Self {
+            scroll_offset: state.get_offset(),
+            selected: state.selected().to_vec(),
+        }
+    }
+}
+
+impl From<LibraryViewState> for CheckTreeState<String> {
+    fn from(saved: LibraryViewState) -> Self {
+        let mut state = Self::default();
+        state.set_offset(saved.scroll_offset);
+        if !saved.selected.is_empty() {
+            state.select(saved.selected);
+        }
+        state
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -109,9 +155,17 @@ impl ItemViewProps for AlbumViewProps {
         .alignment(Alignment::Center)
     }
 
+    fn description(&self) -> Option<&str> {
+        self.album.description.as_deref()
+    }
+
     fn tree_items(&self) -> Result<Vec<CheckTreeItem<String>>, std::io::Error> {
         let artist_tree = checktree_utils::create_artist_tree_item(self.artists.as_slice())?;
-        let song_tree = checktree_utils::create_song_tree_item(self.songs.as_ref())?;
+        let song_tree = if self.album.discs > 1 {
+            checktree_utils::create_song_tree_item_by_disc(self.songs.as_ref())?
+        } else {
+            checktree_utils::create_song_tree_item(self.songs.as_ref())?
+        };
         Ok(vec![artist_tree, song_tree])
     }
 }
@@ -178,6 +232,14 @@ impl ItemViewProps for ArtistViewProps {
         .alignment(Alignment::Center)
     }
 
+    fn description(&self) -> Option<&str> {
+        self.artist.biography.as_deref()
+    }
+
+    fn description_title() -> &'static str {
+        "Biography"
+    }
+
     fn tree_items(&self) -> Result<Vec<CheckTreeItem<String>>, std::io::Error> {
         let album_tree = checktree_utils::create_album_tree_item(self.albums.as_ref())?;
         let song_tree = checktree_utils::create_song_tree_item(self.songs.as_ref())?;
@@ -190,6 +252,8 @@ pub struct CollectionViewProps {
     pub id: Thing,
     pub collection: Collection,
     pub songs: Box<[Song]>,
+    /// summary statistics of the analyzed songs in the collection, if any have been analyzed
+    pub stats: Option<CollectionAnalysisStats>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -281,6 +345,12 @@ impl ItemViewProps for SongViewProps {
                     Style::default().italic(),
                 ),
             ]),
+            Line::from(self.song.rating.map_or_else(Vec::new, |rating| {
+                vec![
+                    Span::raw("Rating: "),
+                    Span::styled(rating_stars(rating), Style::default().italic()),
+                ]
+            })),
         ])
         .alignment(Alignment::Center)
     }
@@ -308,10 +378,29 @@ pub struct RadioViewProps {
     pub songs: Box<[Song]>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LyricsViewProps {
+    /// The song the lyrics are of
+    pub id: SongId,
+    /// The lyrics, if the song has any
+    pub lyrics: Option<Arc<str>>,
+}
+
+/// The rating as a row of stars, e.g. `★★★☆☆` for 3 out of 5 stars.
+fn rating_stars(rating: u8) -> String {
+    let rating = rating.min(MAX_RATING);
+    "★".repeat(rating.into()) + &"☆".repeat((MAX_RATING - rating).into())
+}
+
 pub mod checktree_utils {
     use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
     use mecomp_storage::db::schemas::{
-        album::Album, artist::Artist, collection::Collection, playlist::Playlist, song::Song, Thing,
+        album::Album,
+        artist::Artist,
+        collection::Collection,
+        playlist::Playlist,
+        song::{Song, SongBrief},
+        Thing,
     };
     use ratatui::{
         layout::Position,
@@ -322,6 +411,7 @@ pub mod checktree_utils {
     use crate::{
         state::action::{Action, AudioAction, PopupAction, QueueAction, ViewAction},
         ui::{
+            colors::TEXT_HIGHLIGHT,
             components::content_view::ActiveView,
             widgets::{
                 popups::PopupType,
@@ -623,7 +713,65 @@ pub mod checktree_utils {
         )
     }
 
+    /// Like `create_song_tree_item`, but the songs are grouped under a "Disc N" item for each disc.
+    ///
+    /// Assumes the songs are already sorted by disc (as `library_album_get_songs` returns them).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree item cannot be created (e.g. duplicate ids)
+    pub fn create_song_tree_item_by_disc(
+        songs: &[Song],
+    ) -> Result<CheckTreeItem<'_, String>, std::io::Error> {
+        let discs = songs
+            .chunk_by(|a, b| a.disc == b.disc)
+            .map(|disc_songs| {
+                let disc = disc_songs[0]
+                    .disc
+                    .map_or_else(|| "Unknown Disc".to_string(), |disc| format!("Disc {disc}"));
+                CheckTreeItem::<String>::new_with_items(
+                    disc_songs,
+                    &disc,
+                    format!("{disc} ({}):", disc_songs.len()),
+                    create_song_tree_leaf,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut tree = CheckTreeItem::new(
+            "Songs".to_string(),
+            format!("Songs ({}):", songs.len()),
+            discs,
+        )?;
+        if tree.children().is_empty() {
+            tree.add_child(create_dummy_leaf())?;
+        }
+        Ok(tree)
+    }
+
     pub fn create_song_tree_leaf<'a>(song: &Song) -> CheckTreeItem<'a, String> {
+        let mut line = Line::from(vec![
+            Span::styled(song.title.to_string(), Style::default().bold()),
+            Span::raw(" "),
+            Span::styled(
+                song.artist
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                Style::default().italic(),
+            ),
+        ]);
+        if song.favorite {
+            line.push_span(Span::styled(
+                " ♥",
+                Style::default().fg(TEXT_HIGHLIGHT.into()),
+            ));
+        }
+        CheckTreeItem::new_leaf(song.id.to_string(), line)
+    }
+
+    pub fn create_song_brief_tree_leaf<'a>(song: &SongBrief) -> CheckTreeItem<'a, String> {
         CheckTreeItem::new_leaf(
             song.id.to_string(),
             Line::from(vec![
@@ -35,7 +35,7 @@ use super::{
     },
     sort_mode::{NameSort, SongSort},
     traits::SortMode,
-    PlaylistViewProps,
+    LibraryViewState, PlaylistViewProps,
 };
 
 #[allow(clippy::module_name_repetitions)]
@@ -358,6 +358,8 @@ pub struct LibraryPlaylistsView {
     input_box: InputBox,
     /// Is the input box visible
     input_box_visible: bool,
+    /// Is the input box naming the playlist the checked playlists are merged into (rather than a new playlist)
+    merging: bool,
 }
 
 #[derive(Debug)]
@@ -386,6 +388,7 @@ impl Component for LibraryPlaylistsView {
         Self {
             input_box: InputBox::new(state, action_tx.clone()),
             input_box_visible: false,
+            merging: false,
             action_tx,
             props: Props::from(state),
             tree_state: Mutex::new(CheckTreeState::default()),
@@ -399,7 +402,9 @@ impl Component for LibraryPlaylistsView {
         let tree_state = if state.active_view == ActiveView::Playlists {
             self.tree_state
         } else {
-            Mutex::new(CheckTreeState::default())
+            // keep the scroll position and selection around for when the user navigates back
+            let saved = LibraryViewState::from(&self.tree_state.into_inner().unwrap());
+            Mutex::new(saved.into())
         };
 
         Self {
@@ -420,16 +425,22 @@ impl Component for LibraryPlaylistsView {
         if self.input_box_visible {
             match key.code {
                 // if the user presses Enter, we try to create a new playlist with the given name
+                // (or merge the checked playlists into one with that name)
                 KeyCode::Enter => {
                     let name = self.input_box.text();
                     if !name.is_empty() {
-                        self.action_tx
-                            .send(Action::Library(LibraryAction::CreatePlaylist(
-                                name.to_string(),
-                            )))
-                            .unwrap();
+                        let action = if self.merging {
+                            let mut tree_state = self.tree_state.lock().unwrap();
+                            let checked = tree_state.get_checked_things();
+                            tree_state.reset();
+                            LibraryAction::MergePlaylists(checked, name.to_string())
+                        } else {
+                            LibraryAction::CreatePlaylist(name.to_string())
+                        };
+                        self.action_tx.send(Action::Library(action)).unwrap();
                     }
                     self.input_box_visible = false;
+                    self.merging = false;
                 }
                 // defer to the input box
                 _ => {
@@ -474,6 +485,10 @@ impl Component for LibraryPlaylistsView {
                         }
                     }
                 }
+                // when space is pressed, check the selected playlist
+                KeyCode::Char(' ') => {
+                    self.tree_state.lock().unwrap().key_space();
+                }
                 // Change sort mode
                 KeyCode::Char('s') => {
                     self.props.sort_mode = self.props.sort_mode.next();
@@ -487,6 +502,14 @@ impl Component for LibraryPlaylistsView {
                 KeyCode::Char('n') => {
                     self.input_box_visible = true;
                 }
+                // "m" key to merge the checked playlists into a new playlist
+                KeyCode::Char('m') => {
+                    if !self.tree_state.lock().unwrap().checked().is_empty() {
+                        self.input_box.reset();
+                        self.input_box_visible = true;
+                        self.merging = true;
+                    }
+                }
                 // "d" key to delete the selected playlist
                 KeyCode::Char('d') => {
                     let things = self.tree_state.lock().unwrap().get_selected_thing();
@@ -524,6 +547,7 @@ impl Component for LibraryPlaylistsView {
                 && kind == MouseEventKind::Down(MouseButton::Left)
             {
                 self.input_box_visible = false;
+                self.merging = false;
             }
         } else {
             let area = Rect {
@@ -573,7 +597,7 @@ impl ComponentRender<RenderProps> for LibraryPlaylistsView {
             .title_bottom(if self.input_box_visible {
                 ""
             } else {
-                " \u{23CE} : Open | ←/↑/↓/→: Navigate | s/S: change sort"
+                " \u{23CE} : Open | ←/↑/↓/→: Navigate | \u{2423} Check | s/S: change sort"
             })
             .border_style(border_style);
         let content_area = border.inner(props.area);
@@ -605,10 +629,12 @@ impl ComponentRender<RenderProps> for LibraryPlaylistsView {
         // draw additional border around content area to display additional instructions
         let border = Block::new()
             .borders(Borders::TOP)
-            .title_top(if self.input_box_visible {
-                " \u{23CE} : Create (cancel if empty)"
+            .title_top(if !self.input_box_visible {
+                "n: new playlist | d: delete playlist | m: merge checked"
+            } else if self.merging {
+                " \u{23CE} : Merge (cancel if empty)"
             } else {
-                "n: new playlist | d: delete playlist"
+                " \u{23CE} : Create (cancel if empty)"
             })
             .border_style(border_style);
         let area = border.inner(content_area);
@@ -631,9 +657,9 @@ impl ComponentRender<RenderProps> for LibraryPlaylistsView {
             CheckTree::new(&items)
                 .unwrap()
                 .highlight_style(Style::default().fg(TEXT_HIGHLIGHT.into()).bold())
-                // we want this to be rendered like a normal tree, not a check tree, so we don't show the checkboxes
+                // checking playlists is only used to pick the ones to merge, so only show a box when one is checked
                 .node_unchecked_symbol("▪ ")
-                .node_checked_symbol("▪ ")
+                .node_checked_symbol("\u{2611} ")
                 .experimental_scrollbar(Some(Scrollbar::new(ScrollbarOrientation::VerticalRight))),
             props.area,
             &mut self.tree_state.lock().unwrap(),
@@ -1134,11 +1160,11 @@ mod library_view_tests {
             .clone();
         let expected = Buffer::with_lines([
             "┌Library Playlists sorted by: Name─────────────────────────┐",
-            "│n: new playlist | d: delete playlist──────────────────────│",
+            "│n: new playlist | d: delete playlist | m: merge checked───│",
             "│▪ Test Playlist                                           │",
             "│                                                          │",
             "│                                                          │",
-            "└ ⏎ : Open | ←/↑/↓/→: Navigate | s/S: change sort──────────┘",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check | s/S: change sort┘",
         ]);
 
         assert_buffer_eq(&buffer, &expected);
@@ -1247,6 +1273,62 @@ mod library_view_tests {
                 ("playlist", item_id()).into()
             ))
         );
+
+        // merge playlists, nothing happens until a playlist is checked
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('m')));
+        assert_eq!(view.input_box_visible, false);
+        view.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('m')));
+        assert_eq!(view.input_box_visible, true);
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('c')));
+        view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(view.input_box_visible, false);
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Action::Library(LibraryAction::MergePlaylists(
+                vec![("playlist", item_id()).into()],
+                "c".to_string()
+            ))
+        );
+        // the playlists are unchecked after merging
+        assert!(view.tree_state.lock().unwrap().checked().is_empty());
+    }
+
+    #[test]
+    fn test_render_merge() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = LibraryPlaylistsView::new(&state_with_everything(), tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 7);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        terminal.draw(|frame| view.render(frame, props)).unwrap();
+
+        // check the playlist, and start merging
+        view.handle_key_event(KeyEvent::from(KeyCode::Down));
+        view.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('m')));
+
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Library Playlists sorted by: Name─────────────────────────┐",
+            "│┌Enter Name:─────────────────────────────────────────────┐│",
+            "││                                                        ││",
+            "│└────────────────────────────────────────────────────────┘│",
+            "│ ⏎ : Merge (cancel if empty)──────────────────────────────│",
+            "│☑ Test Playlist                                           │",
+            "└──────────────────────────────────────────────────────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
     }
 
     #[test]
@@ -1267,14 +1349,14 @@ mod library_view_tests {
             .clone();
         let expected = Buffer::with_lines([
             "┌Library Playlists sorted by: Name─────────────────────────┐",
-            "│n: new playlist | d: delete playlist──────────────────────│",
+            "│n: new playlist | d: delete playlist | m: merge checked───│",
             "│▪ Test Playlist                                           │",
             "│                                                          │",
             "│                                                          │",
             "│                                                          │",
             "│                                                          │",
             "│                                                          │",
-            "└ ⏎ : Open | ←/↑/↓/→: Navigate | s/S: change sort──────────┘",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check | s/S: change sort┘",
         ]);
         assert_buffer_eq(&buffer, &expected);
 
@@ -3,9 +3,10 @@
 use std::sync::Mutex;
 
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use mecomp_core::{format_duration, state::library::LibraryStatistics};
 use mecomp_storage::db::schemas::song::Song;
 use ratatui::{
-    layout::{Margin, Rect},
+    layout::{Constraint, Layout, Margin, Position, Rect},
     style::{Style, Stylize},
     text::{Line, Span},
     widgets::{Block, Borders, Scrollbar, ScrollbarOrientation},
@@ -15,9 +16,12 @@ use tokio::sync::mpsc::UnboundedSender;
 use crate::{
     state::action::{Action, AudioAction, PopupAction, QueueAction, ViewAction},
     ui::{
-        colors::{BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT},
+        colors::{
+            BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT, TEXT_HIGHLIGHT_ALT, TEXT_NORMAL,
+        },
         components::{content_view::ActiveView, Component, ComponentRender, RenderProps},
         widgets::{
+            input_box::{self, InputBox},
             popups::PopupType,
             tree::{state::CheckTreeState, CheckTree},
         },
@@ -27,7 +31,7 @@ use crate::{
 
 use super::{
     checktree_utils::create_song_tree_leaf, generic::ItemView, sort_mode::SongSort,
-    traits::SortMode, SongViewProps, RADIO_SIZE,
+    traits::SortMode, LibraryViewState, SongViewProps, RADIO_SIZE,
 };
 
 #[allow(clippy::module_name_repetitions)]
@@ -40,11 +44,81 @@ pub struct LibrarySongsView {
     pub(crate) props: Props,
     /// tree state
     tree_state: Mutex<CheckTreeState<String>>,
+    /// Input box for the year filter
+    year_filter_bar: InputBox,
+    /// Is the year filter input box focused
+    year_filter_focused: bool,
 }
 
 pub(crate) struct Props {
     pub(crate) songs: Box<[Song]>,
     pub(crate) sort_mode: SongSort,
+    /// Only show songs released within this range of years (inclusive)
+    pub(crate) year_filter: Option<YearRange>,
+    /// Statistics about the library, summarized above the songs
+    pub(crate) statistics: Option<LibraryStatistics>,
+}
+
+/// An inclusive range of release years to filter songs by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YearRange {
+    pub start: i32,
+    pub end: i32,
+}
+
+impl YearRange {
+    /// Parse a range of years, e.g. "1970-1979", or a single year, e.g. "1975".
+    ///
+    /// Either end of a range can be omitted to leave it unbounded, e.g. "1990-" or "-1979".
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let (start, end) = match input.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (input, input),
+        };
+        if start.is_empty() && end.is_empty() {
+            return None;
+        }
+        let start = if start.is_empty() {
+            i32::MIN
+        } else {
+            start.parse().ok()?
+        };
+        let end = if end.is_empty() {
+            i32::MAX
+        } else {
+            end.parse().ok()?
+        };
+        (start <= end).then_some(Self { start, end })
+    }
+
+    /// Whether a song with the given release year is within the range, songs without a release year never are
+    #[must_use]
+    pub fn contains(&self, release_year: Option<i32>) -> bool {
+        release_year.is_some_and(|year| (self.start..=self.end).contains(&year))
+    }
+}
+
+impl std::fmt::Display for YearRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.start, self.end) {
+            (start, end) if start == end => write!(f, "{start}"),
+            (i32::MIN, end) => write!(f, "-{end}"),
+            (start, i32::MAX) => write!(f, "{start}-"),
+            (start, end) => write!(f, "{start}-{end}"),
+        }
+    }
+}
+
+impl Props {
+    /// The songs that pass the year filter (if any)
+    fn visible_songs(&self) -> impl Iterator<Item = &Song> {
+        self.songs.iter().filter(|song| {
+            self.year_filter
+                .is_none_or(|range| range.contains(song.release_year))
+        })
+    }
 }
 
 impl Component for LibrarySongsView {
@@ -56,8 +130,15 @@ impl Component for LibrarySongsView {
         let mut songs = state.library.songs.clone();
         sort_mode.sort_items(&mut songs);
         Self {
+            year_filter_bar: InputBox::new(state, action_tx.clone()),
+            year_filter_focused: false,
             action_tx,
-            props: Props { songs, sort_mode },
+            props: Props {
+                songs,
+                sort_mode,
+                year_filter: None,
+                statistics: state.additional_view_data.statistics.clone(),
+            },
             tree_state: Mutex::new(CheckTreeState::default()),
         }
     }
@@ -71,11 +152,14 @@ impl Component for LibrarySongsView {
         let tree_state = if state.active_view == ActiveView::Songs {
             self.tree_state
         } else {
-            Mutex::new(CheckTreeState::default())
+            // keep the scroll position and selection around for when the user navigates back
+            let saved = LibraryViewState::from(&self.tree_state.into_inner().unwrap());
+            Mutex::new(saved.into())
         };
         Self {
             props: Props {
                 songs,
+                statistics: state.additional_view_data.statistics.clone(),
                 ..self.props
             },
             tree_state,
@@ -88,11 +172,18 @@ impl Component for LibrarySongsView {
     }
 
     fn handle_key_event(&mut self, key: KeyEvent) {
+        if self.year_filter_focused {
+            self.handle_year_filter_key_event(key);
+            return;
+        }
+
         match key.code {
             // arrow keys
             KeyCode::PageUp => {
                 self.tree_state.lock().unwrap().select_relative(|current| {
-                    current.map_or(self.props.songs.len() - 1, |c| c.saturating_sub(10))
+                    current.map_or(self.props.visible_songs().count().saturating_sub(1), |c| {
+                        c.saturating_sub(10)
+                    })
                 });
             }
             KeyCode::Up => {
@@ -168,13 +259,36 @@ impl Component for LibrarySongsView {
                 self.props.sort_mode = self.props.sort_mode.prev();
                 self.props.sort_mode.sort_items(&mut self.props.songs);
             }
+            // "y" opens the year filter
+            KeyCode::Char('y') => {
+                self.year_filter_bar.set_text(
+                    &self
+                        .props
+                        .year_filter
+                        .map(|range| range.to_string())
+                        .unwrap_or_default(),
+                );
+                self.year_filter_focused = true;
+            }
             _ => {}
         }
     }
 
     fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) {
+        if self.year_filter_focused {
+            // the year filter bar is drawn just inside the outer border
+            let [filter_bar_area, _] = split_area(area.inner(Margin::new(1, 1)));
+            let position = Position::new(mouse.column, mouse.row);
+            if filter_bar_area.contains(position) {
+                self.year_filter_bar
+                    .handle_mouse_event(mouse, filter_bar_area);
+            }
+            return;
+        }
+
         // adjust the area to account for the border
         let area = area.inner(Margin::new(1, 2));
+        let [_, area] = self.split_summary_area(area);
 
         let result = self
             .tree_state
@@ -187,6 +301,79 @@ impl Component for LibrarySongsView {
     }
 }
 
+impl LibrarySongsView {
+    /// Handle key events while the year filter input box is focused.
+    ///
+    /// Enter applies the filter (or clears it, if the input is empty),
+    /// invalid ranges are ignored so they can be corrected.
+    fn handle_year_filter_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter if self.year_filter_bar.is_empty() => {
+                self.set_year_filter(None);
+            }
+            KeyCode::Enter => {
+                if let Some(range) = YearRange::parse(self.year_filter_bar.text()) {
+                    self.set_year_filter(Some(range));
+                }
+            }
+            _ => self.year_filter_bar.handle_key_event(key),
+        }
+    }
+
+    /// Split the content area into the line for the statistics summary (if there are statistics), and the area for the songs.
+    fn split_summary_area(&self, area: Rect) -> [Rect; 2] {
+        if self.props.statistics.is_none() {
+            return [Rect::default(), area];
+        }
+        let [summary_area, songs_area] =
+            *Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(area)
+        else {
+            panic!("Failed to split library songs view area");
+        };
+        [summary_area, songs_area]
+    }
+
+    fn set_year_filter(&mut self, year_filter: Option<YearRange>) {
+        self.props.year_filter = year_filter;
+        self.year_filter_focused = false;
+        self.year_filter_bar.reset();
+        self.tree_state.lock().unwrap().reset();
+    }
+}
+
+/// A one line summary of the library statistics.
+fn statistics_summary(statistics: &LibraryStatistics) -> String {
+    let mut parts = vec![
+        format!("Songs: {}", statistics.total_songs),
+        format!("Albums: {}", statistics.total_albums),
+        format!("Artists: {}", statistics.total_artists),
+        format!("Runtime: {}", format_duration(&statistics.total_runtime)),
+        format!(
+            "Analyzed: {}/{}",
+            statistics.songs_with_analysis, statistics.total_songs
+        ),
+    ];
+    if let Some(bpm) = statistics.average_bpm {
+        parts.push(format!("Avg BPM: {bpm:.0}"));
+    }
+    if let Some(genre) = &statistics.most_common_genre {
+        parts.push(format!(
+            "Genres: {} (top: {genre})",
+            statistics.unique_genres
+        ));
+    }
+    parts.join(" | ")
+}
+
+fn split_area(area: Rect) -> [Rect; 2] {
+    let [filter_bar_area, content_area] =
+        *Layout::vertical([Constraint::Length(3), Constraint::Min(4)]).split(area)
+    else {
+        panic!("Failed to split library songs view area");
+    };
+    [filter_bar_area, content_area]
+}
+
 impl ComponentRender<RenderProps> for LibrarySongsView {
     fn render_border(&self, frame: &mut ratatui::Frame, props: RenderProps) -> RenderProps {
         let border_style = if props.is_focused {
@@ -196,17 +383,45 @@ impl ComponentRender<RenderProps> for LibrarySongsView {
         };
 
         // draw primary border
+        let mut title = vec![
+            Span::styled("Library Songs".to_string(), Style::default().bold()),
+            Span::raw(" sorted by: "),
+            Span::styled(self.props.sort_mode.to_string(), Style::default().italic()),
+        ];
+        if let Some(range) = self.props.year_filter {
+            title.push(Span::raw(" years: "));
+            title.push(Span::styled(range.to_string(), Style::default().italic()));
+        }
         let border = Block::bordered()
-            .title_top(Line::from(vec![
-                Span::styled("Library Songs".to_string(), Style::default().bold()),
-                Span::raw(" sorted by: "),
-                Span::styled(self.props.sort_mode.to_string(), Style::default().italic()),
-            ]))
-            .title_bottom(" \u{23CE} : Open | ←/↑/↓/→: Navigate | \u{2423} Check")
+            .title_top(Line::from(title))
+            .title_bottom(if self.year_filter_focused {
+                " \u{23CE} : Apply (empty to clear) | e.g. 1970-1979, 1990-, 1975"
+            } else {
+                " \u{23CE} : Open | ←/↑/↓/→: Navigate | \u{2423} Check"
+            })
             .border_style(border_style);
         let content_area = border.inner(props.area);
         frame.render_widget(border, props.area);
 
+        // draw the year filter input box, if it's open
+        let content_area = if self.year_filter_focused {
+            let [filter_bar_area, content_area] = split_area(content_area);
+            self.year_filter_bar.render(
+                frame,
+                input_box::RenderProps {
+                    area: filter_bar_area,
+                    text_color: TEXT_HIGHLIGHT_ALT.into(),
+                    border: Block::bordered()
+                        .title("Filter by Year")
+                        .border_style(border_style),
+                    show_cursor: props.is_focused,
+                },
+            );
+            content_area
+        } else {
+            content_area
+        };
+
         // draw an additional border around the content area to display additional instructions
         let border = Block::new()
             .borders(Borders::TOP | Borders::BOTTOM)
@@ -223,7 +438,7 @@ impl ComponentRender<RenderProps> for LibrarySongsView {
                     "q: add to queue | r: start radio | p: add to playlist "
                 },
             )
-            .title_bottom("s/S: change sort")
+            .title_bottom("s/S: change sort | y: filter by year")
             .border_style(border_style);
         frame.render_widget(&border, content_area);
         let content_area = border.inner(content_area);
@@ -235,11 +450,20 @@ impl ComponentRender<RenderProps> for LibrarySongsView {
     }
 
     fn render_content(&self, frame: &mut ratatui::Frame, props: RenderProps) {
+        // summarize the library above the songs
+        let [summary_area, songs_area] = self.split_summary_area(props.area);
+        if let Some(statistics) = &self.props.statistics {
+            frame.render_widget(
+                Line::from(statistics_summary(statistics))
+                    .style(Style::default().fg(TEXT_NORMAL.into()).italic()),
+                summary_area,
+            );
+        }
+
         // create a tree to hold the songs
         let items = self
             .props
-            .songs
-            .iter()
+            .visible_songs()
             .map(create_song_tree_leaf)
             .collect::<Vec<_>>();
 
@@ -249,7 +473,7 @@ impl ComponentRender<RenderProps> for LibrarySongsView {
                 .unwrap()
                 .highlight_style(Style::default().fg(TEXT_HIGHLIGHT.into()).bold())
                 .experimental_scrollbar(Some(Scrollbar::new(ScrollbarOrientation::VerticalRight))),
-            props.area,
+            songs_area,
             &mut self.tree_state.lock().unwrap(),
         );
     }
@@ -300,6 +524,14 @@ mod sort_mode_tests {
                 release_year: Some(2021),
                 extension: "mp3".into(),
                 path: "test.mp3".into(),
+                replaygain_track_gain: None,
+                replaygain_album_gain: None,
+                play_count: 0,
+                skip_count: 0,
+                last_played: None,
+                rating: None,
+                favorite: false,
+                bookmark_positions: Vec::new(),
             },
             Song {
                 id: Song::generate_id(),
@@ -314,6 +546,14 @@ mod sort_mode_tests {
                 release_year: Some(2021),
                 extension: "mp3".into(),
                 path: "test.mp3".into(),
+                replaygain_track_gain: None,
+                replaygain_album_gain: None,
+                play_count: 0,
+                skip_count: 0,
+                last_played: None,
+                rating: None,
+                favorite: false,
+                bookmark_positions: Vec::new(),
             },
             Song {
                 id: Song::generate_id(),
@@ -328,6 +568,14 @@ mod sort_mode_tests {
                 release_year: Some(2021),
                 extension: "mp3".into(),
                 path: "test.mp3".into(),
+                replaygain_track_gain: None,
+                replaygain_album_gain: None,
+                play_count: 0,
+                skip_count: 0,
+                last_played: None,
+                rating: None,
+                favorite: false,
+                bookmark_positions: Vec::new(),
             },
         ];
 
@@ -419,6 +667,49 @@ mod item_view_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_render_rated_song() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut state = state_with_everything();
+        state
+            .additional_view_data
+            .song
+            .as_mut()
+            .unwrap()
+            .song
+            .rating = Some(3);
+        let view = SongView::new(&state, tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 12);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Song View─────────────────────────────────────────────────┐",
+            "│                   Test Song Test Artist                  │",
+            "│  Track/Disc: 0/0  Duration: 3:00.0  Genre(s): Test Genre │",
+            "│                       Rating: ★★★☆☆                      │",
+            "│q: add to queue | r: start radio | p: add to playlist─────│",
+            "│Performing operations on the song─────────────────────────│",
+            "│▶ Artists (1):                                            │",
+            "│☐ Album: Test Album Test Artist                           │",
+            "│▶ Playlists (1):                                          │",
+            "│▶ Collections (1):                                        │",
+            "│                                                          │",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
     #[test]
     fn test_render_no_playlist_no_collection() -> Result<()> {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
@@ -870,6 +1161,7 @@ mod library_view_tests {
     use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
     use pretty_assertions::assert_eq;
     use ratatui::buffer::Buffer;
+    use rstest::rstest;
 
     #[test]
     fn test_new() {
@@ -911,7 +1203,108 @@ mod library_view_tests {
             "│──────────────────────────────────────────────────────────│",
             "│☐ Test Song Test Artist                                   │",
             "│                                                          │",
-            "│s/S: change sort──────────────────────────────────────────│",
+            "│s/S: change sort | y: filter by year──────────────────────│",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    fn statistics() -> LibraryStatistics {
+        LibraryStatistics {
+            total_songs: 1,
+            total_albums: 1,
+            total_artists: 1,
+            total_runtime: std::time::Duration::from_secs(180),
+            songs_with_analysis: 1,
+            songs_without_analysis: 0,
+            average_bpm: Some(120.),
+            most_common_genre: Some("Test Genre".into()),
+            unique_genres: 1,
+        }
+    }
+
+    #[test]
+    fn test_statistics_summary() {
+        assert_eq!(
+            statistics_summary(&statistics()),
+            "Songs: 1 | Albums: 1 | Artists: 1 | Runtime: 00:03:00.00 | Analyzed: 1/1 | Avg BPM: 120 | Genres: 1 (top: Test Genre)"
+        );
+
+        // nothing analyzed, and no genres
+        let statistics = LibraryStatistics {
+            songs_with_analysis: 0,
+            songs_without_analysis: 1,
+            average_bpm: None,
+            most_common_genre: None,
+            unique_genres: 0,
+            ..statistics()
+        };
+        assert_eq!(
+            statistics_summary(&statistics),
+            "Songs: 1 | Albums: 1 | Artists: 1 | Runtime: 00:03:00.00 | Analyzed: 0/1"
+        );
+    }
+
+    #[test]
+    fn test_render_with_statistics() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut state = state_with_everything();
+        state.additional_view_data.statistics = Some(statistics());
+        let view = LibrarySongsView::new(&state, tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 7);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Library Songs sorted by: Artist───────────────────────────┐",
+            "│──────────────────────────────────────────────────────────│",
+            "│Songs: 1 | Albums: 1 | Artists: 1 | Runtime: 00:03:00.00 |│",
+            "│☐ Test Song Test Artist                                   │",
+            "│                                                          │",
+            "│s/S: change sort | y: filter by year──────────────────────│",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+
+        assert_buffer_eq(&buffer, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_favorite() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut state = state_with_everything();
+        let mut songs = state.library.songs.to_vec();
+        songs[0].favorite = true;
+        state.library.songs = songs.into();
+        let view = LibrarySongsView::new(&state, tx);
+
+        let (mut terminal, area) = setup_test_terminal(60, 6);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Library Songs sorted by: Artist───────────────────────────┐",
+            "│──────────────────────────────────────────────────────────│",
+            "│☐ Test Song Test Artist ♥                                 │",
+            "│                                                          │",
+            "│s/S: change sort | y: filter by year──────────────────────│",
             "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
         ]);
 
@@ -939,7 +1332,7 @@ mod library_view_tests {
             "│──────────────────────────────────────────────────────────│",
             "│☐ Test Song Test Artist                                   │",
             "│                                                          │",
-            "│s/S: change sort──────────────────────────────────────────│",
+            "│s/S: change sort | y: filter by year──────────────────────│",
             "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
         ]);
         assert_buffer_eq(&buffer, &expected);
@@ -958,7 +1351,7 @@ mod library_view_tests {
             "│q: add to queue | r: start radio | p: add to playlist ────│",
             "│☑ Test Song Test Artist                                   │",
             "│                                                          │",
-            "│s/S: change sort──────────────────────────────────────────│",
+            "│s/S: change sort | y: filter by year──────────────────────│",
             "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
         ]);
 
@@ -1076,6 +1469,130 @@ mod library_view_tests {
         );
     }
 
+    #[rstest]
+    #[case::range("1970-1979", Some((1970, 1979)))]
+    #[case::whitespace(" 1970 - 1979 ", Some((1970, 1979)))]
+    #[case::single_year("1975", Some((1975, 1975)))]
+    #[case::open_end("1990-", Some((1990, i32::MAX)))]
+    #[case::open_start("-1979", Some((i32::MIN, 1979)))]
+    #[case::backwards("1979-1970", None)]
+    #[case::empty("", None)]
+    #[case::only_dash("-", None)]
+    #[case::not_a_year("seventies", None)]
+    fn test_year_range_parse(#[case] input: &str, #[case] expected: Option<(i32, i32)>) {
+        let parsed = YearRange::parse(input);
+        assert_eq!(
+            parsed,
+            expected.map(|(start, end)| YearRange { start, end })
+        );
+        // displaying a range gives back something that parses to the same range
+        if let Some(range) = parsed {
+            assert_eq!(YearRange::parse(&range.to_string()), Some(range));
+        }
+    }
+
+    #[test]
+    fn test_year_range_contains() {
+        let range = YearRange {
+            start: 1970,
+            end: 1979,
+        };
+        assert!(range.contains(Some(1970)));
+        assert!(range.contains(Some(1979)));
+        assert!(!range.contains(Some(1969)));
+        assert!(!range.contains(Some(1980)));
+        // songs without a release year are excluded
+        assert!(!range.contains(None));
+    }
+
+    #[test]
+    fn test_year_filter() {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut view = LibrarySongsView::new(&state_with_everything(), tx);
+        let (mut terminal, area) = setup_test_terminal(60, 9);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+
+        // open the filter and type a range that excludes the song
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('y')));
+        for c in "1970-1979".chars() {
+            view.handle_key_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Library Songs sorted by: Artist───────────────────────────┐",
+            "│┌Filter by Year──────────────────────────────────────────┐│",
+            "││1970-1979                                               ││",
+            "│└────────────────────────────────────────────────────────┘│",
+            "│──────────────────────────────────────────────────────────│",
+            "│☐ Test Song Test Artist                                   │",
+            "│                                                          │",
+            "│s/S: change sort | y: filter by year──────────────────────│",
+            "└ ⏎ : Apply (empty to clear) | e.g. 1970-1979, 1990-, 1975─┘",
+        ]);
+        assert_buffer_eq(&buffer, &expected);
+
+        // apply the filter
+        view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(
+            view.props.year_filter,
+            Some(YearRange {
+                start: 1970,
+                end: 1979
+            })
+        );
+        let buffer = terminal
+            .draw(|frame| view.render(frame, props))
+            .unwrap()
+            .buffer
+            .clone();
+        let expected = Buffer::with_lines([
+            "┌Library Songs sorted by: Artist years: 1970-1979──────────┐",
+            "│──────────────────────────────────────────────────────────│",
+            "│                                                          │",
+            "│                                                          │",
+            "│                                                          │",
+            "│                                                          │",
+            "│                                                          │",
+            "│s/S: change sort | y: filter by year──────────────────────│",
+            "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
+        ]);
+        assert_buffer_eq(&buffer, &expected);
+
+        // the filter survives state updates
+        let view = view.move_with_state(&state_with_everything());
+        assert_eq!(view.props.visible_songs().count(), 0);
+        let mut view = view;
+
+        // re-opening the filter starts with the current range, an invalid range is ignored
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('y')));
+        assert_eq!(view.year_filter_bar.text(), "1970-1979");
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('x')));
+        view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(view.year_filter_focused);
+
+        // a range that includes the song
+        view.year_filter_bar.set_text("2021");
+        view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(!view.year_filter_focused);
+        assert_eq!(
+            view.props.visible_songs().cloned().collect::<Vec<_>>(),
+            view.props.songs.to_vec()
+        );
+
+        // applying an empty filter clears it
+        view.handle_key_event(KeyEvent::from(KeyCode::Char('y')));
+        view.year_filter_bar.reset();
+        view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(view.props.year_filter, None);
+    }
+
     #[test]
     fn test_mouse() {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
@@ -1097,7 +1614,7 @@ mod library_view_tests {
             "│──────────────────────────────────────────────────────────│",
             "│☐ Test Song Test Artist                                   │",
             "│                                                          │",
-            "│s/S: change sort──────────────────────────────────────────│",
+            "│s/S: change sort | y: filter by year──────────────────────│",
             "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
         ]);
         assert_buffer_eq(&buffer, &expected);
@@ -1122,7 +1639,7 @@ mod library_view_tests {
             "│q: add to queue | r: start radio | p: add to playlist ────│",
             "│☑ Test Song Test Artist                                   │",
             "│                                                          │",
-            "│s/S: change sort──────────────────────────────────────────│",
+            "│s/S: change sort | y: filter by year──────────────────────│",
             "└ ⏎ : Open | ←/↑/↓/→: Navigate | ␣ Check───────────────────┘",
         ]);
         assert_buffer_eq(&buffer, &expected);
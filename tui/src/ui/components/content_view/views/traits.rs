@@ -50,6 +50,19 @@ pub trait ItemViewProps {
 
     fn info_widget(&self) -> impl Widget;
 
+    /// Free-form text about the item (e.g. an album's description), shown in a scrollable panel below the item info
+    fn description(&self) -> Option<&str> {
+        None
+    }
+
+    /// The title of the panel the description is shown in
+    fn description_title() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Description"
+    }
+
     /// Create the tree items for the view
     ///
     /// # Errors
@@ -12,18 +12,18 @@ use ratatui::{
     prelude::Rect,
     style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, LineGauge},
+    widgets::{Block, Borders},
 };
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
     state::{
-        action::{Action, AudioAction, ComponentAction, PlaybackAction, VolumeAction},
+        action::{Action, AudioAction, ComponentAction, PlaybackAction, PopupAction, VolumeAction},
         component::ActiveComponent,
     },
-    ui::colors::{
-        BORDER_FOCUSED, BORDER_UNFOCUSED, GAUGE_FILLED, GAUGE_UNFILLED, TEXT_HIGHLIGHT_ALT,
-        TEXT_NORMAL,
+    ui::{
+        colors::{BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT_ALT, TEXT_NORMAL},
+        widgets::{popups::PopupType, progress_bar::ProgressBarWidget},
     },
 };
 
@@ -40,9 +40,11 @@ pub struct Props {
     pub(crate) is_playing: bool,
     pub(crate) muted: bool,
     pub(crate) volume: f32,
+    pub(crate) gain_db: f32,
     pub(crate) song_runtime: Option<StateRuntime>,
     pub(crate) song_title: Option<String>,
     pub(crate) song_artist: Option<String>,
+    pub(crate) song_bookmarks: Vec<Duration>,
 }
 
 impl From<&AppState> for Props {
@@ -52,6 +54,7 @@ impl From<&AppState> for Props {
             is_playing: !value.paused,
             muted: value.muted,
             volume: value.volume,
+            gain_db: value.gain_db,
             song_runtime: value.runtime,
             song_title: value
                 .current_song
@@ -64,6 +67,11 @@ impl From<&AppState> for Props {
                     .collect::<Vec<String>>()
                     .join(", ")
             }),
+            song_bookmarks: value
+                .current_song
+                .as_ref()
+                .map(|song| song.bookmark_positions.clone())
+                .unwrap_or_default(),
         }
     }
 }
@@ -130,6 +138,36 @@ impl Component for ControlPanel {
                     ))))
                     .unwrap();
             }
+            KeyCode::Char('b') => {
+                if let Some(bookmark) = self.props.song_runtime.and_then(|runtime| {
+                    ProgressBarWidget::next_bookmark(
+                        &self.props.song_bookmarks,
+                        runtime.seek_position,
+                    )
+                }) {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Playback(PlaybackAction::Seek(
+                            SeekType::Absolute,
+                            bookmark,
+                        ))))
+                        .unwrap();
+                }
+            }
+            KeyCode::Char('B') => {
+                if let Some(bookmark) = self.props.song_runtime.and_then(|runtime| {
+                    ProgressBarWidget::previous_bookmark(
+                        &self.props.song_bookmarks,
+                        runtime.seek_position,
+                    )
+                }) {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Playback(PlaybackAction::Seek(
+                            SeekType::Absolute,
+                            bookmark,
+                        ))))
+                        .unwrap();
+                }
+            }
             KeyCode::Char('+' | '=') => {
                 self.action_tx
                     .send(Action::Audio(AudioAction::Playback(
@@ -151,6 +189,12 @@ impl Component for ControlPanel {
                     )))
                     .unwrap();
             }
+            // open the equalizer
+            KeyCode::Char('e') => {
+                self.action_tx
+                    .send(Action::Popup(PopupAction::Open(PopupType::Equalizer)))
+                    .unwrap();
+            }
             // ignore other keys
             _ => {}
         }
@@ -178,13 +222,7 @@ impl Component for ControlPanel {
         } = split_area(area);
 
         // adjust song_progress area to exclude the runtime label
-        let runtime_string_len =
-            u16::try_from(runtime_string(self.props.song_runtime).len()).unwrap_or(u16::MAX);
-        let song_progress = Rect {
-            x: song_progress.x + runtime_string_len,
-            width: song_progress.width - runtime_string_len,
-            ..song_progress
-        };
+        let song_progress = ProgressBarWidget::new(self.props.song_runtime).bar_area(song_progress);
         // adjust play/pause area to only include the icon
         let play_pause = Rect {
             x: play_pause.x + play_pause.width - 3,
@@ -213,21 +251,20 @@ impl Component for ControlPanel {
                     .unwrap();
             }
             MouseEventKind::Down(MouseButton::Left) if song_progress.contains(mouse_position) => {
-                // calculate the ratio of the click position to the song progress bar
-                #[allow(clippy::cast_lossless)]
-                let ratio =
-                    (mouse_position.x - song_progress.x) as f64 / song_progress.width as f64;
-                self.action_tx
-                    .send(Action::Audio(AudioAction::Playback(PlaybackAction::Seek(
-                        SeekType::Absolute,
-                        Duration::from_secs_f64(
-                            self.props
-                                .song_runtime
-                                .map_or(0.0, |runtime| runtime.duration.as_secs_f64())
-                                * ratio,
-                        ),
-                    ))))
-                    .unwrap();
+                if let Some(seek_target) = self.props.song_runtime.and_then(|runtime| {
+                    ProgressBarWidget::handle_mouse_click(
+                        mouse_position,
+                        song_progress,
+                        runtime.duration,
+                    )
+                }) {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Playback(PlaybackAction::Seek(
+                            SeekType::Absolute,
+                            seek_target,
+                        ))))
+                        .unwrap();
+                }
             }
             MouseEventKind::Down(MouseButton::Left) if volume.contains(mouse_position) => {
                 self.action_tx
@@ -255,25 +292,14 @@ impl Component for ControlPanel {
     }
 }
 
-fn runtime_string(runtime: Option<StateRuntime>) -> String {
-    runtime.map_or_else(
-        || String::from("0.0/0.0"),
-        |runtime| {
-            format!(
-                "{}:{:04.1}/{}:{:04.1}",
-                runtime.seek_position.as_secs() / 60,
-                runtime.seek_position.as_secs_f32() % 60.0,
-                runtime.duration.as_secs() / 60,
-                runtime.duration.as_secs_f32() % 60.0
-            )
-        },
-    )
-}
-
 fn volume_string(muted: bool, volume: f32) -> String {
     format!(" {}: {:.1}", if muted { "🔇" } else { "🔊" }, volume * 100.)
 }
 
+fn gain_string(gain_db: f32) -> String {
+    format!("  G: {gain_db:+.0}dB")
+}
+
 #[derive(Debug)]
 struct Areas {
     song_info: Rect,
@@ -388,29 +414,27 @@ impl ComponentRender<RenderProps> for ControlPanel {
 
         // song progress
         frame.render_widget(
-            LineGauge::default()
-                .label(Line::from(runtime_string(self.props.song_runtime)))
-                .filled_style(Style::default().fg(GAUGE_FILLED.into()).bold())
-                .unfilled_style(Style::default().fg(GAUGE_UNFILLED.into()).bold())
-                .ratio(self.props.song_runtime.map_or(0.0, |runtime| {
-                    runtime.seek_position.as_secs_f64() / runtime.duration.as_secs_f64()
-                })),
+            ProgressBarWidget::new(self.props.song_runtime).bookmarks(&self.props.song_bookmarks),
             song_progress,
         );
 
         // volume indicator
         frame.render_widget(
             // muted icon if muted, otherwise a volume icon.
-            Line::from(volume_string(self.props.muted, self.props.volume))
-                .style(Style::default().bold().fg(TEXT_NORMAL.into()))
-                .alignment(Alignment::Left),
+            // the gain is shown alongside the volume, since it is independent of it
+            Line::from(vec![
+                Span::raw(volume_string(self.props.muted, self.props.volume)),
+                Span::raw(gain_string(self.props.gain_db)),
+            ])
+            .style(Style::default().bold().fg(TEXT_NORMAL.into()))
+            .alignment(Alignment::Left),
             volume,
         );
 
         // bottom (instructions)
         frame.render_widget(
             Line::from(
-                "n/p: next/previous | \u{2423}: play/pause | m: mute | +/-: volume | ←/→: seek",
+                "n/p: next/previous | \u{2423}: play/pause | m: mute | +/-: volume | ←/→: seek | b/B: bookmarks | e: equalizer",
             )
             .italic()
             .alignment(Alignment::Center),
@@ -1,6 +1,6 @@
 //! Implementation of the Queue Bar component, a scrollable list of the songs in the queue.
 
-use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use mecomp_core::state::RepeatMode;
 use mecomp_storage::db::schemas::song::Song;
 use ratatui::{
@@ -14,11 +14,15 @@ use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
     state::{
-        action::{Action, AudioAction, ComponentAction, QueueAction},
+        action::{Action, AudioAction, ComponentAction, PopupAction, QueueAction, ViewAction},
         component::ActiveComponent,
     },
-    ui::colors::{
-        BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT, TEXT_HIGHLIGHT_ALT, TEXT_NORMAL,
+    ui::{
+        colors::{
+            BORDER_FOCUSED, BORDER_UNFOCUSED, TEXT_HIGHLIGHT, TEXT_HIGHLIGHT_ALT, TEXT_NORMAL,
+        },
+        components::content_view::ActiveView,
+        widgets::popups::PopupType,
     },
 };
 
@@ -88,6 +92,34 @@ impl Component for QueueBar {
 
     fn handle_key_event(&mut self, key: KeyEvent) {
         match key.code {
+            // Move the selected song up in the queue
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                if let Some(index) = self.list_state.selected().filter(|&index| index > 0) {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Queue(QueueAction::Move(
+                            index,
+                            index - 1,
+                        ))))
+                        .unwrap();
+                    self.list_state.select(Some(index - 1));
+                }
+            }
+            // Move the selected song down in the queue
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                if let Some(index) = self
+                    .list_state
+                    .selected()
+                    .filter(|&index| index + 1 < self.props.queue.len())
+                {
+                    self.action_tx
+                        .send(Action::Audio(AudioAction::Queue(QueueAction::Move(
+                            index,
+                            index + 1,
+                        ))))
+                        .unwrap();
+                    self.list_state.select(Some(index + 1));
+                }
+            }
             // Move the selected index up
             KeyCode::Up => {
                 if let Some(index) = self.list_state.selected() {
@@ -142,6 +174,30 @@ impl Component for QueueBar {
                     .send(Action::Audio(AudioAction::Queue(QueueAction::Shuffle)))
                     .unwrap();
             }
+            // remove duplicate songs from the queue
+            KeyCode::Char('D') => {
+                self.action_tx
+                    .send(Action::Audio(AudioAction::Queue(QueueAction::Dedup)))
+                    .unwrap();
+            }
+            // save the queue as a playlist
+            KeyCode::Char('S') => {
+                self.action_tx
+                    .send(Action::Popup(PopupAction::Open(PopupType::SaveQueue)))
+                    .unwrap();
+            }
+            // open the playback history
+            KeyCode::Char('H') => {
+                self.action_tx
+                    .send(Action::ActiveView(ViewAction::Set(ActiveView::History)))
+                    .unwrap();
+            }
+            // open the lyrics of the current song
+            KeyCode::Char('L') => {
+                self.action_tx
+                    .send(Action::ActiveView(ViewAction::Set(ActiveView::Lyrics)))
+                    .unwrap();
+            }
             // set the repeat mode
             KeyCode::Char('r') => match self.props.repeat_mode {
                 RepeatMode::None => {
@@ -215,7 +271,7 @@ impl ComponentRender<RenderProps> for QueueBar {
                 [
                     Constraint::Length(2),
                     Constraint::Min(0),
-                    Constraint::Length(3),
+                    Constraint::Length(6),
                 ]
                 .as_ref(),
             )
@@ -254,6 +310,9 @@ impl ComponentRender<RenderProps> for QueueBar {
                 Line::from("↑/↓: Move | c: Clear"),
                 Line::from("\u{23CE} : Select | d: Delete"),
                 Line::from("s: Shuffle | r: Repeat"),
+                Line::from("S: Save as Playlist | D: Dedup"),
+                Line::from("⇧↑/⇧↓: Reorder | H: History"),
+                Line::from("L: Lyrics"),
             ]))
             .style(Style::default().fg(TEXT_NORMAL.into()))
             .alignment(ratatui::layout::Alignment::Center),
@@ -5,11 +5,12 @@
 use std::fmt::Display;
 
 use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use mecomp_core::state::library::ScanProgress;
 use ratatui::{
-    layout::{Alignment, Margin, Position, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{Block, Borders, LineGauge, List, ListItem, ListState},
     Frame,
 };
 use tokio::sync::mpsc::UnboundedSender;
@@ -35,6 +36,8 @@ pub struct Sidebar {
     pub action_tx: UnboundedSender<Action>,
     /// List state
     list_state: ListState,
+    /// The progress of the library rescan in progress, if there is one
+    scan_progress: Option<ScanProgress>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -102,21 +105,25 @@ const SIDEBAR_ITEMS: [SidebarItem; 11] = [
 ];
 
 impl Component for Sidebar {
-    fn new(_state: &AppState, action_tx: UnboundedSender<Action>) -> Self
+    fn new(state: &AppState, action_tx: UnboundedSender<Action>) -> Self
     where
         Self: Sized,
     {
         Self {
             action_tx,
             list_state: ListState::default(),
+            scan_progress: state.scan_progress.clone(),
         }
     }
 
-    fn move_with_state(self, _state: &AppState) -> Self
+    fn move_with_state(self, state: &AppState) -> Self
     where
         Self: Sized,
     {
-        self
+        Self {
+            scan_progress: state.scan_progress.clone(),
+            ..self
+        }
     }
 
     fn name(&self) -> &str {
@@ -241,6 +248,30 @@ impl ComponentRender<RenderProps> for Sidebar {
     }
 
     fn render_content(&self, frame: &mut Frame, props: RenderProps) {
+        // while the library is being rescanned, show the progress below the items
+        let area = if let Some(scan_progress) = &self.scan_progress {
+            let [list_area, progress_area] = *Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(props.area)
+            else {
+                panic!("Failed to split sidebar area")
+            };
+
+            frame.render_widget(
+                LineGauge::default()
+                    .ratio(scan_progress.ratio())
+                    .label(format!("Rescan {:.0}%", scan_progress.ratio() * 100.0))
+                    .style(Style::default().fg(TEXT_NORMAL.into()))
+                    .filled_style(Style::default().fg(TEXT_HIGHLIGHT.into())),
+                progress_area,
+            );
+
+            list_area
+        } else {
+            props.area
+        };
+
         let items = SIDEBAR_ITEMS
             .iter()
             .map(|item| {
@@ -259,7 +290,7 @@ impl ComponentRender<RenderProps> for Sidebar {
                         .add_modifier(Modifier::BOLD),
                 )
                 .direction(ratatui::widgets::ListDirection::TopToBottom),
-            props.area,
+            area,
             &mut self.list_state.clone(),
         );
     }
@@ -329,6 +360,49 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_sidebar_render_scan_progress() -> Result<()> {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let sidebar = Sidebar::new(&AppState::default(), tx).move_with_state(&AppState {
+            active_component: ActiveComponent::Sidebar,
+            scan_progress: Some(ScanProgress {
+                scanned: 21,
+                total: 42,
+                current_file: "test.mp3".into(),
+                errors: 0,
+            }),
+            ..state_with_everything()
+        });
+
+        let (mut terminal, area) = setup_test_terminal(19, 15);
+        let props = RenderProps {
+            area,
+            is_focused: true,
+        };
+        let buffer = terminal.draw(|frame| sidebar.render(frame, props))?.buffer;
+        let expected = Buffer::with_lines([
+            "┌Sidebar──────────┐",
+            "│Search           │",
+            "│                 │",
+            "│Songs            │",
+            "│Artists          │",
+            "│Albums           │",
+            "│Playlists        │",
+            "│Collections      │",
+            "│                 │",
+            "│Library Rescan   │",
+            "│Library Analyze  │",
+            "│Library Recluster│",
+            "│Rescan 50% ──────│",
+            "│────↑/↓: Move────│",
+            "└──Enter: Select──┘",
+        ]);
+
+        assert_buffer_eq(buffer, &expected);
+
+        Ok(())
+    }
+
     #[test]
     fn test_navigation_wraps() {
         let (tx, _) = tokio::sync::mpsc::unbounded_channel();
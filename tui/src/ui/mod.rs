@@ -20,8 +20,8 @@ use app::App;
 use components::{
     content_view::{
         views::{
-            AlbumViewProps, ArtistViewProps, CollectionViewProps, PlaylistViewProps,
-            RadioViewProps, SongViewProps, ViewData,
+            AlbumViewProps, ArtistViewProps, CollectionViewProps, LyricsViewProps,
+            PlaylistViewProps, RadioViewProps, SongViewProps, ViewData,
         },
         ActiveView,
     },
@@ -37,7 +37,10 @@ use crossterm::{
 };
 use mecomp_core::{
     rpc::{MusicPlayerClient, SearchResult},
-    state::{library::LibraryFull, StateAudio},
+    state::{
+        library::{LibraryFull, ScanProgress},
+        StateAudio,
+    },
 };
 use mecomp_storage::db::schemas::{album, artist, collection, playlist, song, Thing};
 use one_or_many::OneOrMany;
@@ -62,6 +65,8 @@ pub struct AppState {
     pub library: LibraryFull,
     pub active_view: ActiveView,
     pub additional_view_data: ViewData,
+    /// The progress of the library rescan in progress, if there is one
+    pub scan_progress: Option<ScanProgress>,
 }
 
 const RENDERING_TICK_RATE: Duration = Duration::from_millis(250);
@@ -100,6 +105,7 @@ impl UiManager {
             library: state_rx.library.recv().await.unwrap_or_default(),
             active_view: state_rx.view.recv().await.unwrap_or_default(),
             additional_view_data: ViewData::default(),
+            scan_progress: None,
         };
         let mut app = App::new(&state, self.action_tx.clone());
 
@@ -126,11 +132,21 @@ impl UiManager {
                 },
                 // Handle state updates
                 Some(audio) = state_rx.audio.recv() => {
+                    let song_changed = audio.current_song.as_ref().map(|song| &song.id)
+                        != state.audio.current_song.as_ref().map(|song| &song.id);
                     state = AppState {
                         audio,
                         ..state
                     };
                     app = app.move_with_audio(&state);
+
+                    // the lyrics view shows the lyrics of the current song, so fetch the new song's lyrics
+                    if song_changed && state.active_view == ActiveView::Lyrics {
+                        if let Some(additional_view_data) = handle_additional_view_data(daemon.clone(), &state, &state.active_view).await {
+                            state.additional_view_data = additional_view_data;
+                        }
+                        app = app.move_with_view(&state);
+                    }
                 },
                 Some(search) = state_rx.search.recv() => {
                     state = AppState {
@@ -148,6 +164,13 @@ impl UiManager {
                     };
                     app = app.move_with_library(&state);
                 },
+                Some(scan_progress) = state_rx.scan_progress.recv() => {
+                    state = AppState {
+                        scan_progress,
+                        ..state
+                    };
+                    app = app.move_with_scan_progress(&state);
+                },
                 Some(active_view) = state_rx.view.recv() => {
                     // update view_data
                     let additional_view_data = handle_additional_view_data(daemon.clone(), &state, &active_view).await.unwrap_or(state.additional_view_data);
@@ -221,18 +244,66 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> anyhow
     Ok(terminal.show_cursor()?)
 }
 
+/// How long the panic hook waits for the daemon to report the queue before giving up on saving it.
+const CRASH_RECOVERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Install a panic hook that restores the terminal, and saves the queue to the crash-recovery file
+/// (see [`crate::crash_recovery`]) so it can be restored on the next startup.
+///
+/// `rpc_port` is the port of the daemon to get the queue from.
 #[cfg(not(tarpaulin_include))]
-pub fn init_panic_hook() {
+pub fn init_panic_hook(rpc_port: u16) {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
         // intentionally ignore errors here since we're already in a panic
         let _ = disable_raw_mode();
         let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
 
+        if let (Some(queue), Ok(path)) = (
+            fetch_queue_for_recovery(rpc_port),
+            crate::crash_recovery::crash_recovery_file(),
+        ) {
+            if !queue.is_empty() && crate::crash_recovery::save_queue(&path, &queue).is_ok() {
+                eprintln!("saved the queue to {}", path.display());
+            }
+        }
+
         original_hook(panic_info);
     }));
 }
 
+/// Ask the daemon for the current queue, waiting at most [`CRASH_RECOVERY_TIMEOUT`].
+///
+/// The panic may have happened inside of the tokio runtime, so we can't block on it here,
+/// instead the request is made from a new thread with its own runtime.
+#[cfg(not(tarpaulin_include))]
+fn fetch_queue_for_recovery(rpc_port: u16) -> Option<Box<[song::Song]>> {
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .ok()?;
+
+        runtime.block_on(async {
+            tokio::time::timeout(CRASH_RECOVERY_TIMEOUT, async {
+                let client = mecomp_core::rpc::init_client(rpc_port).await.ok()?;
+                client
+                    .state_audio(tarpc::context::current())
+                    .await
+                    .ok()
+                    .flatten()
+            })
+            .await
+            .ok()
+            .flatten()
+            .map(|state| state.queue)
+        })
+    })
+    .join()
+    .ok()
+    .flatten()
+}
+
 /// Returns `None` if new data is not needed
 #[allow(clippy::too_many_lines)]
 async fn handle_additional_view_data(
@@ -359,14 +430,20 @@ async fn handle_additional_view_data(
                 id: id.to_owned(),
             };
 
-            let collection_view_props = if let Ok((Some(collection), Some(songs))) = tokio::try_join!(
+            let collection_view_props = if let Ok((Some(collection), Some(songs), stats)) = tokio::try_join!(
                 daemon.collection_get(Context::current(), collection_id.clone()),
                 daemon.collection_get_songs(Context::current(), collection_id.clone()),
+                daemon.library_collection_get_analysis_stats(
+                    Context::current(),
+                    collection_id.clone()
+                ),
             ) {
                 Some(CollectionViewProps {
                     id: collection_id,
                     collection,
                     songs,
+                    // the stats are optional, so don't fail to show the collection if they couldn't be computed
+                    stats: stats.ok().flatten(),
                 })
             } else {
                 None
@@ -395,12 +472,43 @@ async fn handle_additional_view_data(
                 ..state.additional_view_data.clone()
             })
         }
+        ActiveView::Songs => {
+            // the statistics are just a summary, so don't fail to show the songs if they couldn't be computed
+            let statistics = daemon
+                .library_statistics(Context::current())
+                .await
+                .ok()
+                .and_then(Result::ok);
+
+            Some(ViewData {
+                statistics,
+                ..state.additional_view_data.clone()
+            })
+        }
+        ActiveView::Lyrics => {
+            let lyrics_view_props = match &state.audio.current_song {
+                Some(song) => daemon
+                    .library_song_get_lyrics(Context::current(), song.id.clone().into())
+                    .await
+                    .ok()
+                    .map(|lyrics| LyricsViewProps {
+                        id: song.id.clone(),
+                        lyrics: lyrics.map(Into::into),
+                    }),
+                None => None,
+            };
+
+            Some(ViewData {
+                lyrics: lyrics_view_props,
+                ..state.additional_view_data.clone()
+            })
+        }
         ActiveView::None
         | ActiveView::Search
-        | ActiveView::Songs
         | ActiveView::Albums
         | ActiveView::Artists
         | ActiveView::Playlists
-        | ActiveView::Collections => None,
+        | ActiveView::Collections
+        | ActiveView::History => None,
     }
 }
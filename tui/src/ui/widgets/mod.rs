@@ -1,3 +1,4 @@
 pub mod input_box;
 pub mod popups;
+pub mod progress_bar;
 pub mod tree;
@@ -0,0 +1,456 @@
+//! Popups for managing collections.
+//!
+//! - [`CollectionRenamer`] prompts the user for a new name for a collection.
+//! - [`CollectionSelector`] prompts the user to select the collection another collection should be merged into.
+//!
+//! The user can cancel either popup by pressing the escape key.
+
+use std::sync::Mutex;
+
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use mecomp_storage::db::schemas::{collection::Collection, Thing};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Scrollbar, ScrollbarOrientation},
+    Frame,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    state::action::{Action, LibraryAction, PopupAction},
+    ui::{
+        colors::{BORDER_FOCUSED, TEXT_HIGHLIGHT, TEXT_HIGHLIGHT_ALT},
+        components::{
+            content_view::views::{
+                checktree_utils::create_collection_tree_leaf, sort_mode::NameSort, traits::SortMode,
+            },
+            Component, ComponentRender,
+        },
+        widgets::{
+            input_box::{InputBox, RenderProps},
+            tree::{state::CheckTreeState, CheckTree},
+        },
+        AppState,
+    },
+};
+
+use super::Popup;
+
+/// A popup that prompts the user for a new name for a collection.
+#[derive(Debug)]
+pub struct CollectionRenamer {
+    /// Action Sender
+    action_tx: UnboundedSender<Action>,
+    /// The collection to rename
+    collection: Thing,
+    /// Collection Name Input Box
+    input_box: InputBox,
+}
+
+impl CollectionRenamer {
+    #[must_use]
+    pub fn new(state: &AppState, action_tx: UnboundedSender<Action>, collection: Thing) -> Self {
+        let mut input_box = InputBox::new(state, action_tx.clone());
+        // start with the current name, so small edits are easy
+        if let Some(current) = state
+            .library
+            .collections
+            .iter()
+            .find(|c| Thing::from(c.id.clone()) == collection)
+        {
+            input_box.set_text(&current.name);
+        }
+
+        Self {
+            action_tx,
+            collection,
+            input_box,
+        }
+    }
+}
+
+impl Popup for CollectionRenamer {
+    fn title(&self) -> Line<'_> {
+        Line::from("Rename Collection")
+    }
+
+    fn instructions(&self) -> Line<'_> {
+        Line::from(" \u{23CE} : Rename (cancel if empty)")
+    }
+
+    fn update_with_state(&mut self, _: &AppState) {}
+
+    fn area(&self, terminal_area: Rect) -> Rect {
+        let [_, horizontal_area, _] = *Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Min(31),
+                Constraint::Percentage(25),
+            ])
+            .split(terminal_area)
+        else {
+            panic!("Failed to split horizontal area");
+        };
+
+        let [_, area, _] = *Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(5),
+                Constraint::Min(0),
+            ])
+            .split(horizontal_area)
+        else {
+            panic!("Failed to split vertical area");
+        };
+        area
+    }
+
+    fn inner_handle_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            // if the user presses Enter, we rename the collection (unless the name is empty)
+            KeyCode::Enter => {
+                let name = self.input_box.text();
+                if !name.is_empty() {
+                    self.action_tx
+                        .send(Action::Library(LibraryAction::RenameCollection(
+                            self.collection.clone(),
+                            name.to_string(),
+                        )))
+                        .unwrap();
+                }
+                self.action_tx
+                    .send(Action::Popup(PopupAction::Close))
+                    .unwrap();
+            }
+            // defer to the input box
+            _ => self.input_box.handle_key_event(key),
+        }
+    }
+
+    fn inner_handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) {
+        // the input box fills the area inside the popup's border
+        self.input_box
+            .handle_mouse_event(mouse, Block::bordered().inner(area));
+    }
+}
+
+impl ComponentRender<Rect> for CollectionRenamer {
+    fn render_border(&self, frame: &mut Frame, area: Rect) -> Rect {
+        self.render_popup_border(frame, area)
+    }
+
+    fn render_content(&self, frame: &mut Frame, area: Rect) {
+        self.input_box.render(
+            frame,
+            RenderProps {
+                area,
+                text_color: TEXT_HIGHLIGHT_ALT.into(),
+                border: Block::bordered()
+                    .title("Enter Name:")
+                    .border_style(Style::default().fg(BORDER_FOCUSED.into())),
+                show_cursor: true,
+            },
+        );
+    }
+}
+
+/// A popup that prompts the user to select the collection that another collection should be merged into.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct CollectionSelector {
+    /// Action Sender
+    action_tx: UnboundedSender<Action>,
+    /// The collection being merged
+    source: Thing,
+    /// The collections that can be merged into (every collection except the source)
+    collections: Box<[Collection]>,
+    /// tree state
+    tree_state: Mutex<CheckTreeState<String>>,
+}
+
+impl CollectionSelector {
+    #[must_use]
+    pub fn new(state: &AppState, action_tx: UnboundedSender<Action>, source: Thing) -> Self {
+        Self {
+            action_tx,
+            collections: Self::targets(state, &source),
+            source,
+            tree_state: Mutex::new(CheckTreeState::default()),
+        }
+    }
+
+    fn targets(state: &AppState, source: &Thing) -> Box<[Collection]> {
+        let mut collections = state
+            .library
+            .collections
+            .iter()
+            .filter(|c| &Thing::from(c.id.clone()) != source)
+            .cloned()
+            .collect::<Box<[_]>>();
+        NameSort::default().sort_items(&mut collections);
+        collections
+    }
+}
+
+impl Popup for CollectionSelector {
+    fn title(&self) -> Line<'_> {
+        Line::from("Merge into Collection")
+    }
+
+    fn instructions(&self) -> Line<'_> {
+        Line::from("  \u{23CE} : Select | ↑/↓: Up/Down")
+    }
+
+    fn update_with_state(&mut self, state: &AppState) {
+        self.collections = Self::targets(state, &self.source);
+    }
+
+    fn area(&self, terminal_area: Rect) -> Rect {
+        let [_, horizontal_area, _] = *Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(50),
+                Constraint::Min(31),
+                Constraint::Percentage(19),
+            ])
+            .split(terminal_area)
+        else {
+            panic!("Failed to split horizontal area");
+        };
+
+        let [_, area, _] = *Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Max(10),
+                Constraint::Min(10),
+                Constraint::Max(10),
+            ])
+            .split(horizontal_area)
+        else {
+            panic!("Failed to split vertical area");
+        };
+        area
+    }
+
+    fn inner_handle_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            // arrow keys
+            KeyCode::PageUp => {
+                self.tree_state.lock().unwrap().select_relative(|current| {
+                    current.map_or(self.collections.len() - 1, |c| c.saturating_sub(10))
+                });
+            }
+            KeyCode::Up => {
+                self.tree_state.lock().unwrap().key_up();
+            }
+            KeyCode::PageDown => {
+                self.tree_state
+                    .lock()
+                    .unwrap()
+                    .select_relative(|current| current.map_or(0, |c| c.saturating_add(10)));
+            }
+            KeyCode::Down => {
+                self.tree_state.lock().unwrap().key_down();
+            }
+            // Enter key merges the source into the selected collection
+            // and closes the popup
+            KeyCode::Enter => {
+                let target = self.tree_state.lock().unwrap().get_selected_thing();
+
+                if let Some(target) = target {
+                    self.action_tx
+                        .send(Action::Library(LibraryAction::MergeCollections(
+                            self.source.clone(),
+                            target,
+                        )))
+                        .unwrap();
+                    self.action_tx
+                        .send(Action::Popup(PopupAction::Close))
+                        .unwrap();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Mouse Event Handler for the inner component of the popup,
+    /// when an item in the list is clicked, it will be selected.
+    fn inner_handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) {
+        let MouseEvent {
+            kind, column, row, ..
+        } = mouse;
+        let mouse_position = Position::new(column, row);
+
+        // adjust the area to account for the border
+        let area = area.inner(Margin::new(1, 1));
+
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) if area.contains(mouse_position) => {
+                self.tree_state.lock().unwrap().mouse_click(mouse_position);
+            }
+            MouseEventKind::ScrollDown if area.contains(mouse_position) => {
+                self.tree_state.lock().unwrap().key_down();
+            }
+            MouseEventKind::ScrollUp if area.contains(mouse_position) => {
+                self.tree_state.lock().unwrap().key_up();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ComponentRender<Rect> for CollectionSelector {
+    fn render_border(&self, frame: &mut Frame, area: Rect) -> Rect {
+        self.render_popup_border(frame, area)
+    }
+
+    fn render_content(&self, frame: &mut Frame, area: Rect) {
+        // create a tree for the collections
+        let collections = self
+            .collections
+            .iter()
+            .map(create_collection_tree_leaf)
+            .collect::<Vec<_>>();
+
+        // render the collections
+        frame.render_stateful_widget(
+            CheckTree::new(&collections)
+                .unwrap()
+                .highlight_style(Style::default().fg(TEXT_HIGHLIGHT.into()).bold())
+                // we want this to be rendered like a normal tree, not a check tree, so we don't show the checkboxes
+                .node_unchecked_symbol("▪ ")
+                .node_checked_symbol("▪ ")
+                .experimental_scrollbar(Some(Scrollbar::new(ScrollbarOrientation::VerticalRight))),
+            area,
+            &mut self.tree_state.lock().unwrap(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        state::component::ActiveComponent,
+        test_utils::setup_test_terminal,
+        ui::components::content_view::{views::ViewData, ActiveView},
+    };
+    use mecomp_core::{
+        rpc::SearchResult,
+        state::{library::LibraryFull, StateAudio},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::{fixture, rstest};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn collection(name: &str) -> Collection {
+        Collection {
+            id: Collection::generate_id(),
+            name: name.into(),
+            runtime: Duration::default(),
+            song_count: 0,
+        }
+    }
+
+    #[fixture]
+    fn state() -> AppState {
+        AppState {
+            active_component: ActiveComponent::default(),
+            audio: StateAudio::default(),
+            search: SearchResult::default(),
+            library: LibraryFull {
+                collections: vec![collection("Collection 0"), collection("Collection 1")]
+                    .into_boxed_slice(),
+                ..Default::default()
+            },
+            active_view: ActiveView::default(),
+            additional_view_data: ViewData::default(),
+            scan_progress: None,
+        }
+    }
+
+    #[rstest]
+    fn test_collection_renamer_prefills_name(state: AppState) {
+        let id: Thing = state.library.collections[0].id.clone().into();
+        let popup = CollectionRenamer::new(&state, unbounded_channel().0, id);
+        assert_eq!(popup.input_box.text(), "Collection 0");
+    }
+
+    #[rstest]
+    fn test_collection_renamer_rename(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let id: Thing = state.library.collections[0].id.clone().into();
+        let mut popup = CollectionRenamer::new(&state, action_tx, id.clone());
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Char('!')));
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Library(LibraryAction::RenameCollection(
+                id,
+                "Collection 0!".to_string()
+            ))
+        );
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Popup(PopupAction::Close)
+        );
+    }
+
+    #[rstest]
+    fn test_collection_renamer_empty_name_cancels(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let id: Thing = state.library.collections[0].id.clone().into();
+        let mut popup = CollectionRenamer::new(&state, action_tx, id);
+
+        popup.input_box.reset();
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Popup(PopupAction::Close)
+        );
+        assert!(action_rx.try_recv().is_err());
+    }
+
+    #[rstest]
+    fn test_collection_selector_excludes_source(state: AppState) {
+        let source: Thing = state.library.collections[0].id.clone().into();
+        let popup = CollectionSelector::new(&state, unbounded_channel().0, source);
+        assert_eq!(popup.collections.as_ref(), &state.library.collections[1..]);
+    }
+
+    #[rstest]
+    fn test_collection_selector_merge(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let source: Thing = state.library.collections[0].id.clone().into();
+        let target: Thing = state.library.collections[1].id.clone().into();
+        let mut popup = CollectionSelector::new(&state, action_tx, source.clone());
+
+        // need to render the popup at least once to load the tree state
+        let (mut terminal, _) = setup_test_terminal(31, 10);
+        terminal.draw(|frame| popup.render_popup(frame)).unwrap();
+
+        // nothing is selected yet, so nothing happens
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(action_rx.try_recv().is_err());
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Down));
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Library(LibraryAction::MergeCollections(source, target))
+        );
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Popup(PopupAction::Close)
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! A popup that shows the bands of the equalizer as vertical sliders, and lets the user adjust them.
+//!
+//! Every change is sent to the daemon immediately, so the user can hear the effect of the change while they make it.
+
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use mecomp_core::state::equalizer::{flat, EqBand, MAX_GAIN_DB, MIN_GAIN_DB};
+use ratatui::{
+    layout::{Position, Rect},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::Block,
+    Frame,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    state::action::{Action, AudioAction, PlaybackAction},
+    ui::{
+        colors::{TEXT_HIGHLIGHT, TEXT_NORMAL},
+        components::ComponentRender,
+        AppState,
+    },
+};
+
+use super::Popup;
+
+/// How many columns each band takes up.
+const BAND_WIDTH: u16 = 6;
+/// How much the gain of a band changes with each key press (or scroll), in decibels.
+const GAIN_STEP_DB: f32 = 1.0;
+/// How many decibels each row of a slider represents.
+const ROW_DB: f32 = 2.0;
+/// How many rows the sliders take up, enough to show the full range of gains (with a row for 0 dB in the middle).
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const SLIDER_HEIGHT: u16 = ((MAX_GAIN_DB - MIN_GAIN_DB) / ROW_DB) as u16 + 1;
+
+/// A popup that shows the bands of the equalizer as vertical sliders, and lets the user adjust them.
+#[derive(Debug)]
+pub struct EqualizerPopup {
+    /// Action Sender
+    action_tx: UnboundedSender<Action>,
+    /// The bands being shown (and adjusted)
+    bands: Vec<EqBand>,
+    /// The bands the daemon last reported, so we only overwrite the user's changes when the daemon's bands change
+    synced: Box<[EqBand]>,
+    /// The index of the selected band
+    selected: usize,
+    /// The index of the preset that will be applied next
+    next_preset: usize,
+}
+
+impl EqualizerPopup {
+    #[must_use]
+    pub fn new(state: &AppState, action_tx: UnboundedSender<Action>) -> Self {
+        let mut popup = Self {
+            action_tx,
+            bands: Vec::new(),
+            synced: Box::new([]),
+            selected: 0,
+            next_preset: 0,
+        };
+        popup.sync(&state.audio.equalizer);
+        popup
+    }
+
+    /// Show the given bands (or a flat equalizer if there are none).
+    fn sync(&mut self, bands: &[EqBand]) {
+        self.synced = bands.into();
+        self.bands = if bands.is_empty() {
+            flat()
+        } else {
+            bands.to_vec()
+        };
+        self.selected = self.selected.min(self.bands.len().saturating_sub(1));
+    }
+
+    /// Set the gain of the selected band (clamped to the allowed range), and send the new bands to the daemon.
+    fn set_gain(&mut self, gain_db: f32) {
+        if let Some(band) = self.bands.get_mut(self.selected) {
+            band.gain_db = gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB);
+            self.send_bands();
+        }
+    }
+
+    /// Change the gain of the selected band by the given amount.
+    fn adjust_gain(&mut self, delta_db: f32) {
+        if let Some(band) = self.bands.get(self.selected) {
+            self.set_gain(band.gain_db + delta_db);
+        }
+    }
+
+    fn send_bands(&self) {
+        self.action_tx
+            .send(Action::Audio(AudioAction::Playback(
+                PlaybackAction::SetEqualizer(self.bands.clone()),
+            )))
+            .ok();
+    }
+
+    /// The gain represented by the given row of the sliders (0 is the top row).
+    fn row_gain(row: u16) -> f32 {
+        MAX_GAIN_DB - f32::from(row) * ROW_DB
+    }
+}
+
+/// A short label for the given frequency, e.g. "125" or "16k".
+fn frequency_label(frequency_hz: f32) -> String {
+    if frequency_hz >= 1000.0 {
+        format!("{}k", (frequency_hz / 1000.0).round())
+    } else {
+        format!("{}", frequency_hz.round())
+    }
+}
+
+impl Popup for EqualizerPopup {
+    fn title(&self) -> Line {
+        Line::from("Equalizer")
+    }
+
+    fn instructions(&self) -> Line {
+        Line::from(" \u{2190}/\u{2192}: band | \u{2191}/\u{2193}: gain | r: reset | p: preset")
+    }
+
+    fn update_with_state(&mut self, state: &AppState) {
+        if *state.audio.equalizer != *self.synced {
+            self.sync(&state.audio.equalizer);
+        }
+    }
+
+    fn area(&self, terminal_area: Rect) -> Rect {
+        // one column per band, plus the border
+        let width = u16::try_from(self.bands.len())
+            .unwrap_or(u16::MAX)
+            .saturating_mul(BAND_WIDTH)
+            .max(u16::try_from(self.instructions().width()).unwrap_or(u16::MAX))
+            .saturating_add(2)
+            .min(terminal_area.width);
+        // the sliders, the gain labels above them, the frequency labels below them, and the border
+        let height = (SLIDER_HEIGHT + 4).min(terminal_area.height);
+
+        let x = terminal_area.x + (terminal_area.width - width) / 2;
+        let y = terminal_area.y + (terminal_area.height - height) / 2;
+        Rect::new(x, y, width, height)
+    }
+
+    fn inner_handle_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Right => {
+                self.selected = (self.selected + 1).min(self.bands.len().saturating_sub(1));
+            }
+            KeyCode::Up => self.adjust_gain(GAIN_STEP_DB),
+            KeyCode::Down => self.adjust_gain(-GAIN_STEP_DB),
+            // reset all the bands to 0 dB
+            KeyCode::Char('r') => {
+                for band in &mut self.bands {
+                    band.gain_db = 0.0;
+                }
+                self.send_bands();
+            }
+            // apply the next preset, the bands will be updated when the daemon reports them
+            KeyCode::Char('p') => {
+                self.action_tx
+                    .send(Action::Audio(AudioAction::Playback(
+                        PlaybackAction::EqualizerPreset(self.next_preset),
+                    )))
+                    .ok();
+                self.next_preset = self.next_preset.wrapping_add(1);
+            }
+            _ => {}
+        }
+    }
+
+    fn inner_handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) {
+        let MouseEvent {
+            kind, column, row, ..
+        } = mouse;
+        let inner = Block::bordered().inner(area);
+        if !inner.contains(Position::new(column, row)) {
+            return;
+        }
+        let band = usize::from((column - inner.x) / BAND_WIDTH);
+
+        match kind {
+            // clicking on a slider selects the band, and sets its gain to the clicked row
+            MouseEventKind::Down(MouseButton::Left) if band < self.bands.len() => {
+                self.selected = band;
+                let slider_row = (row - inner.y).checked_sub(1);
+                if let Some(slider_row) = slider_row.filter(|&r| r < SLIDER_HEIGHT) {
+                    self.set_gain(Self::row_gain(slider_row));
+                }
+            }
+            MouseEventKind::ScrollUp => self.adjust_gain(GAIN_STEP_DB),
+            MouseEventKind::ScrollDown => self.adjust_gain(-GAIN_STEP_DB),
+            _ => {}
+        }
+    }
+}
+
+impl ComponentRender<Rect> for EqualizerPopup {
+    fn render_border(&self, frame: &mut Frame, area: Rect) -> Rect {
+        self.render_popup_border(frame, area)
+    }
+
+    fn render_content(&self, frame: &mut Frame, area: Rect) {
+        let width = usize::from(BAND_WIDTH);
+        let style = |index: usize| {
+            if index == self.selected {
+                Style::default().fg(TEXT_HIGHLIGHT.into())
+            } else {
+                Style::default().fg(TEXT_NORMAL.into())
+            }
+        };
+
+        let mut lines = Vec::with_capacity(usize::from(SLIDER_HEIGHT) + 2);
+        // the gain of each band
+        lines.push(Line::from(
+            self.bands
+                .iter()
+                .enumerate()
+                .map(|(i, band)| {
+                    Span::styled(
+                        format!("{:^width$}", format!("{:+}", band.gain_db)),
+                        style(i),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+        // the sliders, which fill from the 0 dB row towards the gain of the band
+        for row in 0..SLIDER_HEIGHT {
+            let row_gain = Self::row_gain(row);
+            lines.push(Line::from(
+                self.bands
+                    .iter()
+                    .enumerate()
+                    .map(|(i, band)| {
+                        let cell = if row_gain.abs() < f32::EPSILON {
+                            "\u{2500}".repeat(width)
+                        } else if (row_gain > 0.0 && band.gain_db >= row_gain)
+                            || (row_gain < 0.0 && band.gain_db <= row_gain)
+                        {
+                            format!("{:^width$}", "\u{2588}\u{2588}")
+                        } else {
+                            " ".repeat(width)
+                        };
+                        Span::styled(cell, style(i))
+                    })
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        // the frequency of each band
+        lines.push(Line::from(
+            self.bands
+                .iter()
+                .enumerate()
+                .map(|(i, band)| {
+                    Span::styled(
+                        format!("{:^width$}", frequency_label(band.frequency_hz)),
+                        style(i),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+
+        frame.render_widget(Text::from(lines), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::component::ActiveComponent,
+        test_utils::setup_test_terminal,
+        ui::components::content_view::{views::ViewData, ActiveView},
+    };
+    use mecomp_core::{
+        rpc::SearchResult,
+        state::{equalizer::builtin_preset, library::LibraryFull, StateAudio},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::{fixture, rstest};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[fixture]
+    fn state() -> AppState {
+        AppState {
+            active_component: ActiveComponent::default(),
+            audio: StateAudio::default(),
+            search: SearchResult::default(),
+            library: LibraryFull::default(),
+            active_view: ActiveView::default(),
+            additional_view_data: ViewData::default(),
+            scan_progress: None,
+        }
+    }
+
+    fn set_equalizer(gains_db: &[f32]) -> Action {
+        Action::Audio(AudioAction::Playback(PlaybackAction::SetEqualizer(
+            flat()
+                .into_iter()
+                .zip(gains_db)
+                .map(|(band, &gain_db)| EqBand { gain_db, ..band })
+                .collect(),
+        )))
+    }
+
+    #[rstest]
+    #[case::large((100, 100), Rect::new(19, 41, 62, 17))]
+    #[case::small((62, 17), Rect::new(0, 0, 62, 17))]
+    #[case::too_small((40, 10), Rect::new(0, 0, 40, 10))]
+    fn test_equalizer_area(
+        #[case] terminal_size: (u16, u16),
+        #[case] expected_area: Rect,
+        state: AppState,
+    ) {
+        let (_, area) = setup_test_terminal(terminal_size.0, terminal_size.1);
+        let area = EqualizerPopup::new(&state, unbounded_channel().0).area(area);
+        assert_eq!(area, expected_area);
+    }
+
+    #[rstest]
+    fn test_equalizer_defaults_to_flat(state: AppState) {
+        let popup = EqualizerPopup::new(&state, unbounded_channel().0);
+        assert_eq!(popup.bands, flat());
+    }
+
+    #[rstest]
+    fn test_equalizer_adjust_gain(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = EqualizerPopup::new(&state, action_tx);
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Right));
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Up));
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            set_equalizer(&[0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+        );
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Left));
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Left));
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Down));
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            set_equalizer(&[-1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+        );
+        assert!(action_rx.try_recv().is_err());
+    }
+
+    #[rstest]
+    fn test_equalizer_gain_is_clamped(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = EqualizerPopup::new(&state, action_tx);
+
+        for _ in 0..20 {
+            popup.inner_handle_key_event(KeyEvent::from(KeyCode::Up));
+        }
+        assert_eq!(popup.bands[0].gain_db, MAX_GAIN_DB);
+        for _ in 0..40 {
+            popup.inner_handle_key_event(KeyEvent::from(KeyCode::Down));
+        }
+        assert_eq!(popup.bands[0].gain_db, MIN_GAIN_DB);
+        let mut last = None;
+        while let Ok(action) = action_rx.try_recv() {
+            last = Some(action);
+        }
+        assert_eq!(
+            last.unwrap(),
+            set_equalizer(&[MIN_GAIN_DB, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+        );
+    }
+
+    #[rstest]
+    fn test_equalizer_reset(mut state: AppState) {
+        state.audio.equalizer = builtin_preset("bass_boost").unwrap().into();
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = EqualizerPopup::new(&state, action_tx);
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Char('r')));
+        assert_eq!(action_rx.try_recv().unwrap(), set_equalizer(&[0.0; 10]));
+    }
+
+    #[rstest]
+    fn test_equalizer_next_preset(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = EqualizerPopup::new(&state, action_tx);
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Char('p')));
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Char('p')));
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Audio(AudioAction::Playback(PlaybackAction::EqualizerPreset(0)))
+        );
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Audio(AudioAction::Playback(PlaybackAction::EqualizerPreset(1)))
+        );
+    }
+
+    #[rstest]
+    fn test_equalizer_update_with_state(mut state: AppState) {
+        let mut popup = EqualizerPopup::new(&state, unbounded_channel().0);
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Up));
+
+        // the user's changes are kept until the daemon reports different bands
+        popup.update_with_state(&state);
+        assert!((popup.bands[0].gain_db - 1.0).abs() < f32::EPSILON);
+
+        state.audio.equalizer = builtin_preset("vocal").unwrap().into();
+        popup.update_with_state(&state);
+        assert_eq!(popup.bands, builtin_preset("vocal").unwrap());
+    }
+
+    #[rstest]
+    fn test_equalizer_click_sets_gain(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = EqualizerPopup::new(&state, action_tx);
+        let area = Rect::new(0, 0, 62, 17);
+
+        // the second band, on the top row of the slider
+        popup.inner_handle_mouse_event(
+            MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 8,
+                row: 2,
+                modifiers: crossterm::event::KeyModifiers::empty(),
+            },
+            area,
+        );
+        assert_eq!(popup.selected, 1);
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            set_equalizer(&[0.0, MAX_GAIN_DB, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+        );
+    }
+
+    #[rstest]
+    fn test_equalizer_render(mut state: AppState) -> anyhow::Result<()> {
+        state.audio.equalizer = builtin_preset("bass_boost").unwrap().into();
+        let popup = EqualizerPopup::new(&state, unbounded_channel().0);
+        let (mut terminal, _) = setup_test_terminal(62, 17);
+        let buffer = terminal
+            .draw(|frame| popup.render_popup(frame))?
+            .buffer
+            .clone();
+
+        let text = buffer
+            .content()
+            .chunks(62)
+            .map(|row| {
+                row.iter()
+                    .map(ratatui::buffer::Cell::symbol)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            text,
+            [
+                "┌Equalizer───────────────────────────────────────────────────┐",
+                "│  +6    +5    +4    +2    +0    +0    +0    +0    +0    +0  │",
+                "│                                                            │",
+                "│                                                            │",
+                "│                                                            │",
+                "│  ██                                                        │",
+                "│  ██    ██    ██                                            │",
+                "│  ██    ██    ██    ██                                      │",
+                "│────────────────────────────────────────────────────────────│",
+                "│                                                            │",
+                "│                                                            │",
+                "│                                                            │",
+                "│                                                            │",
+                "│                                                            │",
+                "│                                                            │",
+                "│  32    64   125   250   500    1k    2k    4k    8k   16k  │",
+                "└ ←/→: band | ↑/↓: gain | r: reset | p: preset───────────────┘",
+            ]
+        );
+        Ok(())
+    }
+}
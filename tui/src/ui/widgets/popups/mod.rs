@@ -1,5 +1,8 @@
+pub mod collection;
+pub mod equalizer;
 pub mod notification;
 pub mod playlist;
+pub mod queue;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 use mecomp_storage::db::schemas::Thing;
@@ -108,6 +111,13 @@ pub enum PopupType {
     #[allow(dead_code)]
     Notification(Text<'static>),
     Playlist(Vec<Thing>),
+    SaveQueue,
+    /// Rename the given collection
+    RenameCollection(Thing),
+    /// Merge the given collection into another one
+    MergeCollection(Thing),
+    /// Adjust the equalizer
+    Equalizer,
 }
 
 impl PopupType {
@@ -124,6 +134,14 @@ impl PopupType {
             Self::Playlist(items) => {
                 Box::new(playlist::PlaylistSelector::new(state, action_tx, items)) as _
             }
+            Self::SaveQueue => Box::new(queue::QueueSaver::new(state, action_tx)) as _,
+            Self::RenameCollection(collection) => Box::new(collection::CollectionRenamer::new(
+                state, action_tx, collection,
+            )) as _,
+            Self::MergeCollection(source) => Box::new(collection::CollectionSelector::new(
+                state, action_tx, source,
+            )) as _,
+            Self::Equalizer => Box::new(equalizer::EqualizerPopup::new(state, action_tx)) as _,
         }
     }
 }
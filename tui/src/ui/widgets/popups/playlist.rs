@@ -361,6 +361,7 @@ mod tests {
             },
             active_view: ActiveView::default(),
             additional_view_data: ViewData::default(),
+            scan_progress: None,
         }
     }
 
@@ -0,0 +1,208 @@
+//! A popup that prompts the user for a name, then saves the queue as a new playlist with that name.
+//!
+//! The user can cancel the popup by pressing the escape key.
+
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::Block,
+    Frame,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    state::action::{Action, LibraryAction, PopupAction},
+    ui::{
+        colors::{BORDER_FOCUSED, TEXT_HIGHLIGHT_ALT},
+        components::{Component, ComponentRender},
+        widgets::input_box::{InputBox, RenderProps},
+        AppState,
+    },
+};
+
+use super::Popup;
+
+/// A popup that prompts the user for a name, then saves the queue as a new playlist with that name.
+#[derive(Debug)]
+pub struct QueueSaver {
+    /// Action Sender
+    action_tx: UnboundedSender<Action>,
+    /// Playlist Name Input Box
+    input_box: InputBox,
+}
+
+impl QueueSaver {
+    #[must_use]
+    pub fn new(state: &AppState, action_tx: UnboundedSender<Action>) -> Self {
+        Self {
+            input_box: InputBox::new(state, action_tx.clone()),
+            action_tx,
+        }
+    }
+}
+
+impl Popup for QueueSaver {
+    fn title(&self) -> Line {
+        Line::from("Save Queue as Playlist")
+    }
+
+    fn instructions(&self) -> Line {
+        Line::from(" \u{23CE} : Save (cancel if empty)")
+    }
+
+    fn update_with_state(&mut self, _: &AppState) {}
+
+    fn area(&self, terminal_area: Rect) -> Rect {
+        let [_, horizontal_area, _] = *Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Min(31),
+                Constraint::Percentage(25),
+            ])
+            .split(terminal_area)
+        else {
+            panic!("Failed to split horizontal area");
+        };
+
+        let [_, area, _] = *Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(5),
+                Constraint::Min(0),
+            ])
+            .split(horizontal_area)
+        else {
+            panic!("Failed to split vertical area");
+        };
+        area
+    }
+
+    fn inner_handle_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            // if the user presses Enter, we save the queue as a playlist with the given name (unless the name is empty)
+            KeyCode::Enter => {
+                let name = self.input_box.text();
+                if !name.is_empty() {
+                    self.action_tx
+                        .send(Action::Library(LibraryAction::SaveQueueAsPlaylist(
+                            name.to_string(),
+                        )))
+                        .unwrap();
+                }
+                self.action_tx
+                    .send(Action::Popup(PopupAction::Close))
+                    .unwrap();
+            }
+            // defer to the input box
+            _ => self.input_box.handle_key_event(key),
+        }
+    }
+
+    fn inner_handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) {
+        // the input box fills the area inside the popup's border
+        self.input_box
+            .handle_mouse_event(mouse, Block::bordered().inner(area));
+    }
+}
+
+impl ComponentRender<Rect> for QueueSaver {
+    fn render_border(&self, frame: &mut Frame, area: Rect) -> Rect {
+        self.render_popup_border(frame, area)
+    }
+
+    fn render_content(&self, frame: &mut Frame, area: Rect) {
+        self.input_box.render(
+            frame,
+            RenderProps {
+                area,
+                text_color: TEXT_HIGHLIGHT_ALT.into(),
+                border: Block::bordered()
+                    .title("Enter Name:")
+                    .border_style(Style::default().fg(BORDER_FOCUSED.into())),
+                show_cursor: true,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::component::ActiveComponent,
+        test_utils::setup_test_terminal,
+        ui::components::content_view::{views::ViewData, ActiveView},
+    };
+    use mecomp_core::{
+        rpc::SearchResult,
+        state::{library::LibraryFull, StateAudio},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::{fixture, rstest};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[fixture]
+    fn state() -> AppState {
+        AppState {
+            active_component: ActiveComponent::default(),
+            audio: StateAudio::default(),
+            search: SearchResult::default(),
+            library: LibraryFull::default(),
+            active_view: ActiveView::default(),
+            additional_view_data: ViewData::default(),
+            scan_progress: None,
+        }
+    }
+
+    #[rstest]
+    #[case::large((100, 100), Rect::new(25, 48, 50, 5))]
+    #[case::small((31, 5), Rect::new(0, 0, 31, 5))]
+    #[case::too_small((20, 3), Rect::new(0, 0, 20, 3))]
+    fn test_queue_saver_area(
+        #[case] terminal_size: (u16, u16),
+        #[case] expected_area: Rect,
+        state: AppState,
+    ) {
+        let (_, area) = setup_test_terminal(terminal_size.0, terminal_size.1);
+        let area = QueueSaver::new(&state, unbounded_channel().0).area(area);
+        assert_eq!(area, expected_area);
+    }
+
+    #[rstest]
+    fn test_queue_saver_save(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = QueueSaver::new(&state, action_tx);
+
+        for c in "Road Trip".chars() {
+            popup.inner_handle_key_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Library(LibraryAction::SaveQueueAsPlaylist("Road Trip".to_string()))
+        );
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Popup(PopupAction::Close)
+        );
+    }
+
+    #[rstest]
+    fn test_queue_saver_empty_name_cancels(state: AppState) {
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let mut popup = QueueSaver::new(&state, action_tx);
+
+        popup.inner_handle_key_event(KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(
+            action_rx.try_recv().unwrap(),
+            Action::Popup(PopupAction::Close)
+        );
+        assert!(action_rx.try_recv().is_err());
+    }
+}
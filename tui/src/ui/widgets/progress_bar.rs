@@ -0,0 +1,338 @@
+//! A progress bar that shows how far into the current song playback is,
+//! and can be clicked to seek to a position in the song.
+//!
+//! The song's bookmarks are marked on the bar.
+
+use std::time::Duration;
+
+use mecomp_core::state::StateRuntime;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{LineGauge, Widget},
+};
+
+use crate::ui::colors::{GAUGE_FILLED, GAUGE_UNFILLED, TEXT_HIGHLIGHT_ALT};
+
+/// How close to the end of the song a click on the progress bar can seek to,
+/// seeking to the very end of the song would skip it entirely.
+const MIN_DISTANCE_FROM_END: Duration = Duration::from_millis(100);
+
+/// How far past a bookmark playback has to be before jumping to the previous bookmark skips it,
+/// otherwise jumping to a bookmark and then jumping back would just return to the same bookmark.
+const PREVIOUS_BOOKMARK_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// The symbol used to mark bookmarks on the bar.
+const BOOKMARK_SYMBOL: &str = "┃";
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressBarWidget<'a> {
+    runtime: Option<StateRuntime>,
+    bookmarks: &'a [Duration],
+}
+
+impl<'a> ProgressBarWidget<'a> {
+    #[must_use]
+    pub const fn new(runtime: Option<StateRuntime>) -> Self {
+        Self {
+            runtime,
+            bookmarks: &[],
+        }
+    }
+
+    /// Set the bookmarks (positions in the song) to mark on the bar.
+    #[must_use]
+    pub const fn bookmarks(self, bookmarks: &'a [Duration]) -> Self {
+        Self { bookmarks, ..self }
+    }
+
+    /// The label shown before the bar, e.g. "1:05.0/3:20.5"
+    #[must_use]
+    pub fn label(&self) -> String {
+        self.runtime.map_or_else(
+            || String::from("0.0/0.0"),
+            |runtime| {
+                format!(
+                    "{}:{:04.1}/{}:{:04.1}",
+                    runtime.seek_position.as_secs() / 60,
+                    runtime.seek_position.as_secs_f32() % 60.0,
+                    runtime.duration.as_secs() / 60,
+                    runtime.duration.as_secs_f32() % 60.0
+                )
+            },
+        )
+    }
+
+    /// The part of `area` (the area the widget is rendered in) taken up by the bar itself, excluding the label.
+    #[must_use]
+    pub fn bar_area(&self, area: Rect) -> Rect {
+        let label_width = u16::try_from(self.label().len()).unwrap_or(u16::MAX);
+        Rect {
+            x: area.x.saturating_add(label_width),
+            width: area.width.saturating_sub(label_width),
+            ..area
+        }
+    }
+
+    /// Compute the position in the song that a click at `pos` on the bar (occupying `area`) should seek to.
+    ///
+    /// Clicks are clamped to `song_duration - 100ms`, so that clicking at the very end of the bar doesn't skip the song.
+    ///
+    /// Returns `None` if the click wasn't on the bar.
+    #[must_use]
+    pub fn handle_mouse_click(
+        pos: Position,
+        area: Rect,
+        song_duration: Duration,
+    ) -> Option<Duration> {
+        if !area.contains(pos) {
+            return None;
+        }
+
+        let ratio = f64::from(pos.x - area.x) / f64::from(area.width);
+        let seek_target = song_duration.mul_f64(ratio);
+
+        Some(seek_target.min(song_duration.saturating_sub(MIN_DISTANCE_FROM_END)))
+    }
+
+    /// The column of the bar (occupying `area`) that a bookmark at `position` is marked at.
+    ///
+    /// This is the column that, when clicked, seeks closest to the bookmark.
+    ///
+    /// Returns `None` if the bookmark is past the end of the song.
+    #[must_use]
+    pub fn bookmark_column(position: Duration, area: Rect, song_duration: Duration) -> Option<u16> {
+        if area.is_empty() || position > song_duration || song_duration.is_zero() {
+            return None;
+        }
+
+        let ratio = position.as_secs_f64() / song_duration.as_secs_f64();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let offset = (f64::from(area.width) * ratio).round() as u16;
+
+        Some(area.x + offset.min(area.width - 1))
+    }
+
+    /// The first bookmark after `position`, wrapping around to the first bookmark if there isn't one.
+    ///
+    /// `bookmarks` must be sorted.
+    #[must_use]
+    pub fn next_bookmark(bookmarks: &[Duration], position: Duration) -> Option<Duration> {
+        bookmarks
+            .iter()
+            .find(|bookmark| **bookmark > position)
+            .or_else(|| bookmarks.first())
+            .copied()
+    }
+
+    /// The last bookmark before `position`, wrapping around to the last bookmark if there isn't one.
+    ///
+    /// Bookmarks less than a second before `position` are skipped, so that repeatedly jumping backwards doesn't get stuck on the bookmark we just jumped to.
+    ///
+    /// `bookmarks` must be sorted.
+    #[must_use]
+    pub fn previous_bookmark(bookmarks: &[Duration], position: Duration) -> Option<Duration> {
+        let position = position.saturating_sub(PREVIOUS_BOOKMARK_THRESHOLD);
+        bookmarks
+            .iter()
+            .rev()
+            .find(|bookmark| **bookmark < position)
+            .or_else(|| bookmarks.last())
+            .copied()
+    }
+}
+
+impl Widget for ProgressBarWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        LineGauge::default()
+            .label(Line::from(self.label()))
+            .filled_style(Style::default().fg(GAUGE_FILLED.into()).bold())
+            .unfilled_style(Style::default().fg(GAUGE_UNFILLED.into()).bold())
+            .ratio(self.runtime.map_or(0.0, |runtime| {
+                runtime.seek_position.as_secs_f64() / runtime.duration.as_secs_f64()
+            }))
+            .render(area, buf);
+
+        // mark the bookmarks on top of the bar
+        let Some(runtime) = self.runtime else {
+            return;
+        };
+        let bar_area = self.bar_area(area);
+        for bookmark in self.bookmarks {
+            if let Some(x) = Self::bookmark_column(*bookmark, bar_area, runtime.duration) {
+                buf[(x, bar_area.y)]
+                    .set_symbol(BOOKMARK_SYMBOL)
+                    .set_style(Style::default().fg(TEXT_HIGHLIGHT_ALT.into()).bold());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mecomp_core::state::Percent;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::start(0, Some(Duration::from_secs(0)))]
+    #[case::quarter(25, Some(Duration::from_secs(25)))]
+    #[case::half(50, Some(Duration::from_secs(50)))]
+    // the last column is 99% of the way through, which is before the clamp
+    #[case::end(99, Some(Duration::from_secs(99)))]
+    #[case::past_the_end(100, None)]
+    fn test_handle_mouse_click(#[case] x: u16, #[case] expected: Option<Duration>) {
+        let area = Rect::new(0, 0, 100, 1);
+        let song_duration = Duration::from_secs(100);
+
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(x, 0), area, song_duration),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_handle_mouse_click_offset_area() {
+        let area = Rect::new(10, 5, 20, 1);
+        let song_duration = Duration::from_secs(60);
+
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(15, 5), area, song_duration),
+            Some(Duration::from_secs(15))
+        );
+        // clicks outside of the bar are ignored
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(5, 5), area, song_duration),
+            None
+        );
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(15, 6), area, song_duration),
+            None
+        );
+    }
+
+    #[test]
+    fn test_handle_mouse_click_clamps_to_end() {
+        // with a wide bar and a short song, the last column is within 100ms of the end
+        let area = Rect::new(0, 0, 100, 1);
+        let song_duration = Duration::from_secs(1);
+
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(99, 0), area, song_duration),
+            Some(Duration::from_millis(900))
+        );
+
+        // an empty song can only be seeked to the start
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(50, 0), area, Duration::ZERO),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_bar_area() {
+        let widget = ProgressBarWidget::new(Some(StateRuntime {
+            seek_position: Duration::from_secs(65),
+            seek_percent: Percent::new(32.5),
+            duration: Duration::from_secs(200),
+        }));
+        assert_eq!(widget.label(), "1:05.0/3:20.0");
+
+        let area = Rect::new(10, 3, 50, 1);
+        assert_eq!(widget.bar_area(area), Rect::new(23, 3, 37, 1));
+    }
+
+    #[rstest]
+    #[case::start(Duration::from_secs(0), Some(10))]
+    #[case::quarter(Duration::from_secs(15), Some(15))]
+    #[case::end(Duration::from_secs(60), Some(29))]
+    #[case::past_the_end(Duration::from_secs(61), None)]
+    fn test_bookmark_column(#[case] position: Duration, #[case] expected: Option<u16>) {
+        let area = Rect::new(10, 5, 20, 1);
+        assert_eq!(
+            ProgressBarWidget::bookmark_column(position, area, Duration::from_secs(60)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_bookmark_column_round_trips_through_click() {
+        // clicking a bookmark's marker seeks to (roughly) the bookmark
+        let area = Rect::new(10, 5, 20, 1);
+        let song_duration = Duration::from_secs(60);
+        let bookmark = Duration::from_secs(45);
+
+        let x = ProgressBarWidget::bookmark_column(bookmark, area, song_duration).unwrap();
+        assert_eq!(
+            ProgressBarWidget::handle_mouse_click(Position::new(x, 5), area, song_duration),
+            Some(bookmark)
+        );
+    }
+
+    #[rstest]
+    #[case::before_all(5, Some(10))]
+    #[case::on_a_bookmark(10, Some(20))]
+    #[case::between(15, Some(20))]
+    #[case::wraps_around(35, Some(10))]
+    fn test_next_bookmark(#[case] position: u64, #[case] expected: Option<u64>) {
+        let bookmarks = [10, 20, 30].map(Duration::from_secs);
+        assert_eq!(
+            ProgressBarWidget::next_bookmark(&bookmarks, Duration::from_secs(position)),
+            expected.map(Duration::from_secs)
+        );
+    }
+
+    #[rstest]
+    #[case::after_all(35, Some(30))]
+    #[case::just_after_a_bookmark(20, Some(10))]
+    #[case::well_after_a_bookmark(25, Some(20))]
+    #[case::wraps_around(5, Some(30))]
+    fn test_previous_bookmark(#[case] position: u64, #[case] expected: Option<u64>) {
+        let bookmarks = [10, 20, 30].map(Duration::from_secs);
+        assert_eq!(
+            ProgressBarWidget::previous_bookmark(&bookmarks, Duration::from_secs(position)),
+            expected.map(Duration::from_secs)
+        );
+    }
+
+    #[test]
+    fn test_no_bookmarks() {
+        assert_eq!(
+            ProgressBarWidget::next_bookmark(&[], Duration::from_secs(5)),
+            None
+        );
+        assert_eq!(
+            ProgressBarWidget::previous_bookmark(&[], Duration::from_secs(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render_bookmarks() {
+        let bookmarks = [Duration::from_secs(100)];
+        let widget = ProgressBarWidget::new(Some(StateRuntime {
+            seek_position: Duration::from_secs(0),
+            seek_percent: Percent::new(0.0),
+            duration: Duration::from_secs(200),
+        }))
+        .bookmarks(&bookmarks);
+
+        let area = Rect::new(0, 0, 33, 1);
+        let mut buffer = Buffer::empty(area);
+        widget.render(area, &mut buffer);
+
+        // the label is 13 columns wide, so the bar spans columns 13..33, and the bookmark is halfway along it
+        assert_eq!(buffer[(23, 0)].symbol(), BOOKMARK_SYMBOL);
+        assert_eq!(
+            buffer
+                .content()
+                .iter()
+                .filter(|cell| cell.symbol() == BOOKMARK_SYMBOL)
+                .count(),
+            1
+        );
+    }
+}
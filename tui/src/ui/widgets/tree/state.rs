@@ -35,6 +35,11 @@ where
         self.offset
     }
 
+    /// Set the scroll offset, the selected item (if any) will still be scrolled into view on the next render.
+    pub const fn set_offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
     #[must_use]
     pub const fn opened(&self) -> &HashSet<Vec<Identifier>> {
         &self.opened